@@ -419,46 +419,57 @@ impl<'a> Context<'a> {
     }
 
     /// Get the layout of the instantiated struct in linear memory. The result will be cached
-    /// for future calls.
+    /// for future calls. Instantiations which differ only in phantom type arguments share
+    /// the same layout.
     pub fn get_struct_layout(&self, st: &QualifiedInstId<StructId>) -> StructLayout {
+        let key = st.erase_phantom_args(self.env);
+        let struct_env = self.env.get_struct(st.to_qualified_id());
         let mut layouts_ref = self.struct_layout.borrow_mut();
-        if layouts_ref.get(st).is_none() {
-            // Compute the fields such that the larger appear first, and pointer fields
-            // precede non-pointer fields.
-            let s_or_v = |ty: &Type| ty.is_vector() || self.type_is_struct(ty);
-            let struct_env = self.env.get_struct(st.to_qualified_id());
-            let ordered_fields = struct_env
-                .get_fields()
-                .map(|field| {
-                    let field_type = field.get_type().instantiate(&st.inst);
-                    let field_size = self.type_size(&field_type);
-                    (field.get_offset(), field_size, field_type)
-                })
-                .sorted_by(|(_, s1, ty1), (_, s2, ty2)| {
-                    if s1 > s2 {
-                        std::cmp::Ordering::Less
-                    } else if s2 > s1 {
-                        std::cmp::Ordering::Greater
-                    } else if s_or_v(ty1) && !s_or_v(ty2) {
-                        std::cmp::Ordering::Less
-                    } else if s_or_v(ty2) && !s_or_v(ty1) {
-                        std::cmp::Ordering::Greater
-                    } else {
-                        std::cmp::Ordering::Equal
-                    }
-                });
-            let mut result = StructLayout::default();
-            for (logical_offs, field_size, ty) in ordered_fields {
-                result.field_order.push(logical_offs);
-                if s_or_v(&ty) {
-                    result.pointer_count += 1
+        if let Some(layout) = layouts_ref.get(&key) {
+            // Field types may still mention phantom type arguments, so instantiate them
+            // for the requested instantiation.
+            let mut layout = layout.clone();
+            for field in struct_env.get_fields() {
+                if let Some((_, ty)) = layout.offsets.get_mut(&field.get_offset()) {
+                    *ty = field.get_type().instantiate(&st.inst);
                 }
-                result.offsets.insert(logical_offs, (result.size, ty));
-                result.size += field_size
             }
-            layouts_ref.insert(st.clone(), result);
+            return layout;
         }
-        layouts_ref.get(st).unwrap().clone()
+        // Compute the fields such that the larger appear first, and pointer fields
+        // precede non-pointer fields.
+        let s_or_v = |ty: &Type| ty.is_vector() || self.type_is_struct(ty);
+        let ordered_fields = struct_env
+            .get_fields()
+            .map(|field| {
+                let field_type = field.get_type().instantiate(&st.inst);
+                let field_size = self.type_size(&field_type);
+                (field.get_offset(), field_size, field_type)
+            })
+            .sorted_by(|(_, s1, ty1), (_, s2, ty2)| {
+                if s1 > s2 {
+                    std::cmp::Ordering::Less
+                } else if s2 > s1 {
+                    std::cmp::Ordering::Greater
+                } else if s_or_v(ty1) && !s_or_v(ty2) {
+                    std::cmp::Ordering::Less
+                } else if s_or_v(ty2) && !s_or_v(ty1) {
+                    std::cmp::Ordering::Greater
+                } else {
+                    std::cmp::Ordering::Equal
+                }
+            });
+        let mut result = StructLayout::default();
+        for (logical_offs, field_size, ty) in ordered_fields {
+            result.field_order.push(logical_offs);
+            if s_or_v(&ty) {
+                result.pointer_count += 1
+            }
+            result.offsets.insert(logical_offs, (result.size, ty));
+            result.size += field_size
+        }
+        layouts_ref.insert(key, result.clone());
+        result
     }
 
     /// Calculate the size, in bytes, for the memory layout of this type.
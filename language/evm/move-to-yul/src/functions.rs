@@ -592,6 +592,18 @@ impl<'a> FunctionGenerator<'a> {
                             builtin(YulFunction::Neq, dest, srcs)
                         }
                     }
+                    VecPack(..) | VecUnpack(..) | VecLen(_) | VecBorrow(_) | VecBorrowMut(_)
+                    | VecPushBack(_) | VecPopBack(_) | VecSwap(_) => ctx.env.error(
+                        &target.get_bytecode_loc(bc.get_attr_id()),
+                        "vector bytecode operations are not yet implemented",
+                    ),
+                    PackVariant(..)
+                    | UnpackVariant(..)
+                    | TestVariant(..)
+                    | BorrowVariantField(..) => ctx.env.error(
+                        &target.get_bytecode_loc(bc.get_attr_id()),
+                        "enum variant operations are not yet implemented",
+                    ),
                     // Specification or other operations which can be ignored here
                     GetField(_, _, _, _)
                     | GetGlobal(_, _, _)
@@ -9,10 +9,12 @@ pub mod comments;
 pub mod keywords;
 pub(crate) mod merge_spec_modules;
 
+pub use syntax::parse_file_string;
+
 use crate::{
     attr_derivation,
     diagnostics::{codes::Severity, Diagnostics, FilesSourceText},
-    parser::{self, ast::PackageDefinition, syntax::parse_file_string},
+    parser::{self, ast::PackageDefinition},
     shared::{CompilationEnv, IndexedPackagePath, NamedAddressMaps},
 };
 use anyhow::anyhow;
@@ -27,6 +27,7 @@ use crate::{
 use internment::LocalIntern;
 use itertools::Itertools;
 use once_cell::sync::Lazy;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::{
     borrow::Borrow, cell::RefCell, collections::HashSet, fmt::Debug, hash::Hash, ops::Deref,
 };
@@ -34,7 +35,7 @@ use std::{
 // =================================================================================================
 /// # Declarations
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct SpecVarDecl {
     pub loc: Loc,
     pub name: Symbol,
@@ -43,7 +44,7 @@ pub struct SpecVarDecl {
     pub init: Option<Exp>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SpecFunDecl {
     pub loc: Loc,
     pub name: Symbol,
@@ -57,19 +58,24 @@ pub struct SpecFunDecl {
     pub is_native: bool,
     pub body: Option<Exp>,
     pub callees: BTreeSet<QualifiedId<SpecFunId>>,
+    #[serde(skip)]
     pub is_recursive: RefCell<Option<bool>>,
+    /// For a recursive spec fun, the index of a parameter which decreases at each recursive
+    /// call, as established by the termination check of the model builder.
+    #[serde(default)]
+    pub decreasing_param: RefCell<Option<usize>>,
 }
 
 // =================================================================================================
 /// # Attributes
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum AttributeValue {
     Value(NodeId, Value),
     Name(NodeId, Option<ModuleName>, Symbol),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Attribute {
     Apply(NodeId, Symbol, Vec<Attribute>),
     Assign(NodeId, Symbol, AttributeValue),
@@ -78,7 +84,7 @@ pub enum Attribute {
 // =================================================================================================
 /// # Conditions
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum ConditionKind {
     LetPost(Symbol),
     LetPre(Symbol),
@@ -213,7 +219,7 @@ impl std::fmt::Display for ConditionKind {
     }
 }
 
-#[derive(Debug, PartialEq, Clone, Copy, Eq, Hash)]
+#[derive(Debug, PartialEq, Clone, Copy, Eq, Hash, Serialize, Deserialize)]
 pub enum QuantKind {
     Forall,
     Exists,
@@ -240,7 +246,7 @@ impl std::fmt::Display for QuantKind {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Condition {
     pub loc: Loc,
     pub kind: ConditionKind,
@@ -263,7 +269,7 @@ impl Condition {
 pub type PropertyBag = BTreeMap<Symbol, PropertyValue>;
 
 /// The value of a property.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum PropertyValue {
     Value(Value),
     Symbol(Symbol),
@@ -271,7 +277,7 @@ pub enum PropertyValue {
 }
 
 /// Specification and properties associated with a language item.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Spec {
     // The location of this specification, if available.
     pub loc: Option<Loc>,
@@ -319,7 +325,7 @@ impl Spec {
 /// generation. In the object model, the original locations and documentation of spec blocks
 /// is reduced to conditions on a `Spec`, with expansion of schemas. This data structure
 /// allows us to discover the original spec blocks and their content.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SpecBlockInfo {
     /// The location of the entire spec block.
     pub loc: Loc,
@@ -330,7 +336,7 @@ pub struct SpecBlockInfo {
 }
 
 /// Describes the target of a spec block.
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum SpecBlockTarget {
     Module,
     Struct(ModuleId, StructId),
@@ -340,7 +346,7 @@ pub enum SpecBlockTarget {
 }
 
 /// Describes a global invariant.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GlobalInvariant {
     pub id: GlobalId,
     pub loc: Loc,
@@ -367,7 +373,7 @@ pub type TempIndex = usize;
 /// - Each expression has a unique node id assigned. This id allows to build attribute tables
 ///   for additional information, like expression type and source location. The id is globally
 ///   unique.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ExpData {
     /// Represents an invalid expression. This is used as a stub for algorithms which
     /// generate expressions but can fail with multiple errors, like a translator from
@@ -456,6 +462,18 @@ impl From<Exp> for ExpData {
     }
 }
 
+impl Serialize for Exp {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.as_ref().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Exp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        ExpData::deserialize(deserializer).map(ExpData::into_exp)
+    }
+}
+
 impl ExpData {
     /// Version of `into` which does not require type annotations.
     pub fn into_exp(self) -> Exp {
@@ -841,7 +859,7 @@ impl<'a> ExpRewriterFunctions for ExpRewriter<'a> {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Operation {
     Function(ModuleId, SpecFunId, Option<Vec<MemoryLabel>>),
     Pack(ModuleId, StructId),
@@ -920,14 +938,14 @@ pub enum Operation {
 /// A label used for referring to a specific memory in Global and Exists expressions.
 pub type MemoryLabel = GlobalId;
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct LocalVarDecl {
     pub id: NodeId,
     pub name: Symbol,
     pub binding: Option<Exp>,
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Serialize, Deserialize)]
 pub enum TraceKind {
     /// A user level TRACE(..) in the source.
     User,
@@ -950,7 +968,7 @@ impl fmt::Display for TraceKind {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize, Deserialize)]
 pub enum Value {
     Address(BigUint),
     Number(BigInt),
@@ -1042,7 +1060,7 @@ impl ExpData {
 /// # Names
 
 /// Represents a module name, consisting of address and name.
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Serialize, Deserialize)]
 pub struct ModuleName(BigUint, Symbol);
 
 impl ModuleName {
@@ -1122,7 +1140,7 @@ impl<'a> fmt::Display for ModuleNameDisplay<'a> {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Serialize, Deserialize)]
 pub struct QualifiedSymbol {
     pub module_name: ModuleName,
     pub symbol: Symbol,
@@ -1207,6 +1225,11 @@ impl<'a> fmt::Display for ExpDisplay<'a> {
         use ExpData::*;
         match self.exp {
             Invalid(_) => write!(f, "*invalid*"),
+            Value(_, crate::ast::Value::Address(addr))
+                if self.env.get_address_alias(addr).is_some() =>
+            {
+                write!(f, "{}", self.env.display_address(addr))
+            }
             Value(_, v) => write!(f, "{}", v),
             LocalVar(_, name) => write!(f, "{}", name.display(self.env.symbol_pool())),
             Temporary(_, idx) => write!(f, "$t{}", idx),
@@ -0,0 +1,248 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Audit heuristics.
+//!
+//! The heuristics point auditors of large code bases at functions which deserve a closer look
+//! first. They are deliberately simple and over-approximate: a finding is not necessarily a
+//! problem, and the absence of findings does not mean the code is correct. The following
+//! heuristics are computed for the functions of the target modules:
+//!
+//! - A public or script function which writes global storage but takes no signer may allow
+//!   anyone to modify state (high priority).
+//! - A function which calls functions of other modules but has no `aborts_if` conditions does
+//!   not document under which conditions the calls abort (medium priority).
+//! - A function with many arithmetic operations but no `aborts_if` conditions does not document
+//!   under which conditions it overflows (low priority).
+//!
+//! Together with the findings, the report shows the assertion density of the target modules,
+//! that is the number of spec conditions relative to the number of instructions.
+
+use crate::{
+    ast::ConditionKind,
+    model::{FunId, FunctionEffectClass, FunctionEnv, GlobalEnv, Loc, Parameter, QualifiedId},
+};
+use move_binary_format::file_format::Bytecode;
+use std::fmt;
+
+/// Functions with at least this many arithmetic operations are reported if they have no
+/// `aborts_if` conditions.
+const MIN_ARITHMETIC_OPERATIONS: usize = 4;
+
+/// The priority of a finding, from high to low.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    High,
+    Medium,
+    Low,
+}
+
+/// The heuristics, in the order of their priority.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Heuristic {
+    /// A public or script function writes global storage without taking a signer.
+    UnguardedStateWrite,
+    /// A function calls other modules without `aborts_if` conditions.
+    UnspecifiedExternalAborts,
+    /// A function performs many arithmetic operations without `aborts_if` conditions.
+    UnspecifiedArithmeticAborts,
+}
+
+impl Heuristic {
+    /// Returns the name of the heuristic, in snake case.
+    pub fn name(self) -> &'static str {
+        match self {
+            Heuristic::UnguardedStateWrite => "unguarded_state_write",
+            Heuristic::UnspecifiedExternalAborts => "unspecified_external_aborts",
+            Heuristic::UnspecifiedArithmeticAborts => "unspecified_arithmetic_aborts",
+        }
+    }
+
+    /// Returns the priority of the findings of the heuristic.
+    pub fn priority(self) -> Priority {
+        match self {
+            Heuristic::UnguardedStateWrite => Priority::High,
+            Heuristic::UnspecifiedExternalAborts => Priority::Medium,
+            Heuristic::UnspecifiedArithmeticAborts => Priority::Low,
+        }
+    }
+}
+
+/// A function which a heuristic flagged for review.
+#[derive(Debug, Clone)]
+pub struct AuditFinding {
+    pub heuristic: Heuristic,
+    pub priority: Priority,
+    pub fun_id: QualifiedId<FunId>,
+    /// The name of the function, e.g. `0x1::M::f`.
+    pub function: String,
+    pub loc: Loc,
+    /// A description of what the heuristic found.
+    pub message: String,
+}
+
+/// The number of spec conditions and instructions of the functions of the target modules.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AssertionDensity {
+    pub conditions: usize,
+    pub instructions: usize,
+}
+
+impl AssertionDensity {
+    /// Returns the number of conditions per 100 instructions.
+    pub fn per_hundred_instructions(&self) -> f64 {
+        if self.instructions == 0 {
+            0.0
+        } else {
+            self.conditions as f64 * 100.0 / self.instructions as f64
+        }
+    }
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Priority::High => write!(f, "high"),
+            Priority::Medium => write!(f, "medium"),
+            Priority::Low => write!(f, "low"),
+        }
+    }
+}
+
+/// Returns the findings of the heuristics for the functions of the target modules, ordered by
+/// priority and function name.
+pub fn audit_findings(env: &GlobalEnv) -> Vec<AuditFinding> {
+    let mut findings = vec![];
+    for fun_env in target_functions(env) {
+        let function = format!(
+            "{}::{}",
+            fun_env.module_env.get_full_name_str(),
+            fun_env.get_simple_name_string()
+        );
+        let mut add = |heuristic: Heuristic, message| {
+            findings.push(AuditFinding {
+                heuristic,
+                priority: heuristic.priority(),
+                fun_id: fun_env.get_qualified_id(),
+                function: function.clone(),
+                loc: fun_env.get_loc(),
+                message,
+            })
+        };
+        let has_aborts_if = fun_env
+            .get_spec()
+            .conditions
+            .iter()
+            .any(|cond| cond.kind == ConditionKind::AbortsIf);
+
+        if fun_env.has_unknown_callers() && !takes_signer(&fun_env) {
+            if let FunctionEffectClass::StateMutating { writes, .. } = fun_env.get_effect_class() {
+                let resources = writes
+                    .into_iter()
+                    .map(|id| env.get_struct(id).get_full_name_with_address())
+                    .collect::<Vec<_>>();
+                add(
+                    Heuristic::UnguardedStateWrite,
+                    format!(
+                        "callable by anyone, writes {} without taking a signer",
+                        resources.join(", ")
+                    ),
+                );
+            }
+        }
+        if !has_aborts_if {
+            let module_id = fun_env.module_env.get_id();
+            let external_calls = fun_env
+                .get_called_functions()
+                .into_iter()
+                .filter(|id| id.module_id != module_id)
+                .count();
+            if external_calls > 0 {
+                add(
+                    Heuristic::UnspecifiedExternalAborts,
+                    format!(
+                        "calls {} function(s) of other modules without `aborts_if` conditions",
+                        external_calls
+                    ),
+                );
+            }
+            let arithmetic_operations = count_arithmetic_operations(&fun_env);
+            if arithmetic_operations >= MIN_ARITHMETIC_OPERATIONS {
+                add(
+                    Heuristic::UnspecifiedArithmeticAborts,
+                    format!(
+                        "performs {} arithmetic operation(s) without `aborts_if` conditions",
+                        arithmetic_operations
+                    ),
+                );
+            }
+        }
+    }
+    findings.sort_by(|f1, f2| (f1.priority, &f1.function).cmp(&(f2.priority, &f2.function)));
+    findings
+}
+
+/// Returns the assertion density of the functions of the target modules.
+pub fn assertion_density(env: &GlobalEnv) -> AssertionDensity {
+    let mut density = AssertionDensity::default();
+    for fun_env in target_functions(env) {
+        density.conditions += fun_env.get_spec().conditions.len();
+        density.instructions += fun_env.get_bytecode().len();
+    }
+    density
+}
+
+/// Returns a report of the assertion density and the findings of the heuristics for the target
+/// modules.
+pub fn audit_report(env: &GlobalEnv) -> String {
+    let density = assertion_density(env);
+    let findings = audit_findings(env);
+    let mut res = format!(
+        "assertion density: {} condition(s) over {} instruction(s), {:.1} per 100 instructions\n\
+         {} finding(s)\n",
+        density.conditions,
+        density.instructions,
+        density.per_hundred_instructions(),
+        findings.len()
+    );
+    for finding in findings {
+        res.push_str(&format!(
+            "\n[{}] {}: {}\n  {}\n",
+            finding.priority,
+            finding.function,
+            finding.message,
+            finding.loc.display(env)
+        ));
+    }
+    res
+}
+
+/// Returns the non-native functions of the target modules.
+fn target_functions(env: &GlobalEnv) -> impl Iterator<Item = FunctionEnv<'_>> {
+    env.get_modules()
+        .filter(|m| m.is_target())
+        .flat_map(|m| m.into_functions())
+        .filter(|f| !f.is_native())
+}
+
+/// Returns true if the function takes a signer, by value or by reference.
+fn takes_signer(fun_env: &FunctionEnv) -> bool {
+    fun_env
+        .get_parameters()
+        .iter()
+        .any(|Parameter(_, ty)| ty.skip_reference().is_signer())
+}
+
+/// Returns the number of arithmetic operations of the function which may abort.
+fn count_arithmetic_operations(fun_env: &FunctionEnv) -> usize {
+    fun_env
+        .get_bytecode()
+        .iter()
+        .filter(|bc| {
+            matches!(
+                bc,
+                Bytecode::Add | Bytecode::Sub | Bytecode::Mul | Bytecode::Div | Bytecode::Mod
+            )
+        })
+        .count()
+}
@@ -0,0 +1,441 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Generation of Rust code for decoding and encoding Move values in BCS.
+//!
+//! The generated code defines a Rust struct for each struct type, including every struct it
+//! transitively contains, and implements the `Bcs` trait for it. Generic structs are generated
+//! per instantiation, so all types must be fully instantiated at generation time. The code is
+//! self-contained: tools like indexers can decode events and resources from their bytes
+//! without depending on the Move VM or the model.
+
+use crate::{
+    code_writer::CodeWriter,
+    emitln,
+    model::{GlobalEnv, Loc, QualifiedInstId, StructId},
+    ty::{PrimitiveType, Type},
+};
+use anyhow::{anyhow, bail};
+use itertools::Itertools;
+use move_core_types::{account_address::AccountAddress, parser::parse_type_tag};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Identifiers which are keywords in Rust and are escaped when used as field names.
+const RUST_KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in", "let", "loop",
+    "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return", "static",
+    "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use", "virtual",
+    "where", "while", "yield",
+];
+
+/// A generator of Rust BCS codecs for a set of types.
+pub struct RustBcsGenerator<'env> {
+    env: &'env GlobalEnv,
+    /// The struct instantiations to generate, in dependency order.
+    structs: Vec<QualifiedInstId<StructId>>,
+    visited: BTreeSet<QualifiedInstId<StructId>>,
+}
+
+impl<'env> RustBcsGenerator<'env> {
+    pub fn new(env: &'env GlobalEnv) -> Self {
+        Self {
+            env,
+            structs: vec![],
+            visited: BTreeSet::new(),
+        }
+    }
+
+    /// Adds a type, given as a string like `0x1::Event::EventHandle<u64>`.
+    pub fn add_type_str(&mut self, type_str: &str) -> anyhow::Result<()> {
+        let tag =
+            parse_type_tag(type_str).map_err(|e| anyhow!("invalid type `{}`: {}", type_str, e))?;
+        let ty = self
+            .env
+            .resolve_type_tag(&tag)
+            .ok_or_else(|| anyhow!("type `{}` is not defined in the model", type_str))?;
+        self.add_type(&ty)
+    }
+
+    /// Adds a type, together with all struct types it contains. The type must not contain
+    /// type parameters.
+    pub fn add_type(&mut self, ty: &Type) -> anyhow::Result<()> {
+        match ty {
+            Type::Primitive(PrimitiveType::Bool)
+            | Type::Primitive(PrimitiveType::U8)
+            | Type::Primitive(PrimitiveType::U64)
+            | Type::Primitive(PrimitiveType::U128)
+            | Type::Primitive(PrimitiveType::Address)
+            | Type::Primitive(PrimitiveType::Signer) => Ok(()),
+            Type::Vector(elem_ty) => self.add_type(elem_ty),
+            Type::Struct(mid, sid, inst) => {
+                let id = mid.qualified_inst(*sid, inst.clone());
+                if self.visited.contains(&id) {
+                    return Ok(());
+                }
+                let struct_env = self.env.get_struct(id.to_qualified_id());
+                if struct_env.is_native() {
+                    bail!(
+                        "native struct `{}` has no BCS layout",
+                        struct_env.get_full_name_str()
+                    )
+                }
+                for field_env in struct_env.get_fields() {
+                    self.add_type(&field_env.get_type().instantiate(inst))?;
+                }
+                self.visited.insert(id.clone());
+                self.structs.push(id);
+                Ok(())
+            }
+            _ => bail!(
+                "type `{}` is not fully instantiated or has no BCS layout",
+                ty.display(&self.env.get_type_display_ctx())
+            ),
+        }
+    }
+
+    /// Adds all non-generic structs of the target modules.
+    pub fn add_target_structs(&mut self) -> anyhow::Result<()> {
+        for module_env in self.env.get_modules().filter(|m| m.is_target()) {
+            for struct_env in module_env.get_structs() {
+                if struct_env.get_type_parameters().is_empty()
+                    && !struct_env.is_native()
+                    && !struct_env.is_ghost_memory()
+                {
+                    self.add_type(&Type::Struct(
+                        module_env.get_id(),
+                        struct_env.get_id(),
+                        vec![],
+                    ))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Generates the Rust code for the types added so far.
+    pub fn generate(&self) -> String {
+        let writer = CodeWriter::new(Loc::default());
+        let names = self.rust_names();
+        emitln!(
+            writer,
+            "// Generated BCS codecs of Move types. Do not edit.\n"
+        );
+        self.generate_prelude(&writer);
+        for id in &self.structs {
+            self.generate_struct(&writer, &names, id);
+        }
+        writer.extract_result()
+    }
+
+    fn generate_prelude(&self, writer: &CodeWriter) {
+        emitln!(
+            writer,
+            r#"/// An error decoding BCS bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BcsError(pub String);
+
+impl std::fmt::Display for BcsError {{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {{
+        write!(f, "BCS error: {{}}", self.0)
+    }}
+}}
+
+impl std::error::Error for BcsError {{}}
+
+/// A value which can be encoded and decoded in BCS.
+pub trait Bcs: Sized {{
+    /// Appends the encoding of the value.
+    fn serialize(&self, out: &mut Vec<u8>);
+
+    /// Decodes a value from the front of the input, advancing it.
+    fn deserialize(input: &mut &[u8]) -> Result<Self, BcsError>;
+
+    fn to_bcs(&self) -> Vec<u8> {{
+        let mut out = vec![];
+        self.serialize(&mut out);
+        out
+    }}
+
+    /// Decodes a value from the bytes, which must not contain anything else.
+    fn from_bcs(bytes: &[u8]) -> Result<Self, BcsError> {{
+        let mut input = bytes;
+        let value = Self::deserialize(&mut input)?;
+        if !input.is_empty() {{
+            return Err(BcsError(format!("{{}} trailing bytes", input.len())));
+        }}
+        Ok(value)
+    }}
+}}
+
+/// An account address, which is also the encoding of a signer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Address(pub [u8; ADDRESS_LENGTH]);
+
+pub const ADDRESS_LENGTH: usize = {};
+
+fn read_bytes<'a>(input: &mut &'a [u8], len: usize) -> Result<&'a [u8], BcsError> {{
+    if input.len() < len {{
+        return Err(BcsError("unexpected end of input".to_string()));
+    }}
+    let (bytes, rest) = input.split_at(len);
+    *input = rest;
+    Ok(bytes)
+}}
+
+fn write_length(out: &mut Vec<u8>, mut len: usize) {{
+    while len >= 0x80 {{
+        out.push((len & 0x7f) as u8 | 0x80);
+        len >>= 7;
+    }}
+    out.push(len as u8);
+}}
+
+fn read_length(input: &mut &[u8]) -> Result<usize, BcsError> {{
+    let mut len: u64 = 0;
+    for shift in (0..32).step_by(7) {{
+        let byte = read_bytes(input, 1)?[0];
+        len |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {{
+            if shift > 0 && byte == 0 {{
+                return Err(BcsError("non-canonical length".to_string()));
+            }}
+            if len > u32::MAX as u64 {{
+                return Err(BcsError("length out of range".to_string()));
+            }}
+            return Ok(len as usize);
+        }}
+    }}
+    Err(BcsError("length out of range".to_string()))
+}}
+
+impl Bcs for bool {{
+    fn serialize(&self, out: &mut Vec<u8>) {{
+        out.push(*self as u8);
+    }}
+
+    fn deserialize(input: &mut &[u8]) -> Result<Self, BcsError> {{
+        match read_bytes(input, 1)?[0] {{
+            0 => Ok(false),
+            1 => Ok(true),
+            b => Err(BcsError(format!("invalid bool {{}}", b))),
+        }}
+    }}
+}}
+
+macro_rules! impl_bcs_for_int {{
+    ($ty:ty) => {{
+        impl Bcs for $ty {{
+            fn serialize(&self, out: &mut Vec<u8>) {{
+                out.extend_from_slice(&self.to_le_bytes());
+            }}
+
+            fn deserialize(input: &mut &[u8]) -> Result<Self, BcsError> {{
+                let mut bytes = [0u8; std::mem::size_of::<$ty>()];
+                bytes.copy_from_slice(read_bytes(input, std::mem::size_of::<$ty>())?);
+                Ok(<$ty>::from_le_bytes(bytes))
+            }}
+        }}
+    }};
+}}
+
+impl_bcs_for_int!(u8);
+impl_bcs_for_int!(u64);
+impl_bcs_for_int!(u128);
+
+impl Bcs for Address {{
+    fn serialize(&self, out: &mut Vec<u8>) {{
+        out.extend_from_slice(&self.0);
+    }}
+
+    fn deserialize(input: &mut &[u8]) -> Result<Self, BcsError> {{
+        let mut bytes = [0u8; ADDRESS_LENGTH];
+        bytes.copy_from_slice(read_bytes(input, ADDRESS_LENGTH)?);
+        Ok(Address(bytes))
+    }}
+}}
+
+impl<T: Bcs> Bcs for Vec<T> {{
+    fn serialize(&self, out: &mut Vec<u8>) {{
+        write_length(out, self.len());
+        for elem in self {{
+            elem.serialize(out);
+        }}
+    }}
+
+    fn deserialize(input: &mut &[u8]) -> Result<Self, BcsError> {{
+        let len = read_length(input)?;
+        (0..len).map(|_| T::deserialize(input)).collect()
+    }}
+}}"#,
+            AccountAddress::LENGTH
+        );
+    }
+
+    fn generate_struct(
+        &self,
+        writer: &CodeWriter,
+        names: &BTreeMap<QualifiedInstId<StructId>, String>,
+        id: &QualifiedInstId<StructId>,
+    ) {
+        let struct_env = self.env.get_struct(id.to_qualified_id());
+        let name = &names[id];
+        let fields = struct_env
+            .get_fields()
+            .map(|field_env| {
+                (
+                    rust_field_name(
+                        field_env
+                            .get_name()
+                            .display(self.env.symbol_pool())
+                            .to_string(),
+                    ),
+                    self.rust_type(names, &field_env.get_type().instantiate(&id.inst)),
+                )
+            })
+            .collect_vec();
+        emitln!(writer);
+        emitln!(
+            writer,
+            "/// The Move struct `{}`.",
+            Type::Struct(id.module_id, id.id, id.inst.clone())
+                .display(&self.env.get_type_display_ctx())
+        );
+        emitln!(writer, "#[derive(Debug, Clone, PartialEq, Eq, Hash)]");
+        emitln!(writer, "pub struct {} {{", name);
+        writer.indent();
+        for (field, ty) in &fields {
+            emitln!(writer, "pub {}: {},", field, ty);
+        }
+        writer.unindent();
+        emitln!(writer, "}");
+        emitln!(writer);
+        emitln!(writer, "impl Bcs for {} {{", name);
+        writer.indent();
+        emitln!(writer, "fn serialize(&self, out: &mut Vec<u8>) {");
+        writer.indent();
+        for (field, _) in &fields {
+            emitln!(writer, "self.{}.serialize(out);", field);
+        }
+        writer.unindent();
+        emitln!(writer, "}");
+        emitln!(writer);
+        emitln!(
+            writer,
+            "fn deserialize(input: &mut &[u8]) -> Result<Self, BcsError> {"
+        );
+        writer.indent();
+        emitln!(writer, "Ok(Self {");
+        writer.indent();
+        for (field, ty) in &fields {
+            emitln!(writer, "{}: <{}>::deserialize(input)?,", field, ty);
+        }
+        writer.unindent();
+        emitln!(writer, "})");
+        writer.unindent();
+        emitln!(writer, "}");
+        writer.unindent();
+        emitln!(writer, "}");
+    }
+
+    /// Returns the Rust type representing a Move type.
+    fn rust_type(&self, names: &BTreeMap<QualifiedInstId<StructId>, String>, ty: &Type) -> String {
+        match ty {
+            Type::Primitive(PrimitiveType::Bool) => "bool".to_string(),
+            Type::Primitive(PrimitiveType::U8) => "u8".to_string(),
+            Type::Primitive(PrimitiveType::U64) => "u64".to_string(),
+            Type::Primitive(PrimitiveType::U128) => "u128".to_string(),
+            Type::Vector(elem_ty) => format!("Vec<{}>", self.rust_type(names, elem_ty)),
+            Type::Struct(mid, sid, inst) => names[&mid.qualified_inst(*sid, inst.clone())].clone(),
+            // Address and signer; other types are rejected when added.
+            _ => "Address".to_string(),
+        }
+    }
+
+    /// Computes the names of the Rust structs. A struct is named after the Move struct, prefixed
+    /// by the module name if another module declares a struct of the same name. Instantiations
+    /// of generic structs are named after their type arguments, e.g. `EventHandleOfU64`.
+    fn rust_names(&self) -> BTreeMap<QualifiedInstId<StructId>, String> {
+        let pool = self.env.symbol_pool();
+        let declared = self
+            .structs
+            .iter()
+            .map(|id| id.to_qualified_id())
+            .collect::<BTreeSet<_>>();
+        let mut names = BTreeMap::new();
+        let mut used = BTreeSet::new();
+        for id in &self.structs {
+            let struct_env = self.env.get_struct(id.to_qualified_id());
+            let struct_name = struct_env.get_name();
+            let ambiguous = declared.iter().any(|other| {
+                *other != id.to_qualified_id()
+                    && self.env.get_struct(*other).get_name() == struct_name
+            });
+            let mut name = if ambiguous {
+                format!(
+                    "{}{}",
+                    struct_env.module_env.get_name().display(pool),
+                    struct_name.display(pool)
+                )
+            } else {
+                struct_name.display(pool).to_string()
+            };
+            if !id.inst.is_empty() {
+                name.push_str("Of");
+                for ty in &id.inst {
+                    name.push_str(&self.type_name(ty));
+                }
+            }
+            // Make sure the name is unique, which it is in all but contrived cases.
+            let mut unique_name = name.clone();
+            let mut count = 1;
+            while !used.insert(unique_name.clone()) {
+                count += 1;
+                unique_name = format!("{}{}", name, count);
+            }
+            names.insert(id.clone(), unique_name);
+        }
+        names
+    }
+
+    /// Returns the name of a type argument, as used in the names of instantiations. Struct
+    /// arguments need not be generated themselves, e.g. if they are phantom.
+    fn type_name(&self, ty: &Type) -> String {
+        match ty {
+            Type::Primitive(PrimitiveType::Bool) => "Bool".to_string(),
+            Type::Primitive(PrimitiveType::U8) => "U8".to_string(),
+            Type::Primitive(PrimitiveType::U64) => "U64".to_string(),
+            Type::Primitive(PrimitiveType::U128) => "U128".to_string(),
+            Type::Primitive(PrimitiveType::Signer) => "Signer".to_string(),
+            Type::Vector(elem_ty) => format!("Vec{}", self.type_name(elem_ty)),
+            Type::Struct(mid, sid, inst) => {
+                let name = self
+                    .env
+                    .get_module(*mid)
+                    .into_struct(*sid)
+                    .get_name()
+                    .display(self.env.symbol_pool())
+                    .to_string();
+                if inst.is_empty() {
+                    name
+                } else {
+                    format!(
+                        "{}Of{}",
+                        name,
+                        inst.iter().map(|ty| self.type_name(ty)).join("")
+                    )
+                }
+            }
+            _ => "Address".to_string(),
+        }
+    }
+}
+
+/// Returns the Rust name of a field, escaping keywords.
+fn rust_field_name(name: String) -> String {
+    match name.as_str() {
+        "self" | "Self" | "super" | "crate" => format!("{}_", name),
+        n if RUST_KEYWORDS.contains(&n) => format!("r#{}", name),
+        _ => name,
+    }
+}
@@ -22,6 +22,7 @@ use crate::{
         module_builder::ModuleBuilder,
     },
     model::{FieldId, Loc, ModuleId, NodeId, QualifiedId, SpecFunId, StructId},
+    source_edit::{Fix, SourceEdit},
     symbol::{Symbol, SymbolPool},
     ty::{PrimitiveType, Substitution, Type, TypeDisplayContext, Variance, BOOL_TYPE},
 };
@@ -153,6 +154,23 @@ impl<'env, 'translator, 'module_translator> ExpTranslator<'env, 'translator, 'mo
         }
     }
 
+    /// Shortcut for reporting an error with notes and fixes.
+    pub fn error_with_notes_and_fixes(
+        &self,
+        loc: &Loc,
+        msg: &str,
+        notes: Vec<String>,
+        fixes: Vec<Fix>,
+    ) {
+        if self.translating_fun_as_spec_fun {
+            *self.errors_generated.borrow_mut() = true;
+        } else {
+            self.parent
+                .parent
+                .error_with_notes_and_fixes(loc, msg, notes, fixes);
+        }
+    }
+
     /// Creates a fresh type variable.
     fn fresh_type_var(&mut self) -> Type {
         let var = Type::Var(self.type_var_counter);
@@ -696,7 +714,7 @@ impl<'env, 'translator, 'module_translator> ExpTranslator<'env, 'translator, 'mo
         let loc = self.to_loc(&exp.loc);
         let (_, exp) = self.translate_exp_free(exp);
         match &exp {
-            ExpData::Call(_, Operation::Global(_), _) => exp,
+            ExpData::Call(_, Operation::Global(_), _) | ExpData::Invalid(_) => exp,
             _ => {
                 self.error(&loc, "global resource access expected");
                 self.new_error_exp()
@@ -1097,16 +1115,86 @@ impl<'env, 'translator, 'module_translator> ExpTranslator<'env, 'translator, 'mo
             );
         }
 
-        self.error(
+        let mut notes = vec![];
+        let mut fixes = vec![];
+        if let EA::ModuleAccess_::Name(name) = &maccess.value {
+            let suggestions = self.suggest_names(name.value.as_str(), &global_var_sym.module_name);
+            if !suggestions.is_empty() {
+                notes.push(format!(
+                    "did you mean {}?",
+                    suggestions.iter().map(|s| format!("`{}`", s)).join(" or ")
+                ));
+            }
+            // Replacing the name is only safe to do without review if there is no choice.
+            let unique = suggestions.len() == 1;
+            let name_loc = self.to_loc(&name.loc);
+            fixes = suggestions
+                .into_iter()
+                .map(|s| {
+                    let fix = Fix::from_edit(
+                        format!("replace with `{}`", s),
+                        SourceEdit::replace(name_loc.clone(), s),
+                    );
+                    if unique {
+                        fix.preferred()
+                    } else {
+                        fix
+                    }
+                })
+                .collect();
+        }
+        notes.push(
+            "a name in a specification refers to a parameter or the `result` of the \
+             enclosing function, a `let` or quantifier variable, a schema parameter, or a \
+             constant or spec variable of the module; locals of the function body are only \
+             visible in inline spec blocks"
+                .to_string(),
+        );
+        self.error_with_notes_and_fixes(
             loc,
             &format!(
                 "undeclared `{}`",
                 global_var_sym.display(self.symbol_pool())
             ),
+            notes,
+            fixes,
         );
         self.new_error_exp()
     }
 
+    /// Computes the names in scope which are similar to the given undeclared name. Candidates
+    /// are the locals in scope as well as the constants and spec variables of the module.
+    fn suggest_names(&self, name: &str, module_name: &ModuleName) -> Vec<String> {
+        let pool = self.symbol_pool();
+        let module_syms = self
+            .parent
+            .parent
+            .const_table
+            .keys()
+            .chain(self.parent.parent.spec_var_table.keys())
+            .filter(|qsym| &qsym.module_name == module_name)
+            .map(|qsym| qsym.symbol);
+        let max_distance = std::cmp::max(1, name.len() / 3);
+        self.local_table
+            .iter()
+            .flat_map(|scope| scope.keys().cloned())
+            .chain(module_syms)
+            .map(|sym| pool.string(sym).to_string())
+            .filter_map(|candidate| {
+                let distance = edit_distance(name, &candidate);
+                if distance <= max_distance {
+                    Some((distance, candidate))
+                } else {
+                    None
+                }
+            })
+            .sorted()
+            .dedup()
+            .take(3)
+            .map(|(_, candidate)| candidate)
+            .collect()
+    }
+
     /// Creates an expression for a constant, checking the expected type.
     fn translate_constant(
         &mut self,
@@ -1330,6 +1418,9 @@ impl<'env, 'translator, 'module_translator> ExpTranslator<'env, 'translator, 'mo
                 );
                 None
             }
+        } else if struct_ty == Type::Error {
+            // An error has already been reported for the expression.
+            None
         } else {
             self.error(
                 loc,
@@ -1449,6 +1540,9 @@ impl<'env, 'translator, 'module_translator> ExpTranslator<'env, 'translator, 'mo
                         notes,
                     );
                 }
+                // Bind the expected type to the error type, so that enclosing expressions
+                // continue to be checked without reporting follow-up errors.
+                self.check_type(loc, &Type::Error, expected_type, "in expression");
                 self.new_error_exp()
             }
             1 => {
@@ -1939,3 +2033,20 @@ impl<'env, 'translator, 'module_translator> ExpTranslator<'env, 'translator, 'mo
         }
     }
 }
+
+/// Computes the Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect_vec();
+    let mut row = (0..=b.len()).collect_vec();
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev_diag = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == *cb { 0 } else { 1 };
+            let next = (row[j + 1] + 1).min(row[j] + 1).min(prev_diag + cost);
+            prev_diag = row[j + 1];
+            row[j + 1] = next;
+        }
+    }
+    row[b.len()]
+}
@@ -14,14 +14,21 @@ use num::BigUint;
 
 use move_compiler::{expansion::ast as EA, parser::ast as PA, shared::NumericalAddress};
 
+use move_binary_format::CompiledModule;
+use move_bytecode_source_map::source_map::SourceMap;
+
 use crate::{
-    ast::{Attribute, ModuleName, Operation, QualifiedSymbol, Spec, Value},
+    ast::{
+        Attribute, Condition, GlobalInvariant, ModuleName, Operation, QualifiedSymbol, Spec,
+        SpecBlockInfo, SpecFunDecl, SpecVarDecl, Value,
+    },
     builder::spec_builtins,
     model::{
-        FunId, FunctionVisibility, GlobalEnv, Loc, ModuleId, QualifiedId, SpecFunId, SpecVarId,
-        StructId,
+        FunId, FunctionData, FunctionVisibility, GlobalEnv, Loc, ModuleId, NamedConstantData,
+        NamedConstantId, QualifiedId, QualifiedInstId, SpecFunId, SpecVarId, StructData, StructId,
     },
     project_2nd,
+    source_edit::Fix,
     symbol::Symbol,
     ty::Type,
 };
@@ -57,6 +64,31 @@ pub(crate) struct ModelBuilder<'env> {
     pub const_table: BTreeMap<QualifiedSymbol, ConstEntry>,
     /// A call graph mapping callers to callees that are Move functions.
     pub move_fun_call_graph: BTreeMap<QualifiedId<SpecFunId>, BTreeSet<QualifiedId<SpecFunId>>>,
+    /// The memory used by the spec functions of translated modules. This is maintained here
+    /// instead of being looked up in the environment, because translated modules are entered
+    /// into the environment only after all modules have been translated.
+    pub spec_fun_used_memory: BTreeMap<QualifiedId<SpecFunId>, BTreeSet<QualifiedInstId<StructId>>>,
+}
+
+/// The result of translating a module, which is entered into the environment by
+/// `ModelBuilder::add_module`. Modules must be added in the order of their ids.
+#[derive(Debug)]
+pub(crate) struct ModuleTranslation {
+    pub module_id: ModuleId,
+    pub loc: Loc,
+    pub attributes: Vec<Attribute>,
+    pub module: CompiledModule,
+    pub source_map: SourceMap,
+    pub named_constants: BTreeMap<NamedConstantId, NamedConstantData>,
+    pub struct_data: BTreeMap<StructId, StructData>,
+    pub function_data: BTreeMap<FunId, FunctionData>,
+    pub spec_vars: Vec<SpecVarDecl>,
+    pub spec_funs: Vec<SpecFunDecl>,
+    pub module_spec: Spec,
+    pub spec_block_infos: Vec<SpecBlockInfo>,
+    /// The global invariants of the module, with the memory they use. Global ids are assigned
+    /// when the module is added, so they do not depend on the order of translation.
+    pub global_invariants: Vec<(Condition, BTreeSet<QualifiedInstId<StructId>>)>,
 }
 
 /// A declaration of a specification function or operator in the builders state.
@@ -151,11 +183,61 @@ impl<'env> ModelBuilder<'env> {
             fun_table: BTreeMap::new(),
             const_table: BTreeMap::new(),
             move_fun_call_graph: BTreeMap::new(),
+            spec_fun_used_memory: BTreeMap::new(),
         };
         spec_builtins::declare_spec_builtins(&mut translator);
         translator
     }
 
+    /// Enters a translated module into the environment.
+    pub fn add_module(&mut self, translation: ModuleTranslation) {
+        let ModuleTranslation {
+            module_id,
+            loc,
+            attributes,
+            module,
+            source_map,
+            named_constants,
+            struct_data,
+            function_data,
+            spec_vars,
+            spec_funs,
+            module_spec,
+            spec_block_infos,
+            global_invariants,
+        } = translation;
+        assert_eq!(
+            module_id.to_usize(),
+            self.env.get_module_count(),
+            "modules added in order of their ids"
+        );
+        for (cond, mem_usage) in global_invariants {
+            let id = self.env.new_global_id();
+            self.env.add_global_invariant(GlobalInvariant {
+                id,
+                loc: cond.loc,
+                kind: cond.kind,
+                mem_usage,
+                declaring_module: module_id,
+                cond: cond.exp,
+                properties: cond.properties,
+            });
+        }
+        self.env.add(
+            loc,
+            attributes,
+            module,
+            source_map,
+            named_constants,
+            struct_data,
+            function_data,
+            spec_vars,
+            spec_funs,
+            module_spec,
+            spec_block_infos,
+        );
+    }
+
     /// Shortcut for translating a Move AST location into ours.
     pub fn to_loc(&self, loc: &move_ir_types::location::Loc) -> Loc {
         self.env.to_loc(loc)
@@ -171,6 +253,17 @@ impl<'env> ModelBuilder<'env> {
         self.env.error_with_notes(at, msg, notes)
     }
 
+    /// Reports a type checking error with notes and fixes.
+    pub fn error_with_notes_and_fixes(
+        &self,
+        at: &Loc,
+        msg: &str,
+        notes: Vec<String>,
+        fixes: Vec<Fix>,
+    ) {
+        self.env.error_with_notes_and_fixes(at, msg, notes, fixes)
+    }
+
     /// Defines a spec function, adding it to the spec fun table.
     pub fn define_spec_fun(&mut self, name: QualifiedSymbol, entry: SpecFunEntry) {
         // TODO: check whether overloads are distinguishable
@@ -26,19 +26,19 @@ use move_ir_types::{ast::ConstantName, location::Spanned};
 
 use crate::{
     ast::{
-        Attribute, AttributeValue, Condition, ConditionKind, Exp, ExpData, GlobalInvariant,
-        ModuleName, Operation, PropertyBag, PropertyValue, QualifiedSymbol, Spec, SpecBlockInfo,
-        SpecBlockTarget, SpecFunDecl, SpecVarDecl, Value,
+        Attribute, AttributeValue, Condition, ConditionKind, Exp, ExpData, ModuleName, Operation,
+        PropertyBag, PropertyValue, QualifiedSymbol, Spec, SpecBlockInfo, SpecBlockTarget,
+        SpecFunDecl, SpecVarDecl, Value,
     },
     builder::{
         exp_translator::ExpTranslator,
-        model_builder::{ConstEntry, LocalVarEntry, ModelBuilder, SpecFunEntry},
+        model_builder::{ConstEntry, LocalVarEntry, ModelBuilder, ModuleTranslation, SpecFunEntry},
     },
     exp_rewriter::{ExpRewriter, ExpRewriterFunctions, RewriteTarget},
     model::{
-        AbilityConstraint, FieldId, FunId, FunctionData, FunctionVisibility, Loc, ModuleId,
-        MoveIrLoc, NamedConstantData, NamedConstantId, NodeId, QualifiedId, QualifiedInstId,
-        SchemaId, SpecFunId, SpecVarId, StructData, StructId, TypeParameter,
+        FieldId, FunId, FunctionData, FunctionVisibility, Loc, ModuleId, MoveIrLoc,
+        NamedConstantData, NamedConstantId, NodeId, QualifiedId, QualifiedInstId, SchemaId,
+        SpecFunId, SpecVarId, StructData, StructId, TypeParameter, TypeParameterKind,
         SCRIPT_BYTECODE_FUN_NAME,
     },
     options::ModelBuilderOptions,
@@ -79,6 +79,8 @@ pub(crate) struct ModuleBuilder<'env, 'translator> {
     /// Let bindings for the current spec block, characterized by a boolean indicating whether
     /// post state is active and the node id of the original expression of the let.
     pub spec_block_lets: BTreeMap<Symbol, (bool, NodeId)>,
+    /// Global invariants of the module, with the memory they use.
+    pub global_invariants: Vec<(Condition, BTreeSet<QualifiedInstId<StructId>>)>,
 }
 
 /// A value which we pass in to spec block analyzers, describing the resolved target of the spec
@@ -126,14 +128,15 @@ impl<'env, 'translator> ModuleBuilder<'env, 'translator> {
             module_spec: Spec::default(),
             spec_block_infos: Default::default(),
             spec_block_lets: BTreeMap::new(),
+            global_invariants: vec![],
         }
     }
 
     /// Translates the given module definition from the Move compiler's expansion phase,
-    /// combined with a compiled module (bytecode) and a source map, and enters it into
-    /// this global environment. Any type check or others errors encountered will be collected
-    /// in the environment for later processing. Dependencies of this module are guaranteed to
-    /// have been analyzed and being already part of the environment.
+    /// combined with a compiled module (bytecode) and a source map, and returns the result
+    /// which is to be entered into the environment. Any type check or others errors encountered
+    /// will be collected in the environment for later processing. Dependencies of this module
+    /// are guaranteed to have been translated, but need not be part of the environment yet.
     ///
     /// Translation happens in three phases:
     ///
@@ -144,8 +147,8 @@ impl<'env, 'translator> ModuleBuilder<'env, 'translator> {
     ///    cyclic references).
     /// 2. In the *definition analysis*, we visit the definitions we have skipped in step (1),
     ///    specifically analyzing and type checking expressions and schema inclusions.
-    /// 3. In the *population phase*, we populate the global environment with the information
-    ///    from this module.
+    /// 3. In the *population phase*, we collect the information from this module which is
+    ///    entered into the global environment via `ModelBuilder::add_module`.
     pub fn translate(
         &mut self,
         loc: Loc,
@@ -153,12 +156,12 @@ impl<'env, 'translator> ModuleBuilder<'env, 'translator> {
         compiled_module: CompiledModule,
         source_map: SourceMap,
         function_infos: UniqueMap<PA::FunctionName, FunctionInfo>,
-    ) {
+    ) -> ModuleTranslation {
         self.decl_ana(&module_def, &compiled_module, &source_map);
         self.def_ana(&module_def, function_infos);
         self.collect_spec_block_infos(&module_def);
         let attrs = self.translate_attributes(&module_def.attributes);
-        self.populate_env_from_result(loc, attrs, compiled_module, source_map);
+        self.populate_result(loc, attrs, compiled_module, source_map)
     }
 }
 
@@ -449,6 +452,7 @@ impl<'env, 'translator> ModuleBuilder<'env, 'translator> {
             body: None,
             callees: Default::default(),
             is_recursive: Default::default(),
+            decreasing_param: Default::default(),
         };
         if let EA::FunctionBody_::Native = def.body.value {
             fun_decl.is_native = true;
@@ -532,6 +536,7 @@ impl<'env, 'translator> ModuleBuilder<'env, 'translator> {
             body: None,
             callees: Default::default(),
             is_recursive: Default::default(),
+            decreasing_param: Default::default(),
         };
         self.spec_funs.push(fun_decl);
     }
@@ -2760,15 +2765,23 @@ impl<'env, 'translator> ModuleBuilder<'env, 'translator> {
         for idx in 0..self.spec_funs.len() {
             self.compute_state_usage_and_callees_for_fun(&mut visited, idx);
         }
+        for (idx, fun_decl) in self.spec_funs.iter().enumerate() {
+            self.parent.spec_fun_used_memory.insert(
+                self.module_id.qualified(SpecFunId::new(idx)),
+                fun_decl.used_memory.clone(),
+            );
+        }
         // Check for purity requirements. All data invariants must be pure expressions and
         // not depend on global state.
         let check_uses_memory = |mid: ModuleId, fid: SpecFunId| {
-            if mid.to_usize() < self.parent.env.get_module_count() {
+            if mid != self.module_id {
                 // This is calling a function from another module we already have
                 // translated.
-                let module_env = self.parent.env.get_module(mid);
-                let fun_decl = module_env.get_spec_fun(fid);
-                fun_decl.used_memory.is_empty()
+                self.parent
+                    .spec_fun_used_memory
+                    .get(&mid.qualified(fid))
+                    .iter()
+                    .all(|used_memory| used_memory.is_empty())
             } else {
                 // This is calling a function from the module we are currently translating.
                 let fun_decl = &self.spec_funs[fid.as_usize()];
@@ -2841,17 +2854,15 @@ impl<'env, 'translator> ModuleBuilder<'env, 'translator> {
                     let inst = self.parent.env.get_node_instantiation(*id);
                     // Extend used memory with that of called functions, after applying type
                     // instantiation of this call.
-                    if mid.to_usize() < self.parent.env.get_module_count() {
+                    if *mid != self.module_id {
                         // This is calling a function from another module we already have
                         // translated.
-                        let module_env = self.parent.env.get_module(*mid);
-                        let fun_decl = module_env.get_spec_fun(*fid);
-                        used_memory.extend(
-                            fun_decl
-                                .used_memory
-                                .iter()
-                                .map(|id| id.instantiate_ref(&inst)),
-                        );
+                        if let Some(fun_used_memory) =
+                            self.parent.spec_fun_used_memory.get(&mid.qualified(*fid))
+                        {
+                            used_memory
+                                .extend(fun_used_memory.iter().map(|id| id.instantiate_ref(&inst)));
+                        }
                     } else {
                         // This is calling a function from the module we are currently translating.
                         // Need to recursively ensure we have computed used_spec_vars because of
@@ -2889,7 +2900,7 @@ impl<'env, 'translator> ModuleBuilder<'env, 'translator> {
 /// ## Module Invariants
 
 impl<'env, 'translator> ModuleBuilder<'env, 'translator> {
-    /// Process module invariants, attaching them to the global env.
+    /// Process module invariants, collecting them together with their memory usage.
     fn process_module_invariants(&mut self) {
         for cond in self.module_spec.conditions.iter().cloned().collect_vec() {
             if matches!(
@@ -2897,17 +2908,7 @@ impl<'env, 'translator> ModuleBuilder<'env, 'translator> {
                 ConditionKind::GlobalInvariant(..) | ConditionKind::GlobalInvariantUpdate(..)
             ) {
                 let (mem_usage, _) = self.compute_state_usage_and_callees_for_exp(None, &cond.exp);
-                let id = self.parent.env.new_global_id();
-                let Condition { loc, exp, .. } = cond;
-                self.parent.env.add_global_invariant(GlobalInvariant {
-                    id,
-                    loc,
-                    kind: cond.kind,
-                    mem_usage,
-                    declaring_module: self.module_id,
-                    cond: exp,
-                    properties: cond.properties.clone(),
-                });
+                self.global_invariants.push((cond, mem_usage));
             }
         }
     }
@@ -2953,7 +2954,7 @@ impl<'env, 'translator> ModuleBuilder<'env, 'translator> {
                             .type_params
                             .iter()
                             .map(|(name, _)| {
-                                TypeParameter(*name, AbilityConstraint(AbilitySet::EMPTY))
+                                TypeParameter(*name, TypeParameterKind::new(AbilitySet::EMPTY))
                             })
                             .collect_vec(),
                     )
@@ -2978,6 +2979,28 @@ impl<'env, 'translator> ModuleBuilder<'env, 'translator> {
     /// Tweak the specifications at the AST level based on `ModuleBuilderOptions`.
     fn apply_tweaks(&mut self, module_def: &EA::ModuleDefinition) {
         self.tweak_pragma_opaque(module_def);
+        self.tweak_assume_dependency_specs(module_def);
+    }
+
+    /// If the `assume_dependency_specs` option is set, functions in dependency modules which
+    /// have specification conditions are marked as opaque, so their callers are verified against
+    /// the specification instead of the implementation. An explicit `pragma opaque = false` is
+    /// respected.
+    fn tweak_assume_dependency_specs(&mut self, module_def: &EA::ModuleDefinition) {
+        let env = &self.parent.env;
+        let options = env
+            .get_extension::<ModelBuilderOptions>()
+            .unwrap_or_default();
+        if !options.assume_dependency_specs || !env.is_dependency(&env.to_loc(&module_def.loc)) {
+            return;
+        }
+        let opaque_symbol = env.symbol_pool().make(OPAQUE_PRAGMA);
+        for spec in self.fun_specs.values_mut() {
+            if spec.has_conditions() && !spec.properties.contains_key(&opaque_symbol) {
+                spec.properties
+                    .insert(opaque_symbol, PropertyValue::Value(Value::Bool(true)));
+            }
+        }
     }
 
     /// If the `ignore_pragma_opaque_*` options are set, the opaque pragma will be
@@ -3061,13 +3084,13 @@ impl<'env, 'translator> ModuleBuilder<'env, 'translator> {
 /// # Environment Population
 
 impl<'env, 'translator> ModuleBuilder<'env, 'translator> {
-    fn populate_env_from_result(
+    fn populate_result(
         &mut self,
         loc: Loc,
         attributes: Vec<Attribute>,
         module: CompiledModule,
         source_map: SourceMap,
-    ) {
+    ) -> ModuleTranslation {
         let struct_data: BTreeMap<StructId, StructData> = (0..module.struct_defs().len())
             .filter_map(|idx| {
                 let def_idx = StructDefinitionIndex(idx as u16);
@@ -3160,7 +3183,8 @@ impl<'env, 'translator> ModuleBuilder<'env, 'translator> {
                 )
             })
             .collect();
-        self.parent.env.add(
+        ModuleTranslation {
+            module_id: self.module_id,
             loc,
             attributes,
             module,
@@ -3168,11 +3192,12 @@ impl<'env, 'translator> ModuleBuilder<'env, 'translator> {
             named_constants,
             struct_data,
             function_data,
-            std::mem::take(&mut self.spec_vars),
-            std::mem::take(&mut self.spec_funs),
-            std::mem::take(&mut self.module_spec),
-            std::mem::take(&mut self.spec_block_infos),
-        );
+            spec_vars: std::mem::take(&mut self.spec_vars),
+            spec_funs: std::mem::take(&mut self.spec_funs),
+            module_spec: std::mem::take(&mut self.module_spec),
+            spec_block_infos: std::mem::take(&mut self.spec_block_infos),
+            global_invariants: std::mem::take(&mut self.global_invariants),
+        }
     }
 }
 
@@ -0,0 +1,125 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Listings of the disassembled signatures of compiled modules.
+//!
+//! A model built from bytecode takes its locations and local names from the source maps of the
+//! modules. If the source file a source map refers to is not available, the model uses a
+//! listing of the signatures of the module instead, in which each struct and function is
+//! located at its disassembled declaration.
+
+use move_binary_format::{
+    access::ModuleAccess,
+    binary_views::BinaryIndexedView,
+    file_format::{FunctionDefinitionIndex, StructDefinitionIndex},
+    CompiledModule,
+};
+use move_bytecode_source_map::{mapping::SourceMapping, source_map::SourceMap};
+use move_command_line_common::files::FileHash;
+use move_disassembler::disassembler::{Disassembler, DisassemblerOptions};
+
+use crate::model::MoveIrLoc;
+
+/// Creates a listing of the signatures of the module, and a copy of the source map in which all
+/// locations refer to the listing. All locations of a function, including those of its code and
+/// locals, are the location of its signature.
+pub(crate) fn disassembled_source_map(
+    module: &CompiledModule,
+    source_map: &SourceMap,
+) -> anyhow::Result<(String, SourceMap)> {
+    let disassembler = Disassembler::new(
+        SourceMapping::new(source_map.clone(), BinaryIndexedView::Module(module)),
+        DisassemblerOptions {
+            only_externally_visible: false,
+            print_code: false,
+            print_basic_blocks: false,
+            print_locals: false,
+        },
+    );
+    let id = module.self_id();
+    let name = format!("0x{}::{}", id.address().short_str_lossless(), id.name());
+    let mut listing = format!(
+        "// Disassembled signatures of module {}\nmodule {} {{\n",
+        name, name
+    );
+    let mut add_item = |item: String| {
+        let start = listing.len();
+        for line in item.lines() {
+            listing.push_str(&format!("    {}\n", line.replace('\t', "    ")));
+        }
+        let span = (start, listing.len() - 1);
+        listing.push('\n');
+        span
+    };
+    let struct_spans = (0..module.struct_defs().len())
+        .map(|i| {
+            Ok(add_item(
+                disassembler.disassemble_struct_def(struct_def_idx(i))?,
+            ))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let function_spans = module
+        .function_defs()
+        .iter()
+        .enumerate()
+        .map(|(i, def)| {
+            let handle = module.function_handle_at(def.function);
+            let signature = disassembler.disassemble_function_def(
+                source_map.get_function_source_map(fun_def_idx(i))?,
+                Some((def, handle)),
+                module.identifier_at(handle.name),
+                &handle.type_parameters,
+                handle.parameters,
+                None,
+            )?;
+            Ok(add_item(format!("{};", signature)))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    listing.push_str("}\n");
+
+    let file_hash = FileHash::new(&listing);
+    let loc = |(start, end): (usize, usize)| MoveIrLoc::new(file_hash, start as u32, end as u32);
+    let mut result = SourceMap::new(loc((0, listing.len())), None);
+    result.module_name_opt = source_map.module_name_opt.clone();
+    result.constant_map = source_map.constant_map.clone();
+    for (i, span) in struct_spans.into_iter().enumerate() {
+        let idx = struct_def_idx(i);
+        let smap = source_map.get_struct_source_map(idx)?;
+        result.add_top_level_struct_mapping(idx, loc(span))?;
+        for (name, _) in &smap.type_parameters {
+            result.add_struct_type_parameter_mapping(idx, (name.clone(), loc(span)))?;
+        }
+        for _ in &smap.fields {
+            result.add_struct_field_mapping(idx, loc(span))?;
+        }
+    }
+    for (i, span) in function_spans.into_iter().enumerate() {
+        let idx = fun_def_idx(i);
+        let fmap = source_map.get_function_source_map(idx)?;
+        result.add_top_level_function_mapping(idx, loc(span), fmap.is_native)?;
+        for (name, _) in &fmap.type_parameters {
+            result.add_function_type_parameter_mapping(idx, (name.clone(), loc(span)))?;
+        }
+        for (name, _) in &fmap.parameters {
+            result.add_parameter_mapping(idx, (name.clone(), loc(span)))?;
+        }
+        for (name, _) in &fmap.locals {
+            result.add_local_mapping(idx, (name.clone(), loc(span)))?;
+        }
+        for offset in fmap.code_map.keys() {
+            result.add_code_mapping(idx, *offset, loc(span))?;
+        }
+        for (label, offset) in &fmap.nops {
+            result.add_nop_mapping(idx, label.clone(), *offset)?;
+        }
+    }
+    Ok((listing, result))
+}
+
+fn struct_def_idx(i: usize) -> StructDefinitionIndex {
+    StructDefinitionIndex(i as u16)
+}
+
+fn fun_def_idx(i: usize) -> FunctionDefinitionIndex {
+    FunctionDefinitionIndex(i as u16)
+}
@@ -0,0 +1,195 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! The call graph of the functions in a global environment.
+//!
+//! The graph is built once from the bytecode of the functions and can then be queried for the
+//! callees and callers of a function, their transitive closures, and the strongly connected
+//! components of the graph, which identify (mutually) recursive functions. Iteration over the
+//! graph is deterministic: functions are visited in the order of their qualified ids.
+
+use crate::model::{FunId, GlobalEnv, QualifiedId};
+use once_cell::sync::Lazy;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A call graph over qualified function ids.
+#[derive(Debug, Clone, Default)]
+pub struct CallGraph {
+    callees: BTreeMap<QualifiedId<FunId>, BTreeSet<QualifiedId<FunId>>>,
+    callers: BTreeMap<QualifiedId<FunId>, BTreeSet<QualifiedId<FunId>>>,
+}
+
+impl CallGraph {
+    /// Builds the call graph of all functions in the environment.
+    pub fn new(env: &GlobalEnv) -> Self {
+        let funs = env
+            .get_modules()
+            .flat_map(|module_env| {
+                module_env
+                    .get_functions()
+                    .map(|fun_env| fun_env.get_qualified_id())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<BTreeSet<_>>();
+        Self::new_for_functions(env, funs)
+    }
+
+    /// Builds the call graph restricted to the given functions. Calls to other functions are
+    /// not represented in the graph.
+    pub fn new_for_functions(
+        env: &GlobalEnv,
+        funs: impl IntoIterator<Item = QualifiedId<FunId>>,
+    ) -> Self {
+        let funs = funs.into_iter().collect::<BTreeSet<_>>();
+        let mut graph = CallGraph::default();
+        for fun_id in &funs {
+            graph.callees.entry(*fun_id).or_default();
+            graph.callers.entry(*fun_id).or_default();
+        }
+        for fun_id in &funs {
+            for callee in env.get_function(*fun_id).get_called_functions() {
+                if funs.contains(&callee) {
+                    graph.add_edge(*fun_id, callee);
+                }
+            }
+        }
+        graph
+    }
+
+    fn add_edge(&mut self, caller: QualifiedId<FunId>, callee: QualifiedId<FunId>) {
+        self.callees.entry(caller).or_default().insert(callee);
+        self.callers.entry(callee).or_default().insert(caller);
+    }
+
+    /// Returns the functions in the graph.
+    pub fn functions(&self) -> impl Iterator<Item = QualifiedId<FunId>> + '_ {
+        self.callees.keys().cloned()
+    }
+
+    /// Returns true if the function is in the graph.
+    pub fn contains(&self, fun_id: QualifiedId<FunId>) -> bool {
+        self.callees.contains_key(&fun_id)
+    }
+
+    /// Returns the functions directly called by the given one.
+    pub fn callees_of(&self, fun_id: QualifiedId<FunId>) -> &BTreeSet<QualifiedId<FunId>> {
+        self.callees.get(&fun_id).unwrap_or(&EMPTY)
+    }
+
+    /// Returns the functions which directly call the given one.
+    pub fn callers_of(&self, fun_id: QualifiedId<FunId>) -> &BTreeSet<QualifiedId<FunId>> {
+        self.callers.get(&fun_id).unwrap_or(&EMPTY)
+    }
+
+    /// Returns the functions reachable from the given one via one or more calls. The function
+    /// itself is only included if it is recursive.
+    pub fn transitive_callees_of(
+        &self,
+        fun_id: QualifiedId<FunId>,
+    ) -> BTreeSet<QualifiedId<FunId>> {
+        Self::reachable(&self.callees, fun_id)
+    }
+
+    /// Returns the functions from which the given one is reachable via one or more calls. The
+    /// function itself is only included if it is recursive.
+    pub fn transitive_callers_of(
+        &self,
+        fun_id: QualifiedId<FunId>,
+    ) -> BTreeSet<QualifiedId<FunId>> {
+        Self::reachable(&self.callers, fun_id)
+    }
+
+    fn reachable(
+        edges: &BTreeMap<QualifiedId<FunId>, BTreeSet<QualifiedId<FunId>>>,
+        start: QualifiedId<FunId>,
+    ) -> BTreeSet<QualifiedId<FunId>> {
+        let mut visited = BTreeSet::new();
+        let mut todo = vec![start];
+        while let Some(fun_id) = todo.pop() {
+            for next in edges.get(&fun_id).unwrap_or(&EMPTY) {
+                if visited.insert(*next) {
+                    todo.push(*next);
+                }
+            }
+        }
+        visited
+    }
+
+    /// Computes the strongly connected components of the graph. Components are returned in
+    /// reverse topological order, i.e. a component only calls into components which precede it.
+    pub fn sccs(&self) -> Vec<BTreeSet<QualifiedId<FunId>>> {
+        let mut state = TarjanState::default();
+        for fun_id in self.callees.keys() {
+            if !state.index.contains_key(fun_id) {
+                self.strong_connect(*fun_id, &mut state);
+            }
+        }
+        state.sccs
+    }
+
+    fn strong_connect(&self, fun_id: QualifiedId<FunId>, state: &mut TarjanState) {
+        let index = state.index.len();
+        state.index.insert(fun_id, index);
+        state.low_link.insert(fun_id, index);
+        state.stack.push(fun_id);
+        state.on_stack.insert(fun_id);
+        for callee in self.callees_of(fun_id) {
+            if !state.index.contains_key(callee) {
+                self.strong_connect(*callee, state);
+                let low_link = state.low_link[&fun_id].min(state.low_link[callee]);
+                state.low_link.insert(fun_id, low_link);
+            } else if state.on_stack.contains(callee) {
+                let low_link = state.low_link[&fun_id].min(state.index[callee]);
+                state.low_link.insert(fun_id, low_link);
+            }
+        }
+        if state.low_link[&fun_id] == index {
+            let mut scc = BTreeSet::new();
+            loop {
+                let member = state.stack.pop().expect("Tarjan stack");
+                state.on_stack.remove(&member);
+                scc.insert(member);
+                if member == fun_id {
+                    break;
+                }
+            }
+            state.sccs.push(scc);
+        }
+    }
+
+    /// Returns true if the function is recursive, directly or via other functions.
+    pub fn is_recursive(&self, fun_id: QualifiedId<FunId>) -> bool {
+        self.transitive_callees_of(fun_id).contains(&fun_id)
+    }
+
+    /// Returns the functions which are recursive, directly or via other functions.
+    pub fn recursive_functions(&self) -> BTreeSet<QualifiedId<FunId>> {
+        self.sccs()
+            .into_iter()
+            .filter(|scc| {
+                scc.len() > 1 || {
+                    let fun_id = scc.iter().next().unwrap();
+                    self.callees_of(*fun_id).contains(fun_id)
+                }
+            })
+            .flatten()
+            .collect()
+    }
+
+    /// Returns the functions in the graph such that each function precedes its callers, except
+    /// for calls between the members of a recursive component, which come in arbitrary order.
+    pub fn bottom_up_order(&self) -> Vec<QualifiedId<FunId>> {
+        self.sccs().into_iter().flatten().collect()
+    }
+}
+
+static EMPTY: Lazy<BTreeSet<QualifiedId<FunId>>> = Lazy::new(BTreeSet::new);
+
+#[derive(Default)]
+struct TarjanState {
+    index: BTreeMap<QualifiedId<FunId>, usize>,
+    low_link: BTreeMap<QualifiedId<FunId>, usize>,
+    stack: Vec<QualifiedId<FunId>>,
+    on_stack: BTreeSet<QualifiedId<FunId>>,
+    sccs: Vec<BTreeSet<QualifiedId<FunId>>>,
+}
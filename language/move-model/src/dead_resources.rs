@@ -0,0 +1,200 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Detection of dead global resources and struct fields.
+//!
+//! Global storage operations and field accesses of a struct can only appear in the module which
+//! declares it, so the code of that module determines how a struct is used. For the target
+//! modules, this finds:
+//!
+//! - Resources which are published with `move_to`, but never read, i.e. there is no `exists`,
+//!   `borrow_global`, `borrow_global_mut` or `move_from` for them.
+//! - Resources, i.e. structs with the `key` ability, which are never published.
+//! - Fields which are written, by packing the struct or assigning the field, but never read,
+//!   by borrowing the field or unpacking the struct. A mutable borrow of a field which is not
+//!   immediately assigned counts as a read.
+//!
+//! Uses in specifications are not considered, as they do not keep data alive at runtime.
+
+use crate::model::{GlobalEnv, Loc, ModuleEnv, QualifiedId, StructId};
+use move_binary_format::{
+    access::ModuleAccess,
+    file_format::{Bytecode, StructDefinitionIndex, StructFieldInformation},
+};
+use std::collections::BTreeSet;
+
+/// The kind of a dead resource or field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DeadResourceKind {
+    /// A resource is published but never read.
+    UnreadResource,
+    /// A resource is never published.
+    UnpublishedResource,
+    /// A field is written but never read.
+    UnreadField,
+}
+
+impl DeadResourceKind {
+    /// Returns the name of the kind, in snake case.
+    pub fn name(self) -> &'static str {
+        match self {
+            DeadResourceKind::UnreadResource => "unread_resource",
+            DeadResourceKind::UnpublishedResource => "unpublished_resource",
+            DeadResourceKind::UnreadField => "unread_field",
+        }
+    }
+}
+
+/// A dead resource or field.
+#[derive(Debug, Clone)]
+pub struct DeadResourceFinding {
+    pub kind: DeadResourceKind,
+    pub struct_id: QualifiedId<StructId>,
+    /// The location of the struct, or of the field for `UnreadField`.
+    pub loc: Loc,
+    pub message: String,
+}
+
+/// How the code of a module uses its structs.
+#[derive(Default)]
+struct StructUsage {
+    published: BTreeSet<StructDefinitionIndex>,
+    read: BTreeSet<StructDefinitionIndex>,
+    written_fields: BTreeSet<(StructDefinitionIndex, usize)>,
+    read_fields: BTreeSet<(StructDefinitionIndex, usize)>,
+}
+
+/// Returns the dead resources and fields of the target modules, in the order of the struct
+/// definitions of the compiled modules.
+pub fn dead_resource_findings(env: &GlobalEnv) -> Vec<DeadResourceFinding> {
+    let mut findings = vec![];
+    for module_env in env.get_modules().filter(|m| m.is_target()) {
+        let usage = struct_usage(&module_env);
+        for idx in 0..module_env.get_verified_module().struct_defs().len() {
+            let def_idx = StructDefinitionIndex(idx as u16);
+            let struct_env = module_env.get_struct_by_def_idx(def_idx);
+            let name = struct_env.get_full_name_with_address();
+            let mut add = |kind: DeadResourceKind, loc: Loc, message: String| {
+                findings.push(DeadResourceFinding {
+                    kind,
+                    struct_id: struct_env.get_qualified_id(),
+                    loc,
+                    message,
+                })
+            };
+            if struct_env.has_memory() {
+                if !usage.published.contains(&def_idx) {
+                    add(
+                        DeadResourceKind::UnpublishedResource,
+                        struct_env.get_loc(),
+                        format!("resource `{}` is never published", name),
+                    );
+                } else if !usage.read.contains(&def_idx) {
+                    add(
+                        DeadResourceKind::UnreadResource,
+                        struct_env.get_loc(),
+                        format!("resource `{}` is published but never read", name),
+                    );
+                }
+            }
+            for field_env in struct_env.get_fields() {
+                let field = (def_idx, field_env.get_offset());
+                if usage.written_fields.contains(&field) && !usage.read_fields.contains(&field) {
+                    add(
+                        DeadResourceKind::UnreadField,
+                        field_env.get_loc(),
+                        format!(
+                            "field `{}` of `{}` is written but never read",
+                            field_env.get_name().display(env.symbol_pool()),
+                            name
+                        ),
+                    );
+                }
+            }
+        }
+    }
+    findings
+}
+
+/// Collects how the functions of the module use its structs.
+fn struct_usage(module_env: &ModuleEnv<'_>) -> StructUsage {
+    let module = module_env.get_verified_module();
+    let field_count =
+        |idx: StructDefinitionIndex| match &module.struct_def_at(idx).field_information {
+            StructFieldInformation::Declared(fields) => fields.len(),
+            StructFieldInformation::Native => 0,
+        };
+    let mut usage = StructUsage::default();
+    for fun_env in module_env.get_functions() {
+        let code = fun_env.get_bytecode();
+        for (offset, bc) in code.iter().enumerate() {
+            match bc {
+                Bytecode::MoveTo(idx) => {
+                    usage.published.insert(*idx);
+                }
+                Bytecode::MoveToGeneric(idx) => {
+                    usage
+                        .published
+                        .insert(module.struct_instantiation_at(*idx).def);
+                }
+                Bytecode::Exists(idx)
+                | Bytecode::ImmBorrowGlobal(idx)
+                | Bytecode::MutBorrowGlobal(idx)
+                | Bytecode::MoveFrom(idx) => {
+                    usage.read.insert(*idx);
+                }
+                Bytecode::ExistsGeneric(idx)
+                | Bytecode::ImmBorrowGlobalGeneric(idx)
+                | Bytecode::MutBorrowGlobalGeneric(idx)
+                | Bytecode::MoveFromGeneric(idx) => {
+                    usage.read.insert(module.struct_instantiation_at(*idx).def);
+                }
+                Bytecode::Pack(idx) | Bytecode::Unpack(idx) => {
+                    let fields = (0..field_count(*idx)).map(|offset| (*idx, offset));
+                    if matches!(bc, Bytecode::Pack(_)) {
+                        usage.written_fields.extend(fields);
+                    } else {
+                        usage.read_fields.extend(fields);
+                    }
+                }
+                Bytecode::PackGeneric(idx) | Bytecode::UnpackGeneric(idx) => {
+                    let def = module.struct_instantiation_at(*idx).def;
+                    let fields = (0..field_count(def)).map(|offset| (def, offset));
+                    if matches!(bc, Bytecode::PackGeneric(_)) {
+                        usage.written_fields.extend(fields);
+                    } else {
+                        usage.read_fields.extend(fields);
+                    }
+                }
+                Bytecode::ImmBorrowField(idx) => {
+                    let handle = module.field_handle_at(*idx);
+                    usage
+                        .read_fields
+                        .insert((handle.owner, handle.field as usize));
+                }
+                Bytecode::ImmBorrowFieldGeneric(idx) => {
+                    let handle = module.field_handle_at(module.field_instantiation_at(*idx).handle);
+                    usage
+                        .read_fields
+                        .insert((handle.owner, handle.field as usize));
+                }
+                Bytecode::MutBorrowField(_) | Bytecode::MutBorrowFieldGeneric(_) => {
+                    let handle = match bc {
+                        Bytecode::MutBorrowField(idx) => module.field_handle_at(*idx),
+                        Bytecode::MutBorrowFieldGeneric(idx) => {
+                            module.field_handle_at(module.field_instantiation_at(*idx).handle)
+                        }
+                        _ => unreachable!(),
+                    };
+                    let field = (handle.owner, handle.field as usize);
+                    usage.written_fields.insert(field);
+                    if !matches!(code.get(offset + 1), Some(Bytecode::WriteRef)) {
+                        usage.read_fields.insert(field);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    usage
+}
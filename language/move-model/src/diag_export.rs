@@ -0,0 +1,382 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Export of diagnostics in SARIF 2.1, for upload to code scanning dashboards.
+//!
+//! Each diagnostic becomes a result of a single run. Results refer to a rule which identifies
+//! the category of the diagnostic. Diagnostics carrying a code use the code as rule; otherwise
+//! verification failures are categorized by their message, and all other diagnostics by their
+//! severity. Files below the root directory given to the exporter are referred to relative to
+//! the `SRCROOT` base, as expected by most dashboards.
+
+use crate::{
+    diag_json::{JsonDiagnostic, JsonLocation},
+    model::GlobalEnv,
+};
+use codespan_reporting::diagnostic::Severity;
+use serde_json::{json, Value};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+/// The SARIF version produced by the exporter.
+pub const SARIF_VERSION: &str = "2.1.0";
+
+/// The base id of artifact locations relative to the root directory.
+const SRCROOT: &str = "SRCROOT";
+
+/// A category of diagnostics.
+struct DiagRule {
+    id: &'static str,
+    description: &'static str,
+    /// The messages of the diagnostics in the category start with one of these.
+    message_prefixes: &'static [&'static str],
+}
+
+const RULES: &[DiagRule] = &[
+    DiagRule {
+        id: "verification/precondition",
+        description: "precondition of a called function does not hold",
+        message_prefixes: &["precondition does not hold"],
+    },
+    DiagRule {
+        id: "verification/postcondition",
+        description: "post-condition of a function does not hold",
+        message_prefixes: &["post-condition does not hold"],
+    },
+    DiagRule {
+        id: "verification/aborts",
+        description: "abort behavior of a function does not match its specification",
+        message_prefixes: &[
+            "abort not covered",
+            "abort code not covered",
+            "function does not abort under this condition",
+        ],
+    },
+    DiagRule {
+        id: "verification/invariant",
+        description: "data or global invariant does not hold",
+        message_prefixes: &[
+            "data invariant does not hold",
+            "global memory invariant does not hold",
+        ],
+    },
+    DiagRule {
+        id: "verification/loop-invariant",
+        description: "loop invariant does not hold",
+        message_prefixes: &[
+            "base case of the loop invariant",
+            "induction case of the loop invariant",
+        ],
+    },
+    DiagRule {
+        id: "verification/emits",
+        description: "emitted events do not match the specification",
+        message_prefixes: &["emitted event not covered"],
+    },
+    DiagRule {
+        id: "verification/inconsistency",
+        description: "assumptions of a function are inconsistent",
+        message_prefixes: &["there is an inconsistent assumption"],
+    },
+    DiagRule {
+        id: "verification/vacuity",
+        description: "specification holds vacuously",
+        message_prefixes: &[
+            "post-conditions hold vacuously",
+            "post-condition holds vacuously",
+        ],
+    },
+    DiagRule {
+        id: "verification/inconclusive",
+        description: "verification did not finish",
+        message_prefixes: &[
+            "verification out of resources",
+            "verification inconclusive",
+            "Boogie execution exceeded",
+        ],
+    },
+];
+
+/// The rules of diagnostics which fall into no other category.
+const SEVERITY_RULES: &[(Severity, DiagRule)] = &[
+    (
+        Severity::Bug,
+        DiagRule {
+            id: "move/bug",
+            description: "internal error",
+            message_prefixes: &[],
+        },
+    ),
+    (
+        Severity::Error,
+        DiagRule {
+            id: "move/error",
+            description: "error",
+            message_prefixes: &[],
+        },
+    ),
+    (
+        Severity::Warning,
+        DiagRule {
+            id: "move/warning",
+            description: "warning",
+            message_prefixes: &[],
+        },
+    ),
+    (
+        Severity::Note,
+        DiagRule {
+            id: "move/note",
+            description: "note",
+            message_prefixes: &[],
+        },
+    ),
+    (
+        Severity::Help,
+        DiagRule {
+            id: "move/help",
+            description: "help",
+            message_prefixes: &[],
+        },
+    ),
+];
+
+/// An exporter of the diagnostics of an environment to SARIF.
+pub struct SarifExporter<'env> {
+    env: &'env GlobalEnv,
+    tool_name: String,
+    root: Option<PathBuf>,
+    properties: BTreeMap<String, Value>,
+}
+
+impl<'env> SarifExporter<'env> {
+    pub fn new(env: &'env GlobalEnv, tool_name: impl Into<String>) -> Self {
+        Self {
+            env,
+            tool_name: tool_name.into(),
+            root: None,
+            properties: BTreeMap::new(),
+        }
+    }
+
+    /// Sets the directory which artifact locations are made relative to.
+    pub fn with_root(self, root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: Some(root.into()),
+            ..self
+        }
+    }
+
+    /// Adds a property of the run, like the seed needed to reproduce it.
+    pub fn with_property(mut self, name: impl Into<String>, value: Value) -> Self {
+        self.properties.insert(name.into(), value);
+        self
+    }
+
+    /// Returns the SARIF log of the diagnostics of given or higher severity.
+    pub fn export(&self, severity: Severity) -> Value {
+        let mut rules: Vec<Value> = vec![];
+        let mut rule_index: BTreeMap<String, usize> = BTreeMap::new();
+        let mut artifacts: Vec<Value> = vec![];
+        let mut artifact_index: BTreeMap<String, usize> = BTreeMap::new();
+        let mut results = vec![];
+        for (diag, fixes) in self.env.get_diags_with_fixes(severity) {
+            let json = JsonDiagnostic::new(self.env, &diag, &fixes);
+            let (rule_id, description) = match &json.code {
+                Some(code) => (code.clone(), json.message.clone()),
+                None => {
+                    let rule = rule_of(diag.severity, &json.message);
+                    (rule.id.to_string(), rule.description.to_string())
+                }
+            };
+            let rule_idx = *rule_index.entry(rule_id.clone()).or_insert_with(|| {
+                rules.push(json!({
+                    "id": rule_id,
+                    "shortDescription": { "text": description },
+                    "defaultConfiguration": { "level": level(diag.severity) },
+                }));
+                rules.len() - 1
+            });
+            let mut physical_location = |loc: &JsonLocation| {
+                let uri = self.uri(&loc.file)?;
+                let idx = *artifact_index.entry(loc.file.clone()).or_insert_with(|| {
+                    artifacts.push(json!({ "location": uri.clone() }));
+                    artifacts.len() - 1
+                });
+                let mut artifact_location = uri;
+                artifact_location["index"] = json!(idx);
+                Some(json!({
+                    "artifactLocation": artifact_location,
+                    "region": region(loc),
+                }))
+            };
+            let mut text = json.message.clone();
+            for note in &json.notes {
+                text.push('\n');
+                text.push_str(note);
+            }
+            let mut result = json!({
+                "ruleId": rule_id,
+                "ruleIndex": rule_idx,
+                "level": level(diag.severity),
+                "message": { "text": text },
+            });
+            if let Some(location) = json.location.as_ref().and_then(&mut physical_location) {
+                result["locations"] = json!([{ "physicalLocation": location }]);
+            }
+            let related = json
+                .labels
+                .iter()
+                .filter_map(|label| {
+                    let location = physical_location(&label.location)?;
+                    Some((location, label.message.clone()))
+                })
+                .enumerate()
+                .map(|(id, (location, message))| {
+                    json!({
+                        "id": id,
+                        "physicalLocation": location,
+                        "message": { "text": message },
+                    })
+                })
+                .collect::<Vec<_>>();
+            if !related.is_empty() {
+                result["relatedLocations"] = json!(related);
+            }
+            let fixes = json
+                .fixes
+                .iter()
+                .filter_map(|fix| {
+                    let mut changes: BTreeMap<String, (Value, Vec<Value>)> = BTreeMap::new();
+                    for edit in &fix.edits {
+                        let location = physical_location(&edit.location)?;
+                        changes
+                            .entry(edit.location.file.clone())
+                            .or_insert_with(|| (location["artifactLocation"].clone(), vec![]))
+                            .1
+                            .push(json!({
+                                "deletedRegion": location["region"],
+                                "insertedContent": { "text": edit.text },
+                            }));
+                    }
+                    Some(json!({
+                        "description": { "text": fix.title },
+                        "artifactChanges": changes
+                            .into_values()
+                            .map(|(artifact_location, replacements)| json!({
+                                "artifactLocation": artifact_location,
+                                "replacements": replacements,
+                            }))
+                            .collect::<Vec<_>>(),
+                    }))
+                })
+                .collect::<Vec<_>>();
+            if !fixes.is_empty() {
+                result["fixes"] = json!(fixes);
+            }
+            results.push(result);
+        }
+        let mut run = json!({
+            "tool": {
+                "driver": {
+                    "name": self.tool_name,
+                    "rules": rules,
+                }
+            },
+            "artifacts": artifacts,
+            "results": results,
+        });
+        if !self.properties.is_empty() {
+            run["properties"] = json!(self.properties);
+        }
+        if let Some(root) = &self.root {
+            if let Some(root) = absolute_path(root) {
+                run["originalUriBaseIds"] = json!({
+                    SRCROOT: { "uri": file_uri(&root, true) }
+                });
+            }
+        }
+        json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": SARIF_VERSION,
+            "runs": [run],
+        })
+    }
+
+    /// Writes the SARIF log of the diagnostics of given or higher severity to a file.
+    pub fn write(&self, path: &Path, severity: Severity) -> anyhow::Result<()> {
+        let log = serde_json::to_string_pretty(&self.export(severity))?;
+        std::fs::write(path, log)?;
+        Ok(())
+    }
+
+    /// Returns the artifact location of a file, or None if the file is not a real one, as for
+    /// the unknown location of the environment.
+    fn uri(&self, file: &str) -> Option<Value> {
+        if file.starts_with('<') {
+            return None;
+        }
+        let absolute = absolute_path(Path::new(file))?;
+        let root = self.root.as_ref().and_then(|root| absolute_path(root));
+        match root
+            .as_ref()
+            .and_then(|root| absolute.strip_prefix(root).ok())
+        {
+            Some(relative) => Some(json!({
+                "uri": relative.to_string_lossy().replace('\\', "/"),
+                "uriBaseId": SRCROOT,
+            })),
+            None => Some(json!({ "uri": file_uri(&absolute, false) })),
+        }
+    }
+}
+
+/// Returns the rule of a diagnostic without code.
+fn rule_of(severity: Severity, message: &str) -> &'static DiagRule {
+    RULES
+        .iter()
+        .find(|rule| rule.message_prefixes.iter().any(|p| message.starts_with(p)))
+        .unwrap_or_else(|| {
+            &SEVERITY_RULES
+                .iter()
+                .find(|(s, _)| *s == severity)
+                .expect("rule for severity")
+                .1
+        })
+}
+
+/// Returns the SARIF level of a severity.
+fn level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Bug | Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Note | Severity::Help => "note",
+    }
+}
+
+fn region(loc: &JsonLocation) -> Value {
+    json!({
+        "startLine": loc.start_line,
+        "startColumn": loc.start_column,
+        "endLine": loc.end_line,
+        "endColumn": loc.end_column,
+    })
+}
+
+fn absolute_path(path: &Path) -> Option<PathBuf> {
+    if path.is_absolute() {
+        Some(path.to_path_buf())
+    } else {
+        Some(std::env::current_dir().ok()?.join(path))
+    }
+}
+
+fn file_uri(path: &Path, is_dir: bool) -> String {
+    let mut uri = format!("file://{}", path.to_string_lossy().replace('\\', "/"));
+    if is_dir && !uri.ends_with('/') {
+        uri.push('/');
+    }
+    uri
+}
@@ -0,0 +1,154 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Diagnostics in a machine-readable JSON representation.
+//!
+//! Editors and other tools consume diagnostics of the model builder and the prover via
+//! `GlobalEnv::report_diag_json`, which writes each diagnostic as a JSON object on its own line.
+//! Positions are given both as byte offsets and as 1-based line and column numbers. Fixes
+//! attached to a diagnostic are included with their edits.
+
+use crate::{
+    model::{GlobalEnv, Loc},
+    source_edit::Fix,
+};
+use codespan::{ByteIndex, FileId, Span};
+use codespan_reporting::diagnostic::{Diagnostic, LabelStyle, Severity};
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// A diagnostic, located at its primary label.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonDiagnostic {
+    /// One of `bug`, `error`, `warning`, `note`, or `help`.
+    pub severity: String,
+    pub code: Option<String>,
+    pub message: String,
+    /// The location of the primary label, if the diagnostic has one.
+    pub location: Option<JsonLocation>,
+    /// The secondary labels of the diagnostic.
+    pub labels: Vec<JsonLabel>,
+    pub notes: Vec<String>,
+    pub fixes: Vec<JsonFix>,
+}
+
+/// A range of a source file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonLocation {
+    pub file: String,
+    /// The byte offset of the start of the range.
+    pub start: usize,
+    /// The byte offset of the end of the range, exclusive.
+    pub end: usize,
+    pub start_line: usize,
+    pub start_column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+/// A secondary label of a diagnostic.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonLabel {
+    pub location: JsonLocation,
+    pub message: String,
+}
+
+/// A fix of a diagnostic.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonFix {
+    pub title: String,
+    pub preferred: bool,
+    pub edits: Vec<JsonEdit>,
+}
+
+/// An edit replacing the text at a location.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonEdit {
+    pub location: JsonLocation,
+    pub text: String,
+}
+
+impl JsonDiagnostic {
+    pub fn new(env: &GlobalEnv, diag: &Diagnostic<FileId>, fixes: &[Fix]) -> Self {
+        let location = diag
+            .labels
+            .iter()
+            .find(|l| l.style == LabelStyle::Primary)
+            .map(|l| JsonLocation::from_range(env, l.file_id, &l.range));
+        let labels = diag
+            .labels
+            .iter()
+            .filter(|l| l.style == LabelStyle::Secondary)
+            .map(|l| JsonLabel {
+                location: JsonLocation::from_range(env, l.file_id, &l.range),
+                message: l.message.clone(),
+            })
+            .collect();
+        Self {
+            severity: severity_name(diag.severity).to_string(),
+            code: diag.code.clone(),
+            message: diag.message.clone(),
+            location,
+            labels,
+            notes: diag.notes.clone(),
+            fixes: fixes.iter().map(|fix| JsonFix::new(env, fix)).collect(),
+        }
+    }
+}
+
+impl JsonFix {
+    fn new(env: &GlobalEnv, fix: &Fix) -> Self {
+        let edits = fix
+            .edits
+            .files()
+            .flat_map(|file_id| fix.edits.edits_of(file_id))
+            .map(|edit| JsonEdit {
+                location: JsonLocation::from_loc(env, edit.loc()),
+                text: edit.text().to_string(),
+            })
+            .collect();
+        Self {
+            title: fix.title.clone(),
+            preferred: fix.preferred,
+            edits,
+        }
+    }
+}
+
+impl JsonLocation {
+    pub fn from_loc(env: &GlobalEnv, loc: &Loc) -> Self {
+        let (start_line, start_column) = position(env, loc.file_id(), loc.span().start());
+        let (end_line, end_column) = position(env, loc.file_id(), loc.span().end());
+        Self {
+            file: env.get_file(loc.file_id()).to_string_lossy().to_string(),
+            start: loc.span().start().to_usize(),
+            end: loc.span().end().to_usize(),
+            start_line,
+            start_column,
+            end_line,
+            end_column,
+        }
+    }
+
+    fn from_range(env: &GlobalEnv, file_id: FileId, range: &Range<usize>) -> Self {
+        let span = Span::new(range.start as u32, range.end as u32);
+        Self::from_loc(env, &Loc::new(file_id, span))
+    }
+}
+
+/// Returns the 1-based line and column of a byte offset, or zeros if it is not in the file.
+fn position(env: &GlobalEnv, file_id: FileId, pos: ByteIndex) -> (usize, usize) {
+    env.get_location(&Loc::new(file_id, Span::new(pos, pos)))
+        .map(|l| (l.line.0 as usize + 1, l.column.0 as usize + 1))
+        .unwrap_or_default()
+}
+
+fn severity_name(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Bug => "bug",
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Note => "note",
+        Severity::Help => "help",
+    }
+}
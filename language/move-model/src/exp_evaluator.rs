@@ -0,0 +1,611 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! An evaluator for specification expressions over concrete values.
+//!
+//! The evaluator computes the value of a specification expression given concrete values for the
+//! temporaries, local variables, and function results it refers to, as well as snapshots of the
+//! global memory before and after a function call. Arithmetic is unbounded, as in the
+//! specification language. Expressions whose value is unspecified, e.g. a vector index out of
+//! bounds or a `global` for a resource which does not exist, are reported as evaluation errors,
+//! as are constructs which cannot be evaluated, e.g. uninterpreted functions or quantifiers over
+//! infinite domains.
+
+use crate::{
+    ast::{Exp, ExpData, LocalVarDecl, MemoryLabel, Operation, QuantKind, TempIndex},
+    global_state::GlobalState,
+    model::{GlobalEnv, Loc, NodeId, QualifiedInstId, StructId},
+    symbol::Symbol,
+    ty::{PrimitiveType, Type},
+    value::MoveValue,
+};
+use itertools::Itertools;
+use num::{BigInt, BigUint, One, Signed, ToPrimitive, Zero};
+use std::{collections::BTreeMap, fmt};
+
+/// The maximal nesting of spec function calls, to bound the evaluation of recursive functions.
+const MAX_CALL_DEPTH: usize = 256;
+
+/// An error which occurred during evaluation, with the location of the offending expression.
+#[derive(Debug, Clone)]
+pub struct EvalError {
+    pub loc: Loc,
+    pub message: String,
+}
+
+pub type EvalResult<T> = Result<T, EvalError>;
+
+/// The local state of an evaluation: bindings of local variables, the type arguments of the
+/// spec function being evaluated, and whether evaluation happens in the pre-state of `old(..)`.
+#[derive(Clone, Default)]
+struct Frame {
+    locals: BTreeMap<Symbol, MoveValue>,
+    type_args: Vec<Type>,
+    in_old: bool,
+    depth: usize,
+}
+
+/// An evaluator for specification expressions. The state is set up with the builder methods
+/// before calling `ExpEvaluator::eval`.
+pub struct ExpEvaluator<'env> {
+    env: &'env GlobalEnv,
+    temps: BTreeMap<TempIndex, MoveValue>,
+    locals: BTreeMap<Symbol, MoveValue>,
+    results: Vec<MoveValue>,
+    memory: GlobalState,
+    labelled_memory: BTreeMap<MemoryLabel, GlobalState>,
+    old_temps: Option<BTreeMap<TempIndex, MoveValue>>,
+    old_memory: Option<GlobalState>,
+}
+
+impl<'env> ExpEvaluator<'env> {
+    /// Creates an evaluator with no bindings and an empty global memory.
+    pub fn new(env: &'env GlobalEnv) -> Self {
+        Self {
+            env,
+            temps: BTreeMap::new(),
+            locals: BTreeMap::new(),
+            results: vec![],
+            memory: GlobalState::new(),
+            labelled_memory: BTreeMap::new(),
+            old_temps: None,
+            old_memory: None,
+        }
+    }
+
+    /// Binds the value of a temporary, e.g. a function parameter.
+    pub fn temp(mut self, idx: TempIndex, val: MoveValue) -> Self {
+        self.temps.insert(idx, val);
+        self
+    }
+
+    /// Binds the value of a local variable, e.g. a variable introduced by a `let` in a schema.
+    pub fn local(mut self, name: Symbol, val: MoveValue) -> Self {
+        self.locals.insert(name, val);
+        self
+    }
+
+    /// Sets the values of the function results, as referred to by `result` or `result_<n>`.
+    pub fn results(mut self, vals: Vec<MoveValue>) -> Self {
+        self.results = vals;
+        self
+    }
+
+    /// Sets the global memory.
+    pub fn memory(mut self, memory: GlobalState) -> Self {
+        self.memory = memory;
+        self
+    }
+
+    /// Sets the global memory for a memory label, as used by instrumented expressions.
+    pub fn labelled_memory(mut self, label: MemoryLabel, memory: GlobalState) -> Self {
+        self.labelled_memory.insert(label, memory);
+        self
+    }
+
+    /// Sets the state for `old(..)` expressions. Temporaries which are not bound in the old
+    /// state are taken from the current state.
+    pub fn old_state(mut self, temps: BTreeMap<TempIndex, MoveValue>, memory: GlobalState) -> Self {
+        self.old_temps = Some(temps);
+        self.old_memory = Some(memory);
+        self
+    }
+
+    /// Evaluates the expression.
+    pub fn eval(&self, exp: &Exp) -> EvalResult<MoveValue> {
+        let frame = Frame {
+            locals: self.locals.clone(),
+            ..Default::default()
+        };
+        self.eval_exp(exp, &frame)
+    }
+
+    /// Evaluates a boolean expression, e.g. a specification condition.
+    pub fn eval_bool(&self, exp: &Exp) -> EvalResult<bool> {
+        let frame = Frame {
+            locals: self.locals.clone(),
+            ..Default::default()
+        };
+        self.eval_bool_in(exp, &frame)
+    }
+
+    fn eval_exp(&self, exp: &Exp, frame: &Frame) -> EvalResult<MoveValue> {
+        use ExpData::*;
+        match exp.as_ref() {
+            Value(_, val) => Ok(MoveValue::from(val)),
+            LocalVar(id, name) => frame.locals.get(name).cloned().ok_or_else(|| {
+                self.error(
+                    *id,
+                    format!(
+                        "local `{}` is not bound",
+                        name.display(self.env.symbol_pool())
+                    ),
+                )
+            }),
+            Temporary(id, idx) => {
+                let old_val = if frame.in_old {
+                    self.old_temps.as_ref().and_then(|temps| temps.get(idx))
+                } else {
+                    None
+                };
+                old_val
+                    .or_else(|| self.temps.get(idx))
+                    .cloned()
+                    .ok_or_else(|| self.error(*id, format!("temporary `$t{}` is not bound", idx)))
+            }
+            Call(id, oper, args) => self.eval_call(*id, oper, args, frame),
+            IfElse(_, cond, then_exp, else_exp) => {
+                if self.eval_bool_in(cond, frame)? {
+                    self.eval_exp(then_exp, frame)
+                } else {
+                    self.eval_exp(else_exp, frame)
+                }
+            }
+            Block(_, decls, body) => {
+                let mut frame = frame.clone();
+                for decl in decls {
+                    if let Some(binding) = &decl.binding {
+                        let val = self.eval_exp(binding, &frame)?;
+                        frame.locals.insert(decl.name, val);
+                    }
+                }
+                self.eval_exp(body, &frame)
+            }
+            Quant(id, kind, ranges, _, condition, body) => {
+                self.eval_quant(*id, *kind, ranges, condition.as_ref(), body, frame)
+            }
+            Invalid(id) | Invoke(id, ..) | Lambda(id, ..) => {
+                Err(self.error(*id, "expression cannot be evaluated".to_string()))
+            }
+        }
+    }
+
+    fn eval_bool_in(&self, exp: &Exp, frame: &Frame) -> EvalResult<bool> {
+        match self.eval_exp(exp, frame)? {
+            MoveValue::Bool(b) => Ok(b),
+            _ => Err(self.type_error(exp.node_id())),
+        }
+    }
+
+    fn eval_number(&self, exp: &Exp, frame: &Frame) -> EvalResult<BigInt> {
+        match self.eval_exp(exp, frame)? {
+            MoveValue::Number(n) => Ok(n),
+            _ => Err(self.type_error(exp.node_id())),
+        }
+    }
+
+    fn eval_address(&self, exp: &Exp, frame: &Frame) -> EvalResult<BigUint> {
+        match self.eval_exp(exp, frame)? {
+            MoveValue::Address(a) => Ok(a),
+            _ => Err(self.type_error(exp.node_id())),
+        }
+    }
+
+    fn eval_vector(&self, exp: &Exp, frame: &Frame) -> EvalResult<Vec<MoveValue>> {
+        match self.eval_exp(exp, frame)? {
+            MoveValue::Vector(elems) => Ok(elems),
+            _ => Err(self.type_error(exp.node_id())),
+        }
+    }
+
+    fn eval_struct(
+        &self,
+        exp: &Exp,
+        frame: &Frame,
+    ) -> EvalResult<(QualifiedInstId<StructId>, Vec<MoveValue>)> {
+        match self.eval_exp(exp, frame)? {
+            MoveValue::Struct(qid, fields) => Ok((qid, fields)),
+            _ => Err(self.type_error(exp.node_id())),
+        }
+    }
+
+    fn eval_call(
+        &self,
+        id: NodeId,
+        oper: &Operation,
+        args: &[Exp],
+        frame: &Frame,
+    ) -> EvalResult<MoveValue> {
+        use Operation::*;
+        let num = |i: usize| self.eval_number(&args[i], frame);
+        let boolean = |i: usize| self.eval_bool_in(&args[i], frame);
+        let val = match oper {
+            Function(mid, fid, _) => {
+                let module_env = self.env.get_module(*mid);
+                let decl = module_env.get_spec_fun(*fid);
+                let body = match &decl.body {
+                    Some(body) if !decl.uninterpreted && !decl.is_native => body.clone(),
+                    _ => {
+                        return Err(self.error(
+                            id,
+                            format!(
+                                "function `{}` has no definition to evaluate",
+                                decl.name.display(self.env.symbol_pool())
+                            ),
+                        ))
+                    }
+                };
+                if frame.depth >= MAX_CALL_DEPTH {
+                    return Err(self.error(id, "call depth exceeded".to_string()));
+                }
+                let mut locals = BTreeMap::new();
+                for ((name, _), arg) in decl.params.iter().zip(args) {
+                    locals.insert(*name, self.eval_exp(arg, frame)?);
+                }
+                let callee_frame = Frame {
+                    locals,
+                    type_args: self.instantiation(id, frame),
+                    in_old: frame.in_old,
+                    depth: frame.depth + 1,
+                };
+                return self.eval_exp(&body, &callee_frame);
+            }
+            Pack(..) => {
+                let qid = self.struct_type(id, frame)?;
+                let fields = args
+                    .iter()
+                    .map(|arg| self.eval_exp(arg, frame))
+                    .collect::<EvalResult<Vec<_>>>()?;
+                MoveValue::Struct(qid, fields)
+            }
+            Select(mid, sid, fid) => {
+                let offset = self
+                    .env
+                    .get_module(*mid)
+                    .into_struct(*sid)
+                    .get_field(*fid)
+                    .get_offset();
+                let (_, mut fields) = self.eval_struct(&args[0], frame)?;
+                fields.swap_remove(offset)
+            }
+            UpdateField(mid, sid, fid) => {
+                let offset = self
+                    .env
+                    .get_module(*mid)
+                    .into_struct(*sid)
+                    .get_field(*fid)
+                    .get_offset();
+                let (qid, mut fields) = self.eval_struct(&args[0], frame)?;
+                fields[offset] = self.eval_exp(&args[1], frame)?;
+                MoveValue::Struct(qid, fields)
+            }
+            Result(idx) => self
+                .results
+                .get(*idx)
+                .cloned()
+                .ok_or_else(|| self.error(id, format!("result {} is not bound", idx)))?,
+            Index => {
+                let mut elems = self.eval_vector(&args[0], frame)?;
+                let idx = self.index(id, &num(1)?, elems.len())?;
+                elems.swap_remove(idx)
+            }
+            Slice => {
+                let elems = self.eval_vector(&args[0], frame)?;
+                let (lo, hi) = self.eval_range(&args[1], frame)?;
+                let lo = self.index(id, &lo, elems.len() + 1)?;
+                let hi = self.index(id, &hi, elems.len() + 1)?;
+                MoveValue::Vector(elems.get(lo..hi.max(lo)).unwrap_or_default().to_vec())
+            }
+            Range | RangeVec => {
+                let (lo, hi) = self.eval_range_bounds(id, oper, args, frame)?;
+                MoveValue::Vector(Self::range_values(lo, hi))
+            }
+            Add => MoveValue::Number(num(0)? + num(1)?),
+            Sub => MoveValue::Number(num(0)? - num(1)?),
+            Mul => MoveValue::Number(num(0)? * num(1)?),
+            Div | Mod => {
+                let (lhs, rhs) = (num(0)?, num(1)?);
+                if rhs.is_zero() {
+                    return Err(self.error(id, "division by zero".to_string()));
+                }
+                MoveValue::Number(if matches!(oper, Div) {
+                    lhs / rhs
+                } else {
+                    lhs % rhs
+                })
+            }
+            BitOr => MoveValue::Number(num(0)? | num(1)?),
+            BitAnd => MoveValue::Number(num(0)? & num(1)?),
+            Xor => MoveValue::Number(num(0)? ^ num(1)?),
+            Shl | Shr => {
+                let lhs = num(0)?;
+                let shift = num(1)?
+                    .to_usize()
+                    .ok_or_else(|| self.error(id, "shift amount out of range".to_string()))?;
+                MoveValue::Number(if matches!(oper, Shl) {
+                    lhs << shift
+                } else {
+                    lhs >> shift
+                })
+            }
+            Implies => MoveValue::Bool(!boolean(0)? || boolean(1)?),
+            Iff => MoveValue::Bool(boolean(0)? == boolean(1)?),
+            And => MoveValue::Bool(boolean(0)? && boolean(1)?),
+            Or => MoveValue::Bool(boolean(0)? || boolean(1)?),
+            Not => MoveValue::Bool(!boolean(0)?),
+            Eq | Identical => {
+                MoveValue::Bool(self.eval_exp(&args[0], frame)? == self.eval_exp(&args[1], frame)?)
+            }
+            Neq => {
+                MoveValue::Bool(self.eval_exp(&args[0], frame)? != self.eval_exp(&args[1], frame)?)
+            }
+            Lt => MoveValue::Bool(num(0)? < num(1)?),
+            Gt => MoveValue::Bool(num(0)? > num(1)?),
+            Le => MoveValue::Bool(num(0)? <= num(1)?),
+            Ge => MoveValue::Bool(num(0)? >= num(1)?),
+            Len => MoveValue::Number(BigInt::from(self.eval_vector(&args[0], frame)?.len())),
+            Global(label) | Exists(label) => {
+                let qid = match self.instantiation(id, frame).pop() {
+                    Some(Type::Struct(mid, sid, inst)) => mid.qualified_inst(sid, inst),
+                    _ => return Err(self.type_error(id)),
+                };
+                let addr = self.eval_address(&args[0], frame)?;
+                let resource = self.memory_at(id, label, frame)?.get(&addr, &qid);
+                if matches!(oper, Exists(_)) {
+                    MoveValue::Bool(resource.is_some())
+                } else {
+                    resource
+                        .cloned()
+                        .ok_or_else(|| self.error(id, "resource does not exist".to_string()))?
+                }
+            }
+            Old => {
+                if self.old_temps.is_none() {
+                    return Err(self.error(id, "the old state is not available".to_string()));
+                }
+                let mut old_frame = frame.clone();
+                old_frame.in_old = true;
+                return self.eval_exp(&args[0], &old_frame);
+            }
+            Trace(_) => return self.eval_exp(&args[0], frame),
+            EmptyVec => MoveValue::Vector(vec![]),
+            SingleVec => MoveValue::Vector(vec![self.eval_exp(&args[0], frame)?]),
+            UpdateVec => {
+                let mut elems = self.eval_vector(&args[0], frame)?;
+                let idx = self.index(id, &num(1)?, elems.len())?;
+                elems[idx] = self.eval_exp(&args[2], frame)?;
+                MoveValue::Vector(elems)
+            }
+            ConcatVec => {
+                let mut elems = self.eval_vector(&args[0], frame)?;
+                elems.extend(self.eval_vector(&args[1], frame)?);
+                MoveValue::Vector(elems)
+            }
+            IndexOfVec => {
+                let elems = self.eval_vector(&args[0], frame)?;
+                let elem = self.eval_exp(&args[1], frame)?;
+                MoveValue::Number(match elems.iter().position(|e| e == &elem) {
+                    Some(idx) => BigInt::from(idx),
+                    None => BigInt::from(-1),
+                })
+            }
+            ContainsVec => {
+                let elems = self.eval_vector(&args[0], frame)?;
+                MoveValue::Bool(elems.contains(&self.eval_exp(&args[1], frame)?))
+            }
+            InRangeVec => {
+                let len = BigInt::from(self.eval_vector(&args[0], frame)?.len());
+                let idx = num(1)?;
+                MoveValue::Bool(!idx.is_negative() && idx < len)
+            }
+            InRangeRange => {
+                let (lo, hi) = self.eval_range(&args[0], frame)?;
+                let idx = num(1)?;
+                MoveValue::Bool(lo <= idx && idx < hi)
+            }
+            MaxU8 => MoveValue::Number(BigInt::from(u8::MAX)),
+            MaxU64 => MoveValue::Number(BigInt::from(u64::MAX)),
+            MaxU128 => MoveValue::Number(BigInt::from(u128::MAX)),
+            _ => return Err(self.error(id, format!("operation `{:?}` cannot be evaluated", oper))),
+        };
+        Ok(val)
+    }
+
+    /// Evaluates a quantifier by enumerating the values of its ranges.
+    fn eval_quant(
+        &self,
+        id: NodeId,
+        kind: QuantKind,
+        ranges: &[(LocalVarDecl, Exp)],
+        condition: Option<&Exp>,
+        body: &Exp,
+        frame: &Frame,
+    ) -> EvalResult<MoveValue> {
+        let mut domains = vec![];
+        for (_, range) in ranges {
+            domains.push(self.eval_domain(range, frame)?);
+        }
+        let mut chosen: Option<MoveValue> = None;
+        for vals in domains.into_iter().multi_cartesian_product() {
+            let mut body_frame = frame.clone();
+            for ((decl, _), val) in ranges.iter().zip(vals.iter()) {
+                body_frame.locals.insert(decl.name, val.clone());
+            }
+            if let Some(cond) = condition {
+                if !self.eval_bool_in(cond, &body_frame)? {
+                    continue;
+                }
+            }
+            match kind {
+                QuantKind::Forall => {
+                    if !self.eval_bool_in(body, &body_frame)? {
+                        return Ok(MoveValue::Bool(false));
+                    }
+                }
+                QuantKind::Exists => {
+                    if self.eval_bool_in(body, &body_frame)? {
+                        return Ok(MoveValue::Bool(true));
+                    }
+                }
+                QuantKind::Choose | QuantKind::ChooseMin => {
+                    if self.eval_bool_in(body, &body_frame)? {
+                        let val = vals.into_iter().next().expect("choice variable");
+                        if kind == QuantKind::Choose {
+                            return Ok(val);
+                        }
+                        if chosen.as_ref().map(|c| &val < c).unwrap_or(true) {
+                            chosen = Some(val);
+                        }
+                    }
+                }
+            }
+        }
+        match kind {
+            QuantKind::Forall => Ok(MoveValue::Bool(true)),
+            QuantKind::Exists => Ok(MoveValue::Bool(false)),
+            QuantKind::Choose | QuantKind::ChooseMin => {
+                chosen.ok_or_else(|| self.error(id, "no value satisfies the choice".to_string()))
+            }
+        }
+    }
+
+    /// Returns the values a quantified variable ranges over.
+    fn eval_domain(&self, range: &Exp, frame: &Frame) -> EvalResult<Vec<MoveValue>> {
+        let id = range.node_id();
+        match range.as_ref() {
+            ExpData::Call(_, Operation::Range, _) | ExpData::Call(_, Operation::RangeVec, _) => {
+                let (lo, hi) = self.eval_range(range, frame)?;
+                Ok(Self::range_values(lo, hi))
+            }
+            ExpData::Call(_, Operation::TypeDomain, _) => {
+                match self.env.get_node_type(id).instantiate(&frame.type_args) {
+                    Type::TypeDomain(ty) => match *ty {
+                        Type::Primitive(PrimitiveType::Bool) => {
+                            Ok(vec![MoveValue::Bool(false), MoveValue::Bool(true)])
+                        }
+                        Type::Primitive(PrimitiveType::U8) => Ok(Self::range_values(
+                            BigInt::zero(),
+                            BigInt::from(u8::MAX) + BigInt::one(),
+                        )),
+                        _ => Err(self.error(id, "cannot enumerate an infinite domain".to_string())),
+                    },
+                    _ => Err(self.type_error(id)),
+                }
+            }
+            ExpData::Call(_, Operation::ResourceDomain, _) => {
+                match self.env.get_node_type(id).instantiate(&frame.type_args) {
+                    Type::ResourceDomain(mid, sid, inst) => {
+                        let memory = self.memory_at(id, &None, frame)?;
+                        Ok(memory
+                            .resources_of(mid, sid, inst.as_deref())
+                            .map(|(_, _, val)| val.clone())
+                            .collect())
+                    }
+                    _ => Err(self.type_error(id)),
+                }
+            }
+            _ => self.eval_vector(range, frame),
+        }
+    }
+
+    /// Evaluates a range expression `lo..hi` or `range(v)` to its bounds.
+    fn eval_range(&self, exp: &Exp, frame: &Frame) -> EvalResult<(BigInt, BigInt)> {
+        match exp.as_ref() {
+            ExpData::Call(id, oper, args) => self.eval_range_bounds(*id, oper, args, frame),
+            _ => Err(self.error(exp.node_id(), "expected a range".to_string())),
+        }
+    }
+
+    fn eval_range_bounds(
+        &self,
+        id: NodeId,
+        oper: &Operation,
+        args: &[Exp],
+        frame: &Frame,
+    ) -> EvalResult<(BigInt, BigInt)> {
+        match oper {
+            Operation::Range => Ok((
+                self.eval_number(&args[0], frame)?,
+                self.eval_number(&args[1], frame)?,
+            )),
+            Operation::RangeVec => Ok((
+                BigInt::zero(),
+                BigInt::from(self.eval_vector(&args[0], frame)?.len()),
+            )),
+            _ => Err(self.error(id, "expected a range".to_string())),
+        }
+    }
+
+    fn range_values(lo: BigInt, hi: BigInt) -> Vec<MoveValue> {
+        let mut vals = vec![];
+        let mut i = lo;
+        while i < hi {
+            vals.push(MoveValue::Number(i.clone()));
+            i += 1;
+        }
+        vals
+    }
+
+    /// Converts a number into an index below `len`.
+    fn index(&self, id: NodeId, idx: &BigInt, len: usize) -> EvalResult<usize> {
+        idx.to_usize()
+            .filter(|i| *i < len)
+            .ok_or_else(|| self.error(id, format!("index {} out of bounds", idx)))
+    }
+
+    /// Returns the memory to be used for a `global` or `exists` with the given label.
+    fn memory_at(
+        &self,
+        id: NodeId,
+        label: &Option<MemoryLabel>,
+        frame: &Frame,
+    ) -> EvalResult<&GlobalState> {
+        match label {
+            Some(label) => self
+                .labelled_memory
+                .get(label)
+                .ok_or_else(|| self.error(id, "memory label is not bound".to_string())),
+            None if frame.in_old => Ok(self.old_memory.as_ref().unwrap_or(&self.memory)),
+            None => Ok(&self.memory),
+        }
+    }
+
+    /// Returns the struct type of the node, instantiated with the type arguments of the frame.
+    fn struct_type(&self, id: NodeId, frame: &Frame) -> EvalResult<QualifiedInstId<StructId>> {
+        match self.env.get_node_type(id).instantiate(&frame.type_args) {
+            Type::Struct(mid, sid, inst) => Ok(mid.qualified_inst(sid, inst)),
+            _ => Err(self.type_error(id)),
+        }
+    }
+
+    fn instantiation(&self, id: NodeId, frame: &Frame) -> Vec<Type> {
+        Type::instantiate_vec(self.env.get_node_instantiation(id), &frame.type_args)
+    }
+
+    fn error(&self, id: NodeId, message: String) -> EvalError {
+        EvalError {
+            loc: self.env.get_node_loc(id),
+            message,
+        }
+    }
+
+    fn type_error(&self, id: NodeId) -> EvalError {
+        self.error(id, "unexpected value".to_string())
+    }
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
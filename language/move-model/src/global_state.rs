@@ -0,0 +1,201 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A snapshot of the global memory, mapping addresses and resource types to the resources stored
+//! under them. Snapshots can be compared, yielding the resources which have been created, deleted
+//! or modified between them, and displayed in terms of the model.
+
+use crate::{
+    model::{GlobalEnv, ModuleId, QualifiedInstId, StructId},
+    ty::Type,
+    value::MoveValue,
+};
+use num::BigUint;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+/// A snapshot of the global memory.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GlobalState {
+    resources: BTreeMap<(BigUint, QualifiedInstId<StructId>), MoveValue>,
+}
+
+/// A change of a resource between two snapshots of the global memory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResourceChange {
+    Created(BigUint, QualifiedInstId<StructId>, MoveValue),
+    Deleted(BigUint, QualifiedInstId<StructId>, MoveValue),
+    /// A modified resource, with its old and new value.
+    Modified(BigUint, QualifiedInstId<StructId>, MoveValue, MoveValue),
+}
+
+impl GlobalState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the resource of the given type stored at the address, if any.
+    pub fn get(&self, addr: &BigUint, ty: &QualifiedInstId<StructId>) -> Option<&MoveValue> {
+        self.resources.get(&(addr.clone(), ty.clone()))
+    }
+
+    /// Returns true if a resource of the given type is stored at the address.
+    pub fn exists(&self, addr: &BigUint, ty: &QualifiedInstId<StructId>) -> bool {
+        self.get(addr, ty).is_some()
+    }
+
+    /// Stores a resource at the address, returning the resource previously stored there.
+    pub fn put(
+        &mut self,
+        addr: BigUint,
+        ty: QualifiedInstId<StructId>,
+        val: MoveValue,
+    ) -> Option<MoveValue> {
+        self.resources.insert((addr, ty), val)
+    }
+
+    /// Removes the resource of the given type from the address, returning it.
+    pub fn remove(&mut self, addr: &BigUint, ty: &QualifiedInstId<StructId>) -> Option<MoveValue> {
+        self.resources.remove(&(addr.clone(), ty.clone()))
+    }
+
+    /// Returns an iterator over all resources, ordered by address and type.
+    pub fn iter(&self) -> impl Iterator<Item = (&BigUint, &QualifiedInstId<StructId>, &MoveValue)> {
+        self.resources
+            .iter()
+            .map(|((addr, ty), val)| (addr, ty, val))
+    }
+
+    /// Returns the resources of a struct, restricted to the given instantiation if one is
+    /// provided.
+    pub fn resources_of<'a>(
+        &'a self,
+        module_id: ModuleId,
+        struct_id: StructId,
+        inst: Option<&'a [Type]>,
+    ) -> impl Iterator<Item = (&'a BigUint, &'a QualifiedInstId<StructId>, &'a MoveValue)> {
+        self.iter().filter(move |(_, ty, _)| {
+            ty.module_id == module_id
+                && ty.id == struct_id
+                && inst.map(|inst| inst == ty.inst.as_slice()).unwrap_or(true)
+        })
+    }
+
+    /// Returns the addresses which hold at least one resource.
+    pub fn addresses(&self) -> BTreeSet<BigUint> {
+        self.resources
+            .keys()
+            .map(|(addr, _)| addr.clone())
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.resources.is_empty()
+    }
+
+    /// Returns the changes from this state to the new state.
+    pub fn diff(&self, new: &GlobalState) -> Vec<ResourceChange> {
+        let mut changes = vec![];
+        for ((addr, ty), val) in &self.resources {
+            match new.get(addr, ty) {
+                None => changes.push(ResourceChange::Deleted(
+                    addr.clone(),
+                    ty.clone(),
+                    val.clone(),
+                )),
+                Some(new_val) if new_val != val => changes.push(ResourceChange::Modified(
+                    addr.clone(),
+                    ty.clone(),
+                    val.clone(),
+                    new_val.clone(),
+                )),
+                _ => {}
+            }
+        }
+        for ((addr, ty), val) in &new.resources {
+            if !self.exists(addr, ty) {
+                changes.push(ResourceChange::Created(
+                    addr.clone(),
+                    ty.clone(),
+                    val.clone(),
+                ));
+            }
+        }
+        changes
+    }
+
+    /// Returns a value which displays the state, one resource per line.
+    pub fn display<'a>(&'a self, env: &'a GlobalEnv) -> GlobalStateDisplay<'a> {
+        GlobalStateDisplay { env, state: self }
+    }
+}
+
+impl ResourceChange {
+    /// Returns a value which displays the change in one line.
+    pub fn display<'a>(&'a self, env: &'a GlobalEnv) -> ResourceChangeDisplay<'a> {
+        ResourceChangeDisplay { env, change: self }
+    }
+}
+
+/// Returns the display of a resource location, e.g. `Coin::Balance<u64> at 0x42`.
+fn display_location(env: &GlobalEnv, addr: &BigUint, ty: &QualifiedInstId<StructId>) -> String {
+    format!(
+        "{} at {}",
+        ty.to_type().display(&env.get_type_display_ctx()),
+        env.display_address(addr)
+    )
+}
+
+/// Helper type for displaying global states.
+pub struct GlobalStateDisplay<'a> {
+    env: &'a GlobalEnv,
+    state: &'a GlobalState,
+}
+
+impl<'a> fmt::Display for GlobalStateDisplay<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (addr, ty, val) in self.state.iter() {
+            writeln!(
+                f,
+                "{}: {}",
+                display_location(self.env, addr, ty),
+                val.display(self.env)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Helper type for displaying resource changes.
+pub struct ResourceChangeDisplay<'a> {
+    env: &'a GlobalEnv,
+    change: &'a ResourceChange,
+}
+
+impl<'a> fmt::Display for ResourceChangeDisplay<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.change {
+            ResourceChange::Created(addr, ty, val) => write!(
+                f,
+                "+ {}: {}",
+                display_location(self.env, addr, ty),
+                val.display(self.env)
+            ),
+            ResourceChange::Deleted(addr, ty, val) => write!(
+                f,
+                "- {}: {}",
+                display_location(self.env, addr, ty),
+                val.display(self.env)
+            ),
+            ResourceChange::Modified(addr, ty, old, new) => write!(
+                f,
+                "~ {}: {} -> {}",
+                display_location(self.env, addr, ty),
+                old.display(self.env),
+                new.display(self.env)
+            ),
+        }
+    }
+}
@@ -0,0 +1,235 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Selection of the Move language version a package targets.
+//!
+//! The model builder accepts the features of the latest version. If an older version is selected
+//! via `ModelBuilderOptions::language_version`, the built model is checked for features which are
+//! not available in that version, and each use is reported as an error, so the same build of the
+//! model can be used for packages targeting different releases of Move.
+
+use crate::{
+    model::{FunctionEnv, GlobalEnv, Loc, ModuleEnv},
+    ty::{PrimitiveType, Type},
+};
+use move_binary_format::{
+    access::ModuleAccess,
+    file_format::{Bytecode, CodeOffset, SignatureToken, Visibility},
+};
+use move_core_types::account_address::AccountAddress;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeSet, fmt, str::FromStr};
+
+/// The number of bytes of an address in the versions of Move before `V3`.
+const LEGACY_ADDRESS_LENGTH: usize = 16;
+
+/// A version of the Move language.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum LanguageVersion {
+    /// The initial version: `u64` as the only integer type, 16 byte addresses, and public or
+    /// private functions.
+    V1,
+    /// Adds the integer types `u8` and `u128`, friend declarations, and `public(friend)` and
+    /// `public(script)` functions.
+    V2,
+    /// Adds addresses longer than 16 bytes, up to the address length of this build.
+    V3,
+}
+
+impl Default for LanguageVersion {
+    fn default() -> Self {
+        Self::latest()
+    }
+}
+
+impl LanguageVersion {
+    /// The latest version, which has all features supported by the model.
+    pub fn latest() -> Self {
+        LanguageVersion::V3
+    }
+
+    /// Returns the integer types available in this version.
+    pub fn integer_types(self) -> &'static [PrimitiveType] {
+        match self {
+            LanguageVersion::V1 => &[PrimitiveType::U64],
+            LanguageVersion::V2 | LanguageVersion::V3 => {
+                &[PrimitiveType::U8, PrimitiveType::U64, PrimitiveType::U128]
+            }
+        }
+    }
+
+    /// Returns the maximal number of bytes of an address in this version.
+    pub fn address_length(self) -> usize {
+        match self {
+            LanguageVersion::V1 | LanguageVersion::V2 => LEGACY_ADDRESS_LENGTH,
+            LanguageVersion::V3 => AccountAddress::LENGTH,
+        }
+    }
+
+    /// Returns true if friend declarations and `public(friend)` functions are available.
+    pub fn has_friends(self) -> bool {
+        self >= LanguageVersion::V2
+    }
+
+    /// Returns true if `public(script)` functions are available.
+    pub fn has_script_functions(self) -> bool {
+        self >= LanguageVersion::V2
+    }
+}
+
+impl fmt::Display for LanguageVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LanguageVersion::V1 => f.write_str("1"),
+            LanguageVersion::V2 => f.write_str("2"),
+            LanguageVersion::V3 => f.write_str("3"),
+        }
+    }
+}
+
+impl FromStr for LanguageVersion {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim_start_matches(|c| c == 'v' || c == 'V') {
+            "1" => Ok(LanguageVersion::V1),
+            "2" => Ok(LanguageVersion::V2),
+            "3" => Ok(LanguageVersion::V3),
+            _ => Err(anyhow::anyhow!("unknown language version `{}`", s)),
+        }
+    }
+}
+
+/// Reports an error for each use of a feature in the modules of the environment which is not
+/// available in the given language version.
+pub fn check_language_version(env: &GlobalEnv, version: LanguageVersion) {
+    if version == LanguageVersion::latest() {
+        return;
+    }
+    let checker = VersionChecker { env, version };
+    for module_env in env.get_modules() {
+        checker.check_module(&module_env);
+    }
+}
+
+struct VersionChecker<'env> {
+    env: &'env GlobalEnv,
+    version: LanguageVersion,
+}
+
+impl<'env> VersionChecker<'env> {
+    fn check_module(&self, module_env: &ModuleEnv) {
+        let addr_bytes = (module_env.get_name().addr().bits() as usize + 7) / 8;
+        if addr_bytes > self.version.address_length() {
+            self.unavailable(
+                &module_env.get_loc(),
+                &format!("addresses of {} bytes", addr_bytes),
+            );
+        }
+        if !self.version.has_friends() && !module_env.get_friends().is_empty() {
+            self.unavailable(&module_env.get_loc(), "friend declarations");
+        }
+        for struct_env in module_env.get_structs() {
+            let mut reported = BTreeSet::new();
+            for field_env in struct_env.get_fields() {
+                self.check_type(&struct_env.get_loc(), &field_env.get_type(), &mut reported);
+            }
+        }
+        for fun_env in module_env.get_functions() {
+            self.check_function(&fun_env);
+        }
+    }
+
+    fn check_function(&self, fun_env: &FunctionEnv) {
+        let loc = fun_env.get_loc();
+        match fun_env.visibility() {
+            Visibility::Friend if !self.version.has_friends() => {
+                self.unavailable(&loc, "`public(friend)` functions")
+            }
+            Visibility::Script if !self.version.has_script_functions() => {
+                self.unavailable(&loc, "`public(script)` functions")
+            }
+            _ => {}
+        }
+        // Unavailable integer types are reported once per function, at the signature or local
+        // declaration if they appear there, otherwise at their first use in the code.
+        let mut reported = BTreeSet::new();
+        let signature_types = (0..fun_env.get_local_count())
+            .map(|idx| fun_env.get_local_type(idx))
+            .chain(fun_env.get_return_types());
+        for ty in signature_types {
+            self.check_type(&loc, &ty, &mut reported);
+        }
+        if fun_env.is_native() {
+            return;
+        }
+        let module = fun_env.module_env.get_verified_module();
+        for (offset, bc) in fun_env.get_bytecode().iter().enumerate() {
+            let loc = || fun_env.get_bytecode_loc(offset as CodeOffset);
+            let ty = match bc {
+                Bytecode::LdU8(_) | Bytecode::CastU8 => PrimitiveType::U8,
+                Bytecode::LdU128(_) | Bytecode::CastU128 => PrimitiveType::U128,
+                Bytecode::LdConst(idx) => {
+                    let constant = module.constant_at(*idx);
+                    if constant.type_ == SignatureToken::Address {
+                        self.check_address(&loc(), &constant.data);
+                    }
+                    continue;
+                }
+                _ => continue,
+            };
+            if self.check_integer_type(&loc(), ty, &reported) {
+                reported.insert(ty);
+            }
+        }
+    }
+
+    /// Checks an address constant, serialized as a fixed number of big endian bytes.
+    fn check_address(&self, loc: &Loc, data: &[u8]) {
+        let addr_bytes = data.len() - data.iter().take_while(|b| **b == 0).count();
+        if addr_bytes > self.version.address_length() {
+            self.unavailable(loc, &format!("addresses of {} bytes", addr_bytes));
+        }
+    }
+
+    fn check_type(&self, loc: &Loc, ty: &Type, reported: &mut BTreeSet<PrimitiveType>) {
+        ty.visit(&mut |t| {
+            if let Type::Primitive(prim) = t {
+                if self.check_integer_type(loc, *prim, reported) {
+                    reported.insert(*prim);
+                }
+            }
+        });
+    }
+
+    /// Reports the integer type if it is not available and has not been reported yet. Returns
+    /// true if it has been reported.
+    fn check_integer_type(
+        &self,
+        loc: &Loc,
+        ty: PrimitiveType,
+        reported: &BTreeSet<PrimitiveType>,
+    ) -> bool {
+        let name = match ty {
+            PrimitiveType::U8 => "u8",
+            PrimitiveType::U64 => "u64",
+            PrimitiveType::U128 => "u128",
+            _ => return false,
+        };
+        if self.version.integer_types().contains(&ty) || reported.contains(&ty) {
+            return false;
+        }
+        self.unavailable(loc, &format!("integer type `{}`", name));
+        true
+    }
+
+    fn unavailable(&self, loc: &Loc, feature: &str) {
+        self.env.error(
+            loc,
+            &format!(
+                "{} not available in Move language version {}",
+                feature, self.version
+            ),
+        );
+    }
+}
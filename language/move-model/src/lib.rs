@@ -7,13 +7,18 @@ use codespan::ByteIndex;
 use codespan_reporting::diagnostic::{Diagnostic, Label, LabelStyle};
 use itertools::Itertools;
 #[allow(unused_imports)]
-use log::warn;
+use log::{debug, warn};
 use move_symbol_pool::Symbol as MoveSymbol;
-use std::collections::{BTreeMap, BTreeSet};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    mem,
+    path::Path,
+};
 
 use builder::module_builder::ModuleBuilder;
 use move_binary_format::{
     access::ModuleAccess,
+    binary_views::BinaryIndexedView,
     check_bounds::BoundsChecker,
     file_format::{
         self_module_name, AddressIdentifierIndex, CompiledModule, CompiledScript,
@@ -22,40 +27,70 @@ use move_binary_format::{
         StructDefinitionIndex, Visibility,
     },
 };
+use move_bytecode_source_map::source_map::SourceMap;
+use move_command_line_common::files::{find_move_filenames, FileHash};
 use move_compiler::{
     self,
+    command_line::compiler::EMPTY_COMPILER,
     compiled_unit::{self, AnnotatedCompiledScript, AnnotatedCompiledUnit},
-    diagnostics::Diagnostics,
+    diagnostics::{Diagnostics, FilesSourceText},
     expansion::ast::{self as E, Address, ModuleDefinition, ModuleIdent, ModuleIdent_},
     parser::ast::{self as P, ModuleName as ParserModuleName},
     shared::{parse_named_address, unique_map::UniqueMap, NumericalAddress, PackagePaths},
-    Compiler, Flags, PASS_COMPILATION, PASS_EXPANSION, PASS_PARSER,
+    Compiler, Flags, SteppedCompiler, PASS_COMPILATION, PASS_EXPANSION, PASS_PARSER,
 };
 use move_core_types::{account_address::AccountAddress, identifier::Identifier};
 use move_ir_types::location::sp;
 use num::{BigUint, Num};
+use sha2::{Digest, Sha256};
 
 use crate::{
     ast::{ModuleName, Spec},
     builder::model_builder::ModelBuilder,
-    model::{FunId, FunctionData, GlobalEnv, Loc, ModuleData, ModuleId, StructId},
+    bytecode_source::disassembled_source_map,
+    language_version::check_language_version,
+    model::{FunId, FunctionData, GlobalEnv, ModuleData, ModuleId, MoveIrLoc, StructId},
     options::ModelBuilderOptions,
     simplifier::{SpecRewriter, SpecRewriterPipeline},
+    spec_fun_termination::check_spec_fun_termination,
 };
 
 pub mod ast;
+pub mod audit_heuristics;
+pub mod bcs_codegen;
 mod builder;
+mod bytecode_source;
+pub mod call_graph;
 pub mod code_writer;
+pub mod dead_resources;
+pub mod diag_export;
+pub mod diag_json;
+pub mod exp_evaluator;
 pub mod exp_generator;
 pub mod exp_rewriter;
+pub mod global_state;
+pub mod language_version;
+pub mod lint;
+pub mod mock_gen;
 pub mod model;
+pub mod module_fetcher;
 pub mod native;
 pub mod options;
 pub mod pragmas;
+pub mod script_composer;
 pub mod simplifier;
+pub mod source_edit;
+pub mod spec_extraction;
+pub mod spec_formatter;
+pub mod spec_fun_termination;
+pub mod spec_metrics;
+pub mod spec_test;
 pub mod spec_translator;
+pub mod state_import;
 pub mod symbol;
+pub mod transaction_sequence;
 pub mod ty;
+pub mod value;
 
 // =================================================================================================
 // Entry Point
@@ -98,6 +133,624 @@ pub fn run_model_builder_with_options_and_compilation_flags<
 ) -> anyhow::Result<GlobalEnv> {
     let mut env = GlobalEnv::new();
     env.set_extension(options);
+    if let Some(program) = compile_verified_program(&mut env, move_sources, deps, flags)? {
+        // Now that it is known that the program has no errors, run the spec checker on verified
+        // units plus expanded AST. This will populate the environment including any errors.
+        run_spec_checker(&mut env, program.units, program.expansion_ast);
+    }
+    Ok(env)
+}
+
+/// The result of compiling and verifying a Move program, as input to the spec checker. This is
+/// retained in the environment of a model build to rebuild the model or re-check the specs of a
+/// module without recompilation, see `run_spec_checker_for_module`.
+#[derive(Debug, Clone)]
+pub struct VerifiedProgram {
+    /// The source files the program was compiled from.
+    files: FilesSourceText,
+    /// The verified compiled units.
+    units: Vec<AnnotatedCompiledUnit>,
+    /// The expanded AST of the compiled units.
+    expansion_ast: E::Program,
+}
+
+/// Build the move model like `run_model_builder_with_options_and_compilation_flags`, and in
+/// addition return the verified program the model has been built from. The program is `None` if
+/// compilation or bytecode verification failed.
+pub fn run_model_builder_with_verified_program<
+    Paths: Into<MoveSymbol>,
+    NamedAddress: Into<MoveSymbol>,
+>(
+    move_sources: Vec<PackagePaths<Paths, NamedAddress>>,
+    deps: Vec<PackagePaths<Paths, NamedAddress>>,
+    options: ModelBuilderOptions,
+    flags: Flags,
+) -> anyhow::Result<(GlobalEnv, Option<VerifiedProgram>)> {
+    let move_sources = into_symbol_paths(move_sources);
+    let deps = into_symbol_paths(deps);
+    let mut env = GlobalEnv::new();
+    env.set_extension(options);
+    let program =
+        compile_verified_program(&mut env, move_sources.clone(), deps.clone(), flags.clone())?;
+    if let Some(program) = &program {
+        run_spec_checker(
+            &mut env,
+            program.units.clone(),
+            program.expansion_ast.clone(),
+        );
+        env.set_extension(BuildInputs {
+            move_sources,
+            deps,
+            flags,
+            program: program.clone(),
+        });
+    }
+    Ok((env, program))
+}
+
+/// The inputs of a model build, retained in the environment so the model can be rebuilt with
+/// `run_model_builder_reusing_bytecode`.
+struct BuildInputs {
+    move_sources: Vec<PackagePaths>,
+    deps: Vec<PackagePaths>,
+    flags: Flags,
+    program: VerifiedProgram,
+}
+
+fn into_symbol_paths<Paths: Into<MoveSymbol>, NamedAddress: Into<MoveSymbol>>(
+    paths: Vec<PackagePaths<Paths, NamedAddress>>,
+) -> Vec<PackagePaths> {
+    paths
+        .into_iter()
+        .map(|p| PackagePaths {
+            name: p.name,
+            paths: p.paths.into_iter().map(Into::into).collect(),
+            named_address_map: p
+                .named_address_map
+                .into_iter()
+                .map(|(n, a)| (n.into(), a))
+                .collect(),
+        })
+        .collect()
+}
+
+/// Rebuild the model of a previous build after some of its sources have changed, reusing the
+/// bytecode of the modules which are not affected by the changes. The previous environment must
+/// have been built with `run_model_builder_with_verified_program` or by this function, which
+/// retain the verified program in the environment.
+///
+/// Modules whose source is listed in `changed_files` or whose source text has changed since the
+/// previous build are recompiled, together with the modules which depend on them, directly or
+/// indirectly. The bytecode of all other modules is taken from the previous build, which saves
+/// code generation and bytecode verification for them. Nothing else is reused: all sources are
+/// parsed, expanded, and type checked again, as the compiler resolves names against the whole
+/// program, and the model data of all modules, including their specs, is built from scratch in
+/// a new environment.
+pub fn run_model_builder_reusing_bytecode(
+    env: &GlobalEnv,
+    changed_files: &[String],
+) -> anyhow::Result<GlobalEnv> {
+    let inputs = env.get_extension::<BuildInputs>().ok_or_else(|| {
+        anyhow::anyhow!("the model has not been built with a retained verified program")
+    })?;
+    let options = env
+        .get_extension::<ModelBuilderOptions>()
+        .map(|o| o.as_ref().clone())
+        .unwrap_or_default();
+    let mut new_env = GlobalEnv::new();
+    new_env.set_extension(options);
+    let (compiler, expansion_ast, files, dep_files) = match parse_and_expand(
+        &mut new_env,
+        inputs.move_sources.clone(),
+        inputs.deps.clone(),
+        inputs.flags.clone(),
+    )? {
+        Some(res) => res,
+        None => return Ok(new_env),
+    };
+    let visited_modules = collect_program_modules(&expansion_ast, &dep_files);
+
+    // Determine the modules which need to be recompiled: those from changed sources, those
+    // without bytecode from the previous build, and those depending on any of them.
+    let is_changed = |fhash: &FileHash| {
+        let (fname, _) = &files[fhash];
+        !inputs.program.files.contains_key(fhash)
+            || changed_files.iter().any(|f| f.as_str() == fname.as_str())
+    };
+    let previous_units = inputs
+        .program
+        .units
+        .iter()
+        .filter_map(|unit| match unit {
+            AnnotatedCompiledUnit::Module(m) => Some((m.module_ident().value, unit.clone())),
+            AnnotatedCompiledUnit::Script(_) => None,
+        })
+        .collect::<BTreeMap<_, _>>();
+    let changed = expansion_ast
+        .modules
+        .key_cloned_iter()
+        .filter(|(_, mdef)| is_changed(&mdef.loc.file_hash()))
+        .map(|(mident, _)| mident.value)
+        .collect::<BTreeSet<_>>();
+    let (units, expansion_ast) = match compile_affected_units(
+        &mut new_env,
+        compiler,
+        expansion_ast,
+        &visited_modules,
+        &changed,
+        previous_units,
+    ) {
+        Some(res) => res,
+        None => return Ok(new_env),
+    };
+
+    run_spec_checker(&mut new_env, units.clone(), expansion_ast.clone());
+    new_env.set_extension(BuildInputs {
+        move_sources: inputs.move_sources.clone(),
+        deps: inputs.deps.clone(),
+        flags: inputs.flags.clone(),
+        program: VerifiedProgram {
+            files,
+            units,
+            expansion_ast,
+        },
+    });
+    Ok(new_env)
+}
+
+/// Compiles the modules of the program which are affected by changes: the modules in `changed`,
+/// those without a unit in `previous_units`, and those depending on any of them, directly or
+/// indirectly. The units of all other modules are taken from `previous_units`. Returns the
+/// units of the modules in dependency order followed by the scripts, together with the expanded
+/// program in which all modules are source modules, or `None` if there have been errors.
+fn compile_affected_units(
+    env: &mut GlobalEnv,
+    compiler: SteppedCompiler<'static, EMPTY_COMPILER>,
+    expansion_ast: E::Program,
+    visited_modules: &BTreeSet<ModuleIdent_>,
+    changed: &BTreeSet<ModuleIdent_>,
+    mut previous_units: BTreeMap<ModuleIdent_, AnnotatedCompiledUnit>,
+) -> Option<(Vec<AnnotatedCompiledUnit>, E::Program)> {
+    let mut affected = expansion_ast
+        .modules
+        .key_cloned_iter()
+        .filter(|(mident, _)| {
+            visited_modules.contains(&mident.value)
+                && (changed.contains(&mident.value) || !previous_units.contains_key(&mident.value))
+        })
+        .map(|(mident, _)| mident.value)
+        .collect::<BTreeSet<_>>();
+    loop {
+        let dependents = expansion_ast
+            .modules
+            .key_cloned_iter()
+            .filter(|(mident, mdef)| {
+                visited_modules.contains(&mident.value)
+                    && !affected.contains(&mident.value)
+                    && mdef
+                        .immediate_neighbors
+                        .key_cloned_iter()
+                        .any(|(neighbor, _)| affected.contains(&neighbor.value))
+            })
+            .map(|(mident, _)| mident.value)
+            .collect::<Vec<_>>();
+        if dependents.is_empty() {
+            break;
+        }
+        affected.extend(dependents);
+    }
+
+    debug!(
+        "recompiling {} of {} modules",
+        affected.len(),
+        visited_modules.len()
+    );
+
+    // Compile the affected modules, with the others as dependencies only.
+    let E::Program { modules, scripts } = expansion_ast;
+    let modules = modules.filter_map(|mident, mut mdef| {
+        visited_modules.contains(&mident.value).then(|| {
+            mdef.is_source_module = affected.contains(&mident.value);
+            mdef
+        })
+    });
+    let expansion_ast = E::Program { modules, scripts };
+    let new_units = compile_units(env, compiler, expansion_ast.clone())?;
+
+    // Merge new and previous units, preserving the topological order of modules.
+    let (mut units, scripts): (Vec<_>, Vec<_>) = new_units
+        .into_iter()
+        .partition(|unit| matches!(unit, AnnotatedCompiledUnit::Module(_)));
+    units.extend(
+        visited_modules
+            .iter()
+            .filter(|mident| !affected.contains(mident))
+            .filter_map(|mident| previous_units.remove(mident)),
+    );
+    let dependency_order = |unit: &AnnotatedCompiledUnit| match unit {
+        AnnotatedCompiledUnit::Module(m) => expansion_ast
+            .modules
+            .get_(&m.module_ident().value)
+            .map(|mdef| mdef.dependency_order)
+            .unwrap_or_default(),
+        AnnotatedCompiledUnit::Script(_) => usize::MAX,
+    };
+    units.sort_by_key(dependency_order);
+    units.extend(scripts);
+    let E::Program { modules, scripts } = expansion_ast;
+    let expansion_ast = E::Program {
+        modules: modules.filter_map(|_, mut mdef| {
+            mdef.is_source_module = true;
+            Some(mdef)
+        }),
+        scripts,
+    };
+    Some((units, expansion_ast))
+}
+
+/// A cache of the compiled modules of named packages, which allows rebuilding the model of a
+/// workspace without recompiling packages whose sources have not changed. Only the bytecode is
+/// cached; the model data of all modules is built from scratch. The modules of a package are
+/// cached with a fingerprint of the package, determined by the hashes of its source files, its
+/// named address bindings, and the compilation flags. A new build of the package replaces them,
+/// so the cache holds at most one build per package.
+#[derive(Default)]
+pub struct PackageCache {
+    entries: BTreeMap<String, PackageCacheEntry>,
+}
+
+struct PackageCacheEntry {
+    fingerprint: [u8; 32],
+    units: BTreeMap<ModuleIdent_, AnnotatedCompiledUnit>,
+}
+
+impl PackageCache {
+    /// Returns the number of packages in the cache.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the number of cached modules.
+    pub fn module_count(&self) -> usize {
+        self.entries.values().map(|entry| entry.units.len()).sum()
+    }
+}
+
+/// Build the move model like `run_model_builder_with_options_and_compilation_flags`, taking the
+/// compiled modules of named packages whose fingerprint has not changed from the cache, and
+/// adding the modules of all named packages to the cache afterwards. Modules which depend on
+/// recompiled modules are recompiled as well; modules outside of named packages are always
+/// compiled. Specs are checked for all modules, as the environment is built from scratch.
+pub fn run_model_builder_with_package_cache<
+    Paths: Into<MoveSymbol>,
+    NamedAddress: Into<MoveSymbol>,
+>(
+    move_sources: Vec<PackagePaths<Paths, NamedAddress>>,
+    deps: Vec<PackagePaths<Paths, NamedAddress>>,
+    options: ModelBuilderOptions,
+    flags: Flags,
+    cache: &mut PackageCache,
+) -> anyhow::Result<GlobalEnv> {
+    let move_sources = into_symbol_paths(move_sources);
+    let deps = into_symbol_paths(deps);
+    let mut env = GlobalEnv::new();
+    env.set_extension(options);
+    let (compiler, expansion_ast, files, dep_files) =
+        match parse_and_expand(&mut env, move_sources.clone(), deps.clone(), flags.clone())? {
+            Some(res) => res,
+            None => return Ok(env),
+        };
+    let visited_modules = collect_program_modules(&expansion_ast, &dep_files);
+
+    // Compute the fingerprints of the packages.
+    let package_of = |env: &GlobalEnv, fhash: FileHash| {
+        env.get_file_id(fhash)
+            .and_then(|file_id| env.get_file_package(file_id))
+            .map(|name| name.to_string())
+    };
+    let mut package_files: BTreeMap<String, Vec<FileHash>> = BTreeMap::new();
+    for fhash in files.keys() {
+        if let Some(package) = package_of(&env, *fhash) {
+            package_files.entry(package).or_default().push(*fhash);
+        }
+    }
+    let fingerprints = package_files
+        .into_iter()
+        .map(|(package, fhashes)| {
+            let mut hasher = Sha256::new();
+            for fhash in fhashes {
+                hasher.update(&fhash.0);
+            }
+            for paths in move_sources.iter().chain(deps.iter()) {
+                if paths.name.map(|n| n.as_str() == package).unwrap_or(false) {
+                    hasher.update(format!("{:?}", paths.named_address_map).as_bytes());
+                }
+            }
+            hasher.update(format!("{:?}", flags).as_bytes());
+            let fingerprint: [u8; 32] = hasher.finalize().into();
+            (package, fingerprint)
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    // Modules of packages which are in the cache are taken from there, all others are compiled.
+    let mut previous_units = BTreeMap::new();
+    let mut changed = BTreeSet::new();
+    for (mident, mdef) in expansion_ast.modules.key_cloned_iter() {
+        let cached = package_of(&env, mdef.loc.file_hash())
+            .and_then(|package| {
+                cache
+                    .entries
+                    .get(&package)
+                    .filter(|entry| entry.fingerprint == fingerprints[&package])
+            })
+            .and_then(|entry| entry.units.get(&mident.value));
+        match cached {
+            Some(unit) => {
+                previous_units.insert(mident.value, unit.clone());
+            }
+            None => {
+                changed.insert(mident.value);
+            }
+        }
+    }
+    let (units, expansion_ast) = match compile_affected_units(
+        &mut env,
+        compiler,
+        expansion_ast,
+        &visited_modules,
+        &changed,
+        previous_units,
+    ) {
+        Some(res) => res,
+        None => return Ok(env),
+    };
+
+    // Update the cache with the modules of the packages, replacing previous builds of them.
+    for (package, fingerprint) in &fingerprints {
+        cache.entries.insert(
+            package.clone(),
+            PackageCacheEntry {
+                fingerprint: *fingerprint,
+                units: BTreeMap::new(),
+            },
+        );
+    }
+    for unit in &units {
+        if let AnnotatedCompiledUnit::Module(m) = unit {
+            if let Some(package) = package_of(&env, m.loc.file_hash()) {
+                cache
+                    .entries
+                    .get_mut(&package)
+                    .expect("package entry")
+                    .units
+                    .insert(m.module_ident().value, unit.clone());
+            }
+        }
+    }
+
+    run_spec_checker(&mut env, units.clone(), expansion_ast.clone());
+    env.set_extension(BuildInputs {
+        move_sources,
+        deps,
+        flags,
+        program: VerifiedProgram {
+            files,
+            units,
+            expansion_ast,
+        },
+    });
+    Ok(env)
+}
+
+/// Re-run the spec checker for a single module of a previous build, without recompiling Move
+/// code. This is intended for workflows which iterate on specs. The previous environment must
+/// have been built with `run_model_builder_with_verified_program` or
+/// `run_model_builder_reusing_bytecode`, which retain the verified program in the environment.
+///
+/// Only the source files of the module with the given name (either `M` or `0x1::M`) and of the
+/// modules it depends on, directly or indirectly, are parsed and expanded again. The new AST of
+/// the module is checked together with the verified bytecode of the previous build; all other
+/// modules are taken from the previous build.
+///
+/// It is the responsibility of the caller to ensure that only specs have changed. As a sanity
+/// check, an error is returned if the module does not exist in the program or if the
+/// declarations of its functions, structs, or constants have changed, including their
+/// signatures. Locations in the bytecode of the module still refer to the previous version of
+/// its source, which is added to the environment as a dependency.
+pub fn run_spec_checker_for_module(
+    env: &GlobalEnv,
+    module_name: &str,
+) -> anyhow::Result<GlobalEnv> {
+    let inputs = env.get_extension::<BuildInputs>().ok_or_else(|| {
+        anyhow::anyhow!("the model has not been built with a retained verified program")
+    })?;
+    let program = &inputs.program;
+    let is_target = |mident: &ModuleIdent_| {
+        mident.to_string() == module_name || mident.module.to_string() == module_name
+    };
+    let mident = match program
+        .expansion_ast
+        .modules
+        .key_cloned_iter()
+        .find(|(mident, _)| is_target(&mident.value))
+    {
+        Some((mident, _)) => mident,
+        None => anyhow::bail!("module `{}` not found in verified program", module_name),
+    };
+
+    // Restrict the sources to the files of the module and of the modules it depends on, which
+    // are needed to expand it.
+    let mut related_modules = BTreeSet::new();
+    collect_related_modules_recursive(
+        &mident.value,
+        &program.expansion_ast.modules,
+        &mut related_modules,
+    );
+    let related_files = related_modules
+        .iter()
+        .map(|mident| {
+            let fhash = program
+                .expansion_ast
+                .modules
+                .get_(mident)
+                .unwrap()
+                .loc
+                .file_hash();
+            program.files[&fhash].0.as_str()
+        })
+        .collect::<BTreeSet<_>>();
+    let restrict = |packages: &[PackagePaths]| {
+        packages
+            .iter()
+            .filter_map(|package| {
+                let paths = related_files
+                    .iter()
+                    .filter(|file| {
+                        package
+                            .paths
+                            .iter()
+                            .any(|path| Path::new(file).starts_with(path.as_str()))
+                    })
+                    .map(|file| MoveSymbol::from(*file))
+                    .collect::<Vec<_>>();
+                (!paths.is_empty()).then(|| PackagePaths {
+                    name: package.name,
+                    paths,
+                    named_address_map: package.named_address_map.clone(),
+                })
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let options = env
+        .get_extension::<ModelBuilderOptions>()
+        .map(|o| o.as_ref().clone())
+        .unwrap_or_default();
+    let mut new_env = GlobalEnv::new();
+    new_env.set_extension(options);
+    let expansion_ast = match parse_and_expand(
+        &mut new_env,
+        restrict(&inputs.move_sources),
+        restrict(&inputs.deps),
+        inputs.flags.clone(),
+    )? {
+        Some((_, expansion_ast, _, _)) => expansion_ast,
+        None => return Ok(new_env),
+    };
+    // Add source files of the previous build which have not been parsed, so locations in the
+    // retained bytecode can be translated.
+    for fhash in program.files.keys().sorted() {
+        if new_env.get_file_id(*fhash).is_none() {
+            let (fname, fsrc) = program.files.get(fhash).unwrap();
+            new_env.add_source(*fhash, fname.as_str(), fsrc, /* is_dep */ true);
+        }
+    }
+
+    // Replace the AST of the module in the previous program by the new one.
+    let mut new_mdef = match expansion_ast.modules.get(&mident) {
+        Some(mdef) => mdef.clone(),
+        None => anyhow::bail!("module `{}` not found in sources", module_name),
+    };
+    let mut eprog = program.expansion_ast.clone();
+    let old_mdef = eprog.modules.get_mut(&mident).unwrap();
+    if !declarations_match(old_mdef, &new_mdef) {
+        anyhow::bail!(
+            "declarations of module `{}` have changed, the program needs to be recompiled",
+            module_name
+        )
+    }
+    new_mdef.is_source_module = old_mdef.is_source_module;
+    *old_mdef = new_mdef;
+
+    run_spec_checker(&mut new_env, program.units.clone(), eprog);
+    // Retain the inputs of the previous build, so specs can be re-checked again.
+    new_env.set_extension(BuildInputs {
+        move_sources: inputs.move_sources.clone(),
+        deps: inputs.deps.clone(),
+        flags: inputs.flags.clone(),
+        program: program.clone(),
+    });
+    Ok(new_env)
+}
+
+/// Returns true if two versions of a module declare the same functions, structs, and constants
+/// with the same signatures, ignoring locations, function bodies, and specs.
+fn declarations_match(old: &ModuleDefinition, new: &ModuleDefinition) -> bool {
+    let functions_match = old.functions.len() == new.functions.len()
+        && old.functions.iter().all(|(_, name, old_fun)| {
+            new.functions.get_(name).map_or(false, |new_fun| {
+                mem::discriminant(&old_fun.visibility) == mem::discriminant(&new_fun.visibility)
+                    && old_fun.signature == new_fun.signature
+                    && old_fun.acquires == new_fun.acquires
+                    && matches!(old_fun.body.value, E::FunctionBody_::Native)
+                        == matches!(new_fun.body.value, E::FunctionBody_::Native)
+            })
+        });
+    let structs_match = old.structs.len() == new.structs.len()
+        && old.structs.iter().all(|(_, name, old_struct)| {
+            new.structs.get_(name).map_or(false, |new_struct| {
+                old_struct.abilities == new_struct.abilities
+                    && old_struct.type_parameters == new_struct.type_parameters
+                    && match (&old_struct.fields, &new_struct.fields) {
+                        (
+                            E::StructFields::Defined(old_fields),
+                            E::StructFields::Defined(new_fields),
+                        ) => old_fields == new_fields,
+                        (E::StructFields::Native(_), E::StructFields::Native(_)) => true,
+                        _ => false,
+                    }
+            })
+        });
+    let constants_match = old.constants.len() == new.constants.len()
+        && old.constants.iter().all(|(_, name, old_const)| {
+            new.constants.get_(name).map_or(false, |new_const| {
+                old_const.signature == new_const.signature
+            })
+        });
+    functions_match && structs_match && constants_match
+}
+
+/// Parse and expand the given sources, adding source files, documentation, and errors to the
+/// env. Returns the compiler ready to continue from expansion, the expanded program, the source
+/// files, and the hashes of dependency files, or `None` if there have been errors.
+#[allow(clippy::type_complexity)]
+fn parse_and_expand<Paths: Into<MoveSymbol>, NamedAddress: Into<MoveSymbol>>(
+    env: &mut GlobalEnv,
+    move_sources: Vec<PackagePaths<Paths, NamedAddress>>,
+    deps: Vec<PackagePaths<Paths, NamedAddress>>,
+    flags: Flags,
+) -> anyhow::Result<
+    Option<(
+        SteppedCompiler<'static, EMPTY_COMPILER>,
+        E::Program,
+        FilesSourceText,
+        BTreeSet<FileHash>,
+    )>,
+> {
+    // Step 0: exclude or add files containing only specifications, as requested by the options.
+    let (spec_files, exclude_spec_files) = env
+        .get_extension::<ModelBuilderOptions>()
+        .map(|o| (o.spec_files.clone(), o.exclude_spec_files))
+        .unwrap_or_default();
+    let mut move_sources = filter_spec_files(move_sources, exclude_spec_files)?;
+    let deps = filter_spec_files(deps, exclude_spec_files)?;
+    if !spec_files.is_empty() {
+        if move_sources.is_empty() {
+            move_sources.push(PackagePaths {
+                name: None,
+                paths: vec![],
+                named_address_map: BTreeMap::new(),
+            })
+        }
+        move_sources[0]
+            .paths
+            .extend(spec_files.iter().map(|f| MoveSymbol::from(f.as_str())));
+    }
 
     // Step 1: parse the program to get comments and a separation of targets and dependencies.
     let (files, comments_and_compiler_res) = Compiler::from_package_paths(move_sources, deps)
@@ -109,8 +762,8 @@ pub fn run_model_builder_with_options_and_compilation_flags<
             for (fhash, (fname, fsrc)) in &files {
                 env.add_source(*fhash, fname.as_str(), fsrc, /* is_dep */ false);
             }
-            add_move_lang_diagnostics(&mut env, diags);
-            return Ok(env);
+            add_move_lang_diagnostics(env, diags);
+            return Ok(None);
         }
         Ok(res) => res,
     };
@@ -125,6 +778,22 @@ pub fn run_model_builder_with_options_and_compilation_flags<
         let (fname, fsrc) = files.get(fhash).unwrap();
         env.add_source(*fhash, fname.as_str(), fsrc, dep_files.contains(fhash));
     }
+    // Record the packages the files belong to, so diagnostics can be grouped by package.
+    for def in parsed_prog
+        .source_definitions
+        .iter()
+        .chain(parsed_prog.lib_definitions.iter())
+    {
+        if let Some(package) = &def.package {
+            let file_id = env
+                .get_file_id(def.def.file_hash())
+                .expect("file name defined");
+            env.set_file_package(file_id, package.as_str());
+        }
+    }
+    if !check_spec_files(env, &files, &parsed_prog) {
+        return Ok(None);
+    }
 
     // Add any documentation comments found by the Move compiler to the env.
     for (fhash, documentation) in comment_map {
@@ -152,33 +821,116 @@ pub fn run_model_builder_with_options_and_compilation_flags<
             lib_definitions: vec![],
         }
     };
-    let (compiler, expansion_ast) = match compiler.at_parser(parsed_prog).run::<PASS_EXPANSION>() {
+    match compiler.at_parser(parsed_prog).run::<PASS_EXPANSION>() {
         Err(diags) => {
-            add_move_lang_diagnostics(&mut env, diags);
-            return Ok(env);
+            add_move_lang_diagnostics(env, diags);
+            Ok(None)
         }
-        Ok(compiler) => compiler.into_ast(),
-    };
-    // Extract the module/script closure
-    let mut visited_modules = BTreeSet::new();
-    for (_, mident, mdef) in &expansion_ast.modules {
-        let src_file_hash = mdef.loc.file_hash();
-        if !dep_files.contains(&src_file_hash) {
-            collect_related_modules_recursive(mident, &expansion_ast.modules, &mut visited_modules);
+        Ok(compiler) => {
+            let (compiler, expansion_ast) = compiler.into_ast();
+            Ok(Some((compiler, expansion_ast, files, dep_files)))
         }
     }
-    for sdef in expansion_ast.scripts.values() {
-        let src_file_hash = sdef.loc.file_hash();
-        if !dep_files.contains(&src_file_hash) {
-            for (_, mident, _neighbor) in &sdef.immediate_neighbors {
-                collect_related_modules_recursive(
-                    mident,
-                    &expansion_ast.modules,
-                    &mut visited_modules,
-                );
+}
+
+/// The extension of files which contain only specification modules.
+pub const SPEC_FILE_EXTENSION: &str = "spec.move";
+
+/// Determines whether the file contains only specification modules, based on its name.
+fn is_spec_file(file_name: &str) -> bool {
+    file_name.ends_with(&format!(".{}", SPEC_FILE_EXTENSION))
+}
+
+/// Converts the packages into their symbolic representation. If `exclude_spec_files` is set,
+/// directories are expanded into the Move files they contain, and files containing only
+/// specifications are removed.
+fn filter_spec_files<Paths: Into<MoveSymbol>, NamedAddress: Into<MoveSymbol>>(
+    packages: Vec<PackagePaths<Paths, NamedAddress>>,
+    exclude_spec_files: bool,
+) -> anyhow::Result<Vec<PackagePaths>> {
+    packages
+        .into_iter()
+        .map(|package| {
+            let mut paths: Vec<MoveSymbol> = package.paths.into_iter().map(Into::into).collect();
+            if exclude_spec_files {
+                let path_strs = paths.iter().map(|p| p.as_str()).collect::<Vec<_>>();
+                paths = find_move_filenames(&path_strs, /* keep_specified_files */ true)?
+                    .into_iter()
+                    .filter(|f| !is_spec_file(f))
+                    .map(|f| MoveSymbol::from(f.as_str()))
+                    .collect();
+            }
+            Ok(PackagePaths {
+                name: package.name,
+                paths,
+                named_address_map: package
+                    .named_address_map
+                    .into_iter()
+                    .map(|(name, addr)| (name.into(), addr))
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+/// Checks that files containing only specifications do not contain any other definitions than
+/// specification modules, reporting errors to the env. Returns false if there have been errors.
+fn check_spec_files(env: &mut GlobalEnv, files: &FilesSourceText, prog: &P::Program) -> bool {
+    let in_spec_file =
+        |loc: &move_ir_types::location::Loc| is_spec_file(files[&loc.file_hash()].0.as_str());
+    let check_module = |env: &mut GlobalEnv, m: &P::ModuleDefinition| {
+        if !m.is_spec_module && in_spec_file(&m.loc) {
+            env.error(
+                &env.to_loc(&m.name.0.loc),
+                &format!(
+                    "module `{}` not allowed in a file containing only specifications; \
+                     use `spec {}` to specify the module",
+                    m.name, m.name
+                ),
+            );
+            false
+        } else {
+            true
+        }
+    };
+    let mut ok = true;
+    for package in prog.source_definitions.iter().chain(&prog.lib_definitions) {
+        match &package.def {
+            P::Definition::Module(m) => ok &= check_module(env, m),
+            P::Definition::Address(a) => {
+                for m in &a.modules {
+                    ok &= check_module(env, m)
+                }
+            }
+            P::Definition::Script(s) => {
+                if in_spec_file(&s.loc) {
+                    env.error(
+                        &env.to_loc(&s.loc),
+                        "script not allowed in a file containing only specifications",
+                    );
+                    ok = false;
+                }
             }
         }
     }
+    ok
+}
+
+/// Compile the given sources into verified units, adding source files, documentation, and errors
+/// to the env. Returns `None` if there have been errors.
+fn compile_verified_program<Paths: Into<MoveSymbol>, NamedAddress: Into<MoveSymbol>>(
+    env: &mut GlobalEnv,
+    move_sources: Vec<PackagePaths<Paths, NamedAddress>>,
+    deps: Vec<PackagePaths<Paths, NamedAddress>>,
+    flags: Flags,
+) -> anyhow::Result<Option<VerifiedProgram>> {
+    let (compiler, expansion_ast, files, dep_files) =
+        match parse_and_expand(env, move_sources, deps, flags)? {
+            Some(res) => res,
+            None => return Ok(None),
+        };
+    // Extract the module/script closure
+    let visited_modules = collect_program_modules(&expansion_ast, &dep_files);
 
     // Step 3: selective compilation.
     let expansion_ast = {
@@ -191,14 +943,30 @@ pub fn run_model_builder_with_options_and_compilation_flags<
         });
         E::Program { modules, scripts }
     };
+    Ok(
+        compile_units(env, compiler, expansion_ast.clone()).map(|units| VerifiedProgram {
+            files,
+            units,
+            expansion_ast,
+        }),
+    )
+}
+
+/// Runs the compiler from expansion to the compiled units, which are verified. Returns `None`
+/// if there have been errors.
+fn compile_units(
+    env: &mut GlobalEnv,
+    compiler: SteppedCompiler<'static, EMPTY_COMPILER>,
+    expansion_ast: E::Program,
+) -> Option<Vec<AnnotatedCompiledUnit>> {
     // Run the compiler fully to the compiled units
     let units = match compiler
-        .at_expansion(expansion_ast.clone())
+        .at_expansion(expansion_ast)
         .run::<PASS_COMPILATION>()
     {
         Err(diags) => {
-            add_move_lang_diagnostics(&mut env, diags);
-            return Ok(env);
+            add_move_lang_diagnostics(env, diags);
+            return None;
         }
         Ok(compiler) => {
             let (units, warnings) = compiler.into_compiled_units();
@@ -206,7 +974,7 @@ pub fn run_model_builder_with_options_and_compilation_flags<
                 // NOTE: these diagnostics are just warnings. it should be feasible to continue the
                 // model building here. But before that, register the warnings to the `GlobalEnv`
                 // first so we get a chance to report these warnings as well.
-                add_move_lang_diagnostics(&mut env, warnings);
+                add_move_lang_diagnostics(env, warnings);
             }
             units
         }
@@ -214,14 +982,38 @@ pub fn run_model_builder_with_options_and_compilation_flags<
     // Check for bytecode verifier errors (there should not be any)
     let diags = compiled_unit::verify_units(&units);
     if !diags.is_empty() {
-        add_move_lang_diagnostics(&mut env, diags);
-        return Ok(env);
+        add_move_lang_diagnostics(env, diags);
+        return None;
     }
+    Some(units)
+}
 
-    // Now that it is known that the program has no errors, run the spec checker on verified units
-    // plus expanded AST. This will populate the environment including any errors.
-    run_spec_checker(&mut env, units, expansion_ast);
-    Ok(env)
+/// Collects the modules which are defined in or used by the sources (as opposed to the
+/// dependencies) of the program.
+fn collect_program_modules(
+    expansion_ast: &E::Program,
+    dep_files: &BTreeSet<FileHash>,
+) -> BTreeSet<ModuleIdent_> {
+    let mut visited_modules = BTreeSet::new();
+    for (_, mident, mdef) in &expansion_ast.modules {
+        let src_file_hash = mdef.loc.file_hash();
+        if !dep_files.contains(&src_file_hash) {
+            collect_related_modules_recursive(mident, &expansion_ast.modules, &mut visited_modules);
+        }
+    }
+    for sdef in expansion_ast.scripts.values() {
+        let src_file_hash = sdef.loc.file_hash();
+        if !dep_files.contains(&src_file_hash) {
+            for (_, mident, _neighbor) in &sdef.immediate_neighbors {
+                collect_related_modules_recursive(
+                    mident,
+                    &expansion_ast.modules,
+                    &mut visited_modules,
+                );
+            }
+        }
+    }
+    visited_modules
 }
 
 fn collect_related_modules_recursive<'a>(
@@ -244,47 +1036,173 @@ fn collect_related_modules_recursive<'a>(
 /// should appear earlier in the vector than its parents).
 pub fn run_bytecode_model_builder<'a>(
     modules: impl IntoIterator<Item = &'a CompiledModule>,
+) -> anyhow::Result<GlobalEnv> {
+    run_bytecode_model_builder_with_source_maps(modules.into_iter().map(|m| (m, None)), vec![])
+}
+
+/// Build a `GlobalEnv` from a collection of `CompiledModule`'s, as `run_bytecode_model_builder`,
+/// using the source maps given for modules to obtain locations and names of parameters and
+/// locals. `sources` are pairs of file names and contents of the source files the source maps
+/// refer to. For a module whose source file is not given, a listing of its disassembled
+/// signatures is added to the environment instead, and locations refer to this listing.
+pub fn run_bytecode_model_builder_with_source_maps<'a>(
+    modules: impl IntoIterator<Item = (&'a CompiledModule, Option<&'a SourceMap>)>,
+    sources: impl IntoIterator<Item = (String, String)>,
 ) -> anyhow::Result<GlobalEnv> {
     let mut env = GlobalEnv::new();
-    for (i, m) in modules.into_iter().enumerate() {
-        let id = m.self_id();
-        let addr = addr_to_big_uint(id.address());
-        let module_name = ModuleName::new(addr, env.symbol_pool().make(id.name().as_str()));
-        let module_id = ModuleId::new(i);
-        let mut module_data = ModuleData::stub(module_name.clone(), module_id, m.clone());
-
-        // add functions
-        for (i, def) in m.function_defs().iter().enumerate() {
-            let def_idx = FunctionDefinitionIndex(i as u16);
-            let name = m.identifier_at(m.function_handle_at(def.function).name);
-            let symbol = env.symbol_pool().make(name.as_str());
-            let fun_id = FunId::new(symbol);
-            let data = FunctionData::stub(symbol, def_idx, def.function);
-            module_data.function_data.insert(fun_id, data);
-            module_data.function_idx_to_id.insert(def_idx, fun_id);
+    for (file_name, source) in sources {
+        env.add_source(FileHash::new(&source), &file_name, &source, false);
+    }
+    for (m, source_map) in modules {
+        add_bytecode_module(&mut env, m, source_map, false)?;
+    }
+    Ok(env)
+}
+
+/// Adds a module loaded from bytecode, e.g. the bytecode of a module deployed on chain, to an
+/// environment which may already contain a module of the same name, naming the added module
+/// `name` instead. The modules the bytecode uses must be in the environment. The added module is
+/// a dependency, and its locations refer to a listing of its disassembled signatures.
+pub fn add_renamed_bytecode_module(
+    env: &mut GlobalEnv,
+    module: &CompiledModule,
+    name: &str,
+) -> anyhow::Result<ModuleId> {
+    let mut module = module.clone();
+    let ident = Identifier::new(name)?;
+    let ident_idx = match module.identifiers.iter().position(|i| i == &ident) {
+        Some(idx) => idx,
+        None => {
+            module.identifiers.push(ident);
+            module.identifiers.len() - 1
         }
+    };
+    let self_idx = module.self_module_handle_idx.0 as usize;
+    module.module_handles[self_idx].name = IdentifierIndex::new(ident_idx as u16);
 
-        // add structs
-        for (i, def) in m.struct_defs().iter().enumerate() {
-            let def_idx = StructDefinitionIndex(i as u16);
-            let name = m.identifier_at(m.struct_handle_at(def.struct_handle).name);
-            let symbol = env.symbol_pool().make(name.as_str());
-            let struct_id = StructId::new(symbol);
-            let data = env.create_move_struct_data(
-                m,
-                def_idx,
-                symbol,
-                Loc::default(),
-                Vec::default(),
-                Spec::default(),
+    let to_module_name = |env: &GlobalEnv, handle: &ModuleHandle| {
+        ModuleName::new(
+            addr_to_big_uint(module.address_identifier_at(handle.address)),
+            env.symbol_pool()
+                .make(module.identifier_at(handle.name).as_str()),
+        )
+    };
+    let module_name = to_module_name(env, module.self_handle());
+    if env.find_module(&module_name).is_some() {
+        anyhow::bail!(
+            "module `{}` already exists",
+            module_name.display_full(env.symbol_pool())
+        );
+    }
+    for handle in module.module_handles() {
+        let used_name = to_module_name(env, handle);
+        if used_name != module_name && env.find_module(&used_name).is_none() {
+            anyhow::bail!(
+                "module `{}` used by the bytecode is missing",
+                used_name.display_full(env.symbol_pool())
             );
-            module_data.struct_data.insert(struct_id, data);
-            module_data.struct_idx_to_id.insert(def_idx, struct_id);
         }
+    }
+
+    let source_map = SourceMap::dummy_from_view(
+        &BinaryIndexedView::Module(&module),
+        MoveIrLoc::new(FileHash::empty(), 0, 0),
+    )?;
+    let module_id = ModuleId::new(env.module_data.len());
+    add_bytecode_module(env, &module, Some(&source_map), true)?;
+    Ok(module_id)
+}
 
-        env.module_data.push(module_data);
+/// Adds a module to the environment, obtaining locations and names of parameters and locals from
+/// the source map, if given. If the source file the source map refers to is not in the
+/// environment, a listing of the disassembled signatures is added instead.
+fn add_bytecode_module(
+    env: &mut GlobalEnv,
+    m: &CompiledModule,
+    source_map: Option<&SourceMap>,
+    is_dep: bool,
+) -> anyhow::Result<()> {
+    let id = m.self_id();
+    let addr = addr_to_big_uint(id.address());
+    let module_name = ModuleName::new(addr, env.symbol_pool().make(id.name().as_str()));
+    let module_id = ModuleId::new(env.module_data.len());
+    let mut module_data = ModuleData::stub(module_name.clone(), module_id, m.clone());
+    if let Some(source_map) = source_map {
+        let file_hash = source_map.definition_location.file_hash();
+        module_data.source_map = if env.get_file_id(file_hash).is_some() {
+            source_map.clone()
+        } else {
+            let (listing, source_map) = disassembled_source_map(m, source_map)?;
+            let file_name = format!(
+                "0x{}::{}.mvsig",
+                id.address().short_str_lossless(),
+                id.name()
+            );
+            env.add_source(FileHash::new(&listing), &file_name, &listing, is_dep);
+            source_map
+        };
+        module_data.loc = env.to_loc(&module_data.source_map.definition_location);
     }
-    Ok(env)
+
+    // add functions
+    for (i, def) in m.function_defs().iter().enumerate() {
+        let def_idx = FunctionDefinitionIndex(i as u16);
+        let name = m.identifier_at(m.function_handle_at(def.function).name);
+        let symbol = env.symbol_pool().make(name.as_str());
+        let fun_id = FunId::new(symbol);
+        let data = match module_data.source_map.get_function_source_map(def_idx) {
+            Ok(fmap) => {
+                // The compiler records parameters as the first locals, so parameter names
+                // are looked up by local index.
+                let handle = m.function_handle_at(def.function);
+                let arg_names = (0..m.signature_at(handle.parameters).len())
+                    .map(|idx| {
+                        let (name, _) = fmap.get_parameter_or_local_name(idx as u64)?;
+                        Some(env.symbol_pool().make(&name))
+                    })
+                    .collect::<Option<Vec<_>>>()
+                    .unwrap_or_default();
+                let type_arg_names = fmap
+                    .type_parameters
+                    .iter()
+                    .map(|(name, _)| env.symbol_pool().make(name))
+                    .collect();
+                env.create_function_data(
+                    m,
+                    def_idx,
+                    symbol,
+                    env.to_loc(&fmap.definition_location),
+                    Vec::default(),
+                    arg_names,
+                    type_arg_names,
+                    Spec::default(),
+                )
+            }
+            Err(_) => FunctionData::stub(symbol, def_idx, def.function),
+        };
+        module_data.function_data.insert(fun_id, data);
+        module_data.function_idx_to_id.insert(def_idx, fun_id);
+    }
+
+    // add structs
+    for (i, def) in m.struct_defs().iter().enumerate() {
+        let def_idx = StructDefinitionIndex(i as u16);
+        let name = m.identifier_at(m.struct_handle_at(def.struct_handle).name);
+        let symbol = env.symbol_pool().make(name.as_str());
+        let struct_id = StructId::new(symbol);
+        let loc = module_data
+            .source_map
+            .get_struct_source_map(def_idx)
+            .map(|smap| env.to_loc(&smap.definition_location))
+            .unwrap_or_default();
+        let data =
+            env.create_move_struct_data(m, def_idx, symbol, loc, Vec::default(), Spec::default());
+        module_data.struct_data.insert(struct_id, data);
+        module_data.struct_idx_to_id.insert(def_idx, struct_id);
+    }
+
+    env.module_data.push(module_data);
+    Ok(())
 }
 
 fn add_move_lang_diagnostics(env: &mut GlobalEnv, diags: Diagnostics) {
@@ -427,7 +1345,7 @@ fn run_spec_checker(env: &mut GlobalEnv, units: Vec<AnnotatedCompiledUnit>, mut
     let mut builder = ModelBuilder::new(env);
     // Merge the compiled units with the expanded program, preserving the order of the compiled
     // units which is topological w.r.t. use relation.
-    let modules = units
+    let mut modules = units
         .into_iter()
         .flat_map(|unit| {
             Some(match unit {
@@ -513,36 +1431,90 @@ fn run_spec_checker(env: &mut GlobalEnv, units: Vec<AnnotatedCompiledUnit>, mut
                 }
             })
         })
-        .enumerate();
-    for (module_count, (module_id, expanded_module, compiled_module, source_map, function_infos)) in
+        .map(Some)
+        .collect::<Vec<_>>();
+    // Translate the modules level by level of the dependency graph. The modules of a level only
+    // depend on modules of lower levels, but they are still translated one after the other: the
+    // translation shares the symbol pool, node counter and diagnostics of the environment, which
+    // are not thread-safe. Translated modules are entered into the environment in their original
+    // order, which determines their ids.
+    let levels = dependency_levels(
         modules
-    {
-        let loc = builder.to_loc(&expanded_module.loc);
-        let addr_bytes = builder.resolve_address(&loc, &module_id.value.address);
-        let module_name = ModuleName::from_address_bytes_and_name(
-            addr_bytes,
-            builder
-                .env
-                .symbol_pool()
-                .make(&module_id.value.module.0.value),
-        );
-        let module_id = ModuleId::new(module_count);
-        let mut module_translator = ModuleBuilder::new(&mut builder, module_id, module_name);
-        module_translator.translate(
-            loc,
-            expanded_module,
-            compiled_module,
-            source_map,
-            function_infos,
-        );
+            .iter()
+            .flatten()
+            .map(|(module_ident, expanded_module, ..)| (module_ident, expanded_module)),
+    );
+    let mut translations = modules.iter().map(|_| None).collect::<Vec<_>>();
+    for level in levels {
+        for module_count in level {
+            let (module_id, expanded_module, compiled_module, source_map, function_infos) = modules
+                [module_count]
+                .take()
+                .expect("module translated once");
+            let loc = builder.to_loc(&expanded_module.loc);
+            let addr_bytes = builder.resolve_address(&loc, &module_id.value.address);
+            let module_name = ModuleName::from_address_bytes_and_name(
+                addr_bytes,
+                builder
+                    .env
+                    .symbol_pool()
+                    .make(&module_id.value.module.0.value),
+            );
+            let module_id = ModuleId::new(module_count);
+            let mut module_translator = ModuleBuilder::new(&mut builder, module_id, module_name);
+            translations[module_count] = Some(module_translator.translate(
+                loc,
+                expanded_module,
+                compiled_module,
+                source_map,
+                function_infos,
+            ));
+        }
+    }
+    for translation in translations.into_iter().flatten() {
+        builder.add_module(translation);
     }
     // After all specs have been processed, warn about any unused schemas.
     builder.warn_unused_schemas();
 
+    // Report uses of features which are not available in the selected language version.
+    let language_version = env
+        .get_extension::<ModelBuilderOptions>()
+        .map(|o| o.language_version)
+        .unwrap_or_default();
+    check_language_version(env, language_version);
+
+    // Check that recursive spec functions terminate, so their definitions can be used as axioms.
+    check_spec_fun_termination(env);
+
     // Apply simplification passes
     run_spec_simplifier(env);
 }
 
+/// Groups modules, given in dependency order, into levels, such that each module only depends on
+/// modules of lower levels. Returns the indices of the modules of each level.
+fn dependency_levels<'a>(
+    modules: impl Iterator<Item = (&'a ModuleIdent, &'a ModuleDefinition)>,
+) -> Vec<Vec<usize>> {
+    let mut level_of: BTreeMap<ModuleIdent_, usize> = BTreeMap::new();
+    let mut levels: Vec<Vec<usize>> = vec![];
+    for (idx, (module_ident, module_def)) in modules.enumerate() {
+        let level = module_def
+            .immediate_neighbors
+            .key_cloned_iter()
+            .filter_map(|(neighbor, _)| level_of.get(&neighbor.value))
+            .map(|level| level + 1)
+            .max()
+            .unwrap_or(0);
+        level_of.insert(module_ident.value, level);
+        if level == levels.len() {
+            levels.push(vec![]);
+        }
+        levels[level].push(idx);
+    }
+    levels
+}
+
 fn run_spec_simplifier(env: &mut GlobalEnv) {
     let options = env
         .get_extension::<ModelBuilderOptions>()
@@ -0,0 +1,322 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A framework for lints over the model.
+//!
+//! A lint implements `ModelLint`, checking the target modules of a `GlobalEnv` and reporting
+//! findings for modules and functions. Besides the built-in lints, custom lints can be made
+//! available via `register_lint`. Each lint has a level which determines how its findings are
+//! reported: `allow` drops them, `warn` reports them as warnings, and `deny` as errors. The
+//! level defaults to the one of the lint, and can be overridden for a module or function with
+//! an attribute, where the attribute of a function takes precedence over the one of its module:
+//!
+//! ```move
+//! #[lint(allow(unspecified_external_aborts), deny(unguarded_state_write))]
+//! module 0x42::M { ... }
+//! ```
+
+use crate::{
+    ast::Attribute,
+    audit_heuristics::{audit_findings, Heuristic},
+    dead_resources::{dead_resource_findings, DeadResourceKind},
+    model::{FunctionEnv, GlobalEnv, Loc, ModuleEnv},
+};
+use codespan_reporting::diagnostic::Severity;
+use once_cell::sync::Lazy;
+use std::{collections::BTreeMap, fmt, str::FromStr, sync::Mutex};
+
+/// The name of the attribute which configures lint levels.
+const LINT_ATTRIBUTE: &str = "lint";
+
+/// The level of a lint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LintLevel {
+    Allow,
+    Warn,
+    Deny,
+}
+
+/// A lint over the model.
+pub trait ModelLint {
+    /// The name of the lint, in snake case, as used in attributes.
+    fn name(&self) -> &'static str;
+
+    /// The group of the lint, e.g. `audit` or `style`. Findings are reported by group.
+    fn group(&self) -> &'static str;
+
+    /// A short description of what the lint checks.
+    fn description(&self) -> &'static str;
+
+    /// The level of the lint, unless overridden by an attribute.
+    fn default_level(&self) -> LintLevel {
+        LintLevel::Warn
+    }
+
+    /// Checks the target modules of the environment, reporting findings to the reporter.
+    fn check(&self, env: &GlobalEnv, reporter: &mut LintReporter);
+}
+
+/// A finding of a lint.
+#[derive(Debug, Clone)]
+pub struct LintFinding {
+    pub lint: &'static str,
+    pub group: &'static str,
+    pub level: LintLevel,
+    pub loc: Loc,
+    pub message: String,
+}
+
+/// Collects the findings of a lint, determining their levels.
+pub struct LintReporter<'a> {
+    lint: &'a dyn ModelLint,
+    findings: Vec<LintFinding>,
+}
+
+/// A function which creates a lint.
+pub type LintFactory = fn() -> Box<dyn ModelLint>;
+
+static REGISTERED_LINTS: Lazy<Mutex<Vec<LintFactory>>> = Lazy::new(|| Mutex::new(vec![]));
+
+impl FromStr for LintLevel {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "allow" => Ok(LintLevel::Allow),
+            "warn" => Ok(LintLevel::Warn),
+            "deny" => Ok(LintLevel::Deny),
+            _ => Err(anyhow::anyhow!("unknown lint level `{}`", s)),
+        }
+    }
+}
+
+impl fmt::Display for LintLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LintLevel::Allow => write!(f, "allow"),
+            LintLevel::Warn => write!(f, "warn"),
+            LintLevel::Deny => write!(f, "deny"),
+        }
+    }
+}
+
+impl<'a> LintReporter<'a> {
+    /// Reports a finding for the module.
+    pub fn report_module(&mut self, module_env: &ModuleEnv, loc: &Loc, message: &str) {
+        let level = lint_level(
+            module_env.env,
+            module_env.get_attributes(),
+            self.lint.name(),
+        )
+        .unwrap_or_else(|| self.lint.default_level());
+        self.add(level, loc, message)
+    }
+
+    /// Reports a finding for the function.
+    pub fn report_function(&mut self, fun_env: &FunctionEnv, loc: &Loc, message: &str) {
+        let env = fun_env.module_env.env;
+        let name = self.lint.name();
+        let level = lint_level(env, fun_env.get_attributes(), name)
+            .or_else(|| lint_level(env, fun_env.module_env.get_attributes(), name))
+            .unwrap_or_else(|| self.lint.default_level());
+        self.add(level, loc, message)
+    }
+
+    fn add(&mut self, level: LintLevel, loc: &Loc, message: &str) {
+        if level != LintLevel::Allow {
+            self.findings.push(LintFinding {
+                lint: self.lint.name(),
+                group: self.lint.group(),
+                level,
+                loc: loc.clone(),
+                message: message.to_string(),
+            })
+        }
+    }
+}
+
+/// Registers a lint created by the given factory, which is then run by `run_lints` together
+/// with the built-in lints.
+pub fn register_lint(factory: LintFactory) {
+    REGISTERED_LINTS
+        .lock()
+        .expect("lint registry")
+        .push(factory);
+}
+
+/// Returns the built-in lints followed by the registered ones.
+pub fn all_lints() -> Vec<Box<dyn ModelLint>> {
+    let mut lints: Vec<Box<dyn ModelLint>> = vec![
+        Box::new(AuditLint(Heuristic::UnguardedStateWrite)),
+        Box::new(AuditLint(Heuristic::UnspecifiedExternalAborts)),
+        Box::new(AuditLint(Heuristic::UnspecifiedArithmeticAborts)),
+        Box::new(DeadResourceLint(DeadResourceKind::UnreadResource)),
+        Box::new(DeadResourceLint(DeadResourceKind::UnpublishedResource)),
+        Box::new(DeadResourceLint(DeadResourceKind::UnreadField)),
+    ];
+    lints.extend(
+        REGISTERED_LINTS
+            .lock()
+            .expect("lint registry")
+            .iter()
+            .map(|factory| factory()),
+    );
+    lints
+}
+
+/// Runs all lints over the target modules and returns the findings which are not allowed.
+/// The findings are also added to the diagnostics of the environment, as errors for denied
+/// lints and as warnings otherwise.
+pub fn run_lints(env: &GlobalEnv) -> Vec<LintFinding> {
+    let mut findings = vec![];
+    for lint in all_lints() {
+        let mut reporter = LintReporter {
+            lint: lint.as_ref(),
+            findings: vec![],
+        };
+        lint.check(env, &mut reporter);
+        findings.extend(reporter.findings);
+    }
+    for finding in &findings {
+        let severity = if finding.level == LintLevel::Deny {
+            Severity::Error
+        } else {
+            Severity::Warning
+        };
+        env.diag(
+            severity,
+            &finding.loc,
+            &format!("[lint::{}] {}", finding.lint, finding.message),
+        );
+    }
+    findings
+}
+
+/// Returns a report of the findings, grouped by lint group and lint.
+pub fn lint_report(env: &GlobalEnv, findings: &[LintFinding]) -> String {
+    let mut groups: BTreeMap<_, BTreeMap<_, Vec<_>>> = BTreeMap::new();
+    for finding in findings {
+        groups
+            .entry(finding.group)
+            .or_default()
+            .entry(finding.lint)
+            .or_default()
+            .push(finding);
+    }
+    let mut res = String::new();
+    for (group, lints) in groups {
+        let count: usize = lints.values().map(|findings| findings.len()).sum();
+        res.push_str(&format!("{} ({} finding(s))\n", group, count));
+        for (lint, findings) in lints {
+            res.push_str(&format!("  {} ({})\n", lint, findings.len()));
+            for finding in findings {
+                res.push_str(&format!(
+                    "    [{}] {} {}\n",
+                    finding.level,
+                    finding.message,
+                    finding.loc.display(env)
+                ));
+            }
+        }
+    }
+    res
+}
+
+/// Returns the level of the lint configured by the attributes, if any.
+fn lint_level(env: &GlobalEnv, attributes: &[Attribute], lint: &str) -> Option<LintLevel> {
+    let pool = env.symbol_pool();
+    let mut res = None;
+    for attr in attributes {
+        if let Attribute::Apply(_, name, levels) = attr {
+            if pool.string(*name).as_str() != LINT_ATTRIBUTE {
+                continue;
+            }
+            for level_attr in levels {
+                if let Attribute::Apply(node_id, level, lints) = level_attr {
+                    let applies = lints.iter().any(|lint_attr| {
+                        matches!(lint_attr, Attribute::Apply(_, name, _)
+                                 if pool.string(*name).as_str() == lint)
+                    });
+                    if applies {
+                        match pool.string(*level).parse::<LintLevel>() {
+                            Ok(level) => res = Some(level),
+                            Err(err) => env.error(&env.get_node_loc(*node_id), &err.to_string()),
+                        }
+                    }
+                }
+            }
+        }
+    }
+    res
+}
+
+/// A lint reporting the findings of an audit heuristic.
+struct AuditLint(Heuristic);
+
+impl ModelLint for AuditLint {
+    fn name(&self) -> &'static str {
+        self.0.name()
+    }
+
+    fn group(&self) -> &'static str {
+        "audit"
+    }
+
+    fn description(&self) -> &'static str {
+        match self.0 {
+            Heuristic::UnguardedStateWrite => {
+                "public or script functions which write global storage without taking a signer"
+            }
+            Heuristic::UnspecifiedExternalAborts => {
+                "functions calling other modules without `aborts_if` conditions"
+            }
+            Heuristic::UnspecifiedArithmeticAborts => {
+                "functions with many arithmetic operations without `aborts_if` conditions"
+            }
+        }
+    }
+
+    fn check(&self, env: &GlobalEnv, reporter: &mut LintReporter) {
+        for finding in audit_findings(env)
+            .into_iter()
+            .filter(|finding| finding.heuristic == self.0)
+        {
+            reporter.report_function(
+                &env.get_function(finding.fun_id),
+                &finding.loc,
+                &finding.message,
+            );
+        }
+    }
+}
+
+/// A lint reporting dead resources or fields of one kind.
+struct DeadResourceLint(DeadResourceKind);
+
+impl ModelLint for DeadResourceLint {
+    fn name(&self) -> &'static str {
+        self.0.name()
+    }
+
+    fn group(&self) -> &'static str {
+        "dead_code"
+    }
+
+    fn description(&self) -> &'static str {
+        match self.0 {
+            DeadResourceKind::UnreadResource => "resources which are published but never read",
+            DeadResourceKind::UnpublishedResource => "resources which are never published",
+            DeadResourceKind::UnreadField => "fields which are written but never read",
+        }
+    }
+
+    fn check(&self, env: &GlobalEnv, reporter: &mut LintReporter) {
+        for finding in dead_resource_findings(env)
+            .into_iter()
+            .filter(|finding| finding.kind == self.0)
+        {
+            let struct_env = env.get_struct(finding.struct_id);
+            reporter.report_module(&struct_env.module_env, &finding.loc, &finding.message);
+        }
+    }
+}
@@ -0,0 +1,105 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Generation of mock modules.
+//!
+//! The mock of a module declares the same uses, friends, structs, constants, and functions as
+//! the module, together with its specifications, but the body of each function only aborts. The
+//! mocked functions are made opaque and the mock itself is not verified, so callers of the mock
+//! are verified against the specifications of the original functions, which are assumed to hold.
+//! This allows to compile and verify code against dependencies whose implementation is not
+//! available or not to be disclosed.
+//!
+//! The mock is derived from the source of the module, hence the module must have been built
+//! from source. Spec blocks inlined into function bodies are dropped along with the bodies, as
+//! are the acquires lists of functions.
+
+use crate::model::{GlobalEnv, ModuleEnv};
+use anyhow::{anyhow, bail};
+use move_command_line_common::files::FileHash;
+use move_compiler::{
+    parser::{ast::*, parse_file_string},
+    shared::{CompilationEnv, Flags, Identifier},
+};
+
+/// The body of mocked functions.
+const MOCK_BODY: &str = "{ abort 0 }";
+
+/// Returns the source of the mock of the module.
+pub fn generate_mock_module(env: &GlobalEnv, module_env: &ModuleEnv) -> anyhow::Result<String> {
+    if module_env.is_script_module() {
+        bail!("cannot mock a script");
+    }
+    let module_name = module_env.get_full_name_str();
+    let module_loc = module_env.get_loc();
+    let source = env.get_file_source(module_loc.file_id());
+    let mut compilation_env = CompilationEnv::new(Flags::empty());
+    let (defs, _) = parse_file_string(&mut compilation_env, FileHash::new(source), source)
+        .map_err(|_| anyhow!("cannot parse the source of module `{}`", module_name))?;
+    let simple_name = module_env
+        .get_name()
+        .name()
+        .display(env.symbol_pool())
+        .to_string();
+    let module_start = module_loc.span().start().to_usize();
+    let module_def = defs
+        .iter()
+        .flat_map(|def| match def {
+            Definition::Module(module) => vec![module],
+            Definition::Address(address) => address.modules.iter().collect(),
+            Definition::Script(_) => vec![],
+        })
+        .find(|module| {
+            module.name.value().as_str() == simple_name
+                && (module.loc.start() as usize..module.loc.end() as usize).contains(&module_start)
+        })
+        .ok_or_else(|| anyhow!("cannot find the source of module `{}`", module_name))?;
+
+    // The members of the module, starting with the opening brace.
+    let name_end = module_def.name.loc().end() as usize;
+    let members_start = name_end
+        + source[name_end..]
+            .find('{')
+            .ok_or_else(|| anyhow!("malformed module `{}`", module_name))?;
+    let members_end = module_def.loc.end() as usize - 1;
+
+    let mut mocked_funs = vec![];
+    let mut members = String::new();
+    let mut pos = members_start;
+    for member in &module_def.members {
+        if let ModuleMember::Function(fun) = member {
+            if let FunctionBody_::Defined(_) = &fun.body.value {
+                let (mut start, end) = (fun.body.loc.start() as usize, fun.body.loc.end() as usize);
+                // Since the mocked functions do not access global storage, their acquires lists
+                // are dropped. Other modules never refer to them.
+                if let Some(first) = fun.acquires.first() {
+                    start = source[..first.loc.start() as usize]
+                        .rfind("acquires")
+                        .unwrap_or(start);
+                }
+                members.push_str(&source[pos..start]);
+                members.push_str(MOCK_BODY);
+                pos = end;
+                mocked_funs.push(fun.name.0.value);
+            }
+        }
+    }
+    members.push_str(&source[pos..members_end]);
+    let members = members.trim_end();
+
+    let mut res = format!(
+        "// Mock of module `{}`. Functions abort and are specified by the original \
+         specifications.\n",
+        module_name
+    );
+    res.push_str(&format!("module {} {}\n\n", module_name, members));
+    res.push_str("    spec module {\n        pragma verify = false;\n    }\n");
+    for fun in mocked_funs {
+        res.push_str(&format!(
+            "\n    spec {} {{\n        pragma opaque;\n    }}\n",
+            fun
+        ));
+    }
+    res.push_str("}\n");
+    Ok(res)
+}
@@ -11,7 +11,8 @@
 //! - A `GlobalEnv` which gives access to all modules plus other information on global level,
 //!   and is the owner of all related data.
 //! - A `ModuleEnv` which is a reference to the data of some module in the environment.
-//! - A `StructEnv` which is a reference to the data of some struct in a module.
+//! - A `StructEnv` which is a reference to the data of some struct in a module. An enum is
+//!   represented as a struct with variants, each accessible via a `VariantEnv`.
 //! - A `FunctionEnv` which is a reference to the data of some function in a module.
 
 use std::{
@@ -23,9 +24,11 @@ use std::{
     rc::Rc,
 };
 
-use codespan::{ByteIndex, ByteOffset, ColumnOffset, FileId, Files, LineOffset, Location, Span};
+use codespan::{
+    ByteIndex, ByteOffset, ColumnOffset, FileId, Files, LineIndex, LineOffset, Location, Span,
+};
 use codespan_reporting::{
-    diagnostic::{Diagnostic, Label, Severity},
+    diagnostic::{Diagnostic, Label, LabelStyle, Severity},
     term::{emit, termcolor::WriteColor, Config},
 };
 use itertools::Itertools;
@@ -34,6 +37,7 @@ use log::{info, warn};
 use move_command_line_common::files::FileHash;
 use num::{BigUint, One, ToPrimitive};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 use move_binary_format::{
     access::ModuleAccess,
@@ -41,7 +45,8 @@ use move_binary_format::{
     file_format::{
         AddressIdentifierIndex, Bytecode, Constant as VMConstant, ConstantPoolIndex,
         FunctionDefinitionIndex, FunctionHandleIndex, SignatureIndex, SignatureToken,
-        StructDefinitionIndex, StructFieldInformation, StructHandleIndex, Visibility,
+        StructDefInstantiationIndex, StructDefinitionIndex, StructFieldInformation,
+        StructHandleIndex, Visibility,
     },
     normalized::Type as MType,
     views::{
@@ -61,13 +66,16 @@ use move_disassembler::disassembler::{Disassembler, DisassemblerOptions};
 
 use crate::{
     ast::{
-        ConditionKind, Exp, ExpData, GlobalInvariant, ModuleName, PropertyBag, PropertyValue, Spec,
-        SpecBlockInfo, SpecFunDecl, SpecVarDecl, Value,
+        AttributeValue, ConditionKind, Exp, ExpData, GlobalInvariant, ModuleName, Operation,
+        PropertyBag, PropertyValue, Spec, SpecBlockInfo, SpecBlockTarget, SpecFunDecl, SpecVarDecl,
+        Value,
     },
+    diag_json::JsonDiagnostic,
     pragmas::{
         DELEGATE_INVARIANTS_TO_CALLER_PRAGMA, DISABLE_INVARIANTS_IN_BODY_PRAGMA, FRIEND_PRAGMA,
         INTRINSIC_PRAGMA, OPAQUE_PRAGMA, VERIFY_PRAGMA,
     },
+    source_edit::Fix,
     symbol::{Symbol, SymbolPool},
     ty::{PrimitiveType, Type, TypeDisplayContext, TypeUnificationAdapter, Variance},
 };
@@ -75,7 +83,7 @@ use crate::{
 // import and re-expose symbols
 use crate::ast::Attribute;
 use move_binary_format::file_format::CodeOffset;
-pub use move_binary_format::file_format::{AbilitySet, Visibility as FunctionVisibility};
+pub use move_binary_format::file_format::{Ability, AbilitySet, Visibility as FunctionVisibility};
 
 // =================================================================================================
 /// # Constants
@@ -92,16 +100,24 @@ pub const GHOST_MEMORY_PREFIX: &str = "Ghost$";
 // =================================================================================================
 /// # Locations
 
-/// A location, consisting of a FileId and a span in this file.
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+/// A location, consisting of a FileId and a span in this file. A location in code which has
+/// been expanded at another place, e.g. by inlining a function, also records the site of the
+/// expansion, so diagnostics can show both.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Serialize, Deserialize)]
 pub struct Loc {
     file_id: FileId,
     span: Span,
+    #[serde(default)]
+    expanded_from: Option<Box<Loc>>,
 }
 
 impl Loc {
     pub fn new(file_id: FileId, span: Span) -> Loc {
-        Loc { file_id, span }
+        Loc {
+            file_id,
+            span,
+            expanded_from: None,
+        }
     }
 
     pub fn span(&self) -> Span {
@@ -115,10 +131,10 @@ impl Loc {
     // Delivers a location pointing to the end of this one.
     pub fn at_end(&self) -> Loc {
         if self.span.end() > ByteIndex(0) {
-            Loc::new(
-                self.file_id,
-                Span::new(self.span.end() - ByteOffset(1), self.span.end()),
-            )
+            Loc {
+                span: Span::new(self.span.end() - ByteOffset(1), self.span.end()),
+                ..self.clone()
+            }
         } else {
             self.clone()
         }
@@ -126,10 +142,46 @@ impl Loc {
 
     // Delivers a location pointing to the start of this one.
     pub fn at_start(&self) -> Loc {
-        Loc::new(
-            self.file_id,
-            Span::new(self.span.start(), self.span.start() + ByteOffset(1)),
-        )
+        Loc {
+            span: Span::new(self.span.start(), self.span.start() + ByteOffset(1)),
+            ..self.clone()
+        }
+    }
+
+    /// Returns the site of the expansion which produced the code at this location, if any.
+    pub fn expanded_from(&self) -> Option<&Loc> {
+        self.expanded_from.as_deref()
+    }
+
+    /// Returns this location as part of code expanded at the given site. If this location is
+    /// already part of expanded code, e.g. of a function which has been inlined into the one
+    /// now expanded at the site, the site is added to the end of its expansion trace.
+    pub fn with_expansion_site(&self, site: &Loc) -> Loc {
+        let expanded_from = match &self.expanded_from {
+            Some(inner) => inner.with_expansion_site(site),
+            None => site.clone(),
+        };
+        Loc {
+            expanded_from: Some(Box::new(expanded_from)),
+            ..self.clone()
+        }
+    }
+
+    /// Returns this location without the sites of expansions.
+    pub fn without_expansion(&self) -> Loc {
+        Loc::new(self.file_id, self.span)
+    }
+
+    /// Returns the sites of the expansions which produced the code at this location, from the
+    /// innermost to the outermost one.
+    pub fn expansion_trace(&self) -> Vec<Loc> {
+        let mut trace = vec![];
+        let mut current = self;
+        while let Some(site) = current.expanded_from() {
+            trace.push(site.without_expansion());
+            current = site;
+        }
+        trace
     }
 
     /// Creates a location which encloses all the locations in the provided slice,
@@ -183,55 +235,55 @@ pub type MoveIrLoc = move_ir_types::location::Loc;
 pub type RawIndex = u16;
 
 /// Identifier for a module.
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Serialize, Deserialize)]
 pub struct ModuleId(RawIndex);
 
 /// Identifier for a named constant, relative to module.
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Serialize, Deserialize)]
 pub struct NamedConstantId(Symbol);
 
 /// Identifier for a structure/resource, relative to module.
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Serialize, Deserialize)]
 pub struct StructId(Symbol);
 
 /// Identifier for a field of a structure, relative to struct.
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Serialize, Deserialize)]
 pub struct FieldId(Symbol);
 
 /// Identifier for a Move function, relative to module.
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Serialize, Deserialize)]
 pub struct FunId(Symbol);
 
 /// Identifier for a schema.
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Serialize, Deserialize)]
 pub struct SchemaId(Symbol);
 
 /// Identifier for a specification function, relative to module.
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Serialize, Deserialize)]
 pub struct SpecFunId(RawIndex);
 
 /// Identifier for a specification variable, relative to module.
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Serialize, Deserialize)]
 pub struct SpecVarId(RawIndex);
 
 /// Identifier for a node in the AST, relative to a module. This is used to associate attributes
 /// with the node, like source location and type.
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Serialize, Deserialize)]
 pub struct NodeId(usize);
 
 /// A global id. Instances of this type represent unique identifiers relative to `GlobalEnv`.
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Serialize, Deserialize)]
 pub struct GlobalId(usize);
 
 /// Some identifier qualified by a module.
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Serialize, Deserialize)]
 pub struct QualifiedId<Id> {
     pub module_id: ModuleId,
     pub id: Id,
 }
 
 /// Some identifier qualified by a module and a type instantiation.
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Serialize, Deserialize)]
 pub struct QualifiedInstId<Id> {
     pub module_id: ModuleId,
     pub inst: Vec<Type>,
@@ -399,6 +451,41 @@ impl QualifiedInstId<StructId> {
     pub fn to_type(&self) -> Type {
         Type::Struct(self.module_id, self.id, self.inst.to_owned())
     }
+
+    /// Returns this instantiation with the type arguments for phantom type parameters erased.
+    /// See `Type::erase_phantom_args`.
+    pub fn erase_phantom_args(&self, env: &GlobalEnv) -> QualifiedInstId<StructId> {
+        let ty = self.to_type().erase_phantom_args(env);
+        let (_, _, inst) = ty.require_struct();
+        self.module_id.qualified_inst(self.id, inst.to_vec())
+    }
+}
+
+/// A content-addressed identifier of a function or struct. In contrast to a `QualifiedId`,
+/// whose `ModuleId` depends on the order in which modules are loaded, a stable id is the hash
+/// of the address, module name, name, and signature of the entity, and therefore survives
+/// rebuilds of the model. This makes it suitable for referencing model entities from external
+/// databases.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub struct StableId(pub [u8; 32]);
+
+impl StableId {
+    /// Creates a stable id from the canonical description of an entity.
+    fn from_description(description: &str) -> Self {
+        Self(Sha256::digest(description.as_bytes()).into())
+    }
+}
+
+impl fmt::Display for StableId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&hex::encode(self.0))
+    }
+}
+
+impl fmt::Debug for StableId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
 }
 
 // =================================================================================================
@@ -444,6 +531,343 @@ impl VerificationScope {
     }
 }
 
+// =================================================================================================
+/// # Lenses
+
+/// The kind of a lens entry. At most one entry of each kind is attached to a location.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LensKind {
+    /// Whether the function has been verified or verification failed.
+    VerificationStatus,
+    /// The global memory written by the function.
+    WriteSet,
+    /// The number of functions calling the function.
+    Callers,
+    /// Any other information, identified by a name.
+    Other(String),
+}
+
+/// A lens entry: a short piece of information about a source location, like the verification
+/// status of a function, which editors can display inline without recomputing the analysis
+/// which produced it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LensEntry {
+    pub kind: LensKind,
+    /// A short text, suitable for an inline badge.
+    pub title: String,
+    /// An optional longer description.
+    pub detail: Option<String>,
+}
+
+impl LensEntry {
+    pub fn new(kind: LensKind, title: impl Into<String>) -> Self {
+        Self {
+            kind,
+            title: title.into(),
+            detail: None,
+        }
+    }
+
+    pub fn with_detail(self, detail: impl Into<String>) -> Self {
+        Self {
+            detail: Some(detail.into()),
+            ..self
+        }
+    }
+}
+
+// =================================================================================================
+/// # Compaction
+
+/// Statistics of a compaction of the environment, as returned by `GlobalEnv::compact`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CompactionStats {
+    /// The number of nodes whose location, type, and instantiation have been dropped.
+    pub dropped_nodes: usize,
+    /// The number of symbols whose string representation has been dropped.
+    pub dropped_symbols: usize,
+}
+
+/// The node ids and symbols reachable from the data owned by the environment.
+#[derive(Default)]
+struct LiveSet {
+    nodes: BTreeSet<NodeId>,
+    symbols: BTreeSet<Symbol>,
+}
+
+impl LiveSet {
+    fn add_module(&mut self, data: &ModuleData) {
+        self.symbols.insert(data.name.name());
+        self.add_attributes(&data.attributes);
+        for (id, const_data) in &data.named_constants {
+            self.symbols.insert(id.0);
+            self.add_type(&const_data.typ);
+        }
+        for (id, struct_data) in &data.struct_data {
+            self.symbols.insert(id.0);
+            self.add_attributes(&struct_data.attributes);
+            for (field_id, field_data) in &struct_data.field_data {
+                self.symbols.insert(field_id.0);
+                self.symbols.extend(field_data.variant);
+                if let FieldInfo::Generated { type_ } = &field_data.info {
+                    self.add_type(type_);
+                }
+            }
+            for variant in struct_data.variants.iter().flatten() {
+                self.symbols.insert(variant.name);
+                self.add_attributes(&variant.attributes);
+            }
+            self.add_spec(&struct_data.spec);
+        }
+        for (id, fun_data) in &data.function_data {
+            self.symbols.insert(id.0);
+            self.add_attributes(&fun_data.attributes);
+            self.symbols.extend(fun_data.arg_names.iter().cloned());
+            self.symbols.extend(fun_data.type_arg_names.iter().cloned());
+            self.add_spec(&fun_data.spec);
+            for call in &fun_data.method_calls {
+                self.symbols.insert(call.name);
+                for ty in &call.target.inst {
+                    self.add_type(ty);
+                }
+            }
+        }
+        for decl in data.spec_vars.values() {
+            self.symbols.insert(decl.name);
+            self.add_typed_symbols(&decl.type_params);
+            self.add_type(&decl.type_);
+            if let Some(exp) = &decl.init {
+                self.add_exp(exp);
+            }
+        }
+        for decl in data.spec_funs.values() {
+            self.symbols.insert(decl.name);
+            self.add_typed_symbols(&decl.type_params);
+            self.add_typed_symbols(&decl.params);
+            if let Some(params) = &decl.context_params {
+                self.symbols.extend(params.iter().map(|(sym, _)| *sym));
+            }
+            self.add_type(&decl.result_type);
+            if let Some(exp) = &decl.body {
+                self.add_exp(exp);
+            }
+        }
+        self.add_spec(&data.module_spec);
+        for info in &data.spec_block_infos {
+            if let SpecBlockTarget::Schema(_, id, _) = &info.target {
+                self.symbols.insert(id.0);
+            }
+        }
+    }
+
+    fn add_spec(&mut self, spec: &Spec) {
+        for cond in &spec.conditions {
+            self.add_condition_kind(&cond.kind);
+            self.add_properties(&cond.properties);
+            for exp in cond.all_exps() {
+                self.add_exp(exp);
+            }
+        }
+        self.add_properties(&spec.properties);
+        for spec in spec.on_impl.values() {
+            self.add_spec(spec);
+        }
+    }
+
+    fn add_condition_kind(&mut self, kind: &ConditionKind) {
+        use ConditionKind::*;
+        match kind {
+            LetPost(sym) | LetPre(sym) => {
+                self.symbols.insert(*sym);
+            }
+            GlobalInvariant(syms) | GlobalInvariantUpdate(syms) | Axiom(syms) => {
+                self.symbols.extend(syms.iter().cloned())
+            }
+            _ => {}
+        }
+    }
+
+    fn add_properties(&mut self, properties: &PropertyBag) {
+        for (name, value) in properties {
+            self.symbols.insert(*name);
+            match value {
+                PropertyValue::Symbol(sym) => {
+                    self.symbols.insert(*sym);
+                }
+                PropertyValue::QualifiedSymbol(qsym) => {
+                    self.symbols.insert(qsym.module_name.name());
+                    self.symbols.insert(qsym.symbol);
+                }
+                PropertyValue::Value(_) => {}
+            }
+        }
+    }
+
+    fn add_attributes(&mut self, attributes: &[Attribute]) {
+        for attr in attributes {
+            match attr {
+                Attribute::Apply(id, sym, args) => {
+                    self.nodes.insert(*id);
+                    self.symbols.insert(*sym);
+                    self.add_attributes(args);
+                }
+                Attribute::Assign(id, sym, value) => {
+                    self.nodes.insert(*id);
+                    self.symbols.insert(*sym);
+                    match value {
+                        AttributeValue::Value(id, _) => {
+                            self.nodes.insert(*id);
+                        }
+                        AttributeValue::Name(id, module_name, sym) => {
+                            self.nodes.insert(*id);
+                            self.symbols.insert(*sym);
+                            if let Some(module_name) = module_name {
+                                self.symbols.insert(module_name.name());
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn add_exp(&mut self, exp: &ExpData) {
+        exp.visit(&mut |e| {
+            use ExpData::*;
+            self.nodes.insert(e.node_id());
+            match e {
+                LocalVar(_, sym) => {
+                    self.symbols.insert(*sym);
+                }
+                Call(_, Operation::Pack(_, sid), _) => {
+                    self.symbols.insert(sid.0);
+                }
+                Call(_, Operation::Select(_, sid, fid), _)
+                | Call(_, Operation::UpdateField(_, sid, fid), _) => {
+                    self.symbols.insert(sid.0);
+                    self.symbols.insert(fid.0);
+                }
+                Lambda(_, decls, _) | Block(_, decls, _) => {
+                    for decl in decls {
+                        self.nodes.insert(decl.id);
+                        self.symbols.insert(decl.name);
+                    }
+                }
+                Quant(_, _, ranges, ..) => {
+                    for (decl, _) in ranges {
+                        self.nodes.insert(decl.id);
+                        self.symbols.insert(decl.name);
+                    }
+                }
+                _ => {}
+            }
+        });
+    }
+
+    fn add_typed_symbols(&mut self, decls: &[(Symbol, Type)]) {
+        for (sym, ty) in decls {
+            self.symbols.insert(*sym);
+            self.add_type(ty);
+        }
+    }
+
+    fn add_type(&mut self, ty: &Type) {
+        ty.visit(&mut |t| match t {
+            Type::Struct(_, sid, _) | Type::ResourceDomain(_, sid, _) => {
+                self.symbols.insert(sid.0);
+            }
+            _ => {}
+        });
+    }
+}
+
+// =================================================================================================
+/// # Persistence
+
+/// The version of the format in which `GlobalEnv::save` writes the environment. Must be
+/// increased whenever the representation of persisted model data changes, so that stale
+/// environments are rejected by `GlobalEnv::load`.
+const PERSISTED_ENV_VERSION: u32 = 2;
+
+/// A source file of a persisted environment.
+#[derive(Serialize, Deserialize)]
+struct PersistedFile {
+    hash: FileHash,
+    name: String,
+    content: String,
+    is_dep: bool,
+    package: Option<String>,
+}
+
+/// The data of an environment written by `GlobalEnv::save`. Node ids, global ids, and symbols
+/// are persisted as is, together with the counters and the symbol pool they are relative to.
+/// Source files are persisted in the order they have been added, so they receive the same
+/// `FileId` when added on load. Data which can be derived, like the index maps of modules, as
+/// well as diagnostics and extensions, are not persisted.
+#[derive(Serialize)]
+struct PersistedEnvRef<'a> {
+    version: u32,
+    files: Vec<PersistedFile>,
+    doc_comments: &'a BTreeMap<FileId, BTreeMap<ByteIndex, String>>,
+    symbol_pool: &'a SymbolPool,
+    next_free_node_id: usize,
+    exp_info: &'a BTreeMap<NodeId, ExpInfo>,
+    module_data: &'a [ModuleData],
+    global_id_counter: usize,
+    global_invariants: &'a BTreeMap<GlobalId, GlobalInvariant>,
+    used_spec_funs: &'a BTreeSet<QualifiedId<SpecFunId>>,
+    address_aliases: &'a BTreeMap<BigUint, String>,
+}
+
+/// The owned counterpart of `PersistedEnvRef`, which is read by `GlobalEnv::load`.
+#[derive(Deserialize)]
+struct PersistedEnv {
+    version: u32,
+    files: Vec<PersistedFile>,
+    doc_comments: BTreeMap<FileId, BTreeMap<ByteIndex, String>>,
+    symbol_pool: SymbolPool,
+    next_free_node_id: usize,
+    exp_info: BTreeMap<NodeId, ExpInfo>,
+    module_data: Vec<ModuleData>,
+    global_id_counter: usize,
+    global_invariants: BTreeMap<GlobalId, GlobalInvariant>,
+    used_spec_funs: BTreeSet<QualifiedId<SpecFunId>>,
+    address_aliases: BTreeMap<BigUint, String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "StructDefinitionIndex")]
+struct StructDefinitionIndexDef(u16);
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "StructHandleIndex")]
+struct StructHandleIndexDef(u16);
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "FunctionDefinitionIndex")]
+struct FunctionDefinitionIndexDef(u16);
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "FunctionHandleIndex")]
+struct FunctionHandleIndexDef(u16);
+
+/// Persists compiled modules in their binary format.
+mod compiled_module_serde {
+    use move_binary_format::CompiledModule;
+    use serde::{de::Error as _, ser::Error as _, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(module: &CompiledModule, s: S) -> Result<S::Ok, S::Error> {
+        let mut bytes = vec![];
+        module.serialize(&mut bytes).map_err(S::Error::custom)?;
+        s.serialize_bytes(&bytes)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<CompiledModule, D::Error> {
+        let bytes = Vec::<u8>::deserialize(d)?;
+        CompiledModule::deserialize(&bytes).map_err(D::Error::custom)
+    }
+}
+
 // =================================================================================================
 /// # Global Environment
 
@@ -466,6 +890,9 @@ pub struct GlobalEnv {
     file_idx_to_id: BTreeMap<u16, FileId>,
     /// A set indicating whether a file id is a target or a dependency.
     file_id_is_dep: BTreeSet<FileId>,
+    /// A mapping from file id to the name of the package the file belongs to, for files of
+    /// named packages.
+    file_id_to_package: BTreeMap<FileId, String>,
     /// A special constant location representing an unknown location.
     /// This uses a pseudo entry in `source_files` to be safely represented.
     unknown_loc: Loc,
@@ -478,6 +905,11 @@ pub struct GlobalEnv {
     /// Accumulated diagnosis. In a RefCell so we can add to it without needing a mutable GlobalEnv.
     /// The boolean indicates whether the diag was reported.
     diags: RefCell<Vec<(Diagnostic<FileId>, bool)>>,
+    /// Fixes attached to diagnostics, indexed by the position of the diagnostic in `diags`.
+    fixes: RefCell<BTreeMap<usize, Vec<Fix>>>,
+    /// Lens entries attached to locations by analyses. In a RefCell so we can add to it without
+    /// needing a mutable GlobalEnv.
+    lenses: RefCell<BTreeMap<Loc, Vec<LensEntry>>>,
     /// Pool of symbols -- internalized strings.
     symbol_pool: SymbolPool,
     /// A counter for allocating node ids.
@@ -498,6 +930,8 @@ pub struct GlobalEnv {
     pub used_spec_funs: BTreeSet<QualifiedId<SpecFunId>>,
     /// A type-indexed container for storing extension data in the environment.
     extensions: RefCell<BTreeMap<TypeId, Box<dyn Any>>>,
+    /// Human readable names of account addresses, used when displaying addresses.
+    address_aliases: RefCell<BTreeMap<BigUint, String>>,
 }
 
 /// Struct a helper type for implementing fmt::Display depending on GlobalEnv
@@ -538,7 +972,10 @@ impl GlobalEnv {
             file_id_to_idx,
             file_idx_to_id,
             file_id_is_dep: BTreeSet::new(),
+            file_id_to_package: BTreeMap::new(),
             diags: RefCell::new(vec![]),
+            fixes: Default::default(),
+            lenses: Default::default(),
             symbol_pool: SymbolPool::new(),
             next_free_node_id: Default::default(),
             exp_info: Default::default(),
@@ -548,6 +985,7 @@ impl GlobalEnv {
             global_invariants_for_memory: Default::default(),
             used_spec_funs: BTreeSet::new(),
             extensions: Default::default(),
+            address_aliases: Default::default(),
         }
     }
 
@@ -614,6 +1052,49 @@ impl GlobalEnv {
             .map(|boxed| *boxed)
     }
 
+    /// Adds an alias for an address, which is displayed as `@name` instead of the address.
+    pub fn add_address_alias(&self, addr: BigUint, name: impl Into<String>) {
+        self.address_aliases.borrow_mut().insert(addr, name.into());
+    }
+
+    /// Loads address aliases from a file. Each line of the file has the form
+    /// `name = 0x4f2b`; empty lines and lines starting with `#` are ignored.
+    pub fn load_address_aliases(&self, path: &str) -> anyhow::Result<()> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("cannot read address aliases `{}`: {}", path, e))?;
+        for (line_no, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid =
+                || anyhow::anyhow!("{}:{}: invalid address alias `{}`", path, line_no + 1, line);
+            let (name, addr) = line.split_once('=').ok_or_else(invalid)?;
+            let (name, addr) = (name.trim(), addr.trim());
+            if name.is_empty() || name.contains(char::is_whitespace) {
+                return Err(invalid());
+            }
+            let addr = BigUint::parse_bytes(addr.trim_start_matches("0x").as_bytes(), 16)
+                .ok_or_else(invalid)?;
+            self.add_address_alias(addr, name);
+        }
+        Ok(())
+    }
+
+    /// Returns the alias of an address, if one is defined.
+    pub fn get_address_alias(&self, addr: &BigUint) -> Option<String> {
+        self.address_aliases.borrow().get(addr).cloned()
+    }
+
+    /// Returns the display of an address: `@name` if the address has an alias, otherwise its
+    /// hex representation.
+    pub fn display_address(&self, addr: &BigUint) -> String {
+        match self.get_address_alias(addr) {
+            Some(name) => format!("@{}", name),
+            None => format!("0x{:x}", addr),
+        }
+    }
+
     /// Create a new global id unique to this environment.
     pub fn new_global_id(&self) -> GlobalId {
         let mut counter = self.global_id_counter.borrow_mut();
@@ -647,6 +1128,67 @@ impl GlobalEnv {
         file_id
     }
 
+    /// Associates a source file with the package it belongs to.
+    pub fn set_file_package(&mut self, file_id: FileId, package: &str) {
+        self.file_id_to_package.insert(file_id, package.to_string());
+    }
+
+    /// Returns the name of the package the source file belongs to, if it belongs to a named
+    /// package.
+    pub fn get_file_package(&self, file_id: FileId) -> Option<&str> {
+        self.file_id_to_package.get(&file_id).map(|s| s.as_str())
+    }
+
+    /// Returns the names of the packages the source files belong to.
+    pub fn get_packages(&self) -> BTreeSet<&str> {
+        self.file_id_to_package
+            .values()
+            .map(|s| s.as_str())
+            .collect()
+    }
+
+    /// Returns true if the location is in a dependency, i.e. in a source file which is not a
+    /// target of compilation.
+    pub fn is_dependency(&self, loc: &Loc) -> bool {
+        self.file_id_is_dep.contains(&loc.file_id)
+    }
+
+    /// Removes all specifications from the environment, including pragmas, spec functions and
+    /// variables, and global invariants, leaving declarations and bytecode. This reduces the
+    /// memory held by environments of tools which do not deal with specifications, like ABI
+    /// generation or compatibility checking. Since pragmas are removed as well, functions are no
+    /// longer recognized as intrinsic or opaque afterwards.
+    pub fn strip_specs(&mut self) {
+        for module_data in &mut self.module_data {
+            module_data.module_spec = Spec::default();
+            module_data.spec_vars.clear();
+            module_data.spec_funs.clear();
+            module_data.spec_block_infos.clear();
+            for struct_data in module_data.struct_data.values_mut() {
+                struct_data.spec = Spec::default();
+            }
+            for function_data in module_data.function_data.values_mut() {
+                function_data.spec = Spec::default();
+            }
+        }
+        self.global_invariants.clear();
+        self.global_invariants_for_memory.clear();
+        self.used_spec_funs.clear();
+        self.exp_info.borrow_mut().clear();
+    }
+
+    /// Removes the contents of the source files from the environment, retaining documentation
+    /// comments. Locations stay valid for identifying declarations, but cannot be resolved to
+    /// lines and columns or source text anymore, so diagnostics should be reported before.
+    pub fn strip_sources(&mut self) {
+        let kept = [self.unknown_loc.file_id, self.internal_loc.file_id];
+        for file_id in self.file_id_to_idx.keys() {
+            if !kept.contains(file_id) {
+                self.source_files.update(*file_id, String::new());
+            }
+        }
+    }
+
     /// Find all target modules and return in a vector
     pub fn get_target_modules(&self) -> Vec<ModuleEnv> {
         let mut target_modules: Vec<ModuleEnv> = vec![];
@@ -668,6 +1210,62 @@ impl GlobalEnv {
         self.diags.borrow_mut().push((diag, false));
     }
 
+    /// Adds diagnostic to the environment, together with fixes which resolve it.
+    pub fn add_diag_with_fixes(&self, diag: Diagnostic<FileId>, fixes: Vec<Fix>) {
+        let mut diags = self.diags.borrow_mut();
+        if !fixes.is_empty() {
+            self.fixes.borrow_mut().insert(diags.len(), fixes);
+        }
+        diags.push((diag, false));
+    }
+
+    /// Returns the diagnostics which have fixes attached, together with the fixes.
+    pub fn get_fixes(&self) -> Vec<(Diagnostic<FileId>, Vec<Fix>)> {
+        let diags = self.diags.borrow();
+        self.fixes
+            .borrow()
+            .iter()
+            .map(|(idx, fixes)| (diags[*idx].0.clone(), fixes.clone()))
+            .collect()
+    }
+
+    /// Returns the diagnostics of given or higher severity, whether reported or not, together
+    /// with the fixes attached to them. Duplicate diagnostics are only returned once.
+    pub fn get_diags_with_fixes(&self, severity: Severity) -> Vec<(Diagnostic<FileId>, Vec<Fix>)> {
+        let fixes = self.fixes.borrow();
+        let mut shown = BTreeSet::new();
+        self.diags
+            .borrow()
+            .iter()
+            .enumerate()
+            .filter(|(_, (d, _))| d.severity >= severity && shown.insert(format!("{:?}", d)))
+            .map(|(idx, (d, _))| (d.clone(), fixes.get(&idx).cloned().unwrap_or_default()))
+            .collect()
+    }
+
+    /// Removes the preferred fixes from the environment and returns them. A diagnostic only
+    /// contributes a fix if it is the single preferred one attached to it.
+    pub fn take_preferred_fixes(&self) -> Vec<Fix> {
+        let mut result = vec![];
+        for fixes in self.fixes.borrow_mut().values_mut() {
+            if fixes.iter().filter(|f| f.preferred).count() == 1 {
+                let pos = fixes.iter().position(|f| f.preferred).unwrap();
+                result.push(fixes.remove(pos));
+            }
+        }
+        result
+    }
+
+    /// Returns the labels of a diagnostic at the location: a primary label for the location
+    /// itself, followed by a secondary label for each site of an expansion it is part of.
+    fn labels_for(loc: &Loc) -> Vec<Label<FileId>> {
+        let mut labels = vec![Label::primary(loc.file_id, loc.span)];
+        labels.extend(loc.expansion_trace().into_iter().map(|site| {
+            Label::secondary(site.file_id, site.span).with_message("in expansion of this call")
+        }));
+        labels
+    }
+
     /// Adds an error to this environment, without notes.
     pub fn error(&self, loc: &Loc, msg: &str) {
         self.diag(Severity::Error, loc, msg)
@@ -678,11 +1276,26 @@ impl GlobalEnv {
         self.diag_with_notes(Severity::Error, loc, msg, notes)
     }
 
+    /// Adds an error to this environment, with notes and fixes.
+    pub fn error_with_notes_and_fixes(
+        &self,
+        loc: &Loc,
+        msg: &str,
+        notes: Vec<String>,
+        fixes: Vec<Fix>,
+    ) {
+        let diag = Diagnostic::new(Severity::Error)
+            .with_message(msg)
+            .with_labels(Self::labels_for(loc))
+            .with_notes(notes);
+        self.add_diag_with_fixes(diag, fixes);
+    }
+
     /// Adds a diagnostic of given severity to this environment.
     pub fn diag(&self, severity: Severity, loc: &Loc, msg: &str) {
         let diag = Diagnostic::new(severity)
             .with_message(msg)
-            .with_labels(vec![Label::primary(loc.file_id, loc.span)]);
+            .with_labels(Self::labels_for(loc));
         self.add_diag(diag);
     }
 
@@ -690,7 +1303,7 @@ impl GlobalEnv {
     pub fn diag_with_notes(&self, severity: Severity, loc: &Loc, msg: &str, notes: Vec<String>) {
         let diag = Diagnostic::new(severity)
             .with_message(msg)
-            .with_labels(vec![Label::primary(loc.file_id, loc.span)]);
+            .with_labels(Self::labels_for(loc));
         let diag = diag.with_notes(notes);
         self.add_diag(diag);
     }
@@ -705,7 +1318,7 @@ impl GlobalEnv {
     ) {
         let diag = Diagnostic::new(severity)
             .with_message(msg)
-            .with_labels(vec![Label::primary(loc.file_id, loc.span)]);
+            .with_labels(Self::labels_for(loc));
         let labels = labels
             .into_iter()
             .map(|(l, m)| Label::secondary(l.file_id, l.span).with_message(m))
@@ -714,6 +1327,43 @@ impl GlobalEnv {
         self.add_diag(diag);
     }
 
+    /// Renders the steps of an execution path as source snippets, for use in the notes of a
+    /// diagnostic. Each step is shown with the source line it starts at, followed by the
+    /// message of the step, if any. Consecutive steps on the same line are merged.
+    pub fn render_path(&self, steps: &[(Loc, Option<String>)]) -> Vec<String> {
+        let mut lines: Vec<((FileId, LineIndex), String, Option<String>)> = vec![];
+        for (loc, msg) in steps {
+            let (fname, pos) = match self.get_file_and_location(loc) {
+                Some(res) => res,
+                None => continue,
+            };
+            let key = (loc.file_id(), pos.line);
+            if let Some((last_key, _, last_msg)) = lines.last_mut() {
+                if *last_key == key {
+                    if last_msg.is_none() {
+                        *last_msg = msg.clone();
+                    }
+                    continue;
+                }
+            }
+            let source = self
+                .source_files
+                .line_span(loc.file_id(), pos.line)
+                .and_then(|span| self.source_files.source_slice(loc.file_id(), span))
+                .map(|src| src.trim())
+                .unwrap_or("??");
+            let text = format!("    {}:{}: {}", fname, pos.line + LineOffset(1), source);
+            lines.push((key, text, msg.clone()));
+        }
+        lines
+            .into_iter()
+            .map(|(_, text, msg)| match msg {
+                Some(msg) => format!("{}  // {}", text, msg),
+                None => text,
+            })
+            .collect()
+    }
+
     /// Checks whether any of the diagnostics contains string.
     pub fn has_diag(&self, pattern: &str) -> bool {
         self.diags
@@ -725,6 +1375,60 @@ impl GlobalEnv {
     /// Clear all accumulated diagnosis.
     pub fn clear_diag(&self) {
         self.diags.borrow_mut().clear();
+        self.fixes.borrow_mut().clear();
+    }
+
+    /// Attaches a lens entry to a location, replacing any entry of the same kind.
+    pub fn add_lens(&self, loc: Loc, entry: LensEntry) {
+        let mut lenses = self.lenses.borrow_mut();
+        let entries = lenses.entry(loc).or_default();
+        entries.retain(|e| e.kind != entry.kind);
+        entries.push(entry);
+    }
+
+    /// Returns the lens entries attached to the location.
+    pub fn get_lenses(&self, loc: &Loc) -> Vec<LensEntry> {
+        self.lenses.borrow().get(loc).cloned().unwrap_or_default()
+    }
+
+    /// Returns the lens entries attached to locations in the given file, ordered by location.
+    pub fn get_lenses_in_file(&self, file_id: FileId) -> Vec<(Loc, LensEntry)> {
+        self.lenses
+            .borrow()
+            .iter()
+            .filter(|(loc, _)| loc.file_id() == file_id)
+            .flat_map(|(loc, entries)| entries.iter().map(move |e| (loc.clone(), e.clone())))
+            .collect()
+    }
+
+    /// Removes all lens entries of the given kind.
+    pub fn clear_lenses(&self, kind: &LensKind) {
+        let mut lenses = self.lenses.borrow_mut();
+        for entries in lenses.values_mut() {
+            entries.retain(|e| &e.kind != kind);
+        }
+        lenses.retain(|_, entries| !entries.is_empty());
+    }
+
+    /// Attaches to each function in the target modules a lens entry with the number of its
+    /// callers.
+    pub fn add_caller_lenses(&self) {
+        for module_env in self.get_modules().filter(|m| m.is_target()) {
+            for fun_env in module_env.get_functions() {
+                let callers = fun_env.get_calling_functions();
+                let mut entry =
+                    LensEntry::new(LensKind::Callers, format!("{} caller(s)", callers.len()));
+                if !callers.is_empty() {
+                    entry = entry.with_detail(
+                        callers
+                            .iter()
+                            .map(|id| self.get_function(*id).get_full_name_str())
+                            .join(", "),
+                    );
+                }
+                self.add_lens(fun_env.get_loc(), entry);
+            }
+        }
     }
 
     /// Returns the unknown location.
@@ -753,10 +1457,7 @@ impl GlobalEnv {
                 loc.file_hash()
             )
         });
-        Loc {
-            file_id,
-            span: Span::new(loc.start(), loc.end()),
-        }
+        Loc::new(file_id, Span::new(loc.start(), loc.end()))
     }
 
     /// Returns the file id for a file name, if defined.
@@ -802,6 +1503,15 @@ impl GlobalEnv {
 
     /// Return the source text for the given location.
     pub fn get_source(&self, loc: &Loc) -> Result<&str, codespan_reporting::files::Error> {
+        // Guard against locations beyond the end of the source, e.g. after `strip_sources`,
+        // for which codespan would panic if the source is empty.
+        let len = self.source_files.source(loc.file_id).len();
+        if loc.span.end().to_usize() > len {
+            return Err(codespan_reporting::files::Error::IndexTooLarge {
+                given: loc.span.end().to_usize(),
+                max: len,
+            });
+        }
         self.source_files.source_slice(loc.file_id, loc.span)
     }
 
@@ -810,6 +1520,11 @@ impl GlobalEnv {
         self.source_files.name(file_id)
     }
 
+    /// Returns the full text of a source file.
+    pub fn get_file_source(&self, file_id: FileId) -> &str {
+        self.source_files.source(file_id)
+    }
+
     /// Return the source file names.
     pub fn get_source_file_names(&self) -> Vec<String> {
         self.file_hash_map
@@ -838,6 +1553,20 @@ impl GlobalEnv {
             .collect()
     }
 
+    /// Return the source file names together with the hashes of their content.
+    pub fn get_source_file_hashes(&self) -> BTreeMap<String, FileHash> {
+        self.file_hash_map
+            .iter()
+            .filter_map(|(hash, (k, _))| {
+                if k.eq("<internal>") || k.eq("<unknown>") {
+                    None
+                } else {
+                    Some((k.clone(), *hash))
+                }
+            })
+            .collect()
+    }
+
     // Gets the number of source files in this environment.
     pub fn get_file_count(&self) -> usize {
         self.file_hash_map.len()
@@ -875,6 +1604,38 @@ impl GlobalEnv {
         self.report_diag_with_filter(writer, |d| d.severity >= severity)
     }
 
+    /// Writes accumulated diagnostics of given or higher severity like `report_diag`, grouped
+    /// by the package of the file of their primary label. The diagnostics of each package are
+    /// preceded by a header line naming the package; diagnostics outside of named packages come
+    /// last.
+    pub fn report_diag_by_package<W: WriteColor>(&self, writer: &mut W, severity: Severity) {
+        let package_of = |diag: &Diagnostic<FileId>| {
+            diag.labels
+                .iter()
+                .find(|label| matches!(label.style, LabelStyle::Primary))
+                .and_then(|label| self.get_file_package(label.file_id))
+        };
+        let has_unreported = |package: Option<&str>| {
+            self.diags.borrow().iter().any(|(diag, reported)| {
+                !*reported && diag.severity >= severity && package_of(diag) == package
+            })
+        };
+        let packages = self.get_packages().into_iter().map(Some).chain(Some(None));
+        for package in packages {
+            if !has_unreported(package) {
+                continue;
+            }
+            let header = match package {
+                Some(name) => format!("package `{}`:", name),
+                None => "outside of packages:".to_string(),
+            };
+            writeln!(writer, "{}", header).expect("write must not fail");
+            self.report_diag_with_filter(writer, |diag| {
+                diag.severity >= severity && package_of(diag) == package
+            });
+        }
+    }
+
     /// Writes accumulated diagnostics that pass through `filter`
     pub fn report_diag_with_filter<W: WriteColor, F: Fn(&Diagnostic<FileId>) -> bool>(
         &self,
@@ -900,6 +1661,30 @@ impl GlobalEnv {
         }
     }
 
+    /// Writes accumulated diagnostics of given or higher severity as JSON, one object per line,
+    /// including the fixes attached to them. Like `report_diag`, this only writes diagnostics
+    /// which have not been reported before.
+    pub fn report_diag_json<W: std::io::Write>(
+        &self,
+        writer: &mut W,
+        severity: Severity,
+    ) -> anyhow::Result<()> {
+        let fixes = self.fixes.borrow();
+        let mut shown = BTreeSet::new();
+        for (idx, (diag, reported)) in self.diags.borrow_mut().iter_mut().enumerate() {
+            if *reported || diag.severity < severity {
+                continue;
+            }
+            if shown.insert(format!("{:?}", diag)) {
+                let diag_fixes = fixes.get(&idx).map(|f| f.as_slice()).unwrap_or(&[]);
+                let json = JsonDiagnostic::new(self, diag, diag_fixes);
+                writeln!(writer, "{}", serde_json::to_string(&json)?)?;
+            }
+            *reported = true;
+        }
+        Ok(())
+    }
+
     /// Adds a global invariant to this environment.
     pub fn add_global_invariant(&mut self, inv: GlobalInvariant) {
         let id = inv.id;
@@ -912,6 +1697,40 @@ impl GlobalEnv {
         self.global_invariants.insert(id, inv);
     }
 
+    /// Records a call in method style in the body of the given function.
+    pub fn add_method_call(&mut self, fun_id: QualifiedId<FunId>, call: MethodCall) {
+        self.module_data[fun_id.module_id.to_usize()]
+            .function_data
+            .get_mut(&fun_id.id)
+            .expect("function data")
+            .method_calls
+            .push(call);
+    }
+
+    /// Returns the calls in method style whose location is in the given file, together with
+    /// the function containing them, ordered by location.
+    pub fn get_method_calls_in_file(
+        &self,
+        file_id: FileId,
+    ) -> Vec<(QualifiedId<FunId>, &MethodCall)> {
+        self.get_modules()
+            .flat_map(|module_env| {
+                let module_id = module_env.get_id();
+                let data = module_env.data;
+                data.function_data
+                    .iter()
+                    .flat_map(move |(fun_id, fun_data)| {
+                        fun_data
+                            .method_calls
+                            .iter()
+                            .filter(move |call| call.loc.file_id() == file_id)
+                            .map(move |call| (module_id.qualified(*fun_id), call))
+                    })
+            })
+            .sorted_by(|(_, call1), (_, call2)| call1.loc.cmp(&call2.loc))
+            .collect()
+    }
+
     /// Get global invariant by id.
     pub fn get_global_invariant(&self, id: GlobalId) -> Option<&GlobalInvariant> {
         self.global_invariants.get(&id)
@@ -1033,17 +1852,6 @@ impl GlobalEnv {
             self.symbol_pool.make(module.self_id().name().as_str())
         };
         let name = ModuleName::from_str(&module.self_id().address().to_string(), effective_name);
-        let struct_idx_to_id: BTreeMap<StructDefinitionIndex, StructId> = struct_data
-            .iter()
-            .filter_map(|(id, data)| match &data.info {
-                StructInfo::Declared { def_idx, .. } => Some((*def_idx, *id)),
-                StructInfo::Generated { .. } => None,
-            })
-            .collect();
-        let function_idx_to_id: BTreeMap<FunctionDefinitionIndex, FunId> = function_data
-            .iter()
-            .map(|(id, data)| (data.def_idx, *id))
-            .collect();
         let spec_vars: BTreeMap<SpecVarId, SpecVarDecl> = spec_vars
             .into_iter()
             .enumerate()
@@ -1065,15 +1873,15 @@ impl GlobalEnv {
             .map(|(i, v)| (SpecFunId::new(i), v))
             .collect();
 
-        self.module_data.push(ModuleData {
+        let mut module_data = ModuleData {
             name,
             id: ModuleId(idx as RawIndex),
             module,
             named_constants,
             struct_data,
-            struct_idx_to_id,
+            struct_idx_to_id: BTreeMap::new(),
             function_data,
-            function_idx_to_id,
+            function_idx_to_id: BTreeMap::new(),
             spec_vars,
             spec_funs,
             module_spec,
@@ -1083,7 +1891,9 @@ impl GlobalEnv {
             spec_block_infos,
             used_modules: Default::default(),
             friend_modules: Default::default(),
-        });
+        };
+        module_data.build_index_maps();
+        self.module_data.push(module_data);
     }
 
     /// Creates data for a named constant.
@@ -1125,9 +1935,11 @@ impl GlobalEnv {
             arg_names,
             type_arg_names,
             spec,
+            method_calls: vec![],
             called_funs: Default::default(),
             calling_funs: Default::default(),
             transitive_closure_of_called_funs: Default::default(),
+            effect_class: Default::default(),
         }
     }
 
@@ -1152,7 +1964,15 @@ impl GlobalEnv {
                     .symbol_pool
                     .make(module.identifier_at(field.name).as_str());
                 let info = FieldInfo::Declared { def_idx };
-                map.insert(FieldId(name), FieldData { name, offset, info });
+                map.insert(
+                    FieldId(name),
+                    FieldData {
+                        name,
+                        offset,
+                        variant: None,
+                        info,
+                    },
+                );
             }
             map
         } else {
@@ -1168,6 +1988,7 @@ impl GlobalEnv {
             attributes,
             info,
             field_data,
+            variants: None,
             spec,
         }
     }
@@ -1197,6 +2018,7 @@ impl GlobalEnv {
             FieldData {
                 name: field_name,
                 offset: 0,
+                variant: None,
                 info: FieldInfo::Generated { type_: ty },
             },
         );
@@ -1206,6 +2028,7 @@ impl GlobalEnv {
             attributes: Default::default(),
             info: StructInfo::Generated { spec_var: var_id },
             field_data,
+            variants: None,
             spec: Spec::default(),
         }
     }
@@ -1232,6 +2055,21 @@ impl GlobalEnv {
             .find(|m| m.get_name().name() == simple_name)
     }
 
+    /// Finds a module by a name of the form `M` or `0x1::M`.
+    pub fn find_module_by_name_str(&self, name: &str) -> Option<ModuleEnv<'_>> {
+        self.get_modules().find(|m| {
+            m.get_full_name_str() == name
+                || self.symbol_pool().string(m.get_name().name()).as_str() == name
+        })
+    }
+
+    /// Finds a function by a name of the form `M::f` or `0x1::M::f`.
+    pub fn find_function_by_name_str(&self, name: &str) -> Option<FunctionEnv<'_>> {
+        let (module_name, fun_name) = name.rsplit_once("::")?;
+        self.find_module_by_name_str(module_name)?
+            .find_function(self.symbol_pool().make(fun_name))
+    }
+
     /// Find a module by its bytecode format ID
     pub fn find_module_by_language_storage_id(
         &self,
@@ -1264,6 +2102,118 @@ impl GlobalEnv {
             .flatten()
     }
 
+    /// Resolves a `StructTag`, as found in on-chain data, to the struct it refers to. Unlike
+    /// `find_struct_by_tag`, this also resolves the type arguments of the tag, and returns None
+    /// if any of them is not defined in the model or their number does not match the struct.
+    pub fn resolve_struct_tag(
+        &self,
+        tag: &language_storage::StructTag,
+    ) -> Option<QualifiedId<StructId>> {
+        self.resolve_struct_tag_inst(tag)
+            .map(|id| id.to_qualified_id())
+    }
+
+    /// Like `resolve_struct_tag`, but returns the struct together with its instantiation.
+    pub fn resolve_struct_tag_inst(
+        &self,
+        tag: &language_storage::StructTag,
+    ) -> Option<QualifiedInstId<StructId>> {
+        let id = self.find_struct_by_tag(tag)?;
+        if self.get_struct(id).get_type_parameters().len() != tag.type_params.len() {
+            return None;
+        }
+        let inst = tag
+            .type_params
+            .iter()
+            .map(|ty| self.resolve_type_tag(ty))
+            .collect::<Option<Vec<_>>>()?;
+        Some(id.instantiate(inst))
+    }
+
+    /// Resolves a `TypeTag` to a type of the model, returning None if it refers to a struct
+    /// which cannot be resolved.
+    pub fn resolve_type_tag(&self, tag: &language_storage::TypeTag) -> Option<Type> {
+        use language_storage::TypeTag;
+        Some(match tag {
+            TypeTag::Bool => Type::Primitive(PrimitiveType::Bool),
+            TypeTag::U8 => Type::Primitive(PrimitiveType::U8),
+            TypeTag::U64 => Type::Primitive(PrimitiveType::U64),
+            TypeTag::U128 => Type::Primitive(PrimitiveType::U128),
+            TypeTag::Address => Type::Primitive(PrimitiveType::Address),
+            TypeTag::Signer => Type::Primitive(PrimitiveType::Signer),
+            TypeTag::Vector(elem) => Type::Vector(Box::new(self.resolve_type_tag(elem)?)),
+            TypeTag::Struct(tag) => {
+                let id = self.resolve_struct_tag_inst(tag)?;
+                Type::Struct(id.module_id, id.id, id.inst)
+            }
+        })
+    }
+
+    /// Finds a function by its stable id.
+    pub fn find_function_by_stable_id(&self, id: StableId) -> Option<QualifiedId<FunId>> {
+        self.get_modules()
+            .flat_map(|m| m.into_functions())
+            .find(|f| f.get_stable_id() == id)
+            .map(|f| f.get_qualified_id())
+    }
+
+    /// Finds a struct by its stable id.
+    pub fn find_struct_by_stable_id(&self, id: StableId) -> Option<QualifiedId<StructId>> {
+        self.get_modules()
+            .flat_map(|m| m.into_structs())
+            .find(|s| s.get_stable_id() == id)
+            .map(|s| s.get_qualified_id())
+    }
+
+    /// Returns a representation of a type which is independent of module ids and type parameter
+    /// names, as used for computing stable ids.
+    fn stable_type_str(&self, ty: &Type) -> String {
+        let list = |tys: &[Type]| tys.iter().map(|ty| self.stable_type_str(ty)).join(", ");
+        match ty {
+            Type::Struct(mid, sid, inst) => {
+                let name = self
+                    .get_module(*mid)
+                    .into_struct(*sid)
+                    .get_full_name_with_address();
+                if inst.is_empty() {
+                    name
+                } else {
+                    format!("{}<{}>", name, list(inst))
+                }
+            }
+            Type::Vector(elem) => format!("vector<{}>", self.stable_type_str(elem)),
+            Type::Reference(is_mut, ty) => format!(
+                "&{}{}",
+                if *is_mut { "mut " } else { "" },
+                self.stable_type_str(ty)
+            ),
+            Type::Tuple(tys) => format!("({})", list(tys)),
+            Type::TypeParameter(idx) => format!("#{}", idx),
+            _ => ty.display(&self.get_type_display_ctx()).to_string(),
+        }
+    }
+
+    /// Returns a representation of type parameters which is independent of their names, as
+    /// used for computing stable ids.
+    fn stable_type_params_str(params: &[TypeParameter]) -> String {
+        params
+            .iter()
+            .enumerate()
+            .map(|(idx, param)| {
+                format!(
+                    "{}#{}: {}",
+                    if param.is_phantom() { "phantom " } else { "" },
+                    idx,
+                    param
+                        .get_abilities()
+                        .into_iter()
+                        .map(|a| format!("{:?}", a))
+                        .join(" + ")
+                )
+            })
+            .join(", ")
+    }
+
     /// Return the module enclosing this location.
     pub fn get_enclosing_module(&self, loc: &Loc) -> Option<ModuleEnv<'_>> {
         for data in &self.module_data {
@@ -1533,6 +2483,141 @@ impl GlobalEnv {
             .and_then(|info| info.instantiation.clone())
     }
 
+    /// Compacts the environment by dropping the information of nodes and the string
+    /// representation of symbols which are no longer reachable from the modules and global
+    /// invariants in the environment, e.g. after module data has been replaced. This keeps
+    /// long-running processes like language servers, which rebuild modules on each edit, from
+    /// growing without bound.
+    ///
+    /// Only data owned by the environment is considered reachable. Node ids and symbols held
+    /// elsewhere, e.g. in function targets or environment extensions, must be passed as
+    /// `extra_nodes` and `extra_symbols` if they are still used after compaction; types of
+    /// reachable nodes are kept including the symbols they refer to.
+    pub fn compact(
+        &self,
+        extra_nodes: impl IntoIterator<Item = NodeId>,
+        extra_symbols: impl IntoIterator<Item = Symbol>,
+    ) -> CompactionStats {
+        let mut live = LiveSet::default();
+        for data in &self.module_data {
+            live.add_module(data);
+        }
+        for inv in self.global_invariants.values() {
+            live.add_condition_kind(&inv.kind);
+            live.add_properties(&inv.properties);
+            live.add_exp(&inv.cond);
+        }
+        for mem in self.global_invariants_for_memory.keys() {
+            live.symbols.insert(mem.id.0);
+            for ty in &mem.inst {
+                live.add_type(ty);
+            }
+        }
+        live.nodes.extend(extra_nodes);
+        live.symbols.extend(extra_symbols);
+
+        let mut exp_info = self.exp_info.borrow_mut();
+        let before = exp_info.len();
+        exp_info.retain(|id, _| live.nodes.contains(id));
+        let dropped_nodes = before - exp_info.len();
+        for info in exp_info.values() {
+            live.add_type(&info.ty);
+            for ty in info.instantiation.iter().flatten() {
+                live.add_type(ty);
+            }
+        }
+        let dropped_symbols = self.symbol_pool.retain(&live.symbols);
+        CompactionStats {
+            dropped_nodes,
+            dropped_symbols,
+        }
+    }
+
+    /// Writes the environment to a file, so it can be restored with `GlobalEnv::load` instead
+    /// of being built again from sources. Sources, modules, specs, global invariants, and the
+    /// symbol pool are persisted. Diagnostics, lenses, and extension data are not, so tools
+    /// need to set their extensions again after loading. An environment with errors cannot be
+    /// saved.
+    pub fn save(&self, path: &str) -> anyhow::Result<()> {
+        if self.has_errors() {
+            anyhow::bail!("cannot save an environment with errors")
+        }
+        let files = self
+            .file_idx_to_id
+            .values()
+            .map(|file_id| {
+                let (hash, _) = self
+                    .file_hash_map
+                    .iter()
+                    .find(|(_, (_, id))| id == file_id)
+                    .expect("file hash");
+                PersistedFile {
+                    hash: *hash,
+                    name: self.get_file(*file_id).to_string_lossy().to_string(),
+                    content: self.get_file_source(*file_id).to_string(),
+                    is_dep: self.file_id_is_dep.contains(file_id),
+                    package: self.file_id_to_package.get(file_id).cloned(),
+                }
+            })
+            .collect();
+        let exp_info = self.exp_info.borrow();
+        let address_aliases = self.address_aliases.borrow();
+        let bytes = bcs::to_bytes(&PersistedEnvRef {
+            version: PERSISTED_ENV_VERSION,
+            files,
+            doc_comments: &self.doc_comments,
+            symbol_pool: &self.symbol_pool,
+            next_free_node_id: *self.next_free_node_id.borrow(),
+            exp_info: &exp_info,
+            module_data: &self.module_data,
+            global_id_counter: *self.global_id_counter.borrow(),
+            global_invariants: &self.global_invariants,
+            used_spec_funs: &self.used_spec_funs,
+            address_aliases: &address_aliases,
+        })?;
+        std::fs::write(path, bytes).map_err(|e| anyhow::anyhow!("cannot write `{}`: {}", path, e))
+    }
+
+    /// Reads an environment written by `GlobalEnv::save`.
+    pub fn load(path: &str) -> anyhow::Result<GlobalEnv> {
+        let bytes =
+            std::fs::read(path).map_err(|e| anyhow::anyhow!("cannot read `{}`: {}", path, e))?;
+        let data: PersistedEnv = bcs::from_bytes(&bytes)
+            .map_err(|e| anyhow::anyhow!("cannot load environment from `{}`: {}", path, e))?;
+        if data.version != PERSISTED_ENV_VERSION {
+            anyhow::bail!(
+                "`{}` has been saved in format version {}, expected version {}",
+                path,
+                data.version,
+                PERSISTED_ENV_VERSION
+            )
+        }
+        let mut env = GlobalEnv::new();
+        // The files for the special locations are added by `GlobalEnv::new` already.
+        let builtin_files = env.file_id_to_idx.len();
+        for file in data.files.into_iter().skip(builtin_files) {
+            let file_id = env.add_source(file.hash, &file.name, &file.content, file.is_dep);
+            if let Some(package) = &file.package {
+                env.set_file_package(file_id, package);
+            }
+        }
+        env.doc_comments = data.doc_comments;
+        env.symbol_pool = data.symbol_pool;
+        *env.next_free_node_id.borrow_mut() = data.next_free_node_id;
+        *env.exp_info.borrow_mut() = data.exp_info;
+        for mut module_data in data.module_data {
+            module_data.build_index_maps();
+            env.module_data.push(module_data);
+        }
+        *env.global_id_counter.borrow_mut() = data.global_id_counter;
+        for inv in data.global_invariants.into_values() {
+            env.add_global_invariant(inv);
+        }
+        env.used_spec_funs = data.used_spec_funs;
+        *env.address_aliases.borrow_mut() = data.address_aliases;
+        Ok(env)
+    }
+
     /// Return the total number of declared functions in the modules of `self`
     pub fn get_declared_function_count(&self) -> usize {
         let mut total = 0;
@@ -1650,7 +2735,7 @@ impl Default for GlobalEnv {
 /// # Module Environment
 
 /// Represents data for a module.
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ModuleData {
     /// Module name.
     pub name: ModuleName,
@@ -1662,6 +2747,7 @@ pub struct ModuleData {
     attributes: Vec<Attribute>,
 
     /// Module byte code.
+    #[serde(with = "compiled_module_serde")]
     pub module: CompiledModule,
 
     /// Named constant data
@@ -1671,12 +2757,14 @@ pub struct ModuleData {
     pub struct_data: BTreeMap<StructId, StructData>,
 
     /// Mapping from struct definition index to id in above map.
+    #[serde(skip)]
     pub struct_idx_to_id: BTreeMap<StructDefinitionIndex, StructId>,
 
     /// Function data.
     pub function_data: BTreeMap<FunId, FunctionData>,
 
     /// Mapping from function definition index to id in above map.
+    #[serde(skip)]
     pub function_idx_to_id: BTreeMap<FunctionDefinitionIndex, FunId>,
 
     /// Specification variables, in SpecVarId order.
@@ -1698,9 +2786,11 @@ pub struct ModuleData {
     pub spec_block_infos: Vec<SpecBlockInfo>,
 
     /// A cache for the modules used by this one.
+    #[serde(skip)]
     used_modules: RefCell<BTreeMap<bool, BTreeSet<ModuleId>>>,
 
     /// A cache for the modules declared as friends by this one.
+    #[serde(skip)]
     friend_modules: RefCell<Option<BTreeSet<ModuleId>>>,
 }
 
@@ -1727,6 +2817,23 @@ impl ModuleData {
             friend_modules: Default::default(),
         }
     }
+
+    /// Builds the mappings from definition indices to the ids of structs and functions.
+    fn build_index_maps(&mut self) {
+        self.struct_idx_to_id = self
+            .struct_data
+            .iter()
+            .filter_map(|(id, data)| match &data.info {
+                StructInfo::Declared { def_idx, .. } => Some((*def_idx, *id)),
+                StructInfo::Generated { .. } => None,
+            })
+            .collect();
+        self.function_idx_to_id = self
+            .function_data
+            .iter()
+            .map(|(id, data)| (data.def_idx, *id))
+            .collect();
+    }
 }
 
 /// Represents a module environment.
@@ -1788,6 +2895,12 @@ impl<'env> ModuleEnv<'env> {
         !self.env.file_id_is_dep.contains(&file_id)
     }
 
+    /// Returns the name of the package this module belongs to, if it is defined in a named
+    /// package.
+    pub fn get_package_name(&self) -> Option<&'env str> {
+        self.env.get_file_package(self.data.loc.file_id)
+    }
+
     /// Returns the path to source file of this module.
     pub fn get_source_path(&self) -> &OsStr {
         let file_id = self.data.loc.file_id;
@@ -1928,6 +3041,11 @@ impl<'env> ModuleEnv<'env> {
         &self.data.module
     }
 
+    /// Gets the source map of the bytecode module.
+    pub fn get_source_map(&'env self) -> &'env SourceMap {
+        &self.data.source_map
+    }
+
     /// Gets a `NamedConstantEnv` in this module by name
     pub fn find_named_constant(&'env self, name: Symbol) -> Option<NamedConstantEnv<'env>> {
         let id = NamedConstantId(name);
@@ -2319,7 +3437,7 @@ impl<'env> ModuleEnv<'env> {
 // =================================================================================================
 /// # Struct Environment
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct StructData {
     /// The name of this struct.
     name: Symbol,
@@ -2334,21 +3452,27 @@ pub struct StructData {
     /// Information about this struct.
     info: StructInfo,
 
-    /// Field definitions.
+    /// Field definitions. For an enum, these are the fields of all variants.
     field_data: BTreeMap<FieldId, FieldData>,
 
+    /// The variants of this struct if it is an enum, in declaration order.
+    #[serde(default)]
+    variants: Option<Vec<VariantData>>,
+
     // Associated specification.
     spec: Spec,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 enum StructInfo {
     /// Struct is declared in Move and info found in VM format.
     Declared {
         /// The definition index of this struct in its module.
+        #[serde(with = "StructDefinitionIndexDef")]
         def_idx: StructDefinitionIndex,
 
         /// The handle index of this struct in its module.
+        #[serde(with = "StructHandleIndexDef")]
         handle_idx: StructHandleIndex,
     },
     /// Struct is generated by the prover.
@@ -2440,6 +3564,20 @@ impl<'env> StructEnv<'env> {
         self.module_env.get_id().qualified(self.get_id())
     }
 
+    /// Gets the stable id of this struct, which is computed from the address, module, name,
+    /// type parameters, and abilities of the struct. The fields are not part of the id.
+    pub fn get_stable_id(&self) -> StableId {
+        StableId::from_description(&format!(
+            "struct {}<{}> has {}",
+            self.get_full_name_with_address(),
+            GlobalEnv::stable_type_params_str(&self.get_type_parameters()),
+            self.get_abilities()
+                .into_iter()
+                .map(|a| format!("{:?}", a))
+                .join(" + ")
+        ))
+    }
+
     /// Determines whether this struct is native.
     pub fn is_native(&self) -> bool {
         match &self.data.info {
@@ -2576,7 +3714,10 @@ impl<'env> StructEnv<'env> {
                     .map(|(i, k)| {
                         TypeParameter(
                             pool.make(&format!("$tv{}", i)),
-                            AbilityConstraint(k.constraints),
+                            TypeParameterKind {
+                                abilities: k.constraints,
+                                is_phantom: k.is_phantom,
+                            },
                         )
                     })
                     .collect_vec()
@@ -2586,7 +3727,7 @@ impl<'env> StructEnv<'env> {
                 var_decl
                     .type_params
                     .iter()
-                    .map(|(n, _)| TypeParameter(*n, AbilityConstraint(AbilitySet::ALL)))
+                    .map(|(n, _)| TypeParameter(*n, TypeParameterKind::new(AbilitySet::ALL)))
                     .collect()
             }
         }
@@ -2615,7 +3756,10 @@ impl<'env> StructEnv<'env> {
                             .unwrap_or_else(|| format!("unknown#{}", i));
                         TypeParameter(
                             self.module_env.env.symbol_pool.make(&name),
-                            AbilityConstraint(k.constraints),
+                            TypeParameterKind {
+                                abilities: k.constraints,
+                                is_phantom: k.is_phantom,
+                            },
                         )
                     })
                     .collect_vec()
@@ -2634,6 +3778,39 @@ impl<'env> StructEnv<'env> {
         &self.data.spec
     }
 
+    /// Returns true if this struct is an enum, i.e. has variants.
+    pub fn has_variants(&self) -> bool {
+        self.data.variants.is_some()
+    }
+
+    /// Gets the variants of this struct in declaration order, which is the order of their tags.
+    /// This is empty if the struct is not an enum.
+    pub fn get_variants(&'env self) -> impl Iterator<Item = VariantEnv<'env>> {
+        self.data
+            .variants
+            .iter()
+            .flatten()
+            .map(move |data| VariantEnv {
+                struct_env: self.clone(),
+                data,
+            })
+    }
+
+    /// Finds a variant by its name.
+    pub fn find_variant(&'env self, name: Symbol) -> Option<VariantEnv<'env>> {
+        self.get_variants()
+            .find(|variant| variant.get_name() == name)
+    }
+
+    /// Gets the fields of the given variant, ordered by offset.
+    pub fn get_fields_of_variant(
+        &'env self,
+        variant: Symbol,
+    ) -> impl Iterator<Item = FieldEnv<'env>> {
+        self.get_fields()
+            .filter(move |field| field.data.variant == Some(variant))
+    }
+
     /// Returns the value of a boolean pragma for this struct. This first looks up a
     /// pragma in this struct, then the enclosing module, and finally uses the provided default.
     /// value
@@ -2657,23 +3834,29 @@ impl<'env> StructEnv<'env> {
 // =================================================================================================
 /// # Field Environment
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct FieldData {
     /// The name of this field.
     name: Symbol,
 
-    /// The offset of this field.
+    /// The offset of this field. For a field of an enum variant, this is the offset within the
+    /// variant.
     offset: usize,
 
+    /// The variant this field belongs to, if the struct is an enum.
+    #[serde(default)]
+    variant: Option<Symbol>,
+
     /// More information about this field
     info: FieldInfo,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 enum FieldInfo {
     /// The field is declared in Move.
     Declared {
         /// The struct definition index of this field in its VM module.
+        #[serde(with = "StructDefinitionIndexDef")]
         def_idx: StructDefinitionIndex,
     },
     /// The field is generated by the prover.
@@ -2716,6 +3899,19 @@ impl<'env> FieldEnv<'env> {
         }
     }
 
+    /// Gets the location of this field, or the one of its struct if it has none.
+    pub fn get_loc(&self) -> Loc {
+        if let FieldInfo::Declared { def_idx } = &self.data.info {
+            let module_env = &self.struct_env.module_env;
+            if let Ok(smap) = module_env.data.source_map.get_struct_source_map(*def_idx) {
+                if let Some(loc) = smap.fields.get(self.data.offset) {
+                    return module_env.env.to_loc(loc);
+                }
+            }
+        }
+        self.struct_env.get_loc()
+    }
+
     /// Get documentation associated with this field.
     pub fn get_doc(&self) -> &str {
         if let FieldInfo::Declared { def_idx } = &self.data.info {
@@ -2766,12 +3962,74 @@ impl<'env> FieldEnv<'env> {
     pub fn get_offset(&self) -> usize {
         self.data.offset
     }
+
+    /// Gets the variant this field belongs to, if the struct is an enum.
+    pub fn get_variant(&self) -> Option<Symbol> {
+        self.data.variant
+    }
 }
 
 // =================================================================================================
-/// # Named Constant Environment
+/// # Variant Environment
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VariantData {
+    /// The name of this variant.
+    name: Symbol,
+
+    /// The location of this variant.
+    loc: Loc,
+
+    /// Attributes attached to this variant.
+    attributes: Vec<Attribute>,
+}
 
 #[derive(Debug)]
+pub struct VariantEnv<'env> {
+    /// Reference to enclosing struct.
+    pub struct_env: StructEnv<'env>,
+
+    /// Reference to the variant data.
+    data: &'env VariantData,
+}
+
+impl<'env> VariantEnv<'env> {
+    /// Gets the name of this variant.
+    pub fn get_name(&self) -> Symbol {
+        self.data.name
+    }
+
+    /// Gets the location of this variant.
+    pub fn get_loc(&self) -> Loc {
+        self.data.loc.clone()
+    }
+
+    /// Gets the attributes of this variant.
+    pub fn get_attributes(&self) -> &[Attribute] {
+        &self.data.attributes
+    }
+
+    /// Gets the tag of this variant, which is its position in the declaration of the enum.
+    pub fn get_tag(&self) -> usize {
+        self.struct_env
+            .data
+            .variants
+            .iter()
+            .flatten()
+            .position(|data| data.name == self.data.name)
+            .expect("variant of enum")
+    }
+
+    /// Gets the documentation of this variant.
+    pub fn get_doc(&self) -> &str {
+        self.struct_env.module_env.env.get_doc(&self.data.loc)
+    }
+}
+
+// =================================================================================================
+/// # Named Constant Environment
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct NamedConstantData {
     /// The name of this constant
     name: Symbol,
@@ -2830,17 +4088,109 @@ impl<'env> NamedConstantEnv<'env> {
 /// # Function Environment
 
 /// Represents a type parameter.
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
-pub struct TypeParameter(pub Symbol, pub AbilityConstraint);
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct TypeParameter(pub Symbol, pub TypeParameterKind);
+
+impl TypeParameter {
+    /// Returns the name of this type parameter.
+    pub fn get_name(&self) -> Symbol {
+        self.0
+    }
+
+    /// Returns the abilities an instantiation of this type parameter must have.
+    pub fn get_abilities(&self) -> AbilitySet {
+        self.1.abilities
+    }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-pub struct AbilityConstraint(pub AbilitySet);
+    /// Returns true if instantiations of this type parameter are required to have the ability.
+    pub fn has_ability(&self, ability: Ability) -> bool {
+        self.1.abilities.has_ability(ability)
+    }
+
+    /// Returns true if this type parameter is declared as phantom. Only type parameters of
+    /// structs can be phantom.
+    pub fn is_phantom(&self) -> bool {
+        self.1.is_phantom
+    }
+}
+
+/// Represents the constraints on a type parameter: the abilities required from an instantiation
+/// and whether the parameter is declared as phantom.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct TypeParameterKind {
+    pub abilities: AbilitySet,
+    pub is_phantom: bool,
+}
+
+impl TypeParameterKind {
+    /// Creates the kind of a non-phantom type parameter with the given abilities.
+    pub fn new(abilities: AbilitySet) -> Self {
+        Self {
+            abilities,
+            is_phantom: false,
+        }
+    }
+}
 
 /// Represents a parameter.
 #[derive(Debug, Clone)]
 pub struct Parameter(pub Symbol, pub Type);
 
-#[derive(Debug)]
+/// The effect of a function on global storage, including the effects of the functions it
+/// transitively calls. Mutations through reference parameters are not considered an effect on
+/// global storage; see `FunctionEnv::is_mutating` for those.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FunctionEffectClass {
+    /// The function does not access global storage.
+    Pure,
+    /// The function checks the existence of or immutably borrows the given resources, but does
+    /// not write any.
+    ReadOnly {
+        reads: BTreeSet<QualifiedId<StructId>>,
+    },
+    /// The function moves, moves from, or mutably borrows the given written resources.
+    StateMutating {
+        reads: BTreeSet<QualifiedId<StructId>>,
+        writes: BTreeSet<QualifiedId<StructId>>,
+    },
+}
+
+impl FunctionEffectClass {
+    /// Returns true if the function does not write global storage, i.e. is a view function.
+    pub fn is_view(&self) -> bool {
+        !matches!(self, FunctionEffectClass::StateMutating { .. })
+    }
+}
+
+/// The borrow applied to the receiver of a method call to pass it as the first argument of the
+/// function the call resolves to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum AutoBorrow {
+    /// The receiver is passed as is.
+    None,
+    /// The receiver is borrowed immutably, as in `&x`.
+    Immutable,
+    /// The receiver is borrowed mutably, as in `&mut x`.
+    Mutable,
+    /// The receiver is a mutable reference which is frozen to an immutable one.
+    Freeze,
+}
+
+/// A call in method style, `x.f(..)`, resolved to the function it calls. The call is a plain
+/// call in the bytecode; this information lets tools relate it to the source.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MethodCall {
+    /// The location of the call.
+    pub loc: Loc,
+    /// The name of the method as written at the call.
+    pub name: Symbol,
+    /// The function the call resolves to, instantiated with the type arguments of the call.
+    pub target: QualifiedInstId<FunId>,
+    /// The borrow applied to the receiver.
+    pub auto_borrow: AutoBorrow,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 #[allow(dead_code)]
 pub struct FunctionData {
     /// Name of this function.
@@ -2850,9 +4200,11 @@ pub struct FunctionData {
     loc: Loc,
 
     /// The definition index of this function in its module.
+    #[serde(with = "FunctionDefinitionIndexDef")]
     def_idx: FunctionDefinitionIndex,
 
     /// The handle index of this function in its module.
+    #[serde(with = "FunctionHandleIndexDef")]
     handle_idx: FunctionHandleIndex,
 
     /// Attributes attached to this function.
@@ -2868,14 +4220,25 @@ pub struct FunctionData {
     /// Specification associated with this function.
     spec: Spec,
 
+    /// The calls in method style in the body of this function, as resolved by the front end.
+    #[serde(default)]
+    method_calls: Vec<MethodCall>,
+
     /// A cache for the called functions.
+    #[serde(skip)]
     called_funs: RefCell<Option<BTreeSet<QualifiedId<FunId>>>>,
 
     /// A cache for the calling functions.
+    #[serde(skip)]
     calling_funs: RefCell<Option<BTreeSet<QualifiedId<FunId>>>>,
 
     /// A cache for the transitive closure of the called functions.
+    #[serde(skip)]
     transitive_closure_of_called_funs: RefCell<Option<BTreeSet<QualifiedId<FunId>>>>,
+
+    /// A cache for the effect class.
+    #[serde(skip)]
+    effect_class: RefCell<Option<FunctionEffectClass>>,
 }
 
 impl FunctionData {
@@ -2893,9 +4256,11 @@ impl FunctionData {
             arg_names: vec![],
             type_arg_names: vec![],
             spec: Spec::default(),
+            method_calls: vec![],
             called_funs: Default::default(),
             calling_funs: Default::default(),
             transitive_closure_of_called_funs: Default::default(),
+            effect_class: Default::default(),
         }
     }
 }
@@ -2941,6 +4306,41 @@ impl<'env> FunctionEnv<'env> {
         self.module_env.get_id().qualified(self.get_id())
     }
 
+    /// Gets the stable id of this function, which is computed from the address, module, name,
+    /// and signature of the function.
+    pub fn get_stable_id(&self) -> StableId {
+        let env = self.module_env.env;
+        StableId::from_description(&format!(
+            "fun {}::{}<{}>({}): ({})",
+            self.module_env.get_full_name_str(),
+            self.get_name().display(self.symbol_pool()),
+            GlobalEnv::stable_type_params_str(&self.get_type_parameters()),
+            self.get_parameter_types()
+                .iter()
+                .map(|ty| env.stable_type_str(ty))
+                .join(", "),
+            self.get_return_types()
+                .iter()
+                .map(|ty| env.stable_type_str(ty))
+                .join(", "),
+        ))
+    }
+
+    /// Gets a hash of the specification of this function, computed from the source of its
+    /// conditions. Two runs which report the same hash for a function have seen the same
+    /// specification, so a spec assumed in one run can be discharged by the other.
+    pub fn get_spec_hash(&self) -> StableId {
+        let env = self.module_env.env;
+        StableId::from_description(
+            &self
+                .get_spec()
+                .conditions
+                .iter()
+                .map(|cond| env.get_source(&cond.loc).unwrap_or(""))
+                .join("\n"),
+        )
+    }
+
     /// Get documentation associated with this function.
     pub fn get_doc(&self) -> &str {
         self.module_env.env.get_doc(&self.data.loc)
@@ -3173,6 +4573,135 @@ impl<'env> FunctionEnv<'env> {
             .any(|Parameter(_, ty)| ty.is_mutable_reference())
     }
 
+    /// Returns the effect class of this function. Native functions are assumed not to access
+    /// global storage.
+    pub fn get_effect_class(&self) -> FunctionEffectClass {
+        if let Some(class) = &*self.data.effect_class.borrow() {
+            return class.clone();
+        }
+        let (mut reads, mut writes) = self.get_direct_global_accesses();
+        for fun_id in self.get_transitive_closure_of_called_functions() {
+            let (callee_reads, callee_writes) = self
+                .module_env
+                .env
+                .get_function(fun_id)
+                .get_direct_global_accesses();
+            reads.extend(callee_reads);
+            writes.extend(callee_writes);
+        }
+        let class = if !writes.is_empty() {
+            FunctionEffectClass::StateMutating { reads, writes }
+        } else if !reads.is_empty() {
+            FunctionEffectClass::ReadOnly { reads }
+        } else {
+            FunctionEffectClass::Pure
+        };
+        *self.data.effect_class.borrow_mut() = Some(class.clone());
+        class
+    }
+
+    /// Returns the resources this function, or a function it transitively calls, may publish
+    /// with `move_to` and remove with `move_from`, respectively. Both are included in the writes
+    /// of the effect class.
+    pub fn get_moved_resources(
+        &self,
+    ) -> (
+        BTreeSet<QualifiedId<StructId>>,
+        BTreeSet<QualifiedId<StructId>>,
+    ) {
+        let (mut moved_to, mut moved_from) = self.get_direct_global_moves();
+        for fun_id in self.get_transitive_closure_of_called_functions() {
+            let (callee_moved_to, callee_moved_from) = self
+                .module_env
+                .env
+                .get_function(fun_id)
+                .get_direct_global_moves();
+            moved_to.extend(callee_moved_to);
+            moved_from.extend(callee_moved_from);
+        }
+        (moved_to, moved_from)
+    }
+
+    /// Returns the resources published and removed by the global storage operations of this
+    /// function, not including the functions it calls.
+    fn get_direct_global_moves(
+        &self,
+    ) -> (
+        BTreeSet<QualifiedId<StructId>>,
+        BTreeSet<QualifiedId<StructId>>,
+    ) {
+        let module = &self.module_env.data.module;
+        let resource = |idx: StructDefinitionIndex| {
+            self.module_env
+                .get_id()
+                .qualified(self.module_env.get_struct_id(idx))
+        };
+        let resource_inst =
+            |idx: StructDefInstantiationIndex| resource(module.struct_instantiation_at(idx).def);
+        let mut moved_to = BTreeSet::new();
+        let mut moved_from = BTreeSet::new();
+        for bc in self.get_bytecode() {
+            match bc {
+                Bytecode::MoveTo(idx) => {
+                    moved_to.insert(resource(*idx));
+                }
+                Bytecode::MoveToGeneric(idx) => {
+                    moved_to.insert(resource_inst(*idx));
+                }
+                Bytecode::MoveFrom(idx) => {
+                    moved_from.insert(resource(*idx));
+                }
+                Bytecode::MoveFromGeneric(idx) => {
+                    moved_from.insert(resource_inst(*idx));
+                }
+                _ => {}
+            }
+        }
+        (moved_to, moved_from)
+    }
+
+    /// Returns the resources read and written by the global storage operations of this
+    /// function, not including the functions it calls.
+    fn get_direct_global_accesses(
+        &self,
+    ) -> (
+        BTreeSet<QualifiedId<StructId>>,
+        BTreeSet<QualifiedId<StructId>>,
+    ) {
+        let module = &self.module_env.data.module;
+        let resource = |idx: StructDefinitionIndex| {
+            self.module_env
+                .get_id()
+                .qualified(self.module_env.get_struct_id(idx))
+        };
+        let resource_inst =
+            |idx: StructDefInstantiationIndex| resource(module.struct_instantiation_at(idx).def);
+        let mut reads = BTreeSet::new();
+        let mut writes = BTreeSet::new();
+        for bc in self.get_bytecode() {
+            match bc {
+                Bytecode::Exists(idx) | Bytecode::ImmBorrowGlobal(idx) => {
+                    reads.insert(resource(*idx));
+                }
+                Bytecode::ExistsGeneric(idx) | Bytecode::ImmBorrowGlobalGeneric(idx) => {
+                    reads.insert(resource_inst(*idx));
+                }
+                Bytecode::MutBorrowGlobal(idx)
+                | Bytecode::MoveFrom(idx)
+                | Bytecode::MoveTo(idx) => {
+                    writes.insert(resource(*idx));
+                }
+                Bytecode::MutBorrowGlobalGeneric(idx)
+                | Bytecode::MoveFromGeneric(idx)
+                | Bytecode::MoveToGeneric(idx) => {
+                    writes.insert(resource_inst(*idx));
+                }
+                _ => {}
+            }
+        }
+        (reads, writes)
+    }
+
     /// Returns the name of the friend(the only allowed caller) of this function, if there is one.
     pub fn get_friend_name(&self) -> Option<Rc<String>> {
         self.get_ident_pragma(FRIEND_PRAGMA)
@@ -3211,7 +4740,7 @@ impl<'env> FunctionEnv<'env> {
             .map(|(i, k)| {
                 TypeParameter(
                     self.module_env.env.symbol_pool.make(&format!("$tv{}", i)),
-                    AbilityConstraint(*k),
+                    TypeParameterKind::new(*k),
                 )
             })
             .collect_vec()
@@ -3235,7 +4764,7 @@ impl<'env> FunctionEnv<'env> {
                     .unwrap_or_else(|| format!("unknown#{}", i));
                 TypeParameter(
                     self.module_env.env.symbol_pool.make(&name),
-                    AbilityConstraint(*k),
+                    TypeParameterKind::new(*k),
                 )
             })
             .collect_vec()
@@ -3378,6 +4907,16 @@ impl<'env> FunctionEnv<'env> {
         &self.data.spec
     }
 
+    /// Returns the calls in method style in the body of this function.
+    pub fn get_method_calls(&self) -> &[MethodCall] {
+        &self.data.method_calls
+    }
+
+    /// Returns the call in method style at the given location, if any.
+    pub fn get_method_call_at(&self, loc: &Loc) -> Option<&MethodCall> {
+        self.data.method_calls.iter().find(|call| &call.loc == loc)
+    }
+
     /// Returns the acquired global resource types.
     pub fn get_acquires_global_resources(&'env self) -> Vec<StructId> {
         let function_definition = self
@@ -3580,7 +5119,7 @@ impl<'env> FunctionEnv<'env> {
 /// # Expression Environment
 
 /// Represents context for an expression.
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ExpInfo {
     /// The associated location of this expression.
     loc: Loc,
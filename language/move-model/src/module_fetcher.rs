@@ -0,0 +1,118 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Loading of published modules from a node REST endpoint, so that a model can be built for the
+//! code deployed at an address.
+//!
+//! The endpoint is expected to serve `GET <endpoint>/accounts/<address>/modules`, returning an
+//! array of objects whose `bytecode` field holds the hex encoded module. Fetching requires the
+//! `fetch` feature; parsing of previously downloaded responses is always available.
+
+use anyhow::anyhow;
+use move_binary_format::{access::ModuleAccess, CompiledModule};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+#[cfg(feature = "fetch")]
+use crate::{model::GlobalEnv, run_bytecode_model_builder};
+#[cfg(feature = "fetch")]
+use anyhow::Context;
+#[cfg(feature = "fetch")]
+use move_core_types::account_address::AccountAddress;
+#[cfg(feature = "fetch")]
+use std::collections::BTreeSet;
+
+/// A module in the response of a node REST endpoint.
+#[derive(Debug, Deserialize)]
+struct PublishedModule {
+    bytecode: String,
+}
+
+/// Parses the response of the modules endpoint of an account into compiled modules.
+pub fn parse_account_modules(json: &str) -> anyhow::Result<Vec<CompiledModule>> {
+    let published: Vec<PublishedModule> = serde_json::from_str(json)?;
+    published
+        .iter()
+        .map(|m| {
+            let bytes = hex::decode(m.bytecode.trim_start_matches("0x"))
+                .map_err(|_| anyhow!("invalid hex encoding of module"))?;
+            CompiledModule::deserialize(&bytes)
+                .map_err(|e| anyhow!("cannot deserialize module: {:?}", e))
+        })
+        .collect()
+}
+
+/// Fetches the modules published at an address.
+#[cfg(feature = "fetch")]
+pub fn fetch_account_modules(
+    endpoint: &str,
+    address: &AccountAddress,
+) -> anyhow::Result<Vec<CompiledModule>> {
+    let url = format!(
+        "{}/accounts/{}/modules",
+        endpoint.trim_end_matches('/'),
+        address.to_hex_literal()
+    );
+    let response = attohttpc::get(&url)
+        .send()
+        .with_context(|| format!("fetching `{}`", url))?;
+    if !response.is_success() {
+        return Err(anyhow!("fetching `{}`: status {}", url, response.status()));
+    }
+    parse_account_modules(&response.text()?).with_context(|| format!("fetching `{}`", url))
+}
+
+/// Fetches the modules published at the given addresses, together with the modules they
+/// depend on, and builds a model from their bytecode.
+#[cfg(feature = "fetch")]
+pub fn run_bytecode_model_builder_from_chain(
+    endpoint: &str,
+    addresses: &[AccountAddress],
+) -> anyhow::Result<GlobalEnv> {
+    let mut modules = vec![];
+    let mut fetched = BTreeSet::new();
+    let mut todo = addresses.to_vec();
+    while let Some(address) = todo.pop() {
+        if !fetched.insert(address) {
+            continue;
+        }
+        for module in fetch_account_modules(endpoint, &address)? {
+            todo.extend(
+                module
+                    .immediate_dependencies()
+                    .iter()
+                    .map(|id| *id.address())
+                    .filter(|a| !fetched.contains(a)),
+            );
+            modules.push(module);
+        }
+    }
+    run_bytecode_model_builder(&sort_by_dependencies(modules)?)
+}
+
+/// Sorts modules such that each module comes after the modules it depends on.
+pub fn sort_by_dependencies(modules: Vec<CompiledModule>) -> anyhow::Result<Vec<CompiledModule>> {
+    let mut pending = modules
+        .into_iter()
+        .map(|m| (m.self_id(), m))
+        .collect::<BTreeMap<_, _>>();
+    let mut sorted = vec![];
+    while !pending.is_empty() {
+        let ready = pending
+            .iter()
+            .filter(|(_, m)| {
+                m.immediate_dependencies()
+                    .iter()
+                    .all(|dep| !pending.contains_key(dep))
+            })
+            .map(|(id, _)| id.clone())
+            .collect::<Vec<_>>();
+        if ready.is_empty() {
+            return Err(anyhow!("cyclic module dependencies"));
+        }
+        for id in ready {
+            sorted.push(pending.remove(&id).expect("pending module"));
+        }
+    }
+    Ok(sorted)
+}
@@ -3,7 +3,7 @@
 
 use serde::{Deserialize, Serialize};
 
-use crate::simplifier::SimplificationPass;
+use crate::{language_version::LanguageVersion, simplifier::SimplificationPass};
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(default, deny_unknown_fields)]
@@ -17,6 +17,23 @@ pub struct ModelBuilderOptions {
     /// as long as the function spec has no property marked as `[concrete]` or `[abstract]`.
     pub ignore_pragma_opaque_when_possible: bool,
 
+    /// Treat functions in dependency modules which have a specification as opaque, so that the
+    /// target modules are verified against the specifications of their dependencies instead of
+    /// their implementation. The dependency specifications need to be verified separately.
+    pub assume_dependency_specs: bool,
+
     /// List of simplification passes and the order each pass to be executed
     pub simplification_pipeline: Vec<SimplificationPass>,
+
+    /// Additional files containing only specification modules (`spec 0x1::M { ... }`), which are
+    /// merged into their target modules. Those files are added to the first source package.
+    pub spec_files: Vec<String>,
+
+    /// Exclude files containing only specifications, i.e. files with extension `.spec.move`,
+    /// from the sources and dependencies. This allows to build with or without those specs.
+    pub exclude_spec_files: bool,
+
+    /// The version of the Move language the sources target. Features which are not available in
+    /// this version are reported as errors. Defaults to the latest version.
+    pub language_version: LanguageVersion,
 }
@@ -69,6 +69,30 @@ pub const DISABLE_INVARIANTS_IN_BODY_PRAGMA: &str = "disable_invariants_in_body"
 /// to this function
 pub const DELEGATE_INVARIANTS_TO_CALLER_PRAGMA: &str = "delegate_invariants_to_caller";
 
+/// Pragma indicating that the global invariants modified by a called function are to be asserted
+/// right after the call, even if the called function checks them itself. This is used to verify
+/// that a sequence of calls, like the steps of a transaction sequence, preserves the invariants
+/// after each step.
+pub const ASSERT_INVARIANTS_AFTER_CALLS_PRAGMA: &str = "assert_invariants_after_calls";
+
+/// Pragma declaring the maximal call depth of a function, counting the function itself
+/// as one frame. For recursive functions, this serves as the user-provided bound of the
+/// recursion; for all others, the bound is checked against the call graph.
+pub const MAX_CALL_DEPTH_PRAGMA: &str = "max_call_depth";
+
+/// Pragma naming the parameter of a recursive function which serves as the termination
+/// measure of the recursion, e.g. `pragma measure = n;`.
+pub const MEASURE_PRAGMA: &str = "measure";
+
+/// Pragma declaring how often the loops of a function are unrolled for verification, e.g.
+/// `pragma unroll = 3;`. Executions which iterate a loop more often are not verified.
+pub const UNROLL_PRAGMA: &str = "unroll";
+
+/// Pragma indicating whether calls to a function are inlined into its callers for
+/// verification. If not declared, functions not exceeding the inlining threshold of the
+/// prover are inlined.
+pub const INLINE_PRAGMA: &str = "inline";
+
 /// Checks whether a pragma is valid in a specific spec block.
 pub fn is_pragma_valid_for_block(target: &SpecBlockContext<'_>, pragma: &str) -> bool {
     use crate::builder::module_builder::SpecBlockContext::*;
@@ -81,6 +105,9 @@ pub fn is_pragma_valid_for_block(target: &SpecBlockContext<'_>, pragma: &str) ->
                 | ABORTS_IF_IS_STRICT_PRAGMA
                 | ABORTS_IF_IS_PARTIAL_PRAGMA
                 | INTRINSIC_PRAGMA
+                | MAX_CALL_DEPTH_PRAGMA
+                | UNROLL_PRAGMA
+                | INLINE_PRAGMA
         ),
         Function(..) => matches!(
             pragma,
@@ -102,6 +129,11 @@ pub fn is_pragma_valid_for_block(target: &SpecBlockContext<'_>, pragma: &str) ->
                 | FRIEND_PRAGMA
                 | DISABLE_INVARIANTS_IN_BODY_PRAGMA
                 | DELEGATE_INVARIANTS_TO_CALLER_PRAGMA
+                | ASSERT_INVARIANTS_AFTER_CALLS_PRAGMA
+                | MAX_CALL_DEPTH_PRAGMA
+                | MEASURE_PRAGMA
+                | UNROLL_PRAGMA
+                | INLINE_PRAGMA
         ),
         _ => false,
     }
@@ -0,0 +1,144 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A helper for composing transaction scripts programmatically.
+//!
+//! A composed script is a sequence of calls to entry functions, rendered as Move source. The
+//! model builder turns scripts into pseudo modules named after the script function, so a
+//! composed script can be verified like any other code, including specifications attached to
+//! it via `ScriptComposer::spec`.
+
+use itertools::Itertools;
+use std::path::{Path, PathBuf};
+
+/// A call to a function in a composed script.
+#[derive(Debug, Clone)]
+pub struct ScriptCall {
+    /// The fully qualified name of the function, e.g. `0x1::Coin::transfer`.
+    pub function: String,
+    /// The type arguments, as Move source.
+    pub type_args: Vec<String>,
+    /// The arguments, as Move source. Arguments may refer to the parameters of the script.
+    pub args: Vec<String>,
+}
+
+/// A builder for a script which calls a sequence of functions.
+#[derive(Debug, Clone)]
+pub struct ScriptComposer {
+    name: String,
+    type_params: Vec<(String, Vec<String>)>,
+    params: Vec<(String, String)>,
+    calls: Vec<ScriptCall>,
+    spec: Vec<String>,
+}
+
+impl ScriptComposer {
+    /// Creates a composer for a script whose function has the given name. The name is also the
+    /// name of the pseudo module the script is represented by in the model.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            type_params: vec![],
+            params: vec![],
+            calls: vec![],
+            spec: vec![],
+        }
+    }
+
+    /// Adds a type parameter with the given abilities to the script.
+    pub fn type_param(mut self, name: impl Into<String>, abilities: &[&str]) -> Self {
+        self.type_params.push((
+            name.into(),
+            abilities.iter().map(|a| a.to_string()).collect(),
+        ));
+        self
+    }
+
+    /// Adds a parameter of the given type to the script.
+    pub fn param(mut self, name: impl Into<String>, ty: impl Into<String>) -> Self {
+        self.params.push((name.into(), ty.into()));
+        self
+    }
+
+    /// Adds a call to the given function to the script.
+    pub fn call(mut self, function: impl Into<String>, type_args: &[&str], args: &[&str]) -> Self {
+        self.calls.push(ScriptCall {
+            function: function.into(),
+            type_args: type_args.iter().map(|t| t.to_string()).collect(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+        });
+        self
+    }
+
+    /// Adds a specification condition, e.g. `aborts_if false;`, to the script function.
+    pub fn spec(mut self, condition: impl Into<String>) -> Self {
+        self.spec.push(condition.into());
+        self
+    }
+
+    /// Returns the calls of the script.
+    pub fn calls(&self) -> &[ScriptCall] {
+        &self.calls
+    }
+
+    /// Renders the script as Move source.
+    pub fn to_source(&self) -> String {
+        let mut source = String::new();
+        source.push_str("script {\n");
+        let type_params = if self.type_params.is_empty() {
+            String::new()
+        } else {
+            format!(
+                "<{}>",
+                self.type_params
+                    .iter()
+                    .map(|(name, abilities)| if abilities.is_empty() {
+                        name.to_string()
+                    } else {
+                        format!("{}: {}", name, abilities.join(" + "))
+                    })
+                    .join(", ")
+            )
+        };
+        source.push_str(&format!(
+            "    fun {}{}({}) {{\n",
+            self.name,
+            type_params,
+            self.params
+                .iter()
+                .map(|(name, ty)| format!("{}: {}", name, ty))
+                .join(", ")
+        ));
+        for call in &self.calls {
+            let type_args = if call.type_args.is_empty() {
+                String::new()
+            } else {
+                format!("<{}>", call.type_args.join(", "))
+            };
+            source.push_str(&format!(
+                "        {}{}({});\n",
+                call.function,
+                type_args,
+                call.args.join(", ")
+            ));
+        }
+        source.push_str("    }\n");
+        if !self.spec.is_empty() {
+            source.push_str(&format!("    spec {} {{\n", self.name));
+            for cond in &self.spec {
+                source.push_str(&format!("        {}\n", cond));
+            }
+            source.push_str("    }\n");
+        }
+        source.push_str("}\n");
+        source
+    }
+
+    /// Writes the script as Move source into the given directory, returning the path of the
+    /// written file. The file can be passed to the model builder like any other source.
+    pub fn write_to_dir(&self, dir: &Path) -> anyhow::Result<PathBuf> {
+        let path = dir.join(&self.name).with_extension("move");
+        std::fs::write(&path, self.to_source())?;
+        Ok(path)
+    }
+}
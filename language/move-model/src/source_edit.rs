@@ -0,0 +1,284 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Edits of source files, expressed in terms of model locations.
+//!
+//! Tools which rewrite sources, like renaming, generation of spec scaffolds, or application of
+//! suggested fixes, collect their edits in a `SourceEdits` value. Edits are checked for
+//! conflicts when they are added, and applied to the file text in one go, so the locations
+//! derived from the model stay valid regardless of the order in which edits are produced.
+//!
+//! A `Fix` is a set of edits which resolves a diagnostic. Fixes are attached to diagnostics in
+//! the environment, from where they can be offered by editors or applied in batch.
+
+use crate::model::{GlobalEnv, Loc};
+use anyhow::{anyhow, bail};
+use codespan::{ByteIndex, FileId, Span};
+use codespan_reporting::diagnostic::Severity;
+use std::collections::BTreeMap;
+
+/// An edit of a source file, replacing the text at a location. Insertions are represented by
+/// an empty location, deletions by an empty text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceEdit {
+    loc: Loc,
+    text: String,
+}
+
+/// A conflict between two edits whose locations overlap.
+#[derive(Debug, Clone)]
+pub struct SourceEditConflict {
+    /// The edit which has been rejected.
+    pub edit: SourceEdit,
+    /// The edit which has been added before and conflicts with the rejected one.
+    pub conflicting: SourceEdit,
+}
+
+/// A collection of non-conflicting edits, possibly spanning multiple files.
+#[derive(Debug, Clone, Default)]
+pub struct SourceEdits {
+    edits: BTreeMap<FileId, Vec<SourceEdit>>,
+}
+
+/// A fix of a diagnostic.
+#[derive(Debug, Clone)]
+pub struct Fix {
+    /// A short description of the fix, e.g. "replace with `LIMIT`".
+    pub title: String,
+    /// The edits which implement the fix.
+    pub edits: SourceEdits,
+    /// Whether the fix is safe to apply without user review. Only preferred fixes are applied
+    /// in batch.
+    pub preferred: bool,
+}
+
+impl SourceEdit {
+    /// Creates an edit which inserts text before the location.
+    pub fn insert_before(loc: &Loc, text: impl Into<String>) -> Self {
+        let pos = loc.span().start();
+        Self::replace(Loc::new(loc.file_id(), Span::new(pos, pos)), text)
+    }
+
+    /// Creates an edit which inserts text after the location.
+    pub fn insert_after(loc: &Loc, text: impl Into<String>) -> Self {
+        let pos = loc.span().end();
+        Self::replace(Loc::new(loc.file_id(), Span::new(pos, pos)), text)
+    }
+
+    /// Creates an edit which replaces the text at the location.
+    pub fn replace(loc: Loc, text: impl Into<String>) -> Self {
+        Self {
+            loc,
+            text: text.into(),
+        }
+    }
+
+    /// Creates an edit which deletes the text at the location.
+    pub fn delete(loc: Loc) -> Self {
+        Self::replace(loc, "")
+    }
+
+    /// Creates an edit which adds a line as the last item of a block, given the location of
+    /// the block which ends with `}`. The line is indented one level deeper than the line of
+    /// the closing brace; if it consists of multiple lines, each non-empty one is indented. If
+    /// the block is closed on a line with other content, the line is inserted before the brace
+    /// instead.
+    pub fn insert_in_block(env: &GlobalEnv, block_loc: &Loc, line: &str) -> Self {
+        let source = env.get_file_source(block_loc.file_id());
+        let close = block_loc.span().end().to_usize().saturating_sub(1);
+        let line_start = source[..close].rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let indent = &source[line_start..close];
+        if indent.chars().all(char::is_whitespace) {
+            let pos = ByteIndex(line_start as u32);
+            let text = line
+                .split('\n')
+                .map(|l| {
+                    if l.is_empty() {
+                        "\n".to_string()
+                    } else {
+                        format!("{}    {}\n", indent, l)
+                    }
+                })
+                .collect::<String>();
+            Self::replace(Loc::new(block_loc.file_id(), Span::new(pos, pos)), text)
+        } else {
+            Self::insert_before(&block_loc.at_end(), format!("{} ", line))
+        }
+    }
+
+    /// Returns the location which is replaced by this edit.
+    pub fn loc(&self) -> &Loc {
+        &self.loc
+    }
+
+    /// Returns the text which replaces the location.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns true if this edit inserts text without replacing any.
+    pub fn is_insertion(&self) -> bool {
+        self.loc.span().start() == self.loc.span().end()
+    }
+
+    /// Returns true if this edit cannot be applied together with the other one. Edits conflict
+    /// if they replace overlapping text, or if one inserts text strictly inside the text
+    /// replaced by the other. Insertions at the same position do not conflict; they are
+    /// applied in the order they have been added.
+    pub fn conflicts_with(&self, other: &SourceEdit) -> bool {
+        if self.loc.file_id() != other.loc.file_id() {
+            return false;
+        }
+        let (a, b) = (self.loc.span(), other.loc.span());
+        if self.is_insertion() && other.is_insertion() {
+            false
+        } else if self.is_insertion() {
+            b.start() < a.start() && a.start() < b.end()
+        } else if other.is_insertion() {
+            a.start() < b.start() && b.start() < a.end()
+        } else {
+            a.start() < b.end() && b.start() < a.end()
+        }
+    }
+}
+
+impl Fix {
+    /// Creates a fix which needs review by the user.
+    pub fn new(title: impl Into<String>, edits: SourceEdits) -> Self {
+        Self {
+            title: title.into(),
+            edits,
+            preferred: false,
+        }
+    }
+
+    /// Creates a fix which consists of a single edit and needs review by the user.
+    pub fn from_edit(title: impl Into<String>, edit: SourceEdit) -> Self {
+        let mut edits = SourceEdits::new();
+        edits.add(edit).expect("single edit");
+        Self::new(title, edits)
+    }
+
+    /// Marks the fix as safe to apply without review.
+    pub fn preferred(self) -> Self {
+        Self {
+            preferred: true,
+            ..self
+        }
+    }
+}
+
+impl SourceEditConflict {
+    /// Reports the conflict as an error in the environment.
+    pub fn report(&self, env: &GlobalEnv) {
+        env.diag_with_labels(
+            Severity::Error,
+            self.edit.loc(),
+            "conflicting source edits",
+            vec![(
+                self.conflicting.loc().clone(),
+                "conflicts with this edit".to_string(),
+            )],
+        )
+    }
+}
+
+impl SourceEdits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an edit, unless it conflicts with an edit added before. Adding an edit which is
+    /// equal to one already present has no effect.
+    pub fn add(&mut self, edit: SourceEdit) -> Result<(), SourceEditConflict> {
+        let file_edits = self.edits.entry(edit.loc.file_id()).or_default();
+        if file_edits.contains(&edit) {
+            return Ok(());
+        }
+        if let Some(conflicting) = file_edits.iter().find(|e| e.conflicts_with(&edit)) {
+            return Err(SourceEditConflict {
+                conflicting: conflicting.clone(),
+                edit,
+            });
+        }
+        file_edits.push(edit);
+        Ok(())
+    }
+
+    /// Adds all edits of the other collection. If any of them conflicts, none is added.
+    pub fn merge(&mut self, other: SourceEdits) -> Result<(), SourceEditConflict> {
+        let mut merged = self.clone();
+        for edit in other.edits.into_values().flatten() {
+            merged.add(edit)?;
+        }
+        *self = merged;
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.edits.is_empty()
+    }
+
+    /// Returns the files which are edited.
+    pub fn files(&self) -> impl Iterator<Item = FileId> + '_ {
+        self.edits.keys().cloned()
+    }
+
+    /// Returns the edits of a file, in the order they have been added.
+    pub fn edits_of(&self, file_id: FileId) -> &[SourceEdit] {
+        self.edits
+            .get(&file_id)
+            .map(|e| e.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Applies the edits of a file to its text, which must be the text the locations of the
+    /// edits refer to.
+    pub fn apply_to_text(&self, file_id: FileId, text: &str) -> anyhow::Result<String> {
+        let mut edits = self.edits_of(file_id).iter().collect::<Vec<_>>();
+        // Sort by position, with insertions before replacements starting at the same position.
+        // The sort is stable, so insertions at the same position keep the order of addition.
+        edits.sort_by_key(|e| (e.loc.span().start(), e.loc.span().end()));
+        let mut result = String::with_capacity(text.len());
+        let mut pos = 0;
+        for edit in edits {
+            let start = edit.loc.span().start().to_usize();
+            let end = edit.loc.span().end().to_usize();
+            if end > text.len() || !text.is_char_boundary(start) || !text.is_char_boundary(end) {
+                bail!(
+                    "edit at bytes {}..{} does not fit into the source text",
+                    start,
+                    end
+                )
+            }
+            result.push_str(&text[pos..start]);
+            result.push_str(&edit.text);
+            pos = end;
+        }
+        result.push_str(&text[pos..]);
+        Ok(result)
+    }
+
+    /// Applies the edits to the sources of the environment, returning the new text of each
+    /// edited file.
+    pub fn apply(&self, env: &GlobalEnv) -> anyhow::Result<BTreeMap<FileId, String>> {
+        self.files()
+            .map(|file_id| {
+                let text = self
+                    .apply_to_text(file_id, env.get_file_source(file_id))
+                    .map_err(|e| anyhow!("{}: {}", env.get_file(file_id).to_string_lossy(), e))?;
+                Ok((file_id, text))
+            })
+            .collect()
+    }
+
+    /// Applies the edits to the sources of the environment and writes the edited files.
+    pub fn write(&self, env: &GlobalEnv) -> anyhow::Result<()> {
+        for (file_id, text) in self.apply(env)? {
+            let path = env.get_file(file_id);
+            std::fs::write(path, text)
+                .map_err(|e| anyhow!("cannot write `{}`: {}", path.to_string_lossy(), e))?;
+        }
+        Ok(())
+    }
+}
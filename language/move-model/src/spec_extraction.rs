@@ -0,0 +1,514 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Extraction of repeated spec fragments into spec helper functions.
+//!
+//! Specs often repeat the same complex expression in many conditions, e.g. a predicate over
+//! the state of a resource which is required and ensured by every function of a module. This
+//! refactoring finds such fragments within the specs of a module and replaces them by calls to
+//! a generated spec function, which reduces duplication in the sources and lets the solver
+//! reason about the helper instead of the repeated expression.
+//!
+//! Fragments are compared structurally, up to the names of variables bound inside of them. The
+//! variables which occur free in a fragment become the parameters of the helper. The body of
+//! the helper is the source text of one of the fragments, so a fragment is only extracted if
+//! its text matches the model: each of its sub-expressions is located inside of it, and each
+//! free variable is written as its name. This excludes e.g. fragments stemming from schema
+//! arguments or implicit field access in struct invariants. Fragments which depend on the
+//! pre-state, the function result, or type parameters are not extracted either.
+
+use crate::{
+    ast::{Exp, ExpData, Operation, TempIndex},
+    model::{FunctionEnv, GlobalEnv, Loc, ModuleEnv, NodeId},
+    source_edit::{SourceEdit, SourceEditConflict, SourceEdits},
+    symbol::Symbol,
+    ty::{PrimitiveType, Type},
+};
+use itertools::Itertools;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The minimal number of expression nodes of a fragment to be extracted.
+const MIN_FRAGMENT_SIZE: usize = 8;
+
+/// The minimal number of occurrences of a fragment to be extracted.
+const MIN_OCCURRENCES: usize = 2;
+
+/// The prefix of the names of generated helpers.
+const HELPER_PREFIX: &str = "extracted_spec_";
+
+/// A spec helper function extracted from repeated fragments of the specs of a module.
+#[derive(Debug, Clone)]
+pub struct SpecHelper {
+    /// The name of the helper.
+    pub name: String,
+    /// The parameters, as pairs of name and type in source syntax.
+    pub params: Vec<(String, String)>,
+    /// The result type in source syntax.
+    pub result_type: String,
+    /// The body of the helper, with lines after the first one relative to the indentation of
+    /// the declaration.
+    pub body: String,
+    /// The location of the module into which the helper is inserted.
+    pub module_loc: Loc,
+    /// The locations of the fragments which are replaced by calls, with the arguments.
+    pub calls: Vec<(Loc, Vec<String>)>,
+}
+
+impl SpecHelper {
+    /// Returns the declaration of the helper.
+    pub fn declaration(&self) -> String {
+        format!(
+            "spec fun {}({}): {} {{\n    {}\n}}",
+            self.name,
+            self.params
+                .iter()
+                .map(|(name, ty)| format!("{}: {}", name, ty))
+                .join(", "),
+            self.result_type,
+            self.body.replace('\n', "\n    ")
+        )
+    }
+
+    /// Returns the edits which insert the helper at the end of its module and replace the
+    /// fragments by calls.
+    pub fn edits(&self, env: &GlobalEnv) -> Result<SourceEdits, SourceEditConflict> {
+        let mut edits = SourceEdits::new();
+        edits.add(SourceEdit::insert_in_block(
+            env,
+            &self.module_loc,
+            &format!("\n{}", self.declaration()),
+        ))?;
+        for (loc, args) in &self.calls {
+            edits.add(SourceEdit::replace(
+                loc.clone(),
+                format!("{}({})", self.name, args.join(", ")),
+            ))?;
+        }
+        Ok(edits)
+    }
+}
+
+/// Returns the edits which extract the repeated spec fragments of the target modules.
+pub fn spec_helper_edits(env: &GlobalEnv) -> Result<SourceEdits, SourceEditConflict> {
+    let mut edits = SourceEdits::new();
+    for module_env in env.get_modules().filter(|m| m.is_target()) {
+        for helper in find_spec_helpers(&module_env) {
+            edits.merge(helper.edits(env)?)?;
+        }
+    }
+    Ok(edits)
+}
+
+/// Finds the repeated fragments in the specs of the module which are worth to be extracted,
+/// preferring larger fragments over the fragments they contain.
+pub fn find_spec_helpers(module_env: &ModuleEnv) -> Vec<SpecHelper> {
+    let env = module_env.env;
+    let mut finder = FragmentFinder {
+        module_env,
+        module_loc: module_env.get_loc(),
+        fragments: BTreeMap::new(),
+        keys_at: BTreeMap::new(),
+    };
+    for cond in &module_env.get_spec().conditions {
+        finder.add_exps(None, cond.all_exps());
+    }
+    for struct_env in module_env.get_structs() {
+        for cond in &struct_env.get_spec().conditions {
+            finder.add_exps(None, cond.all_exps());
+        }
+    }
+    for fun_env in module_env.get_functions() {
+        let spec = fun_env.get_spec();
+        for cond in spec
+            .conditions
+            .iter()
+            .chain(spec.on_impl.values().flat_map(|s| s.conditions.iter()))
+        {
+            finder.add_exps(Some(&fun_env), cond.all_exps());
+        }
+    }
+
+    // The same text can be reached in different contexts, e.g. if a schema is included by
+    // multiple functions. Text which does not denote the same fragment in all of them cannot
+    // be replaced.
+    let ambiguous = finder
+        .keys_at
+        .iter()
+        .filter(|(_, keys)| keys.len() > 1)
+        .map(|(loc, _)| loc.clone())
+        .collect::<BTreeSet<_>>();
+    let mut fragments = finder
+        .fragments
+        .into_values()
+        .filter(|f| f.occurrences.len() >= MIN_OCCURRENCES)
+        .collect_vec();
+    fragments.sort_by(|f1, f2| {
+        f2.size
+            .cmp(&f1.size)
+            .then_with(|| f1.occurrences.keys().cmp(f2.occurrences.keys()))
+    });
+
+    let mut taken_names = module_env
+        .get_spec_funs()
+        .map(|(_, decl)| decl.name.display(env.symbol_pool()).to_string())
+        .collect::<BTreeSet<_>>();
+    let mut replaced: Vec<Loc> = vec![];
+    let mut helpers = vec![];
+    for fragment in fragments {
+        let occurrences = fragment
+            .occurrences
+            .into_iter()
+            .filter(|(loc, _)| {
+                !ambiguous.contains(loc)
+                    && !replaced
+                        .iter()
+                        .any(|r| r.is_enclosing(loc) || loc.is_enclosing(r))
+            })
+            .collect_vec();
+        if occurrences.len() < MIN_OCCURRENCES {
+            continue;
+        }
+        let first = &occurrences[0].1;
+        let name = (1..)
+            .map(|n| format!("{}{}", HELPER_PREFIX, n))
+            .find(|n| !taken_names.contains(n))
+            .expect("helper name");
+        taken_names.insert(name.clone());
+        replaced.extend(occurrences.iter().map(|(loc, _)| loc.clone()));
+        helpers.push(SpecHelper {
+            name,
+            params: first
+                .args
+                .iter()
+                .cloned()
+                .zip(fragment.param_types.iter().cloned())
+                .collect(),
+            result_type: fragment.result_type,
+            body: first.body.clone(),
+            module_loc: module_env.get_loc(),
+            calls: occurrences
+                .into_iter()
+                .map(|(loc, occurrence)| (loc, occurrence.args))
+                .collect(),
+        });
+    }
+    helpers
+}
+
+/// A fragment which occurs in the specs of a module, identified by its canonical key.
+struct Fragment {
+    /// The number of expression nodes of the fragment.
+    size: usize,
+    /// The types of the free variables, in source syntax.
+    param_types: Vec<String>,
+    /// The result type, in source syntax.
+    result_type: String,
+    /// The occurrences of the fragment, by location.
+    occurrences: BTreeMap<Loc, Occurrence>,
+}
+
+struct Occurrence {
+    /// The names of the free variables, in order of first occurrence.
+    args: Vec<String>,
+    /// The source text of the occurrence.
+    body: String,
+}
+
+struct FragmentFinder<'a, 'env> {
+    module_env: &'a ModuleEnv<'env>,
+    module_loc: Loc,
+    fragments: BTreeMap<String, Fragment>,
+    /// The keys of the fragments found at a location.
+    keys_at: BTreeMap<Loc, BTreeSet<String>>,
+}
+
+impl<'a, 'env> FragmentFinder<'a, 'env> {
+    fn add_exps<'e>(&mut self, fun_env: Option<&FunctionEnv>, exps: impl Iterator<Item = &'e Exp>) {
+        for exp in exps {
+            exp.visit(&mut |e| self.add_fragment(fun_env, e));
+        }
+    }
+
+    fn add_fragment(&mut self, fun_env: Option<&FunctionEnv>, exp: &ExpData) {
+        let loc = self.module_env.env.get_node_loc(exp.node_id());
+        if !self.module_loc.is_enclosing(&loc) {
+            return;
+        }
+        match self.fragment_at(fun_env, exp, &loc) {
+            Some((key, fragment, occurrence)) => {
+                self.keys_at
+                    .entry(loc.clone())
+                    .or_default()
+                    .insert(key.clone());
+                self.fragments
+                    .entry(key)
+                    .or_insert(fragment)
+                    .occurrences
+                    .insert(loc, occurrence);
+            }
+            None => {
+                // Record that the text does not denote an extractable fragment in this context,
+                // so it is not replaced in other contexts either.
+                self.keys_at.entry(loc).or_default().insert(String::new());
+            }
+        }
+    }
+
+    /// Returns the key of the expression, together with the fragment and occurrence it
+    /// represents, if it can be extracted.
+    fn fragment_at(
+        &self,
+        fun_env: Option<&FunctionEnv>,
+        exp: &ExpData,
+        loc: &Loc,
+    ) -> Option<(String, Fragment, Occurrence)> {
+        let env = self.module_env.env;
+        let mut canon = Canonicalizer {
+            env,
+            key: String::new(),
+            bound: vec![],
+            free: vec![],
+            nodes: vec![],
+        };
+        if !canon.add(exp)
+            || canon.nodes.len() < MIN_FRAGMENT_SIZE
+            || !canon
+                .nodes
+                .iter()
+                .all(|id| loc.is_enclosing(&env.get_node_loc(*id)))
+        {
+            return None;
+        }
+        let mut args = vec![];
+        let mut param_types = vec![];
+        for (var, occurrences) in &canon.free {
+            let name = match var {
+                FreeVar::Local(sym) => sym.display(env.symbol_pool()).to_string(),
+                FreeVar::Temporary(idx) => fun_env?
+                    .get_local_name(*idx)
+                    .display(env.symbol_pool())
+                    .to_string(),
+            };
+            let written_as_name = occurrences.iter().all(|id| {
+                env.get_source(&env.get_node_loc(*id))
+                    .map(|text| text == name)
+                    .unwrap_or(false)
+            });
+            if !written_as_name || args.contains(&name) {
+                return None;
+            }
+            param_types.push(self.type_to_source(&env.get_node_type(occurrences[0]))?);
+            args.push(name);
+        }
+        let result_type = self.type_to_source(&env.get_node_type(exp.node_id()))?;
+        let text = env.get_source(loc).ok()?;
+        let body = reindent(env.get_file_source(loc.file_id()), loc, text);
+        let fragment = Fragment {
+            size: canon.nodes.len(),
+            param_types,
+            result_type,
+            occurrences: BTreeMap::new(),
+        };
+        Some((canon.key, fragment, Occurrence { args, body }))
+    }
+
+    /// Returns the source syntax of a type, if it can be declared as a parameter or result
+    /// of a spec function in this module.
+    fn type_to_source(&self, ty: &Type) -> Option<String> {
+        let env = self.module_env.env;
+        Some(match ty {
+            Type::Primitive(prim) => match prim {
+                PrimitiveType::Bool => "bool",
+                PrimitiveType::U8 => "u8",
+                PrimitiveType::U64 => "u64",
+                PrimitiveType::U128 => "u128",
+                PrimitiveType::Address => "address",
+                PrimitiveType::Signer => "signer",
+                PrimitiveType::Num => "num",
+                PrimitiveType::Range | PrimitiveType::EventStore => return None,
+            }
+            .to_string(),
+            Type::Vector(elem) => format!("vector<{}>", self.type_to_source(elem)?),
+            Type::Struct(mid, sid, inst) => {
+                let struct_env = env.get_module(*mid).into_struct(*sid);
+                if struct_env.is_ghost_memory() {
+                    return None;
+                }
+                let name = struct_env.get_name().display(env.symbol_pool()).to_string();
+                let qualified = if *mid == self.module_env.get_id() {
+                    name
+                } else {
+                    let module_name = struct_env.module_env.get_name();
+                    format!(
+                        "0x{:x}::{}::{}",
+                        module_name.addr(),
+                        module_name.name().display(env.symbol_pool()),
+                        name
+                    )
+                };
+                if inst.is_empty() {
+                    qualified
+                } else {
+                    format!(
+                        "{}<{}>",
+                        qualified,
+                        inst.iter()
+                            .map(|ty| self.type_to_source(ty))
+                            .collect::<Option<Vec<_>>>()?
+                            .join(", ")
+                    )
+                }
+            }
+            _ => return None,
+        })
+    }
+}
+
+/// Shifts the lines of the text at the location after the first one, so they are relative to
+/// the indentation of the line on which the location starts.
+fn reindent(source: &str, loc: &Loc, text: &str) -> String {
+    let start = loc.span().start().to_usize();
+    let line_start = source[..start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let line = &source[line_start..];
+    let indent = line.len() - line.trim_start_matches([' ', '\t']).len();
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| {
+            if i == 0 {
+                line.to_string()
+            } else {
+                let strip = (line.len() - line.trim_start().len()).min(indent);
+                line[strip..].to_string()
+            }
+        })
+        .join("\n")
+}
+
+/// A variable which occurs free in a fragment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FreeVar {
+    Local(Symbol),
+    Temporary(TempIndex),
+}
+
+/// Computes the canonical key of a fragment, in which bound variables are replaced by their
+/// binding depth and free variables by the order of their first occurrence. The key contains
+/// the types of all nodes, so fragments with the same key are instances of the same helper.
+struct Canonicalizer<'env> {
+    env: &'env GlobalEnv,
+    key: String,
+    /// The variables bound at the current position, innermost last.
+    bound: Vec<Symbol>,
+    /// The free variables, in order of first occurrence, with the nodes referring to them.
+    free: Vec<(FreeVar, Vec<NodeId>)>,
+    /// The nodes of the fragment.
+    nodes: Vec<NodeId>,
+}
+
+impl<'env> Canonicalizer<'env> {
+    /// Adds the expression to the key, returning false if it cannot be part of a helper.
+    fn add(&mut self, exp: &ExpData) -> bool {
+        use ExpData::*;
+        let id = exp.node_id();
+        let ty = self.env.get_node_type(id);
+        let inst = self.env.get_node_instantiation_opt(id).unwrap_or_default();
+        if !is_concrete(&ty) || !inst.iter().all(is_concrete) {
+            return false;
+        }
+        self.nodes.push(id);
+        self.key.push_str(&format!("({:?} {:?}", ty, inst));
+        let ok = match exp {
+            Value(_, val) => {
+                self.key.push_str(&format!(" {:?}", val));
+                true
+            }
+            LocalVar(_, sym) => {
+                match self.bound.iter().rposition(|s| s == sym) {
+                    Some(depth) => self.key.push_str(&format!(" b{}", depth)),
+                    None => self.add_free(FreeVar::Local(*sym), id),
+                }
+                true
+            }
+            Temporary(_, idx) => {
+                self.add_free(FreeVar::Temporary(*idx), id);
+                true
+            }
+            Call(_, oper, args) => {
+                self.key.push_str(&format!(" {:?}", oper));
+                is_extractable(oper) && args.iter().all(|arg| self.add(arg))
+            }
+            Quant(_, kind, ranges, triggers, condition, body) => {
+                self.key.push_str(&format!(" {:?}", kind));
+                let depth = self.bound.len();
+                let mut ok = true;
+                for (decl, range) in ranges {
+                    ok = ok && decl.binding.is_none() && self.add(range);
+                }
+                self.bound.extend(ranges.iter().map(|(decl, _)| decl.name));
+                for exp in triggers.iter().flatten().chain(condition.iter()) {
+                    ok = ok && self.add(exp);
+                }
+                ok = ok && self.add(body);
+                self.bound.truncate(depth);
+                ok
+            }
+            Block(_, decls, body) => {
+                let depth = self.bound.len();
+                let mut ok = true;
+                for decl in decls {
+                    if let Some(binding) = &decl.binding {
+                        ok = ok && self.add(binding);
+                    }
+                    self.bound.push(decl.name);
+                }
+                ok = ok && self.add(body);
+                self.bound.truncate(depth);
+                ok
+            }
+            IfElse(_, cond, if_true, if_false) => {
+                self.add(cond) && self.add(if_true) && self.add(if_false)
+            }
+            Invalid(_) | Invoke(..) | Lambda(..) => false,
+        };
+        self.key.push(')');
+        ok
+    }
+
+    fn add_free(&mut self, var: FreeVar, id: NodeId) {
+        let pos = match self.free.iter().position(|(v, _)| *v == var) {
+            Some(pos) => pos,
+            None => {
+                self.free.push((var, vec![]));
+                self.free.len() - 1
+            }
+        };
+        self.free[pos].1.push(id);
+        self.key.push_str(&format!(" f{}", pos));
+    }
+}
+
+/// Returns true if the type does not contain type parameters or inference variables.
+fn is_concrete(ty: &Type) -> bool {
+    let mut concrete = true;
+    ty.visit(&mut |t| {
+        if matches!(t, Type::TypeParameter(_) | Type::Var(_) | Type::Error) {
+            concrete = false
+        }
+    });
+    concrete
+}
+
+/// Returns true if an operation can occur in the body of a helper. This excludes operations
+/// which depend on the context of a condition, like `old(..)` or `result`, and operations which
+/// are only introduced by the prover.
+fn is_extractable(oper: &Operation) -> bool {
+    use Operation::*;
+    match oper {
+        Function(_, _, labels) => labels.is_none(),
+        Global(label) | Exists(label) => label.is_none(),
+        Result(_) | Old | Trace(_) | CanModify | AbortFlag | AbortCode | WellFormed | BoxValue
+        | UnboxValue | EmptyEventStore | ExtendEventStore | EventStoreIncludes
+        | EventStoreIncludedIn | NoOp => false,
+        _ => true,
+    }
+}
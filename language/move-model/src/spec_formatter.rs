@@ -0,0 +1,958 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Formatting of specification blocks.
+//!
+//! The formatter parses a Move source and pretty prints the members of each `spec` block, both
+//! module level blocks and blocks inlined into function bodies, from the parsed AST. All text
+//! outside of spec blocks is left untouched. Members are indented one level deeper than the line
+//! which opens the block, and long conditions are broken at binary operators.
+//!
+//! Comments are preserved: a comment on a line of its own stays before the member which follows
+//! it, and a comment trailing a member stays on the line where the member ends. A member which
+//! contains comments inside of it is kept as written, only re-indented. Single blank lines
+//! between members are kept. Blocks which start with `use` declarations are not formatted.
+//!
+//! Editors can use `spec_format_edits` as a formatting provider; it produces one edit per spec
+//! block whose text changes.
+
+use crate::{
+    model::{GlobalEnv, Loc},
+    source_edit::{SourceEdit, SourceEdits},
+};
+use anyhow::anyhow;
+use codespan::{FileId, Span};
+use itertools::Itertools;
+use move_command_line_common::files::FileHash;
+use move_compiler::{
+    parser::{ast::*, parse_file_string},
+    shared::{CompilationEnv, Flags, Name},
+};
+use move_ir_types::{location::Loc as ParserLoc, sp};
+use pretty::RcDoc;
+
+/// A type alias for the way how we use crate `pretty`'s document type.
+type Doc = RcDoc<'static, ()>;
+
+/// The width which the formatter tries to fit lines into, including indentation.
+const PRETTY_WIDTH: usize = 100;
+
+/// The number of spaces of one level of indentation.
+const INDENT: usize = 4;
+
+/// Formats the spec blocks of a Move source, returning the new source.
+pub fn format_specs(source: &str) -> anyhow::Result<String> {
+    let mut result = String::with_capacity(source.len());
+    let mut pos = 0;
+    for (start, end, text) in format_spec_blocks(source)? {
+        result.push_str(&source[pos..start]);
+        result.push_str(&text);
+        pos = end;
+    }
+    result.push_str(&source[pos..]);
+    Ok(result)
+}
+
+/// Returns the edits which format the spec blocks of a file of the environment.
+pub fn spec_format_edits(env: &GlobalEnv, file_id: FileId) -> anyhow::Result<SourceEdits> {
+    let mut edits = SourceEdits::new();
+    for (start, end, text) in format_spec_blocks(env.get_file_source(file_id))? {
+        let loc = Loc::new(file_id, Span::new(start as u32, end as u32));
+        edits
+            .add(SourceEdit::replace(loc, text))
+            .expect("spec blocks do not overlap");
+    }
+    Ok(edits)
+}
+
+/// Returns the byte range and the formatted text of each spec block whose text changes.
+fn format_spec_blocks(source: &str) -> anyhow::Result<Vec<(usize, usize, String)>> {
+    let mut compilation_env = CompilationEnv::new(Flags::empty());
+    let (defs, _) = parse_file_string(&mut compilation_env, FileHash::new(source), source)
+        .map_err(|_| anyhow!("cannot format specs of a source with syntax errors"))?;
+    let mut blocks = vec![];
+    for def in &defs {
+        match def {
+            Definition::Module(module) => collect_module_specs(module, &mut blocks),
+            Definition::Address(address) => {
+                for module in &address.modules {
+                    collect_module_specs(module, &mut blocks)
+                }
+            }
+            Definition::Script(script) => {
+                blocks.extend(&script.specs);
+                collect_function_specs(&script.function, &mut blocks);
+            }
+        }
+    }
+    let formatter = SpecFormatter { source };
+    Ok(blocks
+        .into_iter()
+        .filter_map(|block| {
+            let (start, end) = (block.loc.start() as usize, block.loc.end() as usize);
+            let text = formatter.format_block(block);
+            if text != source[start..end] {
+                Some((start, end, text))
+            } else {
+                None
+            }
+        })
+        .collect())
+}
+
+fn collect_module_specs<'a>(module: &'a ModuleDefinition, blocks: &mut Vec<&'a SpecBlock>) {
+    for member in &module.members {
+        match member {
+            ModuleMember::Spec(block) => blocks.push(block),
+            ModuleMember::Function(fun) => collect_function_specs(fun, blocks),
+            _ => {}
+        }
+    }
+}
+
+fn collect_function_specs<'a>(fun: &'a Function, blocks: &mut Vec<&'a SpecBlock>) {
+    if let FunctionBody_::Defined(seq) = &fun.body.value {
+        collect_sequence_specs(seq, blocks)
+    }
+}
+
+fn collect_sequence_specs<'a>(seq: &'a Sequence, blocks: &mut Vec<&'a SpecBlock>) {
+    for item in &seq.1 {
+        match &item.value {
+            SequenceItem_::Seq(exp) | SequenceItem_::Bind(_, _, exp) => {
+                collect_exp_specs(exp, blocks)
+            }
+            SequenceItem_::Declare(..) => {}
+        }
+    }
+    if let Some(exp) = seq.3.as_ref() {
+        collect_exp_specs(exp, blocks)
+    }
+}
+
+fn collect_exp_specs<'a>(exp: &'a Exp, blocks: &mut Vec<&'a SpecBlock>) {
+    match &exp.value {
+        Exp_::Spec(block) => blocks.push(block),
+        Exp_::Block(seq) => collect_sequence_specs(seq, blocks),
+        Exp_::IfElse(cond, then_exp, else_exp) => {
+            collect_exp_specs(cond, blocks);
+            collect_exp_specs(then_exp, blocks);
+            if let Some(else_exp) = else_exp {
+                collect_exp_specs(else_exp, blocks)
+            }
+        }
+        Exp_::While(cond, body) => {
+            collect_exp_specs(cond, blocks);
+            collect_exp_specs(body, blocks);
+        }
+        Exp_::Loop(body) => collect_exp_specs(body, blocks),
+        _ => {}
+    }
+}
+
+/// Returns the byte ranges of the comments in the given range of the source.
+fn comments_in(source: &str, start: usize, end: usize) -> Vec<(usize, usize)> {
+    let bytes = source.as_bytes();
+    let mut comments = vec![];
+    let mut pos = start;
+    while pos < end {
+        match (bytes[pos], bytes.get(pos + 1)) {
+            (b'/', Some(b'/')) => {
+                let comment_end = source[pos..end].find('\n').map(|i| pos + i).unwrap_or(end);
+                comments.push((pos, source[pos..comment_end].trim_end().len() + pos));
+                pos = comment_end;
+            }
+            (b'/', Some(b'*')) => {
+                let comment_end = source[pos + 2..end]
+                    .find("*/")
+                    .map(|i| pos + i + 4)
+                    .unwrap_or(end);
+                comments.push((pos, comment_end));
+                pos = comment_end;
+            }
+            (b'"', _) => {
+                // Skip byte and hex strings, which may contain comment delimiters.
+                pos += 1;
+                while pos < end && bytes[pos] != b'"' {
+                    pos += if bytes[pos] == b'\\' { 2 } else { 1 };
+                }
+                pos += 1;
+            }
+            _ => pos += 1,
+        }
+    }
+    comments
+}
+
+/// Returns the position of the first character which is neither whitespace nor part of a
+/// comment, starting at the given position.
+fn skip_trivia(source: &str, mut pos: usize) -> usize {
+    loop {
+        let rest = &source[pos..];
+        let trimmed = rest.trim_start();
+        pos += rest.len() - trimmed.len();
+        if trimmed.starts_with("//") {
+            pos = source[pos..]
+                .find('\n')
+                .map(|i| pos + i)
+                .unwrap_or_else(|| source.len());
+        } else if trimmed.starts_with("/*") {
+            pos = source[pos..]
+                .find("*/")
+                .map(|i| pos + i + 2)
+                .unwrap_or_else(|| source.len());
+        } else {
+            return pos;
+        }
+    }
+}
+
+struct SpecFormatter<'a> {
+    source: &'a str,
+}
+
+impl<'a> SpecFormatter<'a> {
+    /// Formats a spec block, returning its new text.
+    fn format_block(&self, block: &SpecBlock) -> String {
+        let (start, end) = (block.loc.start() as usize, block.loc.end() as usize);
+        if !block.value.uses.is_empty() {
+            return self.source[start..end].to_string();
+        }
+        let indent = self.indent_of(start);
+        if let [member] = block.value.members.as_slice() {
+            let member_start = member.loc.start() as usize;
+            if !self.source[start..member_start].contains('{') {
+                // A block of a single member without braces, like `spec fun` or a module
+                // invariant.
+                return format!(
+                    "{}{}",
+                    &self.source[start..member_start],
+                    self.format_member(member, indent)
+                );
+            }
+        }
+        let open = match &block.value.target.value {
+            SpecBlockTarget_::Code => block.value.target.loc.start() as usize,
+            _ => skip_trivia(self.source, block.value.target.loc.end() as usize),
+        };
+        let header = self.source[start..open].trim_end();
+        let inner = indent + INDENT;
+        let comments = comments_in(self.source, open + 1, end - 1);
+        if comments.is_empty() && !self.source[start..end].contains('\n') {
+            // Keep blocks which are written in one line in one line, if they still fit.
+            let column = start - self.line_start(start);
+            let members = block
+                .value
+                .members
+                .iter()
+                .map(|member| self.format_member(member, inner))
+                .collect::<Vec<_>>();
+            let line = if members.is_empty() {
+                format!("{} {{}}", header)
+            } else {
+                format!("{} {{ {} }}", header, members.join(" "))
+            };
+            if !line.contains('\n') && column + line.len() <= PRETTY_WIDTH {
+                return line;
+            }
+        }
+        let mut out = format!("{} {{", header);
+        let mut comments = comments.into_iter().peekable();
+        let mut prev_end = open + 1;
+        let mut first = true;
+        for member in &block.value.members {
+            let (member_start, member_end) =
+                (member.loc.start() as usize, member.loc.end() as usize);
+            while let Some((comment_start, comment_end)) =
+                comments.next_if(|(comment_start, _)| *comment_start < member_start)
+            {
+                self.separate(&mut out, prev_end, comment_start, inner, first, true);
+                out.push_str(&self.source[comment_start..comment_end]);
+                prev_end = comment_end;
+                first = false;
+            }
+            let mut has_comments = false;
+            while comments
+                .next_if(|(comment_start, _)| *comment_start < member_end)
+                .is_some()
+            {
+                has_comments = true;
+            }
+            self.separate(&mut out, prev_end, member_start, inner, first, false);
+            if has_comments {
+                out.push_str(&self.reindent(member_start, member_end, inner));
+            } else {
+                out.push_str(&self.format_member(member, inner));
+            }
+            prev_end = member_end;
+            first = false;
+        }
+        for (comment_start, comment_end) in comments {
+            self.separate(&mut out, prev_end, comment_start, inner, first, true);
+            out.push_str(&self.source[comment_start..comment_end]);
+            prev_end = comment_end;
+            first = false;
+        }
+        if !first {
+            out.push('\n');
+            out.push_str(&" ".repeat(indent));
+        }
+        out.push('}');
+        out
+    }
+
+    /// Formats a member which starts at the given column, keeping it as written if it contains
+    /// comments or parts the formatter does not print.
+    fn format_member(&self, member: &SpecBlockMember, column: usize) -> String {
+        let (start, end) = (member.loc.start() as usize, member.loc.end() as usize);
+        if has_uses(member) || !comments_in(self.source, start, end).is_empty() {
+            self.reindent(start, end, column)
+        } else {
+            render(self.member(member), column)
+        }
+    }
+
+    /// Appends the separator between the previous item of a block, which ends at `prev_end`, and
+    /// the next one, starting at `next_start`. Comments which start on the line where the
+    /// previous item ends stay on that line; otherwise a new line is started, preceded by a
+    /// blank line if there has been at least one between the items.
+    fn separate(
+        &self,
+        out: &mut String,
+        prev_end: usize,
+        next_start: usize,
+        inner: usize,
+        first: bool,
+        is_comment: bool,
+    ) {
+        let newlines = self.source[prev_end..next_start].matches('\n').count();
+        if is_comment && newlines == 0 {
+            out.push(' ');
+            return;
+        }
+        if !first && newlines > 1 {
+            out.push('\n');
+        }
+        out.push('\n');
+        out.push_str(&" ".repeat(inner));
+    }
+
+    /// Returns the indentation of the line which contains the position.
+    fn indent_of(&self, pos: usize) -> usize {
+        let line_start = self.line_start(pos);
+        self.source[line_start..pos].len() - self.source[line_start..pos].trim_start().len()
+    }
+
+    fn line_start(&self, pos: usize) -> usize {
+        self.source[..pos].rfind('\n').map(|i| i + 1).unwrap_or(0)
+    }
+
+    /// Returns the source text in the range, with lines after the first one shifted such that
+    /// the indentation of the line where the text starts becomes the given one.
+    fn reindent(&self, start: usize, end: usize, indent: usize) -> String {
+        let old_indent = self.indent_of(start);
+        self.source[start..end]
+            .lines()
+            .enumerate()
+            .map(|(i, line)| {
+                if i == 0 || line.trim().is_empty() {
+                    return line.trim_end().to_string();
+                }
+                let line_indent = line.len() - line.trim_start().len();
+                format!(
+                    "{}{}",
+                    " ".repeat((line_indent + indent).saturating_sub(old_indent)),
+                    line.trim()
+                )
+            })
+            .join("\n")
+    }
+
+    /// Returns the source text at the location, with whitespace sequences collapsed unless the
+    /// text contains string literals.
+    fn src(&self, loc: ParserLoc) -> Doc {
+        let text = &self.source[loc.start() as usize..loc.end() as usize];
+        if text.contains('"') {
+            Doc::text(text.to_string())
+        } else {
+            Doc::text(text.split_whitespace().join(" "))
+        }
+    }
+
+    // ---------------------------------------------------------------------------------------
+    // Members
+
+    fn member(&self, member: &SpecBlockMember) -> Doc {
+        let doc = match &member.value {
+            SpecBlockMember_::Condition {
+                kind,
+                properties,
+                exp,
+                additional_exps,
+            } => {
+                let head = self
+                    .condition_kind(kind)
+                    .append(self.properties(properties));
+                let body = match &kind.value {
+                    SpecConditionKind_::AbortsWith | SpecConditionKind_::Modifies => {
+                        comma_list(additional_exps.iter().map(|e| self.exp(e)))
+                    }
+                    SpecConditionKind_::AbortsIf if !additional_exps.is_empty() => self
+                        .exp(exp)
+                        .append(Doc::line())
+                        .append("with ")
+                        .append(self.exp(&additional_exps[0])),
+                    SpecConditionKind_::Emits => {
+                        let mut doc = self
+                            .exp(exp)
+                            .append(Doc::line())
+                            .append("to ")
+                            .append(self.exp(&additional_exps[0]));
+                        if let Some(cond) = additional_exps.get(1) {
+                            doc = doc.append(Doc::line()).append("if ").append(self.exp(cond));
+                        }
+                        doc
+                    }
+                    _ => self.exp(exp),
+                };
+                head.append(" ").append(body.nest(INDENT as isize).group())
+            }
+            SpecBlockMember_::Function {
+                uninterpreted,
+                name,
+                signature,
+                body,
+            } => {
+                let native = if !uninterpreted && body.value == FunctionBody_::Native {
+                    "native "
+                } else {
+                    ""
+                };
+                let head = Doc::text(format!("{}fun {}", native, name.0.value))
+                    .append(self.signature(signature));
+                return match &body.value {
+                    FunctionBody_::Native => head.append(";"),
+                    FunctionBody_::Defined(seq) => head.append(" ").append(self.sequence(seq)),
+                };
+            }
+            SpecBlockMember_::Variable {
+                is_global,
+                name,
+                type_parameters,
+                type_,
+                init,
+            } => {
+                let start = member.loc.start() as usize;
+                let keyword = if *is_global {
+                    "global "
+                } else if self.source[start..].starts_with("local") {
+                    "local "
+                } else {
+                    ""
+                };
+                let mut doc = Doc::text(format!("{}{}", keyword, name.value))
+                    .append(self.type_parameters(type_parameters))
+                    .append(": ")
+                    .append(self.ty(type_));
+                if let Some(init) = init {
+                    doc = doc.append(" = ").append(self.exp(init));
+                }
+                doc
+            }
+            SpecBlockMember_::Let {
+                name,
+                post_state,
+                def,
+            } => Doc::text(format!(
+                "let {}{} = ",
+                if *post_state { "post " } else { "" },
+                name.value
+            ))
+            .append(self.exp(def).nest(INDENT as isize).group()),
+            SpecBlockMember_::Update { lhs, rhs } => Doc::text("update ")
+                .append(self.exp(lhs))
+                .append(" = ")
+                .append(self.exp(rhs).nest(INDENT as isize).group()),
+            SpecBlockMember_::Include { properties, exp } => Doc::text("include")
+                .append(self.properties(properties))
+                .append(" ")
+                .append(self.exp(exp).nest(INDENT as isize).group()),
+            SpecBlockMember_::Apply {
+                exp,
+                patterns,
+                exclusion_patterns,
+            } => {
+                let mut doc = Doc::text("apply ")
+                    .append(self.exp(exp))
+                    .append(Doc::line())
+                    .append("to ")
+                    .append(comma_list(patterns.iter().map(|p| self.src(p.loc))));
+                if !exclusion_patterns.is_empty() {
+                    doc = doc.append(Doc::line()).append("except ").append(comma_list(
+                        exclusion_patterns.iter().map(|p| self.src(p.loc)),
+                    ));
+                }
+                doc.nest(INDENT as isize).group()
+            }
+            SpecBlockMember_::Pragma { properties } => Doc::text("pragma ")
+                .append(comma_list(properties.iter().map(|p| self.property(p))))
+                .nest(INDENT as isize)
+                .group(),
+        };
+        doc.append(";")
+    }
+
+    fn condition_kind(&self, kind: &SpecConditionKind) -> Doc {
+        use SpecConditionKind_::*;
+        match &kind.value {
+            Assert => Doc::text("assert"),
+            Assume => Doc::text("assume"),
+            Decreases => Doc::text("decreases"),
+            AbortsIf => Doc::text("aborts_if"),
+            AbortsWith => Doc::text("aborts_with"),
+            SucceedsIf => Doc::text("succeeds_if"),
+            Modifies => Doc::text("modifies"),
+            Emits => Doc::text("emits"),
+            Ensures => Doc::text("ensures"),
+            Requires => Doc::text("requires"),
+            Invariant(tps) => Doc::text("invariant").append(self.type_parameters(tps)),
+            InvariantUpdate(tps) => Doc::text("invariant")
+                .append(self.type_parameters(tps))
+                .append(" update"),
+            Axiom(tps) => Doc::text("axiom").append(self.type_parameters(tps)),
+        }
+    }
+
+    fn properties(&self, properties: &[PragmaProperty]) -> Doc {
+        if properties.is_empty() {
+            Doc::nil()
+        } else {
+            Doc::text(" ").append(delimited(
+                "[",
+                properties.iter().map(|p| self.property(p)),
+                "]",
+            ))
+        }
+    }
+
+    fn property(&self, property: &PragmaProperty) -> Doc {
+        let name = Doc::text(property.value.name.value.to_string());
+        match &property.value.value {
+            None => name,
+            Some(PragmaValue::Literal(value)) => name.append(" = ").append(self.src(value.loc)),
+            Some(PragmaValue::Ident(chain)) => name.append(" = ").append(self.src(chain.loc)),
+        }
+    }
+
+    fn signature(&self, signature: &FunctionSignature) -> Doc {
+        self.type_parameters(&signature.type_parameters)
+            .append(delimited(
+                "(",
+                signature
+                    .parameters
+                    .iter()
+                    .map(|(var, ty)| Doc::text(format!("{}: ", var.0.value)).append(self.ty(ty))),
+                ")",
+            ))
+            .append(": ")
+            .append(self.ty(&signature.return_type))
+    }
+
+    fn type_parameters(&self, type_parameters: &[(Name, Vec<Ability>)]) -> Doc {
+        if type_parameters.is_empty() {
+            return Doc::nil();
+        }
+        delimited(
+            "<",
+            type_parameters.iter().map(|(name, abilities)| {
+                if abilities.is_empty() {
+                    Doc::text(name.value.to_string())
+                } else {
+                    Doc::text(format!(
+                        "{}: {}",
+                        name.value,
+                        abilities.iter().map(|a| a.value.to_string()).join(" + ")
+                    ))
+                }
+            }),
+            ">",
+        )
+    }
+
+    // ---------------------------------------------------------------------------------------
+    // Types
+
+    fn ty(&self, ty: &Type) -> Doc {
+        match &ty.value {
+            Type_::Apply(name, tys) => self.src(name.loc).append(self.type_args(tys)),
+            Type_::Ref(is_mut, ty) => {
+                Doc::text(if *is_mut { "&mut " } else { "&" }).append(self.ty(ty))
+            }
+            Type_::Fun(args, result) => {
+                pipes(args.iter().map(|t| self.ty(t))).append(self.ty(result))
+            }
+            Type_::Unit => Doc::text("()"),
+            Type_::Multiple(tys) => delimited("(", tys.iter().map(|t| self.ty(t)), ")"),
+        }
+    }
+
+    fn type_args(&self, tys: &[Type]) -> Doc {
+        if tys.is_empty() {
+            Doc::nil()
+        } else {
+            delimited("<", tys.iter().map(|t| self.ty(t)), ">")
+        }
+    }
+
+    fn opt_type_args(&self, tys: &Option<Vec<Type>>) -> Doc {
+        tys.as_ref()
+            .map(|tys| self.type_args(tys))
+            .unwrap_or_else(Doc::nil)
+    }
+
+    // ---------------------------------------------------------------------------------------
+    // Expressions
+
+    fn exp(&self, exp: &Exp) -> Doc {
+        match &exp.value {
+            Exp_::Value(value) => self.src(value.loc),
+            Exp_::Name(name, tys) => self.src(name.loc).append(self.opt_type_args(tys)),
+            Exp_::Call(name, is_macro, tys, args) => self
+                .src(name.loc)
+                .append(if *is_macro { "!" } else { "" })
+                .append(self.opt_type_args(tys))
+                .append(delimited("(", args.value.iter().map(|e| self.exp(e)), ")")),
+            Exp_::Pack(name, tys, fields) => {
+                let doc = self.src(name.loc).append(self.opt_type_args(tys));
+                if fields.is_empty() {
+                    return doc.append(" {}");
+                }
+                let fields = fields.iter().map(|(field, e)| match &e.value {
+                    Exp_::Name(sp!(_, NameAccessChain_::One(n)), None)
+                        if n.value == field.0.value =>
+                    {
+                        Doc::text(n.value.to_string())
+                    }
+                    _ => Doc::text(format!("{}: ", field.0.value)).append(self.exp(e)),
+                });
+                doc.append(" {")
+                    .append(Doc::line().append(comma_list(fields)).nest(INDENT as isize))
+                    .append(Doc::line())
+                    .append("}")
+                    .group()
+            }
+            Exp_::Vector(_, tys, args) => Doc::text("vector")
+                .append(self.opt_type_args(tys))
+                .append(delimited("[", args.value.iter().map(|e| self.exp(e)), "]")),
+            Exp_::IfElse(cond, then_exp, else_exp) => {
+                let mut doc = Doc::text("if (")
+                    .append(self.exp(cond))
+                    .append(")")
+                    .append(Doc::line().append(self.exp(then_exp)).nest(INDENT as isize));
+                if let Some(else_exp) = else_exp {
+                    doc = doc
+                        .append(Doc::line())
+                        .append("else")
+                        .append(Doc::line().append(self.exp(else_exp)).nest(INDENT as isize));
+                }
+                doc.group()
+            }
+            Exp_::Block(seq) if seq.0.is_empty() => self.sequence(seq),
+            Exp_::Lambda(binds, body) => pipes(binds.value.iter().map(|b| self.src(b.loc)))
+                .append(" ")
+                .append(self.exp(body)),
+            Exp_::Quant(kind, binds, triggers, cond, body) => {
+                self.quant(kind, binds, triggers, cond, body)
+            }
+            Exp_::ExpList(exps) => delimited("(", exps.iter().map(|e| self.exp(e)), ")"),
+            Exp_::Unit => Doc::text("()"),
+            Exp_::Dereference(e) => Doc::text("*").append(self.operand(e)),
+            Exp_::UnaryExp(op, e) => Doc::text(op.value.to_string()).append(self.operand(e)),
+            Exp_::Borrow(is_mut, e) => {
+                Doc::text(if *is_mut { "&mut " } else { "&" }).append(self.operand(e))
+            }
+            Exp_::BinopExp(..) => self.binop(exp),
+            Exp_::Dot(e, name) => self.postfix_operand(e).append(format!(".{}", name.value)),
+            Exp_::Index(e, index) => self
+                .postfix_operand(e)
+                .append("[")
+                .append(self.exp(index))
+                .append("]"),
+            Exp_::Cast(e, ty) => Doc::text("(")
+                .append(self.exp(e))
+                .append(" as ")
+                .append(self.ty(ty))
+                .append(")"),
+            Exp_::Annotate(e, ty) => Doc::text("(")
+                .append(self.exp(e))
+                .append(": ")
+                .append(self.ty(ty))
+                .append(")"),
+            _ => self.src(exp.loc),
+        }
+    }
+
+    /// Prints a chain of binary operations of the same precedence, breaking all of them if the
+    /// chain does not fit into a line.
+    fn binop(&self, exp: &Exp) -> Doc {
+        let prec = match &exp.value {
+            Exp_::BinopExp(_, op, _) => precedence(op.value),
+            _ => unreachable!("binary operation"),
+        };
+        let mut rest = vec![];
+        let mut lhs = exp;
+        while let Exp_::BinopExp(l, op, r) = &lhs.value {
+            if precedence(op.value) != prec {
+                break;
+            }
+            rest.push((op, r));
+            lhs = l;
+        }
+        let first = self.binop_operand(lhs, prec, false);
+        let rest = rest.into_iter().rev().map(|(op, r)| {
+            let op = if op.value == BinOp_::Range {
+                Doc::text("..")
+            } else {
+                Doc::line().append(format!("{} ", op.value))
+            };
+            op.append(self.binop_operand(r, prec, true))
+        });
+        first
+            .append(Doc::concat(rest).nest(INDENT as isize))
+            .group()
+    }
+
+    fn binop_operand(&self, exp: &Exp, prec: u32, is_rhs: bool) -> Doc {
+        let needs_parens = match &exp.value {
+            Exp_::BinopExp(_, op, _) => {
+                let op_prec = precedence(op.value);
+                op_prec < prec || is_rhs && op_prec == prec
+            }
+            _ => !is_simple(exp),
+        };
+        self.wrapped(exp, needs_parens)
+    }
+
+    /// Prints the operand of a prefix operator.
+    fn operand(&self, exp: &Exp) -> Doc {
+        let needs_parens = matches!(exp.value, Exp_::BinopExp(..)) || !is_simple(exp);
+        self.wrapped(exp, needs_parens)
+    }
+
+    /// Prints the operand of a field selection or index.
+    fn postfix_operand(&self, exp: &Exp) -> Doc {
+        let needs_parens = matches!(
+            exp.value,
+            Exp_::BinopExp(..) | Exp_::UnaryExp(..) | Exp_::Dereference(..) | Exp_::Borrow(..)
+        ) || !is_simple(exp);
+        self.wrapped(exp, needs_parens)
+    }
+
+    fn wrapped(&self, exp: &Exp, needs_parens: bool) -> Doc {
+        if needs_parens {
+            Doc::text("(").append(self.exp(exp)).append(")")
+        } else {
+            self.exp(exp)
+        }
+    }
+
+    fn quant(
+        &self,
+        kind: &QuantKind,
+        binds: &BindWithRangeList,
+        triggers: &[Vec<Exp>],
+        cond: &Option<Box<Exp>>,
+        body: &Exp,
+    ) -> Doc {
+        let head = match kind.value {
+            QuantKind_::Forall => "forall ",
+            QuantKind_::Exists => "exists ",
+            QuantKind_::Choose => "choose ",
+            QuantKind_::ChooseMin => "choose min ",
+        };
+        let binds = comma_list(binds.value.iter().map(|bind| {
+            let (var, range) = &bind.value;
+            match &range.value {
+                Exp_::Call(sp!(_, NameAccessChain_::One(name)), false, Some(tys), _)
+                    if name.value.as_str() == "$spec_domain" =>
+                {
+                    self.src(var.loc).append(": ").append(self.ty(&tys[0]))
+                }
+                _ => self.src(var.loc).append(" in ").append(self.exp(range)),
+            }
+        }));
+        let mut doc = Doc::text(head).append(binds.group());
+        for trigger in triggers {
+            doc = doc
+                .append(" ")
+                .append(delimited("{", trigger.iter().map(|e| self.exp(e)), "}"));
+        }
+        if matches!(kind.value, QuantKind_::Choose | QuantKind_::ChooseMin) {
+            return doc
+                .append(" where")
+                .append(Doc::line())
+                .append(self.exp(body))
+                .group();
+        }
+        if let Some(cond) = cond {
+            doc = doc.append(" where ").append(self.exp(cond).group());
+        }
+        // The body is not indented relative to the quantifier, as it is usually the whole
+        // condition.
+        doc.append(":")
+            .append(Doc::line())
+            .append(self.exp(body))
+            .group()
+    }
+
+    /// Prints a sequence without `use` declarations as a block, one item per line.
+    fn sequence(&self, seq: &Sequence) -> Doc {
+        let (_, items, _, final_exp) = seq;
+        let mut docs = items
+            .iter()
+            .map(|item| match &item.value {
+                SequenceItem_::Seq(e) => self.exp(e).append(";"),
+                SequenceItem_::Declare(binds, ty) => Doc::text("let ")
+                    .append(self.binds(binds))
+                    .append(self.opt_type_annotation(ty))
+                    .append(";"),
+                SequenceItem_::Bind(binds, ty, e) => Doc::text("let ")
+                    .append(self.binds(binds))
+                    .append(self.opt_type_annotation(ty))
+                    .append(" = ")
+                    .append(self.exp(e).nest(INDENT as isize).group())
+                    .append(";"),
+            })
+            .collect::<Vec<_>>();
+        if let Some(e) = final_exp.as_ref() {
+            docs.push(self.exp(e).group())
+        }
+        if docs.is_empty() {
+            return Doc::text("{}");
+        }
+        Doc::text("{")
+            .append(
+                Doc::hardline()
+                    .append(Doc::intersperse(docs, Doc::hardline()))
+                    .nest(INDENT as isize),
+            )
+            .append(Doc::hardline())
+            .append("}")
+    }
+
+    fn binds(&self, binds: &BindList) -> Doc {
+        if binds.value.len() == 1 {
+            self.src(binds.value[0].loc)
+        } else {
+            delimited("(", binds.value.iter().map(|b| self.src(b.loc)), ")")
+        }
+    }
+
+    fn opt_type_annotation(&self, ty: &Option<Type>) -> Doc {
+        match ty {
+            Some(ty) => Doc::text(": ").append(self.ty(ty)),
+            None => Doc::nil(),
+        }
+    }
+}
+
+/// Returns true if the member is a spec function whose body has `use` declarations, which the
+/// formatter does not print.
+fn has_uses(member: &SpecBlockMember) -> bool {
+    matches!(
+        &member.value,
+        SpecBlockMember_::Function {
+            body: sp!(_, FunctionBody_::Defined((uses, ..))),
+            ..
+        } if !uses.is_empty()
+    )
+}
+
+/// Returns the precedence of a binary operator, as used by the parser.
+fn precedence(op: BinOp_) -> u32 {
+    use BinOp_::*;
+    match op {
+        Implies | Iff => 2,
+        Or => 3,
+        And => 4,
+        Eq | Neq | Lt | Gt | Le | Ge => 5,
+        Range => 6,
+        BitOr => 7,
+        Xor => 8,
+        BitAnd => 9,
+        Shl | Shr => 10,
+        Add | Sub => 11,
+        Mul | Div | Mod => 12,
+    }
+}
+
+/// Returns true if the expression does not need parentheses when used as an operand, apart
+/// from binary operations, whose need depends on precedence.
+fn is_simple(exp: &Exp) -> bool {
+    !matches!(
+        exp.value,
+        Exp_::IfElse(..)
+            | Exp_::While(..)
+            | Exp_::Loop(..)
+            | Exp_::Lambda(..)
+            | Exp_::Quant(..)
+            | Exp_::Assign(..)
+            | Exp_::Return(..)
+            | Exp_::Abort(..)
+    )
+}
+
+/// Returns the items separated by commas, breaking after the commas if they do not fit into a
+/// line.
+fn comma_list(items: impl Iterator<Item = Doc>) -> Doc {
+    Doc::intersperse(items, Doc::text(",").append(Doc::line()))
+}
+
+/// Returns the items enclosed by delimiters, breaking the list into one item per line if it
+/// does not fit into a line.
+fn delimited(open: &'static str, items: impl Iterator<Item = Doc>, close: &'static str) -> Doc {
+    let items = items.collect::<Vec<_>>();
+    if items.is_empty() {
+        return Doc::text(open).append(close);
+    }
+    Doc::text(open)
+        .append(
+            Doc::line_()
+                .append(comma_list(items.into_iter()))
+                .nest(INDENT as isize),
+        )
+        .append(Doc::line_())
+        .append(close)
+        .group()
+}
+
+/// Returns the items enclosed by pipes, as in function types and lambdas. An empty list is
+/// printed as `| |`, since `||` is an operator.
+fn pipes(items: impl Iterator<Item = Doc>) -> Doc {
+    let items = items.collect::<Vec<_>>();
+    if items.is_empty() {
+        Doc::text("| |")
+    } else {
+        Doc::text("|")
+            .append(comma_list(items.into_iter()))
+            .append("|")
+    }
+}
+
+/// Renders a document as a member of a block whose members start at the given column.
+fn render(doc: Doc, column: usize) -> String {
+    let width = PRETTY_WIDTH.saturating_sub(column).max(INDENT * 4);
+    doc.pretty(width)
+        .to_string()
+        .lines()
+        .enumerate()
+        .map(|(i, line)| {
+            let line = line.trim_end();
+            if i == 0 || line.is_empty() {
+                line.to_string()
+            } else {
+                format!("{}{}", " ".repeat(column), line)
+            }
+        })
+        .join("\n")
+}
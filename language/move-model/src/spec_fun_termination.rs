@@ -0,0 +1,431 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Termination check for recursive spec functions.
+//!
+//! The definition of a spec function is translated into an axiom for the backend. For a
+//! recursive function this axiom is a fixpoint equation, which is only consistent if the
+//! recursion terminates: `fun f(x: num): num { f(x) + 1 }` would make any verification
+//! condition trivially true. The check looks, for each group of mutually recursive spec
+//! functions, for a measure: one numeric parameter per function such that at each recursive
+//! call the argument for the parameter of the callee is either
+//!
+//! - the parameter of the caller itself, or
+//! - `p - c` with a constant `c > 0`, or `p / c` with a constant `c > 1`, where the conditions
+//!   under which the call happens (enclosing `if`, `&&`, `||` and `==>`) bound `p` from below
+//!   such that the argument is not negative,
+//!
+//! and each cycle of calls contains at least one call of the second kind. If such a measure is
+//! found, the index of the parameter is recorded in `SpecFunDecl::decreasing_param`; otherwise
+//! a warning is reported, and the backend does not make use of the definition.
+
+use crate::{
+    ast::{Exp, ExpData, Operation, SpecFunDecl, Value},
+    model::{GlobalEnv, Loc, QualifiedId, SpecFunId},
+    symbol::Symbol,
+};
+use codespan_reporting::diagnostic::Severity;
+use num::{BigInt, One};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The maximal number of combinations of parameters which are tried as a measure for a group of
+/// mutually recursive functions.
+const MAX_MEASURE_CANDIDATES: usize = 256;
+
+/// Checks termination of the recursive spec functions in the environment, recording the
+/// decreasing parameter of each function for which termination is established. Warnings are
+/// only reported for functions in target modules.
+pub fn check_spec_fun_termination(env: &GlobalEnv) {
+    let mut checked = BTreeSet::new();
+    for module_env in env.get_modules() {
+        for (id, decl) in module_env.get_spec_funs() {
+            let fun_id = module_env.get_id().qualified(*id);
+            if decl.is_move_fun
+                || decl.body.is_none()
+                || checked.contains(&fun_id)
+                || !env.is_spec_fun_recursive(fun_id)
+            {
+                continue;
+            }
+            let members = recursive_component(env, fun_id);
+            checked.extend(members.iter().cloned());
+            let calls = collect_calls(env, &members);
+            match find_measure(env, &members, &calls) {
+                Some(measure) => {
+                    for (member, param) in measure {
+                        *get_decl(env, member).decreasing_param.borrow_mut() = Some(param);
+                    }
+                }
+                None => {
+                    // Report at the first recursive call in the definition of each member.
+                    for member in &members {
+                        if !env.get_module(member.module_id).is_target() {
+                            continue;
+                        }
+                        let member_decl = get_decl(env, *member);
+                        let loc = calls
+                            .iter()
+                            .find(|call| call.caller == *member)
+                            .map(|call| &call.loc)
+                            .unwrap_or(&member_decl.loc);
+                        env.diag(
+                            Severity::Warning,
+                            loc,
+                            &format!(
+                                "cannot establish termination of recursive spec fun `{}`: \
+                                 no parameter was found which decreases at each recursive call",
+                                member_decl.name.display(env.symbol_pool())
+                            ),
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn get_decl(env: &GlobalEnv, fun_id: QualifiedId<SpecFunId>) -> &SpecFunDecl {
+    &env.module_data[fun_id.module_id.to_usize()].spec_funs[&fun_id.id]
+}
+
+/// Returns the functions which are mutually recursive with the given one, including itself.
+fn recursive_component(
+    env: &GlobalEnv,
+    fun_id: QualifiedId<SpecFunId>,
+) -> BTreeSet<QualifiedId<SpecFunId>> {
+    let reachable = |start: QualifiedId<SpecFunId>| {
+        let mut visited = BTreeSet::new();
+        let mut todo = vec![start];
+        while let Some(next) = todo.pop() {
+            for callee in &get_decl(env, next).callees {
+                if visited.insert(*callee) {
+                    todo.push(*callee);
+                }
+            }
+        }
+        visited
+    };
+    reachable(fun_id)
+        .into_iter()
+        .filter(|callee| *callee == fun_id || reachable(*callee).contains(&fun_id))
+        .chain(std::iter::once(fun_id))
+        .collect()
+}
+
+/// A call between two members of a recursive component.
+struct RecursiveCall {
+    loc: Loc,
+    caller: QualifiedId<SpecFunId>,
+    callee: QualifiedId<SpecFunId>,
+    args: Vec<Exp>,
+    /// The conditions under which the call happens, with their polarity.
+    guards: Vec<(Exp, bool)>,
+    /// The variables bound by quantifiers, lets, and lambdas around the call.
+    bound: BTreeSet<Symbol>,
+}
+
+/// How the measure changes at a recursive call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Decrease {
+    Strict,
+    NonStrict,
+}
+
+/// Collects the calls between the members of the component, in the order of the members.
+fn collect_calls(
+    env: &GlobalEnv,
+    members: &BTreeSet<QualifiedId<SpecFunId>>,
+) -> Vec<RecursiveCall> {
+    let mut calls = vec![];
+    for member in members {
+        if let Some(body) = &get_decl(env, *member).body {
+            let mut collector = CallCollector {
+                env,
+                members,
+                caller: *member,
+                guards: vec![],
+                calls: vec![],
+            };
+            collector.collect(body, &BTreeSet::new());
+            calls.extend(collector.calls);
+        }
+    }
+    calls
+}
+
+/// Searches for a decreasing parameter for each member of the component.
+fn find_measure(
+    env: &GlobalEnv,
+    members: &BTreeSet<QualifiedId<SpecFunId>>,
+    calls: &[RecursiveCall],
+) -> Option<BTreeMap<QualifiedId<SpecFunId>, usize>> {
+    // The candidates for each member are its numeric parameters.
+    let candidates = members
+        .iter()
+        .map(|member| {
+            let params = get_decl(env, *member)
+                .params
+                .iter()
+                .enumerate()
+                .filter(|(_, (_, ty))| ty.is_number())
+                .map(|(idx, _)| idx)
+                .collect::<Vec<_>>();
+            (*member, params)
+        })
+        .collect::<Vec<_>>();
+    let count = candidates
+        .iter()
+        .try_fold(1usize, |acc, (_, params)| acc.checked_mul(params.len()))?;
+    if count == 0 || count > MAX_MEASURE_CANDIDATES {
+        return None;
+    }
+    (0..count).find_map(|mut combination| {
+        let measure = candidates
+            .iter()
+            .map(|(member, params)| {
+                let param = params[combination % params.len()];
+                combination /= params.len();
+                (*member, param)
+            })
+            .collect::<BTreeMap<_, _>>();
+        if is_decreasing(env, &measure, calls) {
+            Some(measure)
+        } else {
+            None
+        }
+    })
+}
+
+/// Checks whether the measure decreases at each call, strictly on each cycle of calls.
+fn is_decreasing(
+    env: &GlobalEnv,
+    measure: &BTreeMap<QualifiedId<SpecFunId>, usize>,
+    calls: &[RecursiveCall],
+) -> bool {
+    let mut non_strict: BTreeMap<_, BTreeSet<_>> = BTreeMap::new();
+    for call in calls {
+        let (param, _) = &get_decl(env, call.caller).params[measure[&call.caller]];
+        match decrease(*param, call, &call.args[measure[&call.callee]]) {
+            Some(Decrease::Strict) => {}
+            Some(Decrease::NonStrict) => {
+                non_strict
+                    .entry(call.caller)
+                    .or_default()
+                    .insert(call.callee);
+            }
+            None => return false,
+        }
+    }
+    // The calls which do not decrease the measure strictly must not form a cycle.
+    fn on_cycle(
+        edges: &BTreeMap<QualifiedId<SpecFunId>, BTreeSet<QualifiedId<SpecFunId>>>,
+        path: &mut Vec<QualifiedId<SpecFunId>>,
+        done: &mut BTreeSet<QualifiedId<SpecFunId>>,
+        node: QualifiedId<SpecFunId>,
+    ) -> bool {
+        if path.contains(&node) {
+            return true;
+        }
+        if !done.insert(node) {
+            return false;
+        }
+        path.push(node);
+        let found = edges
+            .get(&node)
+            .map(|succs| succs.iter().any(|n| on_cycle(edges, path, done, *n)))
+            .unwrap_or(false);
+        path.pop();
+        found
+    }
+    let mut done = BTreeSet::new();
+    !measure
+        .keys()
+        .any(|member| on_cycle(&non_strict, &mut vec![], &mut done, *member))
+}
+
+/// Determines how the argument for the measure of the callee relates to the measure `param` of
+/// the caller.
+fn decrease(param: Symbol, call: &RecursiveCall, arg: &Exp) -> Option<Decrease> {
+    if call.bound.contains(&param) {
+        return None;
+    }
+    let is_param = |e: &Exp| matches!(e.as_ref(), ExpData::LocalVar(_, s) if *s == param);
+    match arg.as_ref() {
+        ExpData::LocalVar(..) if is_param(arg) => Some(Decrease::NonStrict),
+        ExpData::Call(_, op @ Operation::Sub, args)
+        | ExpData::Call(_, op @ Operation::Div, args)
+            if is_param(&args[0]) =>
+        {
+            let c = as_number(&args[1])?;
+            let required = if matches!(op, Operation::Sub) {
+                // `p - c` is smaller than `p`, and not negative if `p >= c`.
+                if c < BigInt::one() {
+                    return None;
+                }
+                c
+            } else {
+                // `p / c` is smaller than `p` if `p > 0`, and not negative then.
+                if c <= BigInt::one() {
+                    return None;
+                }
+                BigInt::one()
+            };
+            let bound = call
+                .guards
+                .iter()
+                .flat_map(|(guard, positive)| lower_bounds(param, guard, *positive))
+                .max()?;
+            if bound >= required {
+                Some(Decrease::Strict)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Returns the lower bounds of `param` implied by the guard if it has the given polarity.
+fn lower_bounds(param: Symbol, guard: &Exp, positive: bool) -> Vec<BigInt> {
+    use Operation::*;
+    let is_param = |e: &Exp| matches!(e.as_ref(), ExpData::LocalVar(_, s) if *s == param);
+    match guard.as_ref() {
+        ExpData::Call(_, Not, args) => lower_bounds(param, &args[0], !positive),
+        ExpData::Call(_, And, args) if positive => args
+            .iter()
+            .flat_map(|arg| lower_bounds(param, arg, positive))
+            .collect(),
+        ExpData::Call(_, Or, args) if !positive => args
+            .iter()
+            .flat_map(|arg| lower_bounds(param, arg, positive))
+            .collect(),
+        ExpData::Call(_, op, args) if args.len() == 2 => {
+            // Normalize the comparison to `param op k`.
+            let (op, k) = if is_param(&args[0]) {
+                (op.clone(), as_number(&args[1]))
+            } else if is_param(&args[1]) {
+                let flipped = match op {
+                    Lt => Gt,
+                    Le => Ge,
+                    Gt => Lt,
+                    Ge => Le,
+                    _ => op.clone(),
+                };
+                (flipped, as_number(&args[0]))
+            } else {
+                return vec![];
+            };
+            let k = match k {
+                Some(k) => k,
+                None => return vec![],
+            };
+            match (op, positive) {
+                (Gt, true) | (Le, false) => vec![k + BigInt::one()],
+                (Ge, true) | (Lt, false) | (Eq, true) => vec![k],
+                _ => vec![],
+            }
+        }
+        _ => vec![],
+    }
+}
+
+fn as_number(exp: &Exp) -> Option<BigInt> {
+    match exp.as_ref() {
+        ExpData::Value(_, Value::Number(n)) => Some(n.clone()),
+        _ => None,
+    }
+}
+
+/// Collects the calls to members of a component in the body of one of its members, together
+/// with the conditions under which they happen.
+struct CallCollector<'a> {
+    env: &'a GlobalEnv,
+    members: &'a BTreeSet<QualifiedId<SpecFunId>>,
+    caller: QualifiedId<SpecFunId>,
+    guards: Vec<(Exp, bool)>,
+    calls: Vec<RecursiveCall>,
+}
+
+impl<'a> CallCollector<'a> {
+    fn collect(&mut self, exp: &Exp, bound: &BTreeSet<Symbol>) {
+        match exp.as_ref() {
+            ExpData::IfElse(_, cond, if_true, if_false) => {
+                self.collect(cond, bound);
+                self.collect_guarded(if_true, cond, true, bound);
+                self.collect_guarded(if_false, cond, false, bound);
+            }
+            ExpData::Call(_, op @ (Operation::And | Operation::Or | Operation::Implies), args)
+                if args.len() == 2 =>
+            {
+                self.collect(&args[0], bound);
+                self.collect_guarded(&args[1], &args[0], !matches!(op, Operation::Or), bound);
+            }
+            ExpData::Call(id, op, args) => {
+                if let Operation::Function(mid, fid, _) = op {
+                    let callee = mid.qualified(*fid);
+                    if self.members.contains(&callee) {
+                        self.calls.push(RecursiveCall {
+                            loc: self.env.get_node_loc(*id),
+                            caller: self.caller,
+                            callee,
+                            args: args.clone(),
+                            guards: self.guards.clone(),
+                            bound: bound.clone(),
+                        });
+                    }
+                }
+                for arg in args {
+                    self.collect(arg, bound);
+                }
+            }
+            ExpData::Invoke(_, target, args) => {
+                self.collect(target, bound);
+                for arg in args {
+                    self.collect(arg, bound);
+                }
+            }
+            ExpData::Lambda(_, decls, body) => {
+                let mut inner = bound.clone();
+                inner.extend(decls.iter().map(|decl| decl.name));
+                self.collect(body, &inner);
+            }
+            ExpData::Quant(_, _, ranges, triggers, condition, body) => {
+                for (_, range) in ranges {
+                    self.collect(range, bound);
+                }
+                let mut inner = bound.clone();
+                inner.extend(ranges.iter().map(|(decl, _)| decl.name));
+                for exp in triggers.iter().flatten().chain(condition) {
+                    self.collect(exp, &inner);
+                }
+                self.collect(body, &inner);
+            }
+            ExpData::Block(_, decls, body) => {
+                let mut inner = bound.clone();
+                for decl in decls {
+                    if let Some(binding) = &decl.binding {
+                        self.collect(binding, &inner);
+                    }
+                    inner.insert(decl.name);
+                }
+                self.collect(body, &inner);
+            }
+            ExpData::Value(..)
+            | ExpData::LocalVar(..)
+            | ExpData::Temporary(..)
+            | ExpData::Invalid(..) => {}
+        }
+    }
+
+    fn collect_guarded(
+        &mut self,
+        exp: &Exp,
+        guard: &Exp,
+        positive: bool,
+        bound: &BTreeSet<Symbol>,
+    ) {
+        self.guards.push((guard.clone(), positive));
+        self.collect(exp, bound);
+        self.guards.pop();
+    }
+}
@@ -0,0 +1,198 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Complexity metrics of specification conditions.
+//!
+//! The metrics indicate which conditions are likely to be hard for the solver, before running
+//! verification: the nesting depth of quantifiers, the number of terms, the number of references
+//! to the pre-state via `old(..)`, and how often a condition is instantiated by including or
+//! applying the schema which declares it. A condition included from a schema appears in the
+//! spec of each function including the schema, with the location it has in the schema.
+
+use crate::{
+    ast::{Condition, ConditionKind, ExpData, Operation, Spec},
+    model::{GlobalEnv, Loc},
+};
+use std::collections::BTreeMap;
+
+/// Conditions whose quantifiers are nested deeper than this are reported as complex.
+const MAX_QUANTIFIER_DEPTH: usize = 1;
+
+/// Conditions with more terms than this are reported as complex.
+const MAX_TERM_COUNT: usize = 150;
+
+/// The metrics of a single condition.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ConditionMetrics {
+    /// The maximal nesting depth of quantifiers.
+    pub quantifier_depth: usize,
+    /// The number of expression nodes, over all expressions of the condition.
+    pub term_count: usize,
+    /// The number of `old(..)` expressions.
+    pub old_references: usize,
+}
+
+/// A condition of a spec, together with its metrics.
+#[derive(Debug, Clone)]
+pub struct ConditionEntry {
+    pub loc: Loc,
+    pub kind: ConditionKind,
+    pub metrics: ConditionMetrics,
+    /// Whether the condition has been included from a schema.
+    pub included: bool,
+    /// The number of specs in the model which contain the condition.
+    pub expansions: usize,
+}
+
+/// The conditions of the spec of a function, struct, or module, with their metrics. Lets are
+/// not counted as conditions.
+#[derive(Debug, Clone)]
+pub struct SpecMetrics {
+    /// The name of the specified item, e.g. `fun M::f`.
+    pub name: String,
+    pub conditions: Vec<ConditionEntry>,
+}
+
+impl ConditionMetrics {
+    /// Computes the metrics of a condition.
+    pub fn of(cond: &Condition) -> Self {
+        let mut metrics = ConditionMetrics::default();
+        let mut depth = 0;
+        for exp in cond.all_exps() {
+            exp.visit_pre_post(&mut |up, e| {
+                if let ExpData::Quant(..) = e {
+                    if !up {
+                        depth += 1;
+                        metrics.quantifier_depth = metrics.quantifier_depth.max(depth);
+                    } else {
+                        depth -= 1;
+                    }
+                }
+                if !up {
+                    metrics.term_count += 1;
+                    if let ExpData::Call(_, Operation::Old, _) = e {
+                        metrics.old_references += 1;
+                    }
+                }
+            });
+        }
+        metrics
+    }
+
+    /// Returns true if the metrics exceed the thresholds beyond which conditions often cause
+    /// solver trouble.
+    pub fn is_complex(&self) -> bool {
+        self.quantifier_depth > MAX_QUANTIFIER_DEPTH || self.term_count > MAX_TERM_COUNT
+    }
+}
+
+impl SpecMetrics {
+    /// Returns the number of conditions in the spec, relative to the number of conditions
+    /// written in the spec itself, or to 1 if all conditions are included from schemas. This is
+    /// 1 if the spec does not include schemas.
+    pub fn schema_expansion_factor(&self) -> f64 {
+        let own = self.conditions.iter().filter(|c| !c.included).count();
+        if own == 0 {
+            self.conditions.len() as f64
+        } else {
+            self.conditions.len() as f64 / own as f64
+        }
+    }
+}
+
+/// Computes the metrics of the specs of the target modules, skipping specs without conditions.
+pub fn spec_metrics(env: &GlobalEnv) -> Vec<SpecMetrics> {
+    let mut result = vec![];
+    for module_env in env.get_modules().filter(|m| m.is_target()) {
+        let module_name = module_env.get_full_name_str();
+        result.push(metrics_of(
+            format!("module {}", module_name),
+            module_env.get_spec(),
+        ));
+        for struct_env in module_env.get_structs() {
+            result.push(metrics_of(
+                format!(
+                    "struct {}::{}",
+                    module_name,
+                    struct_env.get_name().display(env.symbol_pool())
+                ),
+                struct_env.get_spec(),
+            ));
+        }
+        for fun_env in module_env.get_functions() {
+            let spec = fun_env.get_spec();
+            let mut metrics = metrics_of(format!("fun {}", fun_env.get_full_name_str()), spec);
+            for code_spec in spec.on_impl.values() {
+                metrics
+                    .conditions
+                    .extend(metrics_of(String::new(), code_spec).conditions);
+            }
+            result.push(metrics);
+        }
+    }
+    result.retain(|m| !m.conditions.is_empty());
+    let mut expansions = BTreeMap::new();
+    for entry in result.iter().flat_map(|m| &m.conditions) {
+        *expansions.entry(entry.loc.clone()).or_insert(0) += 1;
+    }
+    for entry in result.iter_mut().flat_map(|m| &mut m.conditions) {
+        entry.expansions = expansions[&entry.loc];
+    }
+    result
+}
+
+fn metrics_of(name: String, spec: &Spec) -> SpecMetrics {
+    let conditions = spec
+        .conditions
+        .iter()
+        .filter(|cond| {
+            !matches!(
+                cond.kind,
+                ConditionKind::LetPre(..) | ConditionKind::LetPost(..)
+            )
+        })
+        .map(|cond| ConditionEntry {
+            loc: cond.loc.clone(),
+            kind: cond.kind.clone(),
+            metrics: ConditionMetrics::of(cond),
+            included: spec
+                .loc
+                .as_ref()
+                .map(|loc| !loc.is_enclosing(&cond.loc))
+                .unwrap_or(false),
+            expansions: 1,
+        })
+        .collect();
+    SpecMetrics { name, conditions }
+}
+
+/// Returns a report of the metrics of the specs of the target modules. Conditions which are
+/// likely to cause solver trouble are marked with `(!)`.
+pub fn spec_metrics_report(env: &GlobalEnv) -> String {
+    let mut res = String::new();
+    for metrics in spec_metrics(env) {
+        res.push_str(&format!(
+            "{} ({} condition(s), schema expansion factor {:.1})\n",
+            metrics.name,
+            metrics.conditions.len(),
+            metrics.schema_expansion_factor()
+        ));
+        for entry in &metrics.conditions {
+            let m = &entry.metrics;
+            res.push_str(&format!(
+                "  {:<16} quantifier depth {}, {} term(s), {} old reference(s), \
+                 {} expansion(s){}{} {}\n",
+                entry.kind.to_string(),
+                m.quantifier_depth,
+                m.term_count,
+                m.old_references,
+                entry.expansions,
+                if entry.included { " (included)" } else { "" },
+                if m.is_complex() { " (!)" } else { "" },
+                entry.loc.display(env)
+            ));
+        }
+        res.push('\n');
+    }
+    res
+}
@@ -0,0 +1,149 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Unit tests for specifications.
+//!
+//! A function with the `#[spec_test]` attribute is a spec test. Its body consists of inline spec
+//! blocks whose `assert` conditions are evaluated by the spec evaluator, without running the
+//! prover. This allows spec authors to check helper spec functions on constructed values, like
+//! they unit test code:
+//!
+//! ```move
+//! spec fun max(x: num, y: num): num { if (x >= y) x else y }
+//!
+//! #[spec_test]
+//! fun test_max() {
+//!     spec {
+//!         assert max(1, 2) == 2;
+//!         assert max(S{x: 3}.x, 2) == 3;
+//!     }
+//! }
+//! ```
+//!
+//! Spec tests take no parameters, hence the conditions can only refer to constructed values.
+
+use crate::{
+    ast::{Attribute, ConditionKind},
+    exp_evaluator::ExpEvaluator,
+    model::{FunId, FunctionEnv, GlobalEnv, Loc, QualifiedId},
+};
+
+/// The name of the attribute which marks spec tests.
+pub const SPEC_TEST_ATTRIBUTE: &str = "spec_test";
+
+/// The result of a spec test.
+#[derive(Debug, Clone)]
+pub struct SpecTestResult {
+    pub fun_id: QualifiedId<FunId>,
+    /// The name of the test, e.g. `0x1::M::test_max`.
+    pub name: String,
+    /// The number of conditions which were evaluated.
+    pub conditions: usize,
+    pub failures: Vec<SpecTestFailure>,
+}
+
+/// A condition of a spec test which does not hold or cannot be evaluated.
+#[derive(Debug, Clone)]
+pub struct SpecTestFailure {
+    pub loc: Loc,
+    pub message: String,
+}
+
+impl SpecTestResult {
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// Returns true if the function is a spec test.
+pub fn is_spec_test(fun_env: &FunctionEnv) -> bool {
+    let pool = fun_env.symbol_pool();
+    fun_env.get_attributes().iter().any(|attr| {
+        matches!(attr, Attribute::Apply(_, name, _)
+                 if pool.string(*name).as_str() == SPEC_TEST_ATTRIBUTE)
+    })
+}
+
+/// Runs the spec tests of the target modules. Failures are also added to the diagnostics of the
+/// environment as errors, as are spec tests which take parameters.
+pub fn run_spec_tests(env: &GlobalEnv) -> Vec<SpecTestResult> {
+    let mut results = vec![];
+    for module_env in env.get_modules().filter(|m| m.is_target()) {
+        for fun_env in module_env.get_functions().filter(is_spec_test) {
+            if fun_env.get_parameter_count() > 0 || fun_env.get_type_parameter_count() > 0 {
+                env.error(
+                    &fun_env.get_loc(),
+                    "a spec test must not have parameters or type parameters",
+                );
+                continue;
+            }
+            results.push(run_spec_test(&fun_env));
+        }
+    }
+    for failure in results.iter().flat_map(|result| &result.failures) {
+        env.error(&failure.loc, &failure.message);
+    }
+    results
+}
+
+/// Evaluates the `assert` conditions of the inline spec blocks of the spec test, in code order.
+fn run_spec_test(fun_env: &FunctionEnv) -> SpecTestResult {
+    let env = fun_env.module_env.env;
+    let evaluator = ExpEvaluator::new(env);
+    let spec = fun_env.get_spec();
+    let mut conditions = 0;
+    let mut failures = vec![];
+    for cond in spec
+        .on_impl
+        .values()
+        .flat_map(|spec| spec.filter_kind(ConditionKind::Assert))
+    {
+        conditions += 1;
+        let message = match evaluator.eval_bool(&cond.exp) {
+            Ok(true) => continue,
+            Ok(false) => "spec test assertion does not hold".to_string(),
+            Err(err) => format!("spec test assertion cannot be evaluated: {}", err),
+        };
+        failures.push(SpecTestFailure {
+            loc: cond.loc.clone(),
+            message,
+        });
+    }
+    SpecTestResult {
+        fun_id: fun_env.get_qualified_id(),
+        name: format!(
+            "{}::{}",
+            fun_env.module_env.get_full_name_str(),
+            fun_env.get_simple_name_string()
+        ),
+        conditions,
+        failures,
+    }
+}
+
+/// Returns a report of the results of the spec tests.
+pub fn spec_test_report(env: &GlobalEnv, results: &[SpecTestResult]) -> String {
+    let mut res = String::new();
+    for result in results {
+        res.push_str(&format!(
+            "spec test {} ({} condition(s)) ... {}\n",
+            result.name,
+            result.conditions,
+            if result.passed() { "ok" } else { "FAILED" }
+        ));
+        for failure in &result.failures {
+            res.push_str(&format!(
+                "  {} {}\n",
+                failure.message,
+                failure.loc.display(env)
+            ));
+        }
+    }
+    let passed = results.iter().filter(|result| result.passed()).count();
+    res.push_str(&format!(
+        "{} passed, {} failed\n",
+        passed,
+        results.len() - passed
+    ));
+    res
+}
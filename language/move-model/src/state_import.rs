@@ -0,0 +1,205 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Import of on-chain resources into a `GlobalState`, so that interpretation and verification
+//! can start from a realistic state instead of empty memory.
+//!
+//! Two JSON formats are supported:
+//!
+//! - A state dump, which is an array of resources of the form
+//!   `{"address": "0x42", "type": "0x1::Coin::Balance<0x1::XUS::XUS>", "bcs": "0x..."}`, where
+//!   the value is given either as hex encoded BCS in `bcs` or as JSON in `data`.
+//! - The response of a node REST endpoint listing the resources of an account, which is an
+//!   array of the form `{"type": "...", "data": {...}}`.
+//!
+//! JSON values follow the conventions of node REST APIs: structs are objects keyed by field
+//! name, integers may be numbers or strings, addresses are hex strings, and `vector<u8>` may be
+//! given as a hex string. Resources whose type is not defined in the model are skipped and
+//! reported in the import summary.
+
+use crate::{
+    global_state::GlobalState,
+    model::GlobalEnv,
+    ty::{PrimitiveType, Type},
+    value::MoveValue,
+};
+use anyhow::{anyhow, bail, Context};
+use move_core_types::parser::parse_struct_tag;
+use num::{BigInt, BigUint, Num};
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+
+/// The summary of an import.
+#[derive(Debug, Clone, Default)]
+pub struct ImportSummary {
+    /// The number of resources which have been imported.
+    pub imported: usize,
+    /// The types of the resources which have been skipped because they are not defined in the
+    /// model.
+    pub skipped: Vec<String>,
+}
+
+/// A resource in a state dump.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DumpedResource {
+    address: String,
+    #[serde(rename = "type")]
+    type_: String,
+    bcs: Option<String>,
+    data: Option<JsonValue>,
+}
+
+/// A resource in the response of a node REST endpoint.
+#[derive(Debug, Deserialize)]
+struct AccountResource {
+    #[serde(rename = "type")]
+    type_: String,
+    data: JsonValue,
+}
+
+/// Imports the resources of a state dump into the state.
+pub fn import_state_dump(
+    env: &GlobalEnv,
+    state: &mut GlobalState,
+    json: &str,
+) -> anyhow::Result<ImportSummary> {
+    let resources: Vec<DumpedResource> = serde_json::from_str(json)?;
+    let mut summary = ImportSummary::default();
+    for resource in resources {
+        let ty = match resolve_struct_type(env, &resource.type_)? {
+            Some(ty) => ty,
+            None => {
+                summary.skipped.push(resource.type_);
+                continue;
+            }
+        };
+        let value = match (&resource.bcs, &resource.data) {
+            (Some(bcs), _) => MoveValue::from_bcs(env, &ty, &parse_hex(bcs)?),
+            (None, Some(data)) => value_from_json(env, &ty, data),
+            (None, None) => Err(anyhow!("neither `bcs` nor `data` given")),
+        }
+        .with_context(|| format!("resource `{}` at {}", resource.type_, resource.address))?;
+        put_resource(state, parse_address(&resource.address)?, ty, value)?;
+        summary.imported += 1;
+    }
+    Ok(summary)
+}
+
+/// Imports the resources of an account, as returned by a node REST endpoint, into the state.
+pub fn import_account_resources(
+    env: &GlobalEnv,
+    state: &mut GlobalState,
+    address: &str,
+    json: &str,
+) -> anyhow::Result<ImportSummary> {
+    let address = parse_address(address)?;
+    let resources: Vec<AccountResource> = serde_json::from_str(json)?;
+    let mut summary = ImportSummary::default();
+    for resource in resources {
+        let ty = match resolve_struct_type(env, &resource.type_)? {
+            Some(ty) => ty,
+            None => {
+                summary.skipped.push(resource.type_);
+                continue;
+            }
+        };
+        let value = value_from_json(env, &ty, &resource.data).with_context(|| {
+            format!(
+                "resource `{}` at {}",
+                resource.type_,
+                env.display_address(&address)
+            )
+        })?;
+        put_resource(state, address.clone(), ty, value)?;
+        summary.imported += 1;
+    }
+    Ok(summary)
+}
+
+fn put_resource(
+    state: &mut GlobalState,
+    address: BigUint,
+    ty: Type,
+    value: MoveValue,
+) -> anyhow::Result<()> {
+    match ty {
+        Type::Struct(mid, sid, inst) => {
+            state.put(address, mid.qualified_inst(sid, inst), value);
+            Ok(())
+        }
+        _ => bail!("resource type is not a struct"),
+    }
+}
+
+/// Resolves a struct type given as a string, returning None if the struct or one of its type
+/// arguments is not defined in the model.
+fn resolve_struct_type(env: &GlobalEnv, type_str: &str) -> anyhow::Result<Option<Type>> {
+    let tag = parse_struct_tag(type_str)
+        .with_context(|| format!("invalid resource type `{}`", type_str))?;
+    Ok(env
+        .resolve_struct_tag_inst(&tag)
+        .map(|id| Type::Struct(id.module_id, id.id, id.inst)))
+}
+
+/// Constructs a value of the given type from JSON.
+fn value_from_json(env: &GlobalEnv, ty: &Type, json: &JsonValue) -> anyhow::Result<MoveValue> {
+    Ok(match (ty, json) {
+        (Type::Primitive(PrimitiveType::Bool), JsonValue::Bool(b)) => MoveValue::Bool(*b),
+        (ty, JsonValue::Number(n)) if ty.is_number() => MoveValue::Number(
+            BigInt::from_str_radix(&n.to_string(), 10).map_err(|_| anyhow!("invalid number"))?,
+        ),
+        (ty, JsonValue::String(s)) if ty.is_number() => MoveValue::Number(
+            BigInt::from_str_radix(s, 10).map_err(|_| anyhow!("invalid number `{}`", s))?,
+        ),
+        (Type::Primitive(PrimitiveType::Address), JsonValue::String(s))
+        | (Type::Primitive(PrimitiveType::Signer), JsonValue::String(s)) => {
+            MoveValue::Address(parse_address(s)?)
+        }
+        (Type::Vector(elem_ty), JsonValue::String(s))
+            if **elem_ty == Type::Primitive(PrimitiveType::U8) =>
+        {
+            MoveValue::Vector(
+                parse_hex(s)?
+                    .into_iter()
+                    .map(|b| MoveValue::Number(BigInt::from(b)))
+                    .collect(),
+            )
+        }
+        (Type::Vector(elem_ty), JsonValue::Array(elems)) => MoveValue::Vector(
+            elems
+                .iter()
+                .map(|e| value_from_json(env, elem_ty, e))
+                .collect::<anyhow::Result<Vec<_>>>()?,
+        ),
+        (Type::Struct(mid, sid, inst), JsonValue::Object(fields)) => {
+            let struct_env = env.get_module(*mid).into_struct(*sid);
+            let mut vals = vec![];
+            for field_env in struct_env.get_fields() {
+                let name = field_env.get_name().display(env.symbol_pool()).to_string();
+                let field = fields
+                    .get(&name)
+                    .ok_or_else(|| anyhow!("missing field `{}`", name))?;
+                vals.push(
+                    value_from_json(env, &field_env.get_type().instantiate(inst), field)
+                        .with_context(|| format!("field `{}`", name))?,
+                );
+            }
+            MoveValue::Struct(mid.qualified_inst(*sid, inst.clone()), vals)
+        }
+        _ => bail!(
+            "expected a value of type `{}`, found `{}`",
+            ty.display(&env.get_type_display_ctx()),
+            json
+        ),
+    })
+}
+
+fn parse_address(s: &str) -> anyhow::Result<BigUint> {
+    BigUint::from_str_radix(s.trim_start_matches("0x"), 16)
+        .map_err(|_| anyhow!("invalid address `{}`", s))
+}
+
+fn parse_hex(s: &str) -> anyhow::Result<Vec<u8>> {
+    hex::decode(s.trim_start_matches("0x")).map_err(|_| anyhow!("invalid hex string `{}`", s))
+}
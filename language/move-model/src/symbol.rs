@@ -4,16 +4,17 @@
 //! Contains definitions of symbols -- internalized strings which support fast hashing and
 //! comparison.
 
+use serde::{ser::SerializeSeq, Deserialize, Deserializer, Serialize, Serializer};
 use std::{
     cell::RefCell,
-    collections::HashMap,
+    collections::{BTreeSet, HashMap},
     fmt,
     fmt::{Error, Formatter},
     rc::Rc,
 };
 
 /// Representation of a symbol.
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Serialize, Deserialize)]
 pub struct Symbol(usize);
 
 impl Symbol {
@@ -81,10 +82,69 @@ impl SymbolPool {
     pub fn string(&self, sym: Symbol) -> Rc<String> {
         self.inner.borrow().strings[sym.0].clone()
     }
+
+    /// Drops the string representation of all symbols not in `live`, returning the number of
+    /// symbols dropped. Indices of dropped symbols are not reused, so surviving symbols keep
+    /// their identity. A dropped symbol must not be used afterwards: its string is replaced by a
+    /// placeholder, and `make` on the same string creates a fresh symbol.
+    pub fn retain(&self, live: &BTreeSet<Symbol>) -> usize {
+        let mut pool = self.inner.borrow_mut();
+        let InnerPool { strings, lookup } = &mut *pool;
+        let before = lookup.len();
+        lookup.retain(|_, n| live.contains(&Symbol(*n)));
+        if lookup.len() < before {
+            let dropped = Rc::new(DROPPED_SYMBOL.to_string());
+            for (n, s) in strings.iter_mut().enumerate() {
+                if !live.contains(&Symbol(n)) {
+                    *s = dropped.clone();
+                }
+            }
+        }
+        before - lookup.len()
+    }
 }
 
+/// The string representation of symbols dropped by `SymbolPool::retain`.
+const DROPPED_SYMBOL: &str = "<dropped>";
+
 impl Default for SymbolPool {
     fn default() -> Self {
         Self::new()
     }
 }
+
+/// A pool is persisted as the sequence of its strings in symbol order, with `None` for dropped
+/// symbols, so that symbols keep their identity when the pool is restored.
+impl Serialize for SymbolPool {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let pool = self.inner.borrow();
+        let mut seq = serializer.serialize_seq(Some(pool.strings.len()))?;
+        for (n, s) in pool.strings.iter().enumerate() {
+            let live = pool.lookup.get(s) == Some(&n);
+            seq.serialize_element(&if live { Some(s.as_str()) } else { None })?;
+        }
+        seq.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for SymbolPool {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let entries = Vec::<Option<String>>::deserialize(deserializer)?;
+        let dropped = Rc::new(DROPPED_SYMBOL.to_string());
+        let mut strings = vec![];
+        let mut lookup = HashMap::new();
+        for (n, s) in entries.into_iter().enumerate() {
+            match s {
+                Some(s) => {
+                    let key = Rc::new(s);
+                    strings.push(key.clone());
+                    lookup.insert(key, n);
+                }
+                None => strings.push(dropped.clone()),
+            }
+        }
+        Ok(SymbolPool {
+            inner: RefCell::new(InnerPool { strings, lookup }),
+        })
+    }
+}
@@ -0,0 +1,123 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A harness for verifying multi-transaction protocols.
+//!
+//! A `TransactionSequence` is an ordered list of entry functions. It is turned into a script
+//! which calls the functions in order, with each argument being a parameter of the script and
+//! therefore symbolic. The script carries `pragma assert_invariants_after_calls`, so verifying
+//! it checks that the global invariants hold after each step, given that they hold initially,
+//! for all possible arguments and signers.
+
+use crate::{
+    model::{FunId, GlobalEnv, QualifiedId},
+    pragmas::ASSERT_INVARIANTS_AFTER_CALLS_PRAGMA,
+    script_composer::ScriptComposer,
+    ty::{PrimitiveType, Type},
+};
+use anyhow::bail;
+
+/// A sequence of entry function calls with symbolic arguments.
+pub struct TransactionSequence<'env> {
+    env: &'env GlobalEnv,
+    composer: ScriptComposer,
+    steps: Vec<QualifiedId<FunId>>,
+}
+
+impl<'env> TransactionSequence<'env> {
+    /// Creates an empty sequence, which is represented by a script function of the given name.
+    pub fn new(env: &'env GlobalEnv, name: impl Into<String>) -> Self {
+        Self {
+            env,
+            composer: ScriptComposer::new(name).spec(format!(
+                "pragma {} = true;",
+                ASSERT_INVARIANTS_AFTER_CALLS_PRAGMA
+            )),
+            steps: vec![],
+        }
+    }
+
+    /// Appends a call to the given function. The function must be callable from a script and
+    /// must not be generic. Each parameter of the function becomes a parameter of the script
+    /// named after the step and the parameter; signer references are passed as references to
+    /// signer parameters.
+    pub fn step(mut self, fun_id: QualifiedId<FunId>) -> anyhow::Result<Self> {
+        let fun_env = self.env.get_function(fun_id);
+        let step = self.steps.len();
+        if !fun_env.has_unknown_callers() {
+            bail!(
+                "step {}: function `{}` cannot be called from a script",
+                step,
+                fun_env.get_full_name_str()
+            );
+        }
+        if fun_env.get_type_parameter_count() > 0 {
+            bail!(
+                "step {}: generic function `{}` is not supported",
+                step,
+                fun_env.get_full_name_str()
+            );
+        }
+        let mut args = vec![];
+        let mut composer = self.composer;
+        for param in fun_env.get_parameters() {
+            let name = format!("step{}_{}", step, param.0.display(self.env.symbol_pool()));
+            let (ty, arg) = match &param.1 {
+                Type::Reference(false, ty) if ty.is_signer() => {
+                    ("signer".to_string(), format!("&{}", name))
+                }
+                ty => match Self::script_type_str(ty) {
+                    Some(ty) => (ty, name.clone()),
+                    None => bail!(
+                        "step {}: parameter `{}` of `{}` cannot be passed from a script",
+                        step,
+                        param.0.display(self.env.symbol_pool()),
+                        fun_env.get_full_name_str()
+                    ),
+                },
+            };
+            composer = composer.param(&name, ty);
+            args.push(arg);
+        }
+        let args = args.iter().map(|a| a.as_str()).collect::<Vec<_>>();
+        self.composer = composer.call(
+            format!(
+                "{}::{}",
+                fun_env.module_env.get_full_name_str(),
+                fun_env.get_name().display(self.env.symbol_pool())
+            ),
+            &[],
+            &args,
+        );
+        self.steps.push(fun_id);
+        Ok(self)
+    }
+
+    /// Returns the functions called in the sequence, in order.
+    pub fn steps(&self) -> &[QualifiedId<FunId>] {
+        &self.steps
+    }
+
+    /// Returns the script representing the sequence. Further specification conditions can be
+    /// added to it before it is rendered.
+    pub fn into_script(self) -> ScriptComposer {
+        self.composer
+    }
+
+    /// Returns the Move source of a type which can be used for script parameters.
+    fn script_type_str(ty: &Type) -> Option<String> {
+        match ty {
+            Type::Primitive(prim) => match prim {
+                PrimitiveType::Bool
+                | PrimitiveType::U8
+                | PrimitiveType::U64
+                | PrimitiveType::U128
+                | PrimitiveType::Address
+                | PrimitiveType::Signer => Some(prim.to_string()),
+                _ => None,
+            },
+            Type::Vector(elem) => Some(format!("vector<{}>", Self::script_type_str(elem)?)),
+            _ => None,
+        }
+    }
+}
@@ -13,6 +13,7 @@ use move_binary_format::{file_format::TypeParameterIndex, normalized::Type as MT
 use move_core_types::language_storage::{StructTag, TypeTag};
 
 use crate::model::QualifiedInstId;
+use serde::{Deserialize, Serialize};
 use std::{
     collections::{BTreeMap, BTreeSet, VecDeque},
     fmt,
@@ -20,7 +21,7 @@ use std::{
 };
 
 /// Represents a type.
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Serialize, Deserialize)]
 pub enum Type {
     Primitive(PrimitiveType),
     Tuple(Vec<Type>),
@@ -45,7 +46,7 @@ pub const BOOL_TYPE: Type = Type::Primitive(PrimitiveType::Bool);
 pub const NUM_TYPE: Type = Type::Primitive(PrimitiveType::Num);
 
 /// Represents a primitive (builtin) type.
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Serialize, Deserialize)]
 pub enum PrimitiveType {
     Bool,
     U8,
@@ -235,6 +236,14 @@ impl Type {
         })
     }
 
+    /// Returns true if this is a struct type of an enum. Enums are represented as structs with
+    /// variants, so all operations on struct types apply to them as well.
+    pub fn is_enum(&self, env: &GlobalEnv) -> bool {
+        self.get_struct(env)
+            .map(|(struct_env, _)| struct_env.has_variants())
+            .unwrap_or(false)
+    }
+
     /// Require this to be a struct, if so extracts its content.
     pub fn require_struct(&self) -> (ModuleId, StructId, &[Type]) {
         if let Type::Struct(mid, sid, targs) = self {
@@ -244,6 +253,35 @@ impl Type {
         }
     }
 
+    /// Returns a copy of this type where the type arguments for phantom type parameters of
+    /// structs are replaced by `Type::Error`. Phantom type arguments do not influence the
+    /// representation of values, so the result can be used to key data which depends only on
+    /// the layout of a type.
+    pub fn erase_phantom_args(&self, env: &GlobalEnv) -> Type {
+        use Type::*;
+        match self {
+            Struct(mid, sid, targs) => {
+                let struct_env = env.get_module(*mid).into_struct(*sid);
+                let targs = targs
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, ty)| {
+                        if struct_env.is_phantom_parameter(idx) {
+                            Error
+                        } else {
+                            ty.erase_phantom_args(env)
+                        }
+                    })
+                    .collect();
+                Struct(*mid, *sid, targs)
+            }
+            Vector(et) => Vector(Box::new(et.erase_phantom_args(env))),
+            Tuple(tys) => Tuple(tys.iter().map(|ty| ty.erase_phantom_args(env)).collect()),
+            Reference(is_mut, ty) => Reference(*is_mut, Box::new(ty.erase_phantom_args(env))),
+            _ => self.clone(),
+        }
+    }
+
     /// Instantiates type parameters in this type.
     pub fn instantiate(&self, params: &[Type]) -> Type {
         if params.is_empty() {
@@ -0,0 +1,248 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A representation of Move values in terms of the model.
+//!
+//! Unlike the values of the VM, struct values carry their instantiated type from the model, and
+//! integers are unbounded so the same representation can be used for specification values.
+//! Conversion from and to the VM representation and BCS is directed by a model type, which
+//! determines the integer width and the layout of structs.
+
+use crate::{
+    addr_to_big_uint,
+    ast::Value,
+    big_uint_to_addr,
+    model::{GlobalEnv, QualifiedInstId, StructId},
+    ty::{PrimitiveType, Type},
+};
+use anyhow::{anyhow, bail};
+use itertools::Itertools;
+use move_core_types::value::{MoveStruct, MoveStructLayout, MoveTypeLayout, MoveValue as VMValue};
+use num::{BigInt, BigUint, ToPrimitive, Zero};
+use std::fmt;
+
+/// A Move value.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MoveValue {
+    Bool(bool),
+    Number(BigInt),
+    /// An address, which is also the representation of a signer.
+    Address(BigUint),
+    Vector(Vec<MoveValue>),
+    /// A struct value, with the values of its fields in declaration order.
+    Struct(QualifiedInstId<StructId>, Vec<MoveValue>),
+}
+
+impl MoveValue {
+    /// Returns the default value of a type: false, zero, the zero address, the empty vector, or
+    /// a struct with default fields.
+    pub fn default_of(env: &GlobalEnv, ty: &Type) -> anyhow::Result<Self> {
+        Ok(match ty {
+            Type::Primitive(PrimitiveType::Bool) => MoveValue::Bool(false),
+            Type::Primitive(PrimitiveType::Address) | Type::Primitive(PrimitiveType::Signer) => {
+                MoveValue::Address(BigUint::zero())
+            }
+            ty if ty.is_number() => MoveValue::Number(BigInt::zero()),
+            Type::Vector(_) => MoveValue::Vector(vec![]),
+            Type::Struct(mid, sid, inst) => {
+                let struct_env = env.get_module(*mid).into_struct(*sid);
+                let fields = struct_env
+                    .get_fields()
+                    .map(|field_env| Self::default_of(env, &field_env.get_type().instantiate(inst)))
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                MoveValue::Struct(mid.qualified_inst(*sid, inst.clone()), fields)
+            }
+            _ => bail!(
+                "type `{}` has no values",
+                ty.display(&env.get_type_display_ctx())
+            ),
+        })
+    }
+
+    /// Decodes a value of the given type from BCS.
+    pub fn from_bcs(env: &GlobalEnv, ty: &Type, bytes: &[u8]) -> anyhow::Result<Self> {
+        let layout = type_layout(env, ty)?;
+        Self::from_vm_value(env, ty, &VMValue::simple_deserialize(bytes, &layout)?)
+    }
+
+    /// Encodes the value as BCS, according to the given type.
+    pub fn to_bcs(&self, env: &GlobalEnv, ty: &Type) -> anyhow::Result<Vec<u8>> {
+        self.to_vm_value(env, ty)?
+            .simple_serialize()
+            .ok_or_else(|| anyhow!("cannot serialize value"))
+    }
+
+    /// Converts a value of the VM of the given type.
+    pub fn from_vm_value(env: &GlobalEnv, ty: &Type, value: &VMValue) -> anyhow::Result<Self> {
+        Ok(match (ty, value) {
+            (_, VMValue::Bool(b)) => MoveValue::Bool(*b),
+            (_, VMValue::U8(n)) => MoveValue::Number(BigInt::from(*n)),
+            (_, VMValue::U64(n)) => MoveValue::Number(BigInt::from(*n)),
+            (_, VMValue::U128(n)) => MoveValue::Number(BigInt::from(*n)),
+            (_, VMValue::Address(a)) | (_, VMValue::Signer(a)) => {
+                MoveValue::Address(addr_to_big_uint(a))
+            }
+            (Type::Vector(elem_ty), VMValue::Vector(elems)) => MoveValue::Vector(
+                elems
+                    .iter()
+                    .map(|e| Self::from_vm_value(env, elem_ty, e))
+                    .collect::<anyhow::Result<Vec<_>>>()?,
+            ),
+            (Type::Struct(mid, sid, inst), VMValue::Struct(s)) => {
+                let fields = match s {
+                    MoveStruct::Runtime(vals) => vals.iter().collect_vec(),
+                    MoveStruct::WithFields(fields) | MoveStruct::WithTypes { fields, .. } => {
+                        fields.iter().map(|(_, v)| v).collect_vec()
+                    }
+                };
+                let struct_env = env.get_module(*mid).into_struct(*sid);
+                MoveValue::Struct(
+                    mid.qualified_inst(*sid, inst.clone()),
+                    struct_env
+                        .get_fields()
+                        .zip(fields)
+                        .map(|(field_env, v)| {
+                            Self::from_vm_value(env, &field_env.get_type().instantiate(inst), v)
+                        })
+                        .collect::<anyhow::Result<Vec<_>>>()?,
+                )
+            }
+            _ => bail!("value does not match its type"),
+        })
+    }
+
+    /// Converts the value into a value of the VM of the given type. Fails if a number does not
+    /// fit into the integer type.
+    pub fn to_vm_value(&self, env: &GlobalEnv, ty: &Type) -> anyhow::Result<VMValue> {
+        let out_of_range = || anyhow!("number out of range of type");
+        Ok(match (ty, self) {
+            (Type::Primitive(PrimitiveType::Bool), MoveValue::Bool(b)) => VMValue::Bool(*b),
+            (Type::Primitive(PrimitiveType::U8), MoveValue::Number(n)) => {
+                VMValue::U8(n.to_u8().ok_or_else(out_of_range)?)
+            }
+            (Type::Primitive(PrimitiveType::U64), MoveValue::Number(n)) => {
+                VMValue::U64(n.to_u64().ok_or_else(out_of_range)?)
+            }
+            (Type::Primitive(PrimitiveType::U128), MoveValue::Number(n)) => {
+                VMValue::U128(n.to_u128().ok_or_else(out_of_range)?)
+            }
+            (Type::Primitive(PrimitiveType::Address), MoveValue::Address(a)) => {
+                VMValue::Address(big_uint_to_addr(a))
+            }
+            (Type::Primitive(PrimitiveType::Signer), MoveValue::Address(a)) => {
+                VMValue::Signer(big_uint_to_addr(a))
+            }
+            (Type::Vector(elem_ty), MoveValue::Vector(elems)) => VMValue::Vector(
+                elems
+                    .iter()
+                    .map(|e| e.to_vm_value(env, elem_ty))
+                    .collect::<anyhow::Result<Vec<_>>>()?,
+            ),
+            (Type::Struct(..), MoveValue::Struct(qid, fields)) => {
+                let struct_env = env.get_struct(qid.to_qualified_id());
+                VMValue::Struct(MoveStruct::Runtime(
+                    struct_env
+                        .get_fields()
+                        .zip(fields)
+                        .map(|(field_env, val)| {
+                            val.to_vm_value(env, &field_env.get_type().instantiate(&qid.inst))
+                        })
+                        .collect::<anyhow::Result<Vec<_>>>()?,
+                ))
+            }
+            _ => bail!(
+                "value does not match type `{}`",
+                ty.display(&env.get_type_display_ctx())
+            ),
+        })
+    }
+
+    /// Returns a value which can be displayed, using the environment for struct and field
+    /// names.
+    pub fn display<'a>(&'a self, env: &'a GlobalEnv) -> MoveValueDisplay<'a> {
+        MoveValueDisplay { env, val: self }
+    }
+}
+
+impl From<&Value> for MoveValue {
+    fn from(val: &Value) -> Self {
+        match val {
+            Value::Address(addr) => MoveValue::Address(addr.clone()),
+            Value::Number(n) => MoveValue::Number(n.clone()),
+            Value::Bool(b) => MoveValue::Bool(*b),
+            Value::ByteArray(bytes) => MoveValue::Vector(
+                bytes
+                    .iter()
+                    .map(|b| MoveValue::Number(BigInt::from(*b)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Returns the layout of a type, as needed for BCS.
+pub fn type_layout(env: &GlobalEnv, ty: &Type) -> anyhow::Result<MoveTypeLayout> {
+    Ok(match ty {
+        Type::Primitive(PrimitiveType::Bool) => MoveTypeLayout::Bool,
+        Type::Primitive(PrimitiveType::U8) => MoveTypeLayout::U8,
+        Type::Primitive(PrimitiveType::U64) => MoveTypeLayout::U64,
+        Type::Primitive(PrimitiveType::U128) => MoveTypeLayout::U128,
+        Type::Primitive(PrimitiveType::Address) => MoveTypeLayout::Address,
+        Type::Primitive(PrimitiveType::Signer) => MoveTypeLayout::Signer,
+        Type::Vector(elem_ty) => MoveTypeLayout::Vector(Box::new(type_layout(env, elem_ty)?)),
+        Type::Struct(mid, sid, inst) => {
+            let struct_env = env.get_module(*mid).into_struct(*sid);
+            MoveTypeLayout::Struct(MoveStructLayout::new(
+                struct_env
+                    .get_fields()
+                    .map(|field_env| type_layout(env, &field_env.get_type().instantiate(inst)))
+                    .collect::<anyhow::Result<Vec<_>>>()?,
+            ))
+        }
+        _ => bail!(
+            "type `{}` has no layout",
+            ty.display(&env.get_type_display_ctx())
+        ),
+    })
+}
+
+/// Helper type for displaying values.
+pub struct MoveValueDisplay<'a> {
+    env: &'a GlobalEnv,
+    val: &'a MoveValue,
+}
+
+impl<'a> fmt::Display for MoveValueDisplay<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.val {
+            MoveValue::Bool(b) => write!(f, "{}", b),
+            MoveValue::Number(n) => write!(f, "{}", n),
+            MoveValue::Address(a) => write!(f, "{}", self.env.display_address(a)),
+            MoveValue::Vector(elems) => write!(
+                f,
+                "[{}]",
+                elems.iter().map(|e| e.display(self.env)).join(", ")
+            ),
+            MoveValue::Struct(qid, fields) => {
+                let struct_env = self.env.get_struct(qid.to_qualified_id());
+                let fields = struct_env
+                    .get_fields()
+                    .zip(fields)
+                    .map(|(field_env, val)| {
+                        format!(
+                            "{}: {}",
+                            field_env.get_name().display(self.env.symbol_pool()),
+                            val.display(self.env)
+                        )
+                    })
+                    .join(", ");
+                write!(
+                    f,
+                    "{}{{{}}}",
+                    struct_env.get_name().display(self.env.symbol_pool()),
+                    fields
+                )
+            }
+        }
+    }
+}
@@ -0,0 +1,88 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for human readable names of addresses in displays of the model.
+
+mod common;
+
+use move_model::{global_state::GlobalState, model::GlobalEnv, value::MoveValue};
+use num::BigUint;
+use tempfile::TempDir;
+
+const MODULE: &str = "
+module 0x42::M {
+    struct R has key { owner: address }
+}
+";
+
+/// Loads the aliases from a file with the given content.
+fn load(env: &GlobalEnv, content: &str) -> anyhow::Result<()> {
+    let dir = TempDir::new().unwrap();
+    let path = common::write_source(dir.path(), "aliases.txt", content);
+    env.load_address_aliases(&path)
+}
+
+#[test]
+fn display_aliases() {
+    let env = common::build_ok(MODULE);
+    load(
+        &env,
+        "# Accounts of the test\n\ntreasury = 0x4f2b\n  alice=0x1  \n",
+    )
+    .unwrap();
+    assert_eq!(
+        env.get_address_alias(&BigUint::from(0x4f2bu32)),
+        Some("treasury".to_string())
+    );
+    assert_eq!(env.display_address(&BigUint::from(1u32)), "@alice");
+    assert_eq!(env.display_address(&BigUint::from(2u32)), "0x2");
+
+    // Values and resource locations use the aliases.
+    let module_env = env.find_module_by_name_str("M").unwrap();
+    let struct_env = module_env.find_struct(env.symbol_pool().make("R")).unwrap();
+    let ty = module_env
+        .get_id()
+        .qualified_inst(struct_env.get_id(), vec![]);
+    let value = MoveValue::Struct(
+        ty.clone(),
+        vec![MoveValue::Address(BigUint::from(0x4f2bu32))],
+    );
+    assert_eq!(
+        MoveValue::Address(BigUint::from(1u32))
+            .display(&env)
+            .to_string(),
+        "@alice"
+    );
+    let mut state = GlobalState::new();
+    state.put(BigUint::from(1u32), ty, value);
+    let display = state.display(&env).to_string();
+    assert!(display.starts_with("M::R at @alice: "), "{}", display);
+    assert!(display.contains("@treasury"), "{}", display);
+    assert!(!display.contains("0x4f2b"), "{}", display);
+}
+
+#[test]
+fn invalid_aliases() {
+    let env = GlobalEnv::new();
+    for line in [
+        "treasury 0x4f2b",
+        "= 0x1",
+        "my treasury = 0x1",
+        "treasury = 0xzz",
+    ] {
+        let err = load(&env, &format!("alice = 0x1\n{}\n", line)).unwrap_err();
+        assert!(
+            err.to_string()
+                .ends_with(&format!(":2: invalid address alias `{}`", line)),
+            "{}",
+            err
+        );
+    }
+    let err = env.load_address_aliases("no/such/aliases.txt").unwrap_err();
+    assert!(
+        err.to_string()
+            .starts_with("cannot read address aliases `no/such/aliases.txt`"),
+        "{}",
+        err
+    );
+}
@@ -0,0 +1,121 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for the audit heuristics report.
+
+mod common;
+
+use move_model::audit_heuristics::{
+    assertion_density, audit_findings, audit_report, Heuristic, Priority,
+};
+
+const MODULES: &str = "
+module 0x42::Coin {
+    struct Balance has key { v: u64 }
+
+    public fun set(a: address, v: u64) acquires Balance { borrow_global_mut<Balance>(a).v = v }
+
+    public fun set_guarded(_s: &signer, a: address, v: u64) acquires Balance {
+        borrow_global_mut<Balance>(a).v = v
+    }
+
+    fun set_private(a: address, v: u64) acquires Balance { borrow_global_mut<Balance>(a).v = v }
+
+    public fun value(a: address): u64 acquires Balance { borrow_global<Balance>(a).v }
+    spec value {
+        aborts_if !exists<Balance>(a);
+    }
+
+    public fun calc(x: u64, y: u64): u64 { (x + y) * (x - y) / y }
+
+    public fun calc_specified(x: u64, y: u64): u64 { (x + y) * (x - y) / y }
+    spec calc_specified {
+        aborts_if y == 0;
+    }
+}
+
+module 0x42::Bank {
+    use 0x42::Coin;
+
+    public fun balance(a: address): u64 { Coin::value(a) }
+
+    public fun checked_balance(a: address): u64 { Coin::value(a) }
+    spec checked_balance {
+        aborts_if !exists<Coin::Balance>(a);
+    }
+}
+";
+
+#[test]
+fn audit_findings_by_priority() {
+    let env = common::build_ok(MODULES);
+    let findings = audit_findings(&env)
+        .into_iter()
+        .map(|f| (f.priority, f.heuristic, f.function, f.message))
+        .collect::<Vec<_>>();
+    // Functions which take a signer, are private, only read state, or are specified by
+    // `aborts_if` conditions are not reported.
+    assert_eq!(
+        findings,
+        vec![
+            (
+                Priority::High,
+                Heuristic::UnguardedStateWrite,
+                "0x42::Coin::set".to_string(),
+                "callable by anyone, writes 0x42::Coin::Balance without taking a signer"
+                    .to_string()
+            ),
+            (
+                Priority::Medium,
+                Heuristic::UnspecifiedExternalAborts,
+                "0x42::Bank::balance".to_string(),
+                "calls 1 function(s) of other modules without `aborts_if` conditions".to_string()
+            ),
+            (
+                Priority::Low,
+                Heuristic::UnspecifiedArithmeticAborts,
+                "0x42::Coin::calc".to_string(),
+                "performs 4 arithmetic operation(s) without `aborts_if` conditions".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn audit_report_with_density() {
+    let env = common::build_ok(MODULES);
+    let density = assertion_density(&env);
+    assert_eq!(density.conditions, 3);
+    assert!(density.instructions > 0);
+    assert_eq!(
+        density.per_hundred_instructions(),
+        300.0 / density.instructions as f64
+    );
+
+    let report = audit_report(&env);
+    let mut lines = report.lines();
+    assert_eq!(
+        lines.next().unwrap(),
+        format!(
+            "assertion density: 3 condition(s) over {} instruction(s), {:.1} per 100 instructions",
+            density.instructions,
+            density.per_hundred_instructions()
+        )
+    );
+    assert_eq!(lines.next().unwrap(), "3 finding(s)");
+    let headers = report
+        .lines()
+        .filter(|l| l.starts_with('['))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        headers,
+        vec![
+            "[high] 0x42::Coin::set: callable by anyone, writes 0x42::Coin::Balance without \
+             taking a signer",
+            "[medium] 0x42::Bank::balance: calls 1 function(s) of other modules without \
+             `aborts_if` conditions",
+            "[low] 0x42::Coin::calc: performs 4 arithmetic operation(s) without `aborts_if` \
+             conditions",
+        ]
+    );
+}
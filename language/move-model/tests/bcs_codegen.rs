@@ -0,0 +1,112 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for the generation of Rust BCS codecs of Move types.
+
+mod common;
+
+use move_model::bcs_codegen::RustBcsGenerator;
+
+const MODULES: &str = "
+module 0x42::Event {
+    struct Handle<phantom T> has store { counter: u64, guid: vector<u8> }
+    struct Deposit has store { amount: u64 }
+}
+module 0x42::Coin {
+    use 0x42::Event;
+    struct Deposit has store { type: u8, to: address }
+    struct Info has key {
+        deposits: Event::Handle<Deposit>,
+        last: vector<Event::Deposit>,
+        frozen: bool,
+        total: u128,
+    }
+    struct Pair<T> has store { first: T, second: T }
+}
+";
+
+/// Returns the lines of the generated code, without the prelude shared by all codecs.
+fn generated_structs(generator: &RustBcsGenerator) -> Vec<String> {
+    let code = generator.generate();
+    let start = code
+        .find("\n/// The Move struct")
+        .expect("generated struct");
+    code[start..].lines().map(|l| l.to_string()).collect()
+}
+
+#[test]
+fn codecs_of_structs() {
+    let env = common::build_ok(MODULES);
+    let mut generator = RustBcsGenerator::new(&env);
+    generator.add_type_str("0x42::Coin::Info").unwrap();
+    generator.add_type_str("0x42::Coin::Deposit").unwrap();
+    let lines = generated_structs(&generator);
+    let declared = lines
+        .iter()
+        .filter(|l| l.starts_with("pub struct "))
+        .map(|l| l.as_str())
+        .collect::<Vec<_>>();
+    // Contained structs come first. Generated structs of the same name are prefixed by their
+    // module; the type argument of the phantom parameter is not generated.
+    assert_eq!(
+        declared,
+        vec![
+            "pub struct HandleOfDeposit {",
+            "pub struct EventDeposit {",
+            "pub struct Info {",
+            "pub struct CoinDeposit {",
+        ]
+    );
+    let code = lines.join("\n");
+    for expected in [
+        "/// The Move struct `Event::Handle<Coin::Deposit>`.",
+        "    pub deposits: HandleOfDeposit,",
+        "    pub last: Vec<EventDeposit>,",
+        "    pub frozen: bool,",
+        "    pub total: u128,",
+        "        self.deposits.serialize(out);",
+        "            last: <Vec<EventDeposit>>::deserialize(input)?,",
+    ] {
+        assert!(code.contains(expected), "`{}` not in:\n{}", expected, code);
+    }
+}
+
+#[test]
+fn codecs_of_instantiations() {
+    let env = common::build_ok(MODULES);
+    let mut generator = RustBcsGenerator::new(&env);
+    generator.add_type_str("0x42::Coin::Pair<u64>").unwrap();
+    generator
+        .add_type_str("0x42::Coin::Pair<0x42::Coin::Deposit>")
+        .unwrap();
+    // Adding a type again has no effect.
+    generator.add_type_str("0x42::Coin::Pair<u64>").unwrap();
+    let code = generated_structs(&generator).join("\n");
+    assert_eq!(code.matches("pub struct PairOfU64 {").count(), 1);
+    assert!(code.contains("    pub first: u64,"), "{}", code);
+    // Field names which are Rust keywords are escaped.
+    assert!(code.contains("pub struct Deposit {\n    pub r#type: u8,\n    pub to: Address,"));
+    assert!(code.contains("pub struct PairOfDeposit {\n    pub first: Deposit,"));
+}
+
+#[test]
+fn invalid_types() {
+    let env = common::build_ok(MODULES);
+    let mut generator = RustBcsGenerator::new(&env);
+    let err = generator.add_type_str("0x42::Coin::Missing").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "type `0x42::Coin::Missing` is not defined in the model"
+    );
+    assert!(generator
+        .add_type_str("0x42::Coin::<u64>")
+        .unwrap_err()
+        .to_string()
+        .starts_with("invalid type `0x42::Coin::<u64>`"));
+
+    // All structs of the target modules, except for generic ones.
+    generator.add_target_structs().unwrap();
+    let code = generated_structs(&generator).join("\n");
+    assert_eq!(code.matches("pub struct ").count(), 4, "{}", code);
+    assert!(!code.contains("pub struct Pair"));
+}
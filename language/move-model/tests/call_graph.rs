@@ -0,0 +1,118 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for the call graph and its strongly connected components.
+
+mod common;
+
+use move_model::{
+    call_graph::CallGraph,
+    model::{FunId, GlobalEnv, QualifiedId},
+};
+use std::collections::BTreeSet;
+
+const MODULES: &str = "
+module 0x42::A {
+    public fun leaf(): u64 { 1 }
+    public fun uses_leaf(): u64 { leaf() + 1 }
+}
+module 0x42::B {
+    use 0x42::A;
+
+    // `even` and `odd` are mutually recursive, and `odd` calls into module `A`.
+    fun even(n: u64): bool { if (n == 0) true else odd(n - 1) }
+    fun odd(n: u64): bool { if (n == 0) A::leaf() == 0 else even(n - 1) }
+
+    // `count` is directly recursive.
+    fun count(n: u64): u64 { if (n == 0) 0 else count(n - 1) + 1 }
+
+    fun top(n: u64): u64 {
+        if (even(n)) count(n) else A::uses_leaf()
+    }
+}
+";
+
+fn id(env: &GlobalEnv, name: &str) -> QualifiedId<FunId> {
+    env.find_function_by_name_str(name)
+        .unwrap()
+        .get_qualified_id()
+}
+
+fn names(env: &GlobalEnv, ids: &BTreeSet<QualifiedId<FunId>>) -> BTreeSet<String> {
+    ids.iter()
+        .map(|id| env.get_function(*id).get_full_name_str())
+        .collect()
+}
+
+fn set(names: &[&str]) -> BTreeSet<String> {
+    names.iter().map(|name| name.to_string()).collect()
+}
+
+#[test]
+fn sccs_in_reverse_topological_order() {
+    let env = common::build_ok(MODULES);
+    let graph = CallGraph::new(&env);
+    let sccs = graph.sccs();
+    assert_eq!(
+        sccs.iter()
+            .map(|scc| names(&env, scc))
+            .collect::<BTreeSet<_>>(),
+        vec![
+            set(&["A::leaf"]),
+            set(&["A::uses_leaf"]),
+            set(&["B::even", "B::odd"]),
+            set(&["B::count"]),
+            set(&["B::top"]),
+        ]
+        .into_iter()
+        .collect()
+    );
+    // A component only calls into itself and the components which precede it.
+    let mut seen = BTreeSet::new();
+    for scc in &sccs {
+        seen.extend(scc.iter().cloned());
+        for fun_id in scc {
+            assert!(graph.callees_of(*fun_id).is_subset(&seen));
+        }
+    }
+    let order = graph.bottom_up_order();
+    assert_eq!(order.len(), 6);
+    assert_eq!(order.last(), Some(&id(&env, "B::top")));
+}
+
+#[test]
+fn recursive_functions() {
+    let env = common::build_ok(MODULES);
+    let graph = CallGraph::new(&env);
+    assert_eq!(
+        names(&env, &graph.recursive_functions()),
+        set(&["B::count", "B::even", "B::odd"])
+    );
+    assert!(graph.is_recursive(id(&env, "B::odd")));
+    assert!(!graph.is_recursive(id(&env, "B::top")));
+    assert_eq!(
+        names(&env, &graph.transitive_callees_of(id(&env, "B::even"))),
+        set(&["A::leaf", "B::even", "B::odd"])
+    );
+    assert_eq!(
+        names(&env, &graph.transitive_callers_of(id(&env, "A::leaf"))),
+        set(&["A::uses_leaf", "B::even", "B::odd", "B::top"])
+    );
+}
+
+#[test]
+fn graph_restricted_to_functions() {
+    let env = common::build_ok(MODULES);
+    let graph = CallGraph::new_for_functions(
+        &env,
+        vec![id(&env, "B::even"), id(&env, "B::top"), id(&env, "A::leaf")],
+    );
+    assert!(!graph.contains(id(&env, "B::odd")));
+    // Without `odd`, `even` is no longer recursive.
+    assert!(graph.recursive_functions().is_empty());
+    assert_eq!(
+        names(&env, graph.callees_of(id(&env, "B::top"))),
+        set(&["B::even"])
+    );
+    assert_eq!(graph.sccs().len(), 3);
+}
@@ -0,0 +1,67 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Helpers shared by the tests of the model. Not every test uses every helper.
+#![allow(dead_code)]
+
+use codespan::{FileId, Span};
+use move_command_line_common::files::FileHash;
+use move_compiler::shared::PackagePaths;
+use move_model::{
+    model::{GlobalEnv, Loc},
+    options::ModelBuilderOptions,
+    run_model_builder_with_options,
+};
+use move_symbol_pool::Symbol;
+use std::{collections::BTreeMap, fs, path::Path};
+use tempfile::TempDir;
+
+/// Writes a source file into the directory, returning its path.
+pub fn write_source(dir: &Path, name: &str, content: &str) -> String {
+    let path = dir.join(name);
+    fs::write(&path, content).unwrap();
+    path.to_string_lossy().to_string()
+}
+
+/// Returns the packages for building a model from the source files, without named addresses.
+pub fn package(paths: Vec<String>) -> Vec<PackagePaths<String, String>> {
+    named_package(None, paths)
+}
+
+/// Returns the packages for building a model from the source files of a named package.
+pub fn named_package(name: Option<&str>, paths: Vec<String>) -> Vec<PackagePaths<String, String>> {
+    vec![PackagePaths {
+        name: name.map(Symbol::from),
+        paths,
+        named_address_map: BTreeMap::new(),
+    }]
+}
+
+/// Builds a model from the source text, which may contain errors.
+pub fn build_from_source(source: &str) -> GlobalEnv {
+    let dir = TempDir::new().unwrap();
+    let path = write_source(dir.path(), "sources.move", source);
+    run_model_builder_with_options(package(vec![path]), vec![], ModelBuilderOptions::default())
+        .unwrap()
+}
+
+/// Builds a model from the source text, which must not contain errors.
+pub fn build_ok(source: &str) -> GlobalEnv {
+    let env = build_from_source(source);
+    assert!(!env.has_errors());
+    env
+}
+
+/// Returns an environment without modules which only contains the source text, for testing
+/// diagnostics and edits.
+pub fn env_with_source(name: &str, text: &str) -> (GlobalEnv, FileId) {
+    let mut env = GlobalEnv::new();
+    let file_id = env.add_source(FileHash::new(text), name, text, false);
+    (env, file_id)
+}
+
+/// Returns the location of an occurrence of the pattern in the text, counting from zero.
+pub fn loc_of(file_id: FileId, text: &str, pattern: &str, occurrence: usize) -> Loc {
+    let start = text.match_indices(pattern).nth(occurrence).unwrap().0 as u32;
+    Loc::new(file_id, Span::new(start, start + pattern.len() as u32))
+}
@@ -0,0 +1,110 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for the detection of dead resources and fields.
+
+mod common;
+
+use move_model::{
+    dead_resources::{dead_resource_findings, DeadResourceKind},
+    lint::{run_lints, LintLevel},
+};
+
+const MODULE: &str = "
+module 0x42::M {
+    struct Live has key { value: u64 }
+
+    struct Unread has key { value: u64 }
+
+    struct Unpublished has key { value: u64 }
+
+    struct Generic<T: store> has key { value: T }
+
+    struct Config has drop, store { limit: u64, note: u64, unused: u64 }
+
+    public fun publish(s: &signer, c: Config) {
+        move_to(s, Live { value: 1 });
+        move_to(s, Unread { value: 1 });
+        move_to(s, Generic<Config> { value: c });
+    }
+
+    public fun value(a: address): u64 acquires Live { borrow_global<Live>(a).value }
+
+    public fun has_generic(a: address): bool { exists<Generic<Config>>(a) }
+
+    public fun config(limit: u64): Config { Config { limit, note: 0, unused: 0 } }
+
+    public fun limit(c: &Config): u64 { c.limit }
+
+    public fun set_note(c: &mut Config, note: u64) { c.note = note }
+
+    public fun touch(c: &mut Config): &mut u64 { &mut c.unused }
+}
+";
+
+#[test]
+fn dead_resources() {
+    let env = common::build_ok(MODULE);
+    let findings = dead_resource_findings(&env)
+        .into_iter()
+        .map(|f| {
+            (
+                f.kind,
+                f.message,
+                env.get_source(&f.loc).unwrap().to_string(),
+            )
+        })
+        .collect::<Vec<_>>();
+    // Fields of unread resources are not read either. A mutable borrow which is returned counts
+    // as a read.
+    assert_eq!(
+        findings,
+        vec![
+            (
+                DeadResourceKind::UnreadField,
+                "field `note` of `0x42::M::Config` is written but never read".to_string(),
+                "note".to_string()
+            ),
+            (
+                DeadResourceKind::UnreadField,
+                "field `value` of `0x42::M::Generic` is written but never read".to_string(),
+                "value".to_string()
+            ),
+            (
+                DeadResourceKind::UnpublishedResource,
+                "resource `0x42::M::Unpublished` is never published".to_string(),
+                "struct Unpublished has key { value: u64 }".to_string()
+            ),
+            (
+                DeadResourceKind::UnreadResource,
+                "resource `0x42::M::Unread` is published but never read".to_string(),
+                "struct Unread has key { value: u64 }".to_string()
+            ),
+            (
+                DeadResourceKind::UnreadField,
+                "field `value` of `0x42::M::Unread` is written but never read".to_string(),
+                "value".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn dead_resource_lints() {
+    let env = common::build_ok(MODULE);
+    let findings = run_lints(&env)
+        .into_iter()
+        .filter(|f| f.group == "dead_code")
+        .map(|f| (f.lint, f.level))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        findings,
+        vec![
+            ("unread_resource", LintLevel::Warn),
+            ("unpublished_resource", LintLevel::Warn),
+            ("unread_field", LintLevel::Warn),
+            ("unread_field", LintLevel::Warn),
+            ("unread_field", LintLevel::Warn),
+        ]
+    );
+}
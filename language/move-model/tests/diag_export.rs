@@ -0,0 +1,125 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for the export of diagnostics as SARIF.
+
+mod common;
+
+use codespan_reporting::diagnostic::Severity;
+use move_model::{
+    diag_export::{SarifExporter, SARIF_VERSION},
+    source_edit::{Fix, SourceEdit},
+};
+use serde_json::json;
+
+const TEXT: &str = "module 0x42::M {\n    fun f(x: u64): u64 { x + 1 }\n}\n";
+
+#[test]
+fn export_sarif() {
+    let (env, file_id) = common::env_with_source("/project/sources/M.move", TEXT);
+    env.diag_with_labels(
+        Severity::Error,
+        &common::loc_of(file_id, TEXT, "x + 1", 0),
+        "post-condition does not hold",
+        vec![(
+            common::loc_of(file_id, TEXT, "fun f", 0),
+            "in this function".to_string(),
+        )],
+    );
+    env.error_with_notes_and_fixes(
+        &common::loc_of(file_id, TEXT, "1", 0),
+        "literal is out of range",
+        vec!["consider a constant".to_string()],
+        vec![Fix::from_edit(
+            "replace with `2`",
+            SourceEdit::replace(common::loc_of(file_id, TEXT, "1", 0), "2"),
+        )],
+    );
+    env.diag(Severity::Warning, &env.unknown_loc(), "no location");
+    env.diag(
+        Severity::Note,
+        &common::loc_of(file_id, TEXT, "x", 0),
+        "below the severity",
+    );
+
+    let log = SarifExporter::new(&env, "move-prover")
+        .with_root("/project")
+        .export(Severity::Warning);
+    assert_eq!(log["version"], SARIF_VERSION);
+    let run = &log["runs"][0];
+    assert_eq!(run["tool"]["driver"]["name"], "move-prover");
+    assert_eq!(
+        run["originalUriBaseIds"]["SRCROOT"]["uri"],
+        "file:///project/"
+    );
+    let rule_ids = run["tool"]["driver"]["rules"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|rule| rule["id"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        rule_ids,
+        vec!["verification/postcondition", "move/error", "move/warning"]
+    );
+    // The file is referred to relative to the root, and listed once.
+    assert_eq!(
+        run["artifacts"],
+        json!([{ "location": { "uri": "sources/M.move", "uriBaseId": "SRCROOT" } }])
+    );
+
+    let results = run["results"].as_array().unwrap();
+    assert_eq!(results.len(), 3);
+    let postcondition = &results[0];
+    assert_eq!(postcondition["ruleIndex"], 0);
+    assert_eq!(postcondition["level"], "error");
+    let location = &postcondition["locations"][0]["physicalLocation"];
+    assert_eq!(location["artifactLocation"]["index"], 0);
+    assert_eq!(
+        location["region"],
+        json!({ "startLine": 2, "startColumn": 26, "endLine": 2, "endColumn": 31 })
+    );
+    assert_eq!(
+        postcondition["relatedLocations"][0]["message"]["text"],
+        "in this function"
+    );
+
+    let literal = &results[1];
+    assert_eq!(
+        literal["message"]["text"],
+        "literal is out of range\nconsider a constant"
+    );
+    let change = &literal["fixes"][0]["artifactChanges"][0];
+    assert_eq!(change["artifactLocation"]["uri"], "sources/M.move");
+    assert_eq!(change["replacements"][0]["insertedContent"]["text"], "2");
+
+    // A diagnostic at the unknown location has no location.
+    let no_location = &results[2];
+    assert_eq!(no_location["level"], "warning");
+    assert!(no_location.get("locations").is_none());
+}
+
+#[test]
+fn export_sarif_outside_root() {
+    let (env, file_id) = common::env_with_source("/project/sources/M.move", TEXT);
+    env.error(&common::loc_of(file_id, TEXT, "fun f", 0), "some error");
+    let log = SarifExporter::new(&env, "move-prover")
+        .with_root("/elsewhere")
+        .export(Severity::Warning);
+    assert_eq!(
+        log["runs"][0]["artifacts"],
+        json!([{ "location": { "uri": "file:///project/sources/M.move" } }])
+    );
+}
+
+#[test]
+fn export_sarif_properties() {
+    let (env, file_id) = common::env_with_source("/project/sources/M.move", TEXT);
+    env.error(&common::loc_of(file_id, TEXT, "fun f", 0), "some error");
+    let log = SarifExporter::new(&env, "move-prover").export(Severity::Warning);
+    assert!(log["runs"][0].get("properties").is_none());
+    let log = SarifExporter::new(&env, "move-prover")
+        .with_property("seed", json!(42))
+        .export(Severity::Warning);
+    assert_eq!(log["runs"][0]["properties"], json!({ "seed": 42 }));
+}
@@ -0,0 +1,104 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for reporting diagnostics as JSON.
+
+mod common;
+
+use codespan_reporting::diagnostic::Severity;
+use move_model::{
+    diag_json::JsonDiagnostic,
+    model::GlobalEnv,
+    source_edit::{Fix, SourceEdit},
+};
+
+const TEXT: &str = "module 0x42::M {\n    const LIMIT: u64 = 10;\n    fun f(): u64 { 10 }\n}\n";
+
+fn report(env: &GlobalEnv, severity: Severity) -> Vec<JsonDiagnostic> {
+    let mut out = vec![];
+    env.report_diag_json(&mut out, severity).unwrap();
+    String::from_utf8(out)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[test]
+fn report_json() {
+    let (env, file_id) = common::env_with_source("sources/M.move", TEXT);
+    let literal = common::loc_of(file_id, TEXT, "10", 1);
+    let fix = Fix::from_edit(
+        "replace with `LIMIT`",
+        SourceEdit::replace(literal.clone(), "LIMIT"),
+    )
+    .preferred();
+    env.error_with_notes_and_fixes(
+        &literal,
+        "literal equals a constant",
+        vec!["constant `LIMIT` is declared".to_string()],
+        vec![fix],
+    );
+    env.diag_with_labels(
+        Severity::Warning,
+        &common::loc_of(file_id, TEXT, "fun f", 0),
+        "unused function",
+        vec![(
+            common::loc_of(file_id, TEXT, "M", 0),
+            "in this module".to_string(),
+        )],
+    );
+    // Duplicates are only reported once, diagnostics below the severity not at all.
+    env.diag_with_labels(
+        Severity::Warning,
+        &common::loc_of(file_id, TEXT, "fun f", 0),
+        "unused function",
+        vec![(
+            common::loc_of(file_id, TEXT, "M", 0),
+            "in this module".to_string(),
+        )],
+    );
+    env.diag(Severity::Note, &literal, "a note");
+
+    let diags = report(&env, Severity::Warning);
+    assert_eq!(diags.len(), 2);
+
+    let error = &diags[0];
+    assert_eq!(error.severity, "error");
+    assert_eq!(error.message, "literal equals a constant");
+    assert_eq!(
+        error.notes,
+        vec!["constant `LIMIT` is declared".to_string()]
+    );
+    let location = error.location.as_ref().unwrap();
+    assert_eq!(location.file, "sources/M.move");
+    assert_eq!(
+        (location.start, location.end),
+        (TEXT.rfind("10").unwrap(), TEXT.rfind("10").unwrap() + 2)
+    );
+    assert_eq!(
+        (
+            location.start_line,
+            location.start_column,
+            location.end_line,
+            location.end_column
+        ),
+        (3, 20, 3, 22)
+    );
+    assert_eq!(error.fixes.len(), 1);
+    assert!(error.fixes[0].preferred);
+    assert_eq!(error.fixes[0].edits[0].text, "LIMIT");
+    assert_eq!(error.fixes[0].edits[0].location.start, location.start);
+
+    let warning = &diags[1];
+    assert_eq!(warning.severity, "warning");
+    assert_eq!(warning.location.as_ref().unwrap().start_line, 3);
+    assert_eq!(warning.labels.len(), 1);
+    assert_eq!(warning.labels[0].message, "in this module");
+    assert_eq!(warning.labels[0].location.start_line, 1);
+    assert!(warning.fixes.is_empty());
+
+    // Diagnostics are only reported once.
+    assert!(report(&env, Severity::Warning).is_empty());
+    assert_eq!(report(&env, Severity::Note).len(), 1);
+}
@@ -0,0 +1,102 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for the classification of functions by their effect on global storage.
+
+mod common;
+
+use move_model::model::{FunctionEffectClass, GlobalEnv, QualifiedId, StructId};
+use std::collections::BTreeSet;
+
+const MODULES: &str = "
+module 0x42::M {
+    struct R has key { v: u64 }
+    struct Box<T: store> has key { t: T }
+
+    public fun add(x: u64, y: u64): u64 { x + y }
+
+    public fun inc(x: &mut u64) { *x = *x + 1 }
+
+    public fun value(a: address): u64 acquires R {
+        if (exists<R>(a)) borrow_global<R>(a).v else 0
+    }
+
+    public fun has_box<T: store>(a: address): bool { exists<Box<T>>(a) }
+
+    public fun bump(a: address) acquires R {
+        let v = value(a);
+        inc(&mut borrow_global_mut<R>(a).v);
+        add(v, 1);
+    }
+
+    public fun unbox<T: store>(a: address): T acquires Box {
+        let Box { t } = move_from<Box<T>>(a);
+        t
+    }
+}
+
+module 0x42::N {
+    use 0x42::M;
+    public fun read_both(a: address): u64 {
+        if (M::has_box<u64>(a)) M::value(a) else 0
+    }
+    public fun take(a: address): u64 { M::unbox<u64>(a) }
+}
+";
+
+fn class(env: &GlobalEnv, name: &str) -> FunctionEffectClass {
+    env.find_function_by_name_str(name)
+        .unwrap()
+        .get_effect_class()
+}
+
+fn resources(env: &GlobalEnv, names: &[&str]) -> BTreeSet<QualifiedId<StructId>> {
+    let module_env = env.find_module_by_name_str("0x42::M").unwrap();
+    names
+        .iter()
+        .map(|name| {
+            module_env
+                .find_struct(env.symbol_pool().make(name))
+                .unwrap()
+                .get_qualified_id()
+        })
+        .collect()
+}
+
+#[test]
+fn effect_classes() {
+    let env = common::build_ok(MODULES);
+    // Mutations through references are not effects on global storage.
+    assert_eq!(class(&env, "M::add"), FunctionEffectClass::Pure);
+    assert_eq!(class(&env, "M::inc"), FunctionEffectClass::Pure);
+    assert_eq!(
+        class(&env, "M::value"),
+        FunctionEffectClass::ReadOnly {
+            reads: resources(&env, &["R"])
+        }
+    );
+    assert_eq!(
+        class(&env, "M::bump"),
+        FunctionEffectClass::StateMutating {
+            reads: resources(&env, &["R"]),
+            writes: resources(&env, &["R"])
+        }
+    );
+    assert_eq!(
+        class(&env, "M::unbox"),
+        FunctionEffectClass::StateMutating {
+            reads: BTreeSet::new(),
+            writes: resources(&env, &["Box"])
+        }
+    );
+    // Effects of callees count, also of generic ones and across modules.
+    assert_eq!(
+        class(&env, "N::read_both"),
+        FunctionEffectClass::ReadOnly {
+            reads: resources(&env, &["R", "Box"])
+        }
+    );
+    assert!(class(&env, "N::read_both").is_view());
+    assert!(!class(&env, "N::take").is_view());
+    assert!(FunctionEffectClass::Pure.is_view());
+}
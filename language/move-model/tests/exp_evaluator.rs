@@ -0,0 +1,123 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for the evaluation of specification expressions over concrete values.
+
+mod common;
+
+use move_model::{
+    ast::{ConditionKind, Exp},
+    exp_evaluator::ExpEvaluator,
+    global_state::GlobalState,
+    model::GlobalEnv,
+    value::MoveValue,
+};
+use num::{BigInt, BigUint};
+use std::collections::BTreeMap;
+
+const MODULE: &str = "
+module 0x42::M {
+    struct R has key { v: u64 }
+
+    fun get(a: address, x: u64): u64 acquires R {
+        borrow_global<R>(a).v + x
+    }
+    spec get {
+        aborts_if !exists<R>(a);
+        ensures result == global<R>(a).v + x;
+        ensures forall i in 0..x: i < result;
+        ensures sum(x) >= old(x);
+    }
+
+    spec fun sum(n: num): num {
+        if (n == 0) 0 else n + sum(n - 1)
+    }
+}
+";
+
+fn conditions(env: &GlobalEnv, kind: ConditionKind) -> Vec<Exp> {
+    env.find_function_by_name_str("M::get")
+        .unwrap()
+        .get_spec()
+        .filter_kind(kind)
+        .map(|cond| cond.exp.clone())
+        .collect()
+}
+
+fn num(n: u64) -> MoveValue {
+    MoveValue::Number(BigInt::from(n))
+}
+
+/// Returns a memory holding `R { v }` at the address `a`.
+fn memory(env: &GlobalEnv, a: u64, v: u64) -> GlobalState {
+    let module_env = env.find_module_by_name_str("M").unwrap();
+    let struct_env = module_env.find_struct(env.symbol_pool().make("R")).unwrap();
+    let ty = module_env
+        .get_id()
+        .qualified_inst(struct_env.get_id(), vec![]);
+    let mut memory = GlobalState::new();
+    memory.put(
+        BigUint::from(a),
+        ty.clone(),
+        MoveValue::Struct(ty, vec![num(v)]),
+    );
+    memory
+}
+
+fn evaluator(env: &GlobalEnv, a: u64, x: u64) -> ExpEvaluator<'_> {
+    ExpEvaluator::new(env)
+        .temp(0, MoveValue::Address(BigUint::from(a)))
+        .temp(1, num(x))
+}
+
+#[test]
+fn eval_conditions() {
+    let env = common::build_ok(MODULE);
+    let eval = evaluator(&env, 1, 3)
+        .memory(memory(&env, 1, 2))
+        .old_state(BTreeMap::new(), memory(&env, 1, 2))
+        .results(vec![num(5)]);
+    for exp in conditions(&env, ConditionKind::Ensures) {
+        assert!(eval.eval_bool(&exp).unwrap());
+    }
+    assert!(!eval
+        .eval_bool(&conditions(&env, ConditionKind::AbortsIf)[0])
+        .unwrap());
+
+    // A wrong result violates the post-conditions.
+    let eval = evaluator(&env, 1, 3)
+        .memory(memory(&env, 1, 2))
+        .results(vec![num(2)]);
+    let ensures = conditions(&env, ConditionKind::Ensures);
+    assert!(!eval.eval_bool(&ensures[0]).unwrap());
+    assert!(!eval.eval_bool(&ensures[1]).unwrap());
+}
+
+#[test]
+fn eval_old_state() {
+    let env = common::build_ok(MODULE);
+    let old_temps = vec![(1, num(100))].into_iter().collect();
+    let eval = evaluator(&env, 1, 3).old_state(old_temps, GlobalState::new());
+    // `sum(3)` is 6, which is less than the old value of `x`.
+    let exp = &conditions(&env, ConditionKind::Ensures)[2];
+    assert!(!eval.eval_bool(exp).unwrap());
+}
+
+#[test]
+fn eval_errors() {
+    let env = common::build_ok(MODULE);
+    // The resource does not exist at the address.
+    let eval = evaluator(&env, 2, 3)
+        .memory(memory(&env, 1, 2))
+        .results(vec![num(5)]);
+    let ensures = conditions(&env, ConditionKind::Ensures);
+    assert!(eval.eval_bool(&ensures[0]).is_err());
+    assert!(eval
+        .eval_bool(&conditions(&env, ConditionKind::AbortsIf)[0])
+        .unwrap());
+
+    // The result is not bound.
+    let eval = evaluator(&env, 1, 3).memory(memory(&env, 1, 2));
+    let err = eval.eval_bool(&ensures[0]).unwrap_err();
+    assert!(err.message.contains("result"));
+}
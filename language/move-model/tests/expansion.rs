@@ -0,0 +1,44 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for locations of expanded code.
+
+mod common;
+
+use codespan_reporting::{diagnostic::Severity, term::termcolor::Buffer};
+
+const SOURCE: &str = "fun inner() { abort 1 }
+fun middle() { inner() }
+fun outer() { middle() }
+";
+
+#[test]
+fn expansion_trace() {
+    let (env, file_id) = common::env_with_source("expansion.move", SOURCE);
+    let abort = common::loc_of(file_id, SOURCE, "abort 1", 0);
+    let inner_call = common::loc_of(file_id, SOURCE, "inner()", 1);
+    let middle_call = common::loc_of(file_id, SOURCE, "middle()", 1);
+
+    // `inner` is inlined into `middle`, which is then inlined into `outer`.
+    let loc = abort
+        .with_expansion_site(&inner_call)
+        .with_expansion_site(&middle_call);
+    assert_eq!(
+        loc.expanded_from(),
+        Some(&inner_call.with_expansion_site(&middle_call))
+    );
+    assert_eq!(loc.expansion_trace(), vec![inner_call, middle_call]);
+    assert_eq!(loc.without_expansion(), abort);
+    assert!(abort.expansion_trace().is_empty());
+    // Derived locations keep the expansion.
+    assert_eq!(loc.at_start().expansion_trace().len(), 2);
+
+    // Diagnostics point to the code and each site of the expansion.
+    env.error(&loc, "aborts");
+    let mut writer = Buffer::no_color();
+    env.report_diag(&mut writer, Severity::Error);
+    let output = String::from_utf8_lossy(&writer.into_inner()).to_string();
+    assert_eq!(output.matches("in expansion of this call").count(), 2);
+    assert!(output.contains("fun inner() { abort 1 }"));
+    assert!(output.contains("fun outer() { middle() }"));
+}
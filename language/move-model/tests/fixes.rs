@@ -0,0 +1,94 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for fixes attached to diagnostics.
+
+mod common;
+
+use codespan::Span;
+use move_model::{
+    model::Loc,
+    source_edit::{Fix, SourceEdit, SourceEdits},
+};
+
+const MODULE: &str = "module 0x42::M {
+    const LIMIT: u64 = 10;
+
+    fun f(a1: u64, a3: u64): u64 { a1 + a3 }
+    spec f {
+        ensures result <= LIMT;
+        ensures result >= a2;
+    }
+}
+";
+
+#[test]
+fn fixes_of_undeclared_names() {
+    let env = common::build_from_source(MODULE);
+    let fixes = env.get_fixes();
+    assert_eq!(fixes.len(), 2);
+
+    // A single suggestion is safe to apply.
+    let (diag, limit_fixes) = &fixes[0];
+    assert_eq!(diag.message, "undeclared `M::LIMT`");
+    assert_eq!(limit_fixes.len(), 1);
+    assert_eq!(limit_fixes[0].title, "replace with `LIMIT`");
+    assert!(limit_fixes[0].preferred);
+
+    // A choice between suggestions needs review.
+    let (diag, param_fixes) = &fixes[1];
+    assert_eq!(diag.message, "undeclared `M::a2`");
+    let titles = param_fixes
+        .iter()
+        .map(|f| f.title.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(titles, vec!["replace with `a1`", "replace with `a3`"]);
+    assert!(param_fixes.iter().all(|f| !f.preferred));
+
+    // Only the single preferred fix is taken, and only once.
+    let preferred = env.take_preferred_fixes();
+    assert_eq!(preferred.len(), 1);
+    assert!(env.take_preferred_fixes().is_empty());
+    let texts = preferred[0].edits.apply(&env).unwrap();
+    let text = texts.values().next().unwrap();
+    assert!(text.contains("ensures result <= LIMIT;"), "{}", text);
+
+    env.clear_diag();
+    assert!(env.get_fixes().is_empty());
+}
+
+#[test]
+fn insert_in_block() {
+    let text = "spec f {\n        ensures true;\n    }\nspec g { ensures true; }\n";
+    let (env, file_id) = common::env_with_source("f.move", text);
+    let block = |start: usize, end: usize| Loc::new(file_id, Span::new(start as u32, end as u32));
+    let f_end = text.find("}\n").unwrap() + 1;
+    let g_start = text.find("spec g").unwrap();
+    let g_end = text.rfind('}').unwrap() + 1;
+
+    let mut edits = SourceEdits::new();
+    // A block closed on its own line gets a new line, indented one level deeper.
+    edits
+        .add(SourceEdit::insert_in_block(
+            &env,
+            &block(0, f_end),
+            "aborts_if false;",
+        ))
+        .unwrap();
+    // A block closed on a line with other content gets the text before the brace.
+    edits
+        .add(SourceEdit::insert_in_block(
+            &env,
+            &block(g_start, g_end),
+            "pragma verify = false;",
+        ))
+        .unwrap();
+    let fix = Fix::new("add clauses", edits);
+    assert!(!fix.preferred);
+    assert!(fix.clone().preferred().preferred);
+    assert_eq!(
+        fix.edits.apply_to_text(file_id, text).unwrap(),
+        "spec f {\n        ensures true;\n        aborts_if false;\n    }\n\
+         spec g { ensures true; pragma verify = false; }\n"
+    );
+}
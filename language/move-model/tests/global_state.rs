@@ -0,0 +1,132 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for snapshots of the global memory.
+
+mod common;
+
+use move_model::{
+    global_state::{GlobalState, ResourceChange},
+    model::{GlobalEnv, QualifiedInstId, StructId},
+    ty::{PrimitiveType, Type},
+    value::MoveValue,
+};
+use num::{BigInt, BigUint};
+
+const MODULE: &str = "
+module 0x42::M {
+    struct Balance<phantom T> has key { value: u64 }
+    struct R has key { flag: bool }
+}
+";
+
+/// Returns the type of the struct with the given name, instantiated with the types.
+fn struct_ty(env: &GlobalEnv, name: &str, inst: Vec<Type>) -> QualifiedInstId<StructId> {
+    let module_env = env.find_module_by_name_str("M").unwrap();
+    let struct_env = module_env
+        .find_struct(env.symbol_pool().make(name))
+        .unwrap();
+    module_env
+        .get_id()
+        .qualified_inst(struct_env.get_id(), inst)
+}
+
+fn u64_ty() -> Type {
+    Type::Primitive(PrimitiveType::U64)
+}
+
+fn balance(env: &GlobalEnv, inst: Type, value: u64) -> (QualifiedInstId<StructId>, MoveValue) {
+    let ty = struct_ty(env, "Balance", vec![inst]);
+    let val = MoveValue::Struct(ty.clone(), vec![MoveValue::Number(BigInt::from(value))]);
+    (ty, val)
+}
+
+fn addr(a: u32) -> BigUint {
+    BigUint::from(a)
+}
+
+#[test]
+fn query_state() {
+    let env = common::build_ok(MODULE);
+    let (u64_balance, v1) = balance(&env, u64_ty(), 1);
+    let (bool_balance, v2) = balance(&env, Type::Primitive(PrimitiveType::Bool), 2);
+    let mut state = GlobalState::new();
+    assert!(state.is_empty());
+    assert_eq!(state.put(addr(1), u64_balance.clone(), v1.clone()), None);
+    state.put(addr(2), bool_balance.clone(), v2.clone());
+    assert_eq!(
+        state.put(addr(1), u64_balance.clone(), v1.clone()),
+        Some(v1.clone())
+    );
+
+    assert!(state.exists(&addr(1), &u64_balance));
+    assert!(!state.exists(&addr(2), &u64_balance));
+    assert_eq!(state.get(&addr(2), &bool_balance), Some(&v2));
+    assert_eq!(
+        state.addresses().into_iter().collect::<Vec<_>>(),
+        vec![addr(1), addr(2)]
+    );
+
+    // Resources of a struct, of any or of one instantiation.
+    let (module_id, struct_id) = (u64_balance.module_id, u64_balance.id);
+    assert_eq!(state.resources_of(module_id, struct_id, None).count(), 2);
+    let inst = [u64_ty()];
+    let of_u64 = state
+        .resources_of(module_id, struct_id, Some(&inst))
+        .map(|(a, _, _)| a.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(of_u64, vec![addr(1)]);
+
+    assert_eq!(state.remove(&addr(1), &u64_balance), Some(v1));
+    assert_eq!(state.iter().count(), 1);
+}
+
+#[test]
+fn diff_and_display() {
+    let env = common::build_ok(MODULE);
+    let (ty, v1) = balance(&env, u64_ty(), 1);
+    let (_, v2) = balance(&env, u64_ty(), 2);
+    let r = struct_ty(&env, "R", vec![]);
+    let r_val = MoveValue::Struct(r.clone(), vec![MoveValue::Bool(true)]);
+
+    let mut old = GlobalState::new();
+    old.put(addr(1), ty.clone(), v1.clone());
+    old.put(addr(2), ty.clone(), v1.clone());
+    old.put(addr(3), r.clone(), r_val.clone());
+    let mut new = old.clone();
+    new.put(addr(1), ty.clone(), v2.clone());
+    new.remove(&addr(3), &r);
+    new.put(addr(4), r.clone(), r_val.clone());
+
+    let changes = old.diff(&new);
+    assert_eq!(
+        changes,
+        vec![
+            ResourceChange::Modified(addr(1), ty, v1, v2),
+            ResourceChange::Deleted(addr(3), r.clone(), r_val.clone()),
+            ResourceChange::Created(addr(4), r, r_val),
+        ]
+    );
+    assert!(new.diff(&new).is_empty());
+
+    let lines = changes
+        .iter()
+        .map(|c| c.display(&env).to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(lines.len(), 3);
+    assert!(
+        lines[0].starts_with("~ M::Balance<u64> at 0x1: "),
+        "{}",
+        lines[0]
+    );
+    assert!(lines[1].starts_with("- M::R at 0x3: "), "{}", lines[1]);
+    assert!(lines[2].starts_with("+ M::R at 0x4: "), "{}", lines[2]);
+
+    let display = new.display(&env).to_string();
+    assert_eq!(display.lines().count(), 3);
+    assert!(
+        display.starts_with("M::Balance<u64> at 0x1: "),
+        "{}",
+        display
+    );
+}
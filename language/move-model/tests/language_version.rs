@@ -0,0 +1,81 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for building models for older versions of the Move language.
+
+mod common;
+
+use codespan_reporting::{diagnostic::Severity, term::termcolor::Buffer};
+use move_model::{
+    language_version::LanguageVersion, options::ModelBuilderOptions, run_model_builder_with_options,
+};
+use tempfile::TempDir;
+
+const MODULES: &str = "
+module 0x42::M {
+    friend 0x42::N;
+
+    struct S has drop { small: u8, large: u128 }
+
+    public fun count(): u64 { 1 }
+
+    public(friend) fun small(x: u64): u8 { (x as u8) + 1u8 }
+
+    public(script) fun large() { let _x = 1u128 + 2; }
+}
+
+module 0x42::N {
+    public fun value(): u64 { 0x42::M::count() }
+}
+";
+
+/// Builds the modules for the language version and returns the error messages.
+fn errors(version: LanguageVersion) -> Vec<String> {
+    let dir = TempDir::new().unwrap();
+    let path = common::write_source(dir.path(), "sources.move", MODULES);
+    let options = ModelBuilderOptions {
+        language_version: version,
+        ..Default::default()
+    };
+    let env = run_model_builder_with_options(common::package(vec![path]), vec![], options).unwrap();
+    let mut writer = Buffer::no_color();
+    env.report_diag(&mut writer, Severity::Error);
+    String::from_utf8_lossy(&writer.into_inner())
+        .lines()
+        .filter_map(|line| line.strip_prefix("error: "))
+        .map(|message| message.to_string())
+        .collect()
+}
+
+#[test]
+fn unavailable_features() {
+    assert!(errors(LanguageVersion::latest()).is_empty());
+    assert!(errors(LanguageVersion::V2).is_empty());
+    // Integer types are reported once per struct or function.
+    assert_eq!(
+        errors(LanguageVersion::V1),
+        vec![
+            "friend declarations not available in Move language version 1",
+            "integer type `u8` not available in Move language version 1",
+            "integer type `u128` not available in Move language version 1",
+            "`public(script)` functions not available in Move language version 1",
+            "integer type `u128` not available in Move language version 1",
+            "`public(friend)` functions not available in Move language version 1",
+            "integer type `u8` not available in Move language version 1",
+        ]
+    );
+}
+
+#[test]
+fn parse_language_version() {
+    assert_eq!("1".parse::<LanguageVersion>().unwrap(), LanguageVersion::V1);
+    assert_eq!(
+        "v2".parse::<LanguageVersion>().unwrap(),
+        LanguageVersion::V2
+    );
+    assert_eq!(LanguageVersion::V3.to_string(), "3");
+    assert_eq!(
+        "4".parse::<LanguageVersion>().unwrap_err().to_string(),
+        "unknown language version `4`"
+    );
+}
@@ -0,0 +1,154 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for the lint framework.
+
+mod common;
+
+use codespan_reporting::{diagnostic::Severity, term::termcolor::Buffer};
+use move_model::{
+    lint::{lint_report, register_lint, run_lints, LintLevel, LintReporter, ModelLint},
+    model::GlobalEnv,
+};
+
+const MODULES: &str = "
+#[lint(deny(unguarded_state_write))]
+module 0x42::Coin {
+    struct Balance has key { v: u64 }
+
+    public fun publish(s: &signer) { move_to(s, Balance { v: 0 }) }
+
+    public fun set(a: address, v: u64) acquires Balance { borrow_global_mut<Balance>(a).v = v }
+
+    #[lint(allow(unguarded_state_write))]
+    public fun reset(a: address) acquires Balance { borrow_global_mut<Balance>(a).v = 0 }
+
+    #[lint(warn(unguarded_state_write))]
+    public fun clear(a: address) acquires Balance { borrow_global_mut<Balance>(a).v = 0 }
+
+    public fun value(a: address): u64 acquires Balance { borrow_global<Balance>(a).v }
+    spec value {
+        aborts_if !exists<Balance>(a);
+    }
+}
+
+/// The bank.
+module 0x42::Bank {
+    use 0x42::Coin;
+
+    public fun balance(a: address): u64 { Coin::value(a) }
+}
+";
+
+/// A custom lint reporting modules without documentation.
+struct UndocumentedModule;
+
+impl ModelLint for UndocumentedModule {
+    fn name(&self) -> &'static str {
+        "undocumented_module"
+    }
+
+    fn group(&self) -> &'static str {
+        "style"
+    }
+
+    fn description(&self) -> &'static str {
+        "modules without documentation"
+    }
+
+    fn check(&self, env: &GlobalEnv, reporter: &mut LintReporter) {
+        for module_env in env.get_modules().filter(|m| m.is_target()) {
+            if module_env.get_doc().trim().is_empty() {
+                reporter.report_module(&module_env, &module_env.get_loc(), "module has no doc");
+            }
+        }
+    }
+}
+
+#[test]
+fn run_lints_with_levels() {
+    register_lint(|| Box::new(UndocumentedModule));
+    let env = common::build_ok(MODULES);
+    let findings = run_lints(&env);
+    // The level of a function attribute overrides the one of its module, which overrides the
+    // default of the lint.
+    assert_eq!(
+        findings
+            .iter()
+            .map(|f| (f.group, f.lint, f.level, f.message.as_str()))
+            .collect::<Vec<_>>(),
+        vec![
+            (
+                "audit",
+                "unguarded_state_write",
+                LintLevel::Warn,
+                "callable by anyone, writes 0x42::Coin::Balance without taking a signer"
+            ),
+            (
+                "audit",
+                "unguarded_state_write",
+                LintLevel::Deny,
+                "callable by anyone, writes 0x42::Coin::Balance without taking a signer"
+            ),
+            (
+                "audit",
+                "unspecified_external_aborts",
+                LintLevel::Warn,
+                "calls 1 function(s) of other modules without `aborts_if` conditions"
+            ),
+            (
+                "style",
+                "undocumented_module",
+                LintLevel::Warn,
+                "module has no doc"
+            ),
+        ]
+    );
+    // Denied lints are reported as errors, the others as warnings.
+    assert!(env.has_errors());
+    assert_eq!(env.error_count(), 1);
+    assert_eq!(env.diag_count(Severity::Warning), 4);
+
+    let report = lint_report(&env, &findings);
+    let headers = report
+        .lines()
+        .filter(|l| !l.trim_start().starts_with('['))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        headers,
+        vec![
+            "audit (3 finding(s))",
+            "  unguarded_state_write (2)",
+            "  unspecified_external_aborts (1)",
+            "style (1 finding(s))",
+            "  undocumented_module (1)",
+        ]
+    );
+    assert!(report.contains(
+        "    [deny] callable by anyone, writes 0x42::Coin::Balance without taking a signer "
+    ));
+}
+
+#[test]
+fn invalid_lint_level() {
+    let env = common::build_ok(
+        "/// M.
+        #[lint(forbid(unguarded_state_write))]
+        module 0x42::M {
+            struct R has key { v: u64 }
+            public fun publish(s: &signer) { move_to(s, R { v: 0 }) }
+            public fun set(a: address): u64 acquires R { borrow_global_mut<R>(a).v = 0; borrow_global<R>(a).v }
+        }",
+    );
+    // The level is determined when a lint reports a finding, which then has the default level.
+    let findings = run_lints(&env);
+    assert_eq!(
+        findings.iter().map(|f| f.level).collect::<Vec<_>>(),
+        vec![LintLevel::Warn]
+    );
+    assert_eq!(env.error_count(), 1);
+    let mut writer = Buffer::no_color();
+    env.report_diag(&mut writer, Severity::Error);
+    assert!(String::from_utf8_lossy(&writer.into_inner())
+        .starts_with("error: unknown lint level `forbid`"));
+}
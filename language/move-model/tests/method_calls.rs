@@ -0,0 +1,85 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for recording calls in method style in the model.
+
+mod common;
+
+use move_model::{
+    model::{AutoBorrow, GlobalEnv, MethodCall},
+    ty::{PrimitiveType, Type},
+};
+use tempfile::TempDir;
+
+const MODULE: &str = "
+module 0x42::M {
+    struct Box<T> has drop { value: T }
+
+    public fun value<T: copy>(self: &Box<T>): T { self.value }
+
+    public fun set<T: drop>(self: &mut Box<T>, value: T) { self.value = value }
+
+    public fun get(b: Box<u64>): u64 { value(&b) }
+
+    public fun reset(b: &mut Box<u64>) { set(b, 0) }
+}
+";
+
+#[test]
+fn method_calls() {
+    let mut env = common::build_ok(MODULE);
+    let get = env.find_function_by_name_str("M::get").unwrap();
+    let reset = env.find_function_by_name_str("M::reset").unwrap();
+    let file_id = get.get_loc().file_id();
+    let (get_id, reset_id) = (get.get_qualified_id(), reset.get_qualified_id());
+    let value_id = env
+        .find_function_by_name_str("M::value")
+        .unwrap()
+        .get_qualified_id();
+    let set_id = env
+        .find_function_by_name_str("M::set")
+        .unwrap()
+        .get_qualified_id();
+    let u64_ty = Type::Primitive(PrimitiveType::U64);
+
+    // The calls as if written `b.value()` and `b.set(0)`.
+    let value_call = MethodCall {
+        loc: common::loc_of(file_id, MODULE, "value(&b)", 0),
+        name: env.symbol_pool().make("value"),
+        target: value_id.instantiate(vec![u64_ty.clone()]),
+        auto_borrow: AutoBorrow::Immutable,
+    };
+    let set_call = MethodCall {
+        loc: common::loc_of(file_id, MODULE, "set(b, 0)", 0),
+        name: env.symbol_pool().make("set"),
+        target: set_id.instantiate(vec![u64_ty]),
+        auto_borrow: AutoBorrow::None,
+    };
+    env.add_method_call(reset_id, set_call.clone());
+    env.add_method_call(get_id, value_call.clone());
+
+    let get = env.get_function(get_id);
+    assert_eq!(get.get_method_calls(), &[value_call.clone()]);
+    assert_eq!(get.get_method_call_at(&value_call.loc), Some(&value_call));
+    assert_eq!(get.get_method_call_at(&set_call.loc), None);
+    // Calls in a file are ordered by location.
+    assert_eq!(
+        env.get_method_calls_in_file(file_id),
+        vec![(get_id, &value_call), (reset_id, &set_call)]
+    );
+
+    // The calls are kept when saving and loading the environment.
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("env.bin").to_string_lossy().to_string();
+    env.save(&path).unwrap();
+    let loaded = GlobalEnv::load(&path).unwrap();
+    let calls = loaded
+        .find_function_by_name_str("M::reset")
+        .unwrap()
+        .get_method_calls()
+        .to_vec();
+    assert_eq!(calls.len(), 1);
+    assert_eq!(loaded.symbol_pool().string(calls[0].name).as_str(), "set");
+    assert_eq!(calls[0].target.id, set_id.id);
+    assert_eq!(calls[0].auto_borrow, AutoBorrow::None);
+}
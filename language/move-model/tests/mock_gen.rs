@@ -0,0 +1,99 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for the generation of mock modules.
+
+mod common;
+
+use move_model::mock_gen::generate_mock_module;
+
+const MODULE: &str = "address 0x42 {
+module Coin {
+    friend 0x42::Bank;
+
+    const EINSUFFICIENT: u64 = 1;
+
+    struct Balance has key { value: u64 }
+
+    public fun value(a: address): u64 acquires Balance {
+        borrow_global<Balance>(a).value
+    }
+    spec value {
+        aborts_if !exists<Balance>(a);
+        ensures result == global<Balance>(a).value;
+    }
+
+    public(friend) fun withdraw(a: address, amount: u64) acquires Balance {
+        let balance = borrow_global_mut<Balance>(a);
+        assert!(balance.value >= amount, EINSUFFICIENT);
+        balance.value = balance.value - amount;
+    }
+}
+
+module Bank {
+    use 0x42::Coin;
+    public fun pay(from: address) { Coin::withdraw(from, Coin::value(from)) }
+}
+}
+";
+
+const BANK: &str = "
+module 0x42::Bank {
+    use 0x42::Coin;
+    public fun pay(from: address) { Coin::withdraw(from, Coin::value(from)) }
+}
+";
+
+const MOCK: &str = "// Mock of module `0x42::Coin`. Functions abort and are specified by the original specifications.
+module 0x42::Coin {
+    friend 0x42::Bank;
+
+    const EINSUFFICIENT: u64 = 1;
+
+    struct Balance has key { value: u64 }
+
+    public fun value(a: address): u64 { abort 0 }
+    spec value {
+        aborts_if !exists<Balance>(a);
+        ensures result == global<Balance>(a).value;
+    }
+
+    public(friend) fun withdraw(a: address, amount: u64) { abort 0 }
+
+    spec module {
+        pragma verify = false;
+    }
+
+    spec value {
+        pragma opaque;
+    }
+
+    spec withdraw {
+        pragma opaque;
+    }
+}
+";
+
+#[test]
+fn mock_module() {
+    let env = common::build_ok(MODULE);
+    let module_env = env.find_module_by_name_str("0x42::Coin").unwrap();
+    let mock = generate_mock_module(&env, &module_env).unwrap();
+    assert_eq!(mock, MOCK);
+
+    // The mock builds, with the same signatures for its users, and its functions are opaque.
+    let env = common::build_ok(&format!("{}{}", mock, BANK));
+    let fun_env = env.find_function_by_name_str("Coin::value").unwrap();
+    assert!(fun_env.is_opaque());
+    assert_eq!(fun_env.get_parameter_count(), 1);
+    assert_eq!(fun_env.get_spec().conditions.len(), 2);
+    assert!(!fun_env.is_pragma_true("verify", || true));
+}
+
+#[test]
+fn cannot_mock_scripts() {
+    let env = common::build_ok("script { fun main() {} }");
+    let script = env.get_modules().find(|m| m.is_script_module()).unwrap();
+    let err = generate_mock_module(&env, &script).unwrap_err();
+    assert_eq!(err.to_string(), "cannot mock a script");
+}
@@ -0,0 +1,81 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for loading published modules from responses of a node REST endpoint.
+
+mod common;
+
+use move_binary_format::CompiledModule;
+use move_model::{
+    module_fetcher::{parse_account_modules, sort_by_dependencies},
+    run_bytecode_model_builder,
+};
+
+const MODULES: &str = "
+module 0x1::A {
+    public fun a(): u64 { 1 }
+}
+module 0x2::B {
+    public fun b(): u64 { 0x1::A::a() + 1 }
+}
+module 0x2::C {
+    public fun c(): u64 { 0x2::B::b() + 0x1::A::a() }
+}
+";
+
+/// Returns the response of the modules endpoint listing the modules.
+fn response(modules: &[&CompiledModule]) -> String {
+    let entries = modules
+        .iter()
+        .map(|m| {
+            let mut bytes = vec![];
+            m.serialize(&mut bytes).unwrap();
+            format!(r#"{{"bytecode": "0x{}", "abi": {{}}}}"#, hex::encode(bytes))
+        })
+        .collect::<Vec<_>>();
+    format!("[{}]", entries.join(", "))
+}
+
+fn names(modules: &[CompiledModule]) -> Vec<String> {
+    modules
+        .iter()
+        .map(|m| m.self_id().name().to_string())
+        .collect()
+}
+
+#[test]
+fn parse_and_sort_modules() {
+    let env = common::build_ok(MODULES);
+    let compiled = env.get_bytecode_modules().collect::<Vec<_>>();
+    let by_name = |name: &str| {
+        *compiled
+            .iter()
+            .find(|m| m.self_id().name().as_str() == name)
+            .unwrap()
+    };
+
+    // Responses list modules in any order.
+    let parsed =
+        parse_account_modules(&response(&[by_name("C"), by_name("B"), by_name("A")])).unwrap();
+    assert_eq!(names(&parsed), vec!["C", "B", "A"]);
+    let sorted = sort_by_dependencies(parsed).unwrap();
+    assert_eq!(names(&sorted), vec!["A", "B", "C"]);
+
+    // The sorted modules can be loaded into a model.
+    let env = run_bytecode_model_builder(&sorted).unwrap();
+    assert!(!env.has_errors());
+    assert!(env.find_function_by_name_str("0x2::C::c").is_some());
+}
+
+#[test]
+fn invalid_responses() {
+    let err = parse_account_modules(r#"[{"bytecode": "0xzz"}]"#).unwrap_err();
+    assert_eq!(err.to_string(), "invalid hex encoding of module");
+    let err = parse_account_modules(r#"[{"bytecode": "0x0102"}]"#).unwrap_err();
+    assert!(
+        err.to_string().starts_with("cannot deserialize module"),
+        "{}",
+        err
+    );
+    assert!(parse_account_modules(r#"{"bytecode": "0x"}"#).is_err());
+}
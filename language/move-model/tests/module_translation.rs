@@ -0,0 +1,88 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for translating modules by dependency level, which must give the same model as
+//! translating them in order.
+
+mod common;
+
+use move_model::model::GlobalEnv;
+
+const MODULES: &str = "
+module 0x42::A {
+    struct R has key { v: u64 }
+    spec module {
+        invariant forall a: address where exists<R>(a): global<R>(a).v > 0;
+    }
+}
+module 0x42::B {
+    use 0x42::A;
+    spec fun value_of(a: address): u64 { global<A::R>(a).v }
+}
+module 0x42::Z {
+    spec fun zero(): u64 { 0 }
+}
+module 0x42::C {
+    use 0x42::A;
+    use 0x42::Z;
+    spec fun positive(a: address): bool { 0x42::B::value_of(a) > Z::zero() }
+    spec module {
+        invariant forall a: address where exists<A::R>(a): positive(a);
+    }
+    struct S has store { a: address }
+    spec S { invariant 0x42::B::value_of(a) > 0; }
+}
+";
+
+fn spec_fun_memory(env: &GlobalEnv, module: &str, name: &str) -> Vec<String> {
+    let module_env = env.find_module_by_name_str(module).unwrap();
+    let (_, decl) = module_env
+        .get_spec_funs()
+        .find(|(_, decl)| decl.name == env.symbol_pool().make(name))
+        .unwrap();
+    decl.used_memory
+        .iter()
+        .map(|mem| env.display(mem).to_string())
+        .collect()
+}
+
+#[test]
+fn translate_by_dependency_level() {
+    let env = common::build_from_source(MODULES);
+
+    // Module ids follow the order of the compiled units, not the levels: `Z` is translated
+    // before `B`, but added after it.
+    let names = env
+        .get_modules()
+        .map(|m| m.get_full_name_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["0x42::A", "0x42::B", "0x42::Z", "0x42::C"]);
+
+    // The memory used by spec functions is known across modules of different levels.
+    assert_eq!(spec_fun_memory(&env, "0x42::B", "value_of"), vec!["A::R"]);
+    assert_eq!(spec_fun_memory(&env, "0x42::C", "positive"), vec!["A::R"]);
+    assert!(spec_fun_memory(&env, "0x42::Z", "zero").is_empty());
+
+    // Global invariants get distinct ids, and are found for the memory they use.
+    let r = env
+        .find_struct_by_tag(&move_core_types::parser::parse_struct_tag("0x42::A::R").unwrap())
+        .unwrap();
+    let invariants = env.get_global_invariants_for_memory(&r.instantiate(vec![]));
+    assert_eq!(invariants.len(), 2);
+    let declaring = invariants
+        .iter()
+        .map(|id| {
+            let inv = env.get_global_invariant(*id).unwrap();
+            env.get_module(inv.declaring_module).get_full_name_str()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(declaring, vec!["0x42::A", "0x42::C"]);
+
+    // A data invariant which depends on global state through a spec function of another
+    // module is rejected.
+    assert!(env.has_diag("data invariants cannot depend on global state"));
+    assert_eq!(
+        env.diag_count(codespan_reporting::diagnostic::Severity::Error),
+        1
+    );
+}
@@ -0,0 +1,129 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for saving and loading environments.
+
+mod common;
+
+use move_model::model::GlobalEnv;
+use std::fs;
+use tempfile::TempDir;
+
+const MODULE: &str = "
+module 0x42::M {
+    struct Counter<phantom T> has key { value: u64 }
+
+    /// Increments the counter.
+    public fun incr<T>(addr: address): u64 acquires Counter {
+        let c = borrow_global_mut<Counter<T>>(addr);
+        c.value = c.value + 1;
+        c.value
+    }
+    spec incr {
+        aborts_if !exists<Counter<T>>(addr);
+        aborts_if global<Counter<T>>(addr).value + 1 > MAX_U64;
+        ensures result == value_of<T>(addr);
+    }
+
+    spec fun value_of<T>(addr: address): u64 {
+        global<Counter<T>>(addr).value
+    }
+
+    spec module {
+        invariant<T> update forall a: address where old(exists<Counter<T>>(a)):
+            global<Counter<T>>(a).value >= old(global<Counter<T>>(a).value);
+    }
+}
+";
+
+/// Describes the modules of the environment, with the declarations, types, specs, and
+/// locations they contain.
+fn describe(env: &GlobalEnv) -> Vec<String> {
+    let tctx = env.get_type_display_ctx();
+    let mut lines = vec![];
+    for module_env in env.get_modules() {
+        lines.push(format!("module {}", module_env.get_full_name_str()));
+        for struct_env in module_env.get_structs() {
+            lines.push(format!(
+                "struct {} {{ {} }}",
+                struct_env.get_full_name_str(),
+                struct_env
+                    .get_fields()
+                    .map(|f| format!(
+                        "{}: {}",
+                        f.get_name().display(env.symbol_pool()),
+                        f.get_type().display(&tctx)
+                    ))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        for fun_env in module_env.get_functions() {
+            lines.push(format!(
+                "fun {}({}) at {:?}: {}",
+                fun_env.get_full_name_str(),
+                fun_env
+                    .get_parameter_types()
+                    .iter()
+                    .map(|ty| ty.display(&tctx).to_string())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                env.get_file_and_location(&fun_env.get_loc()),
+                fun_env.get_doc()
+            ));
+            for cond in &fun_env.get_spec().conditions {
+                lines.push(format!("  {} {}", cond.kind, cond.exp.display(env)));
+            }
+        }
+        for (_, decl) in module_env.get_spec_funs() {
+            if let Some(body) = &decl.body {
+                lines.push(format!(
+                    "spec fun {} = {}",
+                    decl.name.display(env.symbol_pool()),
+                    body.display(env)
+                ));
+            }
+        }
+        for inv in env.get_global_invariants_for_module(module_env.get_id()) {
+            lines.push(format!("invariant {}", inv.cond.display(env)));
+        }
+    }
+    lines
+}
+
+#[test]
+fn save_and_load() {
+    let dir = TempDir::new().unwrap();
+    let env = common::build_from_source(MODULE);
+    assert!(!env.has_errors());
+    let path = dir.path().join("env.bcs").to_string_lossy().to_string();
+    env.save(&path).unwrap();
+
+    let description = describe(&env);
+    assert!(description.iter().any(|line| line.contains("aborts_if")));
+    assert!(description.iter().any(|line| line.starts_with("invariant")));
+    let loaded = GlobalEnv::load(&path).unwrap();
+    assert!(!loaded.has_errors());
+    assert_eq!(describe(&loaded), description);
+
+    // The loaded environment can be saved again, yielding the same environment.
+    let other_path = dir.path().join("env2.bcs").to_string_lossy().to_string();
+    loaded.save(&other_path).unwrap();
+    assert_eq!(
+        describe(&GlobalEnv::load(&other_path).unwrap()),
+        description
+    );
+}
+
+#[test]
+fn save_and_load_errors() {
+    let dir = TempDir::new().unwrap();
+    let env = common::build_from_source("module 0x42::M { fun f(): u64 { true } }");
+    assert!(env.has_errors());
+    let path = dir.path().join("env.bcs").to_string_lossy().to_string();
+    assert!(env.save(&path).is_err());
+
+    fs::write(&path, b"not an environment").unwrap();
+    assert!(GlobalEnv::load(&path).is_err());
+    assert!(GlobalEnv::load(&dir.path().join("missing.bcs").to_string_lossy()).is_err());
+}
@@ -0,0 +1,213 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for rebuilding a model from the results of a previous build.
+
+mod common;
+
+use move_compiler::shared::Flags;
+use move_model::{
+    model::GlobalEnv, options::ModelBuilderOptions, run_model_builder_reusing_bytecode,
+    run_model_builder_with_options, run_model_builder_with_package_cache,
+    run_model_builder_with_verified_program, run_spec_checker_for_module, PackageCache,
+};
+use std::collections::BTreeSet;
+use tempfile::TempDir;
+
+const MODULE_A: &str = "module 0x42::A { public fun one(): u64 { 1 } }";
+const MODULE_B: &str = "module 0x42::B { use 0x42::A; public fun two(): u64 { A::one() + 1 } }";
+
+fn fun_names(env: &GlobalEnv) -> BTreeSet<String> {
+    env.get_modules()
+        .flat_map(|m| {
+            m.get_functions()
+                .map(|f| f.get_full_name_str())
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Builds a model of the modules `A` and `B`, returning it with the paths of their sources.
+fn build(dir: &TempDir) -> (GlobalEnv, String, String) {
+    let a = common::write_source(dir.path(), "A.move", MODULE_A);
+    let b = common::write_source(dir.path(), "B.move", MODULE_B);
+    let (env, program) = run_model_builder_with_verified_program(
+        common::package(vec![a.clone(), b.clone()]),
+        vec![],
+        ModelBuilderOptions::default(),
+        Flags::empty(),
+    )
+    .unwrap();
+    assert!(!env.has_errors());
+    assert!(program.is_some());
+    (env, a, b)
+}
+
+#[test]
+fn rebuild_with_changed_file() {
+    let dir = TempDir::new().unwrap();
+    let (env, _, b) = build(&dir);
+    common::write_source(
+        dir.path(),
+        "B.move",
+        &MODULE_B.replace(
+            "A::one() + 1 }",
+            "A::one() + 1 } public fun three(): u64 { two() + 1 }",
+        ),
+    );
+    let new_env = run_model_builder_reusing_bytecode(&env, &[b]).unwrap();
+    assert!(!new_env.has_errors());
+    assert_eq!(
+        fun_names(&new_env),
+        ["A::one", "B::three", "B::two"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    );
+}
+
+#[test]
+fn rebuild_detects_changed_source_text() {
+    let dir = TempDir::new().unwrap();
+    let (env, _, _) = build(&dir);
+    // The changed file is not passed to the rebuild, but its text differs from the previous
+    // build.
+    common::write_source(
+        dir.path(),
+        "B.move",
+        &MODULE_B.replace("fun two()", "fun two_()"),
+    );
+    let new_env = run_model_builder_reusing_bytecode(&env, &[]).unwrap();
+    assert!(!new_env.has_errors());
+    assert!(fun_names(&new_env).contains("B::two_"));
+    // The rebuilt model can be rebuilt again.
+    let unchanged_env = run_model_builder_reusing_bytecode(&new_env, &[]).unwrap();
+    assert_eq!(fun_names(&unchanged_env), fun_names(&new_env));
+}
+
+#[test]
+fn rebuild_recompiles_dependent_modules() {
+    let dir = TempDir::new().unwrap();
+    let (env, a, _) = build(&dir);
+    // Removing the function `B` depends on must be reported in `B`, which is unchanged itself.
+    common::write_source(
+        dir.path(),
+        "A.move",
+        &MODULE_A.replace("fun one()", "fun uno()"),
+    );
+    let new_env = run_model_builder_reusing_bytecode(&env, &[a]).unwrap();
+    assert!(new_env.has_errors());
+}
+
+#[test]
+fn rebuild_requires_retained_program() {
+    let dir = TempDir::new().unwrap();
+    let a = common::write_source(dir.path(), "A.move", MODULE_A);
+    let env = run_model_builder_with_options(
+        common::package(vec![a]),
+        vec![],
+        ModelBuilderOptions::default(),
+    )
+    .unwrap();
+    assert!(run_model_builder_reusing_bytecode(&env, &[]).is_err());
+}
+
+fn spec_condition_count(env: &GlobalEnv, name: &str) -> usize {
+    env.find_function_by_name_str(name)
+        .unwrap()
+        .get_spec()
+        .conditions
+        .len()
+}
+
+#[test]
+fn recheck_specs_of_module() {
+    let dir = TempDir::new().unwrap();
+    let (env, _, _) = build(&dir);
+    common::write_source(
+        dir.path(),
+        "A.move",
+        &MODULE_A.replace("{ 1 }", "{ 1 } spec one { ensures result == 1; }"),
+    );
+    // `B` depends on `A` but not vice versa, so the broken source of `B` is not parsed.
+    common::write_source(dir.path(), "B.move", "module 0x42::B {");
+    let new_env = run_spec_checker_for_module(&env, "A").unwrap();
+    assert!(!new_env.has_errors());
+    assert_eq!(spec_condition_count(&new_env, "0x42::A::one"), 1);
+    assert_eq!(spec_condition_count(&new_env, "0x42::B::two"), 0);
+
+    // The specs of `B` are checked against the new source of `A`, and the ones re-checked before.
+    common::write_source(
+        dir.path(),
+        "B.move",
+        &MODULE_B.replace("+ 1 }", "+ 1 } spec two { ensures result == 2; }"),
+    );
+    let new_env = run_spec_checker_for_module(&new_env, "0x42::B").unwrap();
+    assert!(!new_env.has_errors());
+    assert_eq!(spec_condition_count(&new_env, "0x42::B::two"), 1);
+}
+
+#[test]
+fn recheck_specs_rejects_changed_signature() {
+    let dir = TempDir::new().unwrap();
+    let (env, _, _) = build(&dir);
+    common::write_source(
+        dir.path(),
+        "A.move",
+        &MODULE_A.replace("one(): u64 { 1 }", "one(): u128 { 1 }"),
+    );
+    assert!(run_spec_checker_for_module(&env, "A").is_err());
+    common::write_source(
+        dir.path(),
+        "A.move",
+        &MODULE_A.replace("public fun", "public(friend) fun"),
+    );
+    assert!(run_spec_checker_for_module(&env, "A").is_err());
+    // Changes to the body are not detected, and not reflected in the model.
+    common::write_source(dir.path(), "A.move", &MODULE_A.replace("{ 1 }", "{ 2 }"));
+    assert!(run_spec_checker_for_module(&env, "A").is_ok());
+    assert!(run_spec_checker_for_module(&env, "C").is_err());
+}
+
+fn build_with_cache(dir: &TempDir, cache: &mut PackageCache) -> GlobalEnv {
+    let a = dir.path().join("A.move").to_string_lossy().to_string();
+    let b = dir.path().join("B.move").to_string_lossy().to_string();
+    let env = run_model_builder_with_package_cache(
+        common::named_package(Some("P"), vec![a, b]),
+        vec![],
+        ModelBuilderOptions::default(),
+        Flags::empty(),
+        cache,
+    )
+    .unwrap();
+    assert!(!env.has_errors());
+    env
+}
+
+#[test]
+fn package_cache_holds_latest_build() {
+    let dir = TempDir::new().unwrap();
+    common::write_source(dir.path(), "A.move", MODULE_A);
+    common::write_source(dir.path(), "B.move", MODULE_B);
+    let mut cache = PackageCache::default();
+    assert!(cache.is_empty());
+    let env = build_with_cache(&dir, &mut cache);
+    assert_eq!((cache.len(), cache.module_count()), (1, 2));
+
+    // A build from the cache yields the same model.
+    let cached_env = build_with_cache(&dir, &mut cache);
+    assert_eq!(fun_names(&cached_env), fun_names(&env));
+    assert_eq!((cache.len(), cache.module_count()), (1, 2));
+
+    // A changed package replaces its previous build in the cache.
+    for idx in 0..3 {
+        common::write_source(
+            dir.path(),
+            "B.move",
+            &MODULE_B.replace("fun two()", &format!("fun two_{}()", idx)),
+        );
+        let changed_env = build_with_cache(&dir, &mut cache);
+        assert!(fun_names(&changed_env).contains(&format!("B::two_{}", idx)));
+        assert_eq!((cache.len(), cache.module_count()), (1, 2));
+    }
+}
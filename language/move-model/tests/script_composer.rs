@@ -0,0 +1,75 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for composing scripts from function calls.
+
+mod common;
+
+use move_model::{
+    options::ModelBuilderOptions, run_model_builder_with_options, script_composer::ScriptComposer,
+};
+use tempfile::TempDir;
+
+const MODULE: &str = "
+module 0x42::Coin {
+    struct Coin<phantom T> has key { value: u64 }
+    public fun mint<T>(_account: &signer, _amount: u64) {}
+    public fun transfer<T>(_from: &signer, _to: address, _amount: u64) {}
+}
+";
+
+fn composer() -> ScriptComposer {
+    ScriptComposer::new("pay")
+        .type_param("T", &["store"])
+        .param("account", "signer")
+        .param("to", "address")
+        .call("0x42::Coin::mint", &["T"], &["&account", "10"])
+        .call("0x42::Coin::transfer", &["T"], &["&account", "to", "10"])
+        .spec("aborts_if false;")
+}
+
+#[test]
+fn renders_source() {
+    let composer = composer();
+    assert_eq!(composer.calls().len(), 2);
+    assert_eq!(composer.calls()[1].args, vec!["&account", "to", "10"]);
+    assert_eq!(
+        composer.to_source(),
+        "script {
+    fun pay<T: store>(account: signer, to: address) {
+        0x42::Coin::mint<T>(&account, 10);
+        0x42::Coin::transfer<T>(&account, to, 10);
+    }
+    spec pay {
+        aborts_if false;
+    }
+}
+"
+    );
+}
+
+#[test]
+fn builds_with_modules() {
+    let dir = TempDir::new().unwrap();
+    let module_path = common::write_source(dir.path(), "Coin.move", MODULE);
+    let script_path = composer().write_to_dir(dir.path()).unwrap();
+    assert!(script_path.ends_with("pay.move"));
+    let env = run_model_builder_with_options(
+        common::package(vec![module_path, script_path.to_string_lossy().to_string()]),
+        vec![],
+        ModelBuilderOptions::default(),
+    )
+    .unwrap();
+    assert!(!env.has_errors());
+
+    // The script is represented by a pseudo module named after its function, which calls the
+    // composed functions and has the composed spec.
+    let fun_env = env.find_function_by_name_str("pay::pay").unwrap();
+    let callees = fun_env
+        .get_called_functions()
+        .into_iter()
+        .map(|id| env.get_function(id).get_full_name_str())
+        .collect::<Vec<_>>();
+    assert_eq!(callees, vec!["Coin::mint", "Coin::transfer"]);
+    assert_eq!(fun_env.get_spec().conditions.len(), 1);
+}
@@ -0,0 +1,106 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for the conflict detection and application of source edits.
+
+mod common;
+
+use codespan::{FileId, Span};
+use codespan_reporting::diagnostic::Severity;
+use move_model::{
+    model::Loc,
+    source_edit::{SourceEdit, SourceEdits},
+};
+
+const TEXT: &str = "fun f(x: u64): u64 { x + 1 }";
+
+fn loc(file_id: FileId, start: u32, end: u32) -> Loc {
+    Loc::new(file_id, Span::new(start, end))
+}
+
+#[test]
+fn overlapping_edits_conflict() {
+    let (_, file_id) = common::env_with_source("f.move", TEXT);
+    let mut edits = SourceEdits::new();
+    // Replace `x + 1` ...
+    edits
+        .add(SourceEdit::replace(loc(file_id, 21, 26), "x + 2"))
+        .unwrap();
+    // ... which overlaps with replacing `1 }`.
+    let conflict = edits
+        .add(SourceEdit::replace(loc(file_id, 25, 28), "2 }"))
+        .unwrap_err();
+    assert_eq!(conflict.edit.text(), "2 }");
+    assert_eq!(conflict.conflicting.text(), "x + 2");
+    // An insertion strictly inside the replaced text conflicts, one at its boundary does not.
+    assert!(edits
+        .add(SourceEdit::insert_before(&loc(file_id, 23, 24), "("))
+        .is_err());
+    assert!(edits
+        .add(SourceEdit::insert_before(&loc(file_id, 21, 22), "("))
+        .is_ok());
+    assert!(edits
+        .add(SourceEdit::insert_after(&loc(file_id, 25, 26), ")"))
+        .is_ok());
+    // Adding an edit twice has no effect.
+    assert!(edits
+        .add(SourceEdit::replace(loc(file_id, 21, 26), "x + 2"))
+        .is_ok());
+    assert_eq!(edits.edits_of(file_id).len(), 3);
+}
+
+#[test]
+fn insertions_at_same_position_keep_order() {
+    let (env, file_id) = common::env_with_source("f.move", TEXT);
+    let mut edits = SourceEdits::new();
+    let at = loc(file_id, 0, 3);
+    edits
+        .add(SourceEdit::insert_before(&at, "public "))
+        .unwrap();
+    edits
+        .add(SourceEdit::insert_before(&at, "inline "))
+        .unwrap();
+    edits.add(SourceEdit::delete(loc(file_id, 19, 28))).unwrap();
+    let texts = edits.apply(&env).unwrap();
+    assert_eq!(texts[&file_id], "public inline fun f(x: u64): u64 ");
+}
+
+#[test]
+fn merge_adds_nothing_on_conflict() {
+    let (_, file_id) = common::env_with_source("f.move", TEXT);
+    let mut edits = SourceEdits::new();
+    edits
+        .add(SourceEdit::replace(loc(file_id, 4, 5), "g"))
+        .unwrap();
+    let mut other = SourceEdits::new();
+    other
+        .add(SourceEdit::replace(loc(file_id, 6, 7), "y"))
+        .unwrap();
+    other
+        .add(SourceEdit::replace(loc(file_id, 4, 5), "h"))
+        .unwrap();
+    assert!(edits.merge(other).is_err());
+    assert_eq!(edits.edits_of(file_id).len(), 1);
+    assert_eq!(
+        edits.apply_to_text(file_id, TEXT).unwrap(),
+        "fun g(x: u64): u64 { x + 1 }"
+    );
+}
+
+#[test]
+fn conflicts_are_reported() {
+    let (env, file_id) = common::env_with_source("f.move", TEXT);
+    let mut edits = SourceEdits::new();
+    edits.add(SourceEdit::delete(loc(file_id, 0, 10))).unwrap();
+    edits
+        .add(SourceEdit::delete(loc(file_id, 5, 15)))
+        .unwrap_err()
+        .report(&env);
+    assert_eq!(env.diag_count(Severity::Error), 1);
+    // Edits beyond the end of the text cannot be applied.
+    let mut edits = SourceEdits::new();
+    edits
+        .add(SourceEdit::replace(loc(file_id, 0, 100), ""))
+        .unwrap();
+    assert!(edits.apply_to_text(file_id, TEXT).is_err());
+}
@@ -0,0 +1,17 @@
+module 0x42::M {
+  struct S has key { x: u64 }
+
+  fun f(a: u64, b: bool): u64 { if (b) a else 0 }
+
+  spec f {
+    let one = a + b;
+    let two = one + true;
+    aborts_if a == b;
+    ensures result == b;
+    ensures global<S>(a).x == 0;
+    modifies global<S>(b);
+    ensures exists<S>(a) && a;
+    ensures forall x: u64: x + b;
+    ensures (a + b)[0] == 1;
+  }
+}
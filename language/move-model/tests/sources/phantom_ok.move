@@ -0,0 +1,28 @@
+module 0x42::Phantom {
+    struct Coin<phantom T> has store { value: u64 }
+
+    struct Pair<phantom A: store, B: copy + drop> has copy, drop { b: B }
+
+    struct Balance<phantom T> has key { coin: Coin<T> }
+
+    // A phantom type parameter can be instantiated with a type without abilities.
+    struct NoAbilities {}
+
+    fun zero<T>(): Coin<T> {
+        Coin { value: 0 }
+    }
+
+    fun pair<A: store, B: copy + drop>(b: B): Pair<A, B> {
+        Pair { b }
+    }
+
+    fun destroy(c: Coin<NoAbilities>): u64 {
+        let Coin { value } = c;
+        value
+    }
+
+    spec module {
+        invariant<T> forall a: address where exists<Balance<T>>(a):
+            global<Balance<T>>(a).coin.value < MAX_U64;
+    }
+}
@@ -0,0 +1,28 @@
+module 0x42::M {
+
+  const MAX_AMOUNT: u64 = 100;
+
+  spec module {
+    global total_supply: num;
+  }
+
+  fun transfer(amount: u64, receiver: address): u64 {
+    let fee = 1;
+    amount - fee
+  }
+
+  spec transfer {
+    // Misspelled parameter.
+    aborts_if amout > MAX_AMOUNT;
+    // Misspelled constant.
+    aborts_if amount > MAX_AMUONT;
+    // Misspelled spec var.
+    ensures total_suply == old(total_supply);
+    // Misspelled result.
+    ensures reslt == amount - 1;
+    // Function body locals are not in scope.
+    ensures fee == 1;
+    // Quantified variable out of scope.
+    ensures (forall a: address: a == receiver) ==> a == receiver;
+  }
+}
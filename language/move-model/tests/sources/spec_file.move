@@ -0,0 +1,11 @@
+module 0x42::M {
+    struct S has key { x: u64 }
+
+    public fun inc(x: u64): u64 {
+        x + 1
+    }
+
+    public fun get(a: address): u64 acquires S {
+        borrow_global<S>(a).x
+    }
+}
@@ -0,0 +1,15 @@
+spec 0x42::M {
+    spec S {
+        invariant x > 0;
+    }
+
+    spec inc {
+        aborts_if x + 1 > MAX_U64;
+        ensures result == x + 1;
+    }
+
+    spec get {
+        aborts_if !exists<S>(a);
+        ensures result == global<S>(a).x;
+    }
+}
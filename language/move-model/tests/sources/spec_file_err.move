@@ -0,0 +1,5 @@
+module 0x42::M {
+    public fun inc(x: u64): u64 {
+        x + 1
+    }
+}
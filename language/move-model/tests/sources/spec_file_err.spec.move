@@ -0,0 +1,11 @@
+spec 0x42::M {
+    spec inc {
+        ensures result == x + 1;
+    }
+}
+
+module 0x42::N {
+    public fun dec(x: u64): u64 {
+        x - 1
+    }
+}
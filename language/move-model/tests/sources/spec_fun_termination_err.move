@@ -0,0 +1,36 @@
+module 0x42::M {
+  spec module {
+    // Terminates: `n` decreases and is positive at the recursive call.
+    fun sum(v: vector<num>, n: num): num {
+      if (n <= 0 || n > len(v)) 0 else sum(v, n - 1) + v[n - 1]
+    }
+
+    // Terminates: `n` decreases at each cycle of calls.
+    fun even(n: num): bool {
+      if (n == 0) true else n > 0 && odd(n - 1)
+    }
+    fun odd(n: num): bool {
+      if (n == 0) false else !even(n)
+    }
+
+    // Terminates: `n` is halved while positive.
+    fun log2(n: num): num {
+      if (n > 1) log2(n / 2) + 1 else 0
+    }
+
+    // Does not terminate: the argument does not change.
+    fun diverge(x: num): num {
+      diverge(x) + 1
+    }
+
+    // Does not terminate: `n` is not bounded from below.
+    fun count_down(n: num): num {
+      count_down(n - 1)
+    }
+
+    // Does not terminate: `n` is shadowed at the recursive call.
+    fun shadowed(n: num): num {
+      if (n > 0) { let n = n + 1; shadowed(n - 1) } else 0
+    }
+  }
+}
@@ -0,0 +1,64 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for the extraction of repeated spec fragments into spec helper functions.
+
+mod common;
+
+use move_model::spec_extraction::{find_spec_helpers, spec_helper_edits};
+
+const MODULE: &str = "module 0x42::M {
+    struct R has key { v: u64 }
+
+    fun f(a: address): u64 acquires R { borrow_global<R>(a).v }
+    spec f {
+        requires exists<R>(a) && global<R>(a).v > 10 && global<R>(a).v < 100;
+        ensures result == global<R>(a).v;
+    }
+
+    fun g(b: address, x: u64): u64 acquires R { borrow_global<R>(b).v + x }
+    spec g {
+        requires exists<R>(b) && global<R>(b).v > 10 && global<R>(b).v < 100;
+        aborts_if x + global<R>(b).v > MAX_U64;
+        ensures result == old(global<R>(b).v) + x;
+        ensures result == old(global<R>(b).v) + x;
+    }
+}
+";
+
+#[test]
+fn extract_repeated_fragments() {
+    let env = common::build_ok(MODULE);
+    let module_env = env.find_module_by_name_str("M").unwrap();
+    let helpers = find_spec_helpers(&module_env);
+    // The fragments depending on the pre-state are not extracted, nor are the fragments of
+    // the extracted one.
+    assert_eq!(helpers.len(), 1);
+    let helper = &helpers[0];
+    assert_eq!(
+        helper.declaration(),
+        "spec fun extracted_spec_1(a: address): bool {\n    \
+         exists<R>(a) && global<R>(a).v > 10 && global<R>(a).v < 100\n}"
+    );
+    let args = helper
+        .calls
+        .iter()
+        .map(|(_, args)| args.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(args, vec![vec!["a".to_string()], vec!["b".to_string()]]);
+
+    // The rewritten module declares the helper and calls it.
+    let edits = spec_helper_edits(&env).unwrap();
+    let texts = edits.apply(&env).unwrap();
+    let text = texts.values().next().unwrap();
+    assert!(text.contains("requires extracted_spec_1(a);"), "{}", text);
+    assert!(text.contains("requires extracted_spec_1(b);"), "{}", text);
+    assert!(
+        text.contains("\n    spec fun extracted_spec_1(a: address): bool {"),
+        "{}",
+        text
+    );
+    let env = common::build_ok(text);
+    let module_env = env.find_module_by_name_str("M").unwrap();
+    assert!(find_spec_helpers(&module_env).is_empty());
+}
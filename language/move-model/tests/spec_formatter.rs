@@ -0,0 +1,80 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for the formatting of specification blocks.
+
+use move_command_line_common::files::FileHash;
+use move_model::{
+    model::GlobalEnv,
+    spec_formatter::{format_specs, spec_format_edits},
+};
+
+const SOURCE: &str = "module 0x42::M {
+    fun f(x: u64): u64 {
+        spec {   assume x>0; };
+        x
+    }
+    spec f {
+      // The result is the argument.
+      ensures result==x; // trailing
+         aborts_if false;
+
+      ensures result == x && result == x && result == x && result == x && result == x && result == x;
+      requires x > 0 /* inside */ && x < 10;
+    }
+    spec module {
+        use 0x1::Signer;
+        invariant   true;
+    }
+}
+";
+
+const FORMATTED: &str = "module 0x42::M {
+    fun f(x: u64): u64 {
+        spec { assume x > 0; };
+        x
+    }
+    spec f {
+        // The result is the argument.
+        ensures result == x; // trailing
+        aborts_if false;
+
+        ensures result == x
+                && result == x
+                && result == x
+                && result == x
+                && result == x
+                && result == x;
+        requires x > 0 /* inside */ && x < 10;
+    }
+    spec module {
+        use 0x1::Signer;
+        invariant   true;
+    }
+}
+";
+
+#[test]
+fn format_spec_blocks() {
+    assert_eq!(format_specs(SOURCE).unwrap(), FORMATTED);
+    // Formatting is idempotent.
+    assert_eq!(format_specs(FORMATTED).unwrap(), FORMATTED);
+}
+
+#[test]
+fn format_edits_of_changed_blocks() {
+    let mut env = GlobalEnv::new();
+    let file_id = env.add_source(FileHash::new(SOURCE), "M.move", SOURCE, false);
+    let edits = spec_format_edits(&env, file_id).unwrap();
+    // The inline block and the block of `f` change, the module block is not formatted.
+    assert_eq!(edits.edits_of(file_id).len(), 2);
+    assert_eq!(edits.apply_to_text(file_id, SOURCE).unwrap(), FORMATTED);
+
+    let formatted_id = env.add_source(FileHash::new(FORMATTED), "N.move", FORMATTED, false);
+    assert!(spec_format_edits(&env, formatted_id).unwrap().is_empty());
+}
+
+#[test]
+fn format_rejects_parse_errors() {
+    assert!(format_specs("module 0x42::M { spec module { invariant ; } }").is_err());
+}
@@ -0,0 +1,88 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for running spec tests.
+
+mod common;
+
+use move_model::spec_test::{run_spec_tests, spec_test_report};
+
+const MODULE: &str = "
+module 0x42::M {
+    struct S has drop { x: u64 }
+
+    spec fun max(x: num, y: num): num { if (x >= y) x else y }
+
+    #[spec_test]
+    fun test_max() {
+        spec {
+            assert max(1, 2) == 2;
+            assert max(S{x: 3}.x, 2) == 3;
+        }
+    }
+
+    #[spec_test]
+    fun test_failing() {
+        spec {
+            assert max(1, 2) == 2;
+            assert max(1, 2) == 1;
+        };
+        spec {
+            assert 1 / 0 == 0;
+        }
+    }
+
+    #[spec_test]
+    fun test_with_param(_x: u64) {}
+
+    fun not_a_test() {
+        spec {
+            assert max(1, 2) == 1;
+        }
+    }
+}
+";
+
+#[test]
+fn spec_tests() {
+    let env = common::build_ok(MODULE);
+    let results = run_spec_tests(&env);
+    assert_eq!(
+        results
+            .iter()
+            .map(|r| (r.name.as_str(), r.conditions, r.passed()))
+            .collect::<Vec<_>>(),
+        vec![
+            ("0x42::M::test_failing", 3, false),
+            ("0x42::M::test_max", 2, true)
+        ]
+    );
+    assert_eq!(
+        results[0]
+            .failures
+            .iter()
+            .map(|f| f.message.as_str())
+            .collect::<Vec<_>>(),
+        vec![
+            "spec test assertion does not hold",
+            "spec test assertion cannot be evaluated: division by zero"
+        ]
+    );
+    // The failures and the test with a parameter are reported as errors.
+    assert_eq!(env.error_count(), 3);
+
+    let report = spec_test_report(&env, &results);
+    let lines = report
+        .lines()
+        .filter(|l| !l.starts_with("  "))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        lines,
+        vec![
+            "spec test 0x42::M::test_failing (3 condition(s)) ... FAILED",
+            "spec test 0x42::M::test_max (2 condition(s)) ... ok",
+            "1 passed, 1 failed",
+        ]
+    );
+    assert!(report.contains("\n  spec test assertion does not hold "));
+}
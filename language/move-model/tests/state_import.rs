@@ -0,0 +1,147 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for importing on-chain resources into a global state.
+
+mod common;
+
+use move_model::{
+    global_state::GlobalState,
+    model::{GlobalEnv, QualifiedInstId, StructId},
+    state_import::{import_account_resources, import_state_dump},
+    ty::Type,
+    value::MoveValue,
+};
+use num::{BigInt, BigUint};
+
+const MODULE: &str = "
+module 0x42::M {
+    struct Coin {}
+    struct Balance<phantom T> has key { value: u64 }
+    struct Inner has store { v: u128 }
+    struct Info has key {
+        owner: address,
+        name: vector<u8>,
+        flags: vector<bool>,
+        inner: Inner,
+    }
+}
+";
+
+/// Returns the type of the struct with the given name, instantiated with the types.
+fn struct_ty(env: &GlobalEnv, name: &str, inst: Vec<Type>) -> QualifiedInstId<StructId> {
+    let module_env = env.find_module_by_name_str("M").unwrap();
+    let struct_env = module_env
+        .find_struct(env.symbol_pool().make(name))
+        .unwrap();
+    module_env
+        .get_id()
+        .qualified_inst(struct_env.get_id(), inst)
+}
+
+fn coin_balance(env: &GlobalEnv) -> QualifiedInstId<StructId> {
+    let coin = struct_ty(env, "Coin", vec![]);
+    struct_ty(
+        env,
+        "Balance",
+        vec![Type::Struct(coin.module_id, coin.id, coin.inst)],
+    )
+}
+
+fn number(n: u64) -> MoveValue {
+    MoveValue::Number(BigInt::from(n))
+}
+
+#[test]
+fn state_dump() {
+    let env = common::build_ok(MODULE);
+    let mut state = GlobalState::new();
+    let summary = import_state_dump(
+        &env,
+        &mut state,
+        r#"[
+            {"address": "0x1", "type": "0x42::M::Balance<0x42::M::Coin>", "bcs": "0x0500000000000000"},
+            {"address": "0x2", "type": "0x42::M::Balance<0x42::M::Coin>", "data": {"value": "7"}},
+            {"address": "0x1", "type": "0x7::Other::T", "data": {}}
+        ]"#,
+    )
+    .unwrap();
+    assert_eq!(summary.imported, 2);
+    assert_eq!(summary.skipped, vec!["0x7::Other::T".to_string()]);
+
+    let ty = coin_balance(&env);
+    assert_eq!(
+        state.get(&BigUint::from(1u32), &ty),
+        Some(&MoveValue::Struct(ty.clone(), vec![number(5)]))
+    );
+    assert_eq!(
+        state.get(&BigUint::from(2u32), &ty),
+        Some(&MoveValue::Struct(ty.clone(), vec![number(7)]))
+    );
+}
+
+#[test]
+fn account_resources() {
+    let env = common::build_ok(MODULE);
+    let mut state = GlobalState::new();
+    let summary = import_account_resources(
+        &env,
+        &mut state,
+        "0x3",
+        r#"[
+            {"type": "0x42::M::Info", "data": {
+                "owner": "0x42",
+                "name": "0x0102",
+                "flags": [true, false],
+                "inner": {"v": "340282366920938463463374607431768211455"}
+            }},
+            {"type": "0x42::M::Balance<0x42::M::Coin>", "data": {"value": 3}}
+        ]"#,
+    )
+    .unwrap();
+    assert_eq!(summary.imported, 2);
+    assert!(summary.skipped.is_empty());
+
+    let info = struct_ty(&env, "Info", vec![]);
+    let inner = struct_ty(&env, "Inner", vec![]);
+    let expected = MoveValue::Struct(
+        info.clone(),
+        vec![
+            MoveValue::Address(BigUint::from(0x42u32)),
+            MoveValue::Vector(vec![number(1), number(2)]),
+            MoveValue::Vector(vec![MoveValue::Bool(true), MoveValue::Bool(false)]),
+            MoveValue::Struct(inner, vec![MoveValue::Number(BigInt::from(u128::MAX))]),
+        ],
+    );
+    assert_eq!(state.get(&BigUint::from(3u32), &info), Some(&expected));
+    assert!(state.exists(&BigUint::from(3u32), &coin_balance(&env)));
+}
+
+#[test]
+fn invalid_resources() {
+    let env = common::build_ok(MODULE);
+    let mut state = GlobalState::new();
+    let err = import_account_resources(
+        &env,
+        &mut state,
+        "0x3",
+        r#"[{"type": "0x42::M::Balance<0x42::M::Coin>", "data": {"amount": 3}}]"#,
+    )
+    .unwrap_err();
+    assert_eq!(
+        format!("{:#}", err),
+        "resource `0x42::M::Balance<0x42::M::Coin>` at 0x3: missing field `value`"
+    );
+
+    let err = import_state_dump(
+        &env,
+        &mut state,
+        r#"[{"address": "0x1", "type": "0x42::M::Balance<0x42::M::Coin>"}]"#,
+    )
+    .unwrap_err();
+    assert_eq!(
+        format!("{:#}", err),
+        "resource `0x42::M::Balance<0x42::M::Coin>` at 0x1: neither `bcs` nor `data` given"
+    );
+    assert!(state.is_empty());
+}
@@ -0,0 +1,70 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for stripping specifications and sources from an environment.
+
+mod common;
+
+const MODULE: &str = "
+module 0x42::M {
+    struct R has key { v: u64 }
+    spec R { invariant v > 0; }
+
+    spec module {
+        invariant forall a: address where exists<R>(a): global<R>(a).v < 10;
+    }
+
+    spec fun value_of(a: address): u64 { global<R>(a).v }
+
+    /// Returns the value at the address.
+    public fun value(a: address): u64 acquires R { borrow_global<R>(a).v }
+    spec value {
+        pragma opaque;
+        ensures result == value_of(a);
+    }
+}
+";
+
+#[test]
+fn strip_specs() {
+    let mut env = common::build_ok(MODULE);
+    env.strip_specs();
+    let module_env = env.find_module_by_name_str("0x42::M").unwrap();
+    assert_eq!(module_env.get_spec_funs().count(), 0);
+    assert!(module_env.get_spec().conditions.is_empty());
+    let struct_env = module_env.find_struct(env.symbol_pool().make("R")).unwrap();
+    assert!(struct_env.get_spec().conditions.is_empty());
+    assert!(env
+        .get_global_invariants_for_module(module_env.get_id())
+        .is_empty());
+
+    // Declarations and bytecode are kept, pragmas are not.
+    let fun_env = module_env
+        .find_function(env.symbol_pool().make("value"))
+        .unwrap();
+    assert!(fun_env.get_spec().conditions.is_empty());
+    assert!(!fun_env.is_opaque());
+    assert_eq!(fun_env.get_parameter_count(), 1);
+    assert!(!fun_env.get_bytecode().is_empty());
+    assert_eq!(
+        fun_env.get_doc().trim(),
+        "Returns the value at the address."
+    );
+}
+
+#[test]
+fn strip_sources() {
+    let mut env = common::build_ok(MODULE);
+    let fun_loc = env.find_function_by_name_str("M::value").unwrap().get_loc();
+    assert!(env.get_source(&fun_loc).is_ok());
+    env.strip_sources();
+    // The source of locations cannot be resolved anymore, without panicking, but declarations
+    // and their documentation remain.
+    assert!(env.get_source(&fun_loc).is_err());
+    let fun_env = env.find_function_by_name_str("M::value").unwrap();
+    assert_eq!(
+        fun_env.get_doc().trim(),
+        "Returns the value at the address."
+    );
+    assert_eq!(fun_env.get_spec().conditions.len(), 1);
+}
@@ -0,0 +1,85 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for resolving on-chain struct and type tags against the model.
+
+mod common;
+
+use move_core_types::parser::{parse_struct_tag, parse_type_tag};
+use move_model::{
+    model::GlobalEnv,
+    ty::{PrimitiveType, Type},
+};
+
+const MODULE: &str = "
+module 0x42::Coin {
+    struct XUS {}
+    struct Balance<phantom T> has key { value: u64 }
+    struct Pair<T1, T2> has store { first: T1, second: T2 }
+}
+";
+
+fn resolve_struct(env: &GlobalEnv, tag: &str) -> Option<String> {
+    env.resolve_struct_tag(&parse_struct_tag(tag).unwrap())
+        .map(|id| env.get_struct(id).get_full_name_str())
+}
+
+fn resolve_type(env: &GlobalEnv, tag: &str) -> Option<String> {
+    env.resolve_type_tag(&parse_type_tag(tag).unwrap())
+        .map(|ty| ty.display(&env.get_type_display_ctx()).to_string())
+}
+
+#[test]
+fn resolve_tags() {
+    let env = common::build_ok(MODULE);
+    assert_eq!(
+        resolve_struct(&env, "0x42::Coin::XUS"),
+        Some("Coin::XUS".to_string())
+    );
+    assert_eq!(
+        resolve_struct(&env, "0x42::Coin::Balance<0x42::Coin::XUS>"),
+        Some("Coin::Balance".to_string())
+    );
+
+    let inst = env
+        .resolve_struct_tag_inst(
+            &parse_struct_tag("0x42::Coin::Pair<u8, vector<0x42::Coin::XUS>>").unwrap(),
+        )
+        .unwrap();
+    let xus = env
+        .resolve_struct_tag(&parse_struct_tag("0x42::Coin::XUS").unwrap())
+        .unwrap();
+    assert_eq!(
+        inst.inst,
+        vec![
+            Type::Primitive(PrimitiveType::U8),
+            Type::Vector(Box::new(Type::Struct(xus.module_id, xus.id, vec![])))
+        ]
+    );
+
+    assert_eq!(
+        resolve_type(&env, "vector<0x42::Coin::Balance<address>>"),
+        Some("vector<Coin::Balance<address>>".to_string())
+    );
+    assert_eq!(resolve_type(&env, "signer"), Some("signer".to_string()));
+}
+
+#[test]
+fn unresolved_tags() {
+    let env = common::build_ok(MODULE);
+    // Unknown modules, structs, and type arguments.
+    assert_eq!(resolve_struct(&env, "0x43::Coin::XUS"), None);
+    assert_eq!(resolve_struct(&env, "0x42::Coin::XDX"), None);
+    assert_eq!(
+        resolve_struct(&env, "0x42::Coin::Balance<0x1::XDX::XDX>"),
+        None
+    );
+    // Wrong numbers of type arguments.
+    assert_eq!(resolve_struct(&env, "0x42::Coin::Balance"), None);
+    assert_eq!(resolve_struct(&env, "0x42::Coin::XUS<u64>"), None);
+    assert_eq!(resolve_struct(&env, "0x42::Coin::Pair<u64>"), None);
+    assert_eq!(
+        resolve_type(&env, "vector<0x42::Coin::Pair<u64, 0x42::Coin::Missing>>"),
+        None
+    );
+}
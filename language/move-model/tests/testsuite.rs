@@ -8,17 +8,33 @@ use move_binary_format::{
 };
 use move_command_line_common::testing::EXP_EXT;
 use move_compiler::shared::PackagePaths;
-use move_model::{run_bytecode_model_builder, run_model_builder};
+use move_model::{
+    ast::Spec,
+    model::{CompactionStats, GlobalEnv, TypeParameter, TypeParameterKind},
+    options::ModelBuilderOptions,
+    run_bytecode_model_builder, run_bytecode_model_builder_with_source_maps,
+    run_model_builder_with_options, SPEC_FILE_EXTENSION,
+};
 use move_prover_test_utils::baseline_test::verify_or_update_baseline;
 use std::path::Path;
 
 fn test_runner(path: &Path) -> datatest_stable::Result<()> {
+    // Files containing only specifications are tested together with the file they specify.
+    if path.to_string_lossy().ends_with(SPEC_FILE_EXTENSION) {
+        return Ok(());
+    }
+    let mut options = ModelBuilderOptions::default();
+    let spec_file = path.with_extension(SPEC_FILE_EXTENSION);
+    if spec_file.exists() {
+        options.spec_files = vec![spec_file.to_string_lossy().to_string()];
+    }
     let targets = vec![PackagePaths {
         name: None,
         paths: vec![path.to_str().unwrap().to_string()],
         named_address_map: std::collections::BTreeMap::<String, _>::new(),
     }];
-    let env = run_model_builder(targets, vec![])?;
+    let env = run_model_builder_with_options(targets, vec![], options)?;
+    check_compaction(&env);
     let diags = if env.diag_count(Severity::Warning) > 0 {
         let mut writer = Buffer::no_color();
         env.report_diag(&mut writer, Severity::Warning);
@@ -42,6 +58,30 @@ fn test_runner(path: &Path) -> datatest_stable::Result<()> {
                 let s = m.get_struct_by_def_idx(idx);
                 let other_s = other_m.get_struct_by_def_idx(idx);
                 assert_eq!(s.get_field_count(), other_s.get_field_count());
+                assert_eq!(s.get_stable_id(), other_s.get_stable_id());
+                assert_eq!(
+                    type_param_kinds(&s.get_type_parameters()),
+                    type_param_kinds(&other_s.get_type_parameters())
+                );
+                // Phantom type parameters are declared in the struct handle.
+                let handle =
+                    raw_module.struct_handle_at(raw_module.struct_def_at(idx).struct_handle);
+                assert_eq!(
+                    other_s
+                        .get_named_type_parameters()
+                        .iter()
+                        .map(|param| param.is_phantom())
+                        .collect::<Vec<_>>(),
+                    handle
+                        .type_parameters
+                        .iter()
+                        .map(|param| param.is_phantom)
+                        .collect::<Vec<_>>()
+                );
+                assert_eq!(
+                    env.find_struct_by_stable_id(other_s.get_stable_id()),
+                    Some(other_s.get_qualified_id())
+                );
                 for f in s.get_fields() {
                     let other_f = other_s.get_field_by_offset(f.get_offset());
                     assert_eq!(f.get_identifier(), other_f.get_identifier());
@@ -55,9 +95,19 @@ fn test_runner(path: &Path) -> datatest_stable::Result<()> {
                         .try_get_function_id(idx)
                         .expect("Function not found"),
                 );
-                assert_eq!(fun.get_identifier(), other_fun.get_identifier())
+                assert_eq!(fun.get_identifier(), other_fun.get_identifier());
+                assert_eq!(fun.get_stable_id(), other_fun.get_stable_id());
+                assert_eq!(
+                    type_param_kinds(&fun.get_type_parameters()),
+                    type_param_kinds(&other_fun.get_type_parameters())
+                );
+                assert_eq!(
+                    env.find_function_by_stable_id(other_fun.get_stable_id()),
+                    Some(other_fun.get_qualified_id())
+                );
             }
         }
+        check_source_maps(&env)?;
 
         "All good, no errors!".to_string()
     };
@@ -66,4 +116,105 @@ fn test_runner(path: &Path) -> datatest_stable::Result<()> {
     Ok(())
 }
 
+/// Returns the abilities and phantom-ness of type parameters, which, unlike their names, do not
+/// depend on the symbol pool of the environment.
+fn type_param_kinds(params: &[TypeParameter]) -> Vec<TypeParameterKind> {
+    params.iter().map(|param| param.1).collect()
+}
+
+/// Checks that the source maps of the modules give a model built from bytecode the names of
+/// the source model, and locations in the sources or, if these are not given, in the listing
+/// of disassembled signatures.
+fn check_source_maps(env: &GlobalEnv) -> anyhow::Result<()> {
+    let module_envs = env.get_modules().collect::<Vec<_>>();
+    let modules = || {
+        module_envs
+            .iter()
+            .map(|m| (m.get_verified_module(), Some(m.get_source_map())))
+    };
+    let sources = env
+        .get_source_file_ids()
+        .into_iter()
+        .map(|id| {
+            (
+                env.get_file(id).to_string_lossy().to_string(),
+                env.get_file_source(id).to_string(),
+            )
+        })
+        .collect::<Vec<_>>();
+    for with_sources in [true, false] {
+        let bytecode_env = run_bytecode_model_builder_with_source_maps(
+            modules(),
+            if with_sources {
+                sources.clone()
+            } else {
+                vec![]
+            },
+        )?;
+        for m in bytecode_env.get_modules() {
+            let other_m = env
+                .find_module_by_language_storage_id(&m.get_verified_module().self_id())
+                .expect("Module not found");
+            for fun in m.get_functions() {
+                let other_fun = other_m.get_function(
+                    other_m
+                        .try_get_function_id(fun.get_def_idx())
+                        .expect("Function not found"),
+                );
+                for idx in 0..fun.get_local_count() {
+                    assert_eq!(
+                        fun.get_local_name(idx)
+                            .display(bytecode_env.symbol_pool())
+                            .to_string(),
+                        other_fun
+                            .get_local_name(idx)
+                            .display(env.symbol_pool())
+                            .to_string()
+                    );
+                }
+                let loc = fun.get_loc();
+                if with_sources {
+                    // The source map locates the name of the function.
+                    assert!(other_fun.get_loc().is_enclosing(&loc));
+                } else {
+                    let source = bytecode_env.get_file_source(loc.file_id());
+                    let text = &source[loc.span().start().to_usize()..loc.span().end().to_usize()];
+                    let name = fun
+                        .get_name()
+                        .display(bytecode_env.symbol_pool())
+                        .to_string();
+                    assert!(text.contains(&name), "{}", source);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks that compacting a freshly built environment keeps everything reachable from specs.
+fn check_compaction(env: &GlobalEnv) {
+    env.compact(vec![], vec![]);
+    let check_spec = |spec: &Spec| {
+        for cond in &spec.conditions {
+            for exp in cond.all_exps() {
+                for id in exp.node_ids() {
+                    assert!(env.get_node_type_opt(id).is_some());
+                }
+            }
+        }
+    };
+    for module_env in env.get_modules() {
+        check_spec(module_env.get_spec());
+        for struct_env in module_env.get_structs() {
+            check_spec(struct_env.get_spec());
+        }
+        for fun_env in module_env.get_functions() {
+            check_spec(fun_env.get_spec());
+            let name = env.symbol_pool().string(fun_env.get_name());
+            assert_eq!(env.symbol_pool().make(&name), fun_env.get_name());
+        }
+    }
+    assert_eq!(env.compact(vec![], vec![]), CompactionStats::default());
+}
+
 datatest_stable::harness!(test_runner, "tests/sources", r".*\.move");
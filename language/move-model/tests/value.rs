@@ -0,0 +1,81 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for the conversion of model values from and to BCS.
+
+mod common;
+
+use move_core_types::account_address::AccountAddress;
+use move_model::{
+    model::GlobalEnv,
+    ty::{PrimitiveType, Type},
+    value::MoveValue,
+};
+use num::{BigInt, BigUint};
+
+const MODULE: &str = "
+module 0x42::M {
+    struct S<T> has drop { a: u8, b: vector<T>, c: address, d: bool }
+}
+";
+
+fn num(n: u64) -> MoveValue {
+    MoveValue::Number(BigInt::from(n))
+}
+
+/// Returns the type `S<u64>` and a value of it.
+fn struct_value(env: &GlobalEnv) -> (Type, MoveValue) {
+    let module_env = env.find_module_by_name_str("M").unwrap();
+    let sid = module_env
+        .find_struct(env.symbol_pool().make("S"))
+        .unwrap()
+        .get_id();
+    let inst = vec![Type::Primitive(PrimitiveType::U64)];
+    let ty = Type::Struct(module_env.get_id(), sid, inst.clone());
+    let val = MoveValue::Struct(
+        module_env.get_id().qualified_inst(sid, inst),
+        vec![
+            num(7),
+            MoveValue::Vector(vec![num(1), num(u64::MAX)]),
+            MoveValue::Address(BigUint::from(0x42u64)),
+            MoveValue::Bool(true),
+        ],
+    );
+    (ty, val)
+}
+
+#[test]
+fn bcs_round_trip() {
+    let env = common::build_ok(MODULE);
+    let (ty, val) = struct_value(&env);
+    let bytes = val.to_bcs(&env, &ty).unwrap();
+    // A struct is encoded like the tuple of its fields.
+    let expected = bcs::to_bytes(&(
+        7u8,
+        vec![1u64, u64::MAX],
+        AccountAddress::from_hex_literal("0x42").unwrap(),
+        true,
+    ))
+    .unwrap();
+    assert_eq!(bytes, expected);
+    assert_eq!(MoveValue::from_bcs(&env, &ty, &bytes).unwrap(), val);
+
+    let ty = Type::Vector(Box::new(Type::Primitive(PrimitiveType::U128)));
+    let val = MoveValue::Vector(vec![MoveValue::Number(BigInt::from(u128::MAX))]);
+    let bytes = val.to_bcs(&env, &ty).unwrap();
+    assert_eq!(MoveValue::from_bcs(&env, &ty, &bytes).unwrap(), val);
+}
+
+#[test]
+fn bcs_errors() {
+    let env = common::build_ok(MODULE);
+    let u8_ty = Type::Primitive(PrimitiveType::U8);
+    // The number does not fit into the type.
+    assert!(num(256).to_bcs(&env, &u8_ty).is_err());
+    // The value does not match the type.
+    assert!(MoveValue::Bool(false).to_bcs(&env, &u8_ty).is_err());
+    // The bytes are not a complete encoding of the type.
+    let (ty, val) = struct_value(&env);
+    let bytes = val.to_bcs(&env, &ty).unwrap();
+    assert!(MoveValue::from_bcs(&env, &ty, &bytes[..bytes.len() - 1]).is_err());
+}
@@ -72,6 +72,16 @@ pub fn boogie_function_name(fun_env: &FunctionEnv<'_>, inst: &[Type]) -> String
     )
 }
 
+/// Return boogie name of the prelude procedure which implements the given vector operation
+/// for the element type.
+pub fn boogie_vector_op_name(env: &GlobalEnv, op_name: &str, elem_ty: &Type) -> String {
+    format!(
+        "$1_Vector_{}{}",
+        op_name,
+        boogie_inst_suffix(env, &[elem_ty.to_owned()])
+    )
+}
+
 /// Return boogie name of given spec var.
 pub fn boogie_spec_var_name(
     module_env: &ModuleEnv<'_>,
@@ -114,6 +124,16 @@ pub fn boogie_choice_fun_name(id: usize) -> String {
     format!("$choice_{}", id)
 }
 
+/// Return the suffix of the boogie procedure for a named function variant. Variant names are
+/// chosen by transformations, so characters not allowed in boogie identifiers are replaced.
+pub fn boogie_named_variant_suffix(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    format!("${}", name)
+}
+
 /// Creates the name of the resource memory domain for any function for the given struct.
 /// This variable represents a local variable of the Boogie translation of this function.
 pub fn boogie_modifies_memory_name(env: &GlobalEnv, memory: &QualifiedInstId<StructId>) -> String {
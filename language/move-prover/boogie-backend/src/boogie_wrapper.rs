@@ -3,7 +3,12 @@
 
 //! Wrapper around the boogie program. Allows to call boogie and analyze the output.
 
-use std::{collections::BTreeMap, fs, num::ParseIntError, option::Option::None};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    num::ParseIntError,
+    option::Option::None,
+};
 
 use anyhow::anyhow;
 use codespan::{ByteIndex, ColumnIndex, LineIndex, Location, Span};
@@ -19,17 +24,27 @@ use move_binary_format::file_format::FunctionDefinitionIndex;
 use move_model::{
     ast::TempIndex,
     code_writer::CodeWriter,
-    model::{FunId, GlobalEnv, Loc, ModuleId, NodeId, QualifiedId, StructId},
+    model::{
+        FunId, FunctionEnv, GlobalEnv, LensEntry, LensKind, Loc, ModuleId, NodeId, QualifiedId,
+        StructId,
+    },
+    pragmas::ABORTS_IF_IS_PARTIAL_PRAGMA,
+    source_edit::{Fix, SourceEdit},
     ty::{PrimitiveType, Type},
 };
-use move_stackless_bytecode::function_target_pipeline::{FunctionTargetsHolder, FunctionVariant};
+use move_stackless_bytecode::{
+    function_target_pipeline::{FunctionTargetsHolder, FunctionVariant},
+    spec_instrumentation::ABORT_NOT_COVERED,
+    stackless_bytecode::{Bytecode, Label as CodeLabel},
+};
 
 // DEBUG
 // use backtrace::Backtrace;
 use crate::{
-    boogie_helpers::boogie_struct_name,
+    boogie_helpers::{boogie_function_name, boogie_struct_name},
+    bytecode_translator::{BYTECODE_START_MARKER, INCONSISTENT_ASSUME_PREFIX, USED_ASSUME_PREFIX},
     options::{BoogieOptions, VectorTheory},
-    prover_task_runner::{ProverTaskRunner, RunBoogieWithSeeds},
+    prover_task_runner::{ProverTaskRunner, RunBoogiePortfolio, RunBoogieWithSeeds},
 };
 
 /// A type alias for the way how we use crate `pretty`'s document type. `pretty` is a
@@ -56,12 +71,112 @@ pub struct BoogieOutput {
     pub all_output: String,
 }
 
+impl BoogieOutput {
+    /// Returns the verification time in seconds of each procedure, as reported by Boogie if
+    /// `report_procedure_times` is set.
+    pub fn procedure_times(&self) -> BTreeMap<String, f64> {
+        static TIME: Lazy<Regex> =
+            Lazy::new(|| Regex::new(r"^\s*\[(?P<secs>[0-9.]+) s\b").unwrap());
+        let mut times = BTreeMap::new();
+        let mut current = None;
+        for line in self.all_output.lines() {
+            if let Some(cap) = VERIFYING.captures(line) {
+                current = Some(cap["proc"].to_string());
+            } else if let Some(cap) = TIME.captures(line) {
+                if let (Some(proc_name), Ok(secs)) = (current.take(), cap["secs"].parse::<f64>()) {
+                    *times.entry(proc_name).or_insert(0.0) += secs;
+                }
+            }
+        }
+        times
+    }
+
+    /// Returns the functions for which errors have been reported. An error is attributed to the
+    /// function enclosing its location, or, if there is none (as for module invariants), to the
+    /// function where its execution trace starts.
+    pub fn failed_functions(&self, env: &GlobalEnv) -> BTreeSet<QualifiedId<FunId>> {
+        self.errors
+            .iter()
+            .filter_map(|error| {
+                env.get_enclosing_function(&error.loc)
+                    .or_else(|| {
+                        error.execution_trace.iter().find_map(|entry| match entry {
+                            TraceEntry::AtLocation(loc) => env.get_enclosing_function(loc),
+                            _ => None,
+                        })
+                    })
+                    .map(|fun_env| fun_env.get_qualified_id())
+            })
+            .collect()
+    }
+
+    /// Returns the total verification time in seconds of the procedures generated for each
+    /// verified function of the target modules, as far as reported by Boogie.
+    pub fn function_times(&self, env: &GlobalEnv) -> BTreeMap<QualifiedId<FunId>, f64> {
+        let proc_times = self.procedure_times();
+        let mut times = BTreeMap::new();
+        for fun_env in env
+            .get_modules()
+            .filter(|m| m.is_target())
+            .flat_map(|m| m.into_functions())
+        {
+            for (proc_name, secs) in &proc_times {
+                if is_verify_procedure_of(proc_name, &fun_env) {
+                    *times.entry(fun_env.get_qualified_id()).or_insert(0.0) += secs;
+                }
+            }
+        }
+        times
+    }
+
+    /// Returns the output reported while verifying the procedures generated for each verified
+    /// function of the target modules. Requires `report_procedure_times` to be set.
+    pub fn function_logs(&self, env: &GlobalEnv) -> BTreeMap<QualifiedId<FunId>, String> {
+        let funs = env
+            .get_modules()
+            .filter(|m| m.is_target())
+            .flat_map(|m| m.into_functions())
+            .collect_vec();
+        let mut logs: BTreeMap<QualifiedId<FunId>, String> = BTreeMap::new();
+        let mut current = None;
+        for line in self.all_output.lines() {
+            if let Some(cap) = VERIFYING.captures(line) {
+                current = funs
+                    .iter()
+                    .find(|fun_env| is_verify_procedure_of(&cap["proc"], fun_env))
+                    .map(|fun_env| fun_env.get_qualified_id());
+            }
+            if let Some(fun_id) = current {
+                let log = logs.entry(fun_id).or_default();
+                log.push_str(line);
+                log.push('\n');
+            }
+        }
+        logs
+    }
+}
+
+/// Matches the line Boogie prints before verifying a procedure.
+static VERIFYING: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^Verifying (?P<proc>\S+) \.\.\.$").unwrap());
+
+/// Returns true if the Boogie procedure is one generated for verifying the function.
+fn is_verify_procedure_of(proc_name: &str, fun_env: &FunctionEnv<'_>) -> bool {
+    let base_name = boogie_function_name(fun_env, &[]);
+    proc_name
+        .strip_prefix(&base_name)
+        .map(|rest| rest.starts_with('$') || rest.starts_with('\''))
+        .unwrap_or(false)
+        && proc_name.contains("$verify")
+}
+
 /// Kind of boogie error.
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum BoogieErrorKind {
     Assertion,
     Inconclusive,
     Inconsistency,
+    Vacuity,
     Internal,
 }
 
@@ -101,6 +216,9 @@ static INCONCLUSIVE_DIAG_STARTS: Lazy<Regex> = Lazy::new(|| {
         .unwrap()
 });
 
+static VACUITY_DIAG_STARTS: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?m)^vacuity_detected\((?P<args>[^)]*)\): (?P<msg>.*)$").unwrap());
+
 static INCONSISTENCY_DIAG_STARTS: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"(?m)^inconsistency_detected\((?P<args>[^)]*)\)").unwrap());
 
@@ -111,21 +229,44 @@ impl<'env> BoogieWrapper<'env> {
         let args = self.options.get_boogie_command(boogie_file)?;
         info!("running solver");
         debug!("command line: {}", args.iter().join(" "));
-        let task = RunBoogieWithSeeds {
-            options: self.options.clone(),
-            boogie_file: boogie_file.to_string(),
-        };
         // When running on complicated formulas(especially those with quantifiers), SMT solvers
         // can suffer from the so-called butterfly effect, where minor changes such as using
         // different random seeds cause significant instabilities in verification times.
-        // Thus by running multiple instances of Boogie with different random seeds, we can
-        // potentially alleviate the instability.
-        let (seed, output_res) = ProverTaskRunner::run_tasks(
-            task,
-            self.options.num_instances,
-            self.options.sequential_task,
-            self.options.hard_timeout_secs,
-        );
+        // Thus by running multiple instances of Boogie with different random seeds, or a
+        // portfolio of different solver configurations, we can potentially alleviate the
+        // instability.
+        let output_res = if self.options.portfolio.is_empty() {
+            let task = RunBoogieWithSeeds {
+                options: self.options.clone(),
+                boogie_file: boogie_file.to_string(),
+            };
+            let (seed, output_res) = ProverTaskRunner::run_tasks(
+                task,
+                self.options.num_instances,
+                self.options.sequential_task,
+                self.options.hard_timeout_secs,
+            );
+            if self.options.num_instances > 1 {
+                debug!("Boogie instance with seed {} finished first", seed);
+            }
+            output_res
+        } else {
+            let task = RunBoogiePortfolio {
+                options: self.options.clone(),
+                boogie_file: boogie_file.to_string(),
+            };
+            let (index, output_res) = ProverTaskRunner::run_tasks(
+                task,
+                self.options.portfolio.len(),
+                self.options.sequential_task,
+                self.options.hard_timeout_secs,
+            );
+            debug!(
+                "Boogie instance with portfolio configuration #{} finished first",
+                index
+            );
+            output_res
+        };
         let output = match output_res {
             Err(err) => {
                 if err.kind() == std::io::ErrorKind::TimedOut {
@@ -149,9 +290,6 @@ impl<'env> BoogieWrapper<'env> {
             }
             Ok(out) => out,
         };
-        if self.options.num_instances > 1 {
-            debug!("Boogie instance with seed {} finished first", seed);
-        }
 
         debug!("analyzing boogie output");
         let out = String::from_utf8_lossy(&output.stdout).to_string();
@@ -205,38 +343,295 @@ impl<'env> BoogieWrapper<'env> {
         let mut errors = self.extract_verification_errors(&out);
         errors.extend(self.extract_inconclusive_errors(&out));
         errors.extend(self.extract_inconsistency_errors(&out));
+        errors.extend(self.extract_vacuity_errors(&out));
         Ok(BoogieOutput {
             errors,
             all_output: out,
         })
     }
 
-    /// Calls boogie and analyzes output.
-    pub fn call_boogie_and_verify_output(&self, boogie_file: &str) -> anyhow::Result<()> {
+    /// Calls boogie and analyzes output, adding errors to the environment. Returns the
+    /// analyzed output.
+    pub fn call_boogie_and_verify_output(&self, boogie_file: &str) -> anyhow::Result<BoogieOutput> {
         let BoogieOutput { errors, all_output } = self.call_boogie(boogie_file)?;
+        let errors = if self.options.counterexample_minimization_steps > 0 {
+            let boogie_src = fs::read_to_string(boogie_file)?;
+            // The steps are shared by all errors, bounding the cost of minimization per run.
+            let mut budget = self.options.counterexample_minimization_steps;
+            errors
+                .into_iter()
+                .map(|error| {
+                    self.minimize_counterexample(boogie_file, &boogie_src, error, &mut budget)
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?
+        } else {
+            errors
+        };
         let boogie_log_file = self.options.get_boogie_log_file(boogie_file);
         let log_file_existed = std::path::Path::new(&boogie_log_file).exists();
         debug!("writing boogie log to {}", boogie_log_file);
         fs::write(&boogie_log_file, &all_output)?;
 
+        let contradicting = if self.options.explain_inconsistencies {
+            self.extract_used_assumes(&all_output, INCONSISTENT_ASSUME_PREFIX)
+        } else {
+            BTreeMap::new()
+        };
         for error in &errors {
-            self.add_error(error);
+            self.add_error(error, &contradicting);
+        }
+        if self.options.report_used_assumptions {
+            self.report_used_assumptions(
+                self.extract_used_assumes(&all_output, USED_ASSUME_PREFIX),
+            );
         }
 
         if !log_file_existed && !self.options.keep_artifacts {
             std::fs::remove_file(boogie_log_file).unwrap_or_default();
         }
 
-        Ok(())
+        let output = BoogieOutput { errors, all_output };
+        self.add_verification_lenses(&output);
+        Ok(output)
+    }
+
+    /// Shrinks the counterexample of an assertion error. The parameters of the verified
+    /// function which have an integer or address type are bounded to smaller values, and vector
+    /// parameters to shorter lengths, as described for `CounterexampleShrinker`. Each attempt
+    /// calls Boogie again on the failing procedure, taking one step from the budget, and the
+    /// model of a reproduced error becomes the new counterexample. Only errors of non-generic
+    /// functions are minimized; other values, like the resources in global memory, are not.
+    fn minimize_counterexample(
+        &self,
+        boogie_file: &str,
+        boogie_src: &str,
+        error: BoogieError,
+        budget: &mut usize,
+    ) -> anyhow::Result<BoogieError> {
+        if error.kind != BoogieErrorKind::Assertion || error.model.is_none() {
+            return Ok(error);
+        }
+        let fun_id = match error.execution_trace.iter().find_map(|e| match e {
+            TraceEntry::Temporary(fun_id, ..) => Some(*fun_id),
+            _ => None,
+        }) {
+            Some(fun_id) => fun_id,
+            None => return Ok(error),
+        };
+        let fun_env = self.env.get_function(fun_id);
+        if fun_env.get_type_parameter_count() > 0 {
+            return Ok(error);
+        }
+        let proc_name = format!("{}$verify", boogie_function_name(&fun_env, &[]));
+        let min_file = format!("{}.min.bpl", boogie_file);
+        let mut options = self.options.clone();
+        options.boogie_flags.push(format!("-proc:{}", proc_name));
+        let wrapper = BoogieWrapper {
+            options: &options,
+            ..*self
+        };
+
+        let mut best = error;
+        let mut shrinker = CounterexampleShrinker::default();
+        while *budget > 0 {
+            let assumption =
+                match shrinker.next_assumption(&self.counterexample_bounds(fun_id, &best)) {
+                    Some(assumption) => assumption,
+                    None => break,
+                };
+            let src = match insert_assumption(boogie_src, &proc_name, &assumption) {
+                Some(src) => src,
+                None => break,
+            };
+            fs::write(&min_file, &src)?;
+            *budget -= 1;
+            let reproduced = wrapper
+                .call_boogie(&min_file)?
+                .errors
+                .into_iter()
+                .find(|new_error| {
+                    new_error.kind == BoogieErrorKind::Assertion
+                        && new_error.loc == best.loc
+                        && new_error.message == best.message
+                        && new_error.model.is_some()
+                });
+            shrinker.record(reproduced.is_some());
+            if let Some(new_error) = reproduced {
+                debug!("shrunk counterexample with `{}`", assumption);
+                best = new_error;
+            }
+        }
+        if !self.options.keep_artifacts {
+            std::fs::remove_file(&min_file).unwrap_or_default();
+        }
+        Ok(best)
+    }
+
+    /// Returns the Boogie terms which can be bounded to shrink the counterexample of the error,
+    /// together with their current value in the counterexample.
+    fn counterexample_bounds(
+        &self,
+        fun_id: QualifiedId<FunId>,
+        error: &BoogieError,
+    ) -> Vec<(String, u128)> {
+        let model = error.model.as_ref().expect("model");
+        let fun_env = self.env.get_function(fun_id);
+        let param_count = fun_env.get_parameter_count();
+        let mut seen = BTreeSet::new();
+        let mut result = vec![];
+        for entry in &error.execution_trace {
+            let (idx, value) = match entry {
+                TraceEntry::Temporary(id, idx, value)
+                    if *id == fun_id && *idx < param_count && seen.insert(*idx) =>
+                {
+                    (*idx, value)
+                }
+                _ => continue,
+            };
+            let ty = match fun_env.get_local_type(idx) {
+                Type::Reference(false, bt) => *bt,
+                ty => ty,
+            };
+            let bound = match &ty {
+                Type::Primitive(
+                    PrimitiveType::U8
+                    | PrimitiveType::U64
+                    | PrimitiveType::U128
+                    | PrimitiveType::Num
+                    | PrimitiveType::Address,
+                ) => value
+                    .extract_literal()
+                    .and_then(|s| s.parse::<u128>().ok())
+                    .map(|n| (format!("$t{}", idx), n)),
+                Type::Vector(elem_ty) => value
+                    .extract_vector(model, elem_ty)
+                    .map(|v| (format!("LenVec($t{})", idx), v.size as u128)),
+                _ => None,
+            };
+            result.extend(bound);
+        }
+        result
+    }
+
+    /// Attaches the verification status of each verified function as a lens entry.
+    fn add_verification_lenses(&self, output: &BoogieOutput) {
+        let failed = output.failed_functions(self.env);
+        for module_env in self.env.get_modules().filter(|m| m.is_target()) {
+            for fun_env in module_env.get_functions() {
+                let verified = self
+                    .targets
+                    .get_target_variants(&fun_env)
+                    .iter()
+                    .any(|v| v.is_verified());
+                if !verified {
+                    continue;
+                }
+                let title = if failed.contains(&fun_env.get_qualified_id()) {
+                    "verification failed"
+                } else {
+                    "verified"
+                };
+                self.env.add_lens(
+                    fun_env.get_loc(),
+                    LensEntry::new(LensKind::VerificationStatus, title),
+                );
+            }
+        }
+    }
+
+    /// Extracts the assumptions with ids of the given prefix which Boogie reported as necessary
+    /// for proofs, grouped by function.
+    fn extract_used_assumes(
+        &self,
+        out: &str,
+        prefix: &str,
+    ) -> BTreeMap<QualifiedId<FunId>, BTreeSet<Loc>> {
+        static NECESSARY_ASSUMES: Lazy<Regex> =
+            Lazy::new(|| Regex::new(r"(?m)^Necessary assume command\(s\):(?P<ids>.*)$").unwrap());
+        let mut used: BTreeMap<QualifiedId<FunId>, BTreeSet<Loc>> = BTreeMap::new();
+        for cap in NECESSARY_ASSUMES.captures_iter(out) {
+            for id in cap.name("ids").unwrap().as_str().split(',') {
+                let id = id.trim();
+                if !id.starts_with(prefix) {
+                    continue;
+                }
+                match self.extract_used_assume(id, prefix) {
+                    Ok((fun_id, loc)) => {
+                        used.entry(fun_id).or_default().insert(loc);
+                    }
+                    Err(ModelParseError(s)) => debug!("[boogie output] {}", s),
+                }
+            }
+        }
+        used
+    }
+
+    /// Reports the assumptions which Boogie found necessary to prove the verification
+    /// conditions, as a note for each verified function.
+    fn report_used_assumptions(&self, used: BTreeMap<QualifiedId<FunId>, BTreeSet<Loc>>) {
+        for (fun_id, locs) in used {
+            let fun_env = self.env.get_function(fun_id);
+            let fun_loc = fun_env.get_loc();
+            let labels = std::iter::once(Label::primary(fun_loc.file_id(), fun_loc.span()))
+                .chain(locs.iter().map(|loc| {
+                    Label::secondary(loc.file_id(), loc.span()).with_message("used assumption")
+                }))
+                .collect();
+            let diag = Diagnostic::note()
+                .with_message(format!(
+                    "proof of `{}` used {} assumption(s)",
+                    fun_env.get_full_name_str(),
+                    locs.len()
+                ))
+                .with_labels(labels);
+            self.env.add_diag(diag);
+        }
     }
 
-    /// Helper to add a boogie error as a codespan Diagnostic.
-    fn add_error(&self, error: &BoogieError) {
+    /// Extracts function and location from the id of an assumption reported as necessary.
+    fn extract_used_assume(
+        &self,
+        id: &str,
+        prefix: &str,
+    ) -> Result<(QualifiedId<FunId>, Loc), ModelParseError> {
+        let elems = id.split(':').collect_vec();
+        if elems.len() == 7 && elems[0] == prefix {
+            let fun_id = self.extract_fun(&elems[1..3].join(","))?;
+            let loc = self.extract_loc(&elems[3..6].join(","))?;
+            Ok((fun_id, loc))
+        } else {
+            Err(ModelParseError(format!("unknown assumption id `{}`", id)))
+        }
+    }
+
+    /// Helper to add a boogie error as a codespan Diagnostic. For inconsistency errors, the
+    /// assumptions which contradict each other are added as labels, if they are known.
+    fn add_error(
+        &self,
+        error: &BoogieError,
+        contradicting: &BTreeMap<QualifiedId<FunId>, BTreeSet<Loc>>,
+    ) {
         // Create the error
-        let label = Label::primary(error.loc.file_id(), error.loc.span());
-        let mut diag = Diagnostic::error()
-            .with_message(error.message.clone())
-            .with_labels(vec![label]);
+        let mut labels = vec![Label::primary(error.loc.file_id(), error.loc.span())];
+        if error.kind == BoogieErrorKind::Inconsistency {
+            if let Some(locs) = self
+                .env
+                .get_enclosing_function(&error.loc)
+                .and_then(|fun_env| contradicting.get(&fun_env.get_qualified_id()))
+            {
+                labels.extend(locs.iter().map(|loc| {
+                    Label::secondary(loc.file_id(), loc.span())
+                        .with_message("contradicting assumption")
+                }));
+            }
+        }
+        let diag = if error.kind == BoogieErrorKind::Vacuity {
+            Diagnostic::warning()
+        } else {
+            Diagnostic::error()
+        };
+        let mut diag = diag.with_message(error.message.clone()).with_labels(labels);
+        let mut abort_at = None;
 
         // Now add trace diagnostics.
         if error.kind.is_from_verification() && !error.execution_trace.is_empty() {
@@ -274,6 +669,7 @@ impl<'env> BoogieWrapper<'env> {
 
             let mut subexp_map = BTreeMap::new();
             let mut global_mem_map = BTreeMap::new();
+            let mut path = vec![];
             for entry in &error.execution_trace {
                 use TraceEntry::*;
                 if abort_in_progress.is_some() && !matches!(entry, Exp(..)) {
@@ -283,6 +679,7 @@ impl<'env> BoogieWrapper<'env> {
                 match entry {
                     AtLocation(loc) => {
                         if loc != &last_loc {
+                            path.push(loc.clone());
                             print_loc(loc, &mut last_loc, &mut display);
                         }
                     }
@@ -296,17 +693,19 @@ impl<'env> BoogieWrapper<'env> {
                                 .get_local_name(*idx)
                                 .display(self.env.symbol_pool())
                                 .to_string();
-                            let var_name =
-                                if self.options.stable_test_output && var_name.contains('$') {
-                                    // Redact the name.
-                                    // TODO(wrwg): There is some non-determinism in how the Move compiler
-                                    //   generates temporaries resulting from if-expressions. They also
-                                    //   happen with the assert-builtin, which the compilers maps to an if.
-                                    //   We should fix those in the Move compiler instead of working around her.
-                                    "<redacted>".to_string()
-                                } else {
-                                    var_name
-                                };
+                            let var_name = if let Some(desc) = fun_target.get_temp_description(*idx)
+                            {
+                                desc.to_string()
+                            } else if self.options.stable_test_output && var_name.contains('$') {
+                                // Redact the name.
+                                // TODO(wrwg): There is some non-determinism in how the Move compiler
+                                //   generates temporaries resulting from if-expressions. They also
+                                //   happen with the assert-builtin, which the compilers maps to an if.
+                                //   We should fix those in the Move compiler instead of working around her.
+                                "<redacted>".to_string()
+                            } else {
+                                var_name
+                            };
                             let ty = fun_target.get_local_type(*idx);
                             let pretty =
                                 value.pretty_or_raw(self, error.model.as_ref().unwrap(), ty);
@@ -396,6 +795,7 @@ impl<'env> BoogieWrapper<'env> {
                     abort_loc.span(),
                 )
                 .with_message(&format!("abort happened here{}", code))]);
+                abort_at = Some(abort_loc);
             }
 
             // Inject information about sub-expressions of this failure
@@ -435,9 +835,102 @@ impl<'env> BoogieWrapper<'env> {
                 display.append(&mut trace_display)
             }
 
+            // Render the path to the failure as source snippets, with the outcome of each
+            // branch on it.
+            if self.options.explain_paths && !path.is_empty() {
+                let steps = path
+                    .iter()
+                    .enumerate()
+                    .map(|(i, loc)| (loc.clone(), self.describe_branch(loc, path.get(i + 1))))
+                    .collect_vec();
+                display.push("Path:".to_string());
+                display.extend(self.env.render_path(&steps));
+            }
+
             diag = diag.with_notes(display);
         }
-        self.env.add_diag(diag);
+        let fixes = if error.message == ABORT_NOT_COVERED {
+            self.abort_not_covered_fixes(&error.loc, abort_at.as_ref())
+        } else {
+            vec![]
+        };
+        self.env.add_diag_with_fixes(diag, fixes);
+    }
+
+    /// Computes fixes for an abort which is not covered by the `aborts_if` clauses of the
+    /// function with the given spec location: adding an `aborts_if` derived from the failed
+    /// `assert!` at the abort location, if there is one, or declaring the clauses as partial.
+    fn abort_not_covered_fixes(&self, spec_loc: &Loc, abort_loc: Option<&Loc>) -> Vec<Fix> {
+        let block_loc = match self
+            .env
+            .get_modules()
+            .flat_map(|m| m.into_functions())
+            .find_map(|f| f.get_spec().loc.clone().filter(|l| l == spec_loc))
+        {
+            Some(loc) => loc,
+            None => return vec![],
+        };
+        let mut fixes = vec![];
+        if let Some(cond) = abort_loc
+            .and_then(|loc| self.env.get_source(loc).ok())
+            .and_then(assert_condition)
+        {
+            let clause = format!("aborts_if !({});", cond);
+            fixes.push(Fix::from_edit(
+                format!("add `{}`", clause),
+                SourceEdit::insert_in_block(self.env, &block_loc, &clause),
+            ));
+        }
+        let pragma = format!("pragma {};", ABORTS_IF_IS_PARTIAL_PRAGMA);
+        fixes.push(Fix::from_edit(
+            format!("add `{}`", pragma),
+            SourceEdit::insert_in_block(self.env, &block_loc, &pragma),
+        ));
+        fixes
+    }
+
+    /// If the location is the one of a branch in the code of its function, describes which
+    /// branch is taken, determined from the next location on the path.
+    fn describe_branch(&self, loc: &Loc, next: Option<&Loc>) -> Option<String> {
+        let fun_env = self.env.get_enclosing_function(loc)?;
+        for (_, target) in self.targets.get_targets(&fun_env) {
+            let code = target.get_bytecode();
+            let label_offsets = Bytecode::label_offsets(code);
+            // Returns the locations of the basic block starting at the label.
+            let block_locs = |label: &CodeLabel| {
+                let start = label_offsets[label] as usize;
+                let end = code[start..]
+                    .iter()
+                    .position(|bc| bc.is_branch())
+                    .map_or(code.len(), |pos| start + pos + 1);
+                code[start..end]
+                    .iter()
+                    .map(|bc| target.get_bytecode_loc(bc.get_attr_id()))
+                    .collect_vec()
+            };
+            for bc in code {
+                if let Bytecode::Branch(attr_id, then_label, else_label, _) = bc {
+                    if &target.get_bytecode_loc(*attr_id) != loc {
+                        continue;
+                    }
+                    let taken = next.map(|next| {
+                        (
+                            block_locs(then_label).contains(next),
+                            block_locs(else_label).contains(next),
+                        )
+                    });
+                    return Some(
+                        match taken {
+                            Some((true, false)) => "condition is true",
+                            Some((false, true)) => "condition is false",
+                            _ => "branch",
+                        }
+                        .to_string(),
+                    );
+                }
+            }
+        }
+        None
     }
 
     fn get_abbreviated_source(&self, node_id: NodeId) -> String {
@@ -501,6 +994,7 @@ impl<'env> BoogieWrapper<'env> {
             if !inbetween.is_empty()
                 && !INCONCLUSIVE_DIAG_STARTS.is_match(inbetween)
                 && !INCONSISTENCY_DIAG_STARTS.is_match(inbetween)
+                && !VACUITY_DIAG_STARTS.is_match(inbetween)
             {
                 // This is unexpected text and we report it as an internal error
                 errors.push(BoogieError {
@@ -521,7 +1015,7 @@ impl<'env> BoogieWrapper<'env> {
             let mut model = Model::new(self);
             self.extract_model(&mut model, out, &mut at);
 
-            if msg != "expected to fail" {
+            if !msg.starts_with("expected to fail") {
                 // Only add this if it is not a negative test. We still needed to parse it.
                 errors.push(BoogieError {
                     kind: BoogieErrorKind::Assertion,
@@ -764,6 +1258,23 @@ impl<'env> BoogieWrapper<'env> {
             .collect_vec()
     }
 
+    fn extract_vacuity_errors(&self, out: &str) -> Vec<BoogieError> {
+        VACUITY_DIAG_STARTS
+            .captures_iter(out)
+            .map(|cap| {
+                let args = cap.name("args").unwrap().as_str();
+                let loc = self.report_error(self.extract_loc(args), self.env.unknown_loc());
+                BoogieError {
+                    kind: BoogieErrorKind::Vacuity,
+                    loc,
+                    message: cap.name("msg").unwrap().as_str().to_string(),
+                    execution_trace: vec![],
+                    model: None,
+                }
+            })
+            .collect_vec()
+    }
+
     /// Gets the code byte index and source location (if available) from a target line/column
     /// position.
     fn get_loc_from_pos(&self, pos: Location) -> Option<Loc> {
@@ -775,6 +1286,74 @@ impl<'env> BoogieWrapper<'env> {
     }
 }
 
+/// Shrinks a counterexample by bounding terms, like parameters or the lengths of vectors, to
+/// smaller values. Each step bounds one term to half of its value in the current
+/// counterexample, in addition to the bounds established so far. If the error is reproduced
+/// under the bounds, they are kept and the new counterexample is shrunk further; otherwise, the
+/// term is not bounded again. As each step either halves a value or excludes a term, shrinking
+/// ends after a number of steps logarithmic in the values.
+#[derive(Debug, Default)]
+pub struct CounterexampleShrinker {
+    /// The bounds under which the current counterexample has been found.
+    bounds: BTreeMap<String, u128>,
+    /// The terms which cannot be shrunk further.
+    exhausted: BTreeSet<String>,
+    /// The term bounded by the last assumption, and the bounds of that assumption.
+    pending: Option<(String, BTreeMap<String, u128>)>,
+}
+
+impl CounterexampleShrinker {
+    /// Returns the assumption to try next, given the terms which can be bounded and their
+    /// values in the current counterexample, or `None` if no term can be shrunk anymore.
+    pub fn next_assumption(&mut self, values: &[(String, u128)]) -> Option<String> {
+        let (term, value) = values
+            .iter()
+            .find(|(term, value)| *value > 0 && !self.exhausted.contains(term))?;
+        let mut bounds = self.bounds.clone();
+        bounds.insert(term.clone(), value / 2);
+        let assumption = bounds
+            .iter()
+            .map(|(term, bound)| format!("{} <= {}", term, bound))
+            .join(" && ");
+        self.pending = Some((term.clone(), bounds));
+        Some(assumption)
+    }
+
+    /// Records whether the error has been reproduced under the last assumption.
+    pub fn record(&mut self, reproduced: bool) {
+        if let Some((term, bounds)) = self.pending.take() {
+            if reproduced {
+                self.bounds = bounds;
+            } else {
+                self.exhausted.insert(term);
+            }
+        }
+    }
+}
+
+/// Inserts an assumption into the Boogie procedure with the given name, right before the
+/// translated bytecode starts. Returns `None` if the procedure cannot be found.
+fn insert_assumption(boogie_src: &str, proc_name: &str, assumption: &str) -> Option<String> {
+    let header = format!(" {}(", proc_name);
+    let mut proc_start = None;
+    let mut pos = 0;
+    for line in boogie_src.split_inclusive('\n') {
+        if line.starts_with("procedure ") && line.contains(&header) {
+            proc_start = Some(pos);
+            break;
+        }
+        pos += line.len();
+    }
+    let proc_start = proc_start?;
+    let code_start = proc_start + boogie_src[proc_start..].find(BYTECODE_START_MARKER)?;
+    Some(format!(
+        "{}assume {};\n{}",
+        &boogie_src[..code_start],
+        assumption,
+        &boogie_src[code_start..]
+    ))
+}
+
 /// Creates a position (line/column pair) from strings which are known to consist only of digits.
 fn make_position(line_str: &str, col_str: &str) -> Location {
     // This will crash on overflow.
@@ -1271,14 +1850,16 @@ impl ModelValue {
             )),
             Type::Primitive(PrimitiveType::Address) => {
                 let addr = BigInt::parse_bytes(&self.extract_literal()?.clone().into_bytes(), 10)?;
-                Some(PrettyDoc::text(format!("0x{}", &addr.to_str_radix(16))))
+                Some(PrettyDoc::text(
+                    wrapper.env.display_address(&addr.to_biguint()?),
+                ))
             }
             Type::Primitive(PrimitiveType::Signer) => {
                 let l = self.extract_list("$signer")?;
                 let addr = BigInt::parse_bytes(&l[0].extract_literal()?.clone().into_bytes(), 10)?;
                 Some(PrettyDoc::text(format!(
-                    "signer{{0x{}}}",
-                    &addr.to_str_radix(16)
+                    "signer{{{}}}",
+                    wrapper.env.display_address(&addr.to_biguint()?)
                 )))
             }
             Type::Vector(param) => self.pretty_vector(wrapper, model, param),
@@ -1591,3 +2172,25 @@ fn index_range_check(max: usize) -> impl FnOnce(usize) -> Result<usize, ModelPar
         }
     }
 }
+
+/// Extracts the condition of an `assert!(cond, code)` from its source.
+fn assert_condition(source: &str) -> Option<&str> {
+    let args = source
+        .trim()
+        .strip_prefix("assert!")?
+        .trim_start()
+        .strip_prefix('(')?
+        .strip_suffix(')')?;
+    // The condition ends at the last comma which is not nested in parentheses.
+    let mut depth = 0;
+    let mut split = None;
+    for (i, c) in args.char_indices() {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            ',' if depth == 0 => split = Some(i),
+            _ => {}
+        }
+    }
+    Some(args[..split?].trim())
+}
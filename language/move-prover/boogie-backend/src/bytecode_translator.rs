@@ -3,7 +3,10 @@
 
 //! This module translates the bytecode of a module to Boogie code.
 
-use std::collections::{BTreeMap, BTreeSet};
+use std::{
+    cell::Cell,
+    collections::{BTreeMap, BTreeSet},
+};
 
 use itertools::Itertools;
 #[allow(unused_imports)]
@@ -21,6 +24,7 @@ use move_stackless_bytecode::{
     function_target_pipeline::{FunctionTargetsHolder, VerificationFlavor},
     mono_analysis,
     stackless_bytecode::{BorrowEdge, BorrowNode, Bytecode, Constant, HavocKind, Operation},
+    vacuity_check::get_vacuity_check,
 };
 
 use crate::{
@@ -28,9 +32,9 @@ use crate::{
         boogie_byte_blob, boogie_debug_track_abort, boogie_debug_track_local,
         boogie_debug_track_return, boogie_equality_for_type, boogie_field_sel, boogie_field_update,
         boogie_function_name, boogie_make_vec_from_strings, boogie_modifies_memory_name,
-        boogie_resource_memory_name, boogie_struct_name, boogie_temp, boogie_type,
-        boogie_type_param, boogie_type_suffix, boogie_type_suffix_for_struct,
-        boogie_well_formed_check, boogie_well_formed_expr,
+        boogie_named_variant_suffix, boogie_resource_memory_name, boogie_struct_name, boogie_temp,
+        boogie_type, boogie_type_param, boogie_type_suffix, boogie_type_suffix_for_struct,
+        boogie_vector_op_name, boogie_well_formed_check, boogie_well_formed_expr,
     },
     options::BoogieOptions,
     spec_translator::SpecTranslator,
@@ -46,12 +50,24 @@ use move_stackless_bytecode::{
     stackless_bytecode::{AbortAction, PropKind},
 };
 
+/// The comment which marks the start of the translated bytecode in a procedure body, after
+/// local declarations and entry assumptions.
+pub const BYTECODE_START_MARKER: &str = "// bytecode translation starts here";
+
+/// The prefix of ids of assumptions which are tracked for reporting used assumptions.
+pub const USED_ASSUME_PREFIX: &str = "used_assume";
+
+/// The prefix of ids of assumptions which are tracked for explaining inconsistencies.
+pub const INCONSISTENT_ASSUME_PREFIX: &str = "inconsistent_assume";
+
 pub struct BoogieTranslator<'env> {
     env: &'env GlobalEnv,
     options: &'env BoogieOptions,
     writer: &'env CodeWriter,
     spec_translator: SpecTranslator<'env>,
     targets: &'env FunctionTargetsHolder,
+    /// Counter to make the ids of labelled assumptions unique.
+    assume_count: Cell<usize>,
 }
 
 pub struct FunctionTranslator<'env> {
@@ -79,6 +95,7 @@ impl<'env> BoogieTranslator<'env> {
             targets,
             writer,
             spec_translator: SpecTranslator::new(writer, env, options),
+            assume_count: Cell::new(0),
         }
     }
 
@@ -430,6 +447,10 @@ impl<'env> FunctionTranslator<'env> {
 
         let (suffix, attribs) = match &fun_target.data.variant {
             FunctionVariant::Baseline => ("".to_string(), "{:inline 1} ".to_string()),
+            FunctionVariant::Named(name) => (
+                boogie_named_variant_suffix(name),
+                "{:inline 1} ".to_string(),
+            ),
             FunctionVariant::Verification(flavor) => {
                 let timeout = fun_target
                     .func_env
@@ -446,7 +467,9 @@ impl<'env> FunctionTranslator<'env> {
 
                 let suffix = match flavor {
                     VerificationFlavor::Regular => "$verify".to_string(),
-                    VerificationFlavor::Instantiated(_) => {
+                    VerificationFlavor::Instantiated(_)
+                    | VerificationFlavor::Split(..)
+                    | VerificationFlavor::Equivalence => {
                         format!("$verify_{}", flavor)
                     }
                     VerificationFlavor::Inconsistency(_) => {
@@ -456,6 +479,16 @@ impl<'env> FunctionTranslator<'env> {
                         ));
                         format!("$verify_{}", flavor)
                     }
+                    VerificationFlavor::Vacuity(..) => {
+                        let (loc, message) = get_vacuity_check(fun_target)
+                            .expect("vacuity check variant has a check");
+                        attribs.push(format!(
+                            "{{:msg_if_verifies \"vacuity_detected{}: {}\"}} ",
+                            self.loc_str(&loc),
+                            message
+                        ));
+                        format!("$verify_{}", flavor)
+                    }
                 };
                 (suffix, attribs.join(""))
             }
@@ -554,7 +587,16 @@ impl<'env> FunctionTranslator<'env> {
         let num_args = fun_target.get_parameter_count();
         for i in num_args..fun_target.get_local_count() {
             let local_type = &self.get_local_type(i);
-            emitln!(writer, "var $t{}: {};", i, boogie_type(env, local_type));
+            match fun_target.get_temp_description(i) {
+                Some(desc) => emitln!(
+                    writer,
+                    "var $t{}: {}; // {}",
+                    i,
+                    boogie_type(env, local_type),
+                    desc
+                ),
+                None => emitln!(writer, "var $t{}: {};", i, boogie_type(env, local_type)),
+            }
         }
         // Generate declarations for renamed parameters.
         let proxied_parameters = self.get_mutable_parameters();
@@ -635,7 +677,7 @@ impl<'env> FunctionTranslator<'env> {
         }
 
         // Generate bytecode
-        emitln!(writer, "\n// bytecode translation starts here");
+        emitln!(writer, "\n{}", BYTECODE_START_MARKER);
         let mut last_tracked_loc = None;
         for bytecode in code.iter() {
             self.translate_bytecode(&mut last_tracked_loc, bytecode);
@@ -730,6 +772,32 @@ impl<'env> FunctionTranslator<'env> {
             emitln!(writer, "// {}", comment);
         }
 
+        // Print the source line, unless it has been printed for the previous instruction, and
+        // the provenance of verification conditions.
+        if options.source_comments {
+            let location = env.get_location(&loc);
+            let same_line = match (last_tracked_loc.as_ref(), &location) {
+                (Some((last_loc, last_line)), Some(location)) => {
+                    last_loc.file_id() == loc.file_id() && *last_line == location.line
+                }
+                _ => false,
+            };
+            if !same_line {
+                if let Some(line) = location.and_then(|location| {
+                    env.get_file_source(loc.file_id())
+                        .lines()
+                        .nth(location.line.to_usize())
+                }) {
+                    emitln!(writer, "// >> {}", line.trim());
+                }
+            }
+            if matches!(bytecode, Prop(..)) {
+                if let Some(info) = fun_target.get_vc_info(attr_id) {
+                    emitln!(writer, "// condition: {}", info);
+                }
+            }
+        }
+
         // Track location for execution traces.
         if matches!(bytecode, Call(_, _, Operation::TraceAbort, ..)) {
             // Ensure that aborts always has the precise location instead of the
@@ -770,6 +838,9 @@ impl<'env> FunctionTranslator<'env> {
                 }
                 PropKind::Assume => {
                     emit!(writer, "assume ");
+                    if let Some(prefix) = self.assume_id_prefix() {
+                        emit!(writer, "{{:id \"{}\"}} ", self.assume_id(prefix, &loc));
+                    }
                     spec_translator.translate(exp, self.type_inst);
                     emitln!(writer, ";");
                 }
@@ -933,7 +1004,15 @@ impl<'env> FunctionTranslator<'env> {
                     }
                     Function(mid, fid, inst) => {
                         let inst = &self.inst_slice(inst);
-                        let callee_env = env.get_module(*mid).into_function(*fid);
+                        // Call the replacement of the callee if it is overridden.
+                        let callee_id = mid.qualified(*fid);
+                        let callee_env = env.get_function(
+                            mono_analysis::get_info(env)
+                                .substitutions
+                                .get(&callee_id)
+                                .cloned()
+                                .unwrap_or(callee_id),
+                        );
 
                         let args_str = srcs.iter().cloned().map(str_local).join(", ");
                         let dest_str = dests
@@ -970,6 +1049,64 @@ impl<'env> FunctionTranslator<'env> {
                         // location tracks before it returns.
                         *last_tracked_loc = None;
                     }
+                    VecLen(ty) | VecBorrow(ty) | VecBorrowMut(ty) | VecPushBack(ty)
+                    | VecPopBack(ty) | VecSwap(ty) => {
+                        // Vector operations are implemented by the same prelude procedures as
+                        // the corresponding native functions of the Vector module.
+                        let op_name = match oper {
+                            VecLen(_) => "length",
+                            VecBorrow(_) => "borrow",
+                            VecBorrowMut(_) => "borrow_mut",
+                            VecPushBack(_) => "push_back",
+                            VecPopBack(_) => "pop_back",
+                            _ => "swap",
+                        };
+                        let args_str = srcs.iter().cloned().map(str_local).join(", ");
+                        let dest_str = dests
+                            .iter()
+                            .cloned()
+                            .map(str_local)
+                            .chain(
+                                srcs.iter()
+                                    .filter(|idx| self.get_local_type(**idx).is_mutable_reference())
+                                    .cloned()
+                                    .map(str_local),
+                            )
+                            .join(",");
+                        let proc_name = boogie_vector_op_name(env, op_name, &self.inst(ty));
+                        if dest_str.is_empty() {
+                            emitln!(writer, "call {}({});", proc_name, args_str);
+                        } else {
+                            emitln!(writer, "call {} := {}({});", dest_str, proc_name, args_str);
+                        }
+                    }
+                    VecPack(_, _) => {
+                        let args = srcs.iter().cloned().map(str_local).collect_vec();
+                        emitln!(
+                            writer,
+                            "{} := {};",
+                            str_local(dests[0]),
+                            boogie_make_vec_from_strings(&args)
+                        );
+                    }
+                    VecUnpack(_, num) => {
+                        let src_str = str_local(srcs[0]);
+                        emitln!(writer, "if (LenVec({}) != {}) {{", src_str, num);
+                        writer.with_indent(|| emitln!(writer, "call $ExecFailureAbort();"));
+                        emitln!(writer, "} else {");
+                        writer.with_indent(|| {
+                            for (i, dest) in dests.iter().enumerate() {
+                                emitln!(
+                                    writer,
+                                    "{} := ReadVec({}, {});",
+                                    str_local(*dest),
+                                    src_str,
+                                    i
+                                );
+                            }
+                        });
+                        emitln!(writer, "}");
+                    }
                     Pack(mid, sid, inst) => {
                         let inst = &self.inst_slice(inst);
                         let struct_env = env.get_module(*mid).into_struct(*sid);
@@ -1420,6 +1557,17 @@ impl<'env> FunctionTranslator<'env> {
                             str_local(op2)
                         );
                     }
+                    PackVariant(..)
+                    | UnpackVariant(..)
+                    | TestVariant(..)
+                    | BorrowVariantField(..) => {
+                        env.error(&loc, "enum variant operations are not yet supported");
+                        emitln!(
+                            writer,
+                            "// enum variant operation not supported: {:?}\nassert false;",
+                            bytecode
+                        );
+                    }
                     BitOr | BitAnd | Xor => {
                         env.error(&loc, "Unsupported operator");
                         emitln!(
@@ -1730,6 +1878,42 @@ impl<'env> FunctionTranslator<'env> {
         );
     }
 
+    /// Returns the prefix of ids for assumptions in this function, if they need to be
+    /// tracked.
+    fn assume_id_prefix(&self) -> Option<&'static str> {
+        let options = self.parent.options;
+        match &self.fun_target.data.variant {
+            FunctionVariant::Verification(VerificationFlavor::Inconsistency(..)) => options
+                .explain_inconsistencies
+                .then(|| INCONSISTENT_ASSUME_PREFIX),
+            FunctionVariant::Verification(VerificationFlavor::Vacuity(..))
+            | FunctionVariant::Baseline
+            | FunctionVariant::Named(..) => None,
+            FunctionVariant::Verification(_) => {
+                options.report_used_assumptions.then(|| USED_ASSUME_PREFIX)
+            }
+        }
+    }
+
+    /// Returns a unique id for an assumption at the given location. The id encodes the
+    /// function and the location, so it can be mapped back if Boogie reports the assumption
+    /// as necessary for a proof.
+    fn assume_id(&self, prefix: &str, loc: &Loc) -> String {
+        let count = self.parent.assume_count.get();
+        self.parent.assume_count.set(count + 1);
+        let file_idx = self.fun_target.global_env().file_id_to_idx(loc.file_id());
+        format!(
+            "{}:{}:{}:{}:{}:{}:{}",
+            prefix,
+            self.fun_target.func_env.module_env.get_id().to_usize(),
+            self.fun_target.func_env.get_def_idx(),
+            file_idx,
+            loc.span().start(),
+            loc.span().end(),
+            count
+        )
+    }
+
     fn loc_str(&self, loc: &Loc) -> String {
         let file_idx = self.fun_target.global_env().file_id_to_idx(loc.file_id());
         format!("({},{},{})", file_idx, loc.span().start(), loc.span().end())
@@ -14,15 +14,15 @@ use tera::{Context, Tera};
 use move_model::{
     code_writer::CodeWriter,
     emit, emitln,
-    model::GlobalEnv,
+    model::{GlobalEnv, ModuleEnv, SpecFunId},
     ty::{PrimitiveType, Type},
 };
 use move_stackless_bytecode::mono_analysis;
 
 use crate::{
-    boogie_helpers::{boogie_type, boogie_type_suffix},
+    boogie_helpers::{boogie_spec_fun_name, boogie_type, boogie_type_suffix},
     bytecode_translator::has_native_equality,
-    options::{BoogieOptions, VectorTheory},
+    options::{BoogieOptions, PreludeTarget, VectorTheory},
 };
 
 const PRELUDE_TEMPLATE: &[u8] = include_bytes!("prelude/prelude.bpl");
@@ -45,6 +45,8 @@ pub mod options;
 mod prover_task_runner;
 mod spec_translator;
 
+pub use spec_translator::check_spec_well_formedness;
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Default)]
 struct TypeInfo {
     name: String,
@@ -124,9 +126,104 @@ pub fn add_prelude(
 
     let expanded_content = tera.render("prelude", &context)?;
     emitln!(writer, &expanded_content);
+
+    // Add the custom prelude text registered in the options.
+    if !options.prelude_extensions.is_empty() {
+        emitln!(writer, "\n// ** Prelude extensions\n");
+    }
+    for (idx, extension) in options.prelude_extensions.iter().enumerate() {
+        let mut context = context.clone();
+        let description = match &extension.target {
+            PreludeTarget::Module(name) => {
+                context.insert("instances", &filter_native(name));
+                format!("module {}", name)
+            }
+            PreludeTarget::SpecFun(name) => {
+                let mut names = vec![];
+                if let Some((module_env, ids)) = find_spec_funs(env, name) {
+                    for id in ids {
+                        let qid = module_env.get_id().qualified(id);
+                        for inst in mono_info.spec_funs.get(&qid).into_iter().flatten() {
+                            names.push(boogie_spec_fun_name(&module_env, id, inst));
+                        }
+                    }
+                }
+                context.insert("spec_fun_names", &names);
+                format!("spec function {}", name)
+            }
+        };
+        let template_name = format!("extension_{}", idx);
+        tera.add_raw_template(&template_name, &extension.content)?;
+        emitln!(writer, "// Extension for {}", description);
+        emitln!(writer, &tera.render(&template_name, &context)?);
+    }
     Ok(())
 }
 
+/// Checks that the modules and spec functions which prelude extensions are registered for
+/// exist in the model. Extensions can only be given for modules with native functions, and for
+/// spec functions without a definition.
+pub fn check_prelude_extensions(env: &GlobalEnv, options: &BoogieOptions) {
+    let loc = env.unknown_loc();
+    for extension in &options.prelude_extensions {
+        match &extension.target {
+            PreludeTarget::Module(name) => match env.find_module_by_name_str(name) {
+                None => env.error(
+                    &loc,
+                    &format!("prelude extension for unknown module `{}`", name),
+                ),
+                Some(module_env) if !module_env.get_functions().any(|f| f.is_native()) => env
+                    .error(
+                        &loc,
+                        &format!(
+                            "prelude extension for module `{}` which has no native functions",
+                            name
+                        ),
+                    ),
+                _ => {}
+            },
+            PreludeTarget::SpecFun(name) => match find_spec_funs(env, name) {
+                None => env.error(
+                    &loc,
+                    &format!("prelude extension for unknown spec function `{}`", name),
+                ),
+                Some((module_env, ids))
+                    if ids
+                        .iter()
+                        .any(|id| module_env.get_spec_fun(*id).body.is_some()) =>
+                {
+                    env.error(
+                        &loc,
+                        &format!(
+                            "prelude extension for spec function `{}` which has a definition",
+                            name
+                        ),
+                    )
+                }
+                _ => {}
+            },
+        }
+    }
+}
+
+/// Finds the overloads of a spec function by a name of the form `0x1::M::f`.
+fn find_spec_funs<'env>(
+    env: &'env GlobalEnv,
+    name: &str,
+) -> Option<(ModuleEnv<'env>, Vec<SpecFunId>)> {
+    let (module_name, fun_name) = name.rsplit_once("::")?;
+    let module_env = env.find_module_by_name_str(module_name)?;
+    let ids = module_env
+        .get_spec_funs_of_name(env.symbol_pool().make(fun_name))
+        .map(|(id, _)| *id)
+        .collect::<Vec<_>>();
+    if ids.is_empty() {
+        None
+    } else {
+        Some((module_env, ids))
+    }
+}
+
 impl TypeInfo {
     fn new(env: &GlobalEnv, options: &BoogieOptions, ty: &Type) -> Self {
         Self {
@@ -40,6 +40,38 @@ impl VectorTheory {
     }
 }
 
+/// A backend configuration which is run as one member of a solver portfolio.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SolverConfig {
+    /// The random seed passed to the solver. If not set, the default seed is used.
+    pub random_seed: Option<usize>,
+    /// Whether to use cvc5 instead of z3.
+    pub use_cvc5: bool,
+    /// Additional flags passed to boogie, e.g. to select a different encoding.
+    pub boogie_flags: Vec<String>,
+}
+
+/// What a custom piece of prelude text belongs to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum PreludeTarget {
+    /// A module with native functions, given as `0x1::M`.
+    Module(String),
+    /// A native or uninterpreted specification function, given as `0x1::M::f`.
+    SpecFun(String),
+}
+
+/// A custom piece of Boogie text, like declarations and axioms for a native module, which is
+/// appended to the prelude. The text is a template which is expanded with the same context as
+/// the prelude. In addition, `instances` holds the type instantiations of the natives of a
+/// module, and `spec_fun_names` the Boogie names of the used instances of a spec function.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PreludeExtension {
+    pub target: PreludeTarget,
+    pub content: String,
+}
+
 /// Boogie options.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default, deny_unknown_fields)]
@@ -105,6 +137,30 @@ pub struct BoogieOptions {
     pub vector_theory: VectorTheory,
     /// Whether to generate a z3 trace file and where to put it.
     pub z3_trace_file: Option<String>,
+    /// The maximal number of additional Boogie calls to shrink the counterexamples of failed
+    /// verification conditions, for all errors of a run. Zero for no minimization.
+    pub counterexample_minimization_steps: usize,
+    /// Whether to report the assumptions (preconditions, invariants, callee specs, ...) which
+    /// have been used to prove the verification conditions of a function.
+    pub report_used_assumptions: bool,
+    /// Whether to report the assumptions which contradict each other when an inconsistency
+    /// is detected.
+    pub explain_inconsistencies: bool,
+    /// Whether to render the path to a verification error as source snippets, showing the
+    /// branches taken on the way.
+    pub explain_paths: bool,
+    /// Whether to interleave the Move source lines and the provenance of the verification
+    /// conditions as comments into the generated Boogie code.
+    pub source_comments: bool,
+    /// Backend configurations which are run in parallel, taking the result of the first one
+    /// which gives a conclusive answer. If empty, `num_instances` instances with different
+    /// random seeds are run instead.
+    pub portfolio: Vec<SolverConfig>,
+    /// Whether to let Boogie report the verification time of each procedure.
+    pub report_procedure_times: bool,
+    /// Custom prelude text for native modules and spec functions, appended to the prelude in
+    /// the order of registration.
+    pub prelude_extensions: Vec<PreludeExtension>,
 }
 
 impl Default for BoogieOptions {
@@ -139,6 +195,14 @@ impl Default for BoogieOptions {
             hard_timeout_secs: 0,
             vector_theory: VectorTheory::BoogieArray,
             z3_trace_file: None,
+            counterexample_minimization_steps: 0,
+            report_used_assumptions: false,
+            explain_inconsistencies: false,
+            explain_paths: false,
+            source_comments: false,
+            portfolio: vec![],
+            report_procedure_times: false,
+            prelude_extensions: vec![],
         }
     }
 }
@@ -218,6 +282,12 @@ impl BoogieOptions {
         if self.generate_smt {
             add(&["-proverLog:@PROC@.smt"]);
         }
+        if self.report_used_assumptions || self.explain_inconsistencies {
+            add(&["-printNecessaryAssumes"]);
+        }
+        if self.report_procedure_times {
+            add(&["-trace"]);
+        }
         for f in &self.boogie_flags {
             add(&[f.as_str()]);
         }
@@ -225,6 +295,36 @@ impl BoogieOptions {
         Ok(result)
     }
 
+    /// Registers prelude text for the native module with the given name, of the form `0x1::M`.
+    pub fn add_module_prelude(&mut self, module: &str, content: impl Into<String>) {
+        self.prelude_extensions.push(PreludeExtension {
+            target: PreludeTarget::Module(module.to_string()),
+            content: content.into(),
+        });
+    }
+
+    /// Registers prelude text, typically axioms, for the spec function with the given name, of
+    /// the form `0x1::M::f`.
+    pub fn add_spec_fun_prelude(&mut self, spec_fun: &str, content: impl Into<String>) {
+        self.prelude_extensions.push(PreludeExtension {
+            target: PreludeTarget::SpecFun(spec_fun.to_string()),
+            content: content.into(),
+        });
+    }
+
+    /// Returns the options for running the given member of a solver portfolio.
+    pub fn with_solver_config(&self, config: &SolverConfig) -> BoogieOptions {
+        let mut options = self.clone();
+        options.use_cvc5 = config.use_cvc5;
+        if let Some(seed) = config.random_seed {
+            options.random_seed = seed;
+        }
+        options
+            .boogie_flags
+            .extend(config.boogie_flags.iter().cloned());
+        options
+    }
+
     /// Returns name of file where to log boogie output.
     pub fn get_boogie_log_file(&self, boogie_file: &str) -> String {
         format!("{}.log", boogie_file)
@@ -252,12 +352,21 @@ impl BoogieOptions {
             )?;
             Self::check_version_is_greater("boogie", &version, MIN_BOOGIE_VERSION)?;
         }
-        if !self.z3_exe.is_empty() && !self.use_cvc5 {
+        // With a portfolio, the solvers used are determined by its configurations.
+        let (uses_z3, uses_cvc5) = if self.portfolio.is_empty() {
+            (!self.use_cvc5, self.use_cvc5)
+        } else {
+            (
+                self.portfolio.iter().any(|c| !c.use_cvc5),
+                self.portfolio.iter().any(|c| c.use_cvc5),
+            )
+        };
+        if !self.z3_exe.is_empty() && uses_z3 {
             let version =
                 Self::get_version("z3", &self.z3_exe, &["--version"], r"version ([0-9.]*)")?;
             Self::check_version_is_greater("z3", &version, MIN_Z3_VERSION)?;
         }
-        if !self.cvc5_exe.is_empty() && self.use_cvc5 {
+        if !self.cvc5_exe.is_empty() && uses_cvc5 {
             let version =
                 Self::get_version("cvc5", &self.cvc5_exe, &["--version"], r"version ([0-9.]*)")?;
             Self::check_version_is_greater("cvc5", &version, MIN_CVC5_VERSION)?;
@@ -8,7 +8,7 @@ use crate::options::BoogieOptions;
 use async_trait::async_trait;
 use futures::{future::FutureExt, pin_mut, select};
 use log::debug;
-use rand::Rng;
+use rand::{rngs::StdRng, Rng, SeedableRng};
 use regex::Regex;
 use std::{
     process::Output,
@@ -111,7 +111,7 @@ impl ProverTaskRunner {
                         return (task_id, result);
                     }
                     debug!("previous instance failed, waiting for another worker to report...");
-                    num_working_instances = usize::saturating_add(num_working_instances, 1);
+                    num_working_instances = usize::saturating_sub(num_working_instances, 1);
                 }
                 Err(RecvTimeoutError::Timeout) => {
                     // recv timeout, i.e. boogie/underlying solver is hanging
@@ -174,8 +174,9 @@ impl ProverTask for RunBoogieWithSeeds {
         if num_instances == 1 {
             return vec![self.options.random_seed];
         }
-        let mut rng = rand::thread_rng();
-        // Otherwise generate a list of random numbers to use as seeds.
+        // Otherwise generate a list of random numbers to use as seeds. They are derived from
+        // the default random seed, so runs are reproducible.
+        let mut rng = StdRng::seed_from_u64(self.options.random_seed as u64);
         (0..num_instances)
             .map(|_| rng.gen::<u8>() as usize)
             .collect()
@@ -195,16 +196,7 @@ impl ProverTask for RunBoogieWithSeeds {
     }
 
     fn is_success(&self, task_result: &Self::TaskResult) -> bool {
-        match task_result {
-            Ok(res) => {
-                if !res.status.success() {
-                    return false;
-                }
-                let output = String::from_utf8_lossy(&res.stdout);
-                self.contains_compilation_error(&output) || !self.contains_timeout(&output)
-            }
-            Err(_) => true, // Count this as success so we terminate everything else
-        }
+        is_conclusive(task_result)
     }
 
     fn make_timeout(&self) -> (Self::TaskId, Self::TaskResult) {
@@ -220,19 +212,89 @@ impl RunBoogieWithSeeds {
             .push(format!("-proverOpt:O:smt.random_seed={}", seed));
         self.options.get_boogie_command(&self.boogie_file)
     }
+}
+
+#[derive(Debug, Clone)]
+pub struct RunBoogiePortfolio {
+    pub options: BoogieOptions,
+    pub boogie_file: String,
+}
+
+#[async_trait]
+impl ProverTask for RunBoogiePortfolio {
+    type TaskResult = std::io::Result<Output>;
+    type TaskId = usize;
+
+    fn init(&mut self, num_instances: usize) -> Vec<Self::TaskId> {
+        // The task id is the index of the configuration in the portfolio.
+        (0..num_instances.min(self.options.portfolio.len())).collect()
+    }
+
+    async fn run(&mut self, task_id: Self::TaskId, sem: Arc<Semaphore>) -> Self::TaskResult {
+        let _guard = sem.acquire().await;
+        let args = self
+            .get_boogie_command(task_id)
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?;
+        debug!(
+            "running Boogie command with portfolio configuration #{}",
+            task_id
+        );
+        Command::new(&args[0])
+            .args(&args[1..])
+            .kill_on_drop(true)
+            .output()
+            .await
+    }
+
+    fn is_success(&self, task_result: &Self::TaskResult) -> bool {
+        is_conclusive(task_result)
+    }
+
+    fn make_timeout(&self) -> (Self::TaskId, Self::TaskResult) {
+        (0, Err(std::io::Error::from(std::io::ErrorKind::TimedOut)))
+    }
+}
 
-    /// Returns whether the output string contains any Boogie compilation errors.
-    fn contains_compilation_error(&self, output: &str) -> bool {
-        let regex =
-            Regex::new(r"(?m)^.*\((?P<line>\d+),(?P<col>\d+)\).*(Error:|error:).*$").unwrap();
-        regex.is_match(output)
+impl RunBoogiePortfolio {
+    /// Returns command line to call boogie with the given portfolio configuration.
+    pub fn get_boogie_command(&self, index: usize) -> anyhow::Result<Vec<String>> {
+        let mut options = self
+            .options
+            .with_solver_config(&self.options.portfolio[index]);
+        options.boogie_flags.push(format!(
+            "-proverOpt:O:smt.random_seed={}",
+            options.random_seed
+        ));
+        options.get_boogie_command(&self.boogie_file)
     }
+}
 
-    /// Returns whether the output string contains any Boogie timeouts/inconclusiveness.
-    fn contains_timeout(&self, output: &str) -> bool {
-        let regex =
-            Regex::new(r"(?m)^.*\((?P<line>\d+),(?P<col>\d+)\).*Verification.*(inconclusive|out of resource|timed out).*$")
-                .unwrap();
-        regex.is_match(output)
+/// Returns whether the result of a Boogie run is conclusive, i.e. it has not failed and
+/// contains no timeouts, unless it contains compilation errors which will not go away by
+/// running another instance.
+fn is_conclusive(task_result: &std::io::Result<Output>) -> bool {
+    match task_result {
+        Ok(res) => {
+            if !res.status.success() {
+                return false;
+            }
+            let output = String::from_utf8_lossy(&res.stdout);
+            contains_compilation_error(&output) || !contains_timeout(&output)
+        }
+        Err(_) => true, // Count this as success so we terminate everything else
     }
 }
+
+/// Returns whether the output string contains any Boogie compilation errors.
+fn contains_compilation_error(output: &str) -> bool {
+    let regex = Regex::new(r"(?m)^.*\((?P<line>\d+),(?P<col>\d+)\).*(Error:|error:).*$").unwrap();
+    regex.is_match(output)
+}
+
+/// Returns whether the output string contains any Boogie timeouts/inconclusiveness.
+fn contains_timeout(output: &str) -> bool {
+    let regex =
+        Regex::new(r"(?m)^.*\((?P<line>\d+),(?P<col>\d+)\).*Verification.*(inconclusive|out of resource|timed out).*$")
+            .unwrap();
+    regex.is_match(output)
+}
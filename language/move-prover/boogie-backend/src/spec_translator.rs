@@ -4,7 +4,7 @@
 //! This module translates specification conditions to Boogie code.
 
 use std::{
-    collections::{BTreeSet, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap},
     rc::Rc,
 };
 
@@ -30,8 +30,9 @@ use crate::{
     },
     options::BoogieOptions,
 };
+use codespan_reporting::diagnostic::Severity;
 use move_model::{
-    ast::{Exp, MemoryLabel, QuantKind, SpecFunDecl, SpecVarDecl, TempIndex},
+    ast::{Exp, MemoryLabel, QuantKind, Spec, SpecFunDecl, SpecVarDecl, TempIndex},
     model::{QualifiedInstId, SpecVarId},
 };
 use move_stackless_bytecode::mono_analysis::MonoInfo;
@@ -249,10 +250,19 @@ impl<'env> SpecTranslator<'env> {
         let recursive = self
             .env
             .is_spec_fun_recursive(module_env.get_id().qualified(id));
+        // The definition of a recursive spec fun is a fixpoint equation, which is only emitted
+        // if the function terminates. Otherwise the function is treated as uninterpreted, as
+        // the equation could be inconsistent.
+        let terminates = fun.is_move_fun || fun.decreasing_param.borrow().is_some();
+        let uninterpreted = fun.uninterpreted || (recursive && !terminates);
         emitln!(
             self.writer,
             "// {}spec fun {}",
-            if recursive { "recursive " } else { "" },
+            match (recursive, terminates) {
+                (false, _) => "",
+                (true, true) => "recursive ",
+                (true, false) => "recursive non-terminating ",
+            },
             fun.loc.display(self.env)
         );
         let result_type = boogie_type(self.env, &self.inst(&fun.result_type));
@@ -284,7 +294,7 @@ impl<'env> SpecTranslator<'env> {
         self.writer.set_location(&fun.loc);
         let boogie_name = boogie_spec_fun_name(module_env, id, &self.type_inst);
         let param_list = mem_params.chain(params).join(", ");
-        let attrs = if fun.uninterpreted || recursive {
+        let attrs = if uninterpreted || recursive {
             ""
         } else {
             "{:inline}"
@@ -297,11 +307,12 @@ impl<'env> SpecTranslator<'env> {
             param_list,
             result_type
         );
-        if fun.uninterpreted {
+        if uninterpreted {
             // Uninterpreted function has no body.
             emitln!(self.writer, ";");
             // Emit axiom about return type. Notice we don't need to process spec_var or memory
-            // parameters because an interpreted functions does not have those.
+            // parameters because an interpreted functions does not have those. A
+            // non-terminating recursive function may use memory, and gets no such axiom.
             let call = format!(
                 "{}({})",
                 boogie_name,
@@ -312,7 +323,7 @@ impl<'env> SpecTranslator<'env> {
             );
             let type_check =
                 boogie_well_formed_expr(self.env, "$$res", &self.inst(&fun.result_type));
-            if !type_check.is_empty() {
+            if !type_check.is_empty() && fun.used_memory.is_empty() {
                 if !param_list.is_empty() {
                     emitln!(
                         self.writer,
@@ -428,7 +439,12 @@ impl<'env> SpecTranslator<'env> {
             emit_valid(&var_decl.0, result_ty);
             match env.get_node_type(info.range.node_id()) {
                 Type::Vector(..) => {
-                    emit!(new_spec_trans.writer, " && InRangeVec(");
+                    // The choice variable ranges over the elements of the vector.
+                    emit!(
+                        new_spec_trans.writer,
+                        " && $ContainsVec{}(",
+                        boogie_inst_suffix(env, &[new_spec_trans.inst(result_ty)])
+                    );
                     new_spec_trans.translate_exp(&info.range);
                     emit!(new_spec_trans.writer, ", {})", &var_decl.0);
                 }
@@ -1120,6 +1136,33 @@ impl<'env> SpecTranslator<'env> {
                     emit!(self.writer, "{{{}}}", resource_value);
                 }
             }
+            // Implicit trigger from vector and integer ranges.
+            if let Some(selections) = self.range_trigger_selections(ranges, condition, body) {
+                emit!(self.writer, "{");
+                let mut comma = "";
+                for (var, _) in ranges {
+                    emit!(self.writer, "{}", comma);
+                    match selections.get(&var.name) {
+                        Some(selection) => self.with_range_selector_assignments(
+                            ranges,
+                            &range_tmps,
+                            &quant_vars,
+                            &resource_vars,
+                            || {
+                                self.translate_exp(selection);
+                            },
+                        ),
+                        None => emit!(
+                            self.writer,
+                            "ReadVec({}, {})",
+                            range_tmps[&var.name],
+                            quant_vars[&var.name]
+                        ),
+                    }
+                    comma = ",";
+                }
+                emit!(self.writer, "}");
+            }
         }
         // Translate range constraints.
         let connective = match kind {
@@ -1193,6 +1236,80 @@ impl<'env> SpecTranslator<'env> {
         );
     }
 
+    /// Determines the terms of the trigger of a quantifier without explicit triggers, whose
+    /// ranges are all vectors or integer ranges. The trigger of a variable ranging over the
+    /// elements of a vector is the selection of the element. For a variable `i` ranging over
+    /// integers, it is the first selection `v[i]` in the condition or body of the quantifier,
+    /// where `v` does not depend on quantified variables, which is returned for `i` in the map.
+    /// Returns `None` if there is no such selection for some variable, leaving the choice of
+    /// triggers to the solver.
+    fn range_trigger_selections(
+        &self,
+        ranges: &[(LocalVarDecl, Exp)],
+        condition: &Option<Exp>,
+        body: &Exp,
+    ) -> Option<BTreeMap<Symbol, Exp>> {
+        let bound_vars = ranges.iter().map(|(var, _)| var.name).collect_vec();
+        let mut selections = BTreeMap::new();
+        for (var, range) in ranges {
+            match self.get_node_type(range.node_id()).skip_reference() {
+                Type::Vector(..) => {}
+                Type::Primitive(PrimitiveType::Range) => {
+                    let selection = condition
+                        .iter()
+                        .chain(std::iter::once(body))
+                        .find_map(|exp| self.find_vector_selection(exp, var.name, &bound_vars))?;
+                    selections.insert(var.name, selection);
+                }
+                _ => return None,
+            }
+        }
+        Some(selections)
+    }
+
+    /// Finds the first selection `v[var]` in the expression where `var` is not shadowed by an
+    /// inner binder, and `v` neither refers to the bound variables nor to inner binders.
+    fn find_vector_selection(&self, exp: &Exp, var: Symbol, bound_vars: &[Symbol]) -> Option<Exp> {
+        let mut result = None;
+        let mut shadowed = vec![];
+        let mut visitor = |up: bool, e: &ExpData| {
+            use ExpData::*;
+            let decls = match e {
+                Lambda(_, decls, _) | Block(_, decls, _) => {
+                    decls.iter().map(|d| d.name).collect_vec()
+                }
+                Quant(_, _, decls, ..) => decls.iter().map(|(d, _)| d.name).collect_vec(),
+                _ => vec![],
+            };
+            if !up {
+                shadowed.extend(decls);
+                if result.is_some() {
+                    return;
+                }
+                if let Call(id, Operation::Index, args) = e {
+                    let is_var = matches!(args[1].as_ref(), LocalVar(_, sym) if *sym == var);
+                    if is_var
+                        && !shadowed.contains(&var)
+                        && args[0]
+                            .free_vars(self.env)
+                            .iter()
+                            .all(|(sym, _)| !bound_vars.contains(sym) && !shadowed.contains(sym))
+                    {
+                        result = Some(Call(*id, Operation::Index, args.clone()).into_exp());
+                    }
+                }
+            } else {
+                for sym in decls {
+                    if let Some(pos) = shadowed.iter().position(|s| *s == sym) {
+                        shadowed.remove(pos);
+                    }
+                }
+            }
+        };
+        exp.visit_pre_post(&mut visitor);
+        result
+    }
+
     /// Translate a `some x: T: P[x]` expression. This saves information about the axiomatized
     /// function representing this expression, to be generated later, and replaces the expression by
     /// a call to this function.
@@ -1394,3 +1511,158 @@ impl<'env> SpecTranslator<'env> {
         }
     }
 }
+
+// Well-formedness Checks
+// ======================
+
+/// Checks the specifications of the target modules for constructs the translation cannot deal
+/// with, and reports them at their location in the source:
+/// - `old(..)` in a condition which is evaluated in a single state, nested `old(..)`, and
+///   `old(..)` whose type differs from the type of its argument, other than by the reference
+///   which `old(..)` of a `&mut` parameter removes;
+/// - calls of Move functions which take mutable references;
+/// - quantified variables which shadow variables bound outside of the quantifier, which are
+///   reported as warnings;
+/// - specification functions with reference parameters or results.
+/// The front end rejects most of these, but specs can also be rewritten after model building,
+/// and without this check such specs fail deep inside of the translation.
+pub fn check_spec_well_formedness(env: &GlobalEnv) {
+    for module_env in env.get_modules().filter(|m| m.is_target()) {
+        check_spec(env, module_env.get_spec());
+        for struct_env in module_env.get_structs() {
+            check_spec(env, struct_env.get_spec());
+        }
+        for fun_env in module_env.get_functions() {
+            check_spec(env, fun_env.get_spec());
+        }
+        for (_, decl) in module_env.get_spec_funs() {
+            check_spec_fun(env, decl);
+        }
+    }
+}
+
+fn check_spec(env: &GlobalEnv, spec: &Spec) {
+    for cond in &spec.conditions {
+        for exp in cond.all_exps() {
+            check_spec_exp(env, exp, cond.kind.allows_old());
+        }
+    }
+    for code_spec in spec.on_impl.values() {
+        check_spec(env, code_spec);
+    }
+}
+
+fn check_spec_fun(env: &GlobalEnv, decl: &SpecFunDecl) {
+    if decl.is_move_fun {
+        // Reference types of Move functions are removed when they are made spec functions.
+        return;
+    }
+    let name = decl.name.display(env.symbol_pool());
+    if decl.result_type.is_reference() {
+        env.error(
+            &decl.loc,
+            &format!("spec function `{}` cannot return a reference", name),
+        );
+    }
+    for (param, ty) in &decl.params {
+        if ty.is_reference() {
+            env.error(
+                &decl.loc,
+                &format!(
+                    "parameter `{}` of spec function `{}` cannot be a reference",
+                    param.display(env.symbol_pool()),
+                    name
+                ),
+            );
+        }
+    }
+    if let Some(body) = &decl.body {
+        check_spec_exp(env, body, false);
+    }
+}
+
+fn check_spec_exp(env: &GlobalEnv, exp: &Exp, allows_old: bool) {
+    let mut old_depth = 0;
+    // The variables bound by the enclosing expressions, and how many each of them binds.
+    let mut bound: Vec<Symbol> = vec![];
+    let mut scopes: Vec<usize> = vec![];
+    exp.visit_pre_post(&mut |up, e| match e {
+        ExpData::Call(id, Operation::Old, args) => {
+            if up {
+                old_depth -= 1;
+                return;
+            }
+            let loc = env.get_node_loc(*id);
+            if !allows_old {
+                env.error(&loc, "`old(..)` expression not allowed in this context");
+            } else if old_depth > 0 {
+                env.error(&loc, "`old(..old(..)..)` not allowed");
+            } else if args.len() != 1
+                || env.get_node_type(*id).skip_reference()
+                    != env.get_node_type(args[0].node_id()).skip_reference()
+            {
+                env.error(
+                    &loc,
+                    "type of `old(..)` differs from the type of its argument",
+                );
+            }
+            old_depth += 1;
+        }
+        ExpData::Call(id, Operation::Function(mid, fid, _), _) if !up => {
+            let module_env = env.get_module(*mid);
+            let decl = module_env.get_spec_fun(*fid);
+            if !decl.is_move_fun {
+                return;
+            }
+            let name = env.symbol_pool().string(decl.name);
+            let fun_env =
+                module_env.find_function(env.symbol_pool().make(name.trim_start_matches('$')));
+            if let Some(fun_env) = fun_env {
+                if fun_env
+                    .get_parameter_types()
+                    .iter()
+                    .any(|ty| ty.is_mutable_reference())
+                {
+                    env.error(
+                        &env.get_node_loc(*id),
+                        &format!(
+                            "calling `{}`, which mutates its arguments, is not allowed in \
+                             specifications",
+                            fun_env.get_full_name_str()
+                        ),
+                    );
+                }
+            }
+        }
+        ExpData::Quant(_, _, ranges, ..) => {
+            if up {
+                bound.truncate(bound.len() - scopes.pop().expect("scope"));
+                return;
+            }
+            for (decl, _) in ranges {
+                if bound.contains(&decl.name) {
+                    env.diag(
+                        Severity::Warning,
+                        &env.get_node_loc(decl.id),
+                        &format!(
+                            "quantified variable `{}` shadows a variable of an enclosing \
+                             expression",
+                            decl.name.display(env.symbol_pool())
+                        ),
+                    );
+                }
+            }
+            bound.extend(ranges.iter().map(|(decl, _)| decl.name));
+            scopes.push(ranges.len());
+        }
+        ExpData::Lambda(_, decls, _) | ExpData::Block(_, decls, _) => {
+            if up {
+                bound.truncate(bound.len() - scopes.pop().expect("scope"));
+            } else {
+                bound.extend(decls.iter().map(|decl| decl.name));
+                scopes.push(decls.len());
+            }
+        }
+        _ => {}
+    });
+}
@@ -0,0 +1,94 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for the shrinking of counterexamples, with the solver simulated by a function which
+//! returns a counterexample under the given bounds, if there is one.
+
+use move_prover_boogie_backend::boogie_wrapper::CounterexampleShrinker;
+use std::collections::BTreeMap;
+
+/// Parses an assumption of the form `t1 <= b1 && t2 <= b2`.
+fn parse_bounds(assumption: &str) -> BTreeMap<String, u128> {
+    assumption
+        .split(" && ")
+        .map(|bound| {
+            let (term, value) = bound.split_once(" <= ").unwrap();
+            (term.to_string(), value.parse().unwrap())
+        })
+        .collect()
+}
+
+/// Shrinks the counterexample with the solver, returning the final counterexample and the
+/// number of solver calls.
+fn shrink<F>(initial: Vec<(String, u128)>, solve: F) -> (Vec<(String, u128)>, usize)
+where
+    F: Fn(&BTreeMap<String, u128>) -> Option<Vec<(String, u128)>>,
+{
+    let mut best = initial;
+    let mut shrinker = CounterexampleShrinker::default();
+    let mut calls = 0;
+    while let Some(assumption) = shrinker.next_assumption(&best) {
+        calls += 1;
+        let counterexample = solve(&parse_bounds(&assumption));
+        shrinker.record(counterexample.is_some());
+        if let Some(counterexample) = counterexample {
+            best = counterexample;
+        }
+    }
+    (best, calls)
+}
+
+/// A solver for the verification condition `x < 10 && len(v) < 3`, which returns the largest
+/// values allowed by the bounds, as the least helpful solver would.
+fn solve_largest(bounds: &BTreeMap<String, u128>) -> Option<Vec<(String, u128)>> {
+    let x = *bounds.get("$t0").unwrap_or(&(u64::MAX as u128));
+    let len = *bounds.get("LenVec($t1)").unwrap_or(&1000);
+    if x >= 10 || len >= 3 {
+        Some(vec![
+            ("$t0".to_string(), x),
+            ("LenVec($t1)".to_string(), len),
+        ])
+    } else {
+        None
+    }
+}
+
+#[test]
+fn shrinks_to_small_witness() {
+    let initial = vec![
+        ("$t0".to_string(), u64::MAX as u128),
+        ("LenVec($t1)".to_string(), 1000),
+    ];
+    let (witness, calls) = shrink(initial, solve_largest);
+    // Once `x` is too small to violate the condition, the solver violates the other conjunct,
+    // so `x` is shrunk to zero, and the length to the smallest violating one reached by halving.
+    assert_eq!(
+        witness,
+        vec![("$t0".to_string(), 0), ("LenVec($t1)".to_string(), 3)]
+    );
+    // The number of calls is logarithmic in the initial values: 64 for `x`, and 9 for the
+    // length, the last of which does not reproduce the error.
+    assert_eq!(calls, 73);
+}
+
+#[test]
+fn keeps_irreducible_witness() {
+    // The error only occurs for `x == 7`.
+    let solve = |bounds: &BTreeMap<String, u128>| {
+        if bounds.get("$t0").map(|b| *b >= 7).unwrap_or(true) {
+            Some(vec![("$t0".to_string(), 7)])
+        } else {
+            None
+        }
+    };
+    let (witness, calls) = shrink(vec![("$t0".to_string(), 7)], solve);
+    assert_eq!(witness, vec![("$t0".to_string(), 7)]);
+    assert_eq!(calls, 1);
+}
+
+#[test]
+fn nothing_to_shrink() {
+    let mut shrinker = CounterexampleShrinker::default();
+    assert_eq!(shrinker.next_assumption(&[]), None);
+    assert_eq!(shrinker.next_assumption(&[("$t0".to_string(), 0)]), None);
+}
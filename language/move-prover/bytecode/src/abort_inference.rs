@@ -0,0 +1,187 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Inference of the abort conditions of functions.
+//!
+//! Based on the abort paths computed by the `AbortPathProcessor`, which must run before, the
+//! `AbortInferenceProcessor` infers for each function the set of causes for which it can abort,
+//! and attaches it to the baseline variant as an `InferredAbortsIf` annotation. Aborts of
+//! callees are propagated through the call graph: a call of a function which can abort
+//! contributes the inferred causes of the callee rather than the call itself. Only calls of
+//! natives and of recursive functions, whose causes are not known, remain as `Callee` causes.
+//!
+//! `inferred_aborts_report` compares the inferred causes with the declared `aborts_if`
+//! conditions of functions which have some, and reports the causes the declaration does not
+//! cover. The comparison is syntactic: a missing or existing resource is covered if an
+//! `aborts_if` condition refers to the resource, an explicit abort code is covered if the
+//! conditions mention it, and other causes are covered by any `aborts_if` condition. Functions
+//! with `pragma aborts_if_is_partial` are not reported.
+
+use crate::{
+    abort_path_analysis::{describe_cause, get_abort_paths, spec_abort_codes, AbortCause},
+    function_target::{FunctionData, FunctionTarget},
+    function_target_pipeline::{FunctionTargetProcessor, FunctionTargetsHolder, FunctionVariant},
+};
+use move_model::{
+    ast::{ConditionKind, ExpData, Value},
+    model::{FunctionEnv, GlobalEnv, Loc},
+    pragmas::ABORTS_IF_IS_PARTIAL_PRAGMA,
+};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Formatter},
+};
+
+/// The annotation attached to the baseline variant of a function.
+#[derive(Debug, Clone, Default)]
+pub struct InferredAbortsIf {
+    /// The causes for which the function can abort, each with the location in the function of
+    /// the first instruction which can abort for it.
+    pub conditions: BTreeMap<AbortCause, Loc>,
+}
+
+/// Returns the inferred abort conditions of the target, if the function has been analyzed.
+pub fn get_inferred_aborts_if<'env>(
+    target: &FunctionTarget<'env>,
+) -> Option<&'env InferredAbortsIf> {
+    target.get_annotations().get::<InferredAbortsIf>()
+}
+
+pub struct AbortInferenceProcessor {}
+
+impl AbortInferenceProcessor {
+    pub fn new() -> Box<Self> {
+        Box::new(AbortInferenceProcessor {})
+    }
+}
+
+impl FunctionTargetProcessor for AbortInferenceProcessor {
+    fn process(
+        &self,
+        targets: &mut FunctionTargetsHolder,
+        fun_env: &FunctionEnv<'_>,
+        mut data: FunctionData,
+    ) -> FunctionData {
+        if fun_env.is_native_or_intrinsic() || data.variant != FunctionVariant::Baseline {
+            return data;
+        }
+        let mut inferred = InferredAbortsIf::default();
+        {
+            let target = FunctionTarget::new(fun_env, &data);
+            let paths = get_abort_paths(&target)
+                .expect("abort path analysis must run before abort inference");
+            for path in &paths.paths {
+                let callee_conditions = match &path.cause {
+                    AbortCause::Callee(callee_id) => targets
+                        .get_data(callee_id, &FunctionVariant::Baseline)
+                        .and_then(|callee_data| callee_data.annotations.get::<InferredAbortsIf>()),
+                    _ => None,
+                };
+                match callee_conditions {
+                    Some(callee_inferred) => {
+                        for cause in callee_inferred.conditions.keys() {
+                            inferred
+                                .conditions
+                                .entry(cause.clone())
+                                .or_insert_with(|| path.loc.clone());
+                        }
+                    }
+                    None => {
+                        inferred
+                            .conditions
+                            .entry(path.cause.clone())
+                            .or_insert_with(|| path.loc.clone());
+                    }
+                }
+            }
+        }
+        data.annotations.set(inferred);
+        data
+    }
+
+    fn name(&self) -> String {
+        "abort_inference".to_string()
+    }
+
+    fn dump_result(
+        &self,
+        f: &mut Formatter<'_>,
+        env: &GlobalEnv,
+        targets: &FunctionTargetsHolder,
+    ) -> fmt::Result {
+        writeln!(f, "\n\n********* Result of abort inference *********\n\n")?;
+        write!(f, "{}", inferred_aborts_report(env, targets))
+    }
+}
+
+/// Returns a report of the functions in the target modules whose declared `aborts_if`
+/// conditions do not cover the inferred ones.
+pub fn inferred_aborts_report(env: &GlobalEnv, targets: &FunctionTargetsHolder) -> String {
+    let mut res = String::new();
+    for module_env in env.get_modules().filter(|m| m.is_target()) {
+        for fun_env in module_env.get_functions() {
+            if !targets.has_target(&fun_env, &FunctionVariant::Baseline)
+                || fun_env.is_pragma_true(ABORTS_IF_IS_PARTIAL_PRAGMA, || false)
+            {
+                continue;
+            }
+            let spec = fun_env.get_spec();
+            let aborts_ifs = spec
+                .conditions
+                .iter()
+                .filter(|cond| cond.kind == ConditionKind::AbortsIf)
+                .collect::<Vec<_>>();
+            if aborts_ifs.is_empty() {
+                continue;
+            }
+            let target = targets.get_target(&fun_env, &FunctionVariant::Baseline);
+            let inferred = match get_inferred_aborts_if(&target) {
+                Some(inferred) => inferred,
+                None => continue,
+            };
+            // Conditions `aborts_if false` declare that the function does not abort.
+            let declared = aborts_ifs
+                .into_iter()
+                .filter(|cond| !matches!(cond.exp.as_ref(), ExpData::Value(_, Value::Bool(false))))
+                .collect::<Vec<_>>();
+            let spec_codes = spec_abort_codes(&fun_env);
+            let uncovered = inferred
+                .conditions
+                .iter()
+                .filter(|(cause, _)| match cause {
+                    AbortCause::MissingResource(id) | AbortCause::ExistingResource(id) => {
+                        !declared.iter().any(|cond| {
+                            cond.exp
+                                .used_memory(env)
+                                .iter()
+                                .any(|(mem, _)| mem.to_qualified_id() == *id)
+                        })
+                    }
+                    AbortCause::Explicit(Some(code))
+                        if !spec_codes.is_empty() && spec_codes.iter().all(Option::is_some) =>
+                    {
+                        !spec_codes.contains(&Some(*code))
+                    }
+                    _ => declared.is_empty(),
+                })
+                .collect::<Vec<_>>();
+            if uncovered.is_empty() {
+                continue;
+            }
+            res.push_str(&format!(
+                "fun {}: {} inferred abort condition(s) not covered by `aborts_if`\n",
+                fun_env.get_full_name_str(),
+                uncovered.len()
+            ));
+            for (cause, loc) in uncovered {
+                res.push_str(&format!(
+                    "  {} {}\n",
+                    describe_cause(env, cause),
+                    loc.display(env)
+                ));
+            }
+            res.push('\n');
+        }
+    }
+    res
+}
@@ -0,0 +1,433 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Enumeration of the abort paths of functions.
+//!
+//! An abort path ends at an instruction which can abort: an explicit `abort`, an arithmetic
+//! operation which can overflow or divide by zero, a global storage operation which finds a
+//! resource missing or already existing, a vector operation with an index out of range, or a
+//! call of a function which can abort. A path is summarized by the branches which guard its
+//! instruction, i.e. the branches of which only one successor can reach the instruction,
+//! together with the outcome needed to reach it.
+//!
+//! The `AbortPathProcessor` attaches the paths of the baseline variant of a function as an
+//! `AbortPaths` annotation. `abort_path_report` compares them with the `aborts_if` and
+//! `aborts_with` conditions of the functions, and points out functions whose abort behavior is
+//! under-specified: functions which can abort but have no abort conditions, which abort with
+//! constant codes their conditions do not mention, or which have fewer `aborts_if` conditions
+//! than distinct causes of aborts.
+
+use crate::{
+    function_target::{FunctionData, FunctionTarget},
+    function_target_pipeline::{FunctionTargetProcessor, FunctionTargetsHolder, FunctionVariant},
+    stackless_bytecode::{Bytecode, Constant, Operation},
+};
+use move_binary_format::file_format::CodeOffset;
+use move_model::{
+    ast::{ConditionKind, ExpData, TempIndex, Value},
+    model::{FunId, FunctionEnv, GlobalEnv, Loc, QualifiedId, StructId},
+    pragmas::ABORTS_IF_IS_PARTIAL_PRAGMA,
+};
+use num::ToPrimitive;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{self, Formatter},
+};
+
+/// The cause of an abort.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AbortCause {
+    /// An explicit `abort`, with its code if it is a constant.
+    Explicit(Option<u64>),
+    /// An arithmetic operation or cast which overflows, underflows, or divides by zero.
+    Arithmetic,
+    /// A global storage operation which finds the resource missing.
+    MissingResource(QualifiedId<StructId>),
+    /// A `move_to` which finds the resource already existing.
+    ExistingResource(QualifiedId<StructId>),
+    /// A vector operation with an index out of range, or on a vector of the wrong length.
+    VectorIndex,
+    /// An access to a value of an enum which is not of the expected variant.
+    Variant,
+    /// A call of a function which can abort.
+    Callee(QualifiedId<FunId>),
+}
+
+/// A path of a function which ends in an abort.
+#[derive(Debug, Clone)]
+pub struct AbortPath {
+    /// The offset of the instruction which aborts.
+    pub offset: CodeOffset,
+    pub loc: Loc,
+    pub cause: AbortCause,
+    /// The locations of the branches guarding the instruction, together with the outcome of
+    /// the branch condition on the path.
+    pub guards: Vec<(Loc, bool)>,
+}
+
+/// The annotation attached to the baseline variant of a function.
+#[derive(Debug, Clone, Default)]
+pub struct AbortPaths {
+    /// The abort paths, in code order.
+    pub paths: Vec<AbortPath>,
+}
+
+/// Returns the abort paths of the target, if the function has been analyzed.
+pub fn get_abort_paths<'env>(target: &FunctionTarget<'env>) -> Option<&'env AbortPaths> {
+    target.get_annotations().get::<AbortPaths>()
+}
+
+pub struct AbortPathProcessor {}
+
+impl AbortPathProcessor {
+    pub fn new() -> Box<Self> {
+        Box::new(AbortPathProcessor {})
+    }
+}
+
+impl FunctionTargetProcessor for AbortPathProcessor {
+    fn process(
+        &self,
+        targets: &mut FunctionTargetsHolder,
+        fun_env: &FunctionEnv<'_>,
+        mut data: FunctionData,
+    ) -> FunctionData {
+        if fun_env.is_native_or_intrinsic() || data.variant != FunctionVariant::Baseline {
+            return data;
+        }
+        let paths = {
+            let target = FunctionTarget::new(fun_env, &data);
+            Self::analyze(&target, targets)
+        };
+        data.annotations.set(paths);
+        data
+    }
+
+    fn name(&self) -> String {
+        "abort_path_analysis".to_string()
+    }
+
+    fn dump_result(
+        &self,
+        f: &mut Formatter<'_>,
+        env: &GlobalEnv,
+        targets: &FunctionTargetsHolder,
+    ) -> fmt::Result {
+        writeln!(
+            f,
+            "\n\n********* Result of abort path analysis *********\n\n"
+        )?;
+        write!(f, "{}", abort_path_report(env, targets))
+    }
+}
+
+impl AbortPathProcessor {
+    fn analyze(target: &FunctionTarget<'_>, targets: &FunctionTargetsHolder) -> AbortPaths {
+        let code = target.get_bytecode();
+        let label_offsets = Bytecode::label_offsets(code);
+        let reachable_from = |start: CodeOffset| {
+            let mut reached = BTreeSet::new();
+            let mut todo = vec![start];
+            while let Some(offset) = todo.pop() {
+                if (offset as usize) < code.len() && reached.insert(offset) {
+                    todo.extend(Bytecode::get_successors(offset, code, &label_offsets));
+                }
+            }
+            reached
+        };
+        // For each branch, the location of its condition, and the code reachable from its two
+        // successors.
+        let branches = code
+            .iter()
+            .enumerate()
+            .filter_map(|(offset, bc)| match bc {
+                Bytecode::Branch(id, then_label, else_label, cond) => Some((
+                    Self::condition_loc(target, code, offset, *cond)
+                        .unwrap_or_else(|| target.get_bytecode_loc(*id)),
+                    reachable_from(label_offsets[then_label]),
+                    reachable_from(label_offsets[else_label]),
+                )),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        let mut paths = vec![];
+        for (offset, bc) in code.iter().enumerate() {
+            let cause = match bc {
+                Bytecode::Abort(_, code_temp) => {
+                    AbortCause::Explicit(Self::constant_code(code, offset, *code_temp))
+                }
+                Bytecode::Call(_, _, op, _, _) => match Self::abort_cause(op, targets) {
+                    Some(cause) => cause,
+                    None => continue,
+                },
+                _ => continue,
+            };
+            let offset = offset as CodeOffset;
+            let guards = branches
+                .iter()
+                .filter_map(|(loc, then_reach, else_reach)| {
+                    match (then_reach.contains(&offset), else_reach.contains(&offset)) {
+                        (true, false) => Some((loc.clone(), true)),
+                        (false, true) => Some((loc.clone(), false)),
+                        _ => None,
+                    }
+                })
+                .collect();
+            paths.push(AbortPath {
+                offset,
+                loc: target.get_bytecode_loc(bc.get_attr_id()),
+                cause,
+                guards,
+            });
+        }
+        AbortPaths { paths }
+    }
+
+    /// Returns the location of the expression which computes the temporary before the offset,
+    /// if it is computed in the same block. This is used for the conditions of branches, as
+    /// the location of a branch is the one of the whole `if` or `while`.
+    fn condition_loc(
+        target: &FunctionTarget<'_>,
+        code: &[Bytecode],
+        offset: usize,
+        temp: TempIndex,
+    ) -> Option<Loc> {
+        for (def_offset, bc) in code[..offset].iter().enumerate().rev() {
+            match bc {
+                Bytecode::Assign(id, dest, ..) | Bytecode::Load(id, dest, _) if *dest == temp => {
+                    return Some(target.get_bytecode_loc(*id));
+                }
+                Bytecode::Call(id, dests, _, srcs, _) if dests.contains(&temp) => {
+                    // The location of an operation is the one of its operator, so enclose the
+                    // locations of the operands.
+                    let mut locs = vec![target.get_bytecode_loc(*id)];
+                    locs.extend(
+                        srcs.iter()
+                            .filter_map(|src| Self::condition_loc(target, code, def_offset, *src)),
+                    );
+                    return Some(Loc::enclosing(&locs.iter().collect::<Vec<_>>()));
+                }
+                Bytecode::Label(..) => return None,
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// Returns the code of an explicit abort if it is loaded from a constant in the same block.
+    fn constant_code(code: &[Bytecode], offset: usize, code_temp: TempIndex) -> Option<u64> {
+        for bc in code[..offset].iter().rev() {
+            match bc {
+                Bytecode::Load(_, dest, constant) if *dest == code_temp => {
+                    return match constant {
+                        Constant::U64(value) => Some(*value),
+                        _ => None,
+                    };
+                }
+                Bytecode::Label(..) => return None,
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// Returns the cause of the abort of the operation, if it can abort.
+    fn abort_cause(op: &Operation, targets: &FunctionTargetsHolder) -> Option<AbortCause> {
+        use Operation::*;
+        let cause = match op {
+            Function(mid, fid, _) => {
+                let callee_id = mid.qualified(*fid);
+                let can_abort = targets
+                    .get_data(&callee_id, &FunctionVariant::Baseline)
+                    .and_then(|data| data.annotations.get::<AbortPaths>())
+                    .map(|callee_paths| !callee_paths.paths.is_empty())
+                    .unwrap_or(true);
+                if !can_abort {
+                    return None;
+                }
+                AbortCause::Callee(callee_id)
+            }
+            MoveTo(mid, sid, _) => AbortCause::ExistingResource(mid.qualified(*sid)),
+            MoveFrom(mid, sid, _) | BorrowGlobal(mid, sid, _) | GetGlobal(mid, sid, _) => {
+                AbortCause::MissingResource(mid.qualified(*sid))
+            }
+            CastU8 | CastU64 | CastU128 | CastU256 | Add | Sub | Mul | Div | Mod => {
+                AbortCause::Arithmetic
+            }
+            VecUnpack(..) | VecBorrow(_) | VecBorrowMut(_) | VecPopBack(_) | VecSwap(_) => {
+                AbortCause::VectorIndex
+            }
+            UnpackVariant(..) | BorrowVariantField(..) => AbortCause::Variant,
+            _ => return None,
+        };
+        Some(cause)
+    }
+}
+
+/// Returns a report of the abort paths of the functions in the target modules which can abort,
+/// pointing out the functions whose abort behavior is under-specified.
+pub fn abort_path_report(env: &GlobalEnv, targets: &FunctionTargetsHolder) -> String {
+    let mut res = String::new();
+    for module_env in env.get_modules().filter(|m| m.is_target()) {
+        for fun_env in module_env.get_functions() {
+            if !targets.has_target(&fun_env, &FunctionVariant::Baseline) {
+                continue;
+            }
+            let target = targets.get_target(&fun_env, &FunctionVariant::Baseline);
+            let paths = match get_abort_paths(&target) {
+                Some(paths) if !paths.paths.is_empty() => &paths.paths,
+                _ => continue,
+            };
+            let spec = fun_env.get_spec();
+            let aborts_if_count = spec
+                .conditions
+                .iter()
+                .filter(|cond| cond.kind == ConditionKind::AbortsIf)
+                .count();
+            let has_aborts_with = spec
+                .conditions
+                .iter()
+                .any(|cond| cond.kind == ConditionKind::AbortsWith);
+            let spec_codes = spec_abort_codes(&fun_env);
+
+            let mut problems = vec![];
+            if aborts_if_count == 0 && !has_aborts_with {
+                problems.push("no abort conditions".to_string());
+            } else {
+                // Only check codes if the conditions specify any, and all of them are constants.
+                let unspecified_codes = paths
+                    .iter()
+                    .filter_map(|path| match path.cause {
+                        AbortCause::Explicit(Some(code))
+                            if !spec_codes.is_empty()
+                                && spec_codes.iter().all(Option::is_some)
+                                && !spec_codes.contains(&Some(code)) =>
+                        {
+                            Some(code)
+                        }
+                        _ => None,
+                    })
+                    .collect::<BTreeSet<_>>();
+                if !unspecified_codes.is_empty() {
+                    problems.push(format!(
+                        "abort code(s) {} not specified",
+                        unspecified_codes
+                            .iter()
+                            .map(|code| code.to_string())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ));
+                }
+                let causes = paths
+                    .iter()
+                    .map(|path| &path.cause)
+                    .collect::<BTreeSet<_>>();
+                if aborts_if_count < causes.len()
+                    && !fun_env.is_pragma_true(ABORTS_IF_IS_PARTIAL_PRAGMA, || false)
+                {
+                    problems.push(format!(
+                        "{} distinct abort cause(s) but {} `aborts_if` condition(s)",
+                        causes.len(),
+                        aborts_if_count
+                    ));
+                }
+            }
+
+            res.push_str(&format!(
+                "fun {}: {} abort path(s), {} `aborts_if` condition(s)\n",
+                fun_env.get_full_name_str(),
+                paths.len(),
+                aborts_if_count
+            ));
+            if !problems.is_empty() {
+                res.push_str(&format!("  under-specified: {}\n", problems.join("; ")));
+            }
+            for path in paths {
+                res.push_str(&format!(
+                    "  {} {}{}\n",
+                    describe_cause(env, &path.cause),
+                    path.loc.display(env),
+                    describe_guards(env, &path.guards)
+                ));
+            }
+            res.push('\n');
+        }
+    }
+    res
+}
+
+/// Returns the abort codes in the `aborts_if` and `aborts_with` conditions of the function,
+/// with `None` for codes which are not constants.
+pub fn spec_abort_codes(fun_env: &FunctionEnv<'_>) -> Vec<Option<u64>> {
+    fun_env
+        .get_spec()
+        .conditions
+        .iter()
+        .flat_map(|cond| match cond.kind {
+            ConditionKind::AbortsIf => cond.additional_exps.iter().collect::<Vec<_>>(),
+            ConditionKind::AbortsWith => cond.all_exps().collect(),
+            _ => vec![],
+        })
+        .map(|exp| match exp.as_ref() {
+            ExpData::Value(_, Value::Number(code)) => code.to_u64(),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Describes the cause of an abort for reports.
+pub fn describe_cause(env: &GlobalEnv, cause: &AbortCause) -> String {
+    match cause {
+        AbortCause::Explicit(Some(code)) => format!("abort with code {}", code),
+        AbortCause::Explicit(None) => "abort".to_string(),
+        AbortCause::Arithmetic => "arithmetic error".to_string(),
+        AbortCause::MissingResource(id) => {
+            format!(
+                "missing resource `{}`",
+                env.get_struct(*id).get_full_name_str()
+            )
+        }
+        AbortCause::ExistingResource(id) => {
+            format!(
+                "existing resource `{}`",
+                env.get_struct(*id).get_full_name_str()
+            )
+        }
+        AbortCause::VectorIndex => "vector index out of range".to_string(),
+        AbortCause::Variant => "unexpected enum variant".to_string(),
+        AbortCause::Callee(id) => {
+            format!("abort in `{}`", env.get_function(*id).get_full_name_str())
+        }
+    }
+}
+
+/// Describes the guards of a path by the source of the branch conditions.
+fn describe_guards(env: &GlobalEnv, guards: &[(Loc, bool)]) -> String {
+    if guards.is_empty() {
+        return String::new();
+    }
+    // Several branches can share a location, e.g. for `&&` and `||`.
+    let mut outcomes: BTreeMap<String, BTreeSet<bool>> = BTreeMap::new();
+    for (loc, outcome) in guards {
+        let source = match env.get_source(loc) {
+            Ok(source) => source.split_whitespace().collect::<Vec<_>>().join(" "),
+            Err(_) => loc.display(env).to_string(),
+        };
+        outcomes.entry(source).or_default().insert(*outcome);
+    }
+    format!(
+        " when {}",
+        outcomes
+            .into_iter()
+            .map(
+                |(source, outcomes)| match (outcomes.contains(&true), outcomes.contains(&false)) {
+                    (true, false) => format!("`{}` holds", source),
+                    (false, true) => format!("`{}` fails", source),
+                    _ => format!("`{}` is partially evaluated", source),
+                }
+            )
+            .collect::<Vec<_>>()
+            .join(" and ")
+    )
+}
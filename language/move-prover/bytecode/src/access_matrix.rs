@@ -0,0 +1,216 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Resource access matrices for audits.
+//!
+//! The access matrix relates the functions of the target modules to the resources they access
+//! in global storage. An access is direct if it stems from an operation of the function itself,
+//! and indirect if it stems from a function it transitively calls. Together with the visibility
+//! of each function and the modules it can be called from, this shows who can read, write,
+//! create, and destroy each resource. Accesses by native functions are not known and hence not
+//! included.
+
+use crate::{
+    function_target_pipeline::{FunctionTargetsHolder, FunctionVariant},
+    stackless_bytecode::{Bytecode, Operation},
+};
+use itertools::Itertools;
+use move_binary_format::file_format::Visibility;
+use move_model::model::{FunctionEnv, GlobalEnv, QualifiedId, StructId};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// An access of a function to a resource.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResourceAccess {
+    /// The resource is checked for existence or borrowed immutably.
+    Read,
+    /// The resource is borrowed mutably.
+    Write,
+    /// The resource is moved to global storage.
+    Create,
+    /// The resource is moved from global storage.
+    Destroy,
+}
+
+const ACCESSES: [ResourceAccess; 4] = [
+    ResourceAccess::Read,
+    ResourceAccess::Write,
+    ResourceAccess::Create,
+    ResourceAccess::Destroy,
+];
+
+/// A cell of the access matrix.
+#[derive(Debug, Clone, Serialize)]
+pub struct AccessEntry {
+    pub resource: String,
+    pub function: String,
+    /// One of `public`, `friend`, `script`, or `private`.
+    pub visibility: String,
+    /// The modules the function can be called from, or `*` if it can be called from anywhere.
+    pub callable_from: Vec<String>,
+    pub direct: BTreeSet<ResourceAccess>,
+    pub indirect: BTreeSet<ResourceAccess>,
+}
+
+/// The access matrix of the target modules, ordered by resource and function.
+#[derive(Debug, Clone, Serialize)]
+pub struct AccessMatrix {
+    pub entries: Vec<AccessEntry>,
+}
+
+impl AccessMatrix {
+    /// Computes the access matrix of the functions of the target modules from their baseline
+    /// variants.
+    pub fn new(env: &GlobalEnv, targets: &FunctionTargetsHolder) -> Self {
+        let mut direct_accesses = BTreeMap::new();
+        let mut cells = BTreeMap::new();
+        for fun_env in env
+            .get_modules()
+            .filter(|m| m.is_target())
+            .flat_map(|m| m.into_functions())
+            .filter(|f| !f.is_native())
+        {
+            let fun_id = fun_env.get_qualified_id();
+            let direct = direct_accesses
+                .entry(fun_id)
+                .or_insert_with(|| direct_accesses_of(targets, &fun_env))
+                .clone();
+            let mut indirect: BTreeMap<_, BTreeSet<_>> = BTreeMap::new();
+            for callee_id in fun_env.get_transitive_closure_of_called_functions() {
+                let callee_accesses = direct_accesses
+                    .entry(callee_id)
+                    .or_insert_with(|| direct_accesses_of(targets, &env.get_function(callee_id)));
+                for (resource, accesses) in callee_accesses.iter() {
+                    indirect
+                        .entry(*resource)
+                        .or_default()
+                        .extend(accesses.iter().cloned());
+                }
+            }
+            let resources = direct
+                .keys()
+                .chain(indirect.keys())
+                .collect::<BTreeSet<_>>();
+            for resource in resources {
+                cells.insert(
+                    (*resource, fun_id),
+                    (
+                        direct.get(resource).cloned().unwrap_or_default(),
+                        indirect.get(resource).cloned().unwrap_or_default(),
+                    ),
+                );
+            }
+        }
+        let entries = cells
+            .into_iter()
+            .map(|((resource, fun_id), (direct, indirect))| {
+                let fun_env = env.get_function(fun_id);
+                let (visibility, callable_from) = callers_of(&fun_env);
+                AccessEntry {
+                    resource: env.get_struct(resource).get_full_name_with_address(),
+                    function: format!(
+                        "{}::{}",
+                        fun_env.module_env.get_full_name_str(),
+                        fun_env.get_simple_name_string()
+                    ),
+                    visibility: visibility.to_string(),
+                    callable_from,
+                    direct,
+                    indirect,
+                }
+            })
+            .collect();
+        Self { entries }
+    }
+
+    /// Returns the matrix in CSV, with one row per accessed resource and function. The access
+    /// columns are `direct`, `indirect`, or empty.
+    pub fn to_csv(&self) -> String {
+        let mut res =
+            "resource,function,visibility,callable_from,read,write,create,destroy\n".to_string();
+        for entry in &self.entries {
+            let accesses = ACCESSES
+                .iter()
+                .map(|access| {
+                    if entry.direct.contains(access) {
+                        "direct"
+                    } else if entry.indirect.contains(access) {
+                        "indirect"
+                    } else {
+                        ""
+                    }
+                })
+                .join(",");
+            res.push_str(&format!(
+                "{},{},{},{},{}\n",
+                entry.resource,
+                entry.function,
+                entry.visibility,
+                entry.callable_from.join(" "),
+                accesses
+            ));
+        }
+        res
+    }
+}
+
+/// Returns the accesses of the global storage operations of the function to the resources.
+fn direct_accesses_of(
+    targets: &FunctionTargetsHolder,
+    fun_env: &FunctionEnv,
+) -> BTreeMap<QualifiedId<StructId>, BTreeSet<ResourceAccess>> {
+    let mut accesses: BTreeMap<_, BTreeSet<_>> = BTreeMap::new();
+    if !targets.has_target(fun_env, &FunctionVariant::Baseline) {
+        return accesses;
+    }
+    let target = targets.get_target(fun_env, &FunctionVariant::Baseline);
+    for bc in target.get_bytecode() {
+        let (mid, sid, access) = match bc {
+            Bytecode::Call(_, _, Operation::Exists(mid, sid, _), _, _) => {
+                (mid, sid, ResourceAccess::Read)
+            }
+            Bytecode::Call(_, dests, Operation::BorrowGlobal(mid, sid, _), _, _) => {
+                if target.get_local_type(dests[0]).is_mutable_reference() {
+                    (mid, sid, ResourceAccess::Write)
+                } else {
+                    (mid, sid, ResourceAccess::Read)
+                }
+            }
+            Bytecode::Call(_, _, Operation::MoveTo(mid, sid, _), _, _) => {
+                (mid, sid, ResourceAccess::Create)
+            }
+            Bytecode::Call(_, _, Operation::MoveFrom(mid, sid, _), _, _) => {
+                (mid, sid, ResourceAccess::Destroy)
+            }
+            _ => continue,
+        };
+        accesses
+            .entry(mid.qualified(*sid))
+            .or_default()
+            .insert(access);
+    }
+    accesses
+}
+
+/// Returns the visibility of the function and the modules it can be called from.
+fn callers_of(fun_env: &FunctionEnv) -> (&'static str, Vec<String>) {
+    let module_env = &fun_env.module_env;
+    let env = module_env.env;
+    if module_env.is_script_module() {
+        return ("script", vec!["*".to_string()]);
+    }
+    match fun_env.visibility() {
+        Visibility::Public => ("public", vec!["*".to_string()]),
+        Visibility::Script => ("script", vec!["*".to_string()]),
+        Visibility::Friend => (
+            "friend",
+            std::iter::once(module_env.get_id())
+                .chain(module_env.get_friend_modules())
+                .map(|mid| env.get_module(mid).get_full_name_str())
+                .collect(),
+        ),
+        Visibility::Private => ("private", vec![module_env.get_full_name_str()]),
+    }
+}
@@ -22,16 +22,18 @@ pub struct Annotations {
 struct Data {
     value: Box<dyn Any>,
     clone_fun: Rc<dyn Fn(&Box<dyn Any>) -> Box<dyn Any>>,
+    fixedpoint: bool,
 }
 
 impl Data {
-    fn new<T: Any + Clone>(x: T) -> Self {
+    fn new<T: Any + Clone>(x: T, fixedpoint: bool) -> Self {
         let clone_fun = Rc::new(|x: &Box<dyn Any>| -> Box<dyn Any> {
             Box::new(x.downcast_ref::<T>().unwrap().clone())
         });
         Self {
             value: Box::new(x),
             clone_fun,
+            fixedpoint,
         }
     }
 }
@@ -41,6 +43,7 @@ impl Clone for Data {
         Self {
             value: (self.clone_fun)(&self.value),
             clone_fun: self.clone_fun.clone(),
+            fixedpoint: self.fixedpoint,
         }
     }
 }
@@ -73,7 +76,7 @@ impl Annotations {
         let id = TypeId::of::<T>();
         self.map
             .entry(id)
-            .or_insert_with(|| Data::new(T::default()))
+            .or_insert_with(|| Data::new(T::default(), true))
             .value
             .downcast_mut::<T>()
             .expect("cast successful")
@@ -82,7 +85,30 @@ impl Annotations {
     /// Sets annotation of type T.
     pub fn set<T: Any + Clone>(&mut self, x: T) {
         let id = TypeId::of::<T>();
-        self.map.insert(id, Data::new(x));
+        self.map.insert(id, Data::new(x, true));
+    }
+
+    /// Sets annotation of type T, recording whether it equals the annotation it replaces. This
+    /// is used by analyses which are iterated over recursive functions until their results are
+    /// stable.
+    pub fn set_with_fixedpoint_check<T: Any + Clone + PartialEq>(&mut self, x: T) {
+        let id = TypeId::of::<T>();
+        let fixedpoint = self.get::<T>() == Some(&x);
+        self.map.insert(id, Data::new(x, fixedpoint));
+    }
+
+    /// Returns true if none of the annotations set by `set_with_fixedpoint_check` has changed
+    /// with its last update.
+    pub fn reached_fixedpoint(&self) -> bool {
+        self.map.values().all(|d| d.fixedpoint)
+    }
+
+    /// Marks all annotations as stable, so that `reached_fixedpoint` only reflects the updates
+    /// which follow.
+    pub fn reset_fixedpoint(&mut self) {
+        for data in self.map.values_mut() {
+            data.fixedpoint = true
+        }
     }
 
     /// Removes annotation of type T.
@@ -2,6 +2,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 //! Data flow analysis computing borrow information for preparation of memory_instrumentation.
+//! The computed borrow graph can be explained via `BorrowAnnotation::explain`, which yields the
+//! chain of borrow edges leading to a node together with the instructions introducing them, and
+//! queried for the aliasing structure of references via `BorrowInfo::may_alias`.
 
 use crate::{
     dataflow_analysis::{DataflowAnalysis, TransferFunctions},
@@ -100,6 +103,91 @@ impl BorrowInfo {
         self.moved_nodes.contains(node)
     }
 
+    /// Returns the edges of the borrow graph, as triples of parent, child, and edge.
+    pub fn get_edges(&self) -> impl Iterator<Item = (&BorrowNode, &BorrowNode, &BorrowEdge)> {
+        self.borrowed_by.iter().flat_map(|(parent, children)| {
+            children
+                .iter()
+                .map(move |(child, edge)| (parent, child, edge))
+        })
+    }
+
+    /// Returns the access paths of a node. For each root the node transitively borrows from,
+    /// this is the root together with the edges on the path from the root to the node, in
+    /// root-first order. Hyper edges are flattened and direct edges are dropped, as the latter
+    /// do not select a part of the borrowed value. A node which does not borrow from another
+    /// node is its own root, with an empty path.
+    pub fn get_access_paths(&self, node: &BorrowNode) -> Vec<(BorrowNode, Vec<BorrowEdge>)> {
+        let mut paths = vec![];
+        self.collect_access_paths(node, &mut vec![], &mut BTreeSet::new(), &mut paths);
+        paths
+    }
+
+    fn collect_access_paths(
+        &self,
+        node: &BorrowNode,
+        suffix: &mut Vec<BorrowEdge>,
+        on_path: &mut BTreeSet<BorrowNode>,
+        paths: &mut Vec<(BorrowNode, Vec<BorrowEdge>)>,
+    ) {
+        let incoming = self.get_incoming(node);
+        if incoming.is_empty() {
+            paths.push((node.clone(), suffix.iter().rev().cloned().collect()));
+            return;
+        }
+        if !on_path.insert(node.clone()) {
+            return;
+        }
+        for (parent, edge) in incoming.iter() {
+            let len = suffix.len();
+            suffix.extend(
+                edge.flatten()
+                    .into_iter()
+                    .rev()
+                    .filter(|e| !matches!(e, BorrowEdge::Direct))
+                    .cloned(),
+            );
+            self.collect_access_paths(parent, suffix, on_path, paths);
+            suffix.truncate(len);
+        }
+        on_path.remove(node);
+    }
+
+    /// Returns true if the nodes may alias, that is, may refer to overlapping memory. This is
+    /// the case if they have access paths from the same root where one path is a prefix of the
+    /// other. Field edges of different fields select disjoint memory, whereas vector index edges
+    /// are considered to select the same element.
+    pub fn may_alias(&self, node1: &BorrowNode, node2: &BorrowNode) -> bool {
+        let paths2 = self.get_access_paths(node2);
+        self.get_access_paths(node1).iter().any(|(root1, path1)| {
+            paths2.iter().any(|(root2, path2)| {
+                root1 == root2
+                    && path1.iter().zip(path2).all(|(e1, e2)| {
+                        !matches!((e1, e2), (BorrowEdge::Field(..), BorrowEdge::Field(..)))
+                            || e1 == e2
+                    })
+            })
+        })
+    }
+
+    /// Returns the references which may alias each other, as pairs of temporaries where the
+    /// first is smaller than the second.
+    pub fn get_aliases(&self) -> BTreeSet<(TempIndex, TempIndex)> {
+        let refs = self
+            .borrowed_by
+            .keys()
+            .chain(self.borrows_from.keys())
+            .filter_map(|node| node.get_ref())
+            .collect::<BTreeSet<_>>();
+        refs.iter()
+            .tuple_combinations()
+            .filter(|(r1, r2)| {
+                self.may_alias(&BorrowNode::Reference(**r1), &BorrowNode::Reference(**r2))
+            })
+            .map(|(r1, r2)| (*r1, *r2))
+            .collect()
+    }
+
     /// Returns nodes which are dying from this to the next state. This includes those which
     /// are directly dying plus those from which they borrow. Returns nodes in child-first order.
     pub fn dying_nodes(&self, next: &BorrowInfo) -> Vec<BorrowNode> {
@@ -346,6 +434,87 @@ impl BorrowAnnotation {
     pub fn get_borrow_info_at(&self, code_offset: CodeOffset) -> Option<&BorrowInfoAtCodeOffset> {
         self.code_map.get(&code_offset)
     }
+
+    /// Returns true if the references in the given temporaries may alias before the
+    /// instruction at the given code offset. See `BorrowInfo::may_alias`.
+    pub fn may_alias_at(
+        &self,
+        code_offset: CodeOffset,
+        temp1: TempIndex,
+        temp2: TempIndex,
+    ) -> bool {
+        self.code_map
+            .get(&code_offset)
+            .map(|at| {
+                at.before
+                    .may_alias(&BorrowNode::Reference(temp1), &BorrowNode::Reference(temp2))
+            })
+            .unwrap_or(false)
+    }
+
+    /// Explains the borrow information of a node before the instruction at the given code
+    /// offset. Returns the edges on the chains from the roots the node borrows from to the node,
+    /// in root-first order, each with the instructions which introduce it.
+    pub fn explain(
+        &self,
+        code_offset: CodeOffset,
+        node: &BorrowNode,
+    ) -> Vec<BorrowExplanationStep> {
+        let info = match self.code_map.get(&code_offset) {
+            Some(at) => &at.before,
+            None => return vec![],
+        };
+        let mut steps = vec![];
+        let mut visited = BTreeSet::new();
+        let mut todo = vec![node.clone()];
+        while let Some(child) = todo.pop() {
+            if !visited.insert(child.clone()) {
+                continue;
+            }
+            for (parent, edge) in info.get_incoming(&child).iter() {
+                steps.push(BorrowExplanationStep {
+                    parent: parent.clone(),
+                    child: child.clone(),
+                    edge: edge.clone(),
+                    introduced_at: self.introduced_at(parent, &child, edge),
+                });
+                todo.push(parent.clone());
+            }
+        }
+        steps.reverse();
+        steps
+    }
+
+    /// Returns the code offsets of the instructions after which the edge exists, but not
+    /// before.
+    fn introduced_at(
+        &self,
+        parent: &BorrowNode,
+        child: &BorrowNode,
+        edge: &BorrowEdge,
+    ) -> Vec<CodeOffset> {
+        let has_edge = |info: &BorrowInfo| {
+            info.borrowed_by
+                .get(parent)
+                .map(|edges| edges.contains(&(child.clone(), edge.clone())))
+                .unwrap_or(false)
+        };
+        self.code_map
+            .iter()
+            .filter(|(_, at)| has_edge(&at.after) && !has_edge(&at.before))
+            .map(|(offset, _)| *offset)
+            .collect()
+    }
+}
+
+/// An edge of the borrow graph in the explanation of a node, together with the instructions
+/// which introduce it.
+#[derive(Debug, Clone)]
+pub struct BorrowExplanationStep {
+    pub parent: BorrowNode,
+    pub child: BorrowNode,
+    pub edge: BorrowEdge,
+    pub introduced_at: Vec<CodeOffset>,
 }
 
 /// Borrow analysis processor.
@@ -407,6 +576,43 @@ impl FunctionTargetProcessor for BorrowAnalysisProcessor {
                 }
             }
         }
+        // Explain the nodes which borrow conditionally from multiple parents, as those are
+        // handled conservatively by memory instrumentation.
+        writeln!(f, "\n==== borrow analysis explanations ====\n")?;
+        for ref module in env.get_modules() {
+            for ref fun in module.get_functions() {
+                for (_, ref target) in targets.get_targets(fun) {
+                    if let Some(an) = target.get_annotations().get::<BorrowAnnotation>() {
+                        let mut explained = BTreeSet::new();
+                        for (offset, at) in &an.code_map {
+                            for node in at.before.borrows_from.keys() {
+                                if at.before.get_parents(node).len() < 2
+                                    || !explained.insert(node.clone())
+                                {
+                                    continue;
+                                }
+                                writeln!(
+                                    f,
+                                    "fun {}[{}] at {}:",
+                                    fun.get_full_name_str(),
+                                    target.data.variant,
+                                    offset
+                                )?;
+                                writeln!(
+                                    f,
+                                    "{}\n",
+                                    format_borrow_explanation(
+                                        target,
+                                        node,
+                                        &an.explain(*offset, node)
+                                    )
+                                )?;
+                            }
+                        }
+                    }
+                }
+            }
+        }
         Ok(())
     }
 }
@@ -582,16 +788,42 @@ impl<'a> TransferFunctions for BorrowAnalysis<'a> {
                             BorrowEdge::Field(mid.qualified_inst(*sid, inst.to_owned()), *field),
                         );
                     }
+                    BorrowVariantField(mid, sid, _, inst, field)
+                        if livevar_annotation_at.after.contains(&dests[0]) =>
+                    {
+                        // Fields of different variants at the same offset share the edge, which
+                        // is conservative, as a value is only of one variant.
+                        let dest_node = self.borrow_node(dests[0]);
+                        let src_node = self.borrow_node(srcs[0]);
+                        state.add_node(dest_node.clone());
+                        state.add_edge(
+                            src_node,
+                            dest_node,
+                            BorrowEdge::Field(mid.qualified_inst(*sid, inst.to_owned()), *field),
+                        );
+                    }
+                    VecBorrowMut(_) if livevar_annotation_at.after.contains(&dests[0]) => {
+                        let dest_node = self.borrow_node(dests[0]);
+                        let src_node = self.borrow_node(srcs[0]);
+                        state.add_node(dest_node.clone());
+                        state.add_edge(src_node, dest_node, BorrowEdge::Index);
+                    }
                     Function(mid, fid, targs) => {
                         let callee_env = &self
                             .func_target
                             .global_env()
                             .get_function_qid(mid.qualified(*fid));
-                        let callee_target = &self
+                        // The data of the function being analyzed is not available for a
+                        // direct recursive call.
+                        let callee_target = self
                             .targets
-                            .get_target(callee_env, &FunctionVariant::Baseline);
-                        if let Some(callee_an) =
-                            callee_target.get_annotations().get::<BorrowAnnotation>()
+                            .get_data(&callee_env.get_qualified_id(), &FunctionVariant::Baseline)
+                            .map(|data| FunctionTarget::new(callee_env, data));
+                        if let Some((callee_target, callee_an)) =
+                            callee_target.as_ref().and_then(|target| {
+                                let an = target.get_annotations().get::<BorrowAnnotation>()?;
+                                Some((target, an))
+                            })
                         {
                             state.instantiate(
                                 callee_target,
@@ -604,11 +836,10 @@ impl<'a> TransferFunctions for BorrowAnalysis<'a> {
                             // This can happen for recursive functions. Check whether the function
                             // has &mut returns, and report an error that we can't deal with it if
                             // so.
-                            let has_muts = (0..callee_target.get_return_count()).any(|idx| {
-                                callee_target.get_return_type(idx).is_mutable_reference()
-                            });
+                            let has_muts = (0..callee_env.get_return_count())
+                                .any(|idx| callee_env.get_return_type(idx).is_mutable_reference());
                             if has_muts {
-                                callee_target.global_env().error(&self.func_target.get_bytecode_loc(*id),
+                                callee_env.module_env.env.error(&self.func_target.get_bytecode_loc(*id),
                                     "restriction: recursive functions which return `&mut` values not supported");
                             }
                         }
@@ -672,3 +903,36 @@ pub fn format_borrow_annotation(
     }
     None
 }
+
+/// Formats the explanation of the borrow information of a node, as returned by
+/// `BorrowAnnotation::explain`, with the source locations of the instructions.
+pub fn format_borrow_explanation(
+    func_target: &FunctionTarget<'_>,
+    node: &BorrowNode,
+    steps: &[BorrowExplanationStep],
+) -> String {
+    let env = func_target.global_env();
+    let code = func_target.get_bytecode();
+    let label_offsets = Bytecode::label_offsets(code);
+    let mut lines = vec![format!("{} borrows via:", node.display(func_target))];
+    for step in steps {
+        lines.push(format!(
+            "  {} -> {} ({})",
+            step.parent.display(func_target),
+            step.child.display(func_target),
+            step.edge.display(env)
+        ));
+        for offset in &step.introduced_at {
+            let bc = &code[*offset as usize];
+            lines.push(format!(
+                "    {}: {} {}",
+                offset,
+                bc.display(func_target, &label_offsets),
+                func_target
+                    .get_bytecode_loc(bc.get_attr_id())
+                    .display_line_only(env)
+            ));
+        }
+    }
+    lines.join("\n")
+}
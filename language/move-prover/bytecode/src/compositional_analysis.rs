@@ -24,27 +24,21 @@ impl<'a> SummaryCache<'a> {
         }
     }
 
-    /// Return a summary for a variant of `fun_id`. Returns None if `fun_id` is a native function
+    /// Return a summary for a variant of `fun_id`. Returns None if `fun_id` is a native function,
+    /// or if its summary has not been computed yet, which happens for a callee in the same group
+    /// of recursive functions. The pipeline processes such a group until the summaries are stable.
     pub fn get<Summary: 'static>(
         &self,
         fun_id: QualifiedId<FunId>,
         variant: &FunctionVariant,
     ) -> Option<&Summary> {
         let fun_env = self.global_env.get_function(fun_id);
+        if fun_env.is_native_or_intrinsic() {
+            return None;
+        }
         self.targets
             .get_data(&fun_id, variant)
-            .map(|fun_data| {
-                if fun_env.is_native_or_intrinsic() {
-                    None
-                } else {
-                    Some(
-                        fun_data.annotations.get::<Summary>().expect(
-                            "Failed to resolve summary; recursion or scheduler bug suspected",
-                        ),
-                    )
-                }
-            })
-            .flatten()
+            .and_then(|fun_data| fun_data.annotations.get::<Summary>())
     }
 
     pub fn global_env(&self) -> &GlobalEnv {
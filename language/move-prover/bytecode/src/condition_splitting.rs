@@ -0,0 +1,245 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Splitting of the verification condition of a function into independent parts.
+//!
+//! Two assertions of a verification variant depend on each other if they refer to a common
+//! temporary or to common global memory: as an assertion is assumed to hold once it has been
+//! checked, one of them may be needed to prove the other. The `ConditionSplittingProcessor`
+//! partitions the assertions into groups which are closed under this relation. If there is
+//! more than one group, the original variant keeps the assertions of the first group, and for
+//! each other group a new verification variant is created which only keeps the assertions of
+//! that group. Assumptions and code are the same in all variants. The resulting verification
+//! conditions are smaller than the original one and can be solved in parallel.
+//!
+//! The groups and the dependencies between assertions are attached to the original variant as
+//! a `ConditionGroups` annotation, which is rendered by `condition_dependencies_report`.
+
+use crate::{
+    function_target::{FunctionData, FunctionTarget},
+    function_target_pipeline::{
+        FunctionTargetProcessor, FunctionTargetsHolder, FunctionVariant, VerificationFlavor,
+    },
+    stackless_bytecode::{AttrId, Bytecode, PropKind},
+};
+use move_model::{
+    ast::{Exp, TempIndex},
+    model::{FunctionEnv, GlobalEnv, QualifiedId, StructId},
+};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Something an assertion refers to, by which it can depend on other assertions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ConditionFootprint {
+    Temporary(TempIndex),
+    /// Global memory, regardless of its instantiation.
+    Memory(QualifiedId<StructId>),
+}
+
+/// The independent groups of assertions of a verification variant.
+#[derive(Debug, Clone, Default)]
+pub struct ConditionGroups {
+    /// The attribute ids of the assertions of each group, in code order. The first group is
+    /// verified by the original variant, group `i > 0` by the variant with flavor
+    /// `VerificationFlavor::Split(_, i)`.
+    pub groups: Vec<Vec<AttrId>>,
+    /// The pairs of assertions which depend on each other directly, together with what they
+    /// both refer to.
+    pub dependencies: Vec<(AttrId, AttrId, BTreeSet<ConditionFootprint>)>,
+}
+
+pub struct ConditionSplittingProcessor {}
+
+impl ConditionSplittingProcessor {
+    pub fn new() -> Box<Self> {
+        Box::new(Self {})
+    }
+}
+
+impl FunctionTargetProcessor for ConditionSplittingProcessor {
+    fn process(
+        &self,
+        targets: &mut FunctionTargetsHolder,
+        fun_env: &FunctionEnv<'_>,
+        mut data: FunctionData,
+    ) -> FunctionData {
+        if fun_env.is_native() || fun_env.is_intrinsic() {
+            // Nothing to do.
+            return data;
+        }
+        let flavor = match &data.variant {
+            FunctionVariant::Verification(
+                flavor @ VerificationFlavor::Regular | flavor @ VerificationFlavor::Instantiated(_),
+            ) => flavor.clone(),
+            _ => {
+                // splitting only applies to regular verification variants
+                return data;
+            }
+        };
+
+        let groups = Self::compute_groups(fun_env.module_env.env, &data);
+        if groups.groups.len() > 1 {
+            for (idx, group) in groups.groups.iter().enumerate().skip(1) {
+                let mut new_data = data.fork(FunctionVariant::Verification(
+                    VerificationFlavor::Split(Box::new(flavor.clone()), idx),
+                ));
+                Self::retain_assertions(&mut new_data, group);
+                targets.insert_target_data(
+                    &fun_env.get_qualified_id(),
+                    new_data.variant.clone(),
+                    new_data,
+                );
+            }
+            Self::retain_assertions(&mut data, &groups.groups[0]);
+        }
+        data.annotations.set(groups);
+        data
+    }
+
+    fn name(&self) -> String {
+        "condition_splitting".to_string()
+    }
+}
+
+impl ConditionSplittingProcessor {
+    /// Partitions the assertions of the function into groups of assertions which depend on each
+    /// other, directly or transitively.
+    fn compute_groups(env: &GlobalEnv, data: &FunctionData) -> ConditionGroups {
+        let assertions = data
+            .code
+            .iter()
+            .filter_map(|bc| match bc {
+                Bytecode::Prop(id, PropKind::Assert, exp) => Some((*id, Self::footprint(env, exp))),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        // Union-find over the indices of the assertions.
+        let mut parent = (0..assertions.len()).collect::<Vec<_>>();
+        fn find(parent: &mut [usize], idx: usize) -> usize {
+            let mut root = idx;
+            while parent[root] != root {
+                root = parent[root];
+            }
+            parent[idx] = root;
+            root
+        }
+        let mut dependencies = vec![];
+        for (i, (id1, footprint1)) in assertions.iter().enumerate() {
+            for (j, (id2, footprint2)) in assertions.iter().enumerate().skip(i + 1) {
+                let shared = footprint1
+                    .intersection(footprint2)
+                    .cloned()
+                    .collect::<BTreeSet<_>>();
+                if !shared.is_empty() {
+                    dependencies.push((*id1, *id2, shared));
+                    let (root1, root2) = (find(&mut parent, i), find(&mut parent, j));
+                    parent[root2.max(root1)] = root1.min(root2);
+                }
+            }
+        }
+
+        // Groups are ordered by their first assertion.
+        let mut group_of_root = BTreeMap::new();
+        let mut groups: Vec<Vec<AttrId>> = vec![];
+        for (idx, (id, _)) in assertions.iter().enumerate() {
+            let root = find(&mut parent, idx);
+            let group = *group_of_root.entry(root).or_insert_with(|| {
+                groups.push(vec![]);
+                groups.len() - 1
+            });
+            groups[group].push(*id);
+        }
+        ConditionGroups {
+            groups,
+            dependencies,
+        }
+    }
+
+    /// Returns the temporaries and memory an expression refers to.
+    fn footprint(env: &GlobalEnv, exp: &Exp) -> BTreeSet<ConditionFootprint> {
+        let temps = exp
+            .used_temporaries(env)
+            .into_iter()
+            .map(|(idx, _)| ConditionFootprint::Temporary(idx));
+        let memory = exp
+            .used_memory(env)
+            .into_iter()
+            .map(|(mem, _)| ConditionFootprint::Memory(mem.to_qualified_id()));
+        temps.chain(memory).collect()
+    }
+
+    /// Removes all assertions from the code which are not in the given group.
+    fn retain_assertions(data: &mut FunctionData, group: &[AttrId]) {
+        data.code.retain(|bc| match bc {
+            Bytecode::Prop(id, PropKind::Assert, _) => group.contains(id),
+            _ => true,
+        });
+    }
+}
+
+/// Returns a report of the independent groups of the conditions of the verified functions in
+/// the target modules, and of the dependencies between the conditions.
+pub fn condition_dependencies_report(env: &GlobalEnv, targets: &FunctionTargetsHolder) -> String {
+    let mut res = String::new();
+    for (fun_id, variant) in targets.get_funs_and_variants() {
+        let fun_env = env.get_function(fun_id);
+        let target = targets.get_target(&fun_env, &variant);
+        let groups = match target.get_annotations().get::<ConditionGroups>() {
+            Some(groups) if !groups.groups.is_empty() => groups,
+            _ => continue,
+        };
+        res.push_str(&format!(
+            "fun {} [{}]: {} condition(s) in {} independent group(s)\n",
+            fun_env.get_full_name_str(),
+            variant,
+            groups.groups.iter().map(|g| g.len()).sum::<usize>(),
+            groups.groups.len()
+        ));
+        for (idx, group) in groups.groups.iter().enumerate() {
+            res.push_str(&format!("  group {}:\n", idx));
+            for id in group {
+                res.push_str(&format!("    {}\n", describe_condition(&target, *id)));
+            }
+        }
+        for (id1, id2, shared) in &groups.dependencies {
+            res.push_str(&format!(
+                "  {} depends on {} via {}\n",
+                target.get_bytecode_loc(*id2).display(env),
+                target.get_bytecode_loc(*id1).display(env),
+                shared
+                    .iter()
+                    .map(|fp| describe_footprint(&target, fp))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        res.push('\n');
+    }
+    res
+}
+
+fn describe_condition(target: &FunctionTarget<'_>, id: AttrId) -> String {
+    let env = target.global_env();
+    match target.get_vc_info(id) {
+        Some(info) => format!("{} ({})", target.get_bytecode_loc(id).display(env), info),
+        None => target.get_bytecode_loc(id).display(env).to_string(),
+    }
+}
+
+fn describe_footprint(target: &FunctionTarget<'_>, footprint: &ConditionFootprint) -> String {
+    let env = target.global_env();
+    match footprint {
+        ConditionFootprint::Temporary(idx) => match target.get_temp_description(*idx) {
+            Some(desc) => desc.to_string(),
+            None if target.has_local_user_name(*idx) => target
+                .get_local_name(*idx)
+                .display(env.symbol_pool())
+                .to_string(),
+            None => format!("$t{}", idx),
+        },
+        ConditionFootprint::Memory(mem) => {
+            format!("global<{}>", env.get_struct(*mem).get_full_name_str())
+        }
+    }
+}
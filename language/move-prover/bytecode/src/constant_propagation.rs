@@ -0,0 +1,343 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+// Constant propagation and folding.
+//
+// This computes for each code offset which temporaries hold a known constant, and uses it to
+// replace operations on constants by loads of their result, and branches on constant conditions
+// by jumps. Operations which would abort for the given constants, e.g. because of an overflow,
+// are not folded. Temporaries which are borrowed are never considered constant, since they may
+// be modified through a reference. The branches made unreachable by this transformation, as
+// well as the definitions of operands which are no longer used, are removed by the subsequent
+// dead_code_elimination.
+
+use crate::{
+    dataflow_analysis::{DataflowAnalysis, TransferFunctions},
+    dataflow_domains::{AbstractDomain, JoinResult},
+    function_target::{FunctionData, FunctionTarget},
+    function_target_pipeline::{FunctionTargetProcessor, FunctionTargetsHolder},
+    stackless_bytecode::{Bytecode, Constant, HavocKind, Operation},
+};
+use ethnum::U256;
+use itertools::Itertools;
+use move_binary_format::file_format::CodeOffset;
+use move_model::{ast::TempIndex, model::FunctionEnv};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The abstract value of a temporary: either a known constant, or not constant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConstantValue {
+    Const(Constant),
+    NonConst,
+}
+
+/// The state of constant propagation, mapping temporaries to their abstract values.
+/// Temporaries which are not yet defined are not in the map.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConstantState(BTreeMap<TempIndex, ConstantValue>);
+
+impl ConstantState {
+    /// Returns the constant the temporary holds, if known.
+    pub fn get_constant(&self, temp: TempIndex) -> Option<&Constant> {
+        match self.0.get(&temp) {
+            Some(ConstantValue::Const(c)) => Some(c),
+            _ => None,
+        }
+    }
+}
+
+/// The annotation for constant propagation, mapping each code offset to the state before the
+/// instruction at the offset.
+#[derive(Debug, Default, Clone)]
+pub struct ConstantAnnotation(BTreeMap<CodeOffset, ConstantState>);
+
+impl ConstantAnnotation {
+    pub fn get_state_at(&self, code_offset: CodeOffset) -> Option<&ConstantState> {
+        self.0.get(&code_offset)
+    }
+}
+
+pub struct ConstantPropagationProcessor {}
+
+impl ConstantPropagationProcessor {
+    pub fn new() -> Box<Self> {
+        Box::new(ConstantPropagationProcessor {})
+    }
+
+    /// Computes the constants held by the temporaries of the function target in the given
+    /// code.
+    pub fn analyze(func_target: &FunctionTarget, code: &[Bytecode]) -> ConstantAnnotation {
+        let borrowed = code
+            .iter()
+            .filter_map(|bc| match bc {
+                Bytecode::Call(_, _, Operation::BorrowLoc, srcs, _) => Some(srcs[0]),
+                _ => None,
+            })
+            .collect();
+        let analyzer = ConstantAnalysis {
+            func_target,
+            borrowed,
+        };
+        let initial_state = ConstantState(
+            (0..func_target.get_parameter_count())
+                .map(|temp| (temp, ConstantValue::NonConst))
+                .collect(),
+        );
+        ConstantAnnotation(analyzer.analyze_code(initial_state, code, |before, _| before.clone()))
+    }
+
+    /// Folds operations on constants and branches on constant conditions.
+    fn fold(code: Vec<Bytecode>, annotation: &ConstantAnnotation) -> Vec<Bytecode> {
+        code.into_iter()
+            .enumerate()
+            .map(|(offset, bc)| {
+                let state = match annotation.get_state_at(offset as CodeOffset) {
+                    Some(state) => state,
+                    // The instruction is not reachable.
+                    None => return bc,
+                };
+                match bc {
+                    Bytecode::Call(attr_id, dests, oper, srcs, aa) => {
+                        match eval_operation(state, &dests, &oper, &srcs) {
+                            Some(value) => Bytecode::Load(attr_id, dests[0], value),
+                            None => Bytecode::Call(attr_id, dests, oper, srcs, aa),
+                        }
+                    }
+                    Bytecode::Branch(attr_id, then_label, else_label, cond) => {
+                        match state.get_constant(cond) {
+                            Some(Constant::Bool(true)) => Bytecode::Jump(attr_id, then_label),
+                            Some(Constant::Bool(false)) => Bytecode::Jump(attr_id, else_label),
+                            _ => Bytecode::Branch(attr_id, then_label, else_label, cond),
+                        }
+                    }
+                    _ => bc,
+                }
+            })
+            .collect()
+    }
+}
+
+impl FunctionTargetProcessor for ConstantPropagationProcessor {
+    fn process(
+        &self,
+        _targets: &mut FunctionTargetsHolder,
+        func_env: &FunctionEnv<'_>,
+        mut data: FunctionData,
+    ) -> FunctionData {
+        if func_env.is_native() {
+            // Native functions have no byte code.
+            return data;
+        }
+        let code = std::mem::take(&mut data.code);
+        let func_target = FunctionTarget::new(func_env, &data);
+        let annotation = Self::analyze(&func_target, &code);
+        data.code = Self::fold(code, &annotation);
+        // Folding replaces instructions one by one, so the annotation remains valid.
+        data.annotations.set(annotation);
+        data
+    }
+
+    fn name(&self) -> String {
+        "constant_propagation".to_string()
+    }
+}
+
+struct ConstantAnalysis<'a> {
+    func_target: &'a FunctionTarget<'a>,
+    borrowed: BTreeSet<TempIndex>,
+}
+
+impl<'a> ConstantAnalysis<'a> {
+    fn set(&self, state: &mut ConstantState, temp: TempIndex, value: ConstantValue) {
+        let value = if self.borrowed.contains(&temp) {
+            ConstantValue::NonConst
+        } else {
+            value
+        };
+        state.0.insert(temp, value);
+    }
+}
+
+impl<'a> TransferFunctions for ConstantAnalysis<'a> {
+    type State = ConstantState;
+    const BACKWARD: bool = false;
+
+    fn execute(&self, state: &mut ConstantState, instr: &Bytecode, _offset: CodeOffset) {
+        use Bytecode::*;
+        match instr {
+            Load(_, dest, value) => self.set(state, *dest, ConstantValue::Const(value.clone())),
+            Assign(_, dest, src, _) if !self.func_target.get_local_type(*dest).is_reference() => {
+                let value = state.0.get(src).cloned().unwrap_or(ConstantValue::NonConst);
+                self.set(state, *dest, value)
+            }
+            Call(_, dests, oper, srcs, _) if eval_operation(state, dests, oper, srcs).is_some() => {
+                let value = eval_operation(state, dests, oper, srcs).unwrap();
+                self.set(state, dests[0], ConstantValue::Const(value))
+            }
+            _ => {
+                let (val_targets, mut_targets) = instr.modifies(self.func_target);
+                for temp in val_targets
+                    .into_iter()
+                    .chain(mut_targets.into_iter().map(|(temp, _)| temp))
+                {
+                    self.set(state, temp, ConstantValue::NonConst)
+                }
+                if let Call(_, _, Operation::Havoc(HavocKind::Value), srcs, _) = instr {
+                    self.set(state, srcs[0], ConstantValue::NonConst)
+                }
+            }
+        }
+    }
+}
+
+impl<'a> DataflowAnalysis for ConstantAnalysis<'a> {}
+
+impl AbstractDomain for ConstantState {
+    fn join(&mut self, other: &Self) -> JoinResult {
+        let mut result = JoinResult::Unchanged;
+        for (temp, other_value) in &other.0 {
+            match self.0.get(temp) {
+                None => {
+                    self.0.insert(*temp, other_value.clone());
+                    result = JoinResult::Changed;
+                }
+                Some(value) if value != other_value && *value != ConstantValue::NonConst => {
+                    self.0.insert(*temp, ConstantValue::NonConst);
+                    result = JoinResult::Changed;
+                }
+                _ => {}
+            }
+        }
+        result
+    }
+}
+
+// =================================================================================================
+// Evaluation
+
+/// Evaluates an operation on constants. Returns None if the operation cannot be folded, in
+/// particular if some operand is not constant or the operation would abort.
+fn eval_operation(
+    state: &ConstantState,
+    dests: &[TempIndex],
+    oper: &Operation,
+    srcs: &[TempIndex],
+) -> Option<Constant> {
+    use Constant::Bool;
+    use Operation::*;
+    if dests.len() != 1 {
+        return None;
+    }
+    let args = srcs
+        .iter()
+        .map(|temp| state.get_constant(*temp))
+        .collect::<Option<Vec<_>>>()?;
+    match (oper, args.as_slice()) {
+        (Not, [Bool(x)]) => Some(Bool(!x)),
+        (And, [Bool(x), Bool(y)]) => Some(Bool(*x && *y)),
+        (Or, [Bool(x), Bool(y)]) => Some(Bool(*x || *y)),
+        (Eq, [x, y]) => Some(Bool(x == y)),
+        (Neq, [x, y]) => Some(Bool(x != y)),
+        (CastU8, [x]) => from_int(8, as_int(x)?.1),
+        (CastU64, [x]) => from_int(64, as_int(x)?.1),
+        (CastU128, [x]) => from_int(128, as_int(x)?.1),
+        (CastU256, [x]) => from_int(256, as_int(x)?.1),
+        (Shl, [x, y]) | (Shr, [x, y]) => {
+            let (bits, x) = as_int(x)?;
+            let (_, y) = as_int(y)?;
+            if y >= U256::from(bits) {
+                // The shift aborts.
+                return None;
+            }
+            let shift = y.as_u32();
+            let res = if matches!(oper, Shl) {
+                (x << shift) & max_int(bits)
+            } else {
+                x >> shift
+            };
+            from_int(bits, res)
+        }
+        (_, [x, y]) => {
+            let (bits, x) = as_int(x)?;
+            let (_, y) = as_int(y)?;
+            match oper {
+                Add => from_int(bits, x.checked_add(y)?),
+                Sub => from_int(bits, x.checked_sub(y)?),
+                Mul => from_int(bits, x.checked_mul(y)?),
+                Div => from_int(bits, x.checked_div(y)?),
+                Mod => from_int(bits, x.checked_rem(y)?),
+                BitOr => from_int(bits, x | y),
+                BitAnd => from_int(bits, x & y),
+                Xor => from_int(bits, x ^ y),
+                Lt => Some(Bool(x < y)),
+                Gt => Some(Bool(x > y)),
+                Le => Some(Bool(x <= y)),
+                Ge => Some(Bool(x >= y)),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Returns the bit width and value of an integer constant.
+fn as_int(c: &Constant) -> Option<(u32, U256)> {
+    match c {
+        Constant::U8(x) => Some((8, U256::from(*x))),
+        Constant::U64(x) => Some((64, U256::from(*x))),
+        Constant::U128(x) => Some((128, U256::from(*x))),
+        Constant::U256(x) => Some((256, *x)),
+        _ => None,
+    }
+}
+
+/// Returns the largest integer of the bit width.
+fn max_int(bits: u32) -> U256 {
+    if bits == 256 {
+        U256::MAX
+    } else {
+        (U256::ONE << bits) - U256::ONE
+    }
+}
+
+/// Returns an integer constant of the bit width, or None if the value is out of range.
+fn from_int(bits: u32, value: U256) -> Option<Constant> {
+    if value > max_int(bits) {
+        return None;
+    }
+    Some(match bits {
+        8 => Constant::U8(value.as_u8()),
+        64 => Constant::U64(value.as_u64()),
+        128 => Constant::U128(value.as_u128()),
+        _ => Constant::U256(value),
+    })
+}
+
+// =================================================================================================
+// Formatting
+
+/// Format a constant annotation, showing the temporaries with known constants.
+pub fn format_constant_annotation(
+    target: &FunctionTarget<'_>,
+    code_offset: CodeOffset,
+) -> Option<String> {
+    let ConstantAnnotation(map) = target.get_annotations().get::<ConstantAnnotation>()?;
+    let state = map.get(&code_offset)?;
+    let constants = state
+        .0
+        .iter()
+        .filter_map(|(temp, value)| match value {
+            ConstantValue::Const(c) => Some(format!(
+                "{} = {}",
+                target.get_local_name(*temp).display(target.symbol_pool()),
+                c
+            )),
+            ConstantValue::NonConst => None,
+        })
+        .join(", ");
+    if constants.is_empty() {
+        None
+    } else {
+        Some(format!("constants: {}", constants))
+    }
+}
@@ -9,6 +9,12 @@
 //! output for processing by the backend, in case type assumptions needed to be added by the backend
 //! (which depends on the compilation scheme). It also handles PackRef/PackRefDeep
 //! instructions introduced by memory instrumentation, as well as the Pack instructions.
+//!
+//! In code instrumented for verification, the invariants of a struct, i.e. the `invariant`
+//! conditions of its spec, are asserted right after each `Pack` of the struct, and at each
+//! `PackRef`/`PackRefDeep`, which the memory instrumentation places where a `&mut` borrow of a
+//! struct ends. While a value is mutably borrowed, its invariant may therefore be temporarily
+//! violated. `PackRefDeep` asserts the invariants of the nested structs as well.
 
 use crate::{
     function_data_builder::FunctionDataBuilder,
@@ -3,6 +3,11 @@
 
 //! Adapted from AbstractInterpreter for Bytecode, this module defines the data-flow analysis
 //! framework for stackless bytecode.
+//!
+//! An analysis defines its abstract domain by implementing `AbstractDomain`, and its transfer
+//! function per instruction and direction by implementing `TransferFunctions`. Implementing the
+//! marker trait `DataflowAnalysis` then provides the fixpoint computation over the control flow
+//! graph, usually invoked via `analyze_code`.
 
 use crate::{
     dataflow_domains::{AbstractDomain, JoinResult},
@@ -113,6 +118,28 @@ pub trait DataflowAnalysis: TransferFunctions {
         state_map
     }
 
+    /// Runs the analysis on the code and maps the before/after state of each instruction to a
+    /// derived state `A`, as `state_per_instruction` does. The control flow graph is constructed
+    /// in the direction of the analysis. Backward analyses start from all blocks, so that blocks
+    /// which cannot reach an exit, like infinite loops, are analyzed as well.
+    fn analyze_code<A, F>(
+        &self,
+        initial_state: Self::State,
+        instrs: &[Bytecode],
+        f: F,
+    ) -> BTreeMap<CodeOffset, A>
+    where
+        F: FnMut(&Self::State, &Self::State) -> A,
+    {
+        let cfg = if Self::BACKWARD {
+            StacklessControlFlowGraph::new_backward(instrs, true)
+        } else {
+            StacklessControlFlowGraph::new_forward(instrs)
+        };
+        let state_map = self.analyze_function(initial_state, instrs, &cfg);
+        self.state_per_instruction(state_map, instrs, &cfg, f)
+    }
+
     /// Takes the StateMap resulting from `analyze_function` and converts it into a map
     /// from each code offset into a derived state `A`. This re-executes the analysis for
     /// each instruction within a basic block to reconstruct the intermediate results
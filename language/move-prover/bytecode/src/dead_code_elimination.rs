@@ -0,0 +1,147 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+// Dead code elimination.
+//
+// This removes blocks which are not reachable from the function entry, destroys of values, and
+// instructions whose only effect is to define temporaries which are dead afterwards. An
+// instruction is only removed if it cannot abort and neither reads nor produces references, so
+// the removal does not change the abort behavior of the function or the lifetime of borrows.
+// Since removing an instruction may render the definitions of its operands dead, elimination is
+// repeated until nothing changes.
+//
+// The copy propagation of reaching_def_analysis leaves many dead assignments behind; running
+// this pass after it shrinks the code which later phases instrument and translate.
+
+use crate::{
+    function_target::{FunctionData, FunctionTarget},
+    function_target_pipeline::{FunctionTargetProcessor, FunctionTargetsHolder},
+    liveness_analysis::{LivenessAnalysisProcessor, LivenessAnnotation},
+    stackless_bytecode::{Bytecode, Operation},
+    stackless_control_flow_graph::StacklessControlFlowGraph,
+};
+use move_binary_format::file_format::CodeOffset;
+use move_model::model::FunctionEnv;
+use std::collections::BTreeSet;
+
+pub struct DeadCodeEliminationProcessor {}
+
+impl DeadCodeEliminationProcessor {
+    pub fn new() -> Box<Self> {
+        Box::new(DeadCodeEliminationProcessor {})
+    }
+}
+
+impl FunctionTargetProcessor for DeadCodeEliminationProcessor {
+    fn process(
+        &self,
+        _targets: &mut FunctionTargetsHolder,
+        func_env: &FunctionEnv<'_>,
+        mut data: FunctionData,
+    ) -> FunctionData {
+        if func_env.is_native() {
+            // Native functions have no byte code.
+            return data;
+        }
+        let code = std::mem::take(&mut data.code);
+        let func_target = FunctionTarget::new(func_env, &data);
+        let mut code = Self::eliminate_unreachable_code(code);
+        loop {
+            let live_vars = LivenessAnalysisProcessor::analyze(&func_target, &code);
+            let old_len = code.len();
+            code = Self::eliminate_dead_definitions(&func_target, &live_vars, code);
+            if code.len() == old_len {
+                break;
+            }
+        }
+        data.code = code;
+        data
+    }
+
+    fn name(&self) -> String {
+        "dead_code_elimination".to_string()
+    }
+}
+
+impl DeadCodeEliminationProcessor {
+    /// Removes the instructions of blocks which are not reachable from the entry block.
+    fn eliminate_unreachable_code(code: Vec<Bytecode>) -> Vec<Bytecode> {
+        let cfg = StacklessControlFlowGraph::new_forward(&code);
+        let mut reachable = BTreeSet::new();
+        let mut todo = vec![cfg.entry_block()];
+        while let Some(block_id) = todo.pop() {
+            if reachable.insert(block_id) {
+                todo.extend(cfg.successors(block_id).iter().cloned());
+            }
+        }
+        let reachable_offsets = reachable
+            .into_iter()
+            .filter_map(|block_id| cfg.instr_indexes(block_id))
+            .flatten()
+            .collect::<BTreeSet<_>>();
+        code.into_iter()
+            .enumerate()
+            .filter(|(offset, _)| reachable_offsets.contains(&(*offset as CodeOffset)))
+            .map(|(_, bc)| bc)
+            .collect()
+    }
+
+    /// Removes the instructions which only define temporaries which are dead afterwards.
+    fn eliminate_dead_definitions(
+        func_target: &FunctionTarget,
+        live_vars: &LivenessAnnotation,
+        code: Vec<Bytecode>,
+    ) -> Vec<Bytecode> {
+        let is_dead = |offset: usize, temps: &[usize]| {
+            temps.iter().all(|temp| {
+                !live_vars.is_live_after(offset as CodeOffset, *temp)
+                    && !func_target.get_local_type(*temp).is_reference()
+            })
+        };
+        code.into_iter()
+            .enumerate()
+            .filter(|(offset, bc)| match bc {
+                Bytecode::Assign(_, dest, _, _) | Bytecode::Load(_, dest, _) => {
+                    !is_dead(*offset, &[*dest])
+                }
+                // Destroying a value has no effect; it only marks the end of its lifetime.
+                Bytecode::Call(_, _, Operation::Destroy, srcs, _) => {
+                    func_target.get_local_type(srcs[0]).is_reference()
+                }
+                Bytecode::Call(_, dests, oper, srcs, None) if Self::is_pure(oper) => {
+                    dests.is_empty()
+                        || !is_dead(*offset, dests)
+                        || srcs
+                            .iter()
+                            .any(|src| func_target.get_local_type(*src).is_reference())
+                }
+                _ => true,
+            })
+            .map(|(_, bc)| bc)
+            .collect()
+    }
+
+    /// Returns true if the operation has no effect besides defining its destinations. Packing
+    /// is not considered pure, since data invariants are checked when a struct is packed.
+    fn is_pure(oper: &Operation) -> bool {
+        use Operation::*;
+        matches!(
+            oper,
+            Not | BitOr
+                | BitAnd
+                | Xor
+                | Shl
+                | Shr
+                | Lt
+                | Gt
+                | Le
+                | Ge
+                | Or
+                | And
+                | Eq
+                | Neq
+                | Exists(..)
+                | GetField(..)
+        )
+    }
+}
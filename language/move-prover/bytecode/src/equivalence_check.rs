@@ -0,0 +1,312 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Instrument checks that two versions of a function are equivalent.
+//!
+//! A function can be checked to agree with another function of the same signature, e.g. a
+//! refactored arithmetic function with its original version, by registering the pair in
+//! `ProverOptions::equivalence_checks`. For each such function which is verified, the
+//! `EquivalenceCheckInstrumenter` creates a verification variant which keeps the assumptions
+//! the regular variant makes at entry, including the preconditions, calls both functions with
+//! the parameters, and asserts that either both return the same results, or both abort with the
+//! same code. If one of the assertions fails, the execution trace of the variant shows the
+//! parameters for which the functions diverge.
+//!
+//! Both functions are called with their implementations, which the verification analysis keeps
+//! for them. The functions must therefore not be opaque or native, and as the variant is created
+//! after the specification instrumentation, they must not be generic. Neither must they take or
+//! return mutable references, nor write global memory: the second call would otherwise start
+//! from the memory left by the first one, and the memory after the calls is not compared.
+//! Reading global memory is fine, as both functions then see the same memory at entry.
+
+use crate::{
+    function_data_builder::FunctionDataBuilder,
+    function_target::FunctionData,
+    function_target_pipeline::{
+        FunctionTargetProcessor, FunctionTargetsHolder, FunctionVariant, VerificationFlavor,
+    },
+    options::ProverOptions,
+    stackless_bytecode::{AbortAction, Bytecode, Operation, PropKind},
+};
+use move_model::{
+    ast::TempIndex,
+    exp_generator::ExpGenerator,
+    model::{FunId, FunctionEnv, GlobalEnv, QualifiedId},
+    ty::{PrimitiveType, Type},
+};
+
+/// Returns the function the given function is checked to be equivalent with, if any.
+pub fn get_equivalence_partner(fun_env: &FunctionEnv<'_>) -> Option<QualifiedId<FunId>> {
+    let env = fun_env.module_env.env;
+    let options = ProverOptions::get(env);
+    options
+        .equivalence_checks
+        .iter()
+        .find_map(|(fun_name, other_name)| {
+            let fun_id = env.find_function_by_name_str(fun_name)?.get_qualified_id();
+            if fun_id == fun_env.get_qualified_id() {
+                env.find_function_by_name_str(other_name)
+                    .map(|other_env| other_env.get_qualified_id())
+            } else {
+                None
+            }
+        })
+}
+
+pub struct EquivalenceCheckInstrumenter {}
+
+impl EquivalenceCheckInstrumenter {
+    pub fn new() -> Box<Self> {
+        Box::new(Self {})
+    }
+}
+
+impl FunctionTargetProcessor for EquivalenceCheckInstrumenter {
+    fn process(
+        &self,
+        targets: &mut FunctionTargetsHolder,
+        fun_env: &FunctionEnv<'_>,
+        data: FunctionData,
+    ) -> FunctionData {
+        if data.variant != FunctionVariant::Verification(VerificationFlavor::Regular) {
+            // instrumentation only applies to regular verification variants
+            return data;
+        }
+        let other_env = match get_equivalence_partner(fun_env) {
+            Some(other_id) => fun_env.module_env.env.get_function(other_id),
+            None => return data,
+        };
+        if Self::check_pair(fun_env, &other_env).is_some() {
+            // reported in `initialize`
+            return data;
+        }
+        let new_data = Self::instrument(fun_env, &other_env, &data);
+        targets.insert_target_data(
+            &fun_env.get_qualified_id(),
+            new_data.variant.clone(),
+            new_data,
+        );
+
+        // the original function data is unchanged
+        data
+    }
+
+    fn name(&self) -> String {
+        "equivalence_check_instrumenter".to_string()
+    }
+
+    fn initialize(&self, env: &GlobalEnv, _targets: &mut FunctionTargetsHolder) {
+        let options = ProverOptions::get(env);
+        for (fun_name, other_name) in &options.equivalence_checks {
+            let find = |name: &str| {
+                let fun_env = env.find_function_by_name_str(name);
+                if fun_env.is_none() {
+                    env.error(
+                        &env.unknown_loc(),
+                        &format!("cannot find function `{}` of equivalence check", name),
+                    );
+                }
+                fun_env
+            };
+            if let (Some(fun_env), Some(other_env)) = (find(fun_name), find(other_name)) {
+                if let Some(message) = Self::check_pair(&fun_env, &other_env) {
+                    env.error(&fun_env.get_loc(), &message);
+                }
+            }
+        }
+    }
+}
+
+impl EquivalenceCheckInstrumenter {
+    /// Checks whether the functions can be checked for equivalence, returning the reason if not.
+    pub fn check_pair(fun_env: &FunctionEnv<'_>, other_env: &FunctionEnv<'_>) -> Option<String> {
+        let (name, other_name) = (fun_env.get_full_name_str(), other_env.get_full_name_str());
+        if fun_env.get_qualified_id() == other_env.get_qualified_id() {
+            return Some(format!(
+                "function `{}` cannot be checked for equivalence with itself",
+                name
+            ));
+        }
+        if fun_env.get_parameter_types() != other_env.get_parameter_types()
+            || fun_env.get_return_types() != other_env.get_return_types()
+        {
+            return Some(format!(
+                "function `{}` cannot be checked for equivalence with `{}` because their \
+                 signatures differ",
+                name, other_name
+            ));
+        }
+        for checked_env in [fun_env, other_env] {
+            let reason = if checked_env.is_native_or_intrinsic() {
+                "it is native"
+            } else if checked_env.is_opaque() {
+                "it is opaque"
+            } else if checked_env.get_type_parameter_count() > 0 {
+                "it is generic"
+            } else if checked_env
+                .get_parameter_types()
+                .iter()
+                .chain(checked_env.get_return_types().iter())
+                .any(|ty| ty.is_mutable_reference())
+            {
+                "it takes or returns mutable references"
+            } else if !checked_env.get_effect_class().is_view() {
+                "it modifies global memory"
+            } else {
+                continue;
+            };
+            return Some(format!(
+                "function `{}` cannot be checked for equivalence because {}",
+                checked_env.get_full_name_str(),
+                reason
+            ));
+        }
+        None
+    }
+
+    /// Creates the equivalence check variant from the regular verification variant.
+    fn instrument(
+        fun_env: &FunctionEnv<'_>,
+        other_env: &FunctionEnv<'_>,
+        data: &FunctionData,
+    ) -> FunctionData {
+        let mut new_data = data.fork(FunctionVariant::Verification(
+            VerificationFlavor::Equivalence,
+        ));
+        // The assumptions at entry contain the preconditions and the well-formedness of the
+        // parameters.
+        let entry = std::mem::take(&mut new_data.code)
+            .into_iter()
+            .take_while(|bc| matches!(bc, Bytecode::Prop(_, PropKind::Assume, _)))
+            .collect::<Vec<_>>();
+        let mut builder = FunctionDataBuilder::new(fun_env, new_data);
+        builder.emit_vec(entry);
+
+        let loc = fun_env.get_loc();
+        let (name, other_name) = (fun_env.get_full_name_str(), other_env.get_full_name_str());
+        builder.set_loc(loc.at_start());
+        let params = (0..fun_env.get_parameter_count()).collect::<Vec<_>>();
+        for param in &params {
+            builder.emit_with(|id| {
+                Bytecode::Call(
+                    id,
+                    vec![],
+                    Operation::TraceLocal(*param),
+                    vec![*param],
+                    None,
+                )
+            });
+        }
+        let return_types = builder.data.return_types.clone();
+        let results = return_types
+            .iter()
+            .map(|ty| builder.new_temp(ty.clone()))
+            .collect::<Vec<_>>();
+        let other_results = return_types
+            .iter()
+            .map(|ty| builder.new_temp(ty.clone()))
+            .collect::<Vec<_>>();
+        let code = builder.new_temp(Type::Primitive(PrimitiveType::Num));
+        let other_code = builder.new_temp(Type::Primitive(PrimitiveType::Num));
+        let fun_op = Operation::Function(fun_env.module_env.get_id(), fun_env.get_id(), vec![]);
+        let other_op =
+            Operation::Function(other_env.module_env.get_id(), other_env.get_id(), vec![]);
+        let abort_label = builder.new_label();
+        let other_abort_label = builder.new_label();
+        let both_abort_label = builder.new_label();
+
+        // Call the other function first, then the function itself.
+        builder.emit_with(|id| {
+            Bytecode::Call(
+                id,
+                other_results.clone(),
+                other_op,
+                params.clone(),
+                Some(AbortAction(other_abort_label, other_code)),
+            )
+        });
+        builder.emit_with(|id| {
+            Bytecode::Call(
+                id,
+                results.clone(),
+                fun_op.clone(),
+                params.clone(),
+                Some(AbortAction(abort_label, code)),
+            )
+        });
+
+        // Both functions return: the results must be the same.
+        for (result, other_result) in results.iter().zip(other_results.iter()) {
+            builder.set_loc_and_vc_info(
+                loc.clone(),
+                &format!(
+                    "function `{}` returns a different result than `{}`",
+                    name, other_name
+                ),
+            );
+            let exp = builder.mk_eq(
+                builder.mk_temporary(*result),
+                builder.mk_temporary(*other_result),
+            );
+            builder.emit_prop(PropKind::Assert, exp);
+        }
+        Self::emit_return(&mut builder, &results);
+
+        // Only the function aborts.
+        builder.emit_with(|id| Bytecode::Label(id, abort_label));
+        builder.set_loc_and_vc_info(
+            loc.clone(),
+            &format!("function `{}` aborts where `{}` returns", name, other_name),
+        );
+        let exp = builder.mk_bool_const(false);
+        builder.emit_prop(PropKind::Assert, exp);
+        Self::emit_abort(&mut builder, code);
+
+        // The other function aborts: the function must abort with the same code.
+        builder.emit_with(|id| Bytecode::Label(id, other_abort_label));
+        builder.emit_with(|id| {
+            Bytecode::Call(
+                id,
+                results.clone(),
+                fun_op,
+                params,
+                Some(AbortAction(both_abort_label, code)),
+            )
+        });
+        builder.set_loc_and_vc_info(
+            loc.clone(),
+            &format!("function `{}` returns where `{}` aborts", name, other_name),
+        );
+        let exp = builder.mk_bool_const(false);
+        builder.emit_prop(PropKind::Assert, exp);
+        Self::emit_return(&mut builder, &results);
+
+        builder.emit_with(|id| Bytecode::Label(id, both_abort_label));
+        builder.set_loc_and_vc_info(
+            loc,
+            &format!(
+                "function `{}` aborts with a different code than `{}`",
+                name, other_name
+            ),
+        );
+        let exp = builder.mk_eq(builder.mk_temporary(code), builder.mk_temporary(other_code));
+        builder.emit_prop(PropKind::Assert, exp);
+        Self::emit_abort(&mut builder, code);
+
+        builder.data
+    }
+
+    fn emit_return(builder: &mut FunctionDataBuilder<'_>, results: &[TempIndex]) {
+        for (idx, result) in results.iter().enumerate() {
+            builder.emit_with(|id| {
+                Bytecode::Call(id, vec![], Operation::TraceReturn(idx), vec![*result], None)
+            });
+        }
+        builder.emit_with(|id| Bytecode::Ret(id, results.to_vec()));
+    }
+
+    fn emit_abort(builder: &mut FunctionDataBuilder<'_>, code: TempIndex) {
+        builder.emit_with(|id| Bytecode::Call(id, vec![], Operation::TraceAbort, vec![code], None));
+        builder.emit_with(|id| Bytecode::Abort(id, code));
+    }
+}
@@ -8,11 +8,13 @@ use crate::{
     function_target::{FunctionData, FunctionTarget},
     stackless_bytecode::{AttrId, Bytecode, HavocKind, Label, Operation, PropKind},
 };
+use move_binary_format::file_format::CodeOffset;
 use move_model::{
     ast::{Exp, TempIndex},
     exp_generator::ExpGenerator,
-    model::{FunctionEnv, Loc},
-    ty::Type,
+    model::{FunctionEnv, Loc, QualifiedInstId, StructId},
+    symbol::Symbol,
+    ty::{Type, BOOL_TYPE},
 };
 
 #[derive(Default)]
@@ -28,6 +30,7 @@ pub struct FunctionDataBuilder<'env> {
     next_free_attr_index: usize,
     next_free_label_index: usize,
     current_loc: Loc,
+    current_code_offset: Option<CodeOffset>,
     next_vc_info: Option<String>,
     next_debug_comment: Option<String>,
 }
@@ -43,6 +46,7 @@ impl<'env> ExpGenerator<'env> for FunctionDataBuilder<'env> {
 
     fn set_loc(&mut self, loc: Loc) {
         self.current_loc = loc;
+        self.current_code_offset = None;
     }
 
     fn add_local(&mut self, ty: Type) -> TempIndex {
@@ -76,6 +80,7 @@ impl<'env> FunctionDataBuilder<'env> {
             next_free_attr_index,
             next_free_label_index,
             current_loc: fun_env.get_loc(),
+            current_code_offset: None,
             next_vc_info: None,
             next_debug_comment: None,
         }
@@ -92,6 +97,12 @@ impl<'env> FunctionDataBuilder<'env> {
         FunctionTarget::new(self.fun_env, &self.data)
     }
 
+    /// Attaches a description to a temporary, like the expression whose value it holds, which
+    /// is shown instead of its bare index.
+    pub fn set_temp_description(&mut self, temp: TempIndex, description: impl Into<String>) {
+        self.data.temp_descriptions.insert(temp, description.into());
+    }
+
     /// Add a return parameter.
     pub fn add_return(&mut self, ty: Type) -> usize {
         let idx = self.data.return_types.len();
@@ -106,7 +117,8 @@ impl<'env> FunctionDataBuilder<'env> {
         self.set_loc(loc);
     }
 
-    /// Sets the default location from a code attribute id.
+    /// Sets the default location from a code attribute id. Bytecode generated afterwards also
+    /// derives from the same offset in the compiled module as the attribute.
     pub fn set_loc_from_attr(&mut self, attr_id: AttrId) {
         let loc = if let Some(l) = self.data.locations.get(&attr_id) {
             l.clone()
@@ -114,6 +126,7 @@ impl<'env> FunctionDataBuilder<'env> {
             self.global_env().unknown_loc()
         };
         self.current_loc = loc;
+        self.current_code_offset = self.data.code_offsets.get(&attr_id).cloned();
     }
 
     /// Gets the location from the bytecode attribute.
@@ -125,11 +138,14 @@ impl<'env> FunctionDataBuilder<'env> {
             .unwrap_or_else(|| self.fun_env.get_loc())
     }
 
-    /// Creates a new bytecode attribute id with default location.
+    /// Creates a new bytecode attribute id with default location and code offset.
     pub fn new_attr(&mut self) -> AttrId {
         let id = AttrId::new(self.next_free_attr_index);
         self.next_free_attr_index += 1;
         self.data.locations.insert(id, self.current_loc.clone());
+        if let Some(code_offset) = self.current_code_offset {
+            self.data.code_offsets.insert(id, code_offset);
+        }
         id
     }
 
@@ -223,4 +239,32 @@ impl<'env> FunctionDataBuilder<'env> {
         });
         (temp, temp_exp)
     }
+
+    /// Emits the lowering of a match on the variants of an enum. The variant of the value
+    /// referenced by `value_ref` is tested against the arms in order, continuing at the label
+    /// of the first arm whose variant matches, or at `default` if none matches. The arms are
+    /// expected to unpack the value with `Operation::UnpackVariant`, or borrow its fields with
+    /// `Operation::BorrowVariantField`.
+    pub fn emit_match(
+        &mut self,
+        value_ref: TempIndex,
+        enum_type: QualifiedInstId<StructId>,
+        arms: &[(Symbol, Label)],
+        default: Label,
+    ) {
+        for (variant, arm_label) in arms {
+            let cond = self.new_temp(BOOL_TYPE);
+            let test = Operation::TestVariant(
+                enum_type.module_id,
+                enum_type.id,
+                *variant,
+                enum_type.inst.clone(),
+            );
+            self.emit_with(|id| Bytecode::Call(id, vec![cond], test, vec![value_ref], None));
+            let next_label = self.new_label();
+            self.emit_with(|id| Bytecode::Branch(id, *arm_label, next_label, cond));
+            self.emit_with(|id| Bytecode::Label(id, next_label));
+        }
+        self.emit_with(|id| Bytecode::Jump(id, default));
+    }
 }
@@ -3,7 +3,9 @@
 
 use crate::{
     annotations::Annotations,
-    borrow_analysis, livevar_analysis, reaching_def_analysis, read_write_set_analysis,
+    borrow_analysis, constant_propagation, liveness_analysis, livevar_analysis,
+    loop_analysis::{self, LoopInfo},
+    reaching_def_analysis, read_write_set_analysis,
     stackless_bytecode::{AttrId, Bytecode, Label},
 };
 use itertools::Itertools;
@@ -70,6 +72,10 @@ pub struct FunctionData {
     pub acquires_global_resources: Vec<StructId>,
     /// A map from byte code attribute to source code location.
     pub locations: BTreeMap<AttrId, Loc>,
+    /// A map from byte code attribute to the offset of the instruction in the compiled module
+    /// the bytecode derives from. Bytecode introduced by transformations inherits the offset
+    /// of the instruction it was generated for, if any.
+    pub code_offsets: BTreeMap<AttrId, CodeOffset>,
     /// The set of asserts that represent loop invariants
     pub loop_invariants: BTreeSet<AttrId>,
     /// A map from byte code attribute to comments associated with this bytecode.
@@ -84,6 +90,9 @@ pub struct FunctionData {
     pub annotations: Annotations,
     /// A mapping from symbolic names to temporaries.
     pub name_to_index: BTreeMap<Symbol, usize>,
+    /// Descriptions of temporaries introduced by transformations, like `old(balance)`, which
+    /// are shown instead of the bare index of the temporary.
+    pub temp_descriptions: BTreeMap<TempIndex, String>,
     /// A cache of targets modified by this function.
     pub modify_targets: BTreeMap<QualifiedId<StructId>, Vec<Exp>>,
     /// The number of ghost type parameters introduced in order to instantiate related invariants
@@ -141,6 +150,12 @@ impl<'env> FunctionTarget<'env> {
         }
     }
 
+    /// Returns the offset of the instruction in the compiled module from which the bytecode
+    /// with the given attribute derives, if any.
+    pub fn get_bytecode_offset(&self, attr_id: AttrId) -> Option<CodeOffset> {
+        self.data.code_offsets.get(&attr_id).cloned()
+    }
+
     /// Returns the debug comment, if any, associated with the given attribute.
     pub fn get_debug_comment(&self, attr_id: AttrId) -> Option<&String> {
         self.data.debug_comments.get(&attr_id)
@@ -226,6 +241,14 @@ impl<'env> FunctionTarget<'env> {
         idx < self.get_user_local_count()
     }
 
+    /// Returns the description of a temporary introduced by a transformation, if any.
+    pub fn get_temp_description(&self, idx: TempIndex) -> Option<&'env str> {
+        self.data
+            .temp_descriptions
+            .get(&idx)
+            .map(|desc| desc.as_str())
+    }
+
     /// Get the index corresponding to a local name. The name must either match a user name,
     /// or have the syntax `$t<N>$`.
     pub fn get_local_index(&self, name: Symbol) -> Option<usize> {
@@ -284,6 +307,15 @@ impl<'env> FunctionTarget<'env> {
         &self.data.annotations
     }
 
+    /// Returns the loops of this function, ordered by header, or None if loop detection has
+    /// not been run.
+    pub fn get_loops(&self) -> Option<&'env [LoopInfo]> {
+        self.data
+            .annotations
+            .get::<loop_analysis::LoopsAnnotation>()
+            .map(|annotation| annotation.loops.as_slice())
+    }
+
     /// Gets acquired resources
     pub fn get_acquires_global_resources(&self) -> &[StructId] {
         &self.data.acquires_global_resources
@@ -416,6 +448,7 @@ impl FunctionData {
         local_types: Vec<Type>,
         return_types: Vec<Type>,
         locations: BTreeMap<AttrId, Loc>,
+        code_offsets: BTreeMap<AttrId, CodeOffset>,
         acquires_global_resources: Vec<StructId>,
         loop_invariants: BTreeSet<AttrId>,
     ) -> Self {
@@ -431,11 +464,13 @@ impl FunctionData {
             return_types,
             acquires_global_resources,
             locations,
+            code_offsets,
             loop_invariants,
             debug_comments: Default::default(),
             vc_infos: Default::default(),
             annotations: Default::default(),
             name_to_index,
+            temp_descriptions: BTreeMap::new(),
             modify_targets,
             ghost_type_param_count: 0,
         }
@@ -467,12 +502,16 @@ impl FunctionData {
             + 1
     }
 
-    /// Apply a variable renaming to this data, adjusting internal data structures.
-    pub fn rename_vars<F>(&mut self, _f: &F)
+    /// Apply a variable renaming to this data, adjusting internal data structures. Data
+    /// attached to variables mapped to `None` is dropped.
+    pub fn rename_vars<F>(&mut self, f: &F)
     where
-        F: Fn(TempIndex) -> TempIndex,
+        F: Fn(TempIndex) -> Option<TempIndex>,
     {
-        // Nothing to do currently.
+        self.temp_descriptions = std::mem::take(&mut self.temp_descriptions)
+            .into_iter()
+            .filter_map(|(idx, desc)| f(idx).map(|new_idx| (new_idx, desc)))
+            .collect();
     }
 
     /// Fork this function data, without annotations, and mark it as the given
@@ -563,13 +602,18 @@ impl<'env> FunctionTarget<'env> {
     /// new formatters relevant for tests.
     pub fn register_annotation_formatters_for_test(&self) {
         self.register_annotation_formatter(Box::new(livevar_analysis::format_livevar_annotation));
+        self.register_annotation_formatter(Box::new(liveness_analysis::format_liveness_annotation));
         self.register_annotation_formatter(Box::new(borrow_analysis::format_borrow_annotation));
+        self.register_annotation_formatter(Box::new(loop_analysis::format_loops_annotation));
         self.register_annotation_formatter(Box::new(
             reaching_def_analysis::format_reaching_def_annotation,
         ));
         self.register_annotation_formatter(Box::new(
             read_write_set_analysis::format_read_write_set_annotation,
         ));
+        self.register_annotation_formatter(Box::new(
+            constant_propagation::format_constant_annotation,
+        ));
     }
 }
 
@@ -623,6 +667,8 @@ impl<'env> fmt::Display for FunctionTarget<'env> {
                         .display(self.global_env().symbol_pool()),
                     ty
                 )
+            } else if let Some(desc) = self.get_temp_description(i) {
+                write!(f, "$t{}|{}: {}", i, desc, ty)
             } else {
                 write!(f, "$t{}: {}", i, ty)
             }
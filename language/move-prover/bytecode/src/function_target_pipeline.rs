@@ -3,15 +3,33 @@
 
 use crate::{
     function_target::{FunctionData, FunctionTarget},
-    print_targets_for_test,
+    options::ProverOptions,
+    pipeline_factory, print_targets_with_filter,
     stackless_bytecode_generator::StacklessBytecodeGenerator,
     stackless_control_flow_graph::generate_cfg_in_dot_format,
 };
+use anyhow::anyhow;
 use core::fmt;
 use itertools::Itertools;
 use log::{debug, info};
-use move_model::model::{FunId, FunctionEnv, GlobalEnv, QualifiedId};
-use std::{collections::BTreeMap, fmt::Formatter, fs};
+use move_model::{
+    ast::{PropertyBag, PropertyValue, Value},
+    call_graph::CallGraph,
+    model::{FunId, FunctionEnv, GlobalEnv, QualifiedId},
+};
+use num::BigInt;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Formatter,
+    fs,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+/// The maximal number of times a group of recursive functions is processed by a processor
+/// before it is considered not to reach a fixed point.
+const MAX_FIXEDPOINT_ITERATIONS: usize = 100;
 
 /// A data structure which holds data for multiple function targets, and allows to
 /// manipulate them as part of a transformation pipeline.
@@ -26,6 +44,15 @@ pub enum VerificationFlavor {
     Regular,
     Instantiated(usize),
     Inconsistency(Box<VerificationFlavor>),
+    /// A variant checking whether a specification holds vacuously, identified by the index
+    /// of the check.
+    Vacuity(Box<VerificationFlavor>, usize),
+    /// A variant verifying a group of conditions which are independent of the conditions
+    /// verified by the variant with the boxed flavor, identified by the index of the group.
+    Split(Box<VerificationFlavor>, usize),
+    /// A variant checking that the function agrees with the function it is compared with by
+    /// `ProverOptions::equivalence_checks`.
+    Equivalence,
 }
 
 impl std::fmt::Display for VerificationFlavor {
@@ -36,6 +63,11 @@ impl std::fmt::Display for VerificationFlavor {
                 write!(f, "instantiated_{}", index)
             }
             VerificationFlavor::Inconsistency(flavor) => write!(f, "inconsistency_{}", flavor),
+            VerificationFlavor::Vacuity(flavor, index) => {
+                write!(f, "vacuity_{}_{}", index, flavor)
+            }
+            VerificationFlavor::Split(flavor, index) => write!(f, "split_{}_{}", index, flavor),
+            VerificationFlavor::Equivalence => write!(f, "equivalence"),
         }
     }
 }
@@ -50,6 +82,10 @@ pub enum FunctionVariant {
     /// of verification have one of those. There can be multiple verification variants,
     /// each identified by a unique flavor.
     Verification(VerificationFlavor),
+    /// A copy of another variant created by a transformation, identified by a name. A named
+    /// variant is processed like the baseline, but is not what callers of the function see;
+    /// this lets a transformation keep both its result and the variant it started from.
+    Named(String),
 }
 
 impl FunctionVariant {
@@ -65,6 +101,7 @@ impl std::fmt::Display for FunctionVariant {
             Baseline => write!(f, "baseline"),
             Verification(VerificationFlavor::Regular) => write!(f, "verification"),
             Verification(v) => write!(f, "verification[{}]", v),
+            Named(name) => write!(f, "{}", name),
         }
     }
 }
@@ -146,12 +183,300 @@ pub struct FunctionTargetPipeline {
     processors: Vec<Box<dyn FunctionTargetProcessor>>,
 }
 
+/// A description of a processing pipeline, which can be committed as a TOML file, e.g.
+///
+/// ```toml
+/// processors = ["eliminate_imm_refs", "mut_ref_instrumentation", "reaching_def_analysis"]
+///
+/// [options]
+/// check_inconsistency = true
+///
+/// [pragmas."0x1::Coin::transfer"]
+/// timeout = 100
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PipelineConfig {
+    /// The names of the processors, in the order they are run. If empty, the default pipeline
+    /// for the options is used.
+    pub processors: Vec<String>,
+    /// The options for the processors. They are used to create the default pipeline, and
+    /// should be set in the environment before running the pipeline, see `ProverOptions::set`.
+    pub options: Option<ProverOptions>,
+    /// Pragmas which override those of module and function specifications, by name of the
+    /// module (`M` or `0x1::M`) or function (`M::f` or `0x1::M::f`).
+    pub pragmas: BTreeMap<String, BTreeMap<String, PragmaValue>>,
+}
+
+/// The value of a pragma in a pipeline configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PragmaValue {
+    Bool(bool),
+    Number(u64),
+    Ident(String),
+}
+
+impl PipelineConfig {
+    /// Reads a configuration from TOML source.
+    pub fn from_toml(source: &str) -> anyhow::Result<Self> {
+        Ok(toml::from_str(source)?)
+    }
+
+    /// Applies the pragma overrides of this configuration to the specifications in the
+    /// environment.
+    pub fn apply_pragmas(&self, env: &mut GlobalEnv) -> anyhow::Result<()> {
+        for (name, pragmas) in &self.pragmas {
+            if let Some(fun_env) = env.find_function_by_name_str(name) {
+                let fun_id = fun_env.get_qualified_id();
+                let mut spec = fun_env.get_spec().clone();
+                Self::add_pragmas(env, &mut spec.properties, pragmas);
+                env.override_function_spec(fun_id, spec);
+            } else if let Some(module_env) = env.find_module_by_name_str(name) {
+                let module_id = module_env.get_id();
+                let mut spec = module_env.get_spec().clone();
+                Self::add_pragmas(env, &mut spec.properties, pragmas);
+                env.override_module_spec(module_id, spec);
+            } else {
+                return Err(anyhow!(
+                    "cannot find module or function `{}` to override pragmas of",
+                    name
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn add_pragmas(
+        env: &GlobalEnv,
+        properties: &mut PropertyBag,
+        pragmas: &BTreeMap<String, PragmaValue>,
+    ) {
+        let pool = env.symbol_pool();
+        for (name, value) in pragmas {
+            let value = match value {
+                PragmaValue::Bool(b) => PropertyValue::Value(Value::Bool(*b)),
+                PragmaValue::Number(n) => PropertyValue::Value(Value::Number(BigInt::from(*n))),
+                PragmaValue::Ident(s) => PropertyValue::Symbol(pool.make(s)),
+            };
+            properties.insert(pool.make(name), value);
+        }
+    }
+}
+
+/// Metrics of a pipeline run, one entry per processor in the order they have been run. The
+/// metrics of the last run are stored as an extension of the environment, see
+/// `PipelineMetrics::get`.
+#[derive(Debug, Clone, Default)]
+pub struct PipelineMetrics {
+    pub processors: Vec<ProcessorMetrics>,
+}
+
+/// Metrics of a processor in a pipeline run.
+#[derive(Debug, Clone, Default)]
+pub struct ProcessorMetrics {
+    pub name: String,
+    /// The time the processor took, summed over all functions.
+    pub time: Duration,
+    /// The number of instructions added to functions, summed over functions which have grown.
+    pub instructions_added: usize,
+    /// The number of instructions removed from functions, summed over functions which have
+    /// shrunk.
+    pub instructions_removed: usize,
+    /// The number of locals added to functions.
+    pub locals_added: usize,
+    /// The size of the function variants which have been changed by the processor, before and
+    /// after it ran. A variant created by the processor is compared with the baseline variant.
+    pub changed: BTreeMap<(QualifiedId<FunId>, FunctionVariant), (FunctionSize, FunctionSize)>,
+}
+
+/// The size of a function variant.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FunctionSize {
+    pub instructions: usize,
+    pub locals: usize,
+}
+
+impl PipelineMetrics {
+    /// Returns the metrics of the last pipeline run in the environment.
+    pub fn get(env: &GlobalEnv) -> Rc<PipelineMetrics> {
+        env.get_extension::<PipelineMetrics>()
+            .unwrap_or_else(|| Rc::new(PipelineMetrics::default()))
+    }
+
+    fn sizes(
+        targets: &FunctionTargetsHolder,
+    ) -> BTreeMap<(QualifiedId<FunId>, FunctionVariant), FunctionSize> {
+        targets
+            .get_funs_and_variants()
+            .filter_map(|(fun, variant)| {
+                let data = targets.get_data(&fun, &variant)?;
+                let size = FunctionSize {
+                    instructions: data.code.len(),
+                    locals: data.local_types.len(),
+                };
+                Some(((fun, variant), size))
+            })
+            .collect()
+    }
+
+    fn record(
+        &mut self,
+        name: String,
+        time: Duration,
+        before: &BTreeMap<(QualifiedId<FunId>, FunctionVariant), FunctionSize>,
+        after: &BTreeMap<(QualifiedId<FunId>, FunctionVariant), FunctionSize>,
+    ) {
+        let mut metrics = ProcessorMetrics {
+            name,
+            time,
+            ..Default::default()
+        };
+        for (key, size_after) in after {
+            let size_before = before
+                .get(key)
+                .or_else(|| before.get(&(key.0, FunctionVariant::Baseline)))
+                .cloned()
+                .unwrap_or_default();
+            if size_before == *size_after {
+                continue;
+            }
+            if size_after.instructions > size_before.instructions {
+                metrics.instructions_added += size_after.instructions - size_before.instructions;
+            } else {
+                metrics.instructions_removed += size_before.instructions - size_after.instructions;
+            }
+            metrics.locals_added += size_after.locals.saturating_sub(size_before.locals);
+            metrics
+                .changed
+                .insert(key.clone(), (size_before, *size_after));
+        }
+        self.processors.push(metrics);
+    }
+
+    /// Reports an error for each function variant whose size exceeds the limits set by
+    /// `ProverOptions::max_instructions` and `ProverOptions::max_locals`, naming the processor
+    /// which has grown it the most.
+    fn check_budget(
+        &self,
+        env: &GlobalEnv,
+        sizes: &BTreeMap<(QualifiedId<FunId>, FunctionVariant), FunctionSize>,
+    ) {
+        let options = ProverOptions::get(env);
+        for ((fun, variant), size) in sizes {
+            let checks: [(&str, Option<usize>, fn(&FunctionSize) -> usize); 2] = [
+                ("instructions", options.max_instructions, |s| s.instructions),
+                ("locals", options.max_locals, |s| s.locals),
+            ];
+            for (what, limit, count) in checks {
+                let actual = count(size);
+                let limit = match limit {
+                    Some(limit) if actual > limit => limit,
+                    _ => continue,
+                };
+                let key = (*fun, variant.clone());
+                let culprit = self
+                    .processors
+                    .iter()
+                    .filter_map(|m| {
+                        let (before, after) = m.changed.get(&key)?;
+                        Some((m, count(before), count(after)))
+                    })
+                    .max_by_key(|(_, before, after)| *after as i64 - *before as i64)
+                    .filter(|(_, before, after)| after > before)
+                    .map(|(m, before, after)| {
+                        format!(
+                            " (most {} have been added by `{}`: {} -> {})",
+                            what, m.name, before, after
+                        )
+                    })
+                    .unwrap_or_default();
+                let fun_env = env.get_function(*fun);
+                env.error(
+                    &fun_env.get_loc(),
+                    &format!(
+                        "function `{}` [{}] has {} {} after bytecode transformation, \
+                         exceeding the limit of {}{}",
+                        fun_env.get_full_name_str(),
+                        variant,
+                        actual,
+                        what,
+                        limit,
+                        culprit
+                    ),
+                );
+            }
+        }
+    }
+
+    /// Returns a report of the metrics, listing for each processor the function variant which
+    /// has grown the most.
+    pub fn report(&self, env: &GlobalEnv) -> String {
+        let mut lines = vec![];
+        for metrics in &self.processors {
+            lines.push(format!(
+                "{:<36} {:>8.3}s  +{} -{} instructions  +{} locals",
+                metrics.name,
+                metrics.time.as_secs_f64(),
+                metrics.instructions_added,
+                metrics.instructions_removed,
+                metrics.locals_added
+            ));
+            if let Some(((fun, variant), (before, after))) =
+                metrics.changed.iter().max_by_key(|(_, (before, after))| {
+                    after.instructions as i64 - before.instructions as i64
+                })
+            {
+                if after.instructions > before.instructions {
+                    lines.push(format!(
+                        "    largest growth: {}[{}] {} -> {} instructions",
+                        env.get_function(*fun).get_full_name_str(),
+                        variant,
+                        before.instructions,
+                        after.instructions
+                    ));
+                }
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+/// A group of functions in the bottom-up order of the call graph, as returned by
+/// `FunctionTargetsHolder::get_funs_bottom_up`.
+#[derive(Debug, Clone)]
+pub struct FunctionGroup {
+    /// The functions of the group.
+    pub funs: BTreeSet<QualifiedId<FunId>>,
+    /// Whether the functions of the group are (mutually) recursive.
+    pub is_recursive: bool,
+}
+
 impl FunctionTargetsHolder {
     /// Get an iterator for all functions this holder.
     pub fn get_funs(&self) -> impl Iterator<Item = QualifiedId<FunId>> + '_ {
         self.targets.keys().cloned()
     }
 
+    /// Returns the functions in this holder in bottom-up order of the call graph, leaves first,
+    /// such that the functions of a group only call functions of the same or preceding groups.
+    /// A group is either a single function or the functions of a cycle of recursive calls. Only
+    /// calls between functions in this holder are considered.
+    pub fn get_funs_bottom_up(&self, env: &GlobalEnv) -> Vec<FunctionGroup> {
+        let call_graph = CallGraph::new_for_functions(env, self.get_funs());
+        call_graph
+            .sccs()
+            .into_iter()
+            .map(|funs| {
+                let is_recursive = funs.len() > 1
+                    || funs
+                        .iter()
+                        .any(|fun_id| call_graph.callees_of(*fun_id).contains(fun_id));
+                FunctionGroup { funs, is_recursive }
+            })
+            .collect()
+    }
+
     /// Gets an iterator for all functions and variants in this holder.
     pub fn get_funs_and_variants(
         &self,
@@ -249,6 +574,21 @@ impl FunctionTargetsHolder {
             .expect("variant exists")
     }
 
+    /// Adds a copy of a variant of a function as a new variant, replacing any existing data of
+    /// the new variant. The copy is processed independently of the original from now on.
+    pub fn copy_variant(
+        &mut self,
+        id: &QualifiedId<FunId>,
+        from: &FunctionVariant,
+        to: FunctionVariant,
+    ) {
+        let data = self
+            .get_data(id, from)
+            .expect("variant to copy exists")
+            .fork(to.clone());
+        self.insert_target_data(id, to, data);
+    }
+
     /// Sets function data for a function's variant.
     pub fn insert_target_data(
         &mut self,
@@ -274,12 +614,36 @@ impl FunctionTargetsHolder {
 }
 
 impl FunctionTargetPipeline {
+    /// Creates a pipeline from a configuration. Processors are looked up by name via
+    /// `pipeline_factory::processor_by_name`. If the configuration does not name any
+    /// processors, the default pipeline for its options is created.
+    pub fn from_config(config: &PipelineConfig) -> anyhow::Result<Self> {
+        if config.processors.is_empty() {
+            return Ok(pipeline_factory::default_pipeline_with_options(
+                &config.options.clone().unwrap_or_default(),
+            ));
+        }
+        let mut pipeline = Self::default();
+        for name in &config.processors {
+            pipeline.add_processor(
+                pipeline_factory::processor_by_name(name)
+                    .ok_or_else(|| anyhow!("unknown processor `{}`", name))?,
+            );
+        }
+        Ok(pipeline)
+    }
+
     /// Adds a processor to this pipeline. Processor will be called in the order they have been
     /// added.
     pub fn add_processor(&mut self, processor: Box<dyn FunctionTargetProcessor>) {
         self.processors.push(processor)
     }
 
+    /// Returns the names of the processors in this pipeline, in the order they are run.
+    pub fn processor_names(&self) -> Vec<String> {
+        self.processors.iter().map(|p| p.name()).collect()
+    }
+
     /// Gets the last processor in the pipeline, for testing.
     pub fn last_processor(&self) -> &dyn FunctionTargetProcessor {
         self.processors
@@ -290,64 +654,68 @@ impl FunctionTargetPipeline {
     }
 
     /// Sort functions in topological order. This is important for the function target processors.
-    /// In programs without recursion or mutual recursion, processing functions in topological order
-    /// means that when a processor sees a caller function, it is guaranteed that all the callees
-    /// have already been analyzed.
+    /// Processing functions in topological order means that when a processor sees a caller
+    /// function, all its callees have already been analyzed, unless they are (mutually) recursive
+    /// with the caller. The functions of a recursive group are adjacent in the order.
     pub fn sort_targets_in_topological_order<'env>(
         env: &'env GlobalEnv,
         targets: &FunctionTargetsHolder,
     ) -> Vec<FunctionEnv<'env>> {
-        let mut worklist = vec![];
-        for fun in targets.get_funs() {
-            let fun_env = env.get_function(fun);
-            worklist.push((
-                fun,
-                fun_env.get_called_functions().into_iter().collect_vec(),
-            ));
-        }
-        let mut to_remove = vec![];
-        let mut topological_order = vec![];
-        // analyze bottom-up from the leaves of the call graph
-        loop {
-            let last = worklist.last();
-            if last.is_none() {
-                break;
-            }
-            // front of the worklist has a nonempty list of callees to analyze. walk through the
-            // worklist and remove the analyzed callees from `to_remove` from each entry in the
-            // worklist.
-            if !last.unwrap().1.is_empty() {
-                for (_f, f_callees) in &mut worklist {
-                    for f in &to_remove {
-                        f_callees.retain(|e| e != f);
+        targets
+            .get_funs_bottom_up(env)
+            .into_iter()
+            .flat_map(|group| group.funs)
+            .map(|fun| env.get_function(fun))
+            .collect()
+    }
+
+    /// Processes a group of (mutually) recursive functions. A function of the group is analyzed
+    /// before the summaries of its callees in the group are available, so the group is processed
+    /// until the annotations set by `Annotations::set_with_fixedpoint_check` are stable. A
+    /// processor which does not use this check, like any transformation, runs only once. If the
+    /// annotations are not stable after `MAX_FIXEDPOINT_ITERATIONS` rounds, an error is reported
+    /// and processing continues with the results of the last round.
+    fn process_recursive_group(
+        env: &GlobalEnv,
+        targets: &mut FunctionTargetsHolder,
+        group: &[FunctionEnv<'_>],
+        processor: &dyn FunctionTargetProcessor,
+    ) {
+        for _ in 0..MAX_FIXEDPOINT_ITERATIONS {
+            // Only the updates of this round count, so that annotations which did not
+            // stabilize with an earlier processor do not keep later processors iterating.
+            for func_env in group {
+                let fun_id = func_env.get_qualified_id();
+                for variant in targets.get_target_variants(func_env) {
+                    if let Some(data) = targets.get_data_mut(&fun_id, &variant) {
+                        data.annotations.reset_fixedpoint();
                     }
                 }
-                // Put functions with 0 calls first in line, at the end of the vector
-                worklist
-                    .sort_by(|(_, callees1), (_, callees2)| callees2.len().cmp(&callees1.len()));
             }
-            let (call_id, callees) = worklist.pop().unwrap();
-            // At this point, one of two things is true:
-            // 1. callees is empty (common case)
-            // 2. callees is nonempty and mid is part of a recursive or mutually recursive
-            //    intra-module call cycle (possible in theory, but doesn't happen in the current
-            //    implementation of the Diem framework).
-            to_remove.push(call_id);
-            let func_env = env.get_function(call_id);
-            if !callees.is_empty() {
-                // The right long-term thing to do here is to allow analysis in case (2) and ask the
-                // analysis processors to deal gracefully with the absence of summaries. But for
-                // now, we intentionally fail because recursion is not expected in Diem Framework
-                // code
-                unimplemented!(
-                    "Recursion or mutual recursion detected in {:?}. \
-                     Make sure that all analyses in processors are prepared to handle recursion",
-                    func_env.get_identifier()
-                );
+            for func_env in group {
+                targets.process(func_env, processor);
+            }
+            let reached_fixedpoint = group.iter().all(|func_env| {
+                let fun_id = func_env.get_qualified_id();
+                targets.get_target_variants(func_env).iter().all(|variant| {
+                    targets
+                        .get_data(&fun_id, variant)
+                        .map(|data| data.annotations.reached_fixedpoint())
+                        .unwrap_or(true)
+                })
+            });
+            if reached_fixedpoint {
+                return;
             }
-            topological_order.push(func_env);
         }
-        topological_order
+        env.error(
+            &group[0].get_loc(),
+            &format!(
+                "`{}` does not reach a fixed point for the recursive functions {}",
+                processor.name(),
+                group.iter().map(|f| f.get_full_name_str()).join(", ")
+            ),
+        );
     }
 
     /// Runs the pipeline on all functions in the targets holder. Processors are run on each
@@ -363,21 +731,49 @@ impl FunctionTargetPipeline {
         H1: Fn(&FunctionTargetsHolder),
         H2: Fn(usize, &dyn FunctionTargetProcessor, &FunctionTargetsHolder),
     {
-        let topological_order = Self::sort_targets_in_topological_order(env, targets);
+        // The topological order is only computed once the first processor which works on
+        // individual functions is reached. This allows single-run processors like the
+        // recursion analysis to run ahead of it and report recursion gracefully.
+        let mut topological_order: Option<Vec<(Vec<FunctionEnv<'_>>, bool)>> = None;
+        let mut metrics = PipelineMetrics::default();
+        let mut sizes = PipelineMetrics::sizes(targets);
         info!("transforming bytecode");
         hook_before_pipeline(targets);
         for (step_count, processor) in self.processors.iter().enumerate() {
+            let start = Instant::now();
             if processor.is_single_run() {
                 processor.run(env, targets);
             } else {
+                let topological_order = topological_order.get_or_insert_with(|| {
+                    targets
+                        .get_funs_bottom_up(env)
+                        .into_iter()
+                        .map(|group| {
+                            let funs = group.funs.iter().map(|fun| env.get_function(*fun));
+                            (funs.collect(), group.is_recursive)
+                        })
+                        .collect()
+                });
                 processor.initialize(env, targets);
-                for func_env in &topological_order {
-                    targets.process(func_env, processor.as_ref());
+                for (group, is_recursive) in topological_order.iter() {
+                    if *is_recursive {
+                        Self::process_recursive_group(env, targets, group, processor.as_ref());
+                    } else {
+                        for func_env in group {
+                            targets.process(func_env, processor.as_ref());
+                        }
+                    }
                 }
                 processor.finalize(env, targets);
             }
+            let time = start.elapsed();
+            let new_sizes = PipelineMetrics::sizes(targets);
+            metrics.record(processor.name(), time, &sizes, &new_sizes);
+            sizes = new_sizes;
             hook_after_each_processor(step_count + 1, processor.as_ref(), targets);
         }
+        metrics.check_budget(env, &sizes);
+        env.set_extension(metrics);
     }
 
     /// Run the pipeline on all functions in the targets holder, with no hooks in effect
@@ -387,7 +783,8 @@ impl FunctionTargetPipeline {
 
     /// Runs the pipeline on all functions in the targets holder, dump the bytecode before the
     /// pipeline as well as after each processor pass. If `dump_cfg` is set, dump the per-function
-    /// control-flow graph (in dot format) too.
+    /// control-flow graph (in dot format) too. Dumps are restricted to the functions given by
+    /// `ProverOptions::dump_functions`, if any.
     pub fn run_with_dump(
         &self,
         env: &GlobalEnv,
@@ -422,7 +819,22 @@ impl FunctionTargetPipeline {
     }
 
     fn print_targets(env: &GlobalEnv, name: &str, targets: &FunctionTargetsHolder) -> String {
-        print_targets_for_test(env, &format!("after processor `{}`", name), targets)
+        let options = ProverOptions::get(env);
+        print_targets_with_filter(
+            env,
+            &format!("after processor `{}`", name),
+            targets,
+            |func_env| Self::is_dumped(&options, func_env),
+        )
+    }
+
+    /// Returns true if the bytecode of the function is dumped.
+    fn is_dumped(options: &ProverOptions, func_env: &FunctionEnv<'_>) -> bool {
+        options.dump_functions.is_empty()
+            || options
+                .dump_functions
+                .iter()
+                .any(|name| func_env.matches_name(name))
     }
 
     fn get_pre_pipeline_dump(env: &GlobalEnv, targets: &FunctionTargetsHolder) -> String {
@@ -466,8 +878,12 @@ impl FunctionTargetPipeline {
         step_count: usize,
         suffix: &str,
     ) {
+        let options = ProverOptions::get(env);
         for (fun_id, variants) in &targets.targets {
             let func_env = env.get_function(*fun_id);
+            if !Self::is_dumped(&options, &func_env) {
+                continue;
+            }
             let func_name = func_env.get_full_name_str();
             let func_name = func_name.replace("::", "__");
             for (variant, data) in variants {
@@ -17,6 +17,7 @@ use move_binary_format::file_format::CodeOffset;
 use move_model::{
     ast::ConditionKind,
     model::{FunId, FunctionEnv, GlobalEnv, GlobalId, QualifiedId, QualifiedInstId, StructId},
+    pragmas::ASSERT_INVARIANTS_AFTER_CALLS_PRAGMA,
     ty::{Type, TypeDisplayContext, TypeInstantiationDerivation, TypeUnificationAdapter, Variance},
 };
 
@@ -258,8 +259,14 @@ impl PerFunctionRelevance {
             .expect("Invariant applicability not available");
         let fun_type_params_arity = target.get_type_parameter_count();
 
+        // functions with this pragma assert the invariants modified by a callee right after the
+        // call, even if the callee checks them itself.
+        let assert_after_calls = target
+            .func_env
+            .is_pragma_true(ASSERT_INVARIANTS_AFTER_CALLS_PRAGMA, || false);
+
         let inv_ro = &inv_applicability.accessed;
-        let (inv_rw_return, inv_rw_normal): (BTreeSet<_>, BTreeSet<_>) =
+        let (inv_rw_return, mut inv_rw_normal): (BTreeSet<_>, BTreeSet<_>) =
             if check_suspendable_inv_on_return {
                 inv_applicability
                     .direct_modified
@@ -269,6 +276,9 @@ impl PerFunctionRelevance {
             } else {
                 (BTreeSet::new(), inv_applicability.direct_modified.clone())
             };
+        if assert_after_calls {
+            inv_rw_normal.extend(inv_applicability.modified.difference(&inv_rw_return));
+        }
 
         // collect invariant applicability and instantiation information per bytecode, i.e.,
         // - which invariants should be instrumented after each instruction and
@@ -287,20 +297,32 @@ impl PerFunctionRelevance {
                     Function(mid, fid, inst) => {
                         let callee_fid = mid.qualified(*fid);
                         get_callee_memory_usage_for_invariant_instrumentation(
-                            env, targets, callee_fid, inst,
+                            env,
+                            targets,
+                            callee_fid,
+                            inst,
+                            assert_after_calls,
                         )
                     }
                     OpaqueCallBegin(mid, fid, inst) => {
                         let callee_fid = mid.qualified(*fid);
                         let (mem_ro, _) = get_callee_memory_usage_for_invariant_instrumentation(
-                            env, targets, callee_fid, inst,
+                            env,
+                            targets,
+                            callee_fid,
+                            inst,
+                            assert_after_calls,
                         );
                         (mem_ro, BTreeSet::new())
                     }
                     OpaqueCallEnd(mid, fid, inst) => {
                         let callee_fid = mid.qualified(*fid);
                         let (_, mem_rw) = get_callee_memory_usage_for_invariant_instrumentation(
-                            env, targets, callee_fid, inst,
+                            env,
+                            targets,
+                            callee_fid,
+                            inst,
+                            assert_after_calls,
                         );
                         (BTreeSet::new(), mem_rw)
                     }
@@ -526,6 +548,7 @@ fn get_callee_memory_usage_for_invariant_instrumentation(
     targets: &FunctionTargetsHolder,
     callee_fid: QualifiedId<FunId>,
     callee_inst: &[Type],
+    assert_after_call: bool,
 ) -> (
     BTreeSet<QualifiedInstId<StructId>>, // memory constitute to entry-point assumptions
     BTreeSet<QualifiedInstId<StructId>>, // memory constitute to in-line or exit-point assertions
@@ -545,7 +568,7 @@ fn get_callee_memory_usage_for_invariant_instrumentation(
     // - if a function `F` is inlined, then all its callee might be inlined as well and
     //   it is important to assume the invariants for them.
     let all_accessed = callee_usage.accessed.get_all_inst(callee_inst);
-    if inv_analysis.fun_set_with_no_inv_check.contains(&callee_fid) {
+    if assert_after_call || inv_analysis.fun_set_with_no_inv_check.contains(&callee_fid) {
         let mem_rw = callee_usage.modified.get_all_inst(callee_inst);
         let mem_ro = all_accessed.difference(&mem_rw).cloned().collect();
         (mem_ro, mem_rw)
@@ -1,7 +1,13 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-// Instrumentation pass which injects global invariants into the bytecode.
+//! Instrumentation pass which injects global invariants into the bytecode.
+//!
+//! The schedule is computed by `global_invariant_analysis` from the memory each function
+//! touches: invariants over memory the function reads are assumed at entry, and invariants over
+//! memory it modifies are asserted after each update of that memory, or before returning if the
+//! function defers the check to its exit. Update invariants additionally get the memory they
+//! refer to in `old` expressions saved before the update.
 
 use crate::{
     function_data_builder::FunctionDataBuilder,
@@ -18,6 +18,9 @@
 //! can prove that `spec always_abort { ensures 1 == 2; }`. If this function aborts unconditionally,
 //! any post-condition can be proved. Checking of this behavior is turned-off by default, and can
 //! be enabled with the `unconditional-abort-as-inconsistency` flag.
+//!
+//! With the `smoke-test` flag, the backend additionally reports the assumptions from which the
+//! instrumented `assert false` could be proved, pointing to the contradicting specifications.
 
 use crate::{
     function_data_builder::FunctionDataBuilder,
@@ -55,8 +58,13 @@ impl FunctionTargetProcessor for InconsistencyCheckInstrumenter {
         }
         let flavor = match &data.variant {
             FunctionVariant::Baseline
-            | FunctionVariant::Verification(VerificationFlavor::Inconsistency(..)) => {
-                // instrumentation only applies to non-inconsistency verification variants
+            | FunctionVariant::Named(..)
+            | FunctionVariant::Verification(VerificationFlavor::Inconsistency(..))
+            | FunctionVariant::Verification(VerificationFlavor::Vacuity(..))
+            | FunctionVariant::Verification(VerificationFlavor::Split(..))
+            | FunctionVariant::Verification(VerificationFlavor::Equivalence) => {
+                // instrumentation only applies to regular verification variants, split variants
+                // having the same assumptions as the variant they are split from
                 return data;
             }
             FunctionVariant::Verification(flavor) => flavor.clone(),
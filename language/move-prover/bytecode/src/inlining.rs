@@ -0,0 +1,293 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Inlining of calls to small functions.
+//!
+//! A call is replaced by the code of the called function if the callee declares `pragma inline`,
+//! or if it does not declare the pragma and has at most as many instructions as given by the
+//! `inline_threshold` prover option. Natives, intrinsics, opaque functions, and functions with
+//! specification blocks in their code are never inlined, nor are recursive calls.
+//!
+//! The processor is run ahead of any other transformation. As functions are processed with
+//! their callees first, calls within an inlined function have already been inlined themselves.
+//! The locals of the callee become fresh locals of the caller, described by their names in the
+//! callee, the parameters being assigned from the arguments of the call, and returns are
+//! replaced by assignments to the destinations of the call followed by a jump to the code after
+//! it. The inlined instructions have fresh attribute ids, with the code offset of the call and
+//! the locations they have in the callee, expanded at the call, so diagnostics for inlined code
+//! also point to the call.
+//!
+//! Functions calling each other recursively see each other's code as it was before inlining,
+//! which is kept in a named variant while the processor runs. Otherwise, the code inlined for
+//! a call within a cycle would depend on which function of the cycle is processed first.
+
+use crate::{
+    function_data_builder::FunctionDataBuilder,
+    function_target::FunctionData,
+    function_target_pipeline::{FunctionTargetProcessor, FunctionTargetsHolder, FunctionVariant},
+    options::ProverOptions,
+    stackless_bytecode::{AssignKind, AttrId, Bytecode, Operation},
+};
+use move_model::{
+    ast::TempIndex,
+    exp_generator::ExpGenerator,
+    model::{FunId, FunctionEnv, GlobalEnv, QualifiedId},
+    pragmas::INLINE_PRAGMA,
+    ty::Type,
+};
+use std::collections::BTreeMap;
+
+/// The prefix of the names of the variants which keep the code of recursive functions before
+/// inlining.
+const UNINLINED_VARIANT: &str = "uninlined";
+
+pub struct InliningProcessor {}
+
+impl InliningProcessor {
+    pub fn new() -> Box<Self> {
+        Box::new(InliningProcessor {})
+    }
+}
+
+impl FunctionTargetProcessor for InliningProcessor {
+    fn process(
+        &self,
+        targets: &mut FunctionTargetsHolder,
+        func_env: &FunctionEnv<'_>,
+        data: FunctionData,
+    ) -> FunctionData {
+        if func_env.is_native() || matches!(data.variant, FunctionVariant::Named(..)) {
+            return data;
+        }
+        let mut builder = FunctionDataBuilder::new(func_env, data);
+        let code = std::mem::take(&mut builder.data.code);
+        for bc in code {
+            match bc {
+                Bytecode::Call(
+                    attr_id,
+                    dests,
+                    Operation::Function(mid, fid, type_args),
+                    srcs,
+                    None,
+                ) if Self::should_inline(targets, func_env, mid.qualified(fid)) => {
+                    Self::inline_call(
+                        &mut builder,
+                        targets,
+                        attr_id,
+                        mid.qualified(fid),
+                        &type_args,
+                        dests,
+                        srcs,
+                    );
+                }
+                _ => builder.emit(bc),
+            }
+        }
+        builder.data
+    }
+
+    fn name(&self) -> String {
+        "inlining".to_string()
+    }
+
+    fn initialize(&self, env: &GlobalEnv, targets: &mut FunctionTargetsHolder) {
+        let groups = targets.get_funs_bottom_up(env);
+        for (index, group) in groups.iter().enumerate() {
+            if group.funs.len() > 1 {
+                let variant = Self::uninlined(index);
+                for fun_id in &group.funs {
+                    targets.copy_variant(fun_id, &FunctionVariant::Baseline, variant.clone());
+                }
+            }
+        }
+    }
+
+    fn finalize(&self, _env: &GlobalEnv, targets: &mut FunctionTargetsHolder) {
+        for (fun_id, variant) in targets.get_funs_and_variants().collect::<Vec<_>>() {
+            if Self::is_uninlined(&variant) {
+                targets.remove_target_data(&fun_id, &variant);
+            }
+        }
+    }
+}
+
+impl InliningProcessor {
+    /// Returns the variant which keeps the code before inlining for the functions of the cycle
+    /// with the given index.
+    fn uninlined(index: usize) -> FunctionVariant {
+        FunctionVariant::Named(format!("{}_{}", UNINLINED_VARIANT, index))
+    }
+
+    fn is_uninlined(variant: &FunctionVariant) -> bool {
+        matches!(variant, FunctionVariant::Named(name) if name.starts_with(UNINLINED_VARIANT))
+    }
+
+    /// Returns the code of the callee which is inlined. If the caller and the callee are part
+    /// of the same cycle of recursive calls, this is the code of the callee before inlining.
+    fn callee_data<'a>(
+        targets: &'a FunctionTargetsHolder,
+        func_env: &FunctionEnv<'_>,
+        callee: &QualifiedId<FunId>,
+    ) -> Option<&'a FunctionData> {
+        targets
+            .get_target_variants(func_env)
+            .iter()
+            .filter(|variant| Self::is_uninlined(variant))
+            .find_map(|variant| targets.get_data(callee, variant))
+            .or_else(|| targets.get_data(callee, &FunctionVariant::Baseline))
+    }
+
+    /// Determines whether calls from the given function to the callee are inlined.
+    fn should_inline(
+        targets: &FunctionTargetsHolder,
+        func_env: &FunctionEnv<'_>,
+        callee: QualifiedId<FunId>,
+    ) -> bool {
+        if callee == func_env.get_qualified_id() {
+            return false;
+        }
+        let callee_env = func_env.module_env.env.get_function(callee);
+        if callee_env.is_native_or_intrinsic() || callee_env.is_opaque() {
+            return false;
+        }
+        let callee_data = match Self::callee_data(targets, func_env, &callee) {
+            Some(data) => data,
+            None => return false,
+        };
+        // Specification blocks in the callee would need to be instantiated at the call, and
+        // loop invariants among them would lose their meaning; such callees are not inlined.
+        if callee_data
+            .code
+            .iter()
+            .any(|bc| matches!(bc, Bytecode::Prop(..)))
+        {
+            return false;
+        }
+        let options = ProverOptions::get(func_env.module_env.env);
+        callee_env.is_pragma_true(INLINE_PRAGMA, || {
+            options
+                .inline_threshold
+                .map(|threshold| callee_data.code.len() <= threshold)
+                .unwrap_or(false)
+        })
+    }
+
+    /// Emits the code of the callee in place of the call with the given attribute id.
+    fn inline_call(
+        builder: &mut FunctionDataBuilder<'_>,
+        targets: &FunctionTargetsHolder,
+        call_attr_id: AttrId,
+        callee: QualifiedId<FunId>,
+        type_args: &[Type],
+        dests: Vec<TempIndex>,
+        srcs: Vec<TempIndex>,
+    ) {
+        let env = builder.global_env();
+        let callee_env = env.get_function(callee);
+        let callee_data = Self::callee_data(targets, builder.fun_env, &callee)
+            .expect("function data of inlined callee");
+        if callee.module_id == builder.fun_env.module_env.get_id() {
+            for sid in &callee_data.acquires_global_resources {
+                if !builder.data.acquires_global_resources.contains(sid) {
+                    builder.data.acquires_global_resources.push(*sid);
+                }
+            }
+        }
+
+        // All inlined instructions derive from the offset of the call in the caller.
+        builder.set_loc_from_attr(call_attr_id);
+        let call_loc = builder.get_loc(call_attr_id);
+        let temps = callee_data
+            .local_types
+            .iter()
+            .map(|ty| builder.new_temp(ty.instantiate(type_args)))
+            .collect::<Vec<_>>();
+        let labels = callee_data
+            .code
+            .iter()
+            .filter_map(|bc| match bc {
+                Bytecode::Label(_, label) => Some((*label, builder.new_label())),
+                _ => None,
+            })
+            .collect::<BTreeMap<_, _>>();
+        let exit_label = builder.new_label();
+
+        // Describe the fresh temporaries by the locals of the callee they stand for.
+        for (idx, temp) in temps.iter().enumerate() {
+            let desc = if !callee_env.is_temporary(idx) {
+                Some(format!(
+                    "{}::{}",
+                    callee_env.get_full_name_str(),
+                    callee_env.get_local_name(idx).display(env.symbol_pool())
+                ))
+            } else {
+                callee_data.temp_descriptions.get(&idx).cloned()
+            };
+            if let Some(desc) = desc {
+                builder.set_temp_description(*temp, desc);
+            }
+        }
+
+        // Assign the arguments of the call to the parameters.
+        let mut comment = Some(format!(
+            "inlined call of {}",
+            callee_env.get_full_name_str()
+        ));
+        for (param, src) in srcs.into_iter().enumerate() {
+            let attr_id = Self::new_attr(builder, &mut comment);
+            builder.emit(Bytecode::Assign(
+                attr_id,
+                temps[param],
+                src,
+                AssignKind::Move,
+            ));
+        }
+
+        for bc in &callee_data.code {
+            match bc {
+                Bytecode::Ret(_, rets) => {
+                    for (dest, ret) in dests.iter().zip(rets) {
+                        let attr_id = Self::new_attr(builder, &mut comment);
+                        builder.emit(Bytecode::Assign(
+                            attr_id,
+                            *dest,
+                            temps[*ret],
+                            AssignKind::Move,
+                        ));
+                    }
+                    let attr_id = Self::new_attr(builder, &mut comment);
+                    builder.emit(Bytecode::Jump(attr_id, exit_label));
+                }
+                _ => {
+                    let attr_id = Self::new_attr(builder, &mut comment);
+                    if let Some(loc) = callee_data.locations.get(&bc.get_attr_id()) {
+                        builder
+                            .data
+                            .locations
+                            .insert(attr_id, loc.with_expansion_site(&call_loc));
+                    }
+                    let bc = bc
+                        .instantiate(env, type_args)
+                        .with_attr_id(attr_id)
+                        .rename_labels(&|label| labels[&label]);
+                    let bc = {
+                        let target = builder.get_target();
+                        bc.remap_all_vars(&target, &mut |temp| temps[temp])
+                    };
+                    builder.emit(bc);
+                }
+            }
+        }
+        builder.emit_with(|attr_id| Bytecode::Label(attr_id, exit_label));
+    }
+
+    /// Creates an attribute id for an inlined instruction, associating it with the debug
+    /// comment if the comment has not been consumed yet.
+    fn new_attr(builder: &mut FunctionDataBuilder<'_>, comment: &mut Option<String>) -> AttrId {
+        let attr_id = builder.new_attr();
+        if let Some(comment) = comment.take() {
+            builder.data.debug_comments.insert(attr_id, comment);
+        }
+        attr_id
+    }
+}
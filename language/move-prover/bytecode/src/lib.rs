@@ -4,19 +4,26 @@
 #![forbid(unsafe_code)]
 
 use crate::function_target_pipeline::FunctionTargetsHolder;
-use move_model::model::GlobalEnv;
+use move_model::model::{FunctionEnv, GlobalEnv};
 
+pub mod abort_inference;
+pub mod abort_path_analysis;
+pub mod access_matrix;
 pub mod access_path;
 pub mod access_path_trie;
 pub mod annotations;
 pub mod borrow_analysis;
 pub mod clean_and_optimize;
 pub mod compositional_analysis;
+pub mod condition_splitting;
+pub mod constant_propagation;
 pub mod data_invariant_instrumentation;
 pub mod dataflow_analysis;
 pub mod dataflow_domains;
+pub mod dead_code_elimination;
 pub mod debug_instrumentation;
 pub mod eliminate_imm_refs;
+pub mod equivalence_check;
 pub mod escape_analysis;
 pub mod function_data_builder;
 pub mod function_target;
@@ -26,22 +33,32 @@ pub mod global_invariant_instrumentation;
 pub mod global_invariant_instrumentation_v2;
 pub mod graph;
 pub mod inconsistency_check;
+pub mod inlining;
+pub mod liveness_analysis;
 pub mod livevar_analysis;
 pub mod loop_analysis;
+pub mod loop_unrolling;
 pub mod memory_instrumentation;
 pub mod mono_analysis;
 pub mod mut_ref_instrumentation;
 pub mod mutation_tester;
+pub mod native_semantics;
 pub mod options;
 pub mod packed_types_analysis;
 pub mod pipeline_factory;
 pub mod reaching_def_analysis;
 pub mod read_write_set_analysis;
+pub mod recursion_analysis;
+pub mod spec_coverage_analysis;
 pub mod spec_instrumentation;
+pub mod spec_var_frame_analysis;
 pub mod stackless_bytecode;
 pub mod stackless_bytecode_generator;
 pub mod stackless_control_flow_graph;
+pub mod state_model;
+pub mod termination_instrumentation;
 pub mod usage_analysis;
+pub mod vacuity_check;
 pub mod verification_analysis;
 pub mod verification_analysis_v2;
 pub mod well_formed_instrumentation;
@@ -52,10 +69,23 @@ pub fn print_targets_for_test(
     header: &str,
     targets: &FunctionTargetsHolder,
 ) -> String {
+    print_targets_with_filter(env, header, targets, |_| true)
+}
+
+/// Print the function targets of those functions for which the filter holds.
+pub fn print_targets_with_filter<F>(
+    env: &GlobalEnv,
+    header: &str,
+    targets: &FunctionTargetsHolder,
+    filter: F,
+) -> String
+where
+    F: Fn(&FunctionEnv<'_>) -> bool,
+{
     let mut text = String::new();
     text.push_str(&format!("============ {} ================\n", header));
     for module_env in env.get_modules() {
-        for func_env in module_env.get_functions() {
+        for func_env in module_env.get_functions().filter(|f| filter(f)) {
             for (variant, target) in targets.get_targets(&func_env) {
                 if !target.data.code.is_empty() || target.func_env.is_native_or_intrinsic() {
                     target.register_annotation_formatters_for_test();
@@ -0,0 +1,199 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+// Liveness analysis.
+//
+// This computes for each code offset the temporaries which are live before and after the
+// instruction at the offset, i.e. which may be read on some path before being overwritten.
+// Unlike livevar_analysis, this does not transform the code, and an assignment reads its source
+// regardless of whether its destination is live, so the result describes the code as is and can
+// be used by any phase which needs liveness.
+
+use crate::{
+    dataflow_analysis::{DataflowAnalysis, TransferFunctions},
+    dataflow_domains::{AbstractDomain, JoinResult},
+    function_target::{FunctionData, FunctionTarget},
+    function_target_pipeline::{FunctionTargetProcessor, FunctionTargetsHolder},
+    stackless_bytecode::{AbortAction, Bytecode},
+};
+use itertools::Itertools;
+use move_binary_format::file_format::CodeOffset;
+use move_model::{ast::TempIndex, model::FunctionEnv};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The temporaries live before and after an instruction.
+#[derive(Debug, Default, Clone)]
+pub struct LivenessInfo {
+    pub before: BTreeSet<TempIndex>,
+    pub after: BTreeSet<TempIndex>,
+}
+
+/// The annotation for liveness analysis, mapping each code offset to the temporaries live at it.
+#[derive(Debug, Default, Clone)]
+pub struct LivenessAnnotation(BTreeMap<CodeOffset, LivenessInfo>);
+
+impl LivenessAnnotation {
+    pub fn get_info_at(&self, code_offset: CodeOffset) -> Option<&LivenessInfo> {
+        self.0.get(&code_offset)
+    }
+
+    /// Returns true if the temporary is live after the instruction at the code offset.
+    pub fn is_live_after(&self, code_offset: CodeOffset, temp: TempIndex) -> bool {
+        self.0
+            .get(&code_offset)
+            .map(|info| info.after.contains(&temp))
+            .unwrap_or(false)
+    }
+}
+
+/// Get the liveness annotation of a function target which has been analyzed.
+pub fn get_liveness<'env>(target: &FunctionTarget<'env>) -> &'env LivenessAnnotation {
+    target
+        .get_annotations()
+        .get::<LivenessAnnotation>()
+        .expect("Invariant violation: target not analyzed")
+}
+
+pub struct LivenessAnalysisProcessor {}
+
+impl LivenessAnalysisProcessor {
+    pub fn new() -> Box<Self> {
+        Box::new(LivenessAnalysisProcessor {})
+    }
+
+    /// Computes the liveness of the temporaries of the function target in the given code.
+    pub fn analyze(func_target: &FunctionTarget, code: &[Bytecode]) -> LivenessAnnotation {
+        let analyzer = LivenessAnalysis { func_target };
+        let initial_state = LivenessState {
+            live: BTreeSet::new(),
+        };
+        LivenessAnnotation(analyzer.analyze_code(initial_state, code, |before, after| {
+            LivenessInfo {
+                before: before.live.clone(),
+                after: after.live.clone(),
+            }
+        }))
+    }
+}
+
+impl FunctionTargetProcessor for LivenessAnalysisProcessor {
+    fn process(
+        &self,
+        _targets: &mut FunctionTargetsHolder,
+        func_env: &FunctionEnv<'_>,
+        mut data: FunctionData,
+    ) -> FunctionData {
+        if func_env.is_native() {
+            // Native functions have no byte code.
+            return data;
+        }
+        let func_target = FunctionTarget::new(func_env, &data);
+        let annotation = Self::analyze(&func_target, &data.code);
+        data.annotations.set(annotation);
+        data
+    }
+
+    fn name(&self) -> String {
+        "liveness_analysis".to_string()
+    }
+}
+
+struct LivenessAnalysis<'a> {
+    func_target: &'a FunctionTarget<'a>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, PartialOrd)]
+struct LivenessState {
+    live: BTreeSet<TempIndex>,
+}
+
+impl LivenessState {
+    fn kill(&mut self, temps: &[TempIndex]) {
+        for temp in temps {
+            self.live.remove(temp);
+        }
+    }
+
+    fn gen(&mut self, temps: &[TempIndex]) {
+        self.live.extend(temps.iter().cloned());
+    }
+}
+
+impl<'a> TransferFunctions for LivenessAnalysis<'a> {
+    type State = LivenessState;
+    const BACKWARD: bool = true;
+
+    fn execute(&self, state: &mut LivenessState, instr: &Bytecode, _idx: CodeOffset) {
+        use Bytecode::*;
+        match instr {
+            Assign(_, dest, src, _) => {
+                state.kill(&[*dest]);
+                state.gen(&[*src]);
+            }
+            Load(_, dest, _) => {
+                state.kill(&[*dest]);
+            }
+            Call(_, dests, _, srcs, on_abort) => {
+                state.kill(dests);
+                state.gen(srcs);
+                if let Some(AbortAction(_, dest)) = on_abort {
+                    state.kill(&[*dest]);
+                }
+            }
+            Ret(_, srcs) => {
+                state.gen(srcs);
+            }
+            Abort(_, src) | Branch(_, _, _, src) => {
+                state.gen(&[*src]);
+            }
+            Prop(_, _, exp) => {
+                for (temp, _) in exp.used_temporaries(self.func_target.global_env()) {
+                    state.gen(&[temp]);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl<'a> DataflowAnalysis for LivenessAnalysis<'a> {}
+
+impl AbstractDomain for LivenessState {
+    fn join(&mut self, other: &Self) -> JoinResult {
+        let old_len = self.live.len();
+        self.live.extend(other.live.iter().cloned());
+        if self.live.len() == old_len {
+            JoinResult::Unchanged
+        } else {
+            JoinResult::Changed
+        }
+    }
+}
+
+// =================================================================================================
+// Formatting
+
+/// Format a liveness annotation.
+pub fn format_liveness_annotation(
+    target: &FunctionTarget<'_>,
+    code_offset: CodeOffset,
+) -> Option<String> {
+    let LivenessAnnotation(map) = target.get_annotations().get::<LivenessAnnotation>()?;
+    let info = map.get(&code_offset)?;
+    let names = |temps: &BTreeSet<TempIndex>| {
+        temps
+            .iter()
+            .map(|temp| {
+                target
+                    .get_local_name(*temp)
+                    .display(target.symbol_pool())
+                    .to_string()
+            })
+            .join(", ")
+    };
+    Some(format!(
+        "live before: {{{}}}, after: {{{}}}",
+        names(&info.before),
+        names(&info.after)
+    ))
+}
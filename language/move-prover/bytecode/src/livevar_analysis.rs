@@ -10,7 +10,6 @@ use crate::{
     function_target::{FunctionData, FunctionTarget},
     function_target_pipeline::{FunctionTargetProcessor, FunctionTargetsHolder},
     stackless_bytecode::{AbortAction, AttrId, Bytecode, Label, Operation},
-    stackless_control_flow_graph::StacklessControlFlowGraph,
 };
 use itertools::Itertools;
 use move_binary_format::file_format::CodeOffset;
@@ -76,13 +75,7 @@ impl FunctionTargetProcessor for LiveVarAnalysisProcessor {
 
         // Eliminate unused locals after dead code elimination.
         let (code, local_types, remap) = Self::eliminate_unused_vars(&func_target, code);
-        data.rename_vars(&|idx| {
-            if let Some(new_idx) = remap.get(&idx) {
-                *new_idx
-            } else {
-                idx
-            }
-        });
+        data.rename_vars(&|idx| remap.get(&idx).cloned());
         data.local_types = local_types;
         data.code = code;
 
@@ -119,18 +112,11 @@ impl LiveVarAnalysisProcessor {
         func_target: &FunctionTarget,
         code: &[Bytecode],
     ) -> BTreeMap<CodeOffset, LiveVarInfoAtCodeOffset> {
-        // Perform backward analysis from all blocks just in case some block
-        // cannot reach an exit block
-        let cfg = StacklessControlFlowGraph::new_backward(code, true);
         let analyzer = LiveVarAnalysis::new(func_target, 0, 0);
-        let state_map = analyzer.analyze_function(
-            LiveVarState {
-                livevars: BTreeSet::new(),
-            },
-            code,
-            &cfg,
-        );
-        analyzer.state_per_instruction(state_map, code, &cfg, |before, after| {
+        let initial_state = LiveVarState {
+            livevars: BTreeSet::new(),
+        };
+        analyzer.analyze_code(initial_state, code, |before, after| {
             LiveVarInfoAtCodeOffset {
                 before: before.livevars.clone(),
                 after: after.livevars.clone(),
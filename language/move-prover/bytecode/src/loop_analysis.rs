@@ -10,6 +10,7 @@ use crate::{
     stackless_bytecode::{AttrId, Bytecode, HavocKind, Label, Operation, PropKind},
     stackless_control_flow_graph::{BlockContent, BlockId, StacklessControlFlowGraph},
 };
+use itertools::Itertools;
 use move_binary_format::file_format::CodeOffset;
 use move_model::{
     ast::{self, TempIndex},
@@ -63,6 +64,111 @@ impl LoopAnnotation {
     }
 }
 
+/// A natural loop of the bytecode, merged with the other natural loops which share its header.
+#[derive(Debug, Clone)]
+pub struct LoopInfo {
+    /// The label at the start of the loop header.
+    pub header: Label,
+    /// The offsets of the instructions which branch back to the header.
+    pub back_edges: BTreeSet<CodeOffset>,
+    /// The offsets of the instructions in the loop, including the header and the back edges.
+    pub body: BTreeSet<CodeOffset>,
+}
+
+/// The annotation for loop detection, holding the loops of the function ordered by header.
+#[derive(Debug, Clone, Default)]
+pub struct LoopsAnnotation {
+    pub loops: Vec<LoopInfo>,
+}
+
+/// Detects the natural loops of a function and records them as an annotation, without
+/// transforming the code. Unlike `LoopAnalysisProcessor`, which eliminates loops, this can be
+/// run by any phase which needs to know about loops, e.g. to instrument or unroll them.
+pub struct LoopDetectionProcessor {}
+
+impl LoopDetectionProcessor {
+    pub fn new() -> Box<Self> {
+        Box::new(LoopDetectionProcessor {})
+    }
+
+    /// Computes the loops of the given code.
+    pub fn analyze(code: &[Bytecode]) -> LoopsAnnotation {
+        let cfg = StacklessControlFlowGraph::new_forward(code);
+        let mut fat_headers = BTreeMap::new();
+        for single_loop in natural_loops(&cfg) {
+            fat_headers
+                .entry(single_loop.loop_header)
+                .or_insert_with(Vec::new)
+                .push(single_loop);
+        }
+        let loops = fat_headers
+            .into_iter()
+            .map(|(header, sub_loops)| {
+                let header = header_label(code, &cfg, header);
+                let back_edges =
+                    LoopAnalysisProcessor::collect_loop_back_edges(code, &cfg, header, &sub_loops);
+                let body = sub_loops
+                    .iter()
+                    .flat_map(|l| l.loop_body.iter())
+                    .filter_map(|block_id| cfg.instr_indexes(*block_id))
+                    .flatten()
+                    .collect();
+                LoopInfo {
+                    header,
+                    back_edges,
+                    body,
+                }
+            })
+            .collect();
+        LoopsAnnotation { loops }
+    }
+}
+
+impl FunctionTargetProcessor for LoopDetectionProcessor {
+    fn process(
+        &self,
+        _targets: &mut FunctionTargetsHolder,
+        func_env: &FunctionEnv<'_>,
+        mut data: FunctionData,
+    ) -> FunctionData {
+        if func_env.is_native() {
+            return data;
+        }
+        let annotation = Self::analyze(&data.code);
+        data.annotations.set(annotation);
+        data
+    }
+
+    fn name(&self) -> String {
+        "loop_detection".to_string()
+    }
+}
+
+/// Computes the natural loops of the control flow graph, one for each back edge.
+fn natural_loops(cfg: &StacklessControlFlowGraph) -> Vec<NaturalLoop<BlockId>> {
+    let entry = cfg.entry_block();
+    let nodes = cfg.blocks();
+    let edges: Vec<(BlockId, BlockId)> = nodes
+        .iter()
+        .flat_map(|x| cfg.successors(*x).iter().map(move |y| (*x, *y)))
+        .collect();
+    let graph = Graph::new(entry, nodes, edges);
+    graph
+        .compute_reducible()
+        .expect("A well-formed Move function is expected to have a reducible control-flow graph")
+}
+
+/// Returns the label which starts the header block of a loop.
+fn header_label(code: &[Bytecode], cfg: &StacklessControlFlowGraph, header: BlockId) -> Label {
+    match cfg.content(header) {
+        BlockContent::Dummy => panic!("A loop header should never be a dummy block"),
+        BlockContent::Basic { lower, upper: _ } => match code[*lower as usize] {
+            Bytecode::Label(_, label) => label,
+            _ => panic!("A loop header block is expected to start with a Label bytecode"),
+        },
+    }
+}
+
 pub struct LoopAnalysisProcessor {}
 
 impl LoopAnalysisProcessor {
@@ -380,26 +486,10 @@ impl LoopAnalysisProcessor {
         let func_target = FunctionTarget::new(func_env, data);
         let code = func_target.get_bytecode();
         let cfg = StacklessControlFlowGraph::new_forward(code);
-        let entry = cfg.entry_block();
-        let nodes = cfg.blocks();
-        let edges: Vec<(BlockId, BlockId)> = nodes
-            .iter()
-            .map(|x| {
-                cfg.successors(*x)
-                    .iter()
-                    .map(|y| (*x, *y))
-                    .collect::<Vec<(BlockId, BlockId)>>()
-            })
-            .flatten()
-            .collect();
-        let graph = Graph::new(entry, nodes, edges);
-        let natural_loops = graph.compute_reducible().expect(
-            "A well-formed Move function is expected to have a reducible control-flow graph",
-        );
 
         // collect shared headers from loops
         let mut fat_headers = BTreeMap::new();
-        for single_loop in natural_loops {
+        for single_loop in natural_loops(&cfg) {
             fat_headers
                 .entry(single_loop.loop_header)
                 .or_insert_with(Vec::new)
@@ -410,13 +500,7 @@ impl LoopAnalysisProcessor {
         let mut fat_loops = BTreeMap::new();
         for (fat_root, sub_loops) in fat_headers {
             // get the label of the scc root
-            let label = match cfg.content(fat_root) {
-                BlockContent::Dummy => panic!("A loop header should never be a dummy block"),
-                BlockContent::Basic { lower, upper: _ } => match code[*lower as usize] {
-                    Bytecode::Label(_, label) => label,
-                    _ => panic!("A loop header block is expected to start with a Label bytecode"),
-                },
-            };
+            let label = header_label(code, &cfg, fat_root);
 
             let invariants = Self::collect_loop_invariants(&cfg, &func_target, fat_root);
             let (val_targets, mut_targets) =
@@ -454,3 +538,34 @@ impl LoopAnalysisProcessor {
         LoopAnnotation { fat_loops }
     }
 }
+
+// =================================================================================================
+// Formatting
+
+/// Format a loops annotation, at the headers and back edges of the loops.
+pub fn format_loops_annotation(
+    target: &FunctionTarget<'_>,
+    code_offset: CodeOffset,
+) -> Option<String> {
+    let annotation = target.get_annotations().get::<LoopsAnnotation>()?;
+    let texts = annotation
+        .loops
+        .iter()
+        .filter_map(|info| match &target.get_bytecode()[code_offset as usize] {
+            Bytecode::Label(_, label) if *label == info.header => Some(format!(
+                "loop header, back edges: {{{}}}, body: {{{}}}",
+                info.back_edges.iter().join(", "),
+                info.body.iter().join(", ")
+            )),
+            _ if info.back_edges.contains(&code_offset) => {
+                Some(format!("back edge to L{}", info.header.as_usize()))
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if texts.is_empty() {
+        None
+    } else {
+        Some(texts.join("\n"))
+    }
+}
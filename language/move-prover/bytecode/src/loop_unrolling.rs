@@ -0,0 +1,180 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Bounded loop unrolling.
+//!
+//! This unrolls the loops of a function a bounded number of times, given by `pragma unroll`
+//! of the function or module, or by the `loop_unroll` prover option. Loops are unrolled from
+//! the inside out: the body of a loop is copied once for each additional iteration, with the
+//! back edges of each copy branching to the header of the next, and the back edges of the last
+//! copy branching to a block which stops verification. Hence no invariants are needed for the
+//! unrolled loops, but executions which iterate a loop more often than the bound are not
+//! verified. Loop invariants of unrolled loops become plain assertions at the start of each
+//! iteration.
+//!
+//! The copies are appended to the code. Their instructions have fresh attribute ids, with the
+//! locations and code offsets of the instructions they are copied from.
+
+use crate::{
+    function_data_builder::{FunctionDataBuilder, FunctionDataBuilderOptions},
+    function_target::FunctionData,
+    function_target_pipeline::{FunctionTargetProcessor, FunctionTargetsHolder},
+    loop_analysis::{LoopDetectionProcessor, LoopInfo},
+    options::ProverOptions,
+    stackless_bytecode::{Bytecode, Operation},
+};
+use move_binary_format::file_format::CodeOffset;
+use move_model::{model::FunctionEnv, pragmas::UNROLL_PRAGMA};
+use std::collections::BTreeMap;
+
+pub struct LoopUnrollingProcessor {}
+
+impl LoopUnrollingProcessor {
+    pub fn new() -> Box<Self> {
+        Box::new(LoopUnrollingProcessor {})
+    }
+}
+
+impl FunctionTargetProcessor for LoopUnrollingProcessor {
+    fn process(
+        &self,
+        _targets: &mut FunctionTargetsHolder,
+        func_env: &FunctionEnv<'_>,
+        mut data: FunctionData,
+    ) -> FunctionData {
+        if func_env.is_native() {
+            return data;
+        }
+        let options = ProverOptions::get(func_env.module_env.env);
+        let bound =
+            func_env.get_num_pragma(UNROLL_PRAGMA, || options.loop_unroll.unwrap_or_default());
+        if bound == 0 {
+            return data;
+        }
+        loop {
+            let loops = LoopDetectionProcessor::analyze(&data.code).loops;
+            let label_offsets = Bytecode::label_offsets(&data.code);
+            // Unroll an innermost loop, i.e. one which contains no other loop header.
+            let innermost = loops.iter().find(|info| {
+                loops.iter().all(|other| {
+                    other.header == info.header
+                        || !info.body.contains(&label_offsets[&other.header])
+                })
+            });
+            match innermost {
+                Some(info) => data = Self::unroll(func_env, data, info, bound),
+                None => break,
+            }
+        }
+        // The loop invariants have been turned into assertions of the unrolled iterations.
+        data.loop_invariants.clear();
+        data
+    }
+
+    fn name(&self) -> String {
+        "loop_unrolling".to_string()
+    }
+}
+
+impl LoopUnrollingProcessor {
+    /// Unrolls the loop such that its body is executed at most `bound` times.
+    fn unroll(
+        func_env: &FunctionEnv<'_>,
+        data: FunctionData,
+        info: &LoopInfo,
+        bound: usize,
+    ) -> FunctionData {
+        let mut builder = FunctionDataBuilder::new_with_options(
+            func_env,
+            data,
+            FunctionDataBuilderOptions {
+                no_fallthrough_jump_removal: true,
+            },
+        );
+        let code = std::mem::take(&mut builder.data.code);
+
+        // The labels of the headers of the iterations, the first being the original header,
+        // followed by the label of the block reached when the bound is exceeded.
+        let mut headers = vec![info.header];
+        let mut label_maps = vec![BTreeMap::new()];
+        for _ in 1..bound {
+            let label_map = info
+                .body
+                .iter()
+                .filter_map(|offset| match &code[*offset as usize] {
+                    Bytecode::Label(_, label) => Some((*label, builder.new_label())),
+                    _ => None,
+                })
+                .collect::<BTreeMap<_, _>>();
+            headers.push(label_map[&info.header]);
+            label_maps.push(label_map);
+        }
+        let stop_label = builder.new_label();
+        headers.push(stop_label);
+
+        // The original code is the first iteration; its back edges branch to the second.
+        for (offset, bc) in code.iter().enumerate() {
+            if info.back_edges.contains(&(offset as CodeOffset)) {
+                builder.emit(bc.clone().rename_labels(&|label| {
+                    if label == info.header {
+                        headers[1]
+                    } else {
+                        label
+                    }
+                }));
+            } else {
+                builder.emit(bc.clone());
+            }
+        }
+
+        // Append a copy of the body for each further iteration.
+        for (iteration, label_map) in label_maps.iter().enumerate().skip(1) {
+            for offset in &info.body {
+                let bc = &code[*offset as usize];
+                let attr_id = bc.get_attr_id();
+                builder.set_loc_from_attr(attr_id);
+                let new_attr_id = builder.new_attr();
+                if let Some(vc_info) = builder.data.vc_infos.get(&attr_id).cloned() {
+                    builder.data.vc_infos.insert(new_attr_id, vc_info);
+                }
+                let is_back_edge = info.back_edges.contains(offset);
+                builder.emit(
+                    bc.clone()
+                        .with_attr_id(new_attr_id)
+                        .rename_labels(&|label| {
+                            if is_back_edge && label == info.header {
+                                headers[iteration + 1]
+                            } else {
+                                *label_map.get(&label).unwrap_or(&label)
+                            }
+                        }),
+                );
+                // Make a fall-through out of the body explicit, as the copy is not followed by
+                // the code the body falls through to.
+                let next = offset + 1;
+                if !bc.is_unconditional_branch()
+                    && !matches!(bc, Bytecode::Branch(..))
+                    && !info.body.contains(&next)
+                {
+                    let next_label = match code.get(next as usize) {
+                        Some(Bytecode::Label(_, label)) => *label,
+                        _ => panic!("A fall-through out of a loop body must lead to a label"),
+                    };
+                    builder.emit_with(|attr_id| Bytecode::Jump(attr_id, next_label));
+                }
+            }
+        }
+
+        // Stop verification of executions which exceed the bound.
+        let header_offset = Bytecode::label_offsets(&code)[&info.header];
+        builder.set_loc_from_attr(code[header_offset as usize].get_attr_id());
+        builder.set_next_debug_comment(format!(
+            "Unrolling bound of the loop with header L{} exceeded",
+            info.header.as_usize()
+        ));
+        builder.emit_with(|attr_id| Bytecode::Label(attr_id, stop_label));
+        builder.clear_next_debug_comment();
+        builder.emit_with(|attr_id| Bytecode::Call(attr_id, vec![], Operation::Stop, vec![], None));
+        builder.data
+    }
+}
@@ -3,7 +3,8 @@
 
 //! Analysis which computes information needed in backends for monomorphization. This
 //! computes the distinct type instantiations in the model for structs and inlined functions.
-//! It also eliminates type quantification (`forall coin_type: type:: P`).
+//! It also eliminates type quantification (`forall coin_type: type:: P`), and resolves the
+//! function overrides registered in `ProverOptions::function_overrides`.
 
 use std::{
     collections::{BTreeMap, BTreeSet},
@@ -11,6 +12,7 @@ use std::{
     rc::Rc,
 };
 
+use codespan_reporting::diagnostic::Severity;
 use itertools::Itertools;
 
 use move_model::{
@@ -26,6 +28,7 @@ use move_model::{
 use crate::{
     function_target::{FunctionData, FunctionTarget},
     function_target_pipeline::{FunctionTargetProcessor, FunctionTargetsHolder, FunctionVariant},
+    options::ProverOptions,
     stackless_bytecode::{Bytecode, Operation},
     usage_analysis::UsageProcessor,
 };
@@ -41,6 +44,11 @@ pub struct MonoInfo {
     pub vec_inst: BTreeSet<Type>,
     pub native_inst: BTreeMap<ModuleId, BTreeSet<Vec<Type>>>,
     pub axioms: Vec<Condition>,
+    /// The functions which are replaced by other functions for verification.
+    pub substitutions: BTreeMap<QualifiedId<FunId>, QualifiedId<FunId>>,
+    /// The substitutions which have been applied, as pairs of a calling function and the
+    /// function replaced in its calls.
+    pub applied_substitutions: BTreeSet<(QualifiedId<FunId>, QualifiedId<FunId>)>,
 }
 
 /// Get the information computed by this analysis.
@@ -145,6 +153,16 @@ impl FunctionTargetProcessor for MonoAnalysisProcessor {
             }
             writeln!(f, "}}")?;
         }
+        for (caller, original) in &info.applied_substitutions {
+            writeln!(
+                f,
+                "fun {} calls {} instead of {}",
+                env.get_function(*caller).get_full_name_str(),
+                env.get_function(info.substitutions[original])
+                    .get_full_name_str(),
+                env.get_function(*original).get_full_name_str()
+            )?;
+        }
 
         Ok(())
     }
@@ -171,6 +189,7 @@ impl MonoAnalysisProcessor {
             done_types: BTreeSet::new(),
             inst_opt: None,
         };
+        analyzer.info.substitutions = Self::resolve_substitutions(env);
         if let Some(axioms) = rewritten_axioms {
             // Analyze newly rewritten axioms.
             for axiom in axioms {
@@ -188,6 +207,61 @@ impl MonoAnalysisProcessor {
         let Analyzer { info, .. } = analyzer;
         env.set_extension(info);
     }
+
+    /// Resolves the function overrides registered in the prover options. Reports an error if
+    /// a function cannot be found or if a replacement has a different signature than the
+    /// function it replaces.
+    fn resolve_substitutions(env: &GlobalEnv) -> BTreeMap<QualifiedId<FunId>, QualifiedId<FunId>> {
+        let options = ProverOptions::get(env);
+        let find = |name: &str| {
+            let fun_env = env.find_function_by_name_str(name);
+            if fun_env.is_none() {
+                env.error(
+                    &env.unknown_loc(),
+                    &format!("cannot find function `{}` of function override", name),
+                );
+            }
+            fun_env
+        };
+        let mut substitutions = BTreeMap::new();
+        for (original, replacement) in &options.function_overrides {
+            let (original_env, replacement_env) = match (find(original), find(replacement)) {
+                (Some(original_env), Some(replacement_env)) => (original_env, replacement_env),
+                _ => continue,
+            };
+            if original_env.get_type_parameter_count() != replacement_env.get_type_parameter_count()
+                || original_env.get_parameter_types() != replacement_env.get_parameter_types()
+                || original_env.get_return_types() != replacement_env.get_return_types()
+            {
+                env.error(
+                    &replacement_env.get_loc(),
+                    &format!(
+                        "function `{}` cannot override `{}` because their signatures differ",
+                        replacement_env.get_full_name_str(),
+                        original_env.get_full_name_str()
+                    ),
+                );
+                continue;
+            }
+            if original_env.is_opaque() {
+                // Calls to opaque functions are replaced by their specification before this
+                // analysis runs.
+                env.diag(
+                    Severity::Warning,
+                    &original_env.get_loc(),
+                    &format!(
+                        "override of opaque function `{}` has no effect",
+                        original_env.get_full_name_str()
+                    ),
+                );
+            }
+            substitutions.insert(
+                original_env.get_qualified_id(),
+                replacement_env.get_qualified_id(),
+            );
+        }
+        substitutions
+    }
 }
 
 struct Analyzer<'a> {
@@ -318,7 +392,7 @@ impl<'a> Analyzer<'a> {
         }
     }
 
-    fn analyze_bytecode(&mut self, _target: &FunctionTarget<'_>, bc: &Bytecode) {
+    fn analyze_bytecode(&mut self, target: &FunctionTarget<'_>, bc: &Bytecode) {
         use Bytecode::*;
         use Operation::*;
         // We only need to analyze function calls, not `pack` or other instructions
@@ -326,7 +400,14 @@ impl<'a> Analyzer<'a> {
         // elsewhere.
         match bc {
             Call(_, _, Function(mid, fid, targs), ..) => {
-                let callee = &self.env.get_module(*mid).into_function(*fid);
+                let mut callee_id = mid.qualified(*fid);
+                if let Some(replacement) = self.info.substitutions.get(&callee_id) {
+                    self.info
+                        .applied_substitutions
+                        .insert((target.func_env.get_qualified_id(), callee_id));
+                    callee_id = *replacement;
+                }
+                let callee = &self.env.get_function(callee_id);
                 let actuals = self.instantiate_vec(targs);
                 if callee.is_native_or_intrinsic() && !actuals.is_empty() {
                     // Mark the associated module to be instantiated with the given actuals.
@@ -340,7 +421,7 @@ impl<'a> Analyzer<'a> {
                 } else if !callee.is_opaque() {
                     // This call needs to be inlined, with targs instantiated by self.inst_opt.
                     // Schedule for later processing if this instance has not been processed yet.
-                    let entry = (mid.qualified(*fid), FunctionVariant::Baseline, actuals);
+                    let entry = (callee_id, FunctionVariant::Baseline, actuals);
                     if !self.done_funs.contains(&entry) {
                         self.todo_funs.push(entry);
                     }
@@ -432,18 +513,47 @@ impl<'a> Analyzer<'a> {
         if !self.done_types.insert(ty.to_owned()) {
             return;
         }
-        ty.visit(&mut |t| match t {
+        match ty {
             Type::Vector(et) => {
+                self.add_type(et);
                 self.info.vec_inst.insert(et.as_ref().clone());
             }
             Type::Struct(mid, sid, targs) => {
-                self.add_struct(self.env.get_module(*mid).into_struct(*sid), targs)
+                let struct_env = self.env.get_module(*mid).into_struct(*sid);
+                for (idx, targ) in targs.iter().enumerate() {
+                    if struct_env.is_phantom_parameter(idx) {
+                        // Phantom type arguments only contribute to the identity of the
+                        // struct type, no values of them are ever constructed. We still
+                        // need to know about the type parameters they mention.
+                        self.add_phantom_type(targ)
+                    } else {
+                        self.add_type(targ)
+                    }
+                }
+                self.add_struct(struct_env, targs)
             }
             Type::TypeParameter(idx) => {
                 self.info.type_params.insert(*idx);
             }
+            Type::Tuple(tys) | Type::Fun(tys, _) => {
+                for t in tys {
+                    self.add_type(t)
+                }
+                if let Type::Fun(_, rt) = ty {
+                    self.add_type(rt)
+                }
+            }
+            Type::Reference(_, t) | Type::TypeDomain(t) => self.add_type(t),
             _ => {}
-        });
+        }
+    }
+
+    fn add_phantom_type(&mut self, ty: &Type) {
+        ty.visit(&mut |t| {
+            if let Type::TypeParameter(idx) = t {
+                self.info.type_params.insert(*idx);
+            }
+        })
     }
 
     fn add_struct(&mut self, struct_: StructEnv<'_>, targs: &[Type]) {
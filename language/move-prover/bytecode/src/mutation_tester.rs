@@ -7,6 +7,8 @@
 //!  other bytecode modification
 //! It emits instructions in bytecode format, but with changes made
 //! Note that this mutation does nothing if mutation flags are not enabled
+//! If mutation is enabled without selecting an operation, the operation is selected based on
+//! the random seed
 
 use crate::{
     function_data_builder::FunctionDataBuilder,
@@ -37,6 +39,48 @@ impl MutationTester {
     }
 }
 
+impl MutationManager {
+    /// Selects the operation to mutate among the arithmetic operations of the verified code,
+    /// derived from the seed. The same seed always selects the same operation, so a run can be
+    /// reproduced, and consecutive seeds select each operation in turn.
+    fn select_by_seed(&mut self, targets: &FunctionTargetsHolder, seed: usize) {
+        // The number of additions, subtractions, multiplications, and divisions.
+        let mut counts = [0; 4];
+        for (fun_id, variant) in targets.get_funs_and_variants() {
+            if !variant.is_verified() {
+                continue;
+            }
+            for bc in &targets.get_data(&fun_id, &variant).unwrap().code {
+                match bc {
+                    Bytecode::Call(_, _, Operation::Add, _, _) => counts[0] += 1,
+                    Bytecode::Call(_, _, Operation::Sub, _, _) => counts[1] += 1,
+                    Bytecode::Call(_, _, Operation::Mul, _, _) => counts[2] += 1,
+                    Bytecode::Call(_, _, Operation::Div, _, _) => counts[3] += 1,
+                    _ => {}
+                }
+            }
+        }
+        let total: usize = counts.iter().sum();
+        if total == 0 {
+            return;
+        }
+        let mut choice = seed % total;
+        let selected = [
+            &mut self.add_sub,
+            &mut self.sub_add,
+            &mut self.mul_div,
+            &mut self.div_mul,
+        ];
+        for (count, nth) in counts.iter().zip(selected) {
+            if choice < *count {
+                *nth = choice + 1;
+                return;
+            }
+            choice -= *count;
+        }
+    }
+}
+
 fn mutate_arith(
     call: Bytecode,
     mutation_value: usize,
@@ -68,18 +112,29 @@ fn mutate_arith(
 }
 
 impl FunctionTargetProcessor for MutationTester {
-    fn initialize(&self, global_env: &GlobalEnv, _targets: &mut FunctionTargetsHolder) {
+    fn initialize(&self, global_env: &GlobalEnv, targets: &mut FunctionTargetsHolder) {
         let options = ProverOptions::get(global_env);
         let m = global_env.get_extension::<MutationManager>();
         match m {
             Some(x) => global_env.set_extension(MutationManager { ..*x }),
-            None => global_env.set_extension(MutationManager {
-                mutated: false,
-                add_sub: options.mutation_add_sub,
-                sub_add: options.mutation_sub_add,
-                mul_div: options.mutation_mul_div,
-                div_mul: options.mutation_div_mul,
-            }),
+            None => {
+                let mut manager = MutationManager {
+                    mutated: false,
+                    add_sub: options.mutation_add_sub,
+                    sub_add: options.mutation_sub_add,
+                    mul_div: options.mutation_mul_div,
+                    div_mul: options.mutation_div_mul,
+                };
+                if options.mutation
+                    && manager.add_sub == 0
+                    && manager.sub_add == 0
+                    && manager.mul_div == 0
+                    && manager.div_mul == 0
+                {
+                    manager.select_by_seed(targets, options.random_seed);
+                }
+                global_env.set_extension(manager)
+            }
         };
     }
 
@@ -0,0 +1,101 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Configurable semantics of native functions. By default, analyses and instrumentation
+//! make worst-case assumptions about natives they do not model by hand: a native may abort,
+//! may write any `&mut` parameter, and may access global memory. Tools embedding the prover
+//! can register a `NativeSemantics` for a native, which is then used instead of the
+//! worst-case assumptions by the bytecode analyses and checked by the interpreter.
+//!
+//! Registrations are stored as an extension of the `GlobalEnv` and are keyed by the full
+//! name of the native, as in `0x1::Vector::borrow_mut`.
+
+use move_model::model::{FunId, FunctionEnv, GlobalEnv, QualifiedId};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The semantics of a native function, as assumed by analyses.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NativeSemantics {
+    /// Whether the native may abort.
+    pub can_abort: bool,
+    /// The indices of `&mut` parameters the native may write to. Other `&mut` parameters
+    /// are guaranteed to be unchanged after the call.
+    pub modified_params: BTreeSet<usize>,
+    /// Whether the native may write to global memory.
+    pub writes_memory: bool,
+}
+
+impl NativeSemantics {
+    /// Semantics of a pure native: it never aborts, and writes neither parameters nor memory.
+    pub fn pure() -> Self {
+        Self::default()
+    }
+
+    /// Declares that the native may abort.
+    pub fn aborts(mut self) -> Self {
+        self.can_abort = true;
+        self
+    }
+
+    /// Declares that the native may write to the `&mut` parameter at the given index.
+    pub fn modifies_param(mut self, idx: usize) -> Self {
+        self.modified_params.insert(idx);
+        self
+    }
+
+    /// Declares that the native may write to global memory.
+    pub fn writes_memory(mut self) -> Self {
+        self.writes_memory = true;
+        self
+    }
+
+    /// Returns true if the native neither aborts nor has side effects.
+    pub fn is_pure(&self) -> bool {
+        !self.can_abort && self.modified_params.is_empty() && !self.writes_memory
+    }
+
+    /// Returns true if the `&mut` parameter at the given index may be written.
+    pub fn modifies(&self, idx: usize) -> bool {
+        self.modified_params.contains(&idx)
+    }
+}
+
+/// The registry of native semantics, stored as an extension of the `GlobalEnv`.
+#[derive(Debug, Clone, Default)]
+pub struct NativeSemanticsRegistry {
+    semantics: BTreeMap<String, NativeSemantics>,
+}
+
+impl NativeSemanticsRegistry {
+    /// Registers semantics for the native with the given full name, e.g.
+    /// `0x1::Vector::length`. A previous registration for the same native is replaced.
+    pub fn register(env: &GlobalEnv, name: &str, semantics: NativeSemantics) {
+        if !env.has_extension::<NativeSemanticsRegistry>() {
+            env.set_extension(NativeSemanticsRegistry::default());
+        }
+        env.update_extension(|registry: &mut NativeSemanticsRegistry| {
+            registry.semantics.insert(name.to_string(), semantics);
+        });
+    }
+
+    /// Returns the registered semantics of the given function, if it is a native and
+    /// semantics for it have been registered.
+    pub fn get(env: &GlobalEnv, fun_id: QualifiedId<FunId>) -> Option<NativeSemantics> {
+        let registry = env.get_extension::<NativeSemanticsRegistry>()?;
+        let fun_env = env.get_function(fun_id);
+        if !fun_env.is_native() {
+            return None;
+        }
+        registry.semantics.get(&native_name(&fun_env)).cloned()
+    }
+}
+
+/// Returns the full name of a native as used for registration.
+fn native_name(fun_env: &FunctionEnv<'_>) -> String {
+    let pool = fun_env.symbol_pool();
+    format!(
+        "{}::{}",
+        fun_env.module_env.get_name().display_full(pool),
+        fun_env.get_name().display(pool)
+    )
+}
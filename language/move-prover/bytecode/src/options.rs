@@ -4,7 +4,7 @@
 use codespan_reporting::diagnostic::Severity;
 use move_model::model::{GlobalEnv, VerificationScope};
 use serde::{Deserialize, Serialize};
-use std::rc::Rc;
+use std::{collections::BTreeMap, rc::Rc};
 
 #[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub enum AutoTraceLevel {
@@ -59,6 +59,9 @@ pub struct ProverOptions {
     pub mutation_mul_div: usize,
     /// Indicates that we should use the divide-multiply mutation on the given block
     pub mutation_div_mul: usize,
+    /// The seed from which randomized choices of transformations are derived, like the
+    /// operation mutated if none is given. This is the same seed as the one of the backend.
+    pub random_seed: usize,
     /// Whether to use the polymorphic boogie backend.
     pub boogie_poly: bool,
     /// Whether pack/unpack should recurse over the structure.
@@ -71,6 +74,9 @@ pub struct ProverOptions {
     pub dump_bytecode: bool,
     /// Whether to dump the control-flow graphs (in dot format) to files, one per each function
     pub dump_cfg: bool,
+    /// The functions to which dumps of the bytecode and control-flow graphs are restricted,
+    /// given by their simple or full name. If empty, all functions are dumped.
+    pub dump_functions: Vec<String>,
     /// Number of Boogie instances to be run concurrently.
     pub num_instances: usize,
     /// Whether to run Boogie instances sequentially.
@@ -79,8 +85,43 @@ pub struct ProverOptions {
     pub check_inconsistency: bool,
     /// Whether to consider a function that abort unconditionally as an inconsistency violation
     pub unconditional_abort_as_inconsistency: bool,
+    /// Whether to check for specifications which hold vacuously
+    pub check_vacuity: bool,
+    /// Whether to verify independent conditions of a function in separate verification
+    /// conditions
+    pub split_conditions: bool,
     /// Whether to run the transformation passes for concrete interpretation (instead of proving)
     pub for_interpretation: bool,
+    /// Functions which are replaced by other functions for verification, e.g. a cryptographic
+    /// native by an abstract model. Maps the name of a function (`Module::fun` or
+    /// `0x1::Module::fun`) to the name of its replacement.
+    pub function_overrides: BTreeMap<String, String>,
+    /// Functions which are checked to be equivalent to other functions, e.g. a refactored
+    /// arithmetic function to its original version. Maps the name of a function to the name of
+    /// the function it must agree with, on results and aborts, for all inputs satisfying its
+    /// preconditions.
+    pub equivalence_checks: BTreeMap<String, String>,
+    /// The maximal number of instructions of a function after bytecode transformation. Larger
+    /// functions are reported as errors.
+    pub max_instructions: Option<usize>,
+    /// The maximal number of locals of a function after bytecode transformation. Functions with
+    /// more locals are reported as errors.
+    pub max_locals: Option<usize>,
+    /// Whether to inline calls to functions, see `inline_threshold`.
+    pub inline_functions: bool,
+    /// Whether to propagate constants through the bytecode.
+    pub propagate_constants: bool,
+    /// Whether to eliminate unreachable code and assignments which are never read.
+    pub eliminate_dead_code: bool,
+    /// Whether to unroll loops, see `loop_unroll`.
+    pub unroll_loops: bool,
+    /// How often to unroll the loops of functions which do not declare `pragma unroll`. If not
+    /// set, loops are only unrolled where declared. Only effective with `unroll_loops`.
+    pub loop_unroll: Option<usize>,
+    /// The maximal number of instructions of a function which does not declare `pragma inline`
+    /// for calls to it to be inlined. If not set, only calls to functions declaring the pragma
+    /// are inlined. Only effective with `inline_functions`.
+    pub inline_threshold: Option<usize>,
 }
 
 // add custom struct for mutation options
@@ -101,17 +142,31 @@ impl Default for ProverOptions {
             mutation_sub_add: 0,
             mutation_mul_div: 0,
             mutation_div_mul: 0,
+            random_seed: 1,
             boogie_poly: false,
             deep_pack_unpack: false,
             auto_trace_level: AutoTraceLevel::Off,
             report_severity: Severity::Warning,
             dump_bytecode: false,
             dump_cfg: false,
+            dump_functions: vec![],
             num_instances: 1,
             sequential_task: false,
             check_inconsistency: false,
             unconditional_abort_as_inconsistency: false,
+            check_vacuity: false,
+            split_conditions: false,
             for_interpretation: false,
+            function_overrides: BTreeMap::new(),
+            equivalence_checks: BTreeMap::new(),
+            max_instructions: None,
+            max_locals: None,
+            inline_functions: false,
+            propagate_constants: false,
+            eliminate_dead_code: false,
+            unroll_loops: false,
+            loop_unroll: None,
+            inline_threshold: None,
         }
     }
 }
@@ -182,7 +182,7 @@ impl FunctionTargetProcessor for PackedTypesProcessor {
         let cache = SummaryCache::new(targets, func_env.module_env.env);
         let analysis = PackedTypesAnalysis { cache };
         let summary = analysis.summarize(&fun_target, initial_state);
-        data.annotations.set(summary);
+        data.annotations.set_with_fixedpoint_check(summary);
         data
     }
 
@@ -1,70 +1,182 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+//! Factories for the standard processing pipelines. Processors from other crates can be added to
+//! those pipelines via `register_processor`, naming the standard processor before or after which
+//! they are run.
+
 use crate::{
+    abort_inference::AbortInferenceProcessor,
+    abort_path_analysis::AbortPathProcessor,
     borrow_analysis::BorrowAnalysisProcessor,
     clean_and_optimize::CleanAndOptimizeProcessor,
+    condition_splitting::ConditionSplittingProcessor,
+    constant_propagation::ConstantPropagationProcessor,
     data_invariant_instrumentation::DataInvariantInstrumentationProcessor,
+    dead_code_elimination::DeadCodeEliminationProcessor,
     debug_instrumentation::DebugInstrumenter,
     eliminate_imm_refs::EliminateImmRefsProcessor,
+    equivalence_check::EquivalenceCheckInstrumenter,
+    escape_analysis::EscapeAnalysisProcessor,
     function_target_pipeline::{FunctionTargetPipeline, FunctionTargetProcessor},
     global_invariant_analysis::GlobalInvariantAnalysisProcessor,
     global_invariant_instrumentation::GlobalInvariantInstrumentationProcessor,
+    global_invariant_instrumentation_v2::GlobalInvariantInstrumentationProcessorV2,
     inconsistency_check::InconsistencyCheckInstrumenter,
+    inlining::InliningProcessor,
+    liveness_analysis::LivenessAnalysisProcessor,
     livevar_analysis::LiveVarAnalysisProcessor,
-    loop_analysis::LoopAnalysisProcessor,
+    loop_analysis::{LoopAnalysisProcessor, LoopDetectionProcessor},
+    loop_unrolling::LoopUnrollingProcessor,
     memory_instrumentation::MemoryInstrumentationProcessor,
     mono_analysis::MonoAnalysisProcessor,
     mut_ref_instrumentation::MutRefInstrumenter,
     mutation_tester::MutationTester,
     options::ProverOptions,
+    packed_types_analysis::PackedTypesProcessor,
     reaching_def_analysis::ReachingDefProcessor,
+    read_write_set_analysis::ReadWriteSetProcessor,
+    recursion_analysis::RecursionAnalysisProcessor,
+    spec_coverage_analysis::SpecCoverageProcessor,
     spec_instrumentation::SpecInstrumentationProcessor,
+    spec_var_frame_analysis::SpecVarFrameProcessor,
+    termination_instrumentation::TerminationInstrumenter,
     usage_analysis::UsageProcessor,
+    vacuity_check::VacuityCheckInstrumenter,
     verification_analysis::VerificationAnalysisProcessor,
+    verification_analysis_v2::VerificationAnalysisProcessorV2,
     well_formed_instrumentation::WellFormedInstrumentationProcessor,
 };
+use once_cell::sync::Lazy;
+use std::sync::Mutex;
+
+/// A function which creates a processor.
+pub type ProcessorFactory = fn() -> Box<dyn FunctionTargetProcessor>;
+
+/// The point in the standard pipelines at which a registered processor is inserted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InsertionPoint {
+    /// Before the processor with the given name.
+    Before(String),
+    /// After the processor with the given name.
+    After(String),
+}
+
+struct RegisteredProcessor {
+    name: String,
+    factory: ProcessorFactory,
+    at: Option<InsertionPoint>,
+}
+
+static REGISTERED_PROCESSORS: Lazy<Mutex<Vec<RegisteredProcessor>>> =
+    Lazy::new(|| Mutex::new(vec![]));
+
+/// Registers a processor created by the given factory. The processor can then be named in
+/// pipeline configurations. If an insertion point is given, the processor is also inserted into
+/// the standard pipelines at this point, provided the pipeline contains the processor the point
+/// refers to. Processors registered for the same point are run in the order of registration.
+pub fn register_processor(factory: ProcessorFactory, at: Option<InsertionPoint>) {
+    let name = factory().name();
+    REGISTERED_PROCESSORS
+        .lock()
+        .expect("processor registry")
+        .push(RegisteredProcessor { name, factory, at });
+}
+
+/// Creates a pipeline from the given processors, with the registered processors inserted at
+/// their insertion points.
+fn with_registered_processors(
+    processors: Vec<Box<dyn FunctionTargetProcessor>>,
+) -> FunctionTargetPipeline {
+    let registry = REGISTERED_PROCESSORS.lock().expect("processor registry");
+    let registered_at = |at: InsertionPoint| {
+        registry
+            .iter()
+            .filter(move |r| r.at.as_ref() == Some(&at))
+            .map(|r| (r.factory)())
+    };
+    let mut res = FunctionTargetPipeline::default();
+    for p in processors {
+        let name = p.name();
+        for before in registered_at(InsertionPoint::Before(name.clone())) {
+            res.add_processor(before)
+        }
+        res.add_processor(p);
+        for after in registered_at(InsertionPoint::After(name)) {
+            res.add_processor(after)
+        }
+    }
+    res
+}
 
 pub fn default_pipeline_with_options(options: &ProverOptions) -> FunctionTargetPipeline {
     // NOTE: the order of these processors is import!
-    let mut processors: Vec<Box<dyn FunctionTargetProcessor>> = vec![
+    let mut processors: Vec<Box<dyn FunctionTargetProcessor>> = vec![];
+    // the optimizations are off by default, as they change the code which is verified
+    if options.inline_functions {
+        processors.push(InliningProcessor::new());
+    }
+    processors.append(&mut vec![
         DebugInstrumenter::new(),
         // transformation and analysis
         EliminateImmRefsProcessor::new(),
         MutRefInstrumenter::new(),
         ReachingDefProcessor::new(),
+    ]);
+    if options.propagate_constants {
+        processors.push(ConstantPropagationProcessor::new());
+    }
+    if options.eliminate_dead_code {
+        processors.push(DeadCodeEliminationProcessor::new());
+    }
+    processors.append(&mut vec![
         LiveVarAnalysisProcessor::new(),
         BorrowAnalysisProcessor::new(),
         MemoryInstrumentationProcessor::new(),
         CleanAndOptimizeProcessor::new(),
         UsageProcessor::new(),
         VerificationAnalysisProcessor::new(),
+    ]);
+    if options.unroll_loops {
+        processors.push(LoopUnrollingProcessor::new());
+    }
+    processors.append(&mut vec![
         LoopAnalysisProcessor::new(),
         // spec instrumentation
         SpecInstrumentationProcessor::new(),
+        TerminationInstrumenter::new(),
         GlobalInvariantAnalysisProcessor::new(),
         GlobalInvariantInstrumentationProcessor::new(),
         WellFormedInstrumentationProcessor::new(),
         DataInvariantInstrumentationProcessor::new(),
         // monomorphization
         MonoAnalysisProcessor::new(),
-    ];
+    ]);
+
+    if !options.equivalence_checks.is_empty() {
+        // equivalence checks call functions which monomorphization needs to see, so they are
+        // created right before it
+        processors.insert(processors.len() - 1, EquivalenceCheckInstrumenter::new());
+    }
 
     if options.mutation {
         // pass which may do nothing
         processors.push(MutationTester::new());
     }
 
-    // inconsistency check instrumentation should be the last one in the pipeline
+    if options.split_conditions {
+        processors.push(ConditionSplittingProcessor::new());
+    }
+
+    // inconsistency and vacuity check instrumentation should be the last ones in the pipeline
     if options.check_inconsistency {
         processors.push(InconsistencyCheckInstrumenter::new());
     }
-
-    let mut res = FunctionTargetPipeline::default();
-    for p in processors {
-        res.add_processor(p);
+    if options.check_vacuity {
+        processors.push(VacuityCheckInstrumenter::new());
     }
-    res
+
+    with_registered_processors(processors)
 }
 
 pub fn default_pipeline() -> FunctionTargetPipeline {
@@ -74,17 +186,21 @@ pub fn default_pipeline() -> FunctionTargetPipeline {
 pub fn experimental_pipeline() -> FunctionTargetPipeline {
     // Enter your pipeline here
     let processors: Vec<Box<dyn FunctionTargetProcessor>> = vec![
+        InliningProcessor::new(),
         DebugInstrumenter::new(),
         // transformation and analysis
         EliminateImmRefsProcessor::new(),
         MutRefInstrumenter::new(),
         ReachingDefProcessor::new(),
+        ConstantPropagationProcessor::new(),
+        DeadCodeEliminationProcessor::new(),
         LiveVarAnalysisProcessor::new(),
         BorrowAnalysisProcessor::new(),
         MemoryInstrumentationProcessor::new(),
         CleanAndOptimizeProcessor::new(),
         UsageProcessor::new(),
         VerificationAnalysisProcessor::new(),
+        LoopUnrollingProcessor::new(),
         LoopAnalysisProcessor::new(),
         // spec instrumentation
         SpecInstrumentationProcessor::new(),
@@ -95,9 +211,60 @@ pub fn experimental_pipeline() -> FunctionTargetPipeline {
         MonoAnalysisProcessor::new(),
     ];
 
-    let mut res = FunctionTargetPipeline::default();
-    for p in processors {
-        res.add_processor(p);
-    }
-    res
+    with_registered_processors(processors)
+}
+
+/// Creates the processor with the given name, as returned by `FunctionTargetProcessor::name`.
+/// This includes registered processors.
+pub fn processor_by_name(name: &str) -> Option<Box<dyn FunctionTargetProcessor>> {
+    let processor: Box<dyn FunctionTargetProcessor> = match name {
+        "abort_inference" => AbortInferenceProcessor::new(),
+        "abort_path_analysis" => AbortPathProcessor::new(),
+        "borrow_analysis" => BorrowAnalysisProcessor::new(),
+        "clean_and_optimize" => CleanAndOptimizeProcessor::new(),
+        "condition_splitting" => ConditionSplittingProcessor::new(),
+        "constant_propagation" => ConstantPropagationProcessor::new(),
+        "data_invariant_instrumenter" => DataInvariantInstrumentationProcessor::new(),
+        "dead_code_elimination" => DeadCodeEliminationProcessor::new(),
+        "debug_instrumenter" => DebugInstrumenter::new(),
+        "eliminate_imm_refs" => EliminateImmRefsProcessor::new(),
+        "entry_point_instrumenter" => WellFormedInstrumentationProcessor::new(),
+        "equivalence_check_instrumenter" => EquivalenceCheckInstrumenter::new(),
+        "escape_analysis" => EscapeAnalysisProcessor::new(),
+        "global_invariant_analysis" => GlobalInvariantAnalysisProcessor::new(),
+        "global_invariant_instrumentation" => GlobalInvariantInstrumentationProcessor::new(),
+        "global_invariant_instrumenter_v2" => GlobalInvariantInstrumentationProcessorV2::new(),
+        "inconsistency_check_instrumenter" => InconsistencyCheckInstrumenter::new(),
+        "inlining" => InliningProcessor::new(),
+        "liveness_analysis" => LivenessAnalysisProcessor::new(),
+        "livevar_analysis" => LiveVarAnalysisProcessor::new(),
+        "loop_analysis" => LoopAnalysisProcessor::new(),
+        "loop_detection" => LoopDetectionProcessor::new(),
+        "loop_unrolling" => LoopUnrollingProcessor::new(),
+        "memory_instr" => MemoryInstrumentationProcessor::new(),
+        "mono_analysis" => MonoAnalysisProcessor::new(),
+        "mut_ref_instrumentation" => MutRefInstrumenter::new(),
+        "mutation_tester" => MutationTester::new(),
+        "packed_types_analysis" => PackedTypesProcessor::new(),
+        "reaching_def_analysis" => ReachingDefProcessor::new(),
+        "read_write_set_analysis" => ReadWriteSetProcessor::new(),
+        "recursion_analysis" => RecursionAnalysisProcessor::new(),
+        "spec_coverage_analysis" => SpecCoverageProcessor::new(),
+        "spec_instrumenter" => SpecInstrumentationProcessor::new(),
+        "spec_var_frame_analysis" => SpecVarFrameProcessor::new(),
+        "termination_instrumenter" => TerminationInstrumenter::new(),
+        "usage_analysis" => UsageProcessor::new(),
+        "vacuity_check_instrumenter" => VacuityCheckInstrumenter::new(),
+        "verification_analysis" => VerificationAnalysisProcessor::new(),
+        "verification_analysis_v2" => VerificationAnalysisProcessorV2::new(),
+        _ => {
+            return REGISTERED_PROCESSORS
+                .lock()
+                .expect("processor registry")
+                .iter()
+                .find(|r| r.name == name)
+                .map(|r| (r.factory)())
+        }
+    };
+    Some(processor)
 }
@@ -12,7 +12,6 @@ use crate::{
     function_target::{FunctionData, FunctionTarget},
     function_target_pipeline::{FunctionTargetProcessor, FunctionTargetsHolder},
     stackless_bytecode::{AbortAction, BorrowNode, Bytecode, Operation},
-    stackless_control_flow_graph::StacklessControlFlowGraph,
 };
 use itertools::Itertools;
 use move_binary_format::file_format::CodeOffset;
@@ -112,22 +111,15 @@ impl FunctionTargetProcessor for ReachingDefProcessor {
         mut data: FunctionData,
     ) -> FunctionData {
         if !func_env.is_native() {
-            let cfg = StacklessControlFlowGraph::new_forward(&data.code);
             let analyzer = ReachingDefAnalysis {
                 _target: FunctionTarget::new(func_env, &data),
                 borrowed_locals: self.borrowed_locals(&data.code),
             };
-            let block_state_map = analyzer.analyze_function(
-                ReachingDefState {
-                    map: BTreeMap::new(),
-                },
-                &data.code,
-                &cfg,
-            );
+            let initial_state = ReachingDefState {
+                map: BTreeMap::new(),
+            };
             let defs =
-                analyzer.state_per_instruction(block_state_map, &data.code, &cfg, |before, _| {
-                    before.map.clone()
-                });
+                analyzer.analyze_code(initial_state, &data.code, |before, _| before.map.clone());
 
             // Run copy propagation transformation.
             let annotations = ReachingDefAnnotation(defs);
@@ -17,6 +17,7 @@ use crate::{
     dataflow_domains::{AbstractDomain, JoinResult},
     function_target::{FunctionData, FunctionTarget},
     function_target_pipeline::{FunctionTargetProcessor, FunctionTargetsHolder, FunctionVariant},
+    native_semantics::{NativeSemantics, NativeSemanticsRegistry},
     stackless_bytecode::{Bytecode, Constant, Operation},
 };
 use move_binary_format::file_format::CodeOffset;
@@ -505,17 +506,27 @@ impl<'a> TransferFunctions for ReadWriteSetAnalysis<'a> {
                             func_env,
                             &callee_fun_env,
                         );
-                    } else {
-                        // native fun. use handwritten model
-                        call_native_function(
-                            state,
-                            callee_fun_env.module_env.get_identifier().as_str(),
-                            callee_fun_env.get_identifier().as_str(),
-                            args,
-                            rets,
-                            func_env,
-                        )
+                    } else if callee_fun_env.is_native_or_intrinsic() {
+                        if let Some(semantics) = NativeSemanticsRegistry::get(global_env, fun_id)
+                            .filter(|semantics| !semantics.writes_memory)
+                        {
+                            // native fun with registered semantics
+                            call_registered_native_function(state, &semantics, args, func_env)
+                        } else {
+                            // native fun. use handwritten model
+                            call_native_function(
+                                state,
+                                callee_fun_env.module_env.get_identifier().as_str(),
+                                callee_fun_env.get_identifier().as_str(),
+                                args,
+                                rets,
+                                func_env,
+                            )
+                        }
                     }
+                    // Otherwise, the callee is recursive and has not been summarized yet. Its
+                    // accesses are added once it has been, as the recursive functions are
+                    // analyzed until their summaries are stable.
                 }
                 OpaqueCallBegin(_, _, _) | OpaqueCallEnd(_, _, _) => {
                     // skip
@@ -604,6 +615,27 @@ impl<'a> TransferFunctions for ReadWriteSetAnalysis<'a> {
     }
 }
 
+/// Execute a call to a native with registered semantics in `state`. The native reads its
+/// arguments and writes the `&mut` parameters it is declared to modify. Natives which write
+/// global memory cannot be described this way and must be modeled by hand.
+fn call_registered_native_function(
+    state: &mut ReadWriteSetState,
+    semantics: &NativeSemantics,
+    args: &[TempIndex],
+    func_env: &FunctionEnv,
+) {
+    for (idx, arg) in args.iter().enumerate() {
+        if state.locals.local_exists(*arg, func_env) {
+            let access = if semantics.modifies(idx) {
+                Access::ReadWrite
+            } else {
+                Access::Read
+            };
+            state.record_access(*arg, access, func_env)
+        }
+    }
+}
+
 /// Execute `rets` = call `module_name`::`function_name`(`args`) in `state`
 fn call_native_function(
     state: &mut ReadWriteSetState,
@@ -747,7 +779,7 @@ impl FunctionTargetProcessor for ReadWriteSetProcessor {
         let cache = SummaryCache::new(targets, func_env.module_env.env);
         let analysis = ReadWriteSetAnalysis { cache, func_env };
         let summary = analysis.summarize(&fun_target, initial_state);
-        data.annotations.set(summary);
+        data.annotations.set_with_fixedpoint_check(summary);
         data
     }
 
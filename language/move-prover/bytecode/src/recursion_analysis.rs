@@ -0,0 +1,306 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Analysis which detects (mutually) recursive functions and enforces the recursion related
+//! pragmas. A recursive function must either declare a termination measure via
+//! `pragma measure = <param>` or bound its recursion via `pragma max_call_depth = <n>`.
+//! For non-recursive functions, a declared `max_call_depth` is checked against the longest
+//! call chain starting at the function. Violations are reported together with the call path
+//! which leads to them. The measure must be an integer parameter; that it decreases at
+//! recursive calls is checked by the `TerminationInstrumenter`.
+
+use crate::{
+    function_target::FunctionTarget,
+    function_target_pipeline::{FunctionTargetProcessor, FunctionTargetsHolder, FunctionVariant},
+};
+use itertools::Itertools;
+use move_model::{
+    ast::TempIndex,
+    call_graph::CallGraph,
+    model::{FunId, FunctionEnv, GlobalEnv, QualifiedId},
+    pragmas::{MAX_CALL_DEPTH_PRAGMA, MEASURE_PRAGMA},
+};
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    fmt::{self, Formatter},
+};
+
+/// The annotation attached to the baseline variant of a recursive function. It contains
+/// one call cycle through the function, starting and ending with the function itself.
+#[derive(Debug, Clone)]
+pub struct RecursionInfo {
+    pub cycle: Vec<QualifiedId<FunId>>,
+}
+
+/// Returns the recursion info of the target, if the function is recursive.
+pub fn get_recursion_info<'env>(target: &FunctionTarget<'env>) -> Option<&'env RecursionInfo> {
+    target.get_annotations().get::<RecursionInfo>()
+}
+
+/// Returns the index of the parameter declared as termination measure of the function via
+/// `pragma measure`, if it is a parameter of an integer type.
+pub fn get_measure_param(fun_env: &FunctionEnv<'_>) -> Option<TempIndex> {
+    let measure = fun_env.get_ident_pragma(MEASURE_PRAGMA)?;
+    let symbol_pool = fun_env.symbol_pool();
+    fun_env
+        .get_parameters()
+        .iter()
+        .position(|param| symbol_pool.string(param.0).as_str() == measure.as_str())
+        .filter(|idx| fun_env.get_local_type(*idx).is_number())
+}
+
+pub struct RecursionAnalysisProcessor {}
+
+impl RecursionAnalysisProcessor {
+    pub fn new() -> Box<Self> {
+        Box::new(RecursionAnalysisProcessor {})
+    }
+
+    /// Computes the call graph restricted to the functions in the targets holder.
+    fn call_graph(env: &GlobalEnv, targets: &FunctionTargetsHolder) -> CallGraph {
+        CallGraph::new_for_functions(env, targets.get_funs())
+    }
+
+    /// Computes the recursive functions, mapping each of them to a call cycle through it.
+    pub fn compute_cycles(
+        env: &GlobalEnv,
+        targets: &FunctionTargetsHolder,
+    ) -> BTreeMap<QualifiedId<FunId>, Vec<QualifiedId<FunId>>> {
+        let call_graph = Self::call_graph(env, targets);
+        let mut cycles = BTreeMap::new();
+        for members in call_graph.sccs() {
+            let is_recursive = members.len() > 1 || {
+                let fun_id = members.iter().next().unwrap();
+                call_graph.callees_of(*fun_id).contains(fun_id)
+            };
+            if !is_recursive {
+                continue;
+            }
+            for fun_id in &members {
+                let cycle = Self::find_cycle(&call_graph, &members, *fun_id);
+                cycles.insert(*fun_id, cycle);
+            }
+        }
+        cycles
+    }
+
+    /// Finds a shortest call cycle from `start` back to itself which stays within the
+    /// given strongly connected component.
+    fn find_cycle(
+        call_graph: &CallGraph,
+        scc: &BTreeSet<QualifiedId<FunId>>,
+        start: QualifiedId<FunId>,
+    ) -> Vec<QualifiedId<FunId>> {
+        let mut parent: BTreeMap<QualifiedId<FunId>, QualifiedId<FunId>> = BTreeMap::new();
+        let mut todo = VecDeque::new();
+        todo.push_back(start);
+        while let Some(fun_id) = todo.pop_front() {
+            for callee in call_graph.callees_of(fun_id) {
+                if !scc.contains(callee) || parent.contains_key(callee) {
+                    continue;
+                }
+                parent.insert(*callee, fun_id);
+                if *callee == start {
+                    let mut cycle = vec![start];
+                    let mut current = fun_id;
+                    while current != start {
+                        cycle.push(current);
+                        current = parent[&current];
+                    }
+                    cycle.push(start);
+                    cycle.reverse();
+                    return cycle;
+                }
+                todo.push_back(*callee);
+            }
+        }
+        unreachable!("function in strongly connected component must be on a cycle")
+    }
+
+    /// Computes the longest call chain starting at `fun_id`. Recursive functions contribute
+    /// their declared `max_call_depth`, if any; otherwise the chain is unbounded and None is
+    /// returned. The result is the depth together with the call path which realizes it.
+    fn longest_call_chain(
+        env: &GlobalEnv,
+        call_graph: &CallGraph,
+        cycles: &BTreeMap<QualifiedId<FunId>, Vec<QualifiedId<FunId>>>,
+        fun_id: QualifiedId<FunId>,
+        cache: &mut BTreeMap<QualifiedId<FunId>, Option<(usize, Vec<QualifiedId<FunId>>)>>,
+    ) -> Option<(usize, Vec<QualifiedId<FunId>>)> {
+        if let Some(result) = cache.get(&fun_id) {
+            return result.clone();
+        }
+        let result = if cycles.contains_key(&fun_id) {
+            let fun_env = env.get_function(fun_id);
+            if fun_env.is_num_pragma_set(MAX_CALL_DEPTH_PRAGMA) {
+                let depth = fun_env.get_num_pragma(MAX_CALL_DEPTH_PRAGMA, || 0);
+                Some((depth, vec![fun_id]))
+            } else {
+                None
+            }
+        } else {
+            let mut longest = Some((1, vec![fun_id]));
+            for callee in call_graph.callees_of(fun_id) {
+                match Self::longest_call_chain(env, call_graph, cycles, *callee, cache) {
+                    Some((depth, path)) => {
+                        if matches!(&longest, Some((current, _)) if depth + 1 > *current) {
+                            let mut new_path = vec![fun_id];
+                            new_path.extend(path);
+                            longest = Some((depth + 1, new_path));
+                        }
+                    }
+                    None => {
+                        longest = None;
+                        break;
+                    }
+                }
+            }
+            longest
+        };
+        cache.insert(fun_id, result.clone());
+        result
+    }
+
+    /// Checks the recursion related pragmas of a function and reports errors.
+    fn check_function(
+        fun_env: &FunctionEnv<'_>,
+        call_graph: &CallGraph,
+        cycles: &BTreeMap<QualifiedId<FunId>, Vec<QualifiedId<FunId>>>,
+        cache: &mut BTreeMap<QualifiedId<FunId>, Option<(usize, Vec<QualifiedId<FunId>>)>>,
+    ) {
+        let env = fun_env.module_env.env;
+        let fun_id = fun_env.get_qualified_id();
+        let display_path = |path: &[QualifiedId<FunId>]| {
+            path.iter()
+                .map(|id| format!("`{}`", env.get_function(*id).get_full_name_str()))
+                .join(" -> ")
+        };
+        if let Some(cycle) = cycles.get(&fun_id) {
+            let has_depth = fun_env.is_num_pragma_set(MAX_CALL_DEPTH_PRAGMA);
+            match fun_env.get_ident_pragma(MEASURE_PRAGMA) {
+                Some(measure) => {
+                    let symbol_pool = fun_env.symbol_pool();
+                    let is_param = fun_env
+                        .get_parameters()
+                        .iter()
+                        .any(|param| symbol_pool.string(param.0).as_str() == measure.as_str());
+                    if !is_param {
+                        env.error(
+                            &fun_env.get_loc(),
+                            &format!(
+                                "termination measure `{}` of function `{}` is not a parameter",
+                                measure,
+                                fun_env.get_full_name_str()
+                            ),
+                        );
+                    } else if get_measure_param(fun_env).is_none() {
+                        env.error(
+                            &fun_env.get_loc(),
+                            &format!(
+                                "termination measure `{}` of function `{}` is not an integer",
+                                measure,
+                                fun_env.get_full_name_str()
+                            ),
+                        );
+                    }
+                }
+                None if !has_depth => env.error_with_notes(
+                    &fun_env.get_loc(),
+                    &format!(
+                        "function `{}` is recursive and requires a termination measure \
+                         (`pragma {} = <param>`) or a bound (`pragma {} = <n>`)",
+                        fun_env.get_full_name_str(),
+                        MEASURE_PRAGMA,
+                        MAX_CALL_DEPTH_PRAGMA
+                    ),
+                    vec![format!("call cycle: {}", display_path(cycle))],
+                ),
+                None => {}
+            }
+        } else if fun_env.is_num_pragma_set(MAX_CALL_DEPTH_PRAGMA) {
+            let max_depth = fun_env.get_num_pragma(MAX_CALL_DEPTH_PRAGMA, || 0);
+            match Self::longest_call_chain(env, call_graph, cycles, fun_id, cache) {
+                Some((depth, path)) if depth > max_depth => env.error_with_notes(
+                    &fun_env.get_loc(),
+                    &format!(
+                        "call depth {} of function `{}` exceeds declared maximum {}",
+                        depth,
+                        fun_env.get_full_name_str(),
+                        max_depth
+                    ),
+                    vec![format!("call path: {}", display_path(&path))],
+                ),
+                Some(_) => {}
+                None => env.error(
+                    &fun_env.get_loc(),
+                    &format!(
+                        "call depth of function `{}` is unbounded because it calls an \
+                         unbounded recursive function",
+                        fun_env.get_full_name_str()
+                    ),
+                ),
+            }
+        }
+    }
+}
+
+impl FunctionTargetProcessor for RecursionAnalysisProcessor {
+    fn is_single_run(&self) -> bool {
+        true
+    }
+
+    fn run(&self, env: &GlobalEnv, targets: &mut FunctionTargetsHolder) {
+        let call_graph = Self::call_graph(env, targets);
+        let cycles = Self::compute_cycles(env, targets);
+        let mut cache = BTreeMap::new();
+        for fun_id in targets.get_funs().collect_vec() {
+            let fun_env = env.get_function(fun_id);
+            if fun_env.module_env.is_target() {
+                Self::check_function(&fun_env, &call_graph, &cycles, &mut cache);
+            }
+            if let Some(cycle) = cycles.get(&fun_id) {
+                if let Some(data) = targets.get_data_mut(&fun_id, &FunctionVariant::Baseline) {
+                    data.annotations.set(RecursionInfo {
+                        cycle: cycle.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    fn name(&self) -> String {
+        "recursion_analysis".to_string()
+    }
+
+    fn dump_result(
+        &self,
+        f: &mut Formatter<'_>,
+        env: &GlobalEnv,
+        targets: &FunctionTargetsHolder,
+    ) -> fmt::Result {
+        writeln!(
+            f,
+            "\n\n********* Result of recursion analysis *********\n\n"
+        )?;
+        for module in env.get_modules() {
+            if !module.is_target() {
+                continue;
+            }
+            for fun in module.get_functions() {
+                let target = targets.get_target(&fun, &FunctionVariant::Baseline);
+                if let Some(info) = get_recursion_info(&target) {
+                    writeln!(
+                        f,
+                        "function {}: {}",
+                        fun.get_full_name_str(),
+                        info.cycle
+                            .iter()
+                            .map(|id| env.get_function(*id).get_full_name_str())
+                            .join(" -> ")
+                    )?;
+                }
+            }
+        }
+        writeln!(f)
+    }
+}
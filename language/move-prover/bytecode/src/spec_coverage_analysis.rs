@@ -0,0 +1,413 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Analysis which determines, for each `ensures` and `aborts_if` condition of a function, the
+//! code which is constrained by the condition. The result can be rendered as a per-line heat
+//! report ("spec coverage"), showing for each source line with code the number of conditions
+//! constraining it. Lines with a count of zero are not constrained by any condition.
+//!
+//! A condition constrains an instruction if the instruction is in the backward slice of what
+//! the condition observes:
+//! - an `ensures` observes the returned values it refers to via `result`, the post-state of
+//!   the `&mut` parameters it refers to, and the global memory it refers to;
+//! - an `aborts_if` observes each instruction which can abort.
+//! The slice follows data dependencies (including writes through references) and control
+//! dependencies, where a branch is considered relevant if its two successors reach different
+//! constrained instructions. The analysis is flow-insensitive and runs on the baseline variant;
+//! it over-approximates the constrained code.
+
+use crate::{
+    function_target::{FunctionData, FunctionTarget},
+    function_target_pipeline::{FunctionTargetProcessor, FunctionTargetsHolder, FunctionVariant},
+    stackless_bytecode::{Bytecode, Operation},
+    usage_analysis::UsageState,
+};
+use itertools::Itertools;
+use move_binary_format::file_format::CodeOffset;
+use move_model::{
+    ast::{Condition, ConditionKind, ExpData, Operation as SpecOperation, TempIndex},
+    model::{FunctionEnv, GlobalEnv, Loc, QualifiedId, StructId},
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{self, Formatter},
+};
+
+/// The annotation attached to the baseline variant of a function with `ensures` or
+/// `aborts_if` conditions.
+#[derive(Debug, Clone, Default)]
+pub struct SpecCoverage {
+    /// The locations of the conditions, in the order of the function spec.
+    pub conditions: Vec<Loc>,
+    /// For each constrained code offset, the indices of the conditions constraining it.
+    pub covered: BTreeMap<CodeOffset, BTreeSet<usize>>,
+}
+
+/// Returns the spec coverage of the target, if the function has been analyzed.
+pub fn get_spec_coverage<'env>(target: &FunctionTarget<'env>) -> Option<&'env SpecCoverage> {
+    target.get_annotations().get::<SpecCoverage>()
+}
+
+pub struct SpecCoverageProcessor {}
+
+impl SpecCoverageProcessor {
+    pub fn new() -> Box<Self> {
+        Box::new(SpecCoverageProcessor {})
+    }
+}
+
+impl FunctionTargetProcessor for SpecCoverageProcessor {
+    fn process(
+        &self,
+        targets: &mut FunctionTargetsHolder,
+        fun_env: &FunctionEnv<'_>,
+        mut data: FunctionData,
+    ) -> FunctionData {
+        if fun_env.is_native()
+            || fun_env.is_intrinsic()
+            || !matches!(data.variant, FunctionVariant::Baseline)
+        {
+            return data;
+        }
+        let conditions = fun_env
+            .get_spec()
+            .conditions
+            .iter()
+            .filter(|c| matches!(c.kind, ConditionKind::Ensures | ConditionKind::AbortsIf))
+            .collect_vec();
+        if conditions.is_empty() {
+            return data;
+        }
+        let target = FunctionTarget::new(fun_env, &data);
+        let mut coverage = SpecCoverage::default();
+        for (idx, cond) in conditions.into_iter().enumerate() {
+            coverage.conditions.push(cond.loc.clone());
+            for offset in Slicer::new(&target, targets).slice(cond) {
+                coverage.covered.entry(offset).or_default().insert(idx);
+            }
+        }
+        data.annotations.set(coverage);
+        data
+    }
+
+    fn name(&self) -> String {
+        "spec_coverage_analysis".to_string()
+    }
+
+    fn dump_result(
+        &self,
+        f: &mut Formatter<'_>,
+        env: &GlobalEnv,
+        targets: &FunctionTargetsHolder,
+    ) -> fmt::Result {
+        writeln!(
+            f,
+            "\n\n********* Result of spec coverage analysis *********\n\n"
+        )?;
+        write!(f, "{}", spec_coverage_report(env, targets))
+    }
+}
+
+/// Computes the backward slice of a condition over the code of a function.
+struct Slicer<'a> {
+    target: &'a FunctionTarget<'a>,
+    targets: &'a FunctionTargetsHolder,
+    /// For each reference, the temporaries it is derived from.
+    derived_from: BTreeMap<TempIndex, BTreeSet<TempIndex>>,
+    /// The temporaries whose values are relevant for the condition.
+    needed: BTreeSet<TempIndex>,
+    /// The offsets of the instructions in the slice.
+    covered: BTreeSet<CodeOffset>,
+}
+
+impl<'a> Slicer<'a> {
+    fn new(target: &'a FunctionTarget<'a>, targets: &'a FunctionTargetsHolder) -> Self {
+        let mut derived_from: BTreeMap<TempIndex, BTreeSet<TempIndex>> = BTreeMap::new();
+        for bc in target.get_bytecode() {
+            match bc {
+                Bytecode::Assign(_, dest, src, _)
+                    if target.get_local_type(*dest).is_reference() =>
+                {
+                    derived_from.entry(*dest).or_default().insert(*src);
+                }
+                Bytecode::Call(_, dests, _, srcs, _) => {
+                    for dest in dests {
+                        if target.get_local_type(*dest).is_reference() {
+                            derived_from
+                                .entry(*dest)
+                                .or_default()
+                                .extend(srcs.iter().cloned());
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        Self {
+            target,
+            targets,
+            derived_from,
+            needed: BTreeSet::new(),
+            covered: BTreeSet::new(),
+        }
+    }
+
+    /// Returns the offsets of the instructions constrained by the condition.
+    fn slice(mut self, cond: &Condition) -> BTreeSet<CodeOffset> {
+        let target = self.target;
+        let code = target.get_bytecode();
+        let mut results = BTreeSet::new();
+        let mut memory = BTreeSet::new();
+        if cond.kind == ConditionKind::Ensures {
+            let param_count = target.get_parameter_count();
+            let env = target.global_env();
+            let needed = &mut self.needed;
+            for exp in std::iter::once(&cond.exp).chain(cond.additional_exps.iter()) {
+                exp.visit(&mut |e| match e {
+                    ExpData::Call(_, SpecOperation::Result(idx), _) => {
+                        results.insert(*idx);
+                    }
+                    ExpData::Temporary(_, idx)
+                        if *idx < param_count
+                            && target.get_local_type(*idx).is_mutable_reference() =>
+                    {
+                        needed.insert(*idx);
+                    }
+                    _ => {}
+                });
+                memory.extend(
+                    exp.used_memory(env)
+                        .into_iter()
+                        .map(|(mem, _)| mem.to_qualified_id()),
+                );
+            }
+        }
+        // Seed the slice with the instructions the condition observes.
+        for (offset, bc) in code.iter().enumerate() {
+            let observed = match bc {
+                Bytecode::Ret(_, srcs) => {
+                    for idx in &results {
+                        if let Some(src) = srcs.get(*idx) {
+                            self.needed.insert(*src);
+                        }
+                    }
+                    !results.is_empty()
+                }
+                Bytecode::Abort(..) => cond.kind == ConditionKind::AbortsIf,
+                Bytecode::Call(_, dests, op, _, _) => {
+                    let observed = (cond.kind == ConditionKind::AbortsIf && op.can_abort())
+                        || self.modifies_memory(op, &memory);
+                    if observed && matches!(op, Operation::BorrowGlobal(..)) {
+                        // Writes through the borrowed reference modify the memory.
+                        self.needed.extend(dests.iter().cloned());
+                    }
+                    observed
+                }
+                _ => false,
+            };
+            if observed {
+                self.cover(offset as CodeOffset, bc);
+            }
+        }
+        // Propagate data and control dependencies until a fixpoint is reached.
+        let label_offsets = Bytecode::label_offsets(code);
+        loop {
+            let mut changed = false;
+            for (offset, bc) in code.iter().enumerate() {
+                let offset = offset as CodeOffset;
+                if !self.covered.contains(&offset) && self.is_dependency(bc) {
+                    self.cover(offset, bc);
+                    changed = true;
+                }
+            }
+            for (offset, bc) in code.iter().enumerate() {
+                let offset = offset as CodeOffset;
+                if let Bytecode::Branch(_, then_label, else_label, _) = bc {
+                    if self.covered.contains(&offset) {
+                        continue;
+                    }
+                    let then_reach = self.reachable_covered(label_offsets[then_label]);
+                    let else_reach = self.reachable_covered(label_offsets[else_label]);
+                    if then_reach != else_reach {
+                        self.cover(offset, bc);
+                        changed = true;
+                    }
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        // Labels and jumps do not represent code of their own.
+        self.covered
+            .into_iter()
+            .filter(|offset| {
+                !matches!(
+                    code[*offset as usize],
+                    Bytecode::Label(..) | Bytecode::Jump(..) | Bytecode::Nop(..)
+                )
+            })
+            .collect()
+    }
+
+    /// Adds the instruction to the slice, making its sources relevant.
+    fn cover(&mut self, offset: CodeOffset, bc: &Bytecode) {
+        self.covered.insert(offset);
+        match bc {
+            Bytecode::Assign(_, _, src, _) => {
+                self.needed.insert(*src);
+            }
+            Bytecode::Call(_, _, _, srcs, _) | Bytecode::Ret(_, srcs) => {
+                self.needed.extend(srcs.iter().cloned())
+            }
+            Bytecode::Branch(_, _, _, cond) => {
+                self.needed.insert(*cond);
+            }
+            Bytecode::Abort(_, src) => {
+                self.needed.insert(*src);
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns true if the instruction defines a relevant temporary, or modifies it through a
+    /// reference.
+    fn is_dependency(&self, bc: &Bytecode) -> bool {
+        let (val_targets, mut_targets) = bc.modifies(self.target);
+        val_targets.iter().any(|t| self.needed.contains(t))
+            || mut_targets
+                .iter()
+                .any(|(t, _)| self.roots(*t).iter().any(|r| self.needed.contains(r)))
+    }
+
+    /// Returns the temporary and all temporaries it is (transitively) derived from.
+    fn roots(&self, temp: TempIndex) -> BTreeSet<TempIndex> {
+        let mut roots = BTreeSet::new();
+        let mut todo = vec![temp];
+        while let Some(t) = todo.pop() {
+            if roots.insert(t) {
+                if let Some(srcs) = self.derived_from.get(&t) {
+                    todo.extend(srcs.iter().cloned());
+                }
+            }
+        }
+        roots
+    }
+
+    /// Returns the covered instructions reachable from the given offset.
+    fn reachable_covered(&self, start: CodeOffset) -> BTreeSet<CodeOffset> {
+        let code = self.target.get_bytecode();
+        let label_offsets = Bytecode::label_offsets(code);
+        let mut visited = BTreeSet::new();
+        let mut todo = vec![start];
+        while let Some(offset) = todo.pop() {
+            if (offset as usize) >= code.len() || !visited.insert(offset) {
+                continue;
+            }
+            if !code[offset as usize].is_exit() {
+                todo.extend(Bytecode::get_successors(offset, code, &label_offsets));
+            }
+        }
+        visited
+            .intersection(&self.covered)
+            .cloned()
+            .collect::<BTreeSet<_>>()
+    }
+
+    /// Returns true if the operation modifies any of the given memory.
+    fn modifies_memory(&self, op: &Operation, memory: &BTreeSet<QualifiedId<StructId>>) -> bool {
+        if memory.is_empty() {
+            return false;
+        }
+        match op {
+            Operation::MoveTo(mid, sid, _)
+            | Operation::MoveFrom(mid, sid, _)
+            | Operation::BorrowGlobal(mid, sid, _) => memory.contains(&mid.qualified(*sid)),
+            Operation::Function(mid, fid, _) => {
+                // If the callee has not been analyzed, conservatively assume it modifies the
+                // memory.
+                match self
+                    .targets
+                    .get_data(&mid.qualified(*fid), &FunctionVariant::Baseline)
+                    .and_then(|data| data.annotations.get::<UsageState>())
+                {
+                    Some(usage) => usage
+                        .modified
+                        .get_all_uninst()
+                        .iter()
+                        .any(|mem| memory.contains(mem)),
+                    None => true,
+                }
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Renders the spec coverage of the functions in the target modules as a per-line heat
+/// report. Each source line of a function is prefixed with the number of conditions
+/// constraining code on that line, if the line has code.
+pub fn spec_coverage_report(env: &GlobalEnv, targets: &FunctionTargetsHolder) -> String {
+    let mut res = String::new();
+    for module_env in env.get_modules().filter(|m| m.is_target()) {
+        for fun_env in module_env.get_functions() {
+            if !targets.has_target(&fun_env, &FunctionVariant::Baseline) {
+                continue;
+            }
+            let target = targets.get_target(&fun_env, &FunctionVariant::Baseline);
+            let coverage = match get_spec_coverage(&target) {
+                Some(coverage) => coverage,
+                None => continue,
+            };
+            // Compute the heat of each line with code.
+            let mut heat: BTreeMap<usize, BTreeSet<usize>> = BTreeMap::new();
+            for (offset, bc) in target.get_bytecode().iter().enumerate() {
+                if matches!(
+                    bc,
+                    Bytecode::Label(..) | Bytecode::Jump(..) | Bytecode::Nop(..)
+                ) {
+                    continue;
+                }
+                if let Some(loc) = env.get_location(&target.get_bytecode_loc(bc.get_attr_id())) {
+                    let conds = heat.entry(loc.line.0 as usize).or_default();
+                    if let Some(covering) = coverage.covered.get(&(offset as CodeOffset)) {
+                        conds.extend(covering.iter().cloned());
+                    }
+                }
+            }
+            let fun_loc = fun_env.get_loc();
+            let (start, source) = match (env.get_location(&fun_loc), env.get_source(&fun_loc)) {
+                (Some(start), Ok(source)) => (start, source),
+                _ => continue,
+            };
+            let start_line = start.line.0 as usize;
+            let unconstrained = heat.values().filter(|c| c.is_empty()).count();
+            res.push_str(&format!(
+                "fun {} ({} condition(s), {} of {} line(s) with code unconstrained)\n",
+                fun_env.get_full_name_str(),
+                coverage.conditions.len(),
+                unconstrained,
+                heat.len()
+            ));
+            for (i, line) in source.lines().enumerate() {
+                let line_no = start_line + i;
+                let count = heat
+                    .get(&line_no)
+                    .map(|c| c.len().to_string())
+                    .unwrap_or_default();
+                // The function location may start in the middle of its first line.
+                let indent = if i == 0 { start.column.0 as usize } else { 0 };
+                res.push_str(&format!(
+                    "{:>5} |{:>3} | {:indent$}{}\n",
+                    line_no + 1,
+                    count,
+                    "",
+                    line,
+                    indent = indent
+                ));
+            }
+            res.push('\n');
+        }
+    }
+    res
+}
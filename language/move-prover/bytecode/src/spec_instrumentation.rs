@@ -20,6 +20,7 @@ use crate::{
         FunctionTargetProcessor, FunctionTargetsHolder, FunctionVariant, VerificationFlavor,
     },
     livevar_analysis::LiveVarAnalysisProcessor,
+    native_semantics::NativeSemanticsRegistry,
     options::ProverOptions,
     reaching_def_analysis::ReachingDefProcessor,
     stackless_bytecode::{
@@ -39,9 +40,9 @@ use std::{
 };
 
 const REQUIRES_FAILS_MESSAGE: &str = "precondition does not hold at this call";
-const ENSURES_FAILS_MESSAGE: &str = "post-condition does not hold";
-const ABORTS_IF_FAILS_MESSAGE: &str = "function does not abort under this condition";
-const ABORT_NOT_COVERED: &str = "abort not covered by any of the `aborts_if` clauses";
+pub(crate) const ENSURES_FAILS_MESSAGE: &str = "post-condition does not hold";
+pub(crate) const ABORTS_IF_FAILS_MESSAGE: &str = "function does not abort under this condition";
+pub const ABORT_NOT_COVERED: &str = "abort not covered by any of the `aborts_if` clauses";
 const ABORTS_CODE_NOT_COVERED: &str =
     "abort code not covered by any of the `aborts_if` or `aborts_with` clauses";
 const EMITS_FAILS_MESSAGE: &str = "function does not emit the expected event";
@@ -458,6 +459,15 @@ impl<'a> Instrumenter<'a> {
         }
     }
 
+    /// Instruments a call of a Move function. A call of a function which is not opaque is kept,
+    /// and the callee is verified on its own or inlined by the backend. A call of an opaque
+    /// function, i.e. one with `pragma opaque`, is replaced by the callee's specification, as
+    /// translated by the `SpecTranslator`: the preconditions are asserted in the verification
+    /// variant (and assumed otherwise), the abort conditions decide whether the call aborts,
+    /// the memory in `modifies` clauses and the `&mut` parameters are havoced, the results are
+    /// only assumed to be well-formed, and the post conditions are assumed. The body of the
+    /// callee is therefore never looked at, which keeps the verification conditions of deep call
+    /// chains small.
     fn instrument_call(
         &mut self,
         id: AttrId,
@@ -476,6 +486,7 @@ impl<'a> Instrumenter<'a> {
 
         let callee_env = env.get_module(mid).into_function(fid);
         let callee_opaque = callee_env.is_opaque();
+        let native_semantics = NativeSemanticsRegistry::get(env, mid.qualified(fid));
         let mut callee_spec = SpecTranslator::translate_fun_spec(
             self.options.auto_trace_level.functions(),
             true,
@@ -506,7 +517,7 @@ impl<'a> Instrumenter<'a> {
                             .set_loc_and_vc_info(loc, REQUIRES_FAILS_MESSAGE);
                         Assert
                     }
-                    FunctionVariant::Baseline => Assume,
+                    FunctionVariant::Baseline | FunctionVariant::Named(..) => Assume,
                 };
                 self.builder.emit_with(|id| Prop(id, prop_kind, cond));
             }
@@ -533,14 +544,23 @@ impl<'a> Instrumenter<'a> {
 
         // From here on code differs depending on whether the callee is opaque or not.
         if !callee_env.is_opaque() || self.options.for_interpretation {
+            // Natives which are registered as non-aborting need no abort action.
+            let can_abort = native_semantics
+                .as_ref()
+                .map(|semantics| semantics.can_abort)
+                .unwrap_or(true);
             self.builder.emit(Call(
                 id,
                 dests,
                 Operation::Function(mid, fid, targs.clone()),
                 srcs,
-                Some(AbortAction(self.abort_label, self.abort_local)),
+                if can_abort {
+                    Some(AbortAction(self.abort_label, self.abort_local))
+                } else {
+                    None
+                },
             ));
-            self.can_abort = true;
+            self.can_abort |= can_abort;
         } else {
             // Generates OpaqueCallBegin.
             self.generate_opaque_call(
@@ -604,18 +624,22 @@ impl<'a> Instrumenter<'a> {
             // There is some special case here about EventHandle types. Even though
             // they are `&mut`, they are never modified, and this is not expressed in the
             // specifications. We treat this by skipping the Havoc for them. TODO: find a better
-            // solution
+            // solution. Similarly, natives with registered semantics only havoc the parameters
+            // they are declared to modify.
             let mut_srcs = srcs
                 .iter()
                 .cloned()
-                .filter(|src| {
+                .enumerate()
+                .filter(|(idx, src)| {
                     let ty = &self.builder.data.local_types[*src];
                     ty.is_mutable_reference()
                         && !self
                             .builder
                             .global_env()
                             .is_wellknown_event_handle_type(ty.skip_reference())
+                        && !matches!(&native_semantics, Some(semantics) if !semantics.modifies(*idx))
                 })
+                .map(|(_, src)| src)
                 .collect_vec();
             for src in &mut_srcs {
                 self.builder.emit_with(|id| {
@@ -850,6 +874,14 @@ impl<'a> Instrumenter<'a> {
     }
 
     /// Generates verification conditions for abort block.
+    ///
+    /// Unless `aborts_if_is_partial` is set, the disjunction of the `aborts_if` conditions must
+    /// hold whenever the function aborts. If any condition specifies a code, either with
+    /// `aborts_if P with C`, where `C` may be a constant of another module like
+    /// `Errors::NOT_PUBLISHED`, or with `aborts_with C1, .., Cn`, the code of the abort must
+    /// also be covered: some `aborts_if` whose condition holds must have no code or that code,
+    /// or the code must be one of the codes of `aborts_with`. Aborts which are not explicit
+    /// have the code `EXECUTION_FAILURE`.
     fn generate_abort_verify(&mut self, spec: &TranslatedSpec) {
         use Bytecode::*;
         use PropKind::*;
@@ -1052,10 +1084,15 @@ impl<'a> Instrumenter<'a> {
 }
 
 //  ================================================================================================
-/// # Modifies Checker
-
 /// Check modifies annotations. This is depending on usage analysis and is therefore
 /// invoked here from the initialize trait function of this processor.
+///
+/// Modifies clauses are checked in two parts. In the verification variant of a function which
+/// declares modifies clauses for a resource, every `move_to`, `move_from` and `borrow_global_mut`
+/// of the resource, as well as every call of a function which declares to modify it, is preceded
+/// by an assertion that the address is permitted, reported at the location of the instruction
+/// (see `generate_modifies_check`). The check here covers the relation between the modifies
+/// clauses of callers and callees.
 fn check_modifies(env: &GlobalEnv, targets: &FunctionTargetsHolder) {
     for module_env in env.get_modules() {
         if module_env.is_target() {
@@ -0,0 +1,209 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Inference of the specification variables a function must update.
+//!
+//! A global invariant which relates a specification ("ghost") variable to some memory, e.g. a
+//! ghost sum to the balances it sums up, can only be preserved by the functions which modify
+//! that memory if they also update the variable. The `SpecVarFrameProcessor`, which requires
+//! the usage analysis, infers for each function the ghost variables it must update because it
+//! directly modifies memory an invariant relates to them, and the ghost variables it updates
+//! itself or through its callees. It attaches both to the baseline variant as a `SpecVarFrame`
+//! annotation. Immutable references must have been eliminated before the usage analysis, as it
+//! counts any borrow of global memory as a modification.
+//!
+//! `spec_var_frame_report` lists the functions in the target modules which do not update all
+//! the variables they must, each with a skeleton spec block containing the missing `update`
+//! statements. The new values must be filled in by the user.
+
+use crate::{
+    function_target::{FunctionData, FunctionTarget},
+    function_target_pipeline::{FunctionTargetProcessor, FunctionTargetsHolder, FunctionVariant},
+    usage_analysis,
+};
+use move_model::model::{FunctionEnv, GlobalEnv, GlobalId, ModuleId, QualifiedId, StructId};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{self, Formatter},
+};
+
+/// The annotation attached to the baseline variant of a function. Specification variables are
+/// represented by their ghost memory.
+#[derive(Debug, Clone, Default)]
+pub struct SpecVarFrame {
+    /// The specification variables the function must update, each with the global invariants
+    /// which relate it to memory the function modifies.
+    pub must_update: BTreeMap<QualifiedId<StructId>, BTreeSet<GlobalId>>,
+    /// The specification variables the function or one of its callees updates.
+    pub updated: BTreeSet<QualifiedId<StructId>>,
+}
+
+impl SpecVarFrame {
+    /// Returns the specification variables the function must but does not update.
+    pub fn missing_updates(
+        &self,
+    ) -> impl Iterator<Item = (&QualifiedId<StructId>, &BTreeSet<GlobalId>)> {
+        self.must_update
+            .iter()
+            .filter(move |(var, _)| !self.updated.contains(*var))
+    }
+}
+
+/// Returns the specification variable frame of the target, if the function has been analyzed.
+pub fn get_spec_var_frame<'env>(target: &FunctionTarget<'env>) -> Option<&'env SpecVarFrame> {
+    target.get_annotations().get::<SpecVarFrame>()
+}
+
+pub struct SpecVarFrameProcessor {}
+
+impl SpecVarFrameProcessor {
+    pub fn new() -> Box<Self> {
+        Box::new(SpecVarFrameProcessor {})
+    }
+}
+
+impl FunctionTargetProcessor for SpecVarFrameProcessor {
+    fn process(
+        &self,
+        _targets: &mut FunctionTargetsHolder,
+        fun_env: &FunctionEnv<'_>,
+        mut data: FunctionData,
+    ) -> FunctionData {
+        if fun_env.is_native_or_intrinsic() || data.variant != FunctionVariant::Baseline {
+            return data;
+        }
+        let env = fun_env.module_env.env;
+        let frame = {
+            let target = FunctionTarget::new(fun_env, &data);
+            let usage = usage_analysis::get_memory_usage(&target);
+            let is_ghost = |mem: &QualifiedId<StructId>| env.get_struct(*mem).is_ghost_memory();
+            let directly_modified = usage
+                .modified
+                .get_direct_uninst()
+                .into_iter()
+                .filter(|mem| !is_ghost(mem))
+                .collect::<BTreeSet<_>>();
+            let mut frame = SpecVarFrame {
+                must_update: BTreeMap::new(),
+                updated: usage
+                    .modified
+                    .get_all_uninst()
+                    .into_iter()
+                    .filter(|mem| is_ghost(mem))
+                    .collect(),
+            };
+            for module_env in env.get_modules() {
+                for inv in env.get_global_invariants_for_module(module_env.get_id()) {
+                    let (vars, mems): (BTreeSet<_>, BTreeSet<_>) = inv
+                        .mem_usage
+                        .iter()
+                        .map(|mem| mem.to_qualified_id())
+                        .partition(|mem| is_ghost(mem));
+                    if mems.is_disjoint(&directly_modified) {
+                        continue;
+                    }
+                    for var in vars {
+                        frame.must_update.entry(var).or_default().insert(inv.id);
+                    }
+                }
+            }
+            frame
+        };
+        data.annotations.set(frame);
+        data
+    }
+
+    fn name(&self) -> String {
+        "spec_var_frame_analysis".to_string()
+    }
+
+    fn dump_result(
+        &self,
+        f: &mut Formatter<'_>,
+        env: &GlobalEnv,
+        targets: &FunctionTargetsHolder,
+    ) -> fmt::Result {
+        writeln!(
+            f,
+            "\n\n********* Result of spec var frame analysis *********\n\n"
+        )?;
+        write!(f, "{}", spec_var_frame_report(env, targets))
+    }
+}
+
+/// Returns a report of the functions in the target modules which do not update all the
+/// specification variables they must, with skeletons of the missing updates.
+pub fn spec_var_frame_report(env: &GlobalEnv, targets: &FunctionTargetsHolder) -> String {
+    let mut res = String::new();
+    for module_env in env.get_modules().filter(|m| m.is_target()) {
+        for fun_env in module_env.get_functions() {
+            if !targets.has_target(&fun_env, &FunctionVariant::Baseline) {
+                continue;
+            }
+            let target = targets.get_target(&fun_env, &FunctionVariant::Baseline);
+            let frame = match get_spec_var_frame(&target) {
+                Some(frame) => frame,
+                None => continue,
+            };
+            let missing = frame.missing_updates().collect::<Vec<_>>();
+            if missing.is_empty() {
+                continue;
+            }
+            res.push_str(&format!(
+                "fun {} modifies memory related to specification variables it does not update\n",
+                fun_env.get_full_name_str()
+            ));
+            res.push_str(&format!(
+                "    spec {} {{\n",
+                fun_env.get_name().display(env.symbol_pool())
+            ));
+            for (var, inv_ids) in missing {
+                for inv_id in inv_ids {
+                    if let Some(inv) = env.get_global_invariant(*inv_id) {
+                        res.push_str(&format!(
+                            "        // required by the invariant {}\n",
+                            inv.loc.display(env)
+                        ));
+                    }
+                }
+                res.push_str(&format!(
+                    "        update {} = /* new value */;\n",
+                    spec_var_name(env, *var, module_env.get_id())
+                ));
+            }
+            res.push_str("    }\n\n");
+        }
+    }
+    res
+}
+
+/// Returns the name of the specification variable backed by the ghost memory, with its type
+/// parameters, as it is referred to from the given module.
+fn spec_var_name(env: &GlobalEnv, ghost_mem: QualifiedId<StructId>, from: ModuleId) -> String {
+    let struct_env = env.get_struct(ghost_mem);
+    let var_id = match struct_env.get_ghost_memory_spec_var() {
+        Some(var_id) => var_id,
+        None => return struct_env.get_full_name_str(),
+    };
+    let module_env = env.get_module(var_id.module_id);
+    let decl = module_env.get_spec_var(var_id.id);
+    let mut name = decl.name.display(env.symbol_pool()).to_string();
+    if var_id.module_id != from {
+        name = format!(
+            "{}::{}",
+            module_env.get_name().display(env.symbol_pool()),
+            name
+        );
+    }
+    if !decl.type_params.is_empty() {
+        name.push_str(&format!(
+            "<{}>",
+            decl.type_params
+                .iter()
+                .map(|(param, _)| param.display(env.symbol_pool()).to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+    name
+}
@@ -1,7 +1,7 @@
 // Copyright (c) The Diem Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::function_target::FunctionTarget;
+use crate::{function_target::FunctionTarget, native_semantics::NativeSemanticsRegistry};
 use ethnum::U256;
 use itertools::Itertools;
 use move_binary_format::file_format::CodeOffset;
@@ -9,6 +9,7 @@ use move_model::{
     ast::{Exp, ExpData, MemoryLabel, TempIndex, TraceKind},
     exp_rewriter::{ExpRewriter, ExpRewriterFunctions, RewriteTarget},
     model::{FunId, GlobalEnv, ModuleId, NodeId, QualifiedInstId, SpecVarId, StructId},
+    symbol::Symbol,
     ty::{Type, TypeDisplayContext},
 };
 use num::BigUint;
@@ -112,6 +113,15 @@ pub enum Operation {
     Pack(ModuleId, StructId, Vec<Type>),
     Unpack(ModuleId, StructId, Vec<Type>),
 
+    // Enum variants, for structs which have variants. Unpacking a variant aborts if the value
+    // is of another variant; testing a variant takes a reference to the value. A variant field
+    // is borrowed via the variants which have the field, at its offset within those variants,
+    // aborting if the value is of none of them.
+    PackVariant(ModuleId, StructId, Symbol, Vec<Type>),
+    UnpackVariant(ModuleId, StructId, Symbol, Vec<Type>),
+    TestVariant(ModuleId, StructId, Symbol, Vec<Type>),
+    BorrowVariantField(ModuleId, StructId, Vec<Symbol>, Vec<Type>, usize),
+
     // Resources
     MoveTo(ModuleId, StructId, Vec<Type>),
     MoveFrom(ModuleId, StructId, Vec<Type>),
@@ -126,6 +136,16 @@ pub enum Operation {
     GetField(ModuleId, StructId, Vec<Type>, usize),
     GetGlobal(ModuleId, StructId, Vec<Type>),
 
+    // Vector, parameterized by the element type
+    VecPack(Type, usize),
+    VecUnpack(Type, usize),
+    VecLen(Type),
+    VecBorrow(Type),
+    VecBorrowMut(Type),
+    VecPushBack(Type),
+    VecPopBack(Type),
+    VecSwap(Type),
+
     // Builtins
     Destroy,
     ReadRef,
@@ -190,6 +210,10 @@ impl Operation {
             Operation::OpaqueCallEnd(_, _, _) => false,
             Operation::Pack(_, _, _) => false,
             Operation::Unpack(_, _, _) => false,
+            Operation::PackVariant(_, _, _, _) => false,
+            Operation::UnpackVariant(_, _, _, _) => true,
+            Operation::TestVariant(_, _, _, _) => false,
+            Operation::BorrowVariantField(_, _, _, _, _) => true,
             Operation::MoveTo(_, _, _) => true,
             Operation::MoveFrom(_, _, _) => true,
             Operation::Exists(_, _, _) => false,
@@ -198,6 +222,14 @@ impl Operation {
             Operation::BorrowGlobal(_, _, _) => true,
             Operation::GetField(_, _, _, _) => false,
             Operation::GetGlobal(_, _, _) => true,
+            Operation::VecPack(_, _) => false,
+            Operation::VecUnpack(_, _) => true,
+            Operation::VecLen(_) => false,
+            Operation::VecBorrow(_) => true,
+            Operation::VecBorrowMut(_) => true,
+            Operation::VecPushBack(_) => false,
+            Operation::VecPopBack(_) => true,
+            Operation::VecSwap(_) => true,
             Operation::Destroy => false,
             Operation::ReadRef => false,
             Operation::WriteRef => false,
@@ -407,6 +439,46 @@ impl Bytecode {
         }
     }
 
+    /// Returns the instruction with the given attribute id.
+    pub fn with_attr_id(self, attr_id: AttrId) -> Self {
+        use Bytecode::*;
+        match self {
+            Assign(_, dest, src, kind) => Assign(attr_id, dest, src, kind),
+            Call(_, dests, oper, srcs, aa) => Call(attr_id, dests, oper, srcs, aa),
+            Ret(_, srcs) => Ret(attr_id, srcs),
+            Load(_, dest, cons) => Load(attr_id, dest, cons),
+            Branch(_, then_label, else_label, cond) => {
+                Branch(attr_id, then_label, else_label, cond)
+            }
+            Jump(_, label) => Jump(attr_id, label),
+            Label(_, label) => Label(attr_id, label),
+            Abort(_, src) => Abort(attr_id, src),
+            Nop(_) => Nop(attr_id),
+            SaveMem(_, label, mem) => SaveMem(attr_id, label, mem),
+            SaveSpecVar(_, label, var) => SaveSpecVar(attr_id, label, var),
+            Prop(_, kind, exp) => Prop(attr_id, kind, exp),
+        }
+    }
+
+    /// Renames the labels the instruction defines or branches to.
+    pub fn rename_labels<F>(self, f: &F) -> Self
+    where
+        F: Fn(Label) -> Label,
+    {
+        use Bytecode::*;
+        match self {
+            Branch(id, then_label, else_label, cond) => {
+                Branch(id, f(then_label), f(else_label), cond)
+            }
+            Jump(id, label) => Jump(id, f(label)),
+            Label(id, label) => Label(id, f(label)),
+            Call(id, dests, oper, srcs, Some(AbortAction(label, temp))) => {
+                Call(id, dests, oper, srcs, Some(AbortAction(f(label), temp)))
+            }
+            _ => self,
+        }
+    }
+
     /// Returns a mapping from labels to code offsets.
     pub fn label_offsets(code: &[Bytecode]) -> BTreeMap<Label, CodeOffset> {
         let mut res = BTreeMap::new();
@@ -572,6 +644,23 @@ impl Bytecode {
                     GetField(mid, sid, tys, field_num) => {
                         GetField(*mid, *sid, Type::instantiate_slice(tys, params), *field_num)
                     }
+                    // enum
+                    PackVariant(mid, sid, variant, tys) => {
+                        PackVariant(*mid, *sid, *variant, Type::instantiate_slice(tys, params))
+                    }
+                    UnpackVariant(mid, sid, variant, tys) => {
+                        UnpackVariant(*mid, *sid, *variant, Type::instantiate_slice(tys, params))
+                    }
+                    TestVariant(mid, sid, variant, tys) => {
+                        TestVariant(*mid, *sid, *variant, Type::instantiate_slice(tys, params))
+                    }
+                    BorrowVariantField(mid, sid, variants, tys, field_num) => BorrowVariantField(
+                        *mid,
+                        *sid,
+                        variants.clone(),
+                        Type::instantiate_slice(tys, params),
+                        *field_num,
+                    ),
                     // storage
                     MoveTo(mid, sid, tys) => {
                         MoveTo(*mid, *sid, Type::instantiate_slice(tys, params))
@@ -588,6 +677,15 @@ impl Bytecode {
                     GetGlobal(mid, sid, tys) => {
                         GetGlobal(*mid, *sid, Type::instantiate_slice(tys, params))
                     }
+                    // vector
+                    VecPack(ty, n) => VecPack(ty.instantiate(params), *n),
+                    VecUnpack(ty, n) => VecUnpack(ty.instantiate(params), *n),
+                    VecLen(ty) => VecLen(ty.instantiate(params)),
+                    VecBorrow(ty) => VecBorrow(ty.instantiate(params)),
+                    VecBorrowMut(ty) => VecBorrowMut(ty.instantiate(params)),
+                    VecPushBack(ty) => VecPushBack(ty.instantiate(params)),
+                    VecPopBack(ty) => VecPopBack(ty.instantiate(params)),
+                    VecSwap(ty) => VecSwap(ty.instantiate(params)),
                     // memory model
                     IsParent(node, edge) => {
                         IsParent(node.instantiate(params), edge.instantiate(params))
@@ -671,10 +769,23 @@ impl Bytecode {
                 // write-ref only distorts the value of the reference, but not the pointer itself
                 (add_abort(vec![], aa), vec![(srcs[0], false)])
             }
-            Call(_, dests, Function(..), srcs, aa) => {
+            Call(_, dests, VecPushBack(_), srcs, aa)
+            | Call(_, dests, VecPopBack(_), srcs, aa)
+            | Call(_, dests, VecSwap(_), srcs, aa) => {
+                // the vector is modified in place through the reference, but the pointer stays
+                // the same
+                (add_abort(dests.clone(), aa), vec![(srcs[0], false)])
+            }
+            Call(_, dests, Function(mid, fid, _), srcs, aa) => {
                 let mut val_targets = vec![];
                 let mut mut_targets = vec![];
-                for src in srcs {
+                // natives with registered semantics only modify the declared parameters
+                let native_semantics =
+                    NativeSemanticsRegistry::get(func_target.global_env(), mid.qualified(*fid));
+                for (idx, src) in srcs.iter().enumerate() {
+                    if matches!(&native_semantics, Some(semantics) if !semantics.modifies(idx)) {
+                        continue;
+                    }
                     if func_target.get_local_type(*src).is_mutable_reference() {
                         // values in mutable references can be distorted, but pointer stays the same
                         mut_targets.push((*src, false));
@@ -917,6 +1028,51 @@ impl<'env> fmt::Display for OperationDisplay<'env> {
                 write!(f, "unpack {}", self.struct_str(*mid, *sid, targs))?;
             }
 
+            // Enum variants
+            PackVariant(mid, sid, variant, targs) => {
+                write!(
+                    f,
+                    "pack_variant {}",
+                    self.variant_str(*mid, *sid, &[*variant], targs)
+                )?;
+            }
+            UnpackVariant(mid, sid, variant, targs) => {
+                write!(
+                    f,
+                    "unpack_variant {}",
+                    self.variant_str(*mid, *sid, &[*variant], targs)
+                )?;
+            }
+            TestVariant(mid, sid, variant, targs) => {
+                write!(
+                    f,
+                    "test_variant {}",
+                    self.variant_str(*mid, *sid, &[*variant], targs)
+                )?;
+            }
+            BorrowVariantField(mid, sid, variants, targs, offset) => {
+                write!(
+                    f,
+                    "borrow_variant_field<{}>",
+                    self.variant_str(*mid, *sid, variants, targs)
+                )?;
+                let struct_env = self
+                    .func_target
+                    .global_env()
+                    .get_module(*mid)
+                    .into_struct(*sid);
+                if let Some(field_env) = variants
+                    .first()
+                    .and_then(|variant| struct_env.get_fields_of_variant(*variant).nth(*offset))
+                {
+                    write!(
+                        f,
+                        ".{}",
+                        field_env.get_name().display(struct_env.symbol_pool())
+                    )?;
+                }
+            }
+
             // Borrow
             BorrowLoc => {
                 write!(f, "borrow_local")?;
@@ -956,6 +1112,32 @@ impl<'env> fmt::Display for OperationDisplay<'env> {
                 write!(f, "get_global<{}>", self.struct_str(*mid, *sid, targs))?;
             }
 
+            // Vector
+            VecPack(ty, n) => {
+                write!(f, "vec_pack<{}>[{}]", self.type_str(ty), n)?;
+            }
+            VecUnpack(ty, n) => {
+                write!(f, "vec_unpack<{}>[{}]", self.type_str(ty), n)?;
+            }
+            VecLen(ty) => {
+                write!(f, "vec_len<{}>", self.type_str(ty))?;
+            }
+            VecBorrow(ty) => {
+                write!(f, "vec_borrow<{}>", self.type_str(ty))?;
+            }
+            VecBorrowMut(ty) => {
+                write!(f, "vec_borrow_mut<{}>", self.type_str(ty))?;
+            }
+            VecPushBack(ty) => {
+                write!(f, "vec_push_back<{}>", self.type_str(ty))?;
+            }
+            VecPopBack(ty) => {
+                write!(f, "vec_pop_back<{}>", self.type_str(ty))?;
+            }
+            VecSwap(ty) => {
+                write!(f, "vec_swap<{}>", self.type_str(ty))?;
+            }
+
             // Resources
             MoveTo(mid, sid, targs) => {
                 write!(f, "move_to<{}>", self.struct_str(*mid, *sid, targs))?;
@@ -1103,6 +1285,32 @@ impl<'env> OperationDisplay<'env> {
         };
         format!("{}", ty.display(&tctx))
     }
+
+    fn variant_str(
+        &self,
+        mid: ModuleId,
+        sid: StructId,
+        variants: &[Symbol],
+        targs: &[Type],
+    ) -> String {
+        let pool = self.func_target.symbol_pool();
+        format!(
+            "{}::{}",
+            self.struct_str(mid, sid, targs),
+            variants
+                .iter()
+                .map(|variant| variant.display(pool).to_string())
+                .join("|")
+        )
+    }
+
+    fn type_str(&self, ty: &Type) -> String {
+        let tctx = TypeDisplayContext::WithEnv {
+            env: self.func_target.global_env(),
+            type_param_names: None,
+        };
+        format!("{}", ty.display(&tctx))
+    }
 }
 
 impl fmt::Display for Constant {
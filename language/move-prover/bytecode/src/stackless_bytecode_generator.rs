@@ -36,6 +36,7 @@ pub struct StacklessBytecodeGenerator<'a> {
     local_types: Vec<Type>,
     code: Vec<Bytecode>,
     location_table: BTreeMap<AttrId, Loc>,
+    offset_table: BTreeMap<AttrId, CodeOffset>,
     loop_invariants: BTreeSet<AttrId>,
     fallthrough_labels: BTreeSet<Label>,
 }
@@ -53,6 +54,7 @@ impl<'a> StacklessBytecodeGenerator<'a> {
             local_types,
             code: vec![],
             location_table: BTreeMap::new(),
+            offset_table: BTreeMap::new(),
             loop_invariants: BTreeSet::new(),
             fallthrough_labels: BTreeSet::new(),
         }
@@ -108,6 +110,7 @@ impl<'a> StacklessBytecodeGenerator<'a> {
             local_types,
             code,
             location_table,
+            offset_table,
             loop_invariants,
             ..
         } = self;
@@ -118,16 +121,18 @@ impl<'a> StacklessBytecodeGenerator<'a> {
             local_types,
             func_env.get_return_types(),
             location_table,
+            offset_table,
             func_env.get_acquires_global_resources(),
             loop_invariants,
         )
     }
 
-    /// Create a new attribute id and populate location table.
+    /// Create a new attribute id and populate location and offset tables.
     fn new_loc_attr(&mut self, code_offset: CodeOffset) -> AttrId {
         let loc = self.func_env.get_bytecode_loc(code_offset);
         let attr = AttrId::new(self.location_table.len());
         self.location_table.insert(attr, loc);
+        self.offset_table.insert(attr, code_offset);
         attr
     }
 
@@ -152,6 +157,14 @@ impl<'a> StacklessBytecodeGenerator<'a> {
             .get_type_actuals(Some(type_params_index))
     }
 
+    /// Returns the element type of the vector signature used by a vector bytecode.
+    fn get_vector_elem_type(&self, sig_idx: SignatureIndex) -> Type {
+        self.get_type_params(sig_idx)
+            .into_iter()
+            .next()
+            .expect("vector signature has an element type")
+    }
+
     #[allow(clippy::cognitive_complexity)]
     pub fn generate_bytecode(
         &mut self,
@@ -1081,15 +1094,116 @@ impl<'a> StacklessBytecodeGenerator<'a> {
 
             MoveBytecode::Nop => self.code.push(Bytecode::Nop(attr_id)),
 
-            // TODO: implement the translation when the the vector-related bytecode is ready
-            MoveBytecode::VecPack(..)
-            | MoveBytecode::VecLen(_)
-            | MoveBytecode::VecImmBorrow(_)
-            | MoveBytecode::VecMutBorrow(_)
-            | MoveBytecode::VecPushBack(_)
-            | MoveBytecode::VecPopBack(_)
-            | MoveBytecode::VecUnpack(..)
-            | MoveBytecode::VecSwap(_) => unimplemented!("Vector bytecode not supported yet"),
+            MoveBytecode::VecPack(sig_idx, num) => {
+                let elem_type = self.get_vector_elem_type(*sig_idx);
+                let mut elem_temp_indices = vec![];
+                for _ in 0..*num {
+                    elem_temp_indices.push(self.temp_stack.pop().unwrap());
+                }
+                elem_temp_indices.reverse();
+                let vec_temp_index = self.temp_count;
+                self.temp_stack.push(vec_temp_index);
+                self.local_types
+                    .push(Type::Vector(Box::new(elem_type.clone())));
+                self.temp_count += 1;
+                self.code.push(mk_call(
+                    Operation::VecPack(elem_type, *num as usize),
+                    vec![vec_temp_index],
+                    elem_temp_indices,
+                ));
+            }
+
+            MoveBytecode::VecUnpack(sig_idx, num) => {
+                let elem_type = self.get_vector_elem_type(*sig_idx);
+                let vec_temp_index = self.temp_stack.pop().unwrap();
+                let mut elem_temp_indices = vec![];
+                for _ in 0..*num {
+                    let elem_temp_index = self.temp_count;
+                    elem_temp_indices.push(elem_temp_index);
+                    self.temp_stack.push(elem_temp_index);
+                    self.local_types.push(elem_type.clone());
+                    self.temp_count += 1;
+                }
+                self.code.push(mk_call(
+                    Operation::VecUnpack(elem_type, *num as usize),
+                    elem_temp_indices,
+                    vec![vec_temp_index],
+                ));
+            }
+
+            MoveBytecode::VecLen(sig_idx) => {
+                let elem_type = self.get_vector_elem_type(*sig_idx);
+                let vec_ref_index = self.temp_stack.pop().unwrap();
+                let len_temp_index = self.temp_count;
+                self.temp_stack.push(len_temp_index);
+                self.local_types.push(Type::Primitive(PrimitiveType::U64));
+                self.temp_count += 1;
+                self.code.push(mk_unary(
+                    Operation::VecLen(elem_type),
+                    len_temp_index,
+                    vec_ref_index,
+                ));
+            }
+
+            MoveBytecode::VecImmBorrow(sig_idx) | MoveBytecode::VecMutBorrow(sig_idx) => {
+                let elem_type = self.get_vector_elem_type(*sig_idx);
+                let index_temp_index = self.temp_stack.pop().unwrap();
+                let vec_ref_index = self.temp_stack.pop().unwrap();
+                let elem_ref_index = self.temp_count;
+                self.temp_stack.push(elem_ref_index);
+                self.temp_count += 1;
+                let is_mut = matches!(bytecode, MoveBytecode::VecMutBorrow(..));
+                self.local_types
+                    .push(Type::Reference(is_mut, Box::new(elem_type.clone())));
+                let op = if is_mut {
+                    Operation::VecBorrowMut(elem_type)
+                } else {
+                    Operation::VecBorrow(elem_type)
+                };
+                self.code.push(mk_binary(
+                    op,
+                    elem_ref_index,
+                    vec_ref_index,
+                    index_temp_index,
+                ));
+            }
+
+            MoveBytecode::VecPushBack(sig_idx) => {
+                let elem_type = self.get_vector_elem_type(*sig_idx);
+                let elem_temp_index = self.temp_stack.pop().unwrap();
+                let vec_ref_index = self.temp_stack.pop().unwrap();
+                self.code.push(mk_call(
+                    Operation::VecPushBack(elem_type),
+                    vec![],
+                    vec![vec_ref_index, elem_temp_index],
+                ));
+            }
+
+            MoveBytecode::VecPopBack(sig_idx) => {
+                let elem_type = self.get_vector_elem_type(*sig_idx);
+                let vec_ref_index = self.temp_stack.pop().unwrap();
+                let elem_temp_index = self.temp_count;
+                self.temp_stack.push(elem_temp_index);
+                self.local_types.push(elem_type.clone());
+                self.temp_count += 1;
+                self.code.push(mk_unary(
+                    Operation::VecPopBack(elem_type),
+                    elem_temp_index,
+                    vec_ref_index,
+                ));
+            }
+
+            MoveBytecode::VecSwap(sig_idx) => {
+                let elem_type = self.get_vector_elem_type(*sig_idx);
+                let second_index = self.temp_stack.pop().unwrap();
+                let first_index = self.temp_stack.pop().unwrap();
+                let vec_ref_index = self.temp_stack.pop().unwrap();
+                self.code.push(mk_call(
+                    Operation::VecSwap(elem_type),
+                    vec![],
+                    vec![vec_ref_index, first_index, second_index],
+                ));
+            }
         }
     }
 
@@ -34,6 +34,7 @@ pub enum BlockContent {
 pub struct StacklessControlFlowGraph {
     entry_block_id: BlockId,
     blocks: Map<BlockId, Block>,
+    predecessors: Map<BlockId, Vec<BlockId>>,
     backward: bool,
 }
 
@@ -42,9 +43,11 @@ const DUMMY_EXIT: BlockId = 1;
 
 impl StacklessControlFlowGraph {
     pub fn new_forward(code: &[Bytecode]) -> Self {
+        let blocks = Self::collect_blocks(code);
         Self {
             entry_block_id: DUMMY_ENTRANCE,
-            blocks: Self::collect_blocks(code),
+            predecessors: Self::collect_predecessors(&blocks),
+            blocks,
             backward: false,
         }
     }
@@ -72,24 +75,40 @@ impl StacklessControlFlowGraph {
                 }
             });
         }
+        let blocks = block_id_to_predecessors
+            .into_iter()
+            .map(|(block_id, predecessors)| {
+                (
+                    block_id,
+                    Block {
+                        successors: predecessors,
+                        content: blocks[&block_id].content,
+                    },
+                )
+            })
+            .collect();
         Self {
             entry_block_id: DUMMY_EXIT,
-            blocks: block_id_to_predecessors
-                .into_iter()
-                .map(|(block_id, predecessors)| {
-                    (
-                        block_id,
-                        Block {
-                            successors: predecessors,
-                            content: blocks[&block_id].content,
-                        },
-                    )
-                })
-                .collect(),
+            predecessors: Self::collect_predecessors(&blocks),
+            blocks,
             backward: true,
         }
     }
 
+    fn collect_predecessors(blocks: &Map<BlockId, Block>) -> Map<BlockId, Vec<BlockId>> {
+        let mut predecessors: Map<BlockId, Vec<BlockId>> =
+            blocks.keys().map(|block_id| (*block_id, vec![])).collect();
+        for (block_id, block) in blocks {
+            for succ_block_id in &block.successors {
+                predecessors
+                    .get_mut(succ_block_id)
+                    .expect("block")
+                    .push(*block_id);
+            }
+        }
+        predecessors
+    }
+
     fn collect_blocks(code: &[Bytecode]) -> Map<BlockId, Block> {
         // First go through and collect basic block offsets.
         // Need to do this first in order to handle backwards edges.
@@ -185,6 +204,41 @@ impl StacklessControlFlowGraph {
         &self.blocks[&block_id].successors
     }
 
+    /// Returns the blocks which have the given block as successor. In a backward graph, these
+    /// are the successors of the block in the code.
+    pub fn predecessors(&self, block_id: BlockId) -> &Vec<BlockId> {
+        &self.predecessors[&block_id]
+    }
+
+    /// Returns the edges from a block to a block which is on the path from the entry block to
+    /// it, as found by a depth-first search. In the graph of a reducible function, which all Move
+    /// functions are, these are exactly the edges from the end of a loop to its header.
+    pub fn back_edges(&self) -> Set<(BlockId, BlockId)> {
+        let mut back_edges = Set::new();
+        let mut visited = Set::new();
+        let mut on_path = Set::new();
+        // The stack holds a block and the index of the next successor to visit.
+        let mut stack = vec![(self.entry_block_id, 0)];
+        visited.insert(self.entry_block_id);
+        on_path.insert(self.entry_block_id);
+        while let Some((block_id, next)) = stack.pop() {
+            let successors = self.successors(block_id);
+            if next < successors.len() {
+                stack.push((block_id, next + 1));
+                let succ_block_id = successors[next];
+                if on_path.contains(&succ_block_id) {
+                    back_edges.insert((block_id, succ_block_id));
+                } else if visited.insert(succ_block_id) {
+                    on_path.insert(succ_block_id);
+                    stack.push((succ_block_id, 0));
+                }
+            } else {
+                on_path.remove(&block_id);
+            }
+        }
+        back_edges
+    }
+
     pub fn content(&self, block_id: BlockId) -> &BlockContent {
         &self.blocks[&block_id].content
     }
@@ -267,8 +321,10 @@ impl<'env> std::fmt::Display for DotCFGBlock<'env> {
     }
 }
 
-// A dummy struct to implement fmt required by petgraph::Dot
-struct DotCFGEdge {}
+// An edge of the dot graph. Back edges are drawn dashed.
+struct DotCFGEdge {
+    is_back_edge: bool,
+}
 
 impl std::fmt::Display for DotCFGEdge {
     fn fmt(&self, _f: &mut std::fmt::Formatter) -> std::fmt::Result {
@@ -297,12 +353,15 @@ pub fn generate_cfg_in_dot_format<'env>(func_target: &'env FunctionTarget<'env>)
     }
 
     // add edges
+    let back_edges = cfg.back_edges();
     for (block_id, block) in &cfg.blocks {
         for successor in &block.successors {
             graph.add_edge(
                 *node_map.get(block_id).unwrap(),
                 *node_map.get(successor).unwrap(),
-                DotCFGEdge {},
+                DotCFGEdge {
+                    is_back_edge: back_edges.contains(&(*block_id, *successor)),
+                },
             );
         }
     }
@@ -310,8 +369,76 @@ pub fn generate_cfg_in_dot_format<'env>(func_target: &'env FunctionTarget<'env>)
     // generate dot string
     format!(
         "{}",
-        Dot::with_attr_getters(&graph, &[], &|_, _| "".to_string(), &|_, _| {
-            "shape=box".to_string()
-        })
+        Dot::with_attr_getters(
+            &graph,
+            &[],
+            &|_, edge| {
+                if edge.weight().is_back_edge {
+                    "style=dashed".to_string()
+                } else {
+                    "".to_string()
+                }
+            },
+            &|_, _| "shape=box".to_string()
+        )
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stackless_bytecode::AttrId;
+
+    // The code of `while (c) { ... }; return`:
+    //
+    //   0: label L0
+    //   1: if (c) goto 2 else goto 4
+    //   2: label L1
+    //   3: goto 0
+    //   4: label L2
+    //   5: return
+    fn loop_code() -> Vec<Bytecode> {
+        let attr = AttrId::new(0);
+        let (l0, l1, l2) = (Label::new(0), Label::new(1), Label::new(2));
+        vec![
+            Bytecode::Label(attr, l0),
+            Bytecode::Branch(attr, l1, l2, 0),
+            Bytecode::Label(attr, l1),
+            Bytecode::Jump(attr, l0),
+            Bytecode::Label(attr, l2),
+            Bytecode::Ret(attr, vec![]),
+        ]
+    }
+
+    fn block_at(cfg: &StacklessControlFlowGraph, offset: CodeOffset) -> BlockId {
+        cfg.blocks()
+            .into_iter()
+            .find(|block_id| {
+                matches!(cfg.content(*block_id), BlockContent::Basic { lower, .. } if *lower == offset)
+            })
+            .expect("block")
+    }
+
+    #[test]
+    fn predecessors() {
+        let cfg = StacklessControlFlowGraph::new_forward(&loop_code());
+        let (header, body, exit) = (block_at(&cfg, 0), block_at(&cfg, 2), block_at(&cfg, 4));
+        let mut header_preds = cfg.predecessors(header).clone();
+        header_preds.sort_unstable();
+        let mut expected = vec![cfg.entry_block(), body];
+        expected.sort_unstable();
+        assert_eq!(header_preds, expected);
+        assert_eq!(cfg.predecessors(exit), &vec![header]);
+        assert_eq!(cfg.predecessors(cfg.exit_block()), &vec![exit]);
+    }
+
+    #[test]
+    fn back_edges() {
+        let cfg = StacklessControlFlowGraph::new_forward(&loop_code());
+        let (header, body) = (block_at(&cfg, 0), block_at(&cfg, 2));
+        assert_eq!(
+            cfg.back_edges().into_iter().collect::<Vec<_>>(),
+            vec![(body, header)]
+        );
+    }
+}
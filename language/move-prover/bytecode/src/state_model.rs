@@ -0,0 +1,224 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! State model documents of modules.
+//!
+//! The state model of a module lists the resources declared by the module, together with the
+//! functions which create, modify, and destroy them, and the invariants which constrain them.
+//! The functions writing a resource are those whose write set, as computed by the usage
+//! analysis, contains the resource. How a function writes the resource is determined by the
+//! `move_to`, `borrow_global_mut`, and `move_from` operations of the function and of the
+//! functions it transitively calls. Functions of all modules are included, since they can
+//! write the resource via the functions of the declaring module.
+
+use crate::{
+    function_target_pipeline::{FunctionTargetsHolder, FunctionVariant},
+    stackless_bytecode::{Bytecode, Operation},
+    usage_analysis::get_memory_usage,
+};
+use itertools::Itertools;
+use move_model::{
+    ast::ConditionKind,
+    model::{FunId, FunctionEnv, GlobalEnv, Loc, ModuleEnv, QualifiedId, StructEnv, StructId},
+    ty::Type,
+};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The effect of a function on a resource.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ResourceEffect {
+    Create,
+    Modify,
+    Destroy,
+}
+
+/// The state model of a resource.
+#[derive(Debug, Clone)]
+pub struct ResourceModel {
+    pub resource: QualifiedId<StructId>,
+    /// The functions which write the resource, with their effects on it.
+    pub writers: BTreeMap<QualifiedId<FunId>, BTreeSet<ResourceEffect>>,
+    /// The data invariants of the resource and the global invariants referring to it.
+    pub invariants: Vec<(ConditionKind, Loc)>,
+}
+
+/// Computes the state models of the resources declared by the module. This requires the usage
+/// analysis to have been run on the baseline variants of the functions.
+pub fn state_model(module_env: &ModuleEnv, targets: &FunctionTargetsHolder) -> Vec<ResourceModel> {
+    let env = module_env.env;
+    let resources = module_env
+        .get_structs()
+        .filter(|s| s.has_memory() && !s.is_ghost_memory())
+        .collect_vec();
+    let mut models = resources
+        .iter()
+        .map(|struct_env| ResourceModel {
+            resource: struct_env.get_qualified_id(),
+            writers: BTreeMap::new(),
+            invariants: invariants_of(env, struct_env),
+        })
+        .collect_vec();
+    let mut direct_effects = BTreeMap::new();
+    for fun_env in env.get_modules().flat_map(|m| m.into_functions()) {
+        if !targets.has_target(&fun_env, &FunctionVariant::Baseline) {
+            continue;
+        }
+        let target = targets.get_target(&fun_env, &FunctionVariant::Baseline);
+        let written = get_memory_usage(&target).modified.get_all_uninst();
+        let mut effects: BTreeMap<QualifiedId<StructId>, BTreeSet<ResourceEffect>> =
+            BTreeMap::new();
+        let mut calls_native = false;
+        for fun_id in fun_env
+            .get_transitive_closure_of_called_functions()
+            .into_iter()
+            .chain(std::iter::once(fun_env.get_qualified_id()))
+        {
+            calls_native |= env.get_function(fun_id).is_native();
+            let callee_effects = direct_effects
+                .entry(fun_id)
+                .or_insert_with(|| direct_effects_of(targets, &env.get_function(fun_id)));
+            for (resource, resource_effects) in callee_effects.iter() {
+                effects
+                    .entry(*resource)
+                    .or_default()
+                    .extend(resource_effects.iter().cloned());
+            }
+        }
+        for model in &mut models {
+            if !written.contains(&model.resource) {
+                continue;
+            }
+            // The write set over-approximates the writes, e.g. by including immutable borrows.
+            // A write without any of the effects above is only considered a modification if
+            // it may stem from a native function.
+            match effects.remove(&model.resource) {
+                Some(effects) => {
+                    model.writers.insert(fun_env.get_qualified_id(), effects);
+                }
+                None if calls_native => {
+                    model.writers.insert(
+                        fun_env.get_qualified_id(),
+                        std::iter::once(ResourceEffect::Modify).collect(),
+                    );
+                }
+                None => {}
+            }
+        }
+    }
+    models
+}
+
+/// Returns the effects of the global storage operations of the function on the resources.
+fn direct_effects_of(
+    targets: &FunctionTargetsHolder,
+    fun_env: &FunctionEnv,
+) -> BTreeMap<QualifiedId<StructId>, BTreeSet<ResourceEffect>> {
+    let mut effects: BTreeMap<QualifiedId<StructId>, BTreeSet<ResourceEffect>> = BTreeMap::new();
+    if !targets.has_target(fun_env, &FunctionVariant::Baseline) {
+        return effects;
+    }
+    let target = targets.get_target(fun_env, &FunctionVariant::Baseline);
+    for bc in target.get_bytecode() {
+        let (mid, sid, effect) = match bc {
+            Bytecode::Call(_, _, Operation::MoveTo(mid, sid, _), _, _) => {
+                (mid, sid, ResourceEffect::Create)
+            }
+            Bytecode::Call(_, _, Operation::MoveFrom(mid, sid, _), _, _) => {
+                (mid, sid, ResourceEffect::Destroy)
+            }
+            Bytecode::Call(_, dests, Operation::BorrowGlobal(mid, sid, _), _, _)
+                if target.get_local_type(dests[0]).is_mutable_reference() =>
+            {
+                (mid, sid, ResourceEffect::Modify)
+            }
+            _ => continue,
+        };
+        effects
+            .entry(mid.qualified(*sid))
+            .or_default()
+            .insert(effect);
+    }
+    effects
+}
+
+/// Returns the data invariants of the struct and the global invariants referring to it, in
+/// any instantiation.
+fn invariants_of(env: &GlobalEnv, struct_env: &StructEnv) -> Vec<(ConditionKind, Loc)> {
+    let mut invariants = struct_env
+        .get_spec()
+        .conditions
+        .iter()
+        .filter(|cond| matches!(cond.kind, ConditionKind::StructInvariant))
+        .map(|cond| (cond.kind.clone(), cond.loc.clone()))
+        .collect_vec();
+    let inst = (0..struct_env.get_type_parameters().len())
+        .map(|i| Type::TypeParameter(i as u16))
+        .collect_vec();
+    let memory = struct_env
+        .module_env
+        .get_id()
+        .qualified_inst(struct_env.get_id(), inst);
+    for id in env.get_global_invariants_for_memory(&memory) {
+        if let Some(inv) = env.get_global_invariant(id) {
+            invariants.push((inv.kind.clone(), inv.loc.clone()));
+        }
+    }
+    invariants
+}
+
+/// Returns the state model document of the module, in markdown.
+pub fn state_model_doc(module_env: &ModuleEnv, targets: &FunctionTargetsHolder) -> String {
+    let env = module_env.env;
+    let mut res = format!(
+        "# State Model of Module `{}`\n",
+        module_env.get_full_name_str()
+    );
+    let models = state_model(module_env, targets);
+    if models.is_empty() {
+        res.push_str("\nThe module declares no resources.\n");
+    }
+    for model in models {
+        let struct_env = env.get_struct(model.resource);
+        res.push_str(&format!(
+            "\n## Resource `{}`\n",
+            struct_env.get_full_name_str()
+        ));
+        if model.writers.is_empty() {
+            res.push_str("\nNot written by any function.\n");
+        }
+        for (effect, title) in [
+            (ResourceEffect::Create, "Created by"),
+            (ResourceEffect::Modify, "Modified by"),
+            (ResourceEffect::Destroy, "Destroyed by"),
+        ] {
+            let funs = model
+                .writers
+                .iter()
+                .filter(|(_, effects)| effects.contains(&effect))
+                .map(|(fun_id, _)| {
+                    format!("- `{}`\n", env.get_function(*fun_id).get_full_name_str())
+                })
+                .collect::<String>();
+            if !funs.is_empty() {
+                res.push_str(&format!("\n{}:\n\n{}", title, funs));
+            }
+        }
+        if model.invariants.is_empty() {
+            res.push_str("\nNot constrained by any invariant.\n");
+        } else {
+            res.push_str("\nConstrained by:\n\n");
+            for (kind, loc) in &model.invariants {
+                let text = env
+                    .get_source(loc)
+                    .map(|s| s.split_whitespace().join(" "))
+                    .unwrap_or_else(|_| kind.to_string());
+                let declared_in = env
+                    .get_file_and_location(loc)
+                    .map(|(file, location)| format!(" ({}:{})", file, location.line.0 + 1))
+                    .unwrap_or_default();
+                res.push_str(&format!("- `{}`{}\n", text, declared_in));
+            }
+        }
+    }
+    res
+}
@@ -0,0 +1,127 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Instrument checks that recursive functions terminate.
+//!
+//! A recursive function can declare a termination measure via `pragma measure = <param>`, where
+//! the parameter is of an integer type (this is checked by the `RecursionAnalysisProcessor`).
+//! For each such function, the `TerminationInstrumenter` saves the value of the measure at
+//! entry of the regular verification variant, and asserts before each call of a function
+//! which is (mutually) recursive with it that the measure passed to the callee is strictly
+//! smaller. As integers are bounded from below, this guarantees that every call chain through
+//! the recursion is finite.
+//!
+//! A callee in the recursion which declares no measure is not checked here; the recursion
+//! analysis requires it to bound its recursion via `pragma max_call_depth` instead.
+
+use crate::{
+    function_data_builder::FunctionDataBuilder,
+    function_target::FunctionData,
+    function_target_pipeline::{
+        FunctionTargetProcessor, FunctionTargetsHolder, FunctionVariant, VerificationFlavor,
+    },
+    recursion_analysis::get_measure_param,
+    stackless_bytecode::{AssignKind, Bytecode, Operation, PropKind},
+};
+use move_model::{
+    ast::{self, TempIndex},
+    exp_generator::ExpGenerator,
+    model::FunctionEnv,
+};
+
+pub struct TerminationInstrumenter {}
+
+impl TerminationInstrumenter {
+    pub fn new() -> Box<Self> {
+        Box::new(Self {})
+    }
+}
+
+impl FunctionTargetProcessor for TerminationInstrumenter {
+    fn process(
+        &self,
+        _targets: &mut FunctionTargetsHolder,
+        fun_env: &FunctionEnv<'_>,
+        data: FunctionData,
+    ) -> FunctionData {
+        if data.variant != FunctionVariant::Verification(VerificationFlavor::Regular) {
+            // instrumentation only applies to regular verification variants
+            return data;
+        }
+        match get_measure_param(fun_env) {
+            Some(measure) => Self::instrument(fun_env, data, measure),
+            None => data,
+        }
+    }
+
+    fn name(&self) -> String {
+        "termination_instrumenter".to_string()
+    }
+}
+
+impl TerminationInstrumenter {
+    fn instrument(
+        fun_env: &FunctionEnv<'_>,
+        data: FunctionData,
+        measure: TempIndex,
+    ) -> FunctionData {
+        let env = fun_env.module_env.env;
+        let fun_id = fun_env.get_qualified_id();
+        let message = format!(
+            "termination measure `{}` of function `{}` does not decrease at this recursive \
+             call",
+            fun_env
+                .symbol_pool()
+                .string(fun_env.get_local_name(measure)),
+            fun_env.get_full_name_str()
+        );
+        let mut builder = FunctionDataBuilder::new(fun_env, data);
+        let code = std::mem::take(&mut builder.data.code);
+
+        // Save the measure after the assumptions at entry, as the parameter may be assigned
+        // later on.
+        let entry_len = code
+            .iter()
+            .take_while(|bc| matches!(bc, Bytecode::Prop(_, PropKind::Assume, _)))
+            .count();
+        let mut code = code.into_iter();
+        builder.emit_vec(code.by_ref().take(entry_len).collect());
+        let saved = builder.new_temp(builder.data.local_types[measure].clone());
+        builder.set_loc(fun_env.get_loc().at_start());
+        builder.emit_with(|id| Bytecode::Assign(id, saved, measure, AssignKind::Copy));
+
+        for bc in code {
+            if let Bytecode::Call(
+                id,
+                _,
+                Operation::Function(mid, fid, _) | Operation::OpaqueCallBegin(mid, fid, _),
+                srcs,
+                _,
+            ) = &bc
+            {
+                let callee_env = env.get_function(mid.qualified(*fid));
+                let callee_measure = if callee_env
+                    .get_transitive_closure_of_called_functions()
+                    .contains(&fun_id)
+                {
+                    get_measure_param(&callee_env)
+                } else {
+                    None
+                };
+                if let Some(callee_measure) = callee_measure {
+                    builder.set_loc_and_vc_info(builder.get_loc(*id), &message);
+                    let exp = builder.mk_bool_call(
+                        ast::Operation::Lt,
+                        vec![
+                            builder.mk_temporary(srcs[callee_measure]),
+                            builder.mk_temporary(saved),
+                        ],
+                    );
+                    builder.emit_prop(PropKind::Assert, exp);
+                }
+            }
+            builder.emit(bc);
+        }
+        builder.data
+    }
+}
@@ -12,7 +12,7 @@ use crate::{
 
 use move_binary_format::file_format::CodeOffset;
 use move_model::{
-    model::{FunctionEnv, GlobalEnv, QualifiedId, QualifiedInstId, StructId},
+    model::{FunctionEnv, GlobalEnv, LensEntry, LensKind, QualifiedId, QualifiedInstId, StructId},
     ty::Type,
 };
 
@@ -29,7 +29,7 @@ pub fn get_memory_usage<'env>(target: &FunctionTarget<'env>) -> &'env UsageState
 }
 
 /// A summary of the memory accessed / modified per function, both directly and transitively.
-#[derive(Default, Clone)]
+#[derive(Default, Clone, PartialEq)]
 pub struct MemoryUsage {
     // The memory directly used in the function.
     pub direct: SetDomain<QualifiedInstId<StructId>>,
@@ -39,10 +39,9 @@ pub struct MemoryUsage {
     pub all: SetDomain<QualifiedInstId<StructId>>,
 }
 
-#[derive(Default, Clone)]
+#[derive(Default, Clone, PartialEq)]
 pub struct UsageState {
-    /// The memory accessed by this function. This is the union of the three individual fields
-    /// below.
+    /// The memory accessed by this function. This is the union of the individual fields below.
     pub accessed: MemoryUsage,
     /// The memory modified by this function.
     pub modified: MemoryUsage,
@@ -50,6 +49,10 @@ pub struct UsageState {
     pub assumed: MemoryUsage,
     /// The memory mentioned by the assert expressions in this function.
     pub asserted: MemoryUsage,
+    /// The memory this function may publish with `move_to`. This is included in `modified`.
+    pub moved_to: MemoryUsage,
+    /// The memory this function may remove with `move_from`. This is included in `modified`.
+    pub moved_from: MemoryUsage,
 }
 
 impl MemoryUsage {
@@ -168,6 +171,12 @@ impl UsageState {
 
     generate_inserter!(asserted, add_direct);
     generate_inserter!(asserted, add_transitive);
+
+    generate_inserter!(moved_to, add_direct);
+    generate_inserter!(moved_to, add_transitive);
+
+    generate_inserter!(moved_from, add_direct);
+    generate_inserter!(moved_from, add_transitive);
 }
 
 /// Helpers for the abstract interpretation process
@@ -177,25 +186,20 @@ impl UsageState {
         self.add_transitive_modified_iter(callee.modified.get_all_inst(inst).into_iter());
         self.add_transitive_assumed_iter(callee.assumed.get_all_inst(inst).into_iter());
         self.add_transitive_asserted_iter(callee.asserted.get_all_inst(inst).into_iter());
+        self.add_transitive_moved_to_iter(callee.moved_to.get_all_inst(inst).into_iter());
+        self.add_transitive_moved_from_iter(callee.moved_from.get_all_inst(inst).into_iter());
     }
 }
 
 impl AbstractDomain for UsageState {
     fn join(&mut self, other: &Self) -> JoinResult {
-        match (
-            self.accessed.join(&other.accessed),
-            self.modified.join(&other.modified),
-            self.assumed.join(&other.assumed),
-            self.asserted.join(&other.asserted),
-        ) {
-            (
-                JoinResult::Unchanged,
-                JoinResult::Unchanged,
-                JoinResult::Unchanged,
-                JoinResult::Unchanged,
-            ) => JoinResult::Unchanged,
-            _ => JoinResult::Changed,
-        }
+        self.accessed
+            .join(&other.accessed)
+            .combine(self.modified.join(&other.modified))
+            .combine(self.assumed.join(&other.assumed))
+            .combine(self.asserted.join(&other.asserted))
+            .combine(self.moved_to.join(&other.moved_to))
+            .combine(self.moved_from.join(&other.moved_from))
     }
 }
 
@@ -234,9 +238,17 @@ impl<'a> TransferFunctions for MemoryUsageAnalysis<'a> {
                         state.subsume_callee(summary, inst);
                     }
                 }
-                MoveTo(mid, sid, inst)
-                | MoveFrom(mid, sid, inst)
-                | BorrowGlobal(mid, sid, inst) => {
+                MoveTo(mid, sid, inst) => {
+                    let mem = mid.qualified_inst(*sid, inst.to_owned());
+                    state.add_direct_moved_to(mem.clone());
+                    state.add_direct_modified(mem);
+                }
+                MoveFrom(mid, sid, inst) => {
+                    let mem = mid.qualified_inst(*sid, inst.to_owned());
+                    state.add_direct_moved_from(mem.clone());
+                    state.add_direct_modified(mem);
+                }
+                BorrowGlobal(mid, sid, inst) => {
                     let mem = mid.qualified_inst(*sid, inst.to_owned());
                     state.add_direct_modified(mem);
                 }
@@ -330,7 +342,7 @@ impl FunctionTargetProcessor for UsageProcessor {
         mut data: FunctionData,
     ) -> FunctionData {
         let summary = Self::analyze(targets, func_env, &data);
-        data.annotations.set(summary);
+        data.annotations.set_with_fixedpoint_check(summary);
         data
     }
 
@@ -338,6 +350,31 @@ impl FunctionTargetProcessor for UsageProcessor {
         "usage_analysis".to_string()
     }
 
+    fn finalize(&self, env: &GlobalEnv, targets: &mut FunctionTargetsHolder) {
+        // Expose the write sets of the functions in the target modules as lens entries.
+        for module in env.get_modules().filter(|m| m.is_target()) {
+            for fun in module.get_functions() {
+                if !targets.has_target(&fun, &FunctionVariant::Baseline) {
+                    continue;
+                }
+                let target = targets.get_target(&fun, &FunctionVariant::Baseline);
+                let modified = &get_memory_usage(&target).modified.all;
+                let title = if modified.is_empty() {
+                    "writes no global memory".to_string()
+                } else {
+                    format!(
+                        "writes {}",
+                        modified
+                            .iter()
+                            .map(|qid| env.display(qid).to_string())
+                            .join(", ")
+                    )
+                };
+                env.add_lens(fun.get_loc(), LensEntry::new(LensKind::WriteSet, title));
+            }
+        }
+    }
+
     fn dump_result(
         &self,
         f: &mut Formatter<'_>,
@@ -384,6 +421,8 @@ impl FunctionTargetProcessor for UsageProcessor {
                     print_usage(&usage.modified, "modified")?;
                     print_usage(&usage.assumed, "assumed")?;
                     print_usage(&usage.asserted, "asserted")?;
+                    print_usage(&usage.moved_to, "moved_to")?;
+                    print_usage(&usage.moved_from, "moved_from")?;
 
                     writeln!(f, "}}")?;
                 }
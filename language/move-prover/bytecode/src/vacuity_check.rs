@@ -0,0 +1,201 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Instrument checks which detect specifications that hold vacuously.
+//!
+//! A specification can be proven for the wrong reasons: an `ensures` is trivially true if no
+//! execution satisfying the preconditions reaches the end of the function, or if it is an
+//! implication whose premise never holds. Similarly, an `aborts_if` whose condition can never
+//! be satisfied does not describe any actual abort. The `VacuityCheckInstrumenter` creates, for
+//! each such check, a new verification variant of the function in which all original assertions
+//! are turned into assumptions, and a single assertion is added which is expected to fail:
+//! - `assert false` before the post-conditions are checked,
+//! - `assert !P` before a post-condition of the form `P ==> Q` is checked, and
+//! - `assert !C` at the abort point of the function for each `aborts_if C`.
+//! If the assertion can be proved, the specification holds vacuously, which is reported by the
+//! backend together with the location of the condition.
+
+use crate::{
+    function_data_builder::FunctionDataBuilder,
+    function_target::{FunctionData, FunctionTarget},
+    function_target_pipeline::{
+        FunctionTargetProcessor, FunctionTargetsHolder, FunctionVariant, VerificationFlavor,
+    },
+    spec_instrumentation::{ABORTS_IF_FAILS_MESSAGE, ABORT_NOT_COVERED, ENSURES_FAILS_MESSAGE},
+    stackless_bytecode::{Bytecode, PropKind},
+};
+
+use move_model::{
+    ast::{Exp, ExpData, Operation},
+    exp_generator::ExpGenerator,
+    model::{FunctionEnv, Loc},
+};
+
+// This prefix is for the boogie wrapper, which does not report failures of such assertions.
+const EXPECTED_TO_FAIL: &str = "expected to fail: ";
+
+const UNREACHABLE_ENSURES_MESSAGE: &str = "post-conditions hold vacuously because no execution \
+    satisfying the preconditions reaches the end of the function";
+const VACUOUS_PREMISE_MESSAGE: &str =
+    "post-condition holds vacuously because its premise is never satisfied";
+const UNSATISFIABLE_ABORTS_IF_MESSAGE: &str =
+    "condition of `aborts_if` is never satisfied under the preconditions";
+
+/// A single vacuity check: the assertion expected to fail is inserted before the bytecode at
+/// the given offset.
+struct VacuityCheck {
+    offset: usize,
+    loc: Loc,
+    exp: Exp,
+    message: &'static str,
+}
+
+pub struct VacuityCheckInstrumenter {}
+
+impl VacuityCheckInstrumenter {
+    pub fn new() -> Box<Self> {
+        Box::new(Self {})
+    }
+}
+
+impl FunctionTargetProcessor for VacuityCheckInstrumenter {
+    fn process(
+        &self,
+        targets: &mut FunctionTargetsHolder,
+        fun_env: &FunctionEnv<'_>,
+        data: FunctionData,
+    ) -> FunctionData {
+        if fun_env.is_native() || fun_env.is_intrinsic() {
+            // Nothing to do.
+            return data;
+        }
+        let flavor = match &data.variant {
+            FunctionVariant::Baseline
+            | FunctionVariant::Named(..)
+            | FunctionVariant::Verification(VerificationFlavor::Inconsistency(..))
+            | FunctionVariant::Verification(VerificationFlavor::Vacuity(..))
+            | FunctionVariant::Verification(VerificationFlavor::Equivalence) => {
+                // instrumentation only applies to regular verification variants
+                return data;
+            }
+            FunctionVariant::Verification(flavor) => flavor.clone(),
+        };
+
+        for (idx, check) in self.collect_checks(fun_env, &data).into_iter().enumerate() {
+            let new_data = data.fork(FunctionVariant::Verification(VerificationFlavor::Vacuity(
+                Box::new(flavor.clone()),
+                idx,
+            )));
+            let mut builder = FunctionDataBuilder::new(fun_env, new_data);
+            let old_code = std::mem::take(&mut builder.data.code);
+            for (offset, bc) in old_code.into_iter().enumerate() {
+                if offset == check.offset {
+                    builder.set_loc_and_vc_info(
+                        check.loc.clone(),
+                        &format!("{}{}", EXPECTED_TO_FAIL, check.message),
+                    );
+                    let exp = check.exp.clone();
+                    builder.emit_with(|id| Bytecode::Prop(id, PropKind::Assert, exp));
+                }
+                match bc {
+                    Bytecode::Prop(id, PropKind::Assert, exp) => {
+                        builder.emit(Bytecode::Prop(id, PropKind::Assume, exp))
+                    }
+                    _ => builder.emit(bc),
+                }
+            }
+            let new_data = builder.data;
+            targets.insert_target_data(
+                &fun_env.get_qualified_id(),
+                new_data.variant.clone(),
+                new_data,
+            );
+        }
+
+        // the original function data is unchanged
+        data
+    }
+
+    fn name(&self) -> String {
+        "vacuity_check_instrumenter".to_string()
+    }
+}
+
+impl VacuityCheckInstrumenter {
+    /// Collects the vacuity checks for the verified function, based on the assertions the spec
+    /// instrumentation has generated for post-conditions and abort conditions.
+    fn collect_checks(&self, fun_env: &FunctionEnv<'_>, data: &FunctionData) -> Vec<VacuityCheck> {
+        let builder = FunctionDataBuilder::new(fun_env, data.clone());
+        let mut checks = vec![];
+        let mut aborts_conds = vec![];
+        let mut abort_offset = None;
+        for (offset, bc) in data.code.iter().enumerate() {
+            let (id, exp) = match bc {
+                Bytecode::Prop(id, PropKind::Assert, exp) => (id, exp),
+                _ => continue,
+            };
+            let loc = data.locations.get(id).cloned().unwrap_or_default();
+            match data.vc_infos.get(id).map(|s| s.as_str()) {
+                Some(ENSURES_FAILS_MESSAGE) => {
+                    if !checks
+                        .iter()
+                        .any(|c: &VacuityCheck| c.message == UNREACHABLE_ENSURES_MESSAGE)
+                    {
+                        checks.push(VacuityCheck {
+                            offset,
+                            loc: fun_env.get_spec_loc(),
+                            exp: builder.mk_bool_const(false),
+                            message: UNREACHABLE_ENSURES_MESSAGE,
+                        })
+                    }
+                    if let ExpData::Call(_, Operation::Implies, args) = exp.as_ref() {
+                        checks.push(VacuityCheck {
+                            offset,
+                            loc,
+                            exp: builder.mk_not(args[0].clone()),
+                            message: VACUOUS_PREMISE_MESSAGE,
+                        })
+                    }
+                }
+                Some(ABORTS_IF_FAILS_MESSAGE) => {
+                    if let ExpData::Call(_, Operation::Not, args) = exp.as_ref() {
+                        aborts_conds.push((loc, args[0].clone()))
+                    }
+                }
+                Some(ABORT_NOT_COVERED) => abort_offset = Some(offset),
+                _ => {}
+            }
+        }
+        // The abort conditions are checked at the abort point, where they are expected to hold
+        // for some execution.
+        if let Some(offset) = abort_offset {
+            for (loc, cond) in aborts_conds {
+                checks.push(VacuityCheck {
+                    offset,
+                    loc,
+                    exp: builder.mk_not(cond),
+                    message: UNSATISFIABLE_ABORTS_IF_MESSAGE,
+                })
+            }
+        }
+        checks
+    }
+}
+
+/// Returns the location and message of the vacuity check in the given target, if it is a
+/// vacuity check variant.
+pub fn get_vacuity_check(target: &FunctionTarget<'_>) -> Option<(Loc, String)> {
+    if !matches!(
+        target.data.variant,
+        FunctionVariant::Verification(VerificationFlavor::Vacuity(..))
+    ) {
+        return None;
+    }
+    target.get_bytecode().iter().find_map(|bc| match bc {
+        Bytecode::Prop(id, PropKind::Assert, _) => {
+            let message = target.get_vc_info(*id)?.strip_prefix(EXPECTED_TO_FAIL)?;
+            Some((target.get_bytecode_loc(*id), message.to_string()))
+        }
+        _ => None,
+    })
+}
@@ -7,6 +7,7 @@
 //! checked after bytecode, checked at function exit, or deferred to caller).
 
 use crate::{
+    equivalence_check,
     function_target::{FunctionData, FunctionTarget},
     function_target_pipeline::{FunctionTargetProcessor, FunctionTargetsHolder, FunctionVariant},
     options::ProverOptions,
@@ -374,6 +375,24 @@ impl VerificationAnalysisProcessor {
         if !info.verified {
             info.verified = true;
             Self::mark_callees_inlined(fun_env, targets);
+            // The data of this function is not in the holder while it is processed, so a
+            // recursive call of it is marked here.
+            if !fun_env.is_opaque()
+                && fun_env
+                    .get_transitive_closure_of_called_functions()
+                    .contains(&fun_env.get_qualified_id())
+            {
+                info.inlined = true;
+            }
+            // An equivalence check calls the implementations of the function and the function
+            // it is compared with.
+            if let Some(other_id) = equivalence_check::get_equivalence_partner(fun_env) {
+                info.inlined = true;
+                if other_id != fun_env.get_qualified_id() {
+                    let other_env = fun_env.module_env.env.get_function(other_id);
+                    Self::mark_inlined(&other_env, targets);
+                }
+            }
         }
     }
 
@@ -388,9 +407,11 @@ impl VerificationAnalysisProcessor {
 
         // at this time, we only have the `baseline` variant in the targets
         let variant = FunctionVariant::Baseline;
-        let data = targets
-            .get_data_mut(&fun_env.get_qualified_id(), &variant)
-            .expect("function data defined");
+        let data = match targets.get_data_mut(&fun_env.get_qualified_id(), &variant) {
+            Some(data) => data,
+            // the function being processed, see `mark_verified`
+            None => return,
+        };
         let info = data.annotations.get_or_default_mut::<VerificationInfo>();
         if !info.inlined {
             info.inlined = true;
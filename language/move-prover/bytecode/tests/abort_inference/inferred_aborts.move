@@ -0,0 +1,69 @@
+module 0x42::InferredAborts {
+    const EINVALID: u64 = 1;
+    const EOTHER: u64 = 2;
+
+    struct R has key { value: u64 }
+    struct S has key { value: u64 }
+
+    fun check(x: u64) {
+        if (x == 0) abort EINVALID;
+    }
+    spec check {
+        aborts_if x == 0 with EINVALID;
+    }
+
+    // The abort of `check` is propagated, and its code is covered.
+    fun check_twice(x: u64, y: u64) {
+        check(x);
+        check(y);
+    }
+    spec check_twice {
+        aborts_if x == 0 || y == 0 with EINVALID;
+    }
+
+    // The propagated code `EINVALID` is not covered.
+    fun check_other(x: u64) {
+        check(x);
+        if (x > 10) abort EOTHER;
+    }
+    spec check_other {
+        aborts_if x > 10 with EOTHER;
+    }
+
+    // The missing `S` is not covered. The missing `R` is, and the overflow is covered by any
+    // condition.
+    fun sum(addr: address): u64 acquires R, S {
+        borrow_global<R>(addr).value + borrow_global<S>(addr).value
+    }
+    spec sum {
+        aborts_if !exists<R>(addr);
+    }
+
+    // Declared not to abort, but it can.
+    fun publish(account: &signer) {
+        move_to(account, R { value: 0 });
+    }
+    spec publish {
+        aborts_if false;
+    }
+
+    // The same, but declared to be partial.
+    fun publish_partial(account: &signer) {
+        move_to(account, S { value: 0 });
+    }
+    spec publish_partial {
+        pragma aborts_if_is_partial;
+        aborts_if false;
+    }
+
+    // The causes of recursive functions are not known, so the call itself remains.
+    fun count(n: u64): u64 {
+        if (n == 0) 0 else count(n - 1) + 1
+    }
+    fun call_count(n: u64): u64 {
+        count(n)
+    }
+    spec call_count {
+        aborts_if false;
+    }
+}
@@ -0,0 +1,64 @@
+// dep: ../../move-stdlib/sources/Vector.move
+
+module 0x42::AbortPaths {
+    use Std::Vector;
+
+    const ENOT_POSITIVE: u64 = 1;
+    const ETOO_LARGE: u64 = 2;
+
+    struct R has key { value: u64 }
+
+    // Does not abort, and is not reported.
+    fun id(x: u64): u64 {
+        x
+    }
+
+    // Calls of functions which do not abort cannot abort either.
+    fun call_id(x: u64): u64 {
+        id(x)
+    }
+
+    fun check(x: u64) {
+        if (x == 0) abort ENOT_POSITIVE;
+        if (x > 100) abort ETOO_LARGE;
+    }
+    spec check {
+        aborts_if x == 0 with ENOT_POSITIVE;
+        aborts_if x > 100 with ETOO_LARGE;
+    }
+
+    // The code `ETOO_LARGE` is not specified.
+    fun check_partially(x: u64) {
+        if (x == 0) abort ENOT_POSITIVE;
+        if (x > 100 && x != 1000) abort ETOO_LARGE;
+    }
+    spec check_partially {
+        aborts_if x == 0 with ENOT_POSITIVE;
+    }
+
+    // No abort conditions at all.
+    fun incr(addr: address): u64 acquires R {
+        let r = borrow_global_mut<R>(addr);
+        r.value = r.value + 1;
+        r.value
+    }
+
+    // Fewer `aborts_if` conditions than causes.
+    fun publish(account: &signer, v: vector<u64>) {
+        check(*Vector::borrow(&v, 0));
+        move_to(account, R { value: 0 });
+    }
+    spec publish {
+        aborts_if len(v) == 0;
+    }
+
+    // The same, but declared to be partial.
+    fun publish_partial(account: &signer, v: vector<u64>) {
+        check(*Vector::borrow(&v, 0));
+        move_to(account, R { value: 0 });
+    }
+    spec publish_partial {
+        pragma aborts_if_is_partial;
+        aborts_if len(v) == 0;
+    }
+}
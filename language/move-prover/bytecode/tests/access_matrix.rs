@@ -0,0 +1,72 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for the resource access matrices of modules.
+
+mod common;
+
+use move_stackless_bytecode::access_matrix::{AccessMatrix, ResourceAccess};
+
+const MODULES: &str = "
+module 0x42::M {
+    friend 0x42::N;
+
+    struct R has key { v: u64 }
+
+    public fun publish(s: &signer) { move_to(s, R { v: 1 }) }
+
+    public(friend) fun bump(a: address) acquires R {
+        let r = borrow_global_mut<R>(a);
+        r.v = r.v + 1;
+    }
+
+    fun remove(a: address) acquires R {
+        let R { v: _ } = move_from<R>(a);
+    }
+
+    public fun reset(s: &signer, a: address) acquires R {
+        if (exists<R>(a)) remove(a);
+        publish(s);
+    }
+
+    public fun value(a: address): u64 acquires R { borrow_global<R>(a).v }
+
+    public fun unrelated(x: u64): u64 { x }
+}
+
+module 0x42::N {
+    use 0x42::M;
+    public(script) fun bump_twice(a: address) { M::bump(a); M::bump(a) }
+}
+";
+
+#[test]
+fn access_matrix() {
+    let env = common::build("access_matrix", MODULES);
+    let matrix = AccessMatrix::new(&env, &common::targets(&env));
+    // Functions which do not access resources have no entries.
+    assert_eq!(
+        matrix.to_csv(),
+        "resource,function,visibility,callable_from,read,write,create,destroy\n\
+         0x42::M::R,0x42::M::bump,friend,0x42::M 0x42::N,,direct,,\n\
+         0x42::M::R,0x42::M::publish,public,*,,,direct,\n\
+         0x42::M::R,0x42::M::remove,private,0x42::M,,,,direct\n\
+         0x42::M::R,0x42::M::reset,public,*,direct,,indirect,indirect\n\
+         0x42::M::R,0x42::M::value,public,*,direct,,,\n\
+         0x42::M::R,0x42::N::bump_twice,script,*,,indirect,,\n"
+    );
+
+    let reset = matrix
+        .entries
+        .iter()
+        .find(|e| e.function == "0x42::M::reset")
+        .unwrap();
+    assert_eq!(
+        reset.direct.iter().cloned().collect::<Vec<_>>(),
+        vec![ResourceAccess::Read]
+    );
+    assert_eq!(
+        reset.indirect.iter().cloned().collect::<Vec<_>>(),
+        vec![ResourceAccess::Create, ResourceAccess::Destroy]
+    );
+}
@@ -0,0 +1,101 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for the aliasing of references computed by the borrow analysis.
+
+mod common;
+
+use move_binary_format::file_format::CodeOffset;
+use move_stackless_bytecode::{
+    borrow_analysis::{BorrowAnalysisProcessor, BorrowAnnotation},
+    eliminate_imm_refs::EliminateImmRefsProcessor,
+    function_target_pipeline::{FunctionTargetPipeline, FunctionVariant},
+    livevar_analysis::LiveVarAnalysisProcessor,
+    mut_ref_instrumentation::MutRefInstrumenter,
+    reaching_def_analysis::ReachingDefProcessor,
+    stackless_bytecode::{BorrowEdge, BorrowNode},
+};
+
+const MODULE: &str = "
+module 0x1::Vector {
+    native public fun borrow_mut<E>(v: &mut vector<E>, i: u64): &mut E;
+}
+
+module 0x42::M {
+    use 0x1::Vector;
+
+    struct S has drop { a: u64, b: u64 }
+
+    fun fields(s: &mut S) {
+        let a = &mut s.a;
+        let b = &mut s.b;
+        *a = 1;
+        *b = 2;
+    }
+
+    fun elements(elems: &mut vector<S>) {
+        let x = Vector::borrow_mut(elems, 0);
+        let a = &mut x.a;
+        *a = 1;
+    }
+}
+";
+
+#[test]
+fn aliasing() {
+    let env = common::build("borrow_aliasing", MODULE);
+    let mut targets = common::targets(&env);
+    let mut pipeline = FunctionTargetPipeline::default();
+    pipeline.add_processor(EliminateImmRefsProcessor::new());
+    pipeline.add_processor(MutRefInstrumenter::new());
+    pipeline.add_processor(ReachingDefProcessor::new());
+    pipeline.add_processor(LiveVarAnalysisProcessor::new());
+    pipeline.add_processor(BorrowAnalysisProcessor::new());
+    pipeline.run(&env, &mut targets);
+    let s_id = env
+        .find_module_by_name_str("0x42::M")
+        .unwrap()
+        .find_struct(env.symbol_pool().make("S"))
+        .unwrap()
+        .get_qualified_id()
+        .instantiate(vec![]);
+
+    // `$t3 := borrow_field<M::S>.a($t0)` and `$t4 := borrow_field<M::S>.b($t0)`: both alias the
+    // parameter, but not each other.
+    let fun_env = env.find_function_by_name_str("M::fields").unwrap();
+    let target = targets.get_target(&fun_env, &FunctionVariant::Baseline);
+    let annotation = target.get_annotations().get::<BorrowAnnotation>().unwrap();
+    let ret = (target.get_bytecode().len() - 1) as CodeOffset;
+    let info = &annotation.get_borrow_info_at(ret).unwrap().before;
+    assert_eq!(
+        info.get_aliases().into_iter().collect::<Vec<_>>(),
+        vec![(0, 3), (0, 4)]
+    );
+    assert!(annotation.may_alias_at(ret, 3, 0));
+    assert!(!annotation.may_alias_at(ret, 3, 4));
+    assert!(!annotation.may_alias_at(0, 3, 0));
+
+    // `$t4 := Vector::borrow_mut<M::S>($t0, $t3)` and `$t5 := borrow_field<M::S>.a($t4)`: the
+    // field of an element aliases the element and the vector.
+    let fun_env = env.find_function_by_name_str("M::elements").unwrap();
+    let target = targets.get_target(&fun_env, &FunctionVariant::Baseline);
+    let annotation = target.get_annotations().get::<BorrowAnnotation>().unwrap();
+    let ret = (target.get_bytecode().len() - 1) as CodeOffset;
+    let info = &annotation.get_borrow_info_at(ret).unwrap().before;
+    assert_eq!(
+        info.get_access_paths(&BorrowNode::Reference(5)),
+        vec![(
+            BorrowNode::Reference(0),
+            vec![BorrowEdge::Index, BorrowEdge::Field(s_id, 0)]
+        )]
+    );
+    assert_eq!(
+        info.get_access_paths(&BorrowNode::Reference(0)),
+        vec![(BorrowNode::Reference(0), vec![])]
+    );
+    assert_eq!(
+        info.get_aliases().into_iter().collect::<Vec<_>>(),
+        vec![(0, 4), (0, 5), (4, 5)]
+    );
+    assert_eq!(info.get_edges().count(), 2);
+}
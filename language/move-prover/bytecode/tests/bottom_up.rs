@@ -0,0 +1,65 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for the bottom-up order of the functions of a targets holder.
+
+mod common;
+
+use move_model::model::GlobalEnv;
+use move_stackless_bytecode::function_target_pipeline::FunctionGroup;
+
+const MODULES: &str = "
+module 0x42::M {
+    public fun leaf(): u64 { 1 }
+
+    public fun even(n: u64): bool { if (n == 0) true else odd(n - 1) }
+
+    public fun odd(n: u64): bool { if (n == 0) false else even(n - 1) }
+
+    public fun count(n: u64): u64 { if (n == 0) leaf() else count(n - 1) + 1 }
+}
+
+module 0x42::N {
+    use 0x42::M;
+    public fun top(n: u64): bool { M::count(n) > 0 && M::even(n) }
+}
+";
+
+/// Returns the names of the functions of the groups, with whether they are recursive.
+fn names(env: &GlobalEnv, groups: &[FunctionGroup]) -> Vec<(Vec<String>, bool)> {
+    groups
+        .iter()
+        .map(|group| {
+            let funs = group
+                .funs
+                .iter()
+                .map(|id| env.get_function(*id).get_full_name_str())
+                .collect();
+            (funs, group.is_recursive)
+        })
+        .collect()
+}
+
+#[test]
+fn funs_bottom_up() {
+    let env = common::build("bottom_up", MODULES);
+    let targets = common::targets(&env);
+    let groups = names(&env, &targets.get_funs_bottom_up(&env));
+    let position = |name: &str| {
+        groups
+            .iter()
+            .position(|(funs, _)| funs.iter().any(|f| f == name))
+            .unwrap()
+    };
+
+    // Each function is in exactly one group, and mutually recursive functions share a group.
+    assert_eq!(groups.iter().map(|(funs, _)| funs.len()).sum::<usize>(), 5);
+    assert!(groups.contains(&(vec!["M::even".to_string(), "M::odd".to_string()], true)));
+    assert!(groups.contains(&(vec!["M::count".to_string()], true)));
+    assert!(groups.contains(&(vec!["M::leaf".to_string()], false)));
+    assert!(groups.contains(&(vec!["N::top".to_string()], false)));
+    // Callees come before their callers.
+    assert!(position("M::leaf") < position("M::count"));
+    assert!(position("M::count") < position("N::top"));
+    assert!(position("M::even") < position("N::top"));
+}
@@ -0,0 +1,95 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for the compiled code offsets of bytecode after transformations.
+
+mod common;
+
+use move_binary_format::file_format::CodeOffset;
+use move_stackless_bytecode::{
+    function_target_pipeline::{FunctionTargetPipeline, FunctionVariant},
+    inlining::InliningProcessor,
+    loop_analysis::LoopDetectionProcessor,
+    loop_unrolling::LoopUnrollingProcessor,
+    stackless_bytecode::{Bytecode, Operation},
+};
+use std::collections::{BTreeMap, BTreeSet};
+
+const MODULE: &str = "
+module 0x42::M {
+    fun inc(x: u64): u64 { x + 1 }
+    spec inc { pragma inline; }
+
+    fun sum(n: u64): u64 {
+        let i = 0;
+        let s = 0;
+        while (i < n) {
+            s = inc(s);
+            i = i + 1;
+        };
+        s
+    }
+    spec sum { pragma unroll = 2; }
+}
+";
+
+#[test]
+fn offsets_survive_inlining_and_unrolling() {
+    let env = common::build("code_offsets", MODULE);
+    let mut targets = common::targets(&env);
+    let fun_env = env.find_function_by_name_str("M::sum").unwrap();
+
+    // Collect the offsets of the generated code: all of it, the loop body, and the call.
+    let target = targets.get_target(&fun_env, &FunctionVariant::Baseline);
+    let code = target.get_bytecode();
+    let offset_of = |bc: &Bytecode| target.get_bytecode_offset(bc.get_attr_id()).unwrap();
+    let all = code.iter().map(offset_of).collect::<BTreeSet<_>>();
+    let loops = LoopDetectionProcessor::analyze(code).loops;
+    assert_eq!(loops.len(), 1);
+    let body = loops[0]
+        .body
+        .iter()
+        .map(|offset| offset_of(&code[*offset as usize]))
+        .collect::<BTreeSet<_>>();
+    let call = code
+        .iter()
+        .find(|bc| matches!(bc, Bytecode::Call(_, _, Operation::Function(..), _, _)))
+        .map(offset_of)
+        .unwrap();
+
+    let mut pipeline = FunctionTargetPipeline::default();
+    pipeline.add_processor(InliningProcessor::new());
+    pipeline.add_processor(LoopUnrollingProcessor::new());
+    pipeline.run(&env, &mut targets);
+    assert!(!env.has_errors());
+
+    let target = targets.get_target(&fun_env, &FunctionVariant::Baseline);
+    let mut counts: BTreeMap<CodeOffset, usize> = BTreeMap::new();
+    let mut inlined = 0;
+    for bc in target.get_bytecode() {
+        // Every instruction still derives from an instruction of the compiled function.
+        let offset = target
+            .get_bytecode_offset(bc.get_attr_id())
+            .unwrap_or_else(|| panic!("no code offset for `{:?}`", bc));
+        assert!(all.contains(&offset));
+        *counts.entry(offset).or_default() += 1;
+        // The code of the callee derives from the call.
+        assert!(!matches!(
+            bc,
+            Bytecode::Call(_, _, Operation::Function(..), _, _)
+        ));
+        if target
+            .get_bytecode_loc(bc.get_attr_id())
+            .expanded_from()
+            .is_some()
+        {
+            assert_eq!(offset, call);
+            inlined += 1;
+        }
+    }
+    assert!(inlined > 0);
+    // The body of the loop has been copied for the second iteration.
+    for offset in body {
+        assert!(counts[&offset] >= 2);
+    }
+}
@@ -0,0 +1,53 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Helpers shared by the tests which run processors on modules given as source text.
+#![allow(dead_code)]
+
+use move_compiler::shared::PackagePaths;
+use move_model::{model::GlobalEnv, options::ModelBuilderOptions, run_model_builder_with_options};
+use move_stackless_bytecode::function_target_pipeline::FunctionTargetsHolder;
+use std::{
+    collections::BTreeMap,
+    fs,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Counts the models built, so tests running in parallel do not share a source file.
+static BUILD_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Builds a model from the source text, which must not contain errors. The name identifies the
+/// test in the name of the temporary source file.
+pub fn build(name: &str, source: &str) -> GlobalEnv {
+    let path = std::env::temp_dir().join(format!(
+        "{}_{}_{}.move",
+        name,
+        std::process::id(),
+        BUILD_COUNT.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::write(&path, source).unwrap();
+    let env = run_model_builder_with_options(
+        vec![PackagePaths {
+            name: None,
+            paths: vec![path.to_string_lossy().to_string()],
+            named_address_map: BTreeMap::<String, _>::new(),
+        }],
+        vec![],
+        ModelBuilderOptions::default(),
+    )
+    .unwrap();
+    fs::remove_file(&path).unwrap();
+    assert!(!env.has_errors());
+    env
+}
+
+/// Returns the baseline targets of all functions of the environment.
+pub fn targets(env: &GlobalEnv) -> FunctionTargetsHolder {
+    let mut targets = FunctionTargetsHolder::default();
+    for module_env in env.get_modules() {
+        for fun_env in module_env.get_functions() {
+            targets.add_target(&fun_env);
+        }
+    }
+    targets
+}
@@ -0,0 +1,11 @@
+module 0x42::TestConditionSplitting {
+
+    public fun pair(x: u64, y: u64): (u64, u64) {
+        (x, y)
+    }
+    spec pair {
+        ensures result_1 == x;
+        ensures result_2 == y;
+        ensures result_1 >= x;
+    }
+}
@@ -0,0 +1,67 @@
+module 0x42::TestConstantPropagation {
+    fun test_fold(): u64 {
+        let x = 2;
+        let y = x * 3;
+        y + 1
+    }
+
+    fun test_branch(a: u64): u64 {
+        let c = 1 < 2;
+        if (c) {
+            a + 1
+        } else {
+            a
+        }
+    }
+
+    fun test_join(c: bool): u64 {
+        let x;
+        if (c) {
+            x = 1;
+        } else {
+            x = 1;
+        };
+        x + 2
+    }
+
+    fun test_join_branch(c: bool): u64 {
+        let b;
+        if (c) {
+            b = 1;
+        } else {
+            b = 2;
+        };
+        let d;
+        if (c) {
+            d = true;
+        } else {
+            d = true;
+        };
+        if (d) {
+            b
+        } else {
+            0
+        }
+    }
+
+    fun test_overflow(): u8 {
+        let x = 255u8;
+        x + 1
+    }
+
+    fun test_loop(n: u64): u64 {
+        let i = 0;
+        let k = 10;
+        while (i < n) {
+            i = i + k;
+        };
+        i
+    }
+
+    fun test_borrow(): u64 {
+        let x = 1;
+        let r = &mut x;
+        *r = 2;
+        x + 1
+    }
+}
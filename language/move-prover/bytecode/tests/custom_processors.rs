@@ -0,0 +1,95 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for registering custom processors at named points of the standard pipelines. The
+//! registry is global, so all registrations happen in one test.
+
+use move_model::model::FunctionEnv;
+use move_stackless_bytecode::{
+    function_target::FunctionData,
+    function_target_pipeline::{
+        FunctionTargetPipeline, FunctionTargetProcessor, FunctionTargetsHolder, PipelineConfig,
+    },
+    options::ProverOptions,
+    pipeline_factory::{self, InsertionPoint},
+};
+
+/// A processor which leaves the code unchanged, with the given name.
+macro_rules! marker {
+    ($ty:ident, $name:expr) => {
+        struct $ty;
+
+        impl FunctionTargetProcessor for $ty {
+            fn process(
+                &self,
+                _targets: &mut FunctionTargetsHolder,
+                _func_env: &FunctionEnv<'_>,
+                data: FunctionData,
+            ) -> FunctionData {
+                data
+            }
+
+            fn name(&self) -> String {
+                $name.to_string()
+            }
+        }
+    };
+}
+
+marker!(First, "first_marker");
+marker!(Second, "second_marker");
+marker!(Unplaced, "unplaced_marker");
+
+/// Returns the position of the processor in the names.
+fn position(names: &[String], name: &str) -> Option<usize> {
+    names.iter().position(|n| n == name)
+}
+
+#[test]
+fn registered_processors() {
+    let before = pipeline_factory::default_pipeline().processor_names();
+    pipeline_factory::register_processor(
+        || Box::new(First),
+        Some(InsertionPoint::After("eliminate_imm_refs".to_string())),
+    );
+    pipeline_factory::register_processor(
+        || Box::new(Second),
+        Some(InsertionPoint::After("eliminate_imm_refs".to_string())),
+    );
+    pipeline_factory::register_processor(
+        || Box::new(Second),
+        Some(InsertionPoint::Before("loop_unrolling".to_string())),
+    );
+    pipeline_factory::register_processor(|| Box::new(Unplaced), None);
+
+    // Processors at the same point run in the order of registration. The point before loop
+    // unrolling is not part of the default pipeline.
+    let names = pipeline_factory::default_pipeline().processor_names();
+    assert_eq!(names.len(), before.len() + 2);
+    let anchor = position(&names, "eliminate_imm_refs").unwrap();
+    assert_eq!(position(&names, "first_marker"), Some(anchor + 1));
+    assert_eq!(position(&names, "second_marker"), Some(anchor + 2));
+    assert_eq!(
+        position(&names, "mut_ref_instrumentation"),
+        Some(anchor + 3)
+    );
+    assert_eq!(position(&names, "unplaced_marker"), None);
+    let names = pipeline_factory::default_pipeline_with_options(&ProverOptions {
+        unroll_loops: true,
+        ..Default::default()
+    })
+    .processor_names();
+    let anchor = position(&names, "loop_unrolling").unwrap();
+    assert_eq!(names[anchor - 1], "second_marker");
+
+    // Registered processors can be named in configurations.
+    let config =
+        PipelineConfig::from_toml(r#"processors = ["unplaced_marker", "eliminate_imm_refs"]"#)
+            .unwrap();
+    assert_eq!(
+        FunctionTargetPipeline::from_config(&config)
+            .unwrap()
+            .processor_names(),
+        vec!["unplaced_marker", "eliminate_imm_refs"]
+    );
+}
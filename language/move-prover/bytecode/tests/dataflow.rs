@@ -0,0 +1,113 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for running dataflow analyses via the shared fixpoint computation.
+
+use move_binary_format::file_format::CodeOffset;
+use move_model::ast::TempIndex;
+use move_stackless_bytecode::{
+    dataflow_analysis::{DataflowAnalysis, TransferFunctions},
+    dataflow_domains::SetDomain,
+    stackless_bytecode::{AssignKind, AttrId, Bytecode, Constant, Label},
+};
+use std::collections::BTreeMap;
+
+/// A forward analysis of the temporaries which may have been assigned.
+struct Assigned;
+
+impl TransferFunctions for Assigned {
+    type State = SetDomain<TempIndex>;
+    const BACKWARD: bool = false;
+
+    fn execute(&self, state: &mut Self::State, instr: &Bytecode, _offset: CodeOffset) {
+        match instr {
+            Bytecode::Assign(_, dest, _, _) | Bytecode::Load(_, dest, _) => {
+                state.insert(*dest);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl DataflowAnalysis for Assigned {}
+
+/// A backward analysis of the temporaries which may be read later.
+struct Read;
+
+impl TransferFunctions for Read {
+    type State = SetDomain<TempIndex>;
+    const BACKWARD: bool = true;
+
+    fn execute(&self, state: &mut Self::State, instr: &Bytecode, _offset: CodeOffset) {
+        match instr {
+            Bytecode::Assign(_, _, src, _) => {
+                state.insert(*src);
+            }
+            Bytecode::Branch(_, _, _, cond) => {
+                state.insert(*cond);
+            }
+            Bytecode::Ret(_, srcs) => state.extend(srcs.iter().cloned()),
+            _ => {}
+        }
+    }
+}
+
+impl DataflowAnalysis for Read {}
+
+fn attr() -> AttrId {
+    AttrId::new(0)
+}
+
+/// Returns the temporaries of the result at each offset, in order.
+fn temps(result: BTreeMap<CodeOffset, SetDomain<TempIndex>>) -> Vec<Vec<TempIndex>> {
+    result
+        .into_values()
+        .map(|s| s.into_iter().collect())
+        .collect()
+}
+
+#[test]
+fn forward_analysis() {
+    let (l1, l2, l3) = (Label::new(1), Label::new(2), Label::new(3));
+    let code = vec![
+        Bytecode::Load(attr(), 0, Constant::Bool(true)),
+        Bytecode::Branch(attr(), l1, l2, 0),
+        Bytecode::Label(attr(), l1),
+        Bytecode::Assign(attr(), 1, 0, AssignKind::Copy),
+        Bytecode::Jump(attr(), l3),
+        Bytecode::Label(attr(), l2),
+        Bytecode::Load(attr(), 2, Constant::U64(1)),
+        Bytecode::Label(attr(), l3),
+        Bytecode::Ret(attr(), vec![1]),
+    ];
+    // The state after each instruction; the branches are joined at `l3`.
+    let result = Assigned.analyze_code(SetDomain::default(), &code, |_, after| after.clone());
+    assert_eq!(
+        temps(result),
+        vec![
+            vec![0],
+            vec![0],
+            vec![0],
+            vec![0, 1],
+            vec![0, 1],
+            vec![0],
+            vec![0, 2],
+            vec![0, 1, 2],
+            vec![0, 1, 2],
+        ]
+    );
+}
+
+#[test]
+fn backward_analysis_of_infinite_loop() {
+    let l0 = Label::new(0);
+    let code = vec![
+        Bytecode::Label(attr(), l0),
+        Bytecode::Assign(attr(), 1, 0, AssignKind::Copy),
+        Bytecode::Jump(attr(), l0),
+    ];
+    // The loop cannot reach an exit, but is analyzed nevertheless. The state before each
+    // instruction includes the reads of the next iteration.
+    let result = Read.analyze_code(SetDomain::default(), &code, |before, _| before.clone());
+    assert_eq!(temps(result), vec![vec![0], vec![0], vec![0]]);
+}
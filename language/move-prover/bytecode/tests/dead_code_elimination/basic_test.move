@@ -0,0 +1,38 @@
+module 0x42::TestDeadCode {
+    struct R has copy, drop {
+        x: u64
+    }
+
+    fun dead_comparison(a: u64, b: u64): u64 {
+        let _c = a < b;
+        let _d = !(a == b);
+        a + b
+    }
+
+    fun dead_field(r: R): u64 {
+        let _x = r.x;
+        1
+    }
+
+    fun aborting_op_kept(a: u64, b: u64): u64 {
+        let _c = a + b;
+        a
+    }
+
+    fun unreachable_after_return(b: bool): u64 {
+        if (b) {
+            return 1
+        } else {
+            return 2
+        };
+        3
+    }
+
+    fun unreachable_loop_exit(): u64 {
+        let i = 0;
+        loop {
+            i = i + 1;
+        };
+        i
+    }
+}
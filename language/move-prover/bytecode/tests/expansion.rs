@@ -0,0 +1,51 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for the expansion sites recorded in the locations of inlined code.
+
+mod common;
+
+use move_stackless_bytecode::{
+    function_target_pipeline::{FunctionTargetPipeline, FunctionVariant},
+    inlining::InliningProcessor,
+    stackless_bytecode::Bytecode,
+};
+
+const MODULE: &str = "
+module 0x42::M {
+    fun inner(x: u64) { if (x > 0) abort 1 }
+    spec inner { pragma inline; }
+
+    fun middle(x: u64) { inner(x) }
+    spec middle { pragma inline; }
+
+    fun outer(x: u64) { middle(x) }
+}
+";
+
+#[test]
+fn expansion_sites_of_inlined_code() {
+    let env = common::build("expansion", MODULE);
+    let mut targets = common::targets(&env);
+    let mut pipeline = FunctionTargetPipeline::default();
+    pipeline.add_processor(InliningProcessor::new());
+    pipeline.run(&env, &mut targets);
+    assert!(!env.has_errors());
+
+    let fun_env = env.find_function_by_name_str("M::outer").unwrap();
+    let target = targets.get_target(&fun_env, &FunctionVariant::Baseline);
+    let abort = target
+        .get_bytecode()
+        .iter()
+        .find(|bc| matches!(bc, Bytecode::Abort(..)))
+        .unwrap();
+    // The abort of `inner` has been expanded at the call in `middle`, which has been expanded at
+    // the call in `outer`.
+    let trace = target
+        .get_bytecode_loc(abort.get_attr_id())
+        .expansion_trace()
+        .iter()
+        .map(|loc| env.get_source(loc).unwrap().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(trace, vec!["inner(x)", "middle(x)"]);
+}
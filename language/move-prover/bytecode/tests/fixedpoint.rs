@@ -0,0 +1,90 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for the processing of recursive functions until their annotations are stable.
+
+mod common;
+
+use codespan_reporting::{diagnostic::Severity, term::termcolor::Buffer};
+use move_model::model::FunctionEnv;
+use move_stackless_bytecode::{
+    function_target::FunctionData,
+    function_target_pipeline::{
+        FunctionTargetPipeline, FunctionTargetProcessor, FunctionTargetsHolder,
+    },
+};
+use std::{cell::Cell, rc::Rc};
+
+const MODULE: &str = "
+module 0x42::M {
+    fun f(n: u64): u64 { if (n == 0) 0 else g(n - 1) }
+    fun g(n: u64): u64 { f(n) }
+}
+";
+
+/// An annotation which changes with every update.
+#[derive(Clone, PartialEq)]
+struct Counter(usize);
+
+/// A processor whose annotations never reach a fixed point.
+struct DivergingProcessor {}
+
+impl FunctionTargetProcessor for DivergingProcessor {
+    fn process(
+        &self,
+        _targets: &mut FunctionTargetsHolder,
+        _fun_env: &FunctionEnv<'_>,
+        mut data: FunctionData,
+    ) -> FunctionData {
+        let count = data.annotations.get::<Counter>().map(|c| c.0).unwrap_or(0);
+        data.annotations
+            .set_with_fixedpoint_check(Counter(count + 1));
+        data
+    }
+
+    fn name(&self) -> String {
+        "diverging".to_string()
+    }
+}
+
+/// A processor which counts how often it is called.
+struct CountingProcessor {
+    calls: Rc<Cell<usize>>,
+}
+
+impl FunctionTargetProcessor for CountingProcessor {
+    fn process(
+        &self,
+        _targets: &mut FunctionTargetsHolder,
+        _fun_env: &FunctionEnv<'_>,
+        data: FunctionData,
+    ) -> FunctionData {
+        self.calls.set(self.calls.get() + 1);
+        data
+    }
+
+    fn name(&self) -> String {
+        "counting".to_string()
+    }
+}
+
+#[test]
+fn non_convergence_is_reported_once() {
+    let env = common::build("fixedpoint", MODULE);
+    let mut targets = common::targets(&env);
+    let calls = Rc::new(Cell::new(0));
+    let mut pipeline = FunctionTargetPipeline::default();
+    pipeline.add_processor(Box::new(DivergingProcessor {}));
+    pipeline.add_processor(Box::new(CountingProcessor {
+        calls: calls.clone(),
+    }));
+    pipeline.run(&env, &mut targets);
+
+    assert_eq!(env.error_count(), 1);
+    let mut out = Buffer::no_color();
+    env.report_diag(&mut out, Severity::Error);
+    assert!(String::from_utf8_lossy(&out.into_inner())
+        .contains("`diverging` does not reach a fixed point for the recursive functions"));
+    // The later processor is not affected by the annotations which did not stabilize.
+    assert_eq!(calls.get(), 2);
+}
@@ -0,0 +1,24 @@
+module 0x1::AssertAfterCalls {
+    struct R has key { v: u64 }
+
+    public fun publish(s: &signer) {
+        move_to(s, R { v: 1 });
+    }
+
+    public fun bump(a: address) acquires R {
+        let r = borrow_global_mut<R>(a);
+        r.v = r.v + 1;
+    }
+
+    public fun sequence(s: &signer, a: address) acquires R {
+        publish(s);
+        bump(a);
+    }
+    spec sequence {
+        pragma assert_invariants_after_calls;
+    }
+
+    spec module {
+        invariant forall a: address where exists<R>(a): global<R>(a).v > 0;
+    }
+}
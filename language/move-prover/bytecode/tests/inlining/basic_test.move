@@ -0,0 +1,18 @@
+module 0x42::TestInlining {
+    struct S has drop { x: u64 }
+
+    fun get_x(s: &S): u64 {
+        s.x
+    }
+    spec get_x {
+        pragma inline;
+    }
+
+    fun inc(x: u64): u64 {
+        x + 1
+    }
+
+    fun test(s: &S): u64 {
+        inc(get_x(s))
+    }
+}
@@ -0,0 +1,22 @@
+module 0x42::TestMutualRecursion {
+    // Each function sees the other's code as it was before inlining, so both are
+    // inlined exactly once, whichever is processed first. Callers outside of the
+    // cycle see the code after inlining.
+    fun even(n: u64): bool {
+        if (n == 0) true else odd(n - 1)
+    }
+    spec even {
+        pragma inline;
+    }
+
+    fun odd(n: u64): bool {
+        if (n == 0) false else even(n - 1)
+    }
+    spec odd {
+        pragma inline;
+    }
+
+    fun test(n: u64): bool {
+        even(n)
+    }
+}
@@ -0,0 +1,22 @@
+module 0x42::TestLiveness {
+    fun test_branch(a: u64, b: u64, c: bool): u64 {
+        let x = a + 1;
+        let y;
+        if (c) {
+            y = x + b;
+        } else {
+            y = b;
+        };
+        y
+    }
+
+    fun test_loop(n: u64): u64 {
+        let i = 0;
+        let sum = 0;
+        while (i < n) {
+            sum = sum + i;
+            i = i + 1;
+        };
+        sum
+    }
+}
@@ -0,0 +1,40 @@
+module 0x42::TestLoopDetection {
+    fun test_no_loop(a: u64, c: bool): u64 {
+        if (c) a + 1 else a
+    }
+
+    fun test_loop(n: u64): u64 {
+        let i = 0;
+        let sum = 0;
+        while (i < n) {
+            sum = sum + i;
+            i = i + 1;
+        };
+        sum
+    }
+
+    fun test_continue(n: u64): u64 {
+        let i = 0;
+        let sum = 0;
+        while (i < n) {
+            i = i + 1;
+            if (i % 2 == 0) continue;
+            sum = sum + i;
+        };
+        sum
+    }
+
+    fun test_nested(n: u64): u64 {
+        let i = 0;
+        let sum = 0;
+        while (i < n) {
+            let j = 0;
+            while (j < i) {
+                sum = sum + j;
+                j = j + 1;
+            };
+            i = i + 1;
+        };
+        sum
+    }
+}
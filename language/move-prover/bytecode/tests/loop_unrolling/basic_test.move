@@ -0,0 +1,42 @@
+module 0x42::TestLoopUnrolling {
+    fun test_loop(n: u64): u64 {
+        let i = 0;
+        let sum = 0;
+        while (i < n) {
+            sum = sum + i;
+            i = i + 1;
+        };
+        sum
+    }
+    spec test_loop {
+        pragma unroll = 3;
+    }
+
+    fun test_nested(n: u64): u64 {
+        let i = 0;
+        let sum = 0;
+        while ({
+            spec { invariant i <= n; };
+            i < n
+        }) {
+            let j = 0;
+            while (j < i) {
+                sum = sum + j;
+                j = j + 1;
+            };
+            i = i + 1;
+        };
+        sum
+    }
+    spec test_nested {
+        pragma unroll = 2;
+    }
+
+    fun test_not_unrolled(n: u64): u64 {
+        let i = 0;
+        while (i < n) {
+            i = i + 1;
+        };
+        i
+    }
+}
@@ -0,0 +1,30 @@
+// override: 0x123::Hash::digest = Model::digest
+// override: Hash::digest_of = Model::digest
+address 0x123 {
+module Hash {
+    native public fun digest(data: vector<u8>): vector<u8>;
+
+    public fun digest_of<T: copy>(x: T, data: vector<u8>): (T, vector<u8>) {
+        (x, digest(data))
+    }
+}
+
+module Model {
+    public fun digest(data: vector<u8>): vector<u8> {
+        data
+    }
+}
+
+module Test {
+    use 0x123::Hash;
+
+    public fun f(data: vector<u8>): vector<u8> {
+        Hash::digest(data)
+    }
+
+    public fun g(data: vector<u8>): vector<u8> {
+        let (_, d) = Hash::digest_of(1, data);
+        d
+    }
+}
+}
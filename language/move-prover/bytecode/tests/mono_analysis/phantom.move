@@ -0,0 +1,33 @@
+address 0x123 {
+module Phantom {
+
+    struct Tag {}
+
+    struct Other<T> has drop {
+        x: T,
+    }
+
+    struct Handle<phantom T> has drop {
+        counter: u64,
+    }
+
+    // `Tag` and `Other<u8>` only appear as phantom type arguments and are therefore not
+    // instantiated.
+    public fun new_tag_handle(): Handle<Tag> {
+        Handle{counter: 0}
+    }
+
+    public fun new_other_handle(): Handle<Other<u8>> {
+        Handle{counter: 1}
+    }
+
+    public fun new_handle<T>(): Handle<T> {
+        Handle{counter: 2}
+    }
+
+    public fun use_handle(): Handle<Other<u64>> {
+        let _x = Other{x: 1u64};
+        new_handle<Other<u64>>()
+    }
+}
+}
@@ -0,0 +1,100 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for the selection of the mutated operation by the random seed.
+
+mod common;
+
+use move_stackless_bytecode::{
+    function_target_pipeline::{FunctionTargetPipeline, FunctionVariant, VerificationFlavor},
+    mutation_tester::MutationTester,
+    options::ProverOptions,
+};
+use std::collections::BTreeSet;
+
+const MODULE: &str = "
+module 0x42::M {
+    fun f(x: u64, y: u64): u64 { (x + y) * (x - y) }
+    fun g(x: u64): u64 { x / 2 + 1 }
+}
+";
+
+/// Runs mutation testing with the given options, returning the function and offset of the
+/// mutated instruction, if any.
+fn mutated(options: ProverOptions) -> Option<(String, usize)> {
+    let env = common::build("mutation_seed", MODULE);
+    ProverOptions::set(&env, options);
+    let mut targets = common::targets(&env);
+    let verification = FunctionVariant::Verification(VerificationFlavor::Regular);
+    for fun_id in targets.get_funs().collect::<Vec<_>>() {
+        targets.copy_variant(&fun_id, &FunctionVariant::Baseline, verification.clone());
+    }
+    let mut pipeline = FunctionTargetPipeline::default();
+    pipeline.add_processor(MutationTester::new());
+    pipeline.run(&env, &mut targets);
+
+    let mut result = None;
+    for module_env in env.get_modules() {
+        for fun_env in module_env.get_functions() {
+            let baseline = targets.get_target(&fun_env, &FunctionVariant::Baseline);
+            let verified = targets.get_target(&fun_env, &verification);
+            for (offset, (bc, mutated_bc)) in baseline
+                .get_bytecode()
+                .iter()
+                .zip(verified.get_bytecode())
+                .enumerate()
+            {
+                if bc != mutated_bc {
+                    assert!(result.is_none(), "more than one instruction mutated");
+                    result = Some((fun_env.get_full_name_str(), offset));
+                }
+            }
+        }
+    }
+    result
+}
+
+fn with_seed(seed: usize) -> ProverOptions {
+    ProverOptions {
+        mutation: true,
+        random_seed: seed,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn same_seed_same_mutation() {
+    for seed in 0..5 {
+        let first = mutated(with_seed(seed));
+        assert!(first.is_some());
+        assert_eq!(mutated(with_seed(seed)), first);
+    }
+}
+
+#[test]
+fn seeds_select_each_operation() {
+    // The module has five arithmetic operations, each selected by one of five seeds.
+    let selected = (0..5)
+        .map(|seed| mutated(with_seed(seed)).unwrap())
+        .collect::<BTreeSet<_>>();
+    assert_eq!(selected.len(), 5);
+    assert_eq!(mutated(with_seed(5)), mutated(with_seed(0)));
+}
+
+#[test]
+fn selected_operation_overrides_seed() {
+    let options = |seed| ProverOptions {
+        mutation: true,
+        mutation_add_sub: 1,
+        random_seed: seed,
+        ..Default::default()
+    };
+    assert_eq!(mutated(options(0)), mutated(options(3)));
+    assert_eq!(
+        mutated(ProverOptions {
+            random_seed: 3,
+            ..Default::default()
+        }),
+        None
+    );
+}
@@ -0,0 +1,84 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for the budgets of instructions and locals enforced after bytecode transformation.
+
+mod common;
+
+use move_model::model::GlobalEnv;
+use move_stackless_bytecode::{
+    function_target_pipeline::{
+        FunctionSize, FunctionTargetPipeline, FunctionVariant, PipelineMetrics,
+    },
+    inlining::InliningProcessor,
+    options::ProverOptions,
+};
+
+const MODULE: &str = "
+module 0x42::M {
+    fun inc(x: u64): u64 { x + 1 }
+    spec inc { pragma inline; }
+
+    fun add_two(x: u64): u64 { inc(inc(x)) }
+}
+";
+
+/// Runs inlining with the given options, returning the environment.
+fn run(options: ProverOptions) -> GlobalEnv {
+    let env = common::build("pipeline_budget", MODULE);
+    ProverOptions::set(&env, options);
+    let mut targets = common::targets(&env);
+    let mut pipeline = FunctionTargetPipeline::default();
+    pipeline.add_processor(InliningProcessor::new());
+    pipeline.run(&env, &mut targets);
+    env
+}
+
+/// Returns the size of `add_two` before and after inlining, as recorded in the metrics.
+fn add_two_size(env: &GlobalEnv) -> (FunctionSize, FunctionSize) {
+    let fun = env
+        .find_function_by_name_str("M::add_two")
+        .unwrap()
+        .get_qualified_id();
+    PipelineMetrics::get(env).processors[0].changed[&(fun, FunctionVariant::Baseline)]
+}
+
+#[test]
+fn within_budget() {
+    let (_, after) = add_two_size(&run(ProverOptions::default()));
+    let env = run(ProverOptions {
+        max_instructions: Some(after.instructions),
+        max_locals: Some(after.locals),
+        ..Default::default()
+    });
+    assert!(!env.has_errors());
+}
+
+#[test]
+fn exceeding_budgets() {
+    let (before, after) = add_two_size(&run(ProverOptions::default()));
+    let env = run(ProverOptions {
+        max_instructions: Some(after.instructions - 1),
+        max_locals: Some(after.locals - 1),
+        ..Default::default()
+    });
+    // The error names the processor which has grown the function.
+    assert!(env.has_diag(&format!(
+        "function `M::add_two` [baseline] has {} instructions after bytecode transformation, \
+         exceeding the limit of {} (most instructions have been added by `inlining`: {} -> {})",
+        after.instructions,
+        after.instructions - 1,
+        before.instructions,
+        after.instructions
+    )));
+    assert!(env.has_diag(&format!(
+        "function `M::add_two` [baseline] has {} locals after bytecode transformation, \
+         exceeding the limit of {} (most locals have been added by `inlining`: {} -> {})",
+        after.locals,
+        after.locals - 1,
+        before.locals,
+        after.locals
+    )));
+    // The inlined function is smaller than the limits.
+    assert!(!env.has_diag("`M::inc`"));
+}
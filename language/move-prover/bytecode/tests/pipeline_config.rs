@@ -0,0 +1,98 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for pipelines created from TOML configurations.
+
+mod common;
+
+use move_stackless_bytecode::{
+    function_target_pipeline::{FunctionTargetPipeline, PipelineConfig},
+    options::ProverOptions,
+    pipeline_factory,
+};
+
+const MODULE: &str = "
+module 0x42::M {
+    fun f(x: u64): u64 { x }
+    spec f { pragma timeout = 10; }
+}
+";
+
+const CONFIG: &str = r#"
+processors = ["eliminate_imm_refs", "mut_ref_instrumentation", "reaching_def_analysis"]
+
+[options]
+check_inconsistency = true
+
+[pragmas."M::f"]
+timeout = 100
+verify = false
+
+[pragmas."0x42::M"]
+aborts_if_is_strict = true
+"#;
+
+#[test]
+fn pipeline_from_config() {
+    let config = PipelineConfig::from_toml(CONFIG).unwrap();
+    assert!(config.options.as_ref().unwrap().check_inconsistency);
+    let pipeline = FunctionTargetPipeline::from_config(&config).unwrap();
+    assert_eq!(
+        pipeline.processor_names(),
+        vec![
+            "eliminate_imm_refs",
+            "mut_ref_instrumentation",
+            "reaching_def_analysis"
+        ]
+    );
+}
+
+#[test]
+fn default_pipeline_for_options() {
+    let config = PipelineConfig::from_toml("[options]\nunroll_loops = true").unwrap();
+    let pipeline = FunctionTargetPipeline::from_config(&config).unwrap();
+    assert_eq!(
+        pipeline.processor_names(),
+        pipeline_factory::default_pipeline_with_options(&ProverOptions {
+            unroll_loops: true,
+            ..Default::default()
+        })
+        .processor_names()
+    );
+    assert!(pipeline
+        .processor_names()
+        .contains(&"loop_unrolling".to_string()));
+}
+
+#[test]
+fn invalid_configs() {
+    let config = PipelineConfig::from_toml(r#"processors = ["no_such_processor"]"#).unwrap();
+    let err = FunctionTargetPipeline::from_config(&config).err().unwrap();
+    assert_eq!(err.to_string(), "unknown processor `no_such_processor`");
+    assert!(PipelineConfig::from_toml("processor = []").is_err());
+}
+
+#[test]
+fn pragma_overrides() {
+    let mut env = common::build("pipeline_config", MODULE);
+    PipelineConfig::from_toml(CONFIG)
+        .unwrap()
+        .apply_pragmas(&mut env)
+        .unwrap();
+    let fun_env = env.find_function_by_name_str("M::f").unwrap();
+    let properties = &fun_env.get_spec().properties;
+    assert_eq!(env.get_num_property(properties, "timeout"), Some(100));
+    assert!(!fun_env.is_pragma_true("verify", || true));
+    let module_env = env.find_module_by_name_str("0x42::M").unwrap();
+    assert_eq!(
+        env.is_property_true(&module_env.get_spec().properties, "aborts_if_is_strict"),
+        Some(true)
+    );
+
+    let unknown = PipelineConfig::from_toml("[pragmas.\"M::g\"]\ntimeout = 1").unwrap();
+    let err = unknown.apply_pragmas(&mut env).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "cannot find module or function `M::g` to override pragmas of"
+    );
+}
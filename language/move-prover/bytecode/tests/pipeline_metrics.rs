@@ -0,0 +1,71 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for the metrics recorded per processor in a pipeline run.
+
+mod common;
+
+use move_stackless_bytecode::{
+    function_target_pipeline::{FunctionTargetPipeline, FunctionVariant, PipelineMetrics},
+    inlining::InliningProcessor,
+    usage_analysis::UsageProcessor,
+};
+
+const MODULE: &str = "
+module 0x42::M {
+    fun inc(x: u64): u64 { x + 1 }
+    spec inc { pragma inline; }
+
+    fun add_two(x: u64): u64 { inc(inc(x)) }
+}
+";
+
+#[test]
+fn metrics_per_processor() {
+    let env = common::build("pipeline_metrics", MODULE);
+    let mut targets = common::targets(&env);
+    let mut pipeline = FunctionTargetPipeline::default();
+    pipeline.add_processor(InliningProcessor::new());
+    pipeline.add_processor(UsageProcessor::new());
+    pipeline.run(&env, &mut targets);
+    assert!(!env.has_errors());
+
+    let metrics = PipelineMetrics::get(&env);
+    let names = metrics
+        .processors
+        .iter()
+        .map(|m| m.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["inlining", "usage_analysis"]);
+
+    // Inlining grows the caller, and only the caller.
+    let inlining = &metrics.processors[0];
+    let add_two = env
+        .find_function_by_name_str("M::add_two")
+        .unwrap()
+        .get_qualified_id();
+    assert_eq!(inlining.changed.len(), 1);
+    let (before, after) = inlining.changed[&(add_two, FunctionVariant::Baseline)];
+    assert_eq!(
+        inlining.instructions_added,
+        after.instructions - before.instructions
+    );
+    assert!(inlining.instructions_added > 0);
+    assert!(inlining.locals_added > 0);
+    assert_eq!(inlining.instructions_removed, 0);
+
+    // The analysis only annotates the code.
+    let usage = &metrics.processors[1];
+    assert!(usage.changed.is_empty());
+    assert_eq!(usage.instructions_added, 0);
+
+    let report = metrics.report(&env);
+    assert!(
+        report.contains(&format!(
+            "    largest growth: M::add_two[baseline] {} -> {} instructions",
+            before.instructions, after.instructions
+        )),
+        "{}",
+        report
+    );
+}
@@ -0,0 +1,29 @@
+// dep: ../../move-stdlib/sources/Vector.move
+// native: 0x2::NativeSemantics::modifies_first = modifies(0)
+// native: 0x2::NativeSemantics::pure_native = pure
+// native: 0x1::Vector::push_back = modifies(0), writes_memory
+
+module 0x2::NativeSemantics {
+    use Std::Vector;
+
+    struct R has key { v: u64 }
+    struct Q has key { v: u64 }
+    struct S has key { v: vector<u64> }
+
+    native fun modifies_first(x: &mut R, y: &mut Q);
+    native fun pure_native(x: &R): u64;
+
+    // The first parameter is read and written, the second only read.
+    fun call_modifies_first(a: address, b: address) acquires R, Q {
+        modifies_first(borrow_global_mut<R>(a), borrow_global_mut<Q>(b))
+    }
+
+    fun call_pure(a: address): u64 acquires R {
+        pure_native(borrow_global<R>(a))
+    }
+
+    // Natives which write memory are analyzed with their handwritten models.
+    fun call_push_back(a: address) acquires S {
+        Vector::push_back(&mut borrow_global_mut<S>(a).v, 1)
+    }
+}
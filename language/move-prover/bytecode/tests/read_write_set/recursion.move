@@ -0,0 +1,16 @@
+address 0x1 {
+module Recursion {
+
+    struct A has key { v: u64 }
+    struct B has key { v: u64 }
+
+    // `even` and `odd` are mutually recursive, so each of them reads both resources.
+    public fun even(a: address, n: u64): u64 acquires A, B {
+        if (n == 0) borrow_global<A>(a).v else odd(a, n - 1)
+    }
+
+    public fun odd(a: address, n: u64): u64 acquires A, B {
+        if (n == 0) borrow_global<B>(a).v else even(a, n - 1)
+    }
+}
+}
@@ -0,0 +1,65 @@
+module 0x42::Recursion {
+
+    // Direct recursion without measure or bound: error.
+    fun fact(n: u64): u64 {
+        if (n == 0) 1 else n * fact(n - 1)
+    }
+
+    // Direct recursion with a measure.
+    fun sum(n: u64): u64 {
+        if (n == 0) 0 else n + sum(n - 1)
+    }
+    spec sum {
+        pragma measure = n;
+    }
+
+    // Measure which is not a parameter: error.
+    fun count(n: u64): u64 {
+        if (n == 0) 0 else 1 + count(n - 1)
+    }
+    spec count {
+        pragma measure = m;
+    }
+
+    // Mutual recursion, bounded in `is_even` only: error for `is_odd`.
+    fun is_even(n: u64): bool {
+        if (n == 0) true else is_odd(n - 1)
+    }
+    spec is_even {
+        pragma max_call_depth = 10;
+    }
+
+    fun is_odd(n: u64): bool {
+        if (n == 0) false else is_even(n - 1)
+    }
+
+    // Call chains of non-recursive functions.
+    fun leaf(): u64 {
+        1
+    }
+
+    fun middle(): u64 {
+        leaf() + 1
+    }
+
+    fun top(): u64 {
+        middle() + 1
+    }
+    spec top {
+        pragma max_call_depth = 2;
+    }
+
+    fun bounded(): u64 {
+        middle() + 1
+    }
+    spec bounded {
+        pragma max_call_depth = 3;
+    }
+
+    fun unbounded(): u64 {
+        fact(3)
+    }
+    spec unbounded {
+        pragma max_call_depth = 5;
+    }
+}
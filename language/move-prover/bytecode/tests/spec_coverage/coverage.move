@@ -0,0 +1,43 @@
+module 0x42::Coverage {
+
+    struct R has key { v: u64 }
+
+    // The result is constrained, the counter update is not.
+    fun incr(x: u64, counter: &mut u64): u64 {
+        let y = x + 1;
+        *counter = x;
+        y
+    }
+    spec incr {
+        aborts_if x + 1 > MAX_U64;
+        ensures result == x + 1;
+    }
+
+    // Only the branch assigning the result is constrained.
+    fun max(a: u64, b: u64): u64 {
+        let m = a;
+        if (b > a) {
+            m = b;
+        };
+        m
+    }
+    spec max {
+        ensures result >= a && result >= b;
+    }
+
+    // The update of the global memory is constrained, the local computation is not.
+    fun set(addr: address, v: u64) acquires R {
+        let unused = v * 2;
+        let r = borrow_global_mut<R>(addr);
+        r.v = v;
+        unused;
+    }
+    spec set {
+        ensures global<R>(addr).v == v;
+    }
+
+    // No conditions: not analyzed.
+    fun id(x: u64): u64 {
+        x
+    }
+}
@@ -0,0 +1,35 @@
+// native: 0x42::TestNativeSemantics::pure_native = pure
+// native: 0x42::TestNativeSemantics::aborting_native = aborts
+// native: 0x42::TestNativeSemantics::modifies_first = modifies(0)
+// Contains tests for calls to natives with registered semantics.
+module 0x42::TestNativeSemantics {
+
+    native fun pure_native(x: u64): u64;
+    native fun aborting_native(x: u64): u64;
+    native fun modifies_first(x: &mut u64, y: &mut u64);
+    native fun unregistered(x: &mut u64, y: &mut u64);
+    spec modifies_first { pragma opaque; }
+    spec unregistered { pragma opaque; }
+
+    // The call has no abort action.
+    fun call_pure(x: u64): u64 {
+        pure_native(x)
+    }
+
+    // The call has an abort action.
+    fun call_aborting(x: u64): u64 {
+        aborting_native(x)
+    }
+
+    // Only the first parameter is havocked after the opaque call.
+    fun call_modifies_first(x: u64, y: u64): u64 {
+        modifies_first(&mut x, &mut y);
+        x + y
+    }
+
+    // Without registered semantics, the call may abort and both parameters are havocked.
+    fun call_unregistered(x: u64, y: u64): u64 {
+        unregistered(&mut x, &mut y);
+        x + y
+    }
+}
@@ -0,0 +1,60 @@
+module 0x42::Balances {
+    struct Balance has key { value: u64 }
+
+    spec module {
+        global total: num;
+        invariant update forall a: address where exists<Balance>(a):
+            global<Balance>(a).value <= total;
+    }
+
+    // Updates the variable, and is not reported.
+    fun deposit(addr: address, amount: u64) acquires Balance {
+        let balance = borrow_global_mut<Balance>(addr);
+        balance.value = balance.value + amount;
+    }
+    spec deposit {
+        update total = total + amount;
+    }
+
+    // Does not update the variable.
+    public fun deposit_unchecked(addr: address, amount: u64) acquires Balance {
+        let balance = borrow_global_mut<Balance>(addr);
+        balance.value = balance.value + amount;
+    }
+
+    // Updates the variable through its callee.
+    fun deposit_twice(addr: address, amount: u64) acquires Balance {
+        deposit(addr, amount);
+        let balance = borrow_global_mut<Balance>(addr);
+        balance.value = balance.value + amount;
+    }
+
+    // Only reads the memory, and is not reported.
+    fun value(addr: address): u64 acquires Balance {
+        borrow_global<Balance>(addr).value
+    }
+}
+
+module 0x42::Registry {
+    use 0x42::Balances;
+
+    struct Entry<phantom T> has key { count: u64 }
+
+    spec module {
+        global entries<T>: num;
+        invariant<T> update forall a: address where exists<Entry<T>>(a):
+            global<Entry<T>>(a).count <= entries<T>;
+    }
+
+    // Does not update the generic variable.
+    fun register<T>(account: &signer) {
+        move_to(account, Entry<T> { count: 0 });
+    }
+
+    // Does not update the generic variable. The memory of `Balances` is only modified by the
+    // callee, which is reported itself.
+    fun register_and_deposit<T>(account: &signer, addr: address) {
+        move_to(account, Entry<T> { count: 0 });
+        Balances::deposit_unchecked(addr, 1);
+    }
+}
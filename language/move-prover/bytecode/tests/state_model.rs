@@ -0,0 +1,163 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for the state model documents of modules.
+
+mod common;
+
+use move_model::{ast::ConditionKind, model::GlobalEnv};
+use move_stackless_bytecode::{
+    function_target_pipeline::{FunctionTargetPipeline, FunctionTargetsHolder},
+    state_model::{state_model, state_model_doc, ResourceEffect},
+    usage_analysis::UsageProcessor,
+};
+
+const MODULES: &str = "
+module 0x42::M {
+    struct R has key { v: u64 }
+    spec R { invariant v > 0; }
+    spec module {
+        invariant forall a: address where exists<R>(a): global<R>(a).v < 100;
+    }
+
+    struct Unused has key { v: u64 }
+
+    public fun publish(s: &signer) { move_to(s, R { v: 1 }) }
+
+    public fun bump(a: address) acquires R {
+        let r = borrow_global_mut<R>(a);
+        if (r.v < 99) r.v = r.v + 1;
+    }
+
+    public fun remove(a: address) acquires R {
+        let R { v: _ } = move_from<R>(a);
+    }
+
+    public fun read(a: address): u64 acquires R { borrow_global<R>(a).v }
+
+    public fun reset(s: &signer, a: address) acquires R {
+        remove(a);
+        publish(s);
+    }
+}
+
+module 0x42::N {
+    use 0x42::M;
+    public fun bump_twice(a: address) { M::bump(a); M::bump(a) }
+}
+
+module 0x42::Empty {
+    public fun f() {}
+}
+";
+
+/// Runs the usage analysis on all functions, returning the environment and the targets.
+fn analyze() -> (GlobalEnv, FunctionTargetsHolder) {
+    let env = common::build("state_model", MODULES);
+    let mut targets = common::targets(&env);
+    let mut pipeline = FunctionTargetPipeline::default();
+    pipeline.add_processor(UsageProcessor::new());
+    pipeline.run(&env, &mut targets);
+    (env, targets)
+}
+
+#[test]
+fn resource_models() {
+    let (env, targets) = analyze();
+    let module_env = env.find_module_by_name_str("0x42::M").unwrap();
+    let models = state_model(&module_env, &targets);
+    assert_eq!(models.len(), 2);
+
+    let r = &models[0];
+    assert_eq!(env.get_struct(r.resource).get_full_name_str(), "M::R");
+    let writers = r
+        .writers
+        .iter()
+        .map(|(fun_id, effects)| {
+            (
+                env.get_function(*fun_id).get_full_name_str(),
+                effects.iter().cloned().collect::<Vec<_>>(),
+            )
+        })
+        .collect::<Vec<_>>();
+    // Reading the resource does not write it. Effects of callees are included, also across
+    // modules.
+    assert_eq!(
+        writers,
+        vec![
+            ("M::bump".to_string(), vec![ResourceEffect::Modify]),
+            ("M::publish".to_string(), vec![ResourceEffect::Create]),
+            ("M::remove".to_string(), vec![ResourceEffect::Destroy]),
+            (
+                "M::reset".to_string(),
+                vec![ResourceEffect::Create, ResourceEffect::Destroy]
+            ),
+            ("N::bump_twice".to_string(), vec![ResourceEffect::Modify]),
+        ]
+    );
+    let kinds = r
+        .invariants
+        .iter()
+        .map(|(kind, _)| kind.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        vec![
+            ConditionKind::StructInvariant,
+            ConditionKind::GlobalInvariant(vec![])
+        ]
+    );
+
+    let unused = &models[1];
+    assert!(unused.writers.is_empty());
+    assert!(unused.invariants.is_empty());
+}
+
+#[test]
+fn state_model_docs() {
+    let (env, targets) = analyze();
+    let doc = state_model_doc(&env.find_module_by_name_str("0x42::M").unwrap(), &targets);
+    assert!(doc.starts_with("# State Model of Module `0x42::M`\n\n## Resource `M::R`\n"));
+    assert!(
+        doc.contains("\nCreated by:\n\n- `M::publish`\n- `M::reset`\n"),
+        "{}",
+        doc
+    );
+    assert!(
+        doc.contains("\nModified by:\n\n- `M::bump`\n- `N::bump_twice`\n"),
+        "{}",
+        doc
+    );
+    assert!(
+        doc.contains("\nDestroyed by:\n\n- `M::remove`\n- `M::reset`\n"),
+        "{}",
+        doc
+    );
+    assert!(
+        doc.contains("\nConstrained by:\n\n- `invariant v > 0;` ("),
+        "{}",
+        doc
+    );
+    assert!(
+        doc.contains("- `invariant forall a: address where exists<R>(a): global<R>(a).v < 100;` ("),
+        "{}",
+        doc
+    );
+    assert!(
+        doc.ends_with(
+            "\n## Resource `M::Unused`\n\nNot written by any function.\n\n\
+             Not constrained by any invariant.\n"
+        ),
+        "{}",
+        doc
+    );
+
+    let doc = state_model_doc(
+        &env.find_module_by_name_str("0x42::Empty").unwrap(),
+        &targets,
+    );
+    assert_eq!(
+        doc,
+        "# State Model of Module `0x42::Empty`\n\nThe module declares no resources.\n"
+    );
+}
@@ -0,0 +1,25 @@
+module 0x42::TestTermination {
+
+    fun count_down(n: u64): u64 {
+        if (n == 0) 0 else count_down(n - 1)
+    }
+    spec count_down {
+        pragma opaque;
+        pragma measure = n;
+        ensures result == 0;
+    }
+
+    fun is_even(n: u64): bool {
+        if (n == 0) true else is_odd(n - 1)
+    }
+    spec is_even {
+        pragma measure = n;
+    }
+
+    fun is_odd(n: u64): bool {
+        if (n == 0) false else is_even(n - 1)
+    }
+    spec is_odd {
+        pragma measure = n;
+    }
+}
@@ -8,9 +8,14 @@ use move_compiler::shared::PackagePaths;
 use move_model::{model::GlobalEnv, options::ModelBuilderOptions, run_model_builder_with_options};
 use move_prover_test_utils::{baseline_test::verify_or_update_baseline, extract_test_directives};
 use move_stackless_bytecode::{
+    abort_inference::AbortInferenceProcessor,
+    abort_path_analysis::AbortPathProcessor,
     borrow_analysis::BorrowAnalysisProcessor,
     clean_and_optimize::CleanAndOptimizeProcessor,
+    condition_splitting::ConditionSplittingProcessor,
+    constant_propagation::ConstantPropagationProcessor,
     data_invariant_instrumentation::DataInvariantInstrumentationProcessor,
+    dead_code_elimination::DeadCodeEliminationProcessor,
     eliminate_imm_refs::EliminateImmRefsProcessor,
     escape_analysis::EscapeAnalysisProcessor,
     function_target_pipeline::{
@@ -18,20 +23,30 @@ use move_stackless_bytecode::{
     },
     global_invariant_analysis::GlobalInvariantAnalysisProcessor,
     global_invariant_instrumentation::GlobalInvariantInstrumentationProcessor,
+    inlining::InliningProcessor,
+    liveness_analysis::LivenessAnalysisProcessor,
     livevar_analysis::LiveVarAnalysisProcessor,
+    loop_analysis::LoopDetectionProcessor,
+    loop_unrolling::LoopUnrollingProcessor,
     memory_instrumentation::MemoryInstrumentationProcessor,
     mono_analysis::MonoAnalysisProcessor,
     mut_ref_instrumentation::MutRefInstrumenter,
+    native_semantics::{NativeSemantics, NativeSemanticsRegistry},
     options::ProverOptions,
     print_targets_for_test,
     reaching_def_analysis::ReachingDefProcessor,
     read_write_set_analysis::ReadWriteSetProcessor,
+    recursion_analysis::RecursionAnalysisProcessor,
+    spec_coverage_analysis::SpecCoverageProcessor,
     spec_instrumentation::SpecInstrumentationProcessor,
+    spec_var_frame_analysis::SpecVarFrameProcessor,
+    termination_instrumentation::TerminationInstrumenter,
     usage_analysis::UsageProcessor,
+    vacuity_check::VacuityCheckInstrumenter,
     verification_analysis::VerificationAnalysisProcessor,
     well_formed_instrumentation::WellFormedInstrumentationProcessor,
 };
-use std::path::Path;
+use std::{cell::RefCell, path::Path};
 
 fn get_tested_transformation_pipeline(
     dir_name: &str,
@@ -56,6 +71,45 @@ fn get_tested_transformation_pipeline(
             pipeline.add_processor(ReachingDefProcessor::new());
             Ok(Some(pipeline))
         }
+        "dead_code_elimination" => {
+            let mut pipeline = FunctionTargetPipeline::default();
+            pipeline.add_processor(EliminateImmRefsProcessor::new());
+            pipeline.add_processor(MutRefInstrumenter::new());
+            pipeline.add_processor(ReachingDefProcessor::new());
+            pipeline.add_processor(DeadCodeEliminationProcessor::new());
+            Ok(Some(pipeline))
+        }
+        "constant_propagation" => {
+            let mut pipeline = FunctionTargetPipeline::default();
+            pipeline.add_processor(EliminateImmRefsProcessor::new());
+            pipeline.add_processor(MutRefInstrumenter::new());
+            pipeline.add_processor(ReachingDefProcessor::new());
+            pipeline.add_processor(ConstantPropagationProcessor::new());
+            Ok(Some(pipeline))
+        }
+        "liveness" => {
+            let mut pipeline = FunctionTargetPipeline::default();
+            pipeline.add_processor(EliminateImmRefsProcessor::new());
+            pipeline.add_processor(MutRefInstrumenter::new());
+            pipeline.add_processor(ReachingDefProcessor::new());
+            pipeline.add_processor(LivenessAnalysisProcessor::new());
+            Ok(Some(pipeline))
+        }
+        "loop_detection" => {
+            let mut pipeline = FunctionTargetPipeline::default();
+            pipeline.add_processor(LoopDetectionProcessor::new());
+            Ok(Some(pipeline))
+        }
+        "inlining" => {
+            let mut pipeline = FunctionTargetPipeline::default();
+            pipeline.add_processor(InliningProcessor::new());
+            Ok(Some(pipeline))
+        }
+        "loop_unrolling" => {
+            let mut pipeline = FunctionTargetPipeline::default();
+            pipeline.add_processor(LoopUnrollingProcessor::new());
+            Ok(Some(pipeline))
+        }
         "livevar" => {
             let mut pipeline = FunctionTargetPipeline::default();
             pipeline.add_processor(EliminateImmRefsProcessor::new());
@@ -183,6 +237,51 @@ fn get_tested_transformation_pipeline(
             pipeline.add_processor(GlobalInvariantInstrumentationProcessor::new());
             Ok(Some(pipeline))
         }
+        "condition_splitting" => {
+            let mut pipeline = FunctionTargetPipeline::default();
+            pipeline.add_processor(EliminateImmRefsProcessor::new());
+            pipeline.add_processor(MutRefInstrumenter::new());
+            pipeline.add_processor(ReachingDefProcessor::new());
+            pipeline.add_processor(LiveVarAnalysisProcessor::new());
+            pipeline.add_processor(BorrowAnalysisProcessor::new());
+            pipeline.add_processor(MemoryInstrumentationProcessor::new());
+            pipeline.add_processor(CleanAndOptimizeProcessor::new());
+            pipeline.add_processor(UsageProcessor::new());
+            pipeline.add_processor(VerificationAnalysisProcessor::new());
+            pipeline.add_processor(SpecInstrumentationProcessor::new());
+            pipeline.add_processor(ConditionSplittingProcessor::new());
+            Ok(Some(pipeline))
+        }
+        "vacuity_check" => {
+            let mut pipeline = FunctionTargetPipeline::default();
+            pipeline.add_processor(EliminateImmRefsProcessor::new());
+            pipeline.add_processor(MutRefInstrumenter::new());
+            pipeline.add_processor(ReachingDefProcessor::new());
+            pipeline.add_processor(LiveVarAnalysisProcessor::new());
+            pipeline.add_processor(BorrowAnalysisProcessor::new());
+            pipeline.add_processor(MemoryInstrumentationProcessor::new());
+            pipeline.add_processor(CleanAndOptimizeProcessor::new());
+            pipeline.add_processor(UsageProcessor::new());
+            pipeline.add_processor(VerificationAnalysisProcessor::new());
+            pipeline.add_processor(SpecInstrumentationProcessor::new());
+            pipeline.add_processor(VacuityCheckInstrumenter::new());
+            Ok(Some(pipeline))
+        }
+        "termination" => {
+            let mut pipeline = FunctionTargetPipeline::default();
+            pipeline.add_processor(EliminateImmRefsProcessor::new());
+            pipeline.add_processor(MutRefInstrumenter::new());
+            pipeline.add_processor(ReachingDefProcessor::new());
+            pipeline.add_processor(LiveVarAnalysisProcessor::new());
+            pipeline.add_processor(BorrowAnalysisProcessor::new());
+            pipeline.add_processor(MemoryInstrumentationProcessor::new());
+            pipeline.add_processor(CleanAndOptimizeProcessor::new());
+            pipeline.add_processor(UsageProcessor::new());
+            pipeline.add_processor(VerificationAnalysisProcessor::new());
+            pipeline.add_processor(SpecInstrumentationProcessor::new());
+            pipeline.add_processor(TerminationInstrumenter::new());
+            Ok(Some(pipeline))
+        }
         "read_write_set" => {
             let mut pipeline = FunctionTargetPipeline::default();
             pipeline.add_processor(Box::new(ReadWriteSetProcessor {}));
@@ -199,6 +298,35 @@ fn get_tested_transformation_pipeline(
             pipeline.add_processor(MonoAnalysisProcessor::new());
             Ok(Some(pipeline))
         }
+        "abort_path_analysis" => {
+            let mut pipeline = FunctionTargetPipeline::default();
+            pipeline.add_processor(AbortPathProcessor::new());
+            Ok(Some(pipeline))
+        }
+        "abort_inference" => {
+            let mut pipeline = FunctionTargetPipeline::default();
+            pipeline.add_processor(AbortPathProcessor::new());
+            pipeline.add_processor(AbortInferenceProcessor::new());
+            Ok(Some(pipeline))
+        }
+        "recursion_analysis" => {
+            let mut pipeline = FunctionTargetPipeline::default();
+            pipeline.add_processor(RecursionAnalysisProcessor::new());
+            Ok(Some(pipeline))
+        }
+        "spec_coverage" => {
+            let mut pipeline = FunctionTargetPipeline::default();
+            pipeline.add_processor(UsageProcessor::new());
+            pipeline.add_processor(SpecCoverageProcessor::new());
+            Ok(Some(pipeline))
+        }
+        "spec_var_frame_analysis" => {
+            let mut pipeline = FunctionTargetPipeline::default();
+            pipeline.add_processor(EliminateImmRefsProcessor::new());
+            pipeline.add_processor(UsageProcessor::new());
+            pipeline.add_processor(SpecVarFrameProcessor::new());
+            Ok(Some(pipeline))
+        }
         "usage_analysis" => {
             let mut pipeline = FunctionTargetPipeline::default();
             pipeline.add_processor(UsageProcessor::new());
@@ -211,6 +339,30 @@ fn get_tested_transformation_pipeline(
     }
 }
 
+/// Parses a `// native:` directive of the form `0x1::M::f = aborts, modifies(0)`. The semantics
+/// is `pure` or a list of `aborts`, `modifies(<parameter index>)`, and `writes_memory`.
+fn parse_native_semantics(directive: &str) -> anyhow::Result<(String, NativeSemantics)> {
+    let (name, items) = directive
+        .split_once('=')
+        .ok_or_else(|| anyhow!("bad native directive `{}`", directive))?;
+    let mut semantics = NativeSemantics::pure();
+    for item in items.split(',').map(str::trim) {
+        semantics = match item {
+            "pure" => semantics,
+            "aborts" => semantics.aborts(),
+            "writes_memory" => semantics.writes_memory(),
+            _ => match item
+                .strip_prefix("modifies(")
+                .and_then(|idx| idx.strip_suffix(')'))
+            {
+                Some(idx) => semantics.modifies_param(idx.parse()?),
+                None => return Err(anyhow!("bad native semantics `{}`", item)),
+            },
+        };
+    }
+    Ok((name.trim().to_string(), semantics))
+}
+
 fn test_runner(path: &Path) -> datatest_stable::Result<()> {
     let mut sources = extract_test_directives(path, "// dep:")?;
     sources.push(path.to_string_lossy().to_string());
@@ -230,9 +382,20 @@ fn test_runner(path: &Path) -> datatest_stable::Result<()> {
     } else {
         let options = ProverOptions {
             stable_test_output: true,
+            function_overrides: extract_test_directives(path, "// override:")?
+                .iter()
+                .filter_map(|o| o.split_once('='))
+                .map(|(original, replacement)| {
+                    (original.trim().to_string(), replacement.trim().to_string())
+                })
+                .collect(),
             ..Default::default()
         };
         env.set_extension(options);
+        for directive in extract_test_directives(path, "// native:")? {
+            let (name, semantics) = parse_native_semantics(&directive)?;
+            NativeSemanticsRegistry::register(&env, &name, semantics);
+        }
         let dir_name = path
             .parent()
             .and_then(|p| p.file_name())
@@ -250,9 +413,26 @@ fn test_runner(path: &Path) -> datatest_stable::Result<()> {
         }
         text += &print_targets_for_test(&env, "initial translation from Move", &targets);
 
-        // Run pipeline if any
+        // Run pipeline if any, printing the function targets after the processors named by
+        // `// print-after:` directives.
         if let Some(pipeline) = pipeline_opt {
-            pipeline.run(&env, &mut targets);
+            let print_after = extract_test_directives(path, "// print-after:")?;
+            let intermediate = RefCell::new(String::new());
+            pipeline.run_with_hook(
+                &env,
+                &mut targets,
+                |_| {},
+                |_, processor, targets| {
+                    if print_after.contains(&processor.name()) {
+                        intermediate.borrow_mut().push_str(&print_targets_for_test(
+                            &env,
+                            &format!("after processor `{}`", processor.name()),
+                            targets,
+                        ));
+                    }
+                },
+            );
+            text += &intermediate.into_inner();
             let processor = pipeline.last_processor();
             if !processor.is_single_run() {
                 text += &print_targets_for_test(
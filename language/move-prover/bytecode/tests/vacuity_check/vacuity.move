@@ -0,0 +1,18 @@
+module 0x42::TestVacuity {
+
+    public fun div(x: u64, y: u64): u64 {
+        x / y
+    }
+    spec div {
+        aborts_if y == 0;
+        ensures y == 1 ==> result == x;
+    }
+
+    public fun always_aborts(x: u64): u64 {
+        abort x
+    }
+    spec always_aborts {
+        aborts_if true;
+        ensures result == x;
+    }
+}
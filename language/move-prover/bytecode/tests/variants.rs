@@ -0,0 +1,109 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for the representation of enum variants in the model and in stackless bytecode.
+
+mod common;
+
+use move_model::ty::{PrimitiveType, Type};
+use move_stackless_bytecode::{
+    function_data_builder::FunctionDataBuilder, function_target::FunctionTarget,
+    function_target_pipeline::FunctionVariant,
+};
+use std::collections::BTreeMap;
+
+const MODULE: &str = "
+module 0x42::M {
+    struct Option<T> has drop { value: T }
+
+    fun get(o: &Option<u64>): u64 { o.value }
+}
+";
+
+#[test]
+fn structs_have_no_variants() {
+    let env = common::build("variants", MODULE);
+    let module_env = env.find_module_by_name_str("0x42::M").unwrap();
+    let struct_env = module_env
+        .find_struct(env.symbol_pool().make("Option"))
+        .unwrap();
+    assert!(!struct_env.has_variants());
+    assert_eq!(struct_env.get_variants().count(), 0);
+    assert!(struct_env
+        .get_fields()
+        .all(|field_env| field_env.get_variant().is_none()));
+    let ty = Type::Struct(
+        struct_env.module_env.get_id(),
+        struct_env.get_id(),
+        vec![Type::Primitive(PrimitiveType::U64)],
+    );
+    assert!(!ty.is_enum(&env));
+}
+
+#[test]
+fn emit_match() {
+    let env = common::build("variants", MODULE);
+    let mut targets = common::targets(&env);
+    let fun_env = env.find_function_by_name_str("M::get").unwrap();
+    let struct_env = fun_env
+        .module_env
+        .find_struct(env.symbol_pool().make("Option"))
+        .unwrap();
+    let enum_type = struct_env
+        .get_qualified_id()
+        .instantiate(vec![Type::Primitive(PrimitiveType::U64)]);
+    let (none, some) = (
+        env.symbol_pool().make("None"),
+        env.symbol_pool().make("Some"),
+    );
+
+    let data = targets.remove_target_data(&fun_env.get_qualified_id(), &FunctionVariant::Baseline);
+    let mut builder = FunctionDataBuilder::new(&fun_env, data);
+    builder.data.code.clear();
+    let (some_label, none_label, default) = (
+        builder.new_label(),
+        builder.new_label(),
+        builder.new_label(),
+    );
+    builder.emit_match(
+        0,
+        enum_type,
+        &[(some, some_label), (none, none_label)],
+        default,
+    );
+    let data = builder.data;
+
+    // Each arm tests the variant of the referenced value and branches to the arm if it matches,
+    // otherwise continues at a new label with the next arm.
+    let target = FunctionTarget::new(&fun_env, &data);
+    let label_offsets = BTreeMap::new();
+    let code = data
+        .code
+        .iter()
+        .map(|bc| bc.display(&target, &label_offsets).to_string())
+        .collect::<Vec<_>>();
+    let (t1, t2) = (data.local_types.len() - 2, data.local_types.len() - 1);
+    let (next1, next2) = (default.as_usize() + 1, default.as_usize() + 2);
+    assert_eq!(
+        code,
+        vec![
+            format!("$t{} := test_variant M::Option<u64>::Some($t0)", t1),
+            format!(
+                "if ($t{}) goto L{} else goto L{}",
+                t1,
+                some_label.as_usize(),
+                next1
+            ),
+            format!("label L{}", next1),
+            format!("$t{} := test_variant M::Option<u64>::None($t0)", t2),
+            format!(
+                "if ($t{}) goto L{} else goto L{}",
+                t2,
+                none_label.as_usize(),
+                next2
+            ),
+            format!("label L{}", next2),
+            format!("goto L{}", default.as_usize()),
+        ]
+    );
+}
@@ -0,0 +1,74 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for the translation of vector bytecodes into stackless bytecode. Except for `VecPack`,
+//! which is used for vector literals, these bytecodes are not generated by the Move compiler, so
+//! the tested module is compiled from Move IR.
+
+use move_ir_to_bytecode::{compiler::compile_module, parser::parse_module};
+use move_model::run_bytecode_model_builder_with_source_maps;
+use move_prover_test_utils::baseline_test::verify_or_update_baseline;
+use move_stackless_bytecode::{
+    function_target_pipeline::FunctionTargetsHolder, print_targets_for_test,
+};
+use std::path::Path;
+
+const MODULE: &str = "
+module 0x42.VectorOps {
+    public pack_unpack(x: u64, y: u64): u64 * u64 {
+        let v: vector<u64>;
+        let a: u64;
+        let b: u64;
+    label b0:
+        v = vec_pack_2<u64>(move(x), move(y));
+        a, b = vec_unpack_2<u64>(move(v));
+        return move(a), move(b);
+    }
+
+    public len(v: &vector<u64>): u64 {
+    label b0:
+        return vec_len<u64>(move(v));
+    }
+
+    public get(v: &vector<u64>, i: u64): u64 {
+    label b0:
+        return *vec_imm_borrow<u64>(move(v), move(i));
+    }
+
+    public set(v: &mut vector<u64>, i: u64, x: u64) {
+        let r: &mut u64;
+    label b0:
+        r = vec_mut_borrow<u64>(move(v), move(i));
+        *move(r) = move(x);
+        return;
+    }
+
+    public push_pop(v: &mut vector<u64>, x: u64): u64 {
+    label b0:
+        vec_push_back<u64>(copy(v), move(x));
+        return vec_pop_back<u64>(move(v));
+    }
+
+    public swap(v: &mut vector<u64>, i: u64, j: u64) {
+    label b0:
+        vec_swap<u64>(move(v), move(i), move(j));
+        return;
+    }
+}
+";
+
+#[test]
+fn vector_ops() {
+    let (module, source_map) = compile_module(parse_module(MODULE).unwrap(), &[]).unwrap();
+    let env =
+        run_bytecode_model_builder_with_source_maps(vec![(&module, Some(&source_map))], vec![])
+            .unwrap();
+    let mut targets = FunctionTargetsHolder::default();
+    for module_env in env.get_modules() {
+        for fun_env in module_env.get_functions() {
+            targets.add_target(&fun_env);
+        }
+    }
+    let text = print_targets_for_test(&env, "initial translation from bytecode", &targets);
+    verify_or_update_baseline(Path::new("tests/vector_ops.exp"), &text).unwrap();
+}
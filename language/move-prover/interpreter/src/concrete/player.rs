@@ -24,6 +24,7 @@ use move_model::{
 use move_stackless_bytecode::{
     function_target::FunctionTarget,
     function_target_pipeline::FunctionTargetsHolder,
+    native_semantics::{NativeSemantics, NativeSemanticsRegistry},
     stackless_bytecode::{
         AbortAction, AssignKind, BorrowEdge, BorrowNode, Bytecode, Constant, HavocKind, Label,
         Operation, PropKind,
@@ -60,6 +61,7 @@ const DIEM_CORE_ADDR: AccountAddress = CORE_CODE_ADDRESS;
 const INDEX_OUT_OF_BOUNDS: u64 = sub_status::NFE_VECTOR_ERROR_BASE + 1;
 const POP_EMPTY_VEC: u64 = sub_status::NFE_VECTOR_ERROR_BASE + 2;
 const DESTROY_NON_EMPTY_VEC: u64 = sub_status::NFE_VECTOR_ERROR_BASE + 3;
+const UNPACK_PARITY_MISMATCH: u64 = sub_status::NFE_VECTOR_ERROR_BASE + 4;
 
 //**************************************************************************************************
 // Execution context
@@ -138,6 +140,58 @@ impl<'env> FunctionContext<'env> {
         Ok(local_state)
     }
 
+    /// Execute a native function for which semantics have been registered, and check that the
+    /// execution conforms to them. Analyses rely on the registered semantics, hence a violation
+    /// indicates that the registration is unsound.
+    fn exec_native_function_with_semantics(
+        &self,
+        semantics: &NativeSemantics,
+        srcs: &[TempIndex],
+        typed_args: Vec<TypedValue>,
+        local_state: &mut LocalState,
+        global_state: &mut GlobalState,
+    ) -> ExecResult<Vec<TypedValue>> {
+        let unmodified_args: BTreeMap<_, _> = typed_args
+            .iter()
+            .enumerate()
+            .filter(|(idx, arg)| arg.get_ty().is_ref(Some(true)) && !semantics.modifies(*idx))
+            .map(|(idx, arg)| (srcs[idx], arg.clone()))
+            .collect();
+        let global_state_before = if semantics.writes_memory {
+            None
+        } else {
+            Some(global_state.clone())
+        };
+
+        let result = self.exec_native_function(srcs, typed_args, local_state, global_state);
+
+        let violation = if result.is_err() && !semantics.can_abort {
+            Some("is registered as non-aborting but aborted")
+        } else if unmodified_args
+            .into_iter()
+            .any(|(src, arg)| local_state.has_value(src) && local_state.get_value(src) != arg)
+        {
+            Some("modified an argument it is not registered to modify")
+        } else if matches!(global_state_before, Some(before) if &before != global_state) {
+            Some("is registered as not writing memory but modified it")
+        } else {
+            None
+        };
+        if let Some(violation) = violation {
+            let env = self.target.global_env();
+            env.error(
+                &self.target.get_loc(),
+                &format!(
+                    "native function `{}` {}",
+                    self.target.func_env.get_full_name_str(),
+                    violation
+                ),
+            );
+            return Err(self.sys_abort(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR));
+        }
+        result
+    }
+
     /// Execute a native function with the type arguments and value arguments.
     fn exec_native_function(
         &self,
@@ -565,6 +619,96 @@ impl<'env> FunctionContext<'env> {
                 );
                 Ok(vec![field])
             }
+            // vector
+            Operation::VecPack(elem_ty, num) => {
+                if cfg!(debug_assertions) {
+                    assert_eq!(typed_args.len(), *num);
+                }
+                let env = self.target.global_env();
+                let elem_ty = convert_model_base_type(env, elem_ty, &self.ty_args);
+                Ok(vec![TypedValue::mk_vector(elem_ty, typed_args)])
+            }
+            Operation::VecUnpack(elem_ty, num) => {
+                if cfg!(debug_assertions) {
+                    assert_eq!(typed_args.len(), 1);
+                }
+                let env = self.target.global_env();
+                let elem_ty = convert_model_base_type(env, elem_ty, &self.ty_args);
+                let elems = typed_args.remove(0).into_vector();
+                if elems.len() != *num {
+                    Err(self.usr_abort(UNPACK_PARITY_MISMATCH))
+                } else {
+                    Ok(elems
+                        .into_iter()
+                        .map(|val| TypedValue::fuse_base(elem_ty.clone(), val))
+                        .collect())
+                }
+            }
+            Operation::VecLen(_) => {
+                if cfg!(debug_assertions) {
+                    assert_eq!(typed_args.len(), 1);
+                }
+                let len = typed_args.remove(0).into_vector().len();
+                Ok(vec![TypedValue::mk_u64(len as u64)])
+            }
+            Operation::VecBorrow(_) => {
+                if cfg!(debug_assertions) {
+                    assert_eq!(typed_args.len(), 2);
+                }
+                let vec_val = typed_args.remove(0);
+                let elem_num = typed_args.remove(0).into_u64() as usize;
+                vec_val
+                    .get_vector_element(elem_num)
+                    .ok_or_else(|| self.usr_abort(INDEX_OUT_OF_BOUNDS))
+                    .map(|res| vec![res])
+            }
+            Operation::VecBorrowMut(_) => {
+                if cfg!(debug_assertions) {
+                    assert_eq!(typed_args.len(), 2);
+                }
+                let vec_val = typed_args.remove(0);
+                let elem_num = typed_args.remove(0).into_u64() as usize;
+                vec_val
+                    .borrow_ref_vector_element(elem_num, true, srcs[0])
+                    .ok_or_else(|| self.usr_abort(INDEX_OUT_OF_BOUNDS))
+                    .map(|res| vec![res])
+            }
+            Operation::VecPushBack(_) => {
+                if cfg!(debug_assertions) {
+                    assert_eq!(typed_args.len(), 2);
+                }
+                let vec_val = typed_args.remove(0);
+                let res = vec_val.update_ref_vector_push_back(typed_args.remove(0));
+                local_state.put_value_override(srcs[0], res);
+                Ok(vec![])
+            }
+            Operation::VecPopBack(_) => {
+                if cfg!(debug_assertions) {
+                    assert_eq!(typed_args.len(), 1);
+                }
+                match typed_args.remove(0).update_ref_vector_pop_back() {
+                    Some((new_vec, elem_val)) => {
+                        local_state.put_value_override(srcs[0], new_vec);
+                        Ok(vec![elem_val])
+                    }
+                    None => Err(self.usr_abort(POP_EMPTY_VEC)),
+                }
+            }
+            Operation::VecSwap(_) => {
+                if cfg!(debug_assertions) {
+                    assert_eq!(typed_args.len(), 3);
+                }
+                let vec_val = typed_args.remove(0);
+                let lhs = typed_args.remove(0).into_u64() as usize;
+                let rhs = typed_args.remove(0).into_u64() as usize;
+                match vec_val.update_ref_vector_swap(lhs, rhs) {
+                    Some(new_vec) => {
+                        local_state.put_value_override(srcs[0], new_vec);
+                        Ok(vec![])
+                    }
+                    None => Err(self.usr_abort(INDEX_OUT_OF_BOUNDS)),
+                }
+            }
             Operation::MoveTo(module_id, struct_id, ty_args) => {
                 if cfg!(debug_assertions) {
                     assert_eq!(typed_args.len(), 2);
@@ -848,6 +992,23 @@ impl<'env> FunctionContext<'env> {
                     self.handle_binary_boolean(op, lhs, rhs, local_state.get_type(dsts[0]));
                 Ok(vec![calculated])
             }
+            // enum variants (TODO: not supported yet, as values do not carry a variant tag).
+            // This is not an abort of the program, hence the abort handler is skipped.
+            Operation::PackVariant(..)
+            | Operation::UnpackVariant(..)
+            | Operation::TestVariant(..)
+            | Operation::BorrowVariantField(..) => {
+                let env = self.target.global_env();
+                env.error(
+                    &self.target.get_loc(),
+                    &format!(
+                        "enum variant operations are not yet supported by the interpreter \
+                         (in `{}`)",
+                        self.target.func_env.get_full_name_str()
+                    ),
+                );
+                return Err(self.sys_abort(StatusCode::FEATURE_UNDER_GATING));
+            }
             // event (TODO: not supported yet)
             Operation::EmitEvent | Operation::EventStoreDiverge => Ok(vec![]),
             // already handled
@@ -914,7 +1075,18 @@ impl<'env> FunctionContext<'env> {
 
         // short-circuit the execution if this is a native function
         if callee_env.is_native() {
-            return callee_ctxt.exec_native_function(srcs, typed_args, local_state, global_state);
+            return match NativeSemanticsRegistry::get(env, callee_env.get_qualified_id()) {
+                None => {
+                    callee_ctxt.exec_native_function(srcs, typed_args, local_state, global_state)
+                }
+                Some(semantics) => callee_ctxt.exec_native_function_with_semantics(
+                    &semantics,
+                    srcs,
+                    typed_args,
+                    local_state,
+                    global_state,
+                ),
+            };
         }
 
         // collect mutable arguments
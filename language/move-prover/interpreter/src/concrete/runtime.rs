@@ -281,7 +281,7 @@ fn check_type_instantiation(
         ));
     }
     for (arg, param) in args.iter().zip(params) {
-        if !param.1 .0.is_subset(get_abilities(env, arg)?) {
+        if !param.get_abilities().is_subset(get_abilities(env, arg)?) {
             return Err(PartialVMError::new(StatusCode::CONSTRAINT_NOT_SATISFIED));
         }
     }
@@ -21,7 +21,9 @@ use move_core_types::{
     value::{MoveStructLayout, MoveTypeLayout},
 };
 use move_model::{
-    model::{GlobalEnv, ModuleId, StructId},
+    addr_to_big_uint,
+    ast::ModuleName,
+    model::{GlobalEnv, ModuleId, QualifiedInstId, StructId},
     ty as MT,
 };
 use move_stackless_bytecode::stackless_bytecode::Constant;
@@ -885,3 +887,49 @@ pub fn convert_model_partial_struct_type(
         fields,
     }
 }
+
+pub fn convert_base_type_to_model(env: &GlobalEnv, ty: &BaseType) -> MT::Type {
+    match ty {
+        BaseType::Primitive(PrimitiveType::Bool) => MT::Type::Primitive(MT::PrimitiveType::Bool),
+        BaseType::Primitive(PrimitiveType::Int(IntType::U8)) => {
+            MT::Type::Primitive(MT::PrimitiveType::U8)
+        }
+        BaseType::Primitive(PrimitiveType::Int(IntType::U64)) => {
+            MT::Type::Primitive(MT::PrimitiveType::U64)
+        }
+        BaseType::Primitive(PrimitiveType::Int(IntType::U128)) => {
+            MT::Type::Primitive(MT::PrimitiveType::U128)
+        }
+        BaseType::Primitive(PrimitiveType::Int(IntType::Num)) => {
+            MT::Type::Primitive(MT::PrimitiveType::Num)
+        }
+        BaseType::Primitive(PrimitiveType::Address) => {
+            MT::Type::Primitive(MT::PrimitiveType::Address)
+        }
+        BaseType::Primitive(PrimitiveType::Signer) => {
+            MT::Type::Primitive(MT::PrimitiveType::Signer)
+        }
+        BaseType::Vector(elem) => MT::Type::Vector(Box::new(convert_base_type_to_model(env, elem))),
+        BaseType::Struct(inst) => convert_struct_type_to_model(env, inst).to_type(),
+    }
+}
+
+pub fn convert_struct_type_to_model(
+    env: &GlobalEnv,
+    inst: &StructInstantiation,
+) -> QualifiedInstId<StructId> {
+    let pool = env.symbol_pool();
+    let module_name = ModuleName::new(
+        addr_to_big_uint(&inst.ident.module.address),
+        pool.make(&inst.ident.module.name),
+    );
+    let module_env = env.find_module(&module_name).unwrap();
+    let struct_env = module_env.find_struct(pool.make(&inst.ident.name)).unwrap();
+    module_env.get_id().qualified_inst(
+        struct_env.get_id(),
+        inst.insts
+            .iter()
+            .map(|ty| convert_base_type_to_model(env, ty))
+            .collect(),
+    )
+}
@@ -15,11 +15,20 @@ use move_core_types::{
     effects::ChangeSet,
     value::{MoveStruct, MoveValue},
 };
-use move_model::ast::{MemoryLabel, TempIndex};
+use move_model::{
+    addr_to_big_uint,
+    ast::{MemoryLabel, TempIndex},
+    big_uint_to_addr,
+    global_state::GlobalState as ModelGlobalState,
+    model::GlobalEnv,
+    ty as MTy,
+    value::MoveValue as ModelValue,
+};
 
 use crate::{
     concrete::ty::{
-        BaseType, PartialStructInstantiation, PrimitiveType, StructInstantiation, Type,
+        convert_model_struct_type, convert_struct_type_to_model, BaseType,
+        PartialStructInstantiation, PrimitiveType, StructInstantiation, Type,
     },
     shared::ident::StructIdent,
 };
@@ -127,6 +136,58 @@ impl BaseValue {
             _ => unreachable!(),
         }
     }
+
+    /// Converts the value into the value representation of the model, given its model type.
+    pub fn to_model_value(&self, env: &GlobalEnv, ty: &MTy::Type) -> ModelValue {
+        match (self, ty) {
+            (Self::Bool(v), _) => ModelValue::Bool(*v),
+            (Self::Int(v), _) => ModelValue::Number(v.clone()),
+            (Self::Address(v), _) | (Self::Signer(v), _) => {
+                ModelValue::Address(addr_to_big_uint(v))
+            }
+            (Self::Vector(v), MTy::Type::Vector(elem_ty)) => {
+                ModelValue::Vector(v.iter().map(|e| e.to_model_value(env, elem_ty)).collect())
+            }
+            (Self::Struct(v), MTy::Type::Struct(module_id, struct_id, inst)) => {
+                let struct_env = env.get_module(*module_id).into_struct(*struct_id);
+                ModelValue::Struct(
+                    module_id.qualified_inst(*struct_id, inst.clone()),
+                    struct_env
+                        .get_fields()
+                        .zip(v)
+                        .map(|(field_env, e)| {
+                            e.to_model_value(env, &field_env.get_type().instantiate(inst))
+                        })
+                        .collect(),
+                )
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// Converts a value of the model representation into a value of the given type.
+    pub fn from_model_value(val: &ModelValue, ty: &BaseType) -> Self {
+        match (val, ty) {
+            (ModelValue::Bool(v), _) => Self::Bool(*v),
+            (ModelValue::Number(v), _) => Self::Int(v.clone()),
+            (ModelValue::Address(v), BaseType::Primitive(PrimitiveType::Signer)) => {
+                Self::Signer(big_uint_to_addr(v))
+            }
+            (ModelValue::Address(v), _) => Self::Address(big_uint_to_addr(v)),
+            (ModelValue::Vector(v), BaseType::Vector(elem_ty)) => Self::Vector(
+                v.iter()
+                    .map(|e| Self::from_model_value(e, elem_ty))
+                    .collect(),
+            ),
+            (ModelValue::Struct(_, v), BaseType::Struct(inst)) => Self::Struct(
+                v.iter()
+                    .zip(inst.fields.iter())
+                    .map(|(e, field)| Self::from_model_value(e, &field.ty))
+                    .collect(),
+            ),
+            _ => unreachable!(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
@@ -1087,6 +1148,35 @@ impl GlobalState {
         }
     }
 
+    /// Converts the resources of the state into the global state representation of the model.
+    pub fn to_model_state(&self, env: &GlobalEnv) -> ModelGlobalState {
+        let mut state = ModelGlobalState::new();
+        for (addr, account_state) in &self.accounts {
+            for (key, val) in &account_state.storage {
+                let ty = convert_struct_type_to_model(env, key);
+                let val = val.to_model_value(env, &ty.to_type());
+                state.put(addr_to_big_uint(addr), ty, val);
+            }
+        }
+        state
+    }
+
+    /// Creates a state holding the resources of the global state representation of the model,
+    /// e.g. a state imported from chain.
+    pub fn from_model_state(env: &GlobalEnv, model_state: &ModelGlobalState) -> Self {
+        let mut state = Self::default();
+        for (addr, ty, val) in model_state.iter() {
+            let key = convert_model_struct_type(env, ty.module_id, ty.id, &ty.inst, &[]);
+            let val = BaseValue::from_model_value(val, &BaseType::mk_struct(key.clone()));
+            state
+                .accounts
+                .entry(big_uint_to_addr(addr))
+                .or_default()
+                .put_resource(&key, val);
+        }
+        state
+    }
+
     /// Calculate the delta (i.e., a ChangeSet) against the old state
     pub fn delta(&self, old_state: &GlobalState) -> ChangeSet {
         fn bcs_serialize_resource(key: &StructInstantiation, val: &BaseValue) -> Vec<u8> {
@@ -1727,7 +1727,7 @@ impl<'env> Docgen<'env> {
 
     /// Display a type parameter.
     fn type_parameter_display(&self, tp: &TypeParameter) -> String {
-        let ability_tokens = self.ability_tokens(tp.1 .0);
+        let ability_tokens = self.ability_tokens(tp.get_abilities());
         if ability_tokens.is_empty() {
             self.name_string(tp.0).to_string()
         } else {
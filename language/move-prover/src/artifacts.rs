@@ -0,0 +1,120 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Per-function artifacts of a prover run. If an artifacts directory is given, a directory
+//! `<module>/<function>` is created in it for each verified function of the target modules,
+//! holding:
+//!
+//! - `bytecode.txt`: the instrumented bytecode of all variants of the function.
+//! - `boogie.bpl`: the Boogie declarations generated for the function, as determined by the
+//!   source locations the code writer records for them.
+//! - `solver.log`: the output of the backend while verifying the procedures of the function.
+//! - `result.json`: the entry of the function in the manifest of the run.
+//!
+//! The complete Boogie program is written to `output.bpl` in the artifacts directory, so that a
+//! failure can be reproduced without rerunning the pipeline.
+
+use crate::manifest::RunManifest;
+use codespan::ByteIndex;
+use move_model::{
+    code_writer::CodeWriter,
+    model::{FunId, GlobalEnv, QualifiedId},
+};
+use move_prover_boogie_backend::boogie_wrapper::BoogieOutput;
+use move_stackless_bytecode::{
+    function_target_pipeline::FunctionTargetsHolder, print_targets_with_filter,
+};
+use std::{collections::BTreeMap, fs, path::Path};
+
+/// The Boogie code of a run, split up by the functions it has been generated for.
+pub struct FunctionArtifacts {
+    program: String,
+    boogie: BTreeMap<QualifiedId<FunId>, String>,
+}
+
+impl FunctionArtifacts {
+    /// Collects the Boogie code generated for each function. A top-level declaration belongs
+    /// to the function enclosing the source location of its first line.
+    pub fn collect(env: &GlobalEnv, writer: &CodeWriter) -> Self {
+        let program = writer.process_result(|result| result.to_string());
+        let mut boogie: BTreeMap<QualifiedId<FunId>, String> = BTreeMap::new();
+        let mut owner = None;
+        let mut offset = 0;
+        for line in program.split_inclusive('\n') {
+            let is_top_level = line
+                .chars()
+                .next()
+                .map(|c| !c.is_whitespace() && c != '}')
+                .unwrap_or(false);
+            if is_top_level {
+                owner = writer
+                    .get_source_location(ByteIndex(offset as u32))
+                    .and_then(|loc| env.get_enclosing_function(&loc))
+                    .map(|fun_env| fun_env.get_qualified_id());
+            }
+            if let Some(fun_id) = owner {
+                boogie.entry(fun_id).or_default().push_str(line);
+            }
+            offset += line.len();
+        }
+        Self { program, boogie }
+    }
+
+    /// Writes the artifacts of the verified functions of the target modules into the directory.
+    pub fn write(
+        &self,
+        env: &GlobalEnv,
+        targets: &FunctionTargetsHolder,
+        manifest: &RunManifest,
+        output: Option<&BoogieOutput>,
+        dir: &str,
+    ) -> anyhow::Result<()> {
+        let dir = Path::new(dir);
+        fs::create_dir_all(dir)?;
+        fs::write(dir.join("output.bpl"), &self.program)?;
+        let logs = output
+            .map(|output| output.function_logs(env))
+            .unwrap_or_default();
+        for module_env in env.get_modules().filter(|m| m.is_target()) {
+            let module_name = module_env.get_full_name_str();
+            let module_manifest = manifest.modules.iter().find(|m| m.name == module_name);
+            for fun_env in module_env.get_functions() {
+                let verified = targets
+                    .get_target_variants(&fun_env)
+                    .iter()
+                    .any(|v| v.is_verified());
+                if !verified {
+                    continue;
+                }
+                let fun_id = fun_env.get_qualified_id();
+                let fun_name = env.symbol_pool().string(fun_env.get_name()).to_string();
+                let fun_dir = dir.join(module_name.replace("::", "_")).join(&fun_name);
+                fs::create_dir_all(&fun_dir)?;
+                fs::write(
+                    fun_dir.join("bytecode.txt"),
+                    print_targets_with_filter(
+                        env,
+                        &format!("bytecode of `{}`", fun_env.get_full_name_str()),
+                        targets,
+                        |f| f.get_qualified_id() == fun_id,
+                    ),
+                )?;
+                if let Some(code) = self.boogie.get(&fun_id) {
+                    fs::write(fun_dir.join("boogie.bpl"), code)?;
+                }
+                if let Some(log) = logs.get(&fun_id) {
+                    fs::write(fun_dir.join("solver.log"), log)?;
+                }
+                if let Some(fun_manifest) =
+                    module_manifest.and_then(|m| m.functions.iter().find(|f| f.name == fun_name))
+                {
+                    fs::write(
+                        fun_dir.join("result.json"),
+                        serde_json::to_string_pretty(fun_manifest)?,
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,101 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Assume-guarantee splitting of verification across modules.
+//!
+//! With the `assume_dependency_specs` model builder option, the target modules are verified
+//! against the specifications of their dependencies, which are recorded as assumed specs in the
+//! manifest of the run. A separate run with the dependencies as targets discharges those specs.
+//! The consistency report ties the two runs together: an assumed spec is discharged only if the
+//! second run verified the same function with the same specification.
+
+use crate::manifest::{AssumedSpec, FunctionResult, RunManifest};
+use std::{collections::BTreeMap, fmt};
+
+/// The status of an assumed spec in the discharging run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DischargeStatus {
+    /// The function has been verified against the assumed spec.
+    Discharged,
+    /// Verification of the function failed.
+    Failed,
+    /// The function has not been verified, e.g. because of `pragma verify = false`.
+    NotVerified,
+    /// The function has been verified, but its spec differs from the assumed one.
+    SpecChanged,
+    /// The function is not part of the target modules of the discharging run.
+    Missing,
+}
+
+impl fmt::Display for DischargeStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use DischargeStatus::*;
+        f.write_str(match self {
+            Discharged => "discharged",
+            Failed => "verification failed",
+            NotVerified => "not verified",
+            SpecChanged => "spec changed",
+            Missing => "not a target",
+        })
+    }
+}
+
+/// The consistency report of an assuming and a discharging run.
+pub struct ConsistencyReport {
+    pub entries: Vec<(AssumedSpec, DischargeStatus)>,
+}
+
+impl ConsistencyReport {
+    /// Checks which of the specs assumed by the `assuming` run are discharged by the
+    /// `discharging` run.
+    pub fn new(assuming: &RunManifest, discharging: &RunManifest) -> Self {
+        let functions = discharging
+            .modules
+            .iter()
+            .flat_map(|m| m.functions.iter())
+            .map(|f| (f.stable_id.as_str(), f))
+            .collect::<BTreeMap<_, _>>();
+        let entries = assuming
+            .assumed_specs
+            .iter()
+            .map(|spec| {
+                let status = match functions.get(spec.stable_id.as_str()) {
+                    None => DischargeStatus::Missing,
+                    Some(f) if f.spec_hash != spec.spec_hash => DischargeStatus::SpecChanged,
+                    Some(f) => match f.result {
+                        FunctionResult::Verified => DischargeStatus::Discharged,
+                        FunctionResult::Failed => DischargeStatus::Failed,
+                        FunctionResult::Skipped | FunctionResult::NotRun => {
+                            DischargeStatus::NotVerified
+                        }
+                    },
+                };
+                (spec.clone(), status)
+            })
+            .collect();
+        Self { entries }
+    }
+
+    /// Returns the number of assumed specs which are not discharged.
+    pub fn undischarged_count(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|(_, status)| *status != DischargeStatus::Discharged)
+            .count()
+    }
+}
+
+impl fmt::Display for ConsistencyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} assumed specs, {} discharged",
+            self.entries.len(),
+            self.entries.len() - self.undischarged_count()
+        )?;
+        for (spec, status) in &self.entries {
+            writeln!(f, "  {}: {}", spec.name, status)?;
+        }
+        Ok(())
+    }
+}
@@ -26,10 +26,14 @@ use move_abigen::AbigenOptions;
 use move_docgen::DocgenOptions;
 use move_errmapgen::ErrmapOptions;
 use move_model::{
-    model::VerificationScope, options::ModelBuilderOptions, simplifier::SimplificationPass,
+    language_version::LanguageVersion, model::VerificationScope, options::ModelBuilderOptions,
+    simplifier::SimplificationPass,
 };
 use move_prover_boogie_backend::options::{BoogieOptions, VectorTheory};
-use move_stackless_bytecode::options::{AutoTraceLevel, ProverOptions};
+use move_stackless_bytecode::{
+    function_target_pipeline::PipelineConfig,
+    options::{AutoTraceLevel, ProverOptions},
+};
 
 /// Atomic used to prevent re-initialization of logging.
 static LOGGER_CONFIGURED: AtomicBool = AtomicBool::new(false);
@@ -60,6 +64,41 @@ pub struct Options {
     pub run_read_write_set: bool,
     /// Whether to run the internal reference escape analysis instead of the prover
     pub run_escape: bool,
+    /// Whether to report the spec coverage of the code instead of running the prover
+    pub run_spec_coverage: bool,
+    /// Whether to report the abort paths of functions instead of running the prover
+    pub run_abort_paths: bool,
+    /// Whether to report functions whose `aborts_if` conditions are weaker than the inferred
+    /// ones instead of running the prover
+    pub run_inferred_aborts: bool,
+    /// Whether to report the specification variables functions must update instead of running
+    /// the prover
+    pub run_spec_var_frames: bool,
+    /// Whether to report the complexity metrics of spec conditions instead of running the prover
+    pub run_spec_metrics: bool,
+    /// Whether to report the findings of audit heuristics instead of running the prover
+    pub run_audit_report: bool,
+    /// Whether to run the model lints instead of the prover
+    pub run_lints: bool,
+    /// Whether to run the spec tests instead of the prover
+    pub run_spec_tests: bool,
+    /// Whether to extract repeated spec fragments into spec helper functions instead of running
+    /// the prover
+    pub run_spec_extraction: bool,
+    /// The directory to write the state model documents of the target modules to, instead of
+    /// running the prover.
+    pub state_model_dir: Option<String>,
+    /// The file to write the resource access matrix of the target modules to, instead of
+    /// running the prover. The matrix is written in JSON if the file ends in `.json`, and in
+    /// CSV otherwise.
+    pub access_matrix_output: Option<String>,
+    /// The directory to write mocks of the target modules to, instead of running the prover.
+    pub mock_dir: Option<String>,
+    /// The file to write Rust BCS codecs of types to, instead of running the prover.
+    pub rust_bcs_output: Option<String>,
+    /// The types to generate Rust BCS codecs for. If empty, the non-generic structs of the
+    /// target modules are used.
+    pub rust_bcs_types: Vec<String>,
     /// The paths to the Move sources.
     pub move_sources: Vec<String>,
     /// The paths to any dependencies for the Move sources. Those will not be verified but
@@ -71,6 +110,22 @@ pub struct Options {
     pub experimental_pipeline: bool,
     /// Options for printing out modules and functions reachable by script functions
     pub script_reach: bool,
+    /// The file to write a machine-readable manifest of the run to, if any.
+    pub manifest_path: Option<String>,
+    /// The directory to write per-function artifacts of the run to, if any.
+    pub artifacts_dir: Option<String>,
+    /// The manifest of a run whose assumed dependency specs are to be discharged by this run.
+    pub discharge_manifest: Option<String>,
+    /// Whether to report the metrics of each processor of the bytecode pipeline.
+    pub report_pipeline_metrics: bool,
+    /// The file defining human readable names of account addresses, if any.
+    pub address_aliases: Option<String>,
+    /// Whether to apply the preferred fixes of reported diagnostics to the sources.
+    pub apply_fixes: bool,
+    /// Whether to report diagnostics as JSON, one object per line, instead of as text.
+    pub json_diagnostics: bool,
+    /// The file to write a SARIF log of the diagnostics to, if any.
+    pub sarif_output: Option<String>,
 
     /// BEGIN OF STRUCTURED OPTIONS. DO NOT ADD VALUE FIELDS AFTER THIS
     /// Options for the model builder.
@@ -83,6 +138,14 @@ pub struct Options {
     pub backend: BoogieOptions,
     /// Options for the ABI generator.
     pub abigen: AbigenOptions,
+    /// The configuration of the bytecode pipeline, if it is not the default one.
+    pub pipeline: Option<PipelineConfig>,
+    /// The packages of a workspace which are verified together with `move_sources`. Each
+    /// package keeps its own named address bindings, and diagnostics are grouped by package.
+    pub workspace_packages: Vec<WorkspacePackage>,
+    /// Paths to the bytecode of modules, e.g. as deployed on chain, whose functions are checked
+    /// to be equivalent with the functions of the same name compiled from `move_sources`.
+    pub equivalence_bytecode: Vec<String>,
     /// Options for the error map generator.
     /// TODO: this currently create errors during deserialization, so skip them for this.
     #[serde(skip_serializing)]
@@ -98,9 +161,24 @@ impl Default for Options {
             run_errmapgen: false,
             run_read_write_set: false,
             run_escape: false,
+            run_spec_coverage: false,
+            run_abort_paths: false,
+            run_inferred_aborts: false,
+            run_spec_var_frames: false,
+            run_spec_metrics: false,
+            run_audit_report: false,
+            run_lints: false,
+            run_spec_tests: false,
+            run_spec_extraction: false,
+            state_model_dir: None,
+            access_matrix_output: None,
+            mock_dir: None,
+            rust_bcs_output: None,
+            rust_bcs_types: vec![],
             verbosity_level: LevelFilter::Info,
             move_sources: vec![],
             move_deps: vec![],
+            equivalence_bytecode: vec![],
             move_named_address_values: vec![],
             model_builder: ModelBuilderOptions::default(),
             prover: ProverOptions::default(),
@@ -108,12 +186,35 @@ impl Default for Options {
             docgen: DocgenOptions::default(),
             abigen: AbigenOptions::default(),
             errmapgen: ErrmapOptions::default(),
+            pipeline: None,
+            workspace_packages: vec![],
             experimental_pipeline: false,
             script_reach: false,
+            manifest_path: None,
+            artifacts_dir: None,
+            discharge_manifest: None,
+            report_pipeline_metrics: false,
+            address_aliases: None,
+            apply_fixes: false,
+            json_diagnostics: false,
+            sarif_output: None,
         }
     }
 }
 
+/// A package of a workspace, with its own sources and named address bindings.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WorkspacePackage {
+    /// The name of the package.
+    pub name: String,
+    /// The paths to the Move sources of the package.
+    pub sources: Vec<String>,
+    /// The values assigned to named addresses in the package, in addition to the ones given by
+    /// `move_named_address_values`, which they override.
+    pub named_address_values: Vec<String>,
+}
+
 pub static DEFAULT_OPTIONS: Lazy<Options> = Lazy::new(Options::default);
 
 impl Options {
@@ -175,6 +276,45 @@ impl Options {
                     .value_name("BOOGIE_FILE")
                     .help("path to the boogie output which represents the verification problem"),
             )
+            .arg(
+                Arg::new("manifest")
+                    .long("manifest")
+                    .takes_value(true)
+                    .value_name("JSON_FILE")
+                    .help("writes a machine-readable manifest of the run (version, options, \
+                    pipeline, file hashes, pragmas, per-function results and timings)"),
+            )
+            .arg(
+                Arg::new("artifacts-dir")
+                    .long("artifacts-dir")
+                    .takes_value(true)
+                    .value_name("DIR")
+                    .help("writes the instrumented bytecode, Boogie code, backend output and \
+                    result of each verified function into a directory of its own under DIR"),
+            )
+            .arg(
+                Arg::new("address-aliases")
+                    .long("address-aliases")
+                    .takes_value(true)
+                    .value_name("FILE")
+                    .help("a file with lines `name = 0x4f2b` defining names of addresses, \
+                    which are displayed as `@name` in diagnostics and counterexamples"),
+            )
+            .arg(
+                Arg::new("assume-dependency-specs")
+                    .long("assume-dependency-specs")
+                    .help("verifies the targets against the specs of dependency functions \
+                    instead of their implementation; the assumed specs are recorded \
+                    in the manifest and can be discharged by a separate run with `--discharge`"),
+            )
+            .arg(
+                Arg::new("discharge")
+                    .long("discharge")
+                    .takes_value(true)
+                    .value_name("JSON_FILE")
+                    .help("checks that the dependency specs assumed by the run with the given \
+                    manifest are verified by this run, and reports any which are not"),
+            )
             .arg(
                 Arg::new("verbosity")
                     .short('v')
@@ -238,7 +378,26 @@ impl Options {
                     .takes_value(true)
                     .value_name("NUMBER")
                     .validator(is_number)
-                    .help("sets a random seed for the prover (default 0)")
+                    .help("sets the random seed for the prover, from which the seeds of all \
+                    randomized components (like multiple solver instances, or the operation \
+                    mutated by `--mutation`) are derived (default 1)")
+            )
+            .arg(
+                Arg::new("minimize-counterexamples")
+                    .long("minimize-counterexamples")
+                    .takes_value(true)
+                    .value_name("STEPS")
+                    .validator(is_number)
+                    .help("shrinks counterexamples of failed verification conditions by \
+                    bounding integer and address parameters and the lengths of vector \
+                    parameters, using at most the given number of additional solver calls \
+                    for all errors of a run")
+            )
+            .arg(
+                Arg::new("report-used-assumptions")
+                    .long("report-used-assumptions")
+                    .help("reports the assumptions used to verify each function, \
+                    to help detecting vacuous proofs and too strong preconditions")
             )
             .arg(
                 Arg::new("cores")
@@ -272,6 +431,32 @@ impl Options {
                     .help("Ignore the \"opaque\" pragma on specs of \
                     internal functions when possible"),
             )
+            .arg(
+                Arg::new("spec-files")
+                    .long("spec-file")
+                    .multiple_occurrences(true)
+                    .number_of_values(1)
+                    .takes_value(true)
+                    .value_name("PATH_TO_SPEC_FILE")
+                    .help("path to a Move file containing only specification modules \
+                    which are merged into their target modules")
+            )
+            .arg(
+                Arg::new("exclude-spec-files")
+                    .long("exclude-spec-files")
+                    .help("excludes files with extension `.spec.move` from the sources \
+                    and dependencies")
+            )
+            .arg(
+                Arg::new("language-version")
+                    .long("language-version")
+                    .takes_value(true)
+                    .value_name("VERSION")
+                    .possible_values(&["1", "2", "3"])
+                    .help("the version of the Move language the sources target; features \
+                    not available in this version are reported as errors. Defaults to the \
+                    latest version.")
+            )
             .arg(
                 Arg::new("simplification-pipeline")
                     .long("simplify")
@@ -316,6 +501,110 @@ impl Options {
                     .long("escape")
                     .help("runs the escape analysis instead of the prover.")
             )
+            .arg(
+                Arg::new("spec-coverage")
+                    .long("spec-coverage")
+                    .help("reports for each line of code how many `ensures` and `aborts_if` \
+                    conditions constrain it, instead of running the prover.")
+            )
+            .arg(
+                Arg::new("abort-paths")
+                    .long("abort-paths")
+                    .help("reports the paths on which each function can abort, and the functions \
+                    whose `aborts_if` conditions do not cover them, instead of running the prover.")
+            )
+            .arg(
+                Arg::new("inferred-aborts")
+                    .long("inferred-aborts")
+                    .help("infers the abort conditions of each function, propagated through \
+                    calls, and reports those not covered by its `aborts_if` conditions, instead \
+                    of running the prover.")
+            )
+            .arg(
+                Arg::new("spec-var-frames")
+                    .long("spec-var-frames")
+                    .help("reports the functions which modify memory related to a specification \
+                    variable by a global invariant without updating the variable, with skeletons \
+                    of the missing `update` statements, instead of running the prover.")
+            )
+            .arg(
+                Arg::new("spec-metrics")
+                    .long("spec-metrics")
+                    .help("reports the quantifier depth, term count, old-state references and \
+                    schema expansions of each spec condition, instead of running the prover.")
+            )
+            .arg(
+                Arg::new("audit-report")
+                    .long("audit-report")
+                    .help("reports the assertion density of the target modules and a prioritized \
+                    list of functions flagged by audit heuristics, instead of running the prover.")
+            )
+            .arg(
+                Arg::new("lint")
+                    .long("lint")
+                    .help("runs the lints over the target modules and reports their findings \
+                    grouped by lint, instead of running the prover. Lint levels are configured \
+                    with attributes like `#[lint(allow(name))]` on modules and functions.")
+            )
+            .arg(
+                Arg::new("spec-test")
+                    .long("spec-test")
+                    .help("evaluates the spec assertions of the `#[spec_test]` functions of the \
+                    target modules, instead of running the prover.")
+            )
+            .arg(
+                Arg::new("extract-spec-helpers")
+                    .long("extract-spec-helpers")
+                    .help("extracts complex spec fragments which are repeated in a module into \
+                    spec helper functions, rewriting the sources, instead of running the prover.")
+            )
+            .arg(
+                Arg::new("state-model")
+                    .long("state-model")
+                    .takes_value(true)
+                    .value_name("DIR")
+                    .help("writes for each target module a document listing its resources, the \
+                    functions creating, modifying, and destroying them, and the invariants \
+                    constraining them, instead of running the prover.")
+            )
+            .arg(
+                Arg::new("access-matrix")
+                    .long("access-matrix")
+                    .takes_value(true)
+                    .value_name("FILE")
+                    .help("writes a matrix of the resources read, written, created, and destroyed \
+                    by the functions of the target modules, together with their visibility, to \
+                    the file as CSV, or as JSON if the file ends in `.json`, instead of running \
+                    the prover.")
+            )
+            .arg(
+                Arg::new("mock-modules")
+                    .long("mock-modules")
+                    .takes_value(true)
+                    .value_name("DIR")
+                    .help("writes for each target module a mock with the same declarations and \
+                    specifications, whose functions abort and are opaque, instead of running \
+                    the prover.")
+            )
+            .arg(
+                Arg::new("rust-bcs")
+                    .long("rust-bcs")
+                    .takes_value(true)
+                    .value_name("FILE")
+                    .help("writes Rust code for encoding and decoding values of types in BCS \
+                    to the file, instead of running the prover.")
+            )
+            .arg(
+                Arg::new("rust-bcs-type")
+                    .long("rust-bcs-type")
+                    .multiple_occurrences(true)
+                    .number_of_values(1)
+                    .takes_value(true)
+                    .value_name("TYPE")
+                    .help("a fully instantiated type to generate Rust BCS code for, e.g. \
+                    `0x1::Event::EventHandle<u64>`. Defaults to the non-generic structs of \
+                    the target modules.")
+            )
             .arg(
                 Arg::new("read-write-set")
                     .long("read-write-set")
@@ -344,7 +633,8 @@ impl Options {
                 Arg::new("mutation")
                     .long("mutation")
                     .help(
-                        "Specifies to use the mutation pass",
+                        "Specifies to use the mutation pass. If no operation to mutate is \
+                        given, it is selected based on the random seed",
                     ),
             )
             .arg(
@@ -433,6 +723,40 @@ impl Options {
                     .validator(is_number)
                     .help("sets the lazy threshold for quantifier instantiation (default 100)")
             )
+            .arg(
+                Arg::new("override")
+                    .long("override")
+                    .multiple_occurrences(true)
+                    .number_of_values(1)
+                    .takes_value(true)
+                    .value_name("FUN=REPLACEMENT")
+                    .help("replaces calls to a function by calls to another function with the same \
+                    signature during verification, e.g. `--override Hash::sha3_256=HashModel::sha3_256`. \
+                    Can be repeated.")
+            )
+            .arg(
+                Arg::new("equivalence")
+                    .long("equivalence")
+                    .multiple_occurrences(true)
+                    .number_of_values(1)
+                    .takes_value(true)
+                    .value_name("FUN=ORIGINAL")
+                    .help("checks that a function returns the same results and aborts with the \
+                    same codes as another function with the same signature for all inputs which \
+                    satisfy its preconditions, e.g. `--equivalence M::fast_mul=M::mul`. Can be \
+                    repeated.")
+            )
+            .arg(
+                Arg::new("equivalence-bytecode")
+                    .long("equivalence-bytecode")
+                    .multiple_occurrences(true)
+                    .number_of_values(1)
+                    .takes_value(true)
+                    .value_name("PATH_TO_MODULE_BYTECODE")
+                    .help("checks that the functions of the module in the given bytecode file, \
+                    e.g. as deployed on chain, are equivalent with the functions of the same name \
+                    compiled from the sources. Can be repeated.")
+            )
             .arg(
                 Arg::new("dump-bytecode")
                     .long("dump-bytecode")
@@ -444,6 +768,17 @@ impl Options {
                     .requires("dump-bytecode")
                     .help("whether to dump the per-function control-flow graphs (in dot format) to files")
             )
+            .arg(
+                Arg::new("dump-function")
+                    .long("dump-function")
+                    .multiple_occurrences(true)
+                    .number_of_values(1)
+                    .takes_value(true)
+                    .value_name("FUNCTION_NAME")
+                    .requires("dump-bytecode")
+                    .help("restricts the dumped bytecode and control-flow graphs to the given \
+                    function. Can be repeated.")
+            )
             .arg(
                 Arg::new("num-instances")
                     .long("num-instances")
@@ -479,12 +814,114 @@ impl Options {
                     .long("generate-smt")
                     .help("instructs boogie to log smtlib files for verified functions")
             )
+            .arg(
+                Arg::new("boogie-source-comments")
+                    .long("boogie-source-comments")
+                    .help("interleaves the Move source lines and the provenance of verification \
+                    conditions as comments into the generated Boogie code")
+            )
             .arg(
                 Arg::new("experimental-pipeline")
                     .long("experimental-pipeline")
                     .short('e')
                     .help("whether to run experimental pipeline")
             )
+            .arg(
+                Arg::new("pipeline-config")
+                    .long("pipeline-config")
+                    .takes_value(true)
+                    .value_name("TOML_FILE")
+                    .help("configures the processors of the bytecode pipeline, their options, \
+                    and pragma overrides from a toml file")
+            )
+            .arg(
+                Arg::new("max-instructions")
+                    .long("max-instructions")
+                    .takes_value(true)
+                    .value_name("COUNT")
+                    .validator(is_number)
+                    .help("reports an error for functions with more instructions after bytecode \
+                    transformation, naming the processor which added most of them")
+            )
+            .arg(
+                Arg::new("inline-functions")
+                    .long("inline-functions")
+                    .help("inlines calls to functions which declare `pragma inline`, or are \
+                    small enough according to `--inline-threshold`")
+            )
+            .arg(
+                Arg::new("propagate-constants")
+                    .long("propagate-constants")
+                    .help("replaces operations on constants by their results and branches on \
+                    constant conditions by jumps")
+            )
+            .arg(
+                Arg::new("eliminate-dead-code")
+                    .long("eliminate-dead-code")
+                    .help("removes unreachable code and assignments to temporaries which are \
+                    never read")
+            )
+            .arg(
+                Arg::new("unroll-loops")
+                    .long("unroll-loops")
+                    .help("unrolls loops of functions which declare `pragma unroll`, or all \
+                    loops if `--loop-unroll` is given")
+            )
+            .arg(
+                Arg::new("loop-unroll")
+                    .long("loop-unroll")
+                    .takes_value(true)
+                    .value_name("COUNT")
+                    .validator(is_number)
+                    .help("unrolls loops the given number of times instead of requiring loop \
+                    invariants, unless a function or module declares `pragma unroll`. Executions \
+                    which iterate a loop more often are not verified. Implies `--unroll-loops`")
+            )
+            .arg(
+                Arg::new("inline-threshold")
+                    .long("inline-threshold")
+                    .takes_value(true)
+                    .value_name("COUNT")
+                    .validator(is_number)
+                    .help("inlines calls to functions with at most the given number of \
+                    instructions, unless a function or module declares `pragma inline`. \
+                    Implies `--inline-functions`")
+            )
+            .arg(
+                Arg::new("max-locals")
+                    .long("max-locals")
+                    .takes_value(true)
+                    .value_name("COUNT")
+                    .validator(is_number)
+                    .help("reports an error for functions with more locals after bytecode \
+                    transformation, naming the processor which added most of them")
+            )
+            .arg(
+                Arg::new("fix")
+                    .long("fix")
+                    .help("applies the fixes of reported diagnostics to the sources, where a \
+                    fix is unambiguous and safe to apply without review")
+            )
+            .arg(
+                Arg::new("json-diagnostics")
+                    .long("json-diagnostics")
+                    .help("reports diagnostics as JSON objects, one per line, including their \
+                    locations, labels, notes, and fixes")
+            )
+            .arg(
+                Arg::new("sarif")
+                    .long("sarif")
+                    .takes_value(true)
+                    .value_name("FILE")
+                    .help("writes the diagnostics to the file as a SARIF 2.1 log, in addition \
+                    to reporting them")
+            )
+            .arg(
+                Arg::new("pipeline-metrics")
+                    .long("pipeline-metrics")
+                    .help("reports the time and the instructions and locals added or removed \
+                    by each processor of the bytecode pipeline")
+            )
             .arg(
                 Arg::new("weak-edges")
                     .long("weak-edges")
@@ -506,6 +943,31 @@ impl Options {
                     .help("treat functions that do not return (i.e., abort unconditionally) \
                     as inconsistency violations")
             )
+            .arg(
+                Arg::new("smoke-test")
+                    .long("smoke-test")
+                    .help("checks for each function whether its assumptions are contradictory, \
+                    by verifying that `assert false` at the function exits fails, and reports \
+                    the contradicting assumptions")
+            )
+            .arg(
+                Arg::new("explain-paths")
+                    .long("explain-paths")
+                    .help("renders the path to each verification error as source snippets, \
+                    showing the branches taken on the way")
+            )
+            .arg(
+                Arg::new("check-vacuity")
+                    .long("check-vacuity")
+                    .help("checks whether post-conditions or abort conditions hold vacuously")
+            )
+            .arg(
+                Arg::new("split-conditions")
+                    .long("split-conditions")
+                    .help("verifies groups of conditions of a function which do not depend on \
+                    each other in separate verification conditions, and reports the groups \
+                    and dependencies found")
+            )
             .arg(
                 Arg::new("verify-only")
                     .long("verify-only")
@@ -569,6 +1031,22 @@ impl Options {
         if matches.is_present("output") {
             options.output_path = matches.value_of("output").unwrap().to_string();
         }
+        if matches.is_present("manifest") {
+            options.manifest_path = Some(matches.value_of("manifest").unwrap().to_string());
+        }
+        if matches.is_present("artifacts-dir") {
+            options.artifacts_dir = Some(matches.value_of("artifacts-dir").unwrap().to_string());
+        }
+        if matches.is_present("address-aliases") {
+            options.address_aliases =
+                Some(matches.value_of("address-aliases").unwrap().to_string());
+        }
+        if matches.is_present("assume-dependency-specs") {
+            options.model_builder.assume_dependency_specs = true;
+        }
+        if matches.is_present("discharge") {
+            options.discharge_manifest = Some(matches.value_of("discharge").unwrap().to_string());
+        }
         if matches.is_present("verbosity") {
             options.verbosity_level = match matches.value_of("verbosity").unwrap() {
                 "error" => LevelFilter::Error,
@@ -656,6 +1134,16 @@ impl Options {
         if matches.is_present("ignore-pragma-opaque-internal-only") {
             options.model_builder.ignore_pragma_opaque_internal_only = true;
         }
+        if matches.occurrences_of("spec-files") > 0 {
+            options.model_builder.spec_files = get_vec("spec-files");
+        }
+        if matches.is_present("exclude-spec-files") {
+            options.model_builder.exclude_spec_files = true;
+        }
+        if matches.is_present("language-version") {
+            options.model_builder.language_version =
+                LanguageVersion::from_str(matches.value_of("language-version").unwrap())?;
+        }
         if matches.occurrences_of("simplification-pipeline") > 0 {
             for name in get_vec("simplification-pipeline") {
                 let pass = SimplificationPass::from_str(&name)
@@ -685,15 +1173,86 @@ impl Options {
         if matches.is_present("escape") {
             options.run_escape = true;
         }
+        if matches.is_present("spec-coverage") {
+            options.run_spec_coverage = true;
+        }
+        if matches.is_present("abort-paths") {
+            options.run_abort_paths = true;
+        }
+        if matches.is_present("inferred-aborts") {
+            options.run_inferred_aborts = true;
+        }
+        if matches.is_present("spec-var-frames") {
+            options.run_spec_var_frames = true;
+        }
+        if matches.is_present("spec-metrics") {
+            options.run_spec_metrics = true;
+        }
+        if matches.is_present("audit-report") {
+            options.run_audit_report = true;
+        }
+        if matches.is_present("lint") {
+            options.run_lints = true;
+        }
+        if matches.is_present("spec-test") {
+            options.run_spec_tests = true;
+        }
+        if matches.is_present("extract-spec-helpers") {
+            options.run_spec_extraction = true;
+        }
+        if matches.is_present("state-model") {
+            options.state_model_dir = Some(matches.value_of("state-model").unwrap().to_string());
+        }
+        if matches.is_present("access-matrix") {
+            options.access_matrix_output =
+                Some(matches.value_of("access-matrix").unwrap().to_string());
+        }
+        if matches.is_present("mock-modules") {
+            options.mock_dir = Some(matches.value_of("mock-modules").unwrap().to_string());
+        }
+        if matches.is_present("rust-bcs") {
+            options.rust_bcs_output = Some(matches.value_of("rust-bcs").unwrap().to_string());
+        }
+        if matches.occurrences_of("rust-bcs-type") > 0 {
+            options.rust_bcs_types = get_vec("rust-bcs-type");
+        }
         if matches.is_present("trace") {
             options.prover.auto_trace_level = AutoTraceLevel::VerifiedFunction;
         }
         if matches.is_present("dump-bytecode") {
             options.prover.dump_bytecode = true;
         }
+        if matches.is_present("override") {
+            for value in get_vec("override") {
+                let (original, replacement) = value
+                    .split_once('=')
+                    .ok_or_else(|| anyhow!("expected `FUN=REPLACEMENT`, found `{}`", value))?;
+                options
+                    .prover
+                    .function_overrides
+                    .insert(original.to_string(), replacement.to_string());
+            }
+        }
+        if matches.is_present("equivalence") {
+            for value in get_vec("equivalence") {
+                let (fun, original) = value
+                    .split_once('=')
+                    .ok_or_else(|| anyhow!("expected `FUN=ORIGINAL`, found `{}`", value))?;
+                options
+                    .prover
+                    .equivalence_checks
+                    .insert(fun.to_string(), original.to_string());
+            }
+        }
+        if matches.is_present("equivalence-bytecode") {
+            options.equivalence_bytecode = get_vec("equivalence-bytecode");
+        }
         if matches.is_present("dump-cfg") {
             options.prover.dump_cfg = true;
         }
+        if matches.is_present("dump-function") {
+            options.prover.dump_functions = get_vec("dump-function");
+        }
         if matches.is_present("num-instances") {
             let num_instances = matches
                 .value_of("num-instances")
@@ -718,9 +1277,76 @@ impl Options {
         if matches.is_present("seed") {
             options.backend.random_seed = matches.value_of("seed").unwrap().parse::<usize>()?;
         }
+        if matches.is_present("minimize-counterexamples") {
+            options.backend.counterexample_minimization_steps = matches
+                .value_of("minimize-counterexamples")
+                .unwrap()
+                .parse::<usize>()?;
+        }
+        if matches.is_present("report-used-assumptions") {
+            options.backend.report_used_assumptions = true;
+            // The used assumptions are reported as notes.
+            if options.prover.report_severity > Severity::Note {
+                options.prover.report_severity = Severity::Note;
+            }
+        }
         if matches.is_present("experimental-pipeline") {
             options.experimental_pipeline = true;
         }
+        if matches.is_present("pipeline-config") {
+            let file = matches.value_of("pipeline-config").unwrap();
+            options.pipeline = Some(PipelineConfig::from_toml(&std::fs::read_to_string(file)?)?);
+        }
+        if matches.is_present("max-instructions") {
+            options.prover.max_instructions = Some(
+                matches
+                    .value_of("max-instructions")
+                    .unwrap()
+                    .parse::<usize>()?,
+            );
+        }
+        if matches.is_present("inline-functions") {
+            options.prover.inline_functions = true;
+        }
+        if matches.is_present("propagate-constants") {
+            options.prover.propagate_constants = true;
+        }
+        if matches.is_present("eliminate-dead-code") {
+            options.prover.eliminate_dead_code = true;
+        }
+        if matches.is_present("unroll-loops") {
+            options.prover.unroll_loops = true;
+        }
+        if matches.is_present("loop-unroll") {
+            options.prover.unroll_loops = true;
+            options.prover.loop_unroll =
+                Some(matches.value_of("loop-unroll").unwrap().parse::<usize>()?);
+        }
+        if matches.is_present("inline-threshold") {
+            options.prover.inline_functions = true;
+            options.prover.inline_threshold = Some(
+                matches
+                    .value_of("inline-threshold")
+                    .unwrap()
+                    .parse::<usize>()?,
+            );
+        }
+        if matches.is_present("max-locals") {
+            options.prover.max_locals =
+                Some(matches.value_of("max-locals").unwrap().parse::<usize>()?);
+        }
+        if matches.is_present("fix") {
+            options.apply_fixes = true;
+        }
+        if matches.is_present("json-diagnostics") {
+            options.json_diagnostics = true;
+        }
+        if matches.is_present("sarif") {
+            options.sarif_output = Some(matches.value_of("sarif").unwrap().to_string());
+        }
+        if matches.is_present("pipeline-metrics") {
+            options.report_pipeline_metrics = true;
+        }
         if matches.is_present("timeout") {
             options.backend.vc_timeout = matches.value_of("timeout").unwrap().parse::<usize>()?;
         }
@@ -748,6 +1374,9 @@ impl Options {
         if matches.is_present("generate-smt") {
             options.backend.generate_smt = true;
         }
+        if matches.is_present("boogie-source-comments") {
+            options.backend.source_comments = true;
+        }
 
         if matches.is_present("check-inconsistency") {
             options.prover.check_inconsistency = true;
@@ -755,6 +1384,19 @@ impl Options {
         if matches.is_present("unconditional-abort-as-inconsistency") {
             options.prover.unconditional_abort_as_inconsistency = true;
         }
+        if matches.is_present("smoke-test") {
+            options.prover.check_inconsistency = true;
+            options.backend.explain_inconsistencies = true;
+        }
+        if matches.is_present("explain-paths") {
+            options.backend.explain_paths = true;
+        }
+        if matches.is_present("check-vacuity") {
+            options.prover.check_vacuity = true;
+        }
+        if matches.is_present("split-conditions") {
+            options.prover.split_conditions = true;
+        }
 
         if matches.is_present("verify-only") {
             options.prover.verify_scope =
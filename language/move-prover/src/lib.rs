@@ -3,7 +3,10 @@
 
 #![forbid(unsafe_code)]
 
-use crate::cli::Options;
+use crate::{
+    artifacts::FunctionArtifacts, assume_guarantee::ConsistencyReport, cli::Options,
+    manifest::RunManifest,
+};
 use anyhow::anyhow;
 use codespan_reporting::{
     diagnostic::Severity,
@@ -12,22 +15,49 @@ use codespan_reporting::{
 #[allow(unused_imports)]
 use log::{debug, info, warn};
 use move_abigen::Abigen;
+use move_binary_format::CompiledModule;
 use move_compiler::shared::PackagePaths;
 use move_docgen::Docgen;
 use move_errmapgen::ErrmapGen;
 use move_model::{
+    add_renamed_bytecode_module,
+    audit_heuristics::audit_report,
+    bcs_codegen::RustBcsGenerator,
     code_writer::CodeWriter,
+    diag_export::SarifExporter,
+    lint::{lint_report, run_lints},
+    mock_gen::generate_mock_module,
     model::{FunctionVisibility, GlobalEnv},
     parse_addresses_from_options, run_model_builder_with_options,
+    source_edit::SourceEdits,
+    spec_extraction::find_spec_helpers,
+    spec_metrics::spec_metrics_report,
+    spec_test::{run_spec_tests, spec_test_report},
 };
 use move_prover_boogie_backend::{
-    add_prelude, boogie_wrapper::BoogieWrapper, bytecode_translator::BoogieTranslator,
+    add_prelude,
+    boogie_wrapper::{BoogieOutput, BoogieWrapper},
+    bytecode_translator::BoogieTranslator,
+    check_prelude_extensions, check_spec_well_formedness,
 };
 use move_stackless_bytecode::{
+    abort_inference::{inferred_aborts_report, AbortInferenceProcessor},
+    abort_path_analysis::{abort_path_report, AbortPathProcessor},
+    access_matrix::AccessMatrix,
+    condition_splitting::condition_dependencies_report,
+    eliminate_imm_refs::EliminateImmRefsProcessor,
+    equivalence_check::EquivalenceCheckInstrumenter,
     escape_analysis::EscapeAnalysisProcessor,
-    function_target_pipeline::{FunctionTargetPipeline, FunctionTargetsHolder},
-    pipeline_factory,
+    function_target_pipeline::{
+        FunctionTargetPipeline, FunctionTargetProcessor, FunctionTargetsHolder, PipelineMetrics,
+    },
+    mono_analysis, pipeline_factory,
     read_write_set_analysis::{self, ReadWriteSetProcessor},
+    recursion_analysis::RecursionAnalysisProcessor,
+    spec_coverage_analysis::{spec_coverage_report, SpecCoverageProcessor},
+    spec_var_frame_analysis::{spec_var_frame_report, SpecVarFrameProcessor},
+    state_model::state_model_doc,
+    usage_analysis::UsageProcessor,
 };
 use std::{
     collections::BTreeSet,
@@ -36,7 +66,10 @@ use std::{
     time::Instant,
 };
 
+pub mod artifacts;
+pub mod assume_guarantee;
 pub mod cli;
+pub mod manifest;
 
 // =================================================================================================
 // Prover API
@@ -53,12 +86,24 @@ pub fn run_move_prover<W: WriteColor>(
     let now = Instant::now();
     // Run the model builder.
     let addrs = parse_addresses_from_options(options.move_named_address_values.clone())?;
-    let env = run_model_builder_with_options(
-        vec![PackagePaths {
-            name: None,
-            paths: options.move_sources.clone(),
-            named_address_map: addrs.clone(),
-        }],
+    let mut packages = vec![PackagePaths {
+        name: None,
+        paths: options.move_sources.clone(),
+        named_address_map: addrs.clone(),
+    }];
+    for package in &options.workspace_packages {
+        let mut named_address_map = addrs.clone();
+        named_address_map.extend(parse_addresses_from_options(
+            package.named_address_values.clone(),
+        )?);
+        packages.push(PackagePaths {
+            name: Some(package.name.as_str().into()),
+            paths: package.sources.clone(),
+            named_address_map,
+        });
+    }
+    let mut env = run_model_builder_with_options(
+        packages,
         vec![PackagePaths {
             name: None,
             paths: options.move_deps.clone(),
@@ -66,16 +111,87 @@ pub fn run_move_prover<W: WriteColor>(
         }],
         options.model_builder.clone(),
     )?;
-    run_move_prover_with_model(&env, error_writer, options, Some(now))
+    if let Some(config) = &options.pipeline {
+        config.apply_pragmas(&mut env)?;
+    }
+    let mut options = options;
+    if !env.has_errors() {
+        add_bytecode_equivalence_checks(&mut env, &mut options)?;
+    }
+    let sarif_output = options.sarif_output.clone();
+    let severity = options.prover.report_severity;
+    let seed = options.backend.random_seed;
+    let result = run_move_prover_with_model(&env, error_writer, options, Some(now));
+    if let Some(path) = sarif_output {
+        SarifExporter::new(&env, "move-prover")
+            .with_root(std::env::current_dir()?)
+            .with_property("seed", serde_json::json!(seed))
+            .write(Path::new(&path), severity)?;
+    }
+    result
+}
+
+/// Adds the modules in the bytecode files of `equivalence_bytecode` to the environment, and checks
+/// each of their functions to be equivalent with the function of the same name compiled from the
+/// sources. Functions which cannot be checked are reported as warnings.
+fn add_bytecode_equivalence_checks(
+    env: &mut GlobalEnv,
+    options: &mut Options,
+) -> anyhow::Result<()> {
+    for path in &options.equivalence_bytecode {
+        let bytes = fs::read(path)?;
+        let module = CompiledModule::deserialize(&bytes)
+            .map_err(|err| anyhow!("cannot deserialize module in `{}`: {:?}", path, err))?;
+        let source_name = module.self_id();
+        let source_module_id = env
+            .find_module_by_language_storage_id(&source_name)
+            .map(|module_env| module_env.get_id())
+            .ok_or_else(|| anyhow!("cannot find source of module `{}`", source_name))?;
+        let bytecode_module_id =
+            add_renamed_bytecode_module(env, &module, &format!("{}_bytecode", source_name.name()))?;
+        let source_module_env = env.get_module(source_module_id);
+        let bytecode_module_env = env.get_module(bytecode_module_id);
+        for bytecode_fun_env in bytecode_module_env.get_functions() {
+            let fun_env = match source_module_env.find_function(bytecode_fun_env.get_name()) {
+                Some(fun_env) => fun_env,
+                None => {
+                    env.error(
+                        &bytecode_fun_env.get_loc(),
+                        &format!(
+                            "function `{}` has no source",
+                            bytecode_fun_env.get_full_name_str()
+                        ),
+                    );
+                    continue;
+                }
+            };
+            if fun_env.is_native_or_intrinsic() && bytecode_fun_env.is_native_or_intrinsic() {
+                continue;
+            }
+            match EquivalenceCheckInstrumenter::check_pair(&fun_env, &bytecode_fun_env) {
+                Some(reason) => env.diag(Severity::Warning, &fun_env.get_loc(), &reason),
+                None => {
+                    options.prover.equivalence_checks.insert(
+                        fun_env.get_full_name_str(),
+                        bytecode_fun_env.get_full_name_str(),
+                    );
+                }
+            }
+        }
+    }
+    Ok(())
 }
 
 pub fn run_move_prover_with_model<W: WriteColor>(
     env: &GlobalEnv,
     error_writer: &mut W,
-    options: Options,
+    mut options: Options,
     timer: Option<Instant>,
 ) -> anyhow::Result<()> {
     let now = timer.unwrap_or_else(Instant::now);
+    if let Some(path) = &options.address_aliases {
+        env.load_address_aliases(path)?;
+    }
 
     let build_duration = now.elapsed();
     check_errors(
@@ -84,10 +200,15 @@ pub fn run_move_prover_with_model<W: WriteColor>(
         error_writer,
         "exiting with model building errors",
     )?;
-    env.report_diag(error_writer, options.prover.report_severity);
+    report_diag(env, &options, error_writer)?;
 
     // Add the prover options as an extension to the environment, so they can be accessed
-    // from there.
+    // from there. Options given by the pipeline configuration take precedence.
+    if let Some(prover_options) = options.pipeline.as_ref().and_then(|c| c.options.clone()) {
+        options.prover = prover_options;
+    }
+    // All randomized choices derive from the one seed of the backend.
+    options.prover.random_seed = options.backend.random_seed;
     env.set_extension(options.prover.clone());
 
     // Until this point, prover and docgen have same code. Here we part ways.
@@ -119,6 +240,81 @@ pub fn run_move_prover_with_model<W: WriteColor>(
             Ok(())
         };
     }
+    // Same for spec coverage analysis
+    if options.run_spec_coverage {
+        return {
+            run_spec_coverage(env, now);
+            Ok(())
+        };
+    }
+    // Same for abort path analysis
+    if options.run_abort_paths {
+        return {
+            run_abort_paths(env, now);
+            Ok(())
+        };
+    }
+    // Same for abort condition inference
+    if options.run_inferred_aborts {
+        return {
+            run_inferred_aborts(env, now);
+            Ok(())
+        };
+    }
+    // Same for spec variable frame inference
+    if options.run_spec_var_frames {
+        return {
+            run_spec_var_frames(env, now);
+            Ok(())
+        };
+    }
+    // Same for spec metrics
+    if options.run_spec_metrics {
+        println!("{}", spec_metrics_report(env));
+        return Ok(());
+    }
+    // Same for audit heuristics
+    if options.run_audit_report {
+        print!("{}", audit_report(env));
+        return Ok(());
+    }
+    // Same for lints
+    if options.run_lints {
+        let findings = run_lints(env);
+        print!("{}", lint_report(env, &findings));
+        return check_errors(env, &options, error_writer, "exiting with denied lints");
+    }
+    // Same for spec tests
+    if options.run_spec_tests {
+        let results = run_spec_tests(env);
+        print!("{}", spec_test_report(env, &results));
+        return check_errors(
+            env,
+            &options,
+            error_writer,
+            "exiting with failed spec tests",
+        );
+    }
+    // Same for spec helper extraction
+    if options.run_spec_extraction {
+        return run_spec_extraction(env);
+    }
+    // Same for state model documents
+    if let Some(dir) = &options.state_model_dir {
+        return run_state_model(env, dir, now);
+    }
+    // Same for resource access matrices
+    if let Some(path) = &options.access_matrix_output {
+        return run_access_matrix(env, path);
+    }
+    // Same for mock modules
+    if let Some(dir) = &options.mock_dir {
+        return run_mock_gen(env, dir);
+    }
+    // Same for Rust BCS codecs
+    if let Some(path) = &options.rust_bcs_output {
+        return run_rust_bcs_gen(env, &options.rust_bcs_types, path);
+    }
 
     // Check correct backend versions.
     options.backend.check_tool_versions()?;
@@ -128,6 +324,26 @@ pub fn run_move_prover_with_model<W: WriteColor>(
         print_script_reach(env);
     }
 
+    // Per-function verification times are needed for the manifest, and the procedures
+    // verified for the artifacts.
+    if options.manifest_path.is_some() || options.artifacts_dir.is_some() {
+        options.backend.report_procedure_times = true;
+    }
+
+    // Expose the callers of functions as lens entries.
+    env.add_caller_lenses();
+
+    // Check that specs can be translated before transforming them, and that custom prelude
+    // text refers to existing natives.
+    check_spec_well_formedness(env);
+    check_prelude_extensions(env, &options.backend);
+    check_errors(
+        env,
+        &options,
+        error_writer,
+        "exiting with ill-formed specifications",
+    )?;
+
     // Create and process bytecode
     let now = Instant::now();
     let targets = create_and_process_bytecode(&options, env);
@@ -149,15 +365,56 @@ pub fn run_move_prover_with_model<W: WriteColor>(
         error_writer,
         "exiting with condition generation errors",
     )?;
+    let artifacts = options
+        .artifacts_dir
+        .as_ref()
+        .map(|_| FunctionArtifacts::collect(env, &code_writer));
 
     // Verify boogie code.
     let now = Instant::now();
-    verify_boogie(env, &options, &targets, code_writer)?;
+    let output = verify_boogie(env, &options, &targets, code_writer)?;
     let verify_duration = now.elapsed();
 
-    // Report durations.
+    // Write the manifest of the run and the per-function artifacts, and check that the specs
+    // assumed by another run are discharged by this one.
+    if options.manifest_path.is_some()
+        || options.discharge_manifest.is_some()
+        || options.artifacts_dir.is_some()
+    {
+        let manifest = RunManifest::new(
+            env,
+            &options,
+            &targets,
+            output.as_ref(),
+            &[
+                ("build", build_duration),
+                ("trafo", trafo_duration),
+                ("gen", gen_duration),
+                ("verify", verify_duration),
+            ],
+        );
+        if let Some(manifest_path) = &options.manifest_path {
+            manifest.write(manifest_path)?;
+        }
+        if let (Some(artifacts), Some(dir)) = (&artifacts, &options.artifacts_dir) {
+            artifacts.write(env, &targets, &manifest, output.as_ref(), dir)?;
+        }
+        if let Some(assuming_path) = &options.discharge_manifest {
+            let report = ConsistencyReport::new(&RunManifest::read(assuming_path)?, &manifest);
+            info!("specs assumed by `{}`: {}", assuming_path, report);
+            if report.undischarged_count() > 0 {
+                return Err(anyhow!(
+                    "{} specs assumed by `{}` are not discharged",
+                    report.undischarged_count(),
+                    assuming_path
+                ));
+            }
+        }
+    }
+
+    // Report durations, and the seed needed to reproduce the run.
     info!(
-        "{:.3}s build, {:.3}s trafo, {:.3}s gen, {:.3}s verify, total {:.3}s",
+        "{:.3}s build, {:.3}s trafo, {:.3}s gen, {:.3}s verify, total {:.3}s (seed {})",
         build_duration.as_secs_f64(),
         trafo_duration.as_secs_f64(),
         gen_duration.as_secs_f64(),
@@ -165,7 +422,8 @@ pub fn run_move_prover_with_model<W: WriteColor>(
         build_duration.as_secs_f64()
             + trafo_duration.as_secs_f64()
             + gen_duration.as_secs_f64()
-            + verify_duration.as_secs_f64()
+            + verify_duration.as_secs_f64(),
+        options.backend.random_seed
     );
     check_errors(
         env,
@@ -181,7 +439,10 @@ pub fn check_errors<W: WriteColor>(
     error_writer: &mut W,
     msg: &'static str,
 ) -> anyhow::Result<()> {
-    env.report_diag(error_writer, options.prover.report_severity);
+    report_diag(env, options, error_writer)?;
+    if options.apply_fixes {
+        apply_fixes(env)?;
+    }
     if env.has_errors() {
         Err(anyhow!(msg))
     } else {
@@ -189,6 +450,50 @@ pub fn check_errors<W: WriteColor>(
     }
 }
 
+/// Writes the diagnostics reported so far, as text or as JSON, depending on the options.
+fn report_diag<W: WriteColor>(
+    env: &GlobalEnv,
+    options: &Options,
+    error_writer: &mut W,
+) -> anyhow::Result<()> {
+    if options.json_diagnostics {
+        env.report_diag_json(error_writer, options.prover.report_severity)
+    } else {
+        if options.workspace_packages.is_empty() {
+            env.report_diag(error_writer, options.prover.report_severity);
+        } else {
+            env.report_diag_by_package(error_writer, options.prover.report_severity);
+        }
+        Ok(())
+    }
+}
+
+/// The fixes which have been applied to the sources so far.
+struct AppliedFixes(SourceEdits);
+
+/// Applies the preferred fixes of the diagnostics reported so far to the sources. Fixes are
+/// accumulated over calls, so the sources are always rewritten from the text the model has been
+/// built from.
+fn apply_fixes(env: &GlobalEnv) -> anyhow::Result<()> {
+    let fixes = env.take_preferred_fixes();
+    if fixes.is_empty() {
+        return Ok(());
+    }
+    let mut applied = env
+        .get_extension::<AppliedFixes>()
+        .map(|a| a.0.clone())
+        .unwrap_or_default();
+    for fix in fixes {
+        match applied.merge(fix.edits) {
+            Ok(()) => info!("applied fix: {}", fix.title),
+            Err(_) => warn!("skipped fix conflicting with other fixes: {}", fix.title),
+        }
+    }
+    applied.write(env)?;
+    env.set_extension(AppliedFixes(applied));
+    Ok(())
+}
+
 pub fn generate_boogie(
     env: &GlobalEnv,
     options: &Options,
@@ -201,12 +506,14 @@ pub fn generate_boogie(
     Ok(writer)
 }
 
+/// Writes the boogie code and verifies it, unless only generation is requested. Returns the
+/// output of boogie, if it has been called.
 pub fn verify_boogie(
     env: &GlobalEnv,
     options: &Options,
     targets: &FunctionTargetsHolder,
     writer: CodeWriter,
-) -> anyhow::Result<()> {
+) -> anyhow::Result<Option<BoogieOutput>> {
     let output_existed = std::path::Path::new(&options.output_path).exists();
     debug!("writing boogie to `{}`", &options.output_path);
     writer.process_result(|result| fs::write(&options.output_path, result))?;
@@ -217,12 +524,13 @@ pub fn verify_boogie(
             writer: &writer,
             options: &options.backend,
         };
-        boogie.call_boogie_and_verify_output(&options.output_path)?;
+        let output = boogie.call_boogie_and_verify_output(&options.output_path)?;
         if !output_existed && !options.backend.keep_artifacts {
             std::fs::remove_file(&options.output_path).unwrap_or_default();
         }
+        return Ok(Some(output));
     }
-    Ok(())
+    Ok(None)
 }
 
 /// Create bytecode and process it.
@@ -249,11 +557,23 @@ pub fn create_and_process_bytecode(options: &Options, env: &GlobalEnv) -> Functi
         }
     }
 
+    // Check recursion before running the pipeline, such that recursion which is not supported is
+    // reported ahead of any errors in the pipeline.
+    RecursionAnalysisProcessor::new().run(env, &mut targets);
+    if env.has_errors() {
+        return targets;
+    }
+
     // Create processing pipeline and run it.
-    let pipeline = if options.experimental_pipeline {
-        pipeline_factory::experimental_pipeline()
-    } else {
-        pipeline_factory::default_pipeline_with_options(&options.prover)
+    let pipeline = match create_pipeline(options) {
+        Ok(pipeline) => pipeline,
+        Err(err) => {
+            env.error(
+                &env.unknown_loc(),
+                &format!("invalid pipeline configuration: {}", err),
+            );
+            return targets;
+        }
     };
 
     if options.prover.dump_bytecode {
@@ -267,9 +587,47 @@ pub fn create_and_process_bytecode(options: &Options, env: &GlobalEnv) -> Functi
         pipeline.run(env, &mut targets);
     }
 
+    if options.report_pipeline_metrics {
+        info!(
+            "bytecode pipeline metrics:\n{}",
+            PipelineMetrics::get(env).report(env)
+        );
+    }
+    if options.prover.split_conditions {
+        info!(
+            "condition dependencies:\n{}",
+            condition_dependencies_report(env, &targets)
+        );
+    }
+
+    // Report the function overrides which have been applied.
+    let mono_info = mono_analysis::get_info(env);
+    for (caller, original) in &mono_info.applied_substitutions {
+        info!(
+            "calling `{}` instead of `{}` in `{}`",
+            env.get_function(mono_info.substitutions[original])
+                .get_full_name_str(),
+            env.get_function(*original).get_full_name_str(),
+            env.get_function(*caller).get_full_name_str()
+        );
+    }
+
     targets
 }
 
+/// Creates the bytecode pipeline for the options.
+pub fn create_pipeline(options: &Options) -> anyhow::Result<FunctionTargetPipeline> {
+    if let Some(config) = &options.pipeline {
+        FunctionTargetPipeline::from_config(config)
+    } else if options.experimental_pipeline {
+        Ok(pipeline_factory::experimental_pipeline())
+    } else {
+        Ok(pipeline_factory::default_pipeline_with_options(
+            &options.prover,
+        ))
+    }
+}
+
 // Tools using the Move prover top-level driver
 // ============================================
 
@@ -325,7 +683,7 @@ fn run_docgen<W: WriteColor>(
         (generating_elapsed - checking_elapsed).as_secs_f64()
     );
     if env.has_errors() {
-        env.report_diag(error_writer, options.prover.report_severity);
+        report_diag(env, options, error_writer)?;
         Err(anyhow!("exiting with documentation generation errors"))
     } else {
         Ok(())
@@ -423,3 +781,173 @@ fn run_escape(env: &GlobalEnv, options: &Options, now: Instant) {
     println!("{}", String::from_utf8_lossy(&error_writer.into_inner()));
     info!("in ms, analysis took {:.3}", (end - start).as_millis())
 }
+
+/// Extracts the repeated spec fragments of the target modules into spec helper functions and
+/// writes the rewritten sources.
+fn run_spec_extraction(env: &GlobalEnv) -> anyhow::Result<()> {
+    let mut edits = SourceEdits::new();
+    for module_env in env.get_modules().filter(|m| m.is_target()) {
+        for helper in find_spec_helpers(&module_env) {
+            println!(
+                "{}: extracted `{}` from {} fragment(s)",
+                module_env.get_full_name_str(),
+                helper.name,
+                helper.calls.len()
+            );
+            helper
+                .edits(env)
+                .and_then(|e| edits.merge(e))
+                .map_err(|_| anyhow!("conflicting edits for `{}`", helper.name))?;
+        }
+    }
+    edits.write(env)
+}
+
+/// Writes the state model document of each target module into the directory.
+fn run_state_model(env: &GlobalEnv, dir: &str, now: Instant) -> anyhow::Result<()> {
+    let mut targets = FunctionTargetsHolder::default();
+    for module_env in env.get_modules() {
+        for func_env in module_env.get_functions() {
+            targets.add_target(&func_env)
+        }
+    }
+    let mut pipeline = FunctionTargetPipeline::default();
+    pipeline.add_processor(UsageProcessor::new());
+
+    let start = now.elapsed();
+    pipeline.run(env, &mut targets);
+    let end = now.elapsed();
+
+    fs::create_dir_all(dir)?;
+    for module_env in env.get_modules().filter(|m| m.is_target()) {
+        let path = Path::new(dir).join(format!(
+            "{}_state_model.md",
+            module_env.get_name().display(env.symbol_pool())
+        ));
+        fs::write(&path, state_model_doc(&module_env, &targets))?;
+    }
+    info!("in ms, analysis took {:.3}", (end - start).as_millis());
+    Ok(())
+}
+
+/// Writes the resource access matrix of the target modules into the file.
+fn run_access_matrix(env: &GlobalEnv, path: &str) -> anyhow::Result<()> {
+    let mut targets = FunctionTargetsHolder::default();
+    for module_env in env.get_modules() {
+        for func_env in module_env.get_functions() {
+            targets.add_target(&func_env)
+        }
+    }
+    let matrix = AccessMatrix::new(env, &targets);
+    let content = if path.ends_with(".json") {
+        serde_json::to_string_pretty(&matrix)?
+    } else {
+        matrix.to_csv()
+    };
+    fs::write(path, content)?;
+    Ok(())
+}
+
+/// Writes the mock of each target module into the directory.
+fn run_mock_gen(env: &GlobalEnv, dir: &str) -> anyhow::Result<()> {
+    fs::create_dir_all(dir)?;
+    for module_env in env
+        .get_modules()
+        .filter(|m| m.is_target() && !m.is_script_module())
+    {
+        let path = Path::new(dir).join(format!(
+            "{}.move",
+            module_env.get_name().display(env.symbol_pool())
+        ));
+        fs::write(&path, generate_mock_module(env, &module_env)?)?;
+    }
+    Ok(())
+}
+
+fn run_rust_bcs_gen(env: &GlobalEnv, types: &[String], path: &str) -> anyhow::Result<()> {
+    let mut generator = RustBcsGenerator::new(env);
+    if types.is_empty() {
+        generator.add_target_structs()?;
+    }
+    for ty in types {
+        generator.add_type_str(ty)?;
+    }
+    fs::write(path, generator.generate())?;
+    Ok(())
+}
+
+fn run_spec_coverage(env: &GlobalEnv, now: Instant) {
+    let mut targets = FunctionTargetsHolder::default();
+    for module_env in env.get_modules() {
+        for func_env in module_env.get_functions() {
+            targets.add_target(&func_env)
+        }
+    }
+    let mut pipeline = FunctionTargetPipeline::default();
+    pipeline.add_processor(UsageProcessor::new());
+    pipeline.add_processor(SpecCoverageProcessor::new());
+
+    let start = now.elapsed();
+    pipeline.run(env, &mut targets);
+    let end = now.elapsed();
+
+    println!("{}", spec_coverage_report(env, &targets));
+    info!("in ms, analysis took {:.3}", (end - start).as_millis())
+}
+
+fn run_abort_paths(env: &GlobalEnv, now: Instant) {
+    let mut targets = FunctionTargetsHolder::default();
+    for module_env in env.get_modules() {
+        for func_env in module_env.get_functions() {
+            targets.add_target(&func_env)
+        }
+    }
+    let mut pipeline = FunctionTargetPipeline::default();
+    pipeline.add_processor(AbortPathProcessor::new());
+
+    let start = now.elapsed();
+    pipeline.run(env, &mut targets);
+    let end = now.elapsed();
+
+    print!("{}", abort_path_report(env, &targets));
+    info!("in ms, analysis took {:.3}", (end - start).as_millis())
+}
+
+fn run_inferred_aborts(env: &GlobalEnv, now: Instant) {
+    let mut targets = FunctionTargetsHolder::default();
+    for module_env in env.get_modules() {
+        for func_env in module_env.get_functions() {
+            targets.add_target(&func_env)
+        }
+    }
+    let mut pipeline = FunctionTargetPipeline::default();
+    pipeline.add_processor(AbortPathProcessor::new());
+    pipeline.add_processor(AbortInferenceProcessor::new());
+
+    let start = now.elapsed();
+    pipeline.run(env, &mut targets);
+    let end = now.elapsed();
+
+    print!("{}", inferred_aborts_report(env, &targets));
+    info!("in ms, analysis took {:.3}", (end - start).as_millis())
+}
+
+fn run_spec_var_frames(env: &GlobalEnv, now: Instant) {
+    let mut targets = FunctionTargetsHolder::default();
+    for module_env in env.get_modules() {
+        for func_env in module_env.get_functions() {
+            targets.add_target(&func_env)
+        }
+    }
+    let mut pipeline = FunctionTargetPipeline::default();
+    pipeline.add_processor(EliminateImmRefsProcessor::new());
+    pipeline.add_processor(UsageProcessor::new());
+    pipeline.add_processor(SpecVarFrameProcessor::new());
+
+    let start = now.elapsed();
+    pipeline.run(env, &mut targets);
+    let end = now.elapsed();
+
+    print!("{}", spec_var_frame_report(env, &targets));
+    info!("in ms, analysis took {:.3}", (end - start).as_millis())
+}
@@ -0,0 +1,206 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A machine-readable manifest of a prover run. The manifest records everything needed to
+//! identify what has been verified and how: the prover version, the options, seed, and pipeline
+//! used, the hashes of the source files, the pragmas in effect, and the result and verification
+//! time of each function. Manifests can be archived as evidence of verification and compared
+//! across runs.
+
+use crate::{cli::Options, create_pipeline};
+use move_model::{
+    ast::{PropertyBag, PropertyValue},
+    model::GlobalEnv,
+    symbol::SymbolPool,
+};
+use move_prover_boogie_backend::boogie_wrapper::BoogieOutput;
+use move_stackless_bytecode::function_target_pipeline::FunctionTargetsHolder;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    time::Duration,
+};
+
+/// The manifest of a prover run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunManifest {
+    /// The version of the prover.
+    pub prover_version: String,
+    /// The options the prover was run with.
+    pub options: Options,
+    /// The random seed all randomized choices of the run derive from, see `--seed`.
+    pub seed: usize,
+    /// The names of the bytecode processors, in the order they were run.
+    pub pipeline: Vec<String>,
+    /// The source files, with the hashes of their content.
+    pub files: BTreeMap<String, String>,
+    /// The target modules.
+    pub modules: Vec<ModuleManifest>,
+    /// The specifications of dependency functions which have been assumed instead of verified.
+    pub assumed_specs: Vec<AssumedSpec>,
+    /// The duration in seconds of each phase of the run.
+    pub timings: BTreeMap<String, f64>,
+}
+
+/// The manifest of a target module.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleManifest {
+    pub name: String,
+    /// The pragmas set in the module spec.
+    pub pragmas: BTreeMap<String, String>,
+    pub functions: Vec<FunctionManifest>,
+}
+
+/// The manifest of a function in a target module.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionManifest {
+    pub name: String,
+    /// The stable id of the function, see `FunctionEnv::get_stable_id`.
+    pub stable_id: String,
+    /// The hash of the specification of the function, see `FunctionEnv::get_spec_hash`.
+    pub spec_hash: String,
+    /// The pragmas set in the function spec.
+    pub pragmas: BTreeMap<String, String>,
+    pub result: FunctionResult,
+    /// The time in seconds the backend spent verifying the function, if reported.
+    pub verification_secs: Option<f64>,
+}
+
+/// The specification of a dependency function assumed by the run, because the function is
+/// opaque and called from the target modules.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssumedSpec {
+    pub name: String,
+    pub stable_id: String,
+    pub spec_hash: String,
+}
+
+/// The verification result of a function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FunctionResult {
+    /// The function has been verified without errors.
+    Verified,
+    /// Verification reported errors for the function.
+    Failed,
+    /// The function is not verified, e.g. because of `pragma verify = false`.
+    Skipped,
+    /// The backend has not been run, e.g. because only code generation was requested.
+    NotRun,
+}
+
+impl RunManifest {
+    /// Creates the manifest of a run. `output` is the output of the backend, if it has been run.
+    pub fn new(
+        env: &GlobalEnv,
+        options: &Options,
+        targets: &FunctionTargetsHolder,
+        output: Option<&BoogieOutput>,
+        timings: &[(&str, Duration)],
+    ) -> Self {
+        let failed = output.map(|o| o.failed_functions(env)).unwrap_or_default();
+        let fun_times = output.map(|o| o.function_times(env)).unwrap_or_default();
+        let modules = env
+            .get_modules()
+            .filter(|m| m.is_target())
+            .map(|module_env| ModuleManifest {
+                name: module_env.get_full_name_str(),
+                pragmas: pragmas(env.symbol_pool(), &module_env.get_spec().properties),
+                functions: module_env
+                    .get_functions()
+                    .map(|fun_env| {
+                        let verified = targets
+                            .get_target_variants(&fun_env)
+                            .iter()
+                            .any(|v| v.is_verified());
+                        let result = if !verified {
+                            FunctionResult::Skipped
+                        } else if output.is_none() {
+                            FunctionResult::NotRun
+                        } else if failed.contains(&fun_env.get_qualified_id()) {
+                            FunctionResult::Failed
+                        } else {
+                            FunctionResult::Verified
+                        };
+                        FunctionManifest {
+                            name: env.symbol_pool().string(fun_env.get_name()).to_string(),
+                            stable_id: fun_env.get_stable_id().to_string(),
+                            spec_hash: fun_env.get_spec_hash().to_string(),
+                            pragmas: pragmas(env.symbol_pool(), &fun_env.get_spec().properties),
+                            result,
+                            verification_secs: fun_times.get(&fun_env.get_qualified_id()).cloned(),
+                        }
+                    })
+                    .collect(),
+            })
+            .collect();
+        let assumed_specs = env
+            .get_modules()
+            .filter(|m| m.is_target())
+            .flat_map(|m| m.into_functions())
+            .flat_map(|f| f.get_transitive_closure_of_called_functions())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|fun_id| env.get_function(fun_id))
+            .filter(|f| !f.module_env.is_target() && f.is_opaque() && f.get_spec().has_conditions())
+            .map(|f| AssumedSpec {
+                name: format!(
+                    "{}::{}",
+                    f.module_env.get_full_name_str(),
+                    env.symbol_pool().string(f.get_name())
+                ),
+                stable_id: f.get_stable_id().to_string(),
+                spec_hash: f.get_spec_hash().to_string(),
+            })
+            .collect();
+        Self {
+            prover_version: env!("CARGO_PKG_VERSION").to_string(),
+            options: options.clone(),
+            seed: options.backend.random_seed,
+            pipeline: create_pipeline(options)
+                .map(|p| p.processor_names())
+                .unwrap_or_default(),
+            files: env
+                .get_source_file_hashes()
+                .into_iter()
+                .map(|(file, hash)| (file, hash.to_string()))
+                .collect(),
+            modules,
+            assumed_specs,
+            timings: timings
+                .iter()
+                .map(|(phase, duration)| (phase.to_string(), duration.as_secs_f64()))
+                .collect(),
+        }
+    }
+
+    /// Writes the manifest as JSON to the given file.
+    pub fn write(&self, file: &str) -> anyhow::Result<()> {
+        std::fs::write(file, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Reads a manifest from the given JSON file.
+    pub fn read(file: &str) -> anyhow::Result<Self> {
+        Ok(serde_json::from_str(&std::fs::read_to_string(file)?)?)
+    }
+}
+
+/// Returns the pragmas in the property bag as strings.
+fn pragmas(pool: &SymbolPool, properties: &PropertyBag) -> BTreeMap<String, String> {
+    properties
+        .iter()
+        .map(|(name, value)| {
+            let value = match value {
+                PropertyValue::Value(v) => v.to_string(),
+                PropertyValue::Symbol(sym) => pool.string(*sym).to_string(),
+                PropertyValue::QualifiedSymbol(qsym) => format!(
+                    "{}::{}",
+                    qsym.module_name.display(pool),
+                    pool.string(qsym.symbol)
+                ),
+            };
+            (pool.string(*name).to_string(), value)
+        })
+        .collect()
+}
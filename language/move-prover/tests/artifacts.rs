@@ -0,0 +1,72 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for the per-function artifacts of a prover run. Boogie is replaced by a script which
+//! reports the procedures verifying `f` and `g`, and the post-condition of `g` as failed.
+
+mod common;
+
+use move_prover::manifest::{FunctionManifest, FunctionResult};
+use std::fs;
+use tempfile::TempDir;
+
+const MODULE: &str = "
+module 0x42::M {
+    fun f(x: u64): u64 { x }
+
+    fun g(x: u64): u64 { x }
+    spec g { ensures result == x + 1; }
+
+    fun h(x: u64): u64 { x }
+    spec h { pragma verify = false; }
+}
+";
+
+const SCRIPT: &str = "
+echo 'Verifying $42_M_f$verify ...'
+echo '  [0.25 s, solver resource count: 10, 1 proof obligation]  verified'
+echo 'Verifying $42_M_g$verify ...'
+echo '  [0.5 s, solver resource count: 20, 1 proof obligation]  error'
+grep -o 'assert_failed([0-9,]*): post-condition does not hold' \"$BPL\"
+echo 'Boogie program verifier finished with 1 verified, 1 error'";
+
+#[test]
+fn writes_function_artifacts() {
+    let dir = TempDir::new().unwrap();
+    let env = common::build(dir.path(), MODULE);
+    let mut options = common::options(
+        dir.path(),
+        common::boogie_script(dir.path(), "boogie", SCRIPT),
+    );
+    let artifacts_dir = dir.path().join("artifacts");
+    options.artifacts_dir = Some(artifacts_dir.to_string_lossy().to_string());
+    let (result, diags) = common::run(&env, options);
+    assert!(result.is_err(), "{}", diags);
+
+    // The complete program is kept, so the failure can be reproduced.
+    assert_eq!(
+        fs::read_to_string(artifacts_dir.join("output.bpl")).unwrap(),
+        fs::read_to_string(common::output_path(dir.path())).unwrap()
+    );
+
+    let read = |fun: &str, file: &str| {
+        fs::read_to_string(artifacts_dir.join("0x42_M").join(fun).join(file)).unwrap()
+    };
+    for (fun, result, log) in [
+        ("f", FunctionResult::Verified, "verified"),
+        ("g", FunctionResult::Failed, "error"),
+    ] {
+        assert!(read(fun, "bytecode.txt").contains(&format!("fun M::{}(", fun)));
+        assert!(read(fun, "boogie.bpl").contains(&format!("$42_M_{}$verify(", fun)));
+        // The solver log only holds the output for the procedures of the function.
+        let solver_log = read(fun, "solver.log");
+        assert!(solver_log.starts_with(&format!("Verifying $42_M_{}$verify ...\n", fun)));
+        assert!(solver_log.contains(log));
+        assert_eq!(solver_log.matches("Verifying").count(), 1);
+        let manifest: FunctionManifest = serde_json::from_str(&read(fun, "result.json")).unwrap();
+        assert_eq!(manifest.name, fun);
+        assert_eq!(manifest.result, result);
+    }
+    // Functions which are not verified have no artifacts.
+    assert!(!artifacts_dir.join("0x42_M").join("h").exists());
+}
@@ -0,0 +1,127 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for verifying a module against the assumed specs of its dependency, and discharging
+//! them by a separate run on the dependency. Boogie is replaced by a script which verifies
+//! everything.
+
+mod common;
+
+use move_model::options::ModelBuilderOptions;
+use move_prover::{
+    assume_guarantee::{ConsistencyReport, DischargeStatus},
+    manifest::RunManifest,
+};
+use std::path::Path;
+use tempfile::TempDir;
+
+const APP: &str = "
+module 0x42::App {
+    use 0x42::Dep;
+    fun add_two(x: u64): u64 { Dep::inc(Dep::inc(x)) }
+}
+";
+
+const DEP: &str = "
+module 0x42::Dep {
+    public fun inc(x: u64): u64 { x + 1 }
+    spec inc { ensures result == x + 1; }
+
+    public fun id(x: u64): u64 { x }
+}
+";
+
+const VERIFY_ALL: &str = "echo 'Boogie program verifier finished with 1 verified, 0 errors'";
+
+/// Runs the prover on the app, assuming the specs of the dependency, and returns the path of
+/// the manifest.
+fn run_app(dir: &Path) -> String {
+    let env = common::build_with_deps(
+        dir,
+        APP,
+        Some(DEP),
+        ModelBuilderOptions {
+            assume_dependency_specs: true,
+            ..Default::default()
+        },
+    );
+    // The dependency function with a spec is opaque, the other one is not.
+    assert!(env
+        .find_function_by_name_str("Dep::inc")
+        .unwrap()
+        .is_opaque());
+    assert!(!env
+        .find_function_by_name_str("Dep::id")
+        .unwrap()
+        .is_opaque());
+
+    let manifest_path = dir.join("app.json").to_string_lossy().to_string();
+    let mut options = common::options(dir, String::new());
+    options.prover.generate_only = true;
+    options.manifest_path = Some(manifest_path.clone());
+    let (result, diags) = common::run(&env, options);
+    result.unwrap_or_else(|e| panic!("{}: {}", e, diags));
+    manifest_path
+}
+
+/// Runs the prover on the dependency with the given source, discharging the specs assumed by
+/// the manifest.
+fn run_dep(dep: &str, assuming: &str) -> anyhow::Result<()> {
+    let dir = TempDir::new().unwrap();
+    let env = common::build(dir.path(), dep);
+    let mut options = common::options(
+        dir.path(),
+        common::boogie_script(dir.path(), "boogie", VERIFY_ALL),
+    );
+    let manifest_path = dir.path().join("dep.json").to_string_lossy().to_string();
+    options.manifest_path = Some(manifest_path.clone());
+    options.discharge_manifest = Some(assuming.to_string());
+    let result = common::run(&env, options).0;
+
+    let report = ConsistencyReport::new(
+        &RunManifest::read(assuming).unwrap(),
+        &RunManifest::read(&manifest_path).unwrap(),
+    );
+    assert_eq!(report.entries.len(), 1);
+    assert_eq!(
+        report.entries[0].1 == DischargeStatus::Discharged,
+        result.is_ok()
+    );
+    result
+}
+
+#[test]
+fn records_assumed_specs() {
+    let dir = TempDir::new().unwrap();
+    let manifest = RunManifest::read(&run_app(dir.path())).unwrap();
+    let names = manifest
+        .assumed_specs
+        .iter()
+        .map(|s| s.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["0x42::Dep::inc"]);
+}
+
+#[test]
+fn discharges_assumed_specs() {
+    let dir = TempDir::new().unwrap();
+    let assuming = run_app(dir.path());
+    run_dep(DEP, &assuming).unwrap();
+}
+
+#[test]
+fn changed_spec_is_not_discharged() {
+    let dir = TempDir::new().unwrap();
+    let assuming = run_app(dir.path());
+    let changed = DEP.replace("ensures result == x + 1;", "ensures result > x;");
+    let err = run_dep(&changed, &assuming).unwrap_err();
+    assert!(err.to_string().contains("1 specs assumed by"), "{}", err);
+}
+
+#[test]
+fn unverified_spec_is_not_discharged() {
+    let dir = TempDir::new().unwrap();
+    let assuming = run_app(dir.path());
+    let unverified = DEP.replace("spec inc {", "spec inc { pragma verify = false;");
+    assert!(run_dep(&unverified, &assuming).is_err());
+}
@@ -0,0 +1,113 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Helpers shared by the tests which run the prover on a module given as source text, with
+//! Boogie replaced by a shell script. Not every test uses every helper.
+#![allow(dead_code)]
+
+use codespan_reporting::term::termcolor::Buffer;
+use move_compiler::shared::PackagePaths;
+use move_model::{model::GlobalEnv, options::ModelBuilderOptions, run_model_builder_with_options};
+use move_prover::{cli::Options, run_move_prover_with_model};
+use std::{
+    collections::BTreeMap,
+    fs,
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+};
+
+/// The version reported by the Boogie script.
+const BOOGIE_VERSION: &str = "Boogie program verifier version 2.9.6";
+
+/// Builds a model from the source text, written to the directory, which must not contain
+/// errors.
+pub fn build(dir: &Path, source: &str) -> GlobalEnv {
+    build_with_deps(dir, source, None, ModelBuilderOptions::default())
+}
+
+/// Builds a model from the source text and the source text of its dependencies, written to the
+/// directory, which must not contain errors.
+pub fn build_with_deps(
+    dir: &Path,
+    source: &str,
+    deps: Option<&str>,
+    options: ModelBuilderOptions,
+) -> GlobalEnv {
+    let path = dir.join("sources.move");
+    fs::write(&path, source).unwrap();
+    let deps = deps
+        .map(|deps| {
+            let path = dir.join("deps.move");
+            fs::write(&path, deps).unwrap();
+            vec![package(&path)]
+        })
+        .unwrap_or_default();
+    let env = run_model_builder_with_options(vec![package(&path)], deps, options).unwrap();
+    assert!(!env.has_errors());
+    env
+}
+
+/// Builds a model from the source text, written to the directory, which may contain errors.
+pub fn build_from_source(dir: &Path, source: &str) -> GlobalEnv {
+    let path = dir.join("sources.move");
+    fs::write(&path, source).unwrap();
+    run_model_builder_with_options(vec![package(&path)], vec![], Default::default()).unwrap()
+}
+
+/// Returns a package without named addresses which consists of the source file.
+fn package(path: &Path) -> PackagePaths<String, String> {
+    PackagePaths {
+        name: None,
+        paths: vec![path.to_string_lossy().to_string()],
+        named_address_map: BTreeMap::new(),
+    }
+}
+
+/// Writes a script into the directory which stands in for Boogie. The script answers the
+/// version check, and otherwise runs the given shell commands, with the arguments of the call
+/// in `$@` and the Boogie file in `$BPL`. Returns the path of the script.
+pub fn boogie_script(dir: &Path, name: &str, commands: &str) -> String {
+    let path = dir.join(name);
+    fs::write(
+        &path,
+        format!(
+            "#!/bin/sh\n\
+            if [ \"$1\" = \"-version\" ]; then echo \"{}\"; exit 0; fi\n\
+            for BPL; do :; done\n\
+            {}\n",
+            BOOGIE_VERSION, commands
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    path.to_string_lossy().to_string()
+}
+
+/// Returns options which call the Boogie script, writing the generated Boogie file into the
+/// directory, where it is kept. No solver is configured, as the script does not call one.
+pub fn options(dir: &Path, boogie_exe: String) -> Options {
+    let mut options = Options {
+        output_path: output_path(dir).to_string_lossy().to_string(),
+        ..Default::default()
+    };
+    options.backend.boogie_exe = boogie_exe;
+    options.backend.z3_exe = String::new();
+    options.backend.cvc5_exe = String::new();
+    options.backend.keep_artifacts = true;
+    options
+}
+
+/// Returns the path of the Boogie file generated with the options of this module.
+pub fn output_path(dir: &Path) -> PathBuf {
+    dir.join("output.bpl")
+}
+
+/// Runs the prover on the environment, returning the result and the reported diagnostics.
+pub fn run(env: &GlobalEnv, options: Options) -> (anyhow::Result<()>, String) {
+    let mut error_writer = Buffer::no_color();
+    let result = run_move_prover_with_model(env, &mut error_writer, options, None);
+    (
+        result,
+        String::from_utf8_lossy(&error_writer.into_inner()).to_string(),
+    )
+}
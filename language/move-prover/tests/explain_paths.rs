@@ -0,0 +1,83 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for rendering the path to a verification error as source snippets. Boogie is replaced
+//! by a script which reports the post-condition of `g` as failed, with an execution trace which
+//! visits the locations of the verified procedure in the order of the Boogie file, except for
+//! those in the then-branch.
+
+mod common;
+
+use tempfile::TempDir;
+
+const MODULE: &str = "
+module 0x42::M {
+    fun g(x: u64): u64 {
+        if (x > 10) {
+            x - 1
+        } else {
+            x + 1
+        }
+    }
+    spec g { ensures result == x + 1; }
+}
+";
+
+/// Returns the script, which skips the locations starting within the then-branch.
+fn script() -> String {
+    let start = MODULE.find("x - 1").unwrap();
+    let end = start + "x - 1".len();
+    format!(
+        "
+grep -o 'assert_failed([0-9,]*): post-condition does not hold' \"$BPL\"
+echo 'Augmented execution trace:'
+awk '/^procedure .*[$]42_M_g[$]verify/ {{ p = 1 }} p' \"$BPL\" | grep -o '[$]at([0-9,]*)' \\
+    | awk -F '[(,)]' '$3 < {} || $3 >= {}'
+echo 'Boogie program verifier finished with 0 verified, 1 error'",
+        start, end
+    )
+}
+
+/// Runs the prover, with or without explaining paths, returning the diagnostics.
+fn run(explain_paths: bool) -> String {
+    let dir = TempDir::new().unwrap();
+    let env = common::build(dir.path(), MODULE);
+    let mut options = common::options(
+        dir.path(),
+        common::boogie_script(dir.path(), "boogie", &script()),
+    );
+    options.backend.explain_paths = explain_paths;
+    let (result, diags) = common::run(&env, options);
+    assert!(result.is_err());
+    assert!(diags.contains("post-condition does not hold"), "{}", diags);
+    diags
+}
+
+#[test]
+fn renders_path() {
+    let diags = run(true);
+    let path = diags
+        .lines()
+        .skip_while(|line| !line.ends_with("Path:"))
+        .skip(1)
+        .map(|line| line.rsplit(".move:").next().unwrap())
+        .collect::<Vec<_>>();
+    // The branch is shown with its outcome, followed by the else-branch.
+    assert_eq!(
+        &path[..3],
+        &[
+            "3: fun g(x: u64): u64 {",
+            "4: if (x > 10) {  // condition is false",
+            "7: x + 1",
+        ],
+        "{}",
+        diags
+    );
+    assert!(!path.iter().any(|step| step.starts_with("5:")), "{}", diags);
+}
+
+#[test]
+fn no_path_by_default() {
+    let diags = run(false);
+    assert!(!diags.contains("Path:"), "{}", diags);
+}
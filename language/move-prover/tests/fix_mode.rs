@@ -0,0 +1,54 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for applying the fixes of reported diagnostics to the sources.
+
+mod common;
+
+use std::fs;
+use tempfile::TempDir;
+
+const MODULE: &str = "module 0x42::M {
+    const LIMIT: u64 = 10;
+
+    fun f(a1: u64, a3: u64): u64 { a1 + a3 }
+    spec f {
+        ensures result <= LIMT;
+        ensures result >= a2;
+    }
+}
+";
+
+/// Runs the prover on the module, returning the diagnostics and the sources after the run.
+fn run(apply_fixes: bool) -> (String, String) {
+    let dir = TempDir::new().unwrap();
+    let env = common::build_from_source(dir.path(), MODULE);
+    assert!(env.has_errors());
+    let mut options = common::options(dir.path(), String::new());
+    options.apply_fixes = apply_fixes;
+    let (result, diags) = common::run(&env, options);
+    assert!(result.is_err());
+    let source = fs::read_to_string(dir.path().join("sources.move")).unwrap();
+    (diags, source)
+}
+
+#[test]
+fn applies_unambiguous_fixes() {
+    let (diags, source) = run(true);
+    // The diagnostics are still reported.
+    assert!(diags.contains("undeclared `M::LIMT`"), "{}", diags);
+    assert!(diags.contains("undeclared `M::a2`"), "{}", diags);
+    // Only the name with a single suggestion is replaced.
+    assert_eq!(
+        source,
+        MODULE.replace("result <= LIMT", "result <= LIMIT"),
+        "{}",
+        source
+    );
+}
+
+#[test]
+fn keeps_sources_by_default() {
+    let (_, source) = run(false);
+    assert_eq!(source, MODULE);
+}
@@ -0,0 +1,99 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for the lens entries attached to functions by a prover run. Boogie is replaced by a
+//! script which reports the post-condition of `g` as failed.
+
+mod common;
+
+use move_model::model::{GlobalEnv, LensEntry, LensKind};
+use tempfile::TempDir;
+
+const MODULE: &str = "
+module 0x42::M {
+    struct R has key { x: u64 }
+
+    fun f(s: &signer) { move_to(s, R { x: g(1) }) }
+
+    fun g(x: u64): u64 { x }
+    spec g { ensures result == x + 1; }
+
+    fun h(x: u64): u64 { x }
+    spec h { pragma verify = false; }
+}
+";
+
+const SCRIPT: &str = "
+grep -o 'assert_failed([0-9,]*): post-condition does not hold' \"$BPL\"
+echo 'Boogie program verifier finished with 1 verified, 1 error'";
+
+/// Returns the title of the lens entry of the kind attached to the function, if any.
+fn lens(env: &GlobalEnv, fun_name: &str, kind: LensKind) -> Option<String> {
+    let fun_env = env.find_function_by_name_str(fun_name).unwrap();
+    env.get_lenses(&fun_env.get_loc())
+        .into_iter()
+        .find(|e| e.kind == kind)
+        .map(|e| e.title)
+}
+
+#[test]
+fn lenses_of_prover_run() {
+    let dir = TempDir::new().unwrap();
+    let env = common::build(dir.path(), MODULE);
+    let options = common::options(
+        dir.path(),
+        common::boogie_script(dir.path(), "boogie", SCRIPT),
+    );
+    let (result, _) = common::run(&env, options);
+    assert!(result.is_err());
+
+    use LensKind::*;
+    assert_eq!(lens(&env, "M::f", VerificationStatus).unwrap(), "verified");
+    assert_eq!(
+        lens(&env, "M::g", VerificationStatus).unwrap(),
+        "verification failed"
+    );
+    assert_eq!(lens(&env, "M::h", VerificationStatus), None);
+    assert_eq!(lens(&env, "M::f", WriteSet).unwrap(), "writes M::R");
+    assert_eq!(
+        lens(&env, "M::g", WriteSet).unwrap(),
+        "writes no global memory"
+    );
+    assert_eq!(lens(&env, "M::f", Callers).unwrap(), "0 caller(s)");
+    assert_eq!(lens(&env, "M::g", Callers).unwrap(), "1 caller(s)");
+
+    // All entries are listed by file, and can be cleared by kind.
+    let file_id = env
+        .find_function_by_name_str("M::f")
+        .unwrap()
+        .get_loc()
+        .file_id();
+    let count = env.get_lenses_in_file(file_id).len();
+    env.clear_lenses(&Callers);
+    assert_eq!(env.get_lenses_in_file(file_id).len(), count - 3);
+    assert_eq!(lens(&env, "M::g", Callers), None);
+}
+
+#[test]
+fn lens_entry_replaces_same_kind() {
+    let dir = TempDir::new().unwrap();
+    let env = common::build(dir.path(), MODULE);
+    let loc = env.find_function_by_name_str("M::h").unwrap().get_loc();
+    env.add_lens(
+        loc.clone(),
+        LensEntry::new(LensKind::Other("a".into()), "1"),
+    );
+    env.add_lens(
+        loc.clone(),
+        LensEntry::new(LensKind::Other("b".into()), "2"),
+    );
+    env.add_lens(
+        loc.clone(),
+        LensEntry::new(LensKind::Other("a".into()), "3").with_detail("replaced"),
+    );
+    let entries = env.get_lenses(&loc);
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].title, "2");
+    assert_eq!(entries[1].title, "3");
+    assert_eq!(entries[1].detail.as_deref(), Some("replaced"));
+}
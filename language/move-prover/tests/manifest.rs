@@ -0,0 +1,81 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for the manifest of a prover run. Boogie is replaced by a script which reports the
+//! times of the procedures verifying `f` and `g`, and the post-condition of `g` as failed.
+
+mod common;
+
+use move_prover::manifest::{FunctionResult, RunManifest};
+use std::collections::BTreeMap;
+use tempfile::TempDir;
+
+const MODULE: &str = "
+module 0x42::M {
+    fun f(x: u64): u64 { x }
+
+    fun g(x: u64): u64 { x }
+    spec g { ensures result == x + 1; }
+
+    fun h(x: u64): u64 { x }
+    spec h { pragma verify = false; }
+}
+";
+
+const SCRIPT: &str = "
+echo 'Verifying $42_M_f$verify ...'
+echo '  [0.25 s, solver resource count: 10, 1 proof obligation]  verified'
+echo 'Verifying $42_M_g$verify ...'
+echo '  [0.5 s, solver resource count: 20, 1 proof obligation]  error'
+grep -o 'assert_failed([0-9,]*): post-condition does not hold' \"$BPL\"
+echo 'Boogie program verifier finished with 1 verified, 1 error'";
+
+/// Runs the prover writing a manifest, returning the results of the functions of the module.
+fn run_with_manifest(generate_only: bool) -> (RunManifest, BTreeMap<String, FunctionResult>) {
+    let dir = TempDir::new().unwrap();
+    let env = common::build(dir.path(), MODULE);
+    let mut options = common::options(
+        dir.path(),
+        common::boogie_script(dir.path(), "boogie", SCRIPT),
+    );
+    let manifest_path = dir.path().join("manifest.json");
+    options.manifest_path = Some(manifest_path.to_string_lossy().to_string());
+    options.prover.generate_only = generate_only;
+    let (result, diags) = common::run(&env, options);
+    assert_eq!(result.is_err(), !generate_only, "{}", diags);
+
+    let manifest = RunManifest::read(&manifest_path.to_string_lossy()).unwrap();
+    assert_eq!(manifest.modules.len(), 1);
+    let results = manifest.modules[0]
+        .functions
+        .iter()
+        .map(|f| (f.name.clone(), f.result))
+        .collect();
+    (manifest, results)
+}
+
+#[test]
+fn records_function_results() {
+    let (manifest, results) = run_with_manifest(false);
+    assert_eq!(results["f"], FunctionResult::Verified);
+    assert_eq!(results["g"], FunctionResult::Failed);
+    assert_eq!(results["h"], FunctionResult::Skipped);
+
+    let functions = &manifest.modules[0].functions;
+    assert_eq!(functions[0].verification_secs, Some(0.25));
+    assert_eq!(functions[1].verification_secs, Some(0.5));
+    assert_eq!(functions[2].verification_secs, None);
+    assert_eq!(functions[2].pragmas["verify"], "false");
+    // The run can be identified by its sources, seed and pipeline.
+    assert_eq!(manifest.files.len(), 1);
+    assert_eq!(manifest.seed, manifest.options.backend.random_seed);
+    assert!(!manifest.pipeline.is_empty());
+}
+
+#[test]
+fn records_functions_not_run() {
+    let (_, results) = run_with_manifest(true);
+    assert_eq!(results["f"], FunctionResult::NotRun);
+    assert_eq!(results["g"], FunctionResult::NotRun);
+    assert_eq!(results["h"], FunctionResult::Skipped);
+}
@@ -0,0 +1,72 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for running a portfolio of solver configurations. Boogie is replaced by a script which
+//! times out for a configuration with the flag `-slow`, and otherwise verifies the file after a
+//! delay, provided it is called with the random seed 7.
+
+mod common;
+
+use move_prover_boogie_backend::options::SolverConfig;
+use tempfile::TempDir;
+
+const MODULE: &str = "
+module 0x42::M {
+    fun f(x: u64): u64 { x }
+    spec f { ensures result == x; }
+}
+";
+
+const SCRIPT: &str = "
+case \"$*\" in
+    *-slow*) echo \"$BPL(1,1): Verification of \\$42_M_f\\$verify timed out\";;
+    *smt.random_seed=7*) sleep 1;;
+    *) exit 1;;
+esac
+echo \"Boogie program verifier finished with 1 verified, 0 errors\"";
+
+/// Runs the prover with the portfolio, returning the reported diagnostics.
+fn run_portfolio(portfolio: Vec<SolverConfig>) -> (anyhow::Result<()>, String) {
+    let dir = TempDir::new().unwrap();
+    let env = common::build(dir.path(), MODULE);
+    let mut options = common::options(
+        dir.path(),
+        common::boogie_script(dir.path(), "boogie", SCRIPT),
+    );
+    options.backend.portfolio = portfolio;
+    common::run(&env, options)
+}
+
+fn slow() -> SolverConfig {
+    SolverConfig {
+        boogie_flags: vec!["-slow".to_string()],
+        ..Default::default()
+    }
+}
+
+fn seeded() -> SolverConfig {
+    SolverConfig {
+        random_seed: Some(7),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn takes_conclusive_result() {
+    // The slow configuration finishes first, but its timeout is not conclusive, so the result
+    // of the seeded configuration is awaited.
+    let (result, diags) = run_portfolio(vec![slow(), seeded()]);
+    result.unwrap();
+    assert!(!diags.contains("timeout"), "{}", diags);
+}
+
+#[test]
+fn reports_timeout_of_last_configuration() {
+    let (result, diags) = run_portfolio(vec![slow()]);
+    assert!(result.is_err());
+    assert!(
+        diags.contains("verification out of resources/timeout"),
+        "{}",
+        diags
+    );
+}
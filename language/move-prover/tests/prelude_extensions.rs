@@ -0,0 +1,100 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for custom prelude text registered for native modules and spec functions. Boogie is
+//! replaced by a script which accepts any file.
+
+mod common;
+
+use tempfile::TempDir;
+
+const MODULE: &str = "
+module 0x42::Hash {
+    native public fun hash<T>(x: &T): u64;
+
+    spec fun spec_hash<T>(x: T): u64;
+
+    spec fun defined(x: u64): u64 { x }
+
+    public fun hash_u64(x: u64): u64 { hash(&x) }
+    spec hash_u64 {
+        ensures result == spec_hash(x);
+    }
+}
+
+module 0x42::Plain {
+    public fun f(): u64 { 1 }
+}
+";
+
+const VERIFIED: &str = "echo \"Boogie program verifier finished with 1 verified, 0 errors\"";
+
+const MODULE_PRELUDE: &str = "{% for instance in instances %}
+procedure {:inline 1} $42_Hash_hash'{{instance.suffix}}'(x: {{instance.name}}) returns (res: int);
+{% endfor %}";
+
+const SPEC_FUN_PRELUDE: &str = "{% for name in spec_fun_names %}
+axiom (forall x: int :: {{name}}(x) <= $MAX_U64);
+{% endfor %}";
+
+#[test]
+fn prelude_extensions() {
+    let dir = TempDir::new().unwrap();
+    let env = common::build(dir.path(), MODULE);
+    let mut options = common::options(
+        dir.path(),
+        common::boogie_script(dir.path(), "boogie", VERIFIED),
+    );
+    options
+        .backend
+        .add_module_prelude("0x42::Hash", MODULE_PRELUDE);
+    options
+        .backend
+        .add_spec_fun_prelude("0x42::Hash::spec_hash", SPEC_FUN_PRELUDE);
+    let (result, diags) = common::run(&env, options);
+    result.unwrap_or_else(|e| panic!("{}: {}", e, diags));
+
+    // The templates are expanded with the instances used by the verified code, after the
+    // prelude.
+    let boogie = std::fs::read_to_string(common::output_path(dir.path())).unwrap();
+    let extensions = boogie
+        .split("// ** Prelude extensions")
+        .nth(1)
+        .expect("prelude extensions");
+    assert!(extensions.contains("// Extension for module 0x42::Hash"));
+    assert!(
+        extensions.contains("procedure {:inline 1} $42_Hash_hash'u64'(x: int) returns (res: int);")
+    );
+    assert!(extensions.contains("// Extension for spec function 0x42::Hash::spec_hash"));
+    assert!(extensions.contains("axiom (forall x: int :: $42_Hash_spec_hash'u64'(x) <= $MAX_U64);"));
+}
+
+#[test]
+fn invalid_prelude_extensions() {
+    let dir = TempDir::new().unwrap();
+    let env = common::build(dir.path(), MODULE);
+    let mut options = common::options(
+        dir.path(),
+        common::boogie_script(dir.path(), "boogie", VERIFIED),
+    );
+    options.backend.add_module_prelude("0x42::Unknown", "");
+    options.backend.add_module_prelude("0x42::Plain", "");
+    options
+        .backend
+        .add_spec_fun_prelude("0x42::Hash::unknown", "");
+    options
+        .backend
+        .add_spec_fun_prelude("0x42::Hash::defined", "");
+    let (result, diags) = common::run(&env, options);
+    assert!(result.is_err());
+    for message in [
+        "prelude extension for unknown module `0x42::Unknown`",
+        "prelude extension for module `0x42::Plain` which has no native functions",
+        "prelude extension for unknown spec function `0x42::Hash::unknown`",
+        "prelude extension for spec function `0x42::Hash::defined` which has a definition",
+    ] {
+        assert!(diags.contains(message), "{}", diags);
+    }
+    // Boogie is not run.
+    assert!(!common::output_path(dir.path()).exists());
+}
@@ -0,0 +1,67 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for the smoke test, which explains inconsistencies by the assumptions they are proved
+//! from. Boogie is replaced by a script which reports each inconsistency check as verified, and
+//! each labelled assumption of the Boogie file as necessary.
+
+mod common;
+
+use tempfile::TempDir;
+
+const MODULE: &str = "
+module 0x42::M {
+    fun f(x: u64): u64 { x }
+    spec f {
+        requires x > 10;
+        requires x < 5;
+    }
+}
+";
+
+const REPORT_INCONSISTENT: &str = "
+grep -o 'inconsistency_detected([0-9,]*)' \"$BPL\"
+case \"$*\" in *-printNecessaryAssumes*)
+    echo \"Necessary assume command(s): $(grep -o 'inconsistent_assume:[0-9:]*' \"$BPL\" | paste -sd, -)\";;
+esac
+echo \"Boogie program verifier finished with 2 verified, 0 errors\"";
+
+/// Runs the inconsistency check, with or without explanation, returning the diagnostics.
+fn check(explain: bool) -> String {
+    let dir = TempDir::new().unwrap();
+    let env = common::build(dir.path(), MODULE);
+    let mut options = common::options(
+        dir.path(),
+        common::boogie_script(dir.path(), "boogie", REPORT_INCONSISTENT),
+    );
+    options.prover.check_inconsistency = true;
+    options.backend.explain_inconsistencies = explain;
+    let (result, diags) = common::run(&env, options);
+    assert!(result.is_err());
+    assert!(
+        diags.contains("there is an inconsistent assumption in the function"),
+        "{}",
+        diags
+    );
+    diags
+}
+
+#[test]
+fn explains_inconsistency() {
+    let diags = check(true);
+    // Both preconditions are pointed to, as they contradict each other only together.
+    for condition in ["requires x > 10;", "requires x < 5;"] {
+        let label = diags
+            .lines()
+            .skip_while(|line| !line.ends_with(condition))
+            .nth(1)
+            .unwrap_or_default();
+        assert!(label.ends_with("contradicting assumption"), "{}", diags);
+    }
+}
+
+#[test]
+fn no_explanation_by_default() {
+    let diags = check(false);
+    assert!(!diags.contains("contradicting assumption"), "{}", diags);
+}
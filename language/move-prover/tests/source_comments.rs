@@ -0,0 +1,57 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for interleaving source lines and the provenance of verification conditions into the
+//! generated Boogie code. Boogie is replaced by a script which accepts any file.
+
+mod common;
+
+use tempfile::TempDir;
+
+const MODULE: &str = "
+module 0x42::M {
+    fun inc(x: u64): u64 {
+        let y = x + 1;
+        y
+    }
+    spec inc {
+        ensures result == x + 1;
+    }
+}
+";
+
+const VERIFIED: &str = "echo \"Boogie program verifier finished with 1 verified, 0 errors\"";
+
+/// Runs the prover with or without source comments and returns the generated Boogie code.
+fn boogie(source_comments: bool) -> String {
+    let dir = TempDir::new().unwrap();
+    let env = common::build(dir.path(), MODULE);
+    let mut options = common::options(
+        dir.path(),
+        common::boogie_script(dir.path(), "boogie", VERIFIED),
+    );
+    options.backend.source_comments = source_comments;
+    let (result, diags) = common::run(&env, options);
+    result.unwrap_or_else(|e| panic!("{}: {}", e, diags));
+    std::fs::read_to_string(common::output_path(dir.path())).unwrap()
+}
+
+#[test]
+fn source_comments() {
+    let boogie = boogie(true);
+    // Each source line is printed in full once before the code for it, not for every
+    // instruction.
+    assert!(boogie.contains("// >> fun inc(x: u64): u64 {\n"));
+    assert_eq!(boogie.matches("// >> let y = x + 1;\n").count(), 1);
+    // Verification conditions are followed by their provenance.
+    assert!(boogie.contains(
+        "// >> ensures result == x + 1;\n    // condition: post-condition does not hold\n"
+    ));
+}
+
+#[test]
+fn no_source_comments_by_default() {
+    let boogie = boogie(false);
+    assert!(!boogie.contains("// >> "));
+    assert!(!boogie.contains("// condition: "));
+}
@@ -0,0 +1,32 @@
+// This file contains tests for choices over the elements of a vector.
+module 0x42::TestChoiceVector {
+    use Std::Vector;
+
+    spec module {
+        fun some_even(v: vector<u64>): u64 {
+            choose x in v where x % 2 == 0
+        }
+    }
+
+    fun evens(): vector<u64> {
+        let v = Vector::empty<u64>();
+        Vector::push_back(&mut v, 3);
+        Vector::push_back(&mut v, 4);
+        Vector::push_back(&mut v, 8);
+        v
+    }
+    spec evens {
+        ensures some_even(result) == 4 || some_even(result) == 8;
+        ensures (choose min x in result where x % 2 == 0) == 4;
+        ensures (choose x in result where x < 4) == 3;
+    }
+
+    fun append_even(v: &mut vector<u64>) {
+        Vector::push_back(v, 2);
+    }
+    spec append_even {
+        // The chosen element is an element of the vector satisfying the condition.
+        ensures contains(v, some_even(v));
+        ensures some_even(v) % 2 == 0;
+    }
+}
@@ -0,0 +1,27 @@
+// flag: --equivalence=0x42::TestEquivalence::average=0x42::TestEquivalence::average_naive
+// flag: --equivalence=0x42::TestEquivalence::double=0x42::TestEquivalence::double_naive
+// This file contains test cases for checking that refactored functions are equivalent to their
+// original versions.
+module 0x42::TestEquivalence {
+
+    fun average_naive(x: u64, y: u64): u64 {
+        (x + y) / 2
+    }
+
+    // Only equivalent to the original version if the sum does not overflow.
+    fun average(x: u64, y: u64): u64 {
+        if (x <= y) x + (y - x) / 2 else y + (x - y) / 2
+    }
+    spec average {
+        requires x + y <= MAX_U64;
+    }
+
+    fun double_naive(x: u64): u64 {
+        x * 2
+    }
+
+    // Aborts on overflow just like the original version.
+    fun double(x: u64): u64 {
+        x + x
+    }
+}
@@ -0,0 +1,21 @@
+// no-boogie-test
+// flag: --equivalence-bytecode=tests/sources/functional/equivalence_bytecode.mv
+// This file contains test cases for checking that the bytecode of a module, e.g. as deployed on
+// chain, has been compiled from its sources. The bytecode has been compiled from:
+//
+//     module 0x42::TestEquivalenceBytecode {
+//         public fun double(x: u64): u64 { x + x }
+//         public fun scale(x: u128): u128 { x * 10 }
+//     }
+module 0x42::TestEquivalenceBytecode {
+
+    // Equivalent to the deployed version, which aborts on overflow just like this one.
+    public fun double(x: u64): u64 {
+        x * 2
+    }
+
+    // Not equivalent to the deployed version, whose signature differs.
+    public fun scale(x: u64): u64 {
+        x * 10
+    }
+}
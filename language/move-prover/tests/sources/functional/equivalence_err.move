@@ -0,0 +1,30 @@
+// no-boogie-test
+// flag: --equivalence=0x42::TestEquivalenceErr::withdraw=0x42::TestEquivalenceErr::withdraw_naive
+// flag: --equivalence=0x42::TestEquivalenceErr::balance=0x42::TestEquivalenceErr::balance_naive
+// This file contains test cases for functions which cannot be checked for equivalence.
+module 0x42::TestEquivalenceErr {
+
+    struct Balance has key { value: u64 }
+
+    // Functions which modify global memory are rejected, as the second call would start from
+    // the memory left by the first one.
+    fun withdraw_naive(addr: address, amount: u64) acquires Balance {
+        let b = borrow_global_mut<Balance>(addr);
+        b.value = b.value - amount;
+    }
+
+    fun withdraw(addr: address, amount: u64) acquires Balance {
+        let b = &mut borrow_global_mut<Balance>(addr).value;
+        *b = *b - amount;
+    }
+
+    // Functions which only read global memory can be checked.
+    fun balance_naive(addr: address): u64 acquires Balance {
+        borrow_global<Balance>(addr).value
+    }
+
+    fun balance(addr: address): u64 acquires Balance {
+        let b = borrow_global<Balance>(addr);
+        b.value
+    }
+}
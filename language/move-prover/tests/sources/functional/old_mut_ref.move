@@ -0,0 +1,33 @@
+// no-boogie-test
+// This file contains test cases for `old(..)` of mutable reference parameters, which is
+// well-formed even though its type is the type of the parameter without the reference.
+module 0x42::TestOldMutRef {
+
+    struct R has drop { x: u64 }
+
+    fun incr(x: &mut u64) {
+        *x = *x + 1;
+        spec {
+            assert x == old(x) + 1;
+        };
+    }
+    spec incr {
+        aborts_if x + 1 > MAX_U64;
+        ensures x == old(x) + 1;
+    }
+
+    fun reset(r: &mut R) {
+        r.x = 0
+    }
+    spec reset {
+        ensures old(r).x >= r.x;
+        ensures old(r.x) >= r.x;
+    }
+
+    fun clear(v: &mut vector<u64>) {
+        *v = vector[]
+    }
+    spec clear {
+        ensures len(v) <= len(old(v));
+    }
+}
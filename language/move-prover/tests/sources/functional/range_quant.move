@@ -0,0 +1,45 @@
+// This file contains test cases for quantifiers over vector ranges, which get their triggers
+// from the vector selections in the body.
+module 0x42::RangeQuant {
+
+    use Std::Vector;
+
+    spec module {
+        fun all_below(v: vector<u64>, bound: u64): bool {
+            forall i in 0..len(v): v[i] < bound
+        }
+        fun sorted(v: vector<u64>): bool {
+            forall i in 0..len(v), j in 0..len(v) where i < j: v[i] <= v[j]
+        }
+    }
+
+    public fun push_below(v: &mut vector<u64>, x: u64) {
+        Vector::push_back(v, x);
+    }
+    spec push_below {
+        requires all_below(v, 10);
+        requires x < 10;
+        ensures all_below(v, 10);
+        ensures forall i in 0..len(old(v)): v[i] == old(v)[i];
+    }
+
+    public fun push_max(v: &mut vector<u64>, x: u64) {
+        Vector::push_back(v, x);
+    }
+    spec push_max {
+        requires sorted(v);
+        requires forall e in v: e <= x;
+        ensures sorted(v);
+    }
+
+    public fun set_first(v: &mut vector<u64>) {
+        if (Vector::length(v) > 0) {
+            *Vector::borrow_mut(v, 0) = 0;
+        }
+    }
+    spec set_first {
+        requires sorted(v);
+        ensures sorted(v);
+        ensures forall i in 1..len(v): v[i] == old(v)[i];
+    }
+}
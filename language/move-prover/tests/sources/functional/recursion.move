@@ -0,0 +1,51 @@
+module 0x42::TestRecursion {
+
+    // Mutually recursive functions with a termination measure.
+    fun is_even(n: u64): bool {
+        if (n == 0) true else is_odd(n - 1)
+    }
+    spec is_even {
+        pragma opaque;
+        pragma measure = n;
+        aborts_if false;
+        ensures result == (n % 2 == 0);
+    }
+
+    fun is_odd(n: u64): bool {
+        if (n == 0) false else is_even(n - 1)
+    }
+    spec is_odd {
+        pragma opaque;
+        pragma measure = n;
+        aborts_if false;
+        ensures result == (n % 2 == 1);
+    }
+
+    // A recursive function which modifies global memory. The memory it modifies includes the
+    // memory modified by the recursive call.
+    struct Counter has key { value: u64 }
+
+    fun drain(addr: address, n: u64) acquires Counter {
+        if (n > 0) {
+            let c = borrow_global_mut<Counter>(addr);
+            if (c.value > 0) c.value = c.value - 1;
+            drain(addr, n - 1)
+        }
+    }
+    spec drain {
+        pragma opaque;
+        pragma measure = n;
+        modifies global<Counter>(addr);
+        aborts_if n > 0 && !exists<Counter>(addr);
+        ensures global<Counter>(addr).value <= old(global<Counter>(addr).value);
+    }
+
+    fun drain_all(addr: address) acquires Counter {
+        let n = borrow_global<Counter>(addr).value;
+        drain(addr, n)
+    }
+    spec drain_all {
+        aborts_if !exists<Counter>(addr);
+        ensures global<Counter>(addr).value <= old(global<Counter>(addr).value);
+    }
+}
@@ -0,0 +1,12 @@
+// no-boogie-test
+// This file contains test cases for diagnostics of the well-formedness check of specifications.
+module 0x42::TestSpecWellFormedWarn {
+
+    fun id(x: u64): u64 {
+        x
+    }
+    spec id {
+        // The inner quantifier shadows the variable of the outer one.
+        ensures forall i: u64: exists i: u64: i == result;
+    }
+}
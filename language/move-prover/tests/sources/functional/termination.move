@@ -0,0 +1,13 @@
+module 0x42::TestTermination {
+
+    // The measure decreases at the recursive call.
+    fun count_down(n: u64): u64 {
+        if (n == 0) 0 else count_down(n - 1)
+    }
+    spec count_down {
+        pragma opaque;
+        pragma measure = n;
+        aborts_if false;
+        ensures result == 0;
+    }
+}
@@ -0,0 +1,28 @@
+// This file contains test cases for vector literals, which are compiled to the `VecPack`
+// bytecode.
+module 0x42::TestVectorLiterals {
+
+    fun empty(): vector<u64> {
+        vector[]
+    }
+    spec empty {
+        ensures result == vec();
+    }
+
+    fun pair(x: u64, y: u64): vector<u64> {
+        vector[x, y]
+    }
+    spec pair {
+        ensures len(result) == 2;
+        ensures result == concat(vec(x), vec(y));
+    }
+
+    fun nested(x: u64): vector<vector<u64>> {
+        vector[vector[x], vector[]]
+    }
+    spec nested {
+        ensures len(result) == 2;
+        ensures result[0] == vec(x);
+        ensures len(result[1]) == 0;
+    }
+}
@@ -0,0 +1,66 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for the report of the assumptions used to prove a function. Boogie is replaced by a
+//! script which reports each labelled assumption of the Boogie file as necessary.
+
+mod common;
+
+use codespan_reporting::diagnostic::Severity;
+use tempfile::TempDir;
+
+const MODULE: &str = "
+module 0x42::M {
+    fun inc(x: u64): u64 { x + 1 }
+    spec inc {
+        requires x < 100;
+        ensures result == x + 1;
+    }
+}
+";
+
+/// The script reports all assumptions with an id as necessary, if asked to.
+const REPORT_ALL: &str = "
+case \"$*\" in *-printNecessaryAssumes*)
+    echo \"Necessary assume command(s): $(grep -o 'used_assume:[0-9:]*' \"$BPL\" | paste -sd, -)\";;
+esac
+echo \"Boogie program verifier finished with 1 verified, 0 errors\"";
+
+#[test]
+fn reports_used_assumptions() {
+    let dir = TempDir::new().unwrap();
+    let env = common::build(dir.path(), MODULE);
+    let mut options = common::options(
+        dir.path(),
+        common::boogie_script(dir.path(), "boogie", REPORT_ALL),
+    );
+    options.backend.report_used_assumptions = true;
+    options.prover.report_severity = Severity::Note;
+    let (result, diags) = common::run(&env, options);
+    result.unwrap();
+
+    // The precondition is assumed with an id which encodes the function and its location.
+    let boogie = std::fs::read_to_string(common::output_path(dir.path())).unwrap();
+    assert!(boogie.contains("assume {:id \"used_assume:"));
+    // The ids reported as necessary are mapped back to the precondition of the function.
+    assert!(diags.contains("proof of `M::inc` used"), "{}", diags);
+    assert!(diags.contains("requires x < 100;"), "{}", diags);
+    assert!(diags.contains("used assumption"), "{}", diags);
+}
+
+#[test]
+fn no_report_by_default() {
+    let dir = TempDir::new().unwrap();
+    let env = common::build(dir.path(), MODULE);
+    let mut options = common::options(
+        dir.path(),
+        common::boogie_script(dir.path(), "boogie", REPORT_ALL),
+    );
+    options.prover.report_severity = Severity::Note;
+    let (result, diags) = common::run(&env, options);
+    result.unwrap();
+
+    let boogie = std::fs::read_to_string(common::output_path(dir.path())).unwrap();
+    assert!(!boogie.contains("used_assume:"));
+    assert!(!diags.contains("used assumption"), "{}", diags);
+}
@@ -0,0 +1,93 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for the Boogie code generated for vector bytecodes. Except for `VecPack`, which is used
+//! for vector literals, these bytecodes are not generated by the Move compiler, so the tested
+//! module is compiled from Move IR.
+
+use codespan_reporting::term::termcolor::Buffer;
+use move_ir_to_bytecode::{compiler::compile_module, parser::parse_module};
+use move_model::run_bytecode_model_builder;
+use move_prover::{cli::Options, run_move_prover_with_model};
+use std::fs;
+use tempfile::TempDir;
+
+const MODULE: &str = "
+module 0x42.VectorOps {
+    public pack_unpack(x: u64, y: u64): u64 * u64 {
+        let v: vector<u64>;
+        let a: u64;
+        let b: u64;
+    label b0:
+        v = vec_pack_2<u64>(move(x), move(y));
+        a, b = vec_unpack_2<u64>(move(v));
+        return move(a), move(b);
+    }
+
+    public get(v: &vector<u64>, i: u64): u64 {
+    label b0:
+        return *vec_imm_borrow<u64>(move(v), move(i));
+    }
+
+    public set(v: &mut vector<u64>, i: u64, x: u64) {
+        let r: &mut u64;
+    label b0:
+        r = vec_mut_borrow<u64>(move(v), move(i));
+        *move(r) = move(x);
+        return;
+    }
+
+    public push_pop(v: &mut vector<u64>, x: u64): u64 {
+        let n: u64;
+    label b0:
+        vec_push_back<u64>(copy(v), move(x));
+        vec_swap<u64>(copy(v), 0, 0);
+        n = vec_len<u64>(freeze(copy(v)));
+        return vec_pop_back<u64>(move(v)) + move(n);
+    }
+}
+";
+
+#[test]
+fn vector_ops_in_boogie() {
+    let (module, _) = compile_module(parse_module(MODULE).unwrap(), &[]).unwrap();
+    let env = run_bytecode_model_builder(vec![&module]).unwrap();
+    let dir = TempDir::new().unwrap();
+    let output_path = dir.path().join("vector_ops.bpl");
+    let mut options = Options {
+        output_path: output_path.to_string_lossy().to_string(),
+        ..Default::default()
+    };
+    options.prover.generate_only = true;
+    let mut error_writer = Buffer::no_color();
+    run_move_prover_with_model(&env, &mut error_writer, options, None).unwrap();
+    let boogie = fs::read_to_string(&output_path).unwrap();
+
+    // Each operation is translated, and the prelude procedures it calls are instantiated for
+    // the element type.
+    for expected in [
+        "$t5 := MakeVec2($t0, $t1);",
+        "if (LenVec($t5) != 2) {",
+        "$t6 := ReadVec($t5, 0);",
+        "$t7 := ReadVec($t5, 1);",
+        "call $t2 := $1_Vector_borrow'u64'($t0, $t1);",
+        "call $t4,$t0 := $1_Vector_borrow_mut'u64'($t0, $t1);",
+        "call $t0 := $1_Vector_push_back'u64'($t0, $t1);",
+        "call $t0 := $1_Vector_swap'u64'($t0, $t3, $t4);",
+        "call $t7 := $1_Vector_length'u64'($t6);",
+        "call $t8,$t0 := $1_Vector_pop_back'u64'($t0);",
+    ] {
+        assert!(boogie.contains(expected), "missing `{}`", expected);
+    }
+    for op in [
+        "borrow",
+        "borrow_mut",
+        "push_back",
+        "swap",
+        "length",
+        "pop_back",
+    ] {
+        let decl = format!("procedure {{:inline 1}} $1_Vector_{}'u64'", op);
+        assert!(boogie.contains(&decl), "missing `{}`", decl);
+    }
+}
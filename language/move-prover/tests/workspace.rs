@@ -0,0 +1,96 @@
+// Copyright (c) The Diem Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for verifying workspaces of packages with their own named address bindings.
+
+use codespan_reporting::term::termcolor::Buffer;
+use move_compiler::shared::PackagePaths;
+use move_model::{parse_addresses_from_options, run_model_builder_with_options};
+use move_prover::{
+    cli::{Options, WorkspacePackage},
+    run_move_prover,
+};
+use std::{fs, path::Path};
+use tempfile::TempDir;
+
+/// Both packages name their address `me`, bound to different addresses.
+const PACKAGE_A: &str = "
+module me::M {
+    public fun f(): u64 { 1 }
+    spec f { ensures result == undefined_a; }
+}
+";
+
+const PACKAGE_B: &str = "
+module me::M {
+    public fun f(): u64 { 2 }
+    spec f { ensures result == undefined_b; }
+}
+";
+
+fn package(dir: &Path, name: &str, source: &str, address: &str) -> WorkspacePackage {
+    let path = dir.join(format!("{}.move", name));
+    fs::write(&path, source).unwrap();
+    WorkspacePackage {
+        name: name.to_string(),
+        sources: vec![path.to_string_lossy().to_string()],
+        named_address_values: vec![format!("me={}", address)],
+    }
+}
+
+#[test]
+fn diagnostics_by_package() {
+    let dir = TempDir::new().unwrap();
+    let options = Options {
+        output_path: dir.path().join("output.bpl").to_string_lossy().to_string(),
+        workspace_packages: vec![
+            package(dir.path(), "A", PACKAGE_A, "0x1"),
+            package(dir.path(), "B", PACKAGE_B, "0x2"),
+        ],
+        ..Default::default()
+    };
+    let mut error_writer = Buffer::no_color();
+    let result = run_move_prover(&mut error_writer, options);
+    assert!(result.is_err());
+
+    // The errors are grouped by the package they occur in.
+    let output = String::from_utf8_lossy(&error_writer.into_inner()).to_string();
+    let a = output.find("package `A`:").expect(&output);
+    let b = output.find("package `B`:").expect(&output);
+    assert!(a < b);
+    let undefined_a = output.find("undefined_a").expect(&output);
+    let undefined_b = output.find("undefined_b").expect(&output);
+    assert!(a < undefined_a && undefined_a < b && b < undefined_b);
+    assert!(!output.contains("outside of packages:"));
+}
+
+#[test]
+fn modules_by_package() {
+    let dir = TempDir::new().unwrap();
+    let packages = vec![
+        package(dir.path(), "A", PACKAGE_A, "0x1"),
+        package(dir.path(), "B", PACKAGE_B, "0x2"),
+    ]
+    .into_iter()
+    .map(|package| PackagePaths {
+        name: Some(package.name.as_str().into()),
+        paths: package.sources,
+        named_address_map: parse_addresses_from_options(package.named_address_values).unwrap(),
+    })
+    .collect();
+    let env = run_model_builder_with_options(packages, vec![], Default::default()).unwrap();
+
+    // The modules of both packages are built, each with its own address.
+    assert_eq!(
+        env.get_packages().into_iter().collect::<Vec<_>>(),
+        vec!["A", "B"]
+    );
+    let package_of = |name: &str| {
+        env.find_module_by_name_str(name)
+            .unwrap()
+            .get_package_name()
+            .map(|s| s.to_string())
+    };
+    assert_eq!(package_of("0x1::M"), Some("A".to_string()));
+    assert_eq!(package_of("0x2::M"), Some("B".to_string()));
+}
@@ -7,7 +7,7 @@ use std::rc::Rc;
 
 use move_model::{
     ast::{Condition, ConditionKind, Exp, ExpData, Operation, QuantKind, SpecBlockTarget, Value},
-    model::{AbilityConstraint, AbilitySet, GlobalEnv, TypeParameter},
+    model::{AbilitySet, GlobalEnv, TypeParameter, TypeParameterKind},
     symbol::Symbol,
     ty::{PrimitiveType, Type},
 };
@@ -386,7 +386,7 @@ impl SpecPrinter<'_> {
             | ConditionKind::Axiom(syms) => {
                 assert!(matches!(self.ctxt, SpecBlockTarget::Module));
                 syms.iter()
-                    .map(|s| TypeParameter(*s, AbilityConstraint(AbilitySet::EMPTY)))
+                    .map(|s| TypeParameter(*s, TypeParameterKind::new(AbilitySet::EMPTY)))
                     .collect()
             }
             // not expected
@@ -346,6 +346,8 @@ pub fn run_generation(args: Args) {
     let (sender, receiver) = bounded(num_threads);
     let (stats_sender, stats_reciever) = unbounded();
     let seed = seed(args.seed);
+    // Report the seed so a run can be reproduced with `--seed`.
+    info!("using seed {}", hex::encode(seed));
 
     let mut threads = Vec::new();
     for tid in 0..num_threads {